@@ -1,3 +1,6 @@
+use std::path::Path;
+use std::process::Command;
+
 fn main() {
     tauri_build::build();
 
@@ -5,4 +8,30 @@ fn main() {
         println!("cargo:rustc-link-lib=z");
         println!("cargo:rustc-link-lib=iconv");
     }
+
+    if let Ok(target) = std::env::var("TARGET") {
+        println!("cargo:rustc-env=CODEX_MONITOR_TARGET={target}");
+    }
+    embed_git_hash();
+}
+
+/// Exposes the current commit as `CODEX_MONITOR_GIT_HASH` for the `version`
+/// RPC. Source tarballs without git simply leave it unset.
+fn embed_git_hash() {
+    let hash = Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|hash| !hash.is_empty());
+    if let Some(hash) = hash {
+        println!("cargo:rustc-env=CODEX_MONITOR_GIT_HASH={hash}");
+    }
+    // Only watch files that exist; a missing path makes cargo rerun every build.
+    for path in ["../.git/HEAD", "../.git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={path}");
+        }
+    }
 }
@@ -1,9 +1,9 @@
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::env;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -11,11 +11,15 @@ use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio::time::timeout;
+use uuid::Uuid;
 
-use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::events::{AppServerEvent, EventSink, SessionExited};
 use crate::codex::args::parse_codex_args;
+use crate::shared::codex_core;
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
-use crate::types::WorkspaceEntry;
+use crate::shared::session_error_core::SessionErrors;
+use crate::shared::timeline_core::{self, SessionEvents};
+use crate::types::{PendingApproval, SessionCapabilities, WorkspaceActivity, WorkspaceEntry};
 
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
@@ -39,6 +43,245 @@ fn extract_thread_id(value: &Value) -> Option<String> {
         })
 }
 
+/// Running turns and unanswered approval requests, derived from the same
+/// app-server traffic the per-thread events are built from.
+#[derive(Default)]
+pub(crate) struct ThreadActivity {
+    running_threads: BTreeSet<String>,
+    /// Approval requests keyed by their JSON-encoded id, kept whole so they
+    /// can be listed and re-sent to clients that missed them.
+    pending_approvals: HashMap<String, ApprovalRequest>,
+    next_approval_seq: u64,
+}
+
+struct ApprovalRequest {
+    seq: u64,
+    thread_id: Option<String>,
+    message: Value,
+}
+
+impl ThreadActivity {
+    /// Returns the thread whose turn the message ended, if any.
+    fn observe(&mut self, value: &Value) -> Option<String> {
+        let method = value.get("method").and_then(|method| method.as_str())?;
+        let thread_id = extract_thread_id(value);
+        match method {
+            "turn/started" => {
+                if let Some(thread_id) = thread_id {
+                    self.running_threads.insert(thread_id);
+                }
+            }
+            "turn/completed" => return self.finish_turn(thread_id),
+            "error" => {
+                let params = value.get("params");
+                let will_retry = params
+                    .and_then(|params| params.get("willRetry").or_else(|| params.get("will_retry")))
+                    .and_then(|value| value.as_bool())
+                    .unwrap_or(false);
+                if !will_retry {
+                    return self.finish_turn(thread_id);
+                }
+            }
+            _ if method.ends_with("requestApproval") => {
+                if let Some(id) = value.get("id") {
+                    self.next_approval_seq += 1;
+                    let request = ApprovalRequest {
+                        seq: self.next_approval_seq,
+                        thread_id,
+                        message: value.clone(),
+                    };
+                    self.pending_approvals.insert(id.to_string(), request);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn finish_turn(&mut self, thread_id: Option<String>) -> Option<String> {
+        let thread_id = thread_id?;
+        self.running_threads.remove(&thread_id);
+        self.pending_approvals
+            .retain(|_, request| request.thread_id.as_deref() != Some(thread_id.as_str()));
+        Some(thread_id)
+    }
+
+    pub(crate) fn is_running(&self, thread_id: &str) -> bool {
+        self.running_threads.contains(thread_id)
+    }
+
+    /// Marks a turn as running before `turn/started` arrives, so a message
+    /// queued in between waits for it instead of starting a second turn.
+    pub(crate) fn mark_running(&mut self, thread_id: &str) {
+        self.running_threads.insert(thread_id.to_string());
+    }
+
+    /// Undoes `mark_running` when the turn failed to start.
+    pub(crate) fn clear_running(&mut self, thread_id: &str) {
+        self.running_threads.remove(thread_id);
+    }
+
+    fn resolve_request(&mut self, request_id: &Value) {
+        self.pending_approvals.remove(&request_id.to_string());
+    }
+
+    pub(crate) fn snapshot(&self, workspace_id: &str) -> WorkspaceActivity {
+        let state = if !self.pending_approvals.is_empty() {
+            "awaiting-approval"
+        } else if !self.running_threads.is_empty() {
+            "running"
+        } else {
+            "idle"
+        };
+        WorkspaceActivity {
+            workspace_id: workspace_id.to_string(),
+            state: state.to_string(),
+            running_threads: self.running_threads.iter().cloned().collect(),
+            pending_approvals: self.pending_approvals.len(),
+        }
+    }
+
+    /// Unanswered approval requests in the order they arrived.
+    pub(crate) fn pending_approvals(&self, workspace_id: &str) -> Vec<PendingApproval> {
+        let mut requests: Vec<&ApprovalRequest> = self.pending_approvals.values().collect();
+        requests.sort_by_key(|request| request.seq);
+        requests
+            .into_iter()
+            .map(|request| PendingApproval {
+                workspace_id: workspace_id.to_string(),
+                request_id: request.message.get("id").cloned().unwrap_or(Value::Null),
+                method: request
+                    .message
+                    .get("method")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                thread_id: request.thread_id.clone(),
+                params: request
+                    .message
+                    .get("params")
+                    .cloned()
+                    .unwrap_or(Value::Null),
+            })
+            .collect()
+    }
+}
+
+/// Turns counted per thread against `maxTurnsPerThread`. A turn is counted
+/// when it is reserved, before `turn/start` goes out, so the limit check and
+/// the increment are one step and concurrent sends can't both slip under it.
+#[derive(Default)]
+pub(crate) struct ThreadTurnCounts {
+    by_thread: HashMap<String, u32>,
+}
+
+impl ThreadTurnCounts {
+    /// Counts one more turn for the thread unless that would go past `limit`.
+    pub(crate) fn reserve(&mut self, thread_id: &str, limit: Option<u32>) -> Result<(), String> {
+        let count = self.by_thread.entry(thread_id.to_string()).or_insert(0);
+        if let Some(limit) = limit.filter(|limit| *limit > 0) {
+            if *count >= limit {
+                return Err(format!(
+                    "thread turn limit reached ({count}/{limit}); reset the thread's turn count to continue"
+                ));
+            }
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Gives back a reserved turn that never started.
+    pub(crate) fn release(&mut self, thread_id: &str) {
+        if let Some(count) = self.by_thread.get_mut(thread_id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// Clears the thread's count and returns what it was.
+    pub(crate) fn reset(&mut self, thread_id: &str) -> u32 {
+        self.by_thread.remove(thread_id).unwrap_or(0)
+    }
+}
+
+/// Messages waiting per thread for the running turn to finish.
+pub(crate) const MAX_QUEUED_MESSAGES_PER_THREAD: usize = 5;
+
+/// A prepared `turn/start` waiting for its thread to go idle.
+pub(crate) struct QueuedTurn {
+    pub(crate) message_id: String,
+    pub(crate) params: Value,
+}
+
+/// Messages queued with `queue_user_message`, in send order per thread.
+#[derive(Default)]
+pub(crate) struct QueuedTurns {
+    by_thread: HashMap<String, VecDeque<QueuedTurn>>,
+}
+
+impl QueuedTurns {
+    pub(crate) fn len(&self, thread_id: &str) -> usize {
+        self.by_thread.get(thread_id).map_or(0, VecDeque::len)
+    }
+
+    /// Queues a turn and returns its message id and 1-based queue position.
+    pub(crate) fn push(
+        &mut self,
+        thread_id: &str,
+        params: Value,
+    ) -> Result<(String, usize), String> {
+        let queue = self.by_thread.entry(thread_id.to_string()).or_default();
+        if queue.len() >= MAX_QUEUED_MESSAGES_PER_THREAD {
+            return Err(format!(
+                "too many queued messages for this thread (max {MAX_QUEUED_MESSAGES_PER_THREAD})"
+            ));
+        }
+        let message_id = Uuid::new_v4().to_string();
+        queue.push_back(QueuedTurn {
+            message_id: message_id.clone(),
+            params,
+        });
+        Ok((message_id, queue.len()))
+    }
+
+    fn pop(&mut self, thread_id: &str) -> Option<QueuedTurn> {
+        let queue = self.by_thread.get_mut(thread_id)?;
+        let turn = queue.pop_front();
+        if queue.is_empty() {
+            self.by_thread.remove(thread_id);
+        }
+        turn
+    }
+
+    /// Drops a queued message and returns its thread; `None` once it has
+    /// been sent.
+    pub(crate) fn cancel(&mut self, message_id: &str) -> Option<String> {
+        for (thread_id, queue) in self.by_thread.iter_mut() {
+            if let Some(index) = queue.iter().position(|turn| turn.message_id == message_id) {
+                queue.remove(index);
+                let thread_id = thread_id.clone();
+                self.by_thread.retain(|_, queue| !queue.is_empty());
+                return Some(thread_id);
+            }
+        }
+        None
+    }
+}
+
+const EXIT_STATUS_POLL_ATTEMPTS: u32 = 50;
+const EXIT_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn session_exited_event(
+    workspace_id: String,
+    status: Option<std::process::ExitStatus>,
+    shutdown_requested: bool,
+) -> SessionExited {
+    SessionExited {
+        workspace_id,
+        code: status.and_then(|status| status.code()),
+        clean: shutdown_requested || status.is_some_and(|status| status.success()),
+    }
+}
+
 fn build_initialize_params(client_version: &str) -> Value {
     json!({
         "clientInfo": {
@@ -60,9 +303,55 @@ pub(crate) struct WorkspaceSession {
     pub(crate) next_id: AtomicU64,
     /// Callbacks for background threads - events for these threadIds are sent through the channel
     pub(crate) background_thread_callbacks: Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    /// Sticky collaboration mode per thread, applied when a turn omits one.
+    pub(crate) thread_collaboration_modes: Mutex<HashMap<String, Value>>,
+    /// Turns reserved per thread, checked against `maxTurnsPerThread`.
+    pub(crate) thread_turn_counts: Mutex<ThreadTurnCounts>,
+    /// Set by `terminate` so the exit watcher reports the exit as clean.
+    pub(crate) shutdown_requested: AtomicBool,
+    /// Turn and approval state across the session's threads.
+    pub(crate) activity: Mutex<ThreadActivity>,
+    /// `codex --version` of the binary this session was spawned from.
+    pub(crate) codex_version: Option<String>,
+    /// Probed on first use, so a reconnect (which spawns a new session)
+    /// probes again.
+    pub(crate) capabilities: Mutex<Option<SessionCapabilities>>,
+    /// First successful `model/list` response, reused to check default
+    /// efforts without a round trip per turn.
+    pub(crate) model_list: Mutex<Option<Value>>,
+    /// First successful `collaborationMode/list` response, cached the same
+    /// way for default modes.
+    pub(crate) collaboration_mode_list: Mutex<Option<Value>>,
+    /// Messages to send as each thread's running turn finishes.
+    pub(crate) queued_turns: Mutex<QueuedTurns>,
 }
 
 impl WorkspaceSession {
+    /// Kills the app-server process tree as an intentional shutdown.
+    pub(crate) async fn terminate(&self) {
+        self.shutdown_requested.store(true, Ordering::SeqCst);
+        self.kill().await;
+    }
+
+    /// Kills the process tree without marking the exit as requested, so a
+    /// server that failed to start is still reported as an unclean exit.
+    async fn kill(&self) {
+        let mut child = self.child.lock().await;
+        kill_child_process_tree(&mut child).await;
+    }
+
+    /// Polls briefly for the exit status once stdout has closed; the child
+    /// lock is only held per poll so `terminate` is never blocked.
+    async fn wait_for_exit_status(&self) -> Option<std::process::ExitStatus> {
+        for _ in 0..EXIT_STATUS_POLL_ATTEMPTS {
+            if let Ok(Some(status)) = self.child.lock().await.try_wait() {
+                return Some(status);
+            }
+            tokio::time::sleep(EXIT_STATUS_POLL_INTERVAL).await;
+        }
+        None
+    }
+
     async fn write_message(&self, value: Value) -> Result<(), String> {
         let mut stdin = self.stdin.lock().await;
         let mut line = serde_json::to_string(&value).map_err(|e| e.to_string())?;
@@ -73,15 +362,59 @@ impl WorkspaceSession {
             .map_err(|e| e.to_string())
     }
 
-    pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
+    /// Registers a pending reply and writes the request, dropping the entry
+    /// again if the write fails.
+    async fn begin_request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<(u64, oneshot::Receiver<Value>), String> {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
         self.pending.lock().await.insert(id, tx);
-        self.write_message(json!({ "id": id, "method": method, "params": params }))
-            .await?;
+        if let Err(err) = self
+            .write_message(json!({ "id": id, "method": method, "params": params }))
+            .await
+        {
+            self.pending.lock().await.remove(&id);
+            return Err(err);
+        }
+        Ok((id, rx))
+    }
+
+    pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let (_, rx) = self.begin_request(method, params).await?;
         rx.await.map_err(|_| "request canceled".to_string())
     }
 
+    /// `send_request` that gives up after `limit`, removing the pending entry
+    /// so a reply that never comes doesn't leak it.
+    pub(crate) async fn send_request_with_timeout(
+        &self,
+        method: &str,
+        params: Value,
+        limit: Duration,
+    ) -> Result<Value, String> {
+        let (id, rx) = self.begin_request(method, params).await?;
+        match timeout(limit, rx).await {
+            Ok(response) => response.map_err(|_| "request canceled".to_string()),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                Err(format!("{method} timed out after {}s", limit.as_secs()))
+            }
+        }
+    }
+
+    /// Sends `turn/start` for a turn reserved in `thread_turn_counts`, giving
+    /// the reservation back if the turn doesn't start.
+    pub(crate) async fn start_turn(&self, thread_id: &str, params: Value) -> Result<Value, String> {
+        let response = self.send_request("turn/start", params).await;
+        if !matches!(&response, Ok(response) if response.get("error").is_none()) {
+            self.thread_turn_counts.lock().await.release(thread_id);
+        }
+        response
+    }
+
     pub(crate) async fn send_notification(
         &self,
         method: &str,
@@ -96,9 +429,50 @@ impl WorkspaceSession {
     }
 
     pub(crate) async fn send_response(&self, id: Value, result: Value) -> Result<(), String> {
-        self.write_message(json!({ "id": id, "result": result }))
-            .await
+        self.write_message(json!({ "id": &id, "result": result }))
+            .await?;
+        self.activity.lock().await.resolve_request(&id);
+        Ok(())
+    }
+}
+
+/// Starts the next message queued for a thread whose turn just finished,
+/// skipping past ones that fail to start. Runs on its own task since the
+/// `turn/start` response comes back through the reader that calls this.
+async fn dispatch_queued_turns<E: EventSink>(
+    session: &Arc<WorkspaceSession>,
+    event_sink: &E,
+    thread_id: String,
+) {
+    if session.queued_turns.lock().await.len(&thread_id) == 0 {
+        return;
     }
+    session.activity.lock().await.mark_running(&thread_id);
+    let session = Arc::clone(session);
+    let event_sink = event_sink.clone();
+    tokio::spawn(async move {
+        loop {
+            let Some(turn) = session.queued_turns.lock().await.pop(&thread_id) else {
+                session.activity.lock().await.clear_running(&thread_id);
+                return;
+            };
+            let error = match session.start_turn(&thread_id, turn.params).await {
+                Ok(response) => codex_core::app_server_result(&response, "turn/start failed").err(),
+                Err(err) => Some(err),
+            };
+            let mut params = json!({ "threadId": thread_id, "messageId": turn.message_id });
+            if let Some(error) = &error {
+                params["error"] = json!(error);
+            }
+            event_sink.emit_app_server_event(AppServerEvent {
+                workspace_id: session.entry.id.clone(),
+                message: json!({ "method": "codex/queuedMessageSent", "params": params }),
+            });
+            if error.is_none() {
+                return;
+            }
+        }
+    });
 }
 
 pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
@@ -287,6 +661,16 @@ pub(crate) async fn check_codex_installation(
     })
 }
 
+/// Where a session records what outlives it: the workspace's timeline events
+/// and its last error, both owned by the app or daemon state.
+#[derive(Clone, Default)]
+pub(crate) struct SessionRecords {
+    pub(crate) events: SessionEvents,
+    pub(crate) errors: SessionErrors,
+}
+
+/// Spawns and initializes a session, recording a failure as the workspace's
+/// last session error and clearing any earlier one on success.
 pub(crate) async fn spawn_workspace_session<E: EventSink>(
     entry: WorkspaceEntry,
     default_codex_bin: Option<String>,
@@ -294,13 +678,41 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     codex_home: Option<PathBuf>,
     client_version: String,
     event_sink: E,
+    records: SessionRecords,
+) -> Result<Arc<WorkspaceSession>, String> {
+    let workspace_id = entry.id.clone();
+    let result = start_workspace_session(
+        entry,
+        default_codex_bin,
+        codex_args,
+        codex_home,
+        client_version,
+        event_sink,
+        records.clone(),
+    )
+    .await;
+    match &result {
+        Ok(_) => records.errors.clear(&workspace_id),
+        Err(err) => records.errors.record(&workspace_id, "spawn", err),
+    }
+    result
+}
+
+async fn start_workspace_session<E: EventSink>(
+    entry: WorkspaceEntry,
+    default_codex_bin: Option<String>,
+    codex_args: Option<String>,
+    codex_home: Option<PathBuf>,
+    client_version: String,
+    event_sink: E,
+    records: SessionRecords,
 ) -> Result<Arc<WorkspaceSession>, String> {
     let codex_bin = entry
         .codex_bin
         .clone()
         .filter(|value| !value.trim().is_empty())
         .or(default_codex_bin);
-    let _ = check_codex_installation(codex_bin.clone()).await?;
+    let codex_version = check_codex_installation(codex_bin.clone()).await?;
 
     let mut command = build_codex_command_with_bin(
         codex_bin,
@@ -327,11 +739,21 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         pending: Mutex::new(HashMap::new()),
         next_id: AtomicU64::new(1),
         background_thread_callbacks: Mutex::new(HashMap::new()),
+        thread_collaboration_modes: Mutex::new(entry.settings.thread_collaboration_modes.clone()),
+        thread_turn_counts: Mutex::new(ThreadTurnCounts::default()),
+        shutdown_requested: AtomicBool::new(false),
+        activity: Mutex::new(ThreadActivity::default()),
+        codex_version,
+        capabilities: Mutex::new(None),
+        model_list: Mutex::new(None),
+        collaboration_mode_list: Mutex::new(None),
+        queued_turns: Mutex::new(QueuedTurns::default()),
     });
 
     let session_clone = Arc::clone(&session);
     let workspace_id = entry.id.clone();
     let event_sink_clone = event_sink.clone();
+    let records_clone = records.clone();
     tokio::spawn(async move {
         let mut lines = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -341,6 +763,11 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
             let value: Value = match serde_json::from_str(&line) {
                 Ok(value) => value,
                 Err(err) => {
+                    records_clone.errors.record(
+                        &workspace_id,
+                        "protocol",
+                        &format!("Unparseable app-server output: {err}"),
+                    );
                     let payload = AppServerEvent {
                         workspace_id: workspace_id.clone(),
                         message: json!({
@@ -362,6 +789,9 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
 
             if let Some(id) = maybe_id {
                 if has_result_or_error {
+                    if value.get("error").is_none() {
+                        records_clone.errors.clear(&workspace_id);
+                    }
                     if let Some(tx) = session_clone.pending.lock().await.remove(&id) {
                         let _ = tx.send(value);
                     }
@@ -377,6 +807,7 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                     }
                     // Don't emit to frontend if this is a background thread event
                     if !sent_to_background {
+                        session_clone.activity.lock().await.observe(&value);
                         let payload = AppServerEvent {
                             workspace_id: workspace_id.clone(),
                             message: value,
@@ -398,6 +829,13 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 }
                 // Don't emit to frontend if this is a background thread event
                 if !sent_to_background {
+                    let finished = session_clone.activity.lock().await.observe(&value);
+                    if let Some(thread_id) = finished {
+                        dispatch_queued_turns(&session_clone, &event_sink_clone, thread_id).await;
+                    }
+                    records_clone
+                        .events
+                        .record_app_server_message(&workspace_id, &value);
                     let payload = AppServerEvent {
                         workspace_id: workspace_id.clone(),
                         message: value,
@@ -406,6 +844,24 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 }
             }
         }
+
+        // stdout only closes when the app-server goes away; fail whatever is
+        // still waiting on a reply instead of letting it time out.
+        session_clone.pending.lock().await.clear();
+        let status = session_clone.wait_for_exit_status().await;
+        let shutdown_requested = session_clone.shutdown_requested.load(Ordering::SeqCst);
+        let exited = session_exited_event(workspace_id, status, shutdown_requested);
+        records_clone
+            .events
+            .record_session_exited(&exited.workspace_id, exited.code, exited.clean);
+        if !exited.clean {
+            records_clone.errors.record(
+                &exited.workspace_id,
+                "exit",
+                &timeline_core::session_exit_summary(exited.code, exited.clean),
+            );
+        }
+        event_sink_clone.emit_session_exited(exited);
     });
 
     let workspace_id = entry.id.clone();
@@ -428,24 +884,21 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     });
 
     let init_params = build_initialize_params(&client_version);
-    let init_result = timeout(
-        Duration::from_secs(15),
-        session.send_request("initialize", init_params),
-    )
-    .await;
-    let init_response = match init_result {
-        Ok(response) => response,
-        Err(_) => {
-            let mut child = session.child.lock().await;
-            kill_child_process_tree(&mut child).await;
-            return Err(
-                "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal."
-                    .to_string(),
-            );
-        }
-    };
-    init_response?;
-    session.send_notification("initialized", None).await?;
+    if session
+        .send_request_with_timeout("initialize", init_params, Duration::from_secs(15))
+        .await
+        .is_err()
+    {
+        session.kill().await;
+        return Err(
+            "Codex app-server did not respond to initialize. Check that `codex app-server` works in Terminal."
+                .to_string(),
+        );
+    }
+    if let Err(err) = session.send_notification("initialized", None).await {
+        session.kill().await;
+        return Err(err);
+    }
 
     let payload = AppServerEvent {
         workspace_id: entry.id.clone(),
@@ -455,14 +908,20 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         }),
     };
     event_sink.emit_app_server_event(payload);
+    records.events.record_session_started(&entry.id);
 
     Ok(session)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{build_initialize_params, extract_thread_id};
+    use super::{
+        build_initialize_params, extract_thread_id, session_exited_event, QueuedTurns,
+        ThreadActivity, ThreadTurnCounts, MAX_QUEUED_MESSAGES_PER_THREAD,
+    };
     use serde_json::json;
+    use std::sync::Arc;
+    use tokio::sync::Mutex;
 
     #[test]
     fn extract_thread_id_reads_camel_case() {
@@ -482,6 +941,48 @@ mod tests {
         assert_eq!(extract_thread_id(&value), None);
     }
 
+    #[test]
+    fn thread_activity_tracks_turns_and_approvals() {
+        let mut activity = ThreadActivity::default();
+        assert_eq!(activity.snapshot("ws-1").state, "idle");
+
+        activity.observe(&json!({ "method": "turn/started", "params": { "threadId": "t1" } }));
+        activity.observe(&json!({ "method": "turn/started", "params": { "threadId": "t2" } }));
+        let running = activity.snapshot("ws-1");
+        assert_eq!(running.state, "running");
+        assert_eq!(running.running_threads, vec!["t1", "t2"]);
+
+        activity.observe(&json!({
+            "id": 7,
+            "method": "item/commandExecution/requestApproval",
+            "params": { "threadId": "t1" }
+        }));
+        assert_eq!(activity.snapshot("ws-1").state, "awaiting-approval");
+        activity.observe(&json!({
+            "id": 8,
+            "method": "item/fileChange/requestApproval",
+            "params": { "threadId": "t2" }
+        }));
+        let pending = activity.pending_approvals("ws-1");
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].request_id, json!(7));
+        assert_eq!(pending[0].method, "item/commandExecution/requestApproval");
+        assert_eq!(pending[1].thread_id.as_deref(), Some("t2"));
+        activity.resolve_request(&json!(7));
+        activity.resolve_request(&json!(8));
+        assert_eq!(activity.snapshot("ws-1").state, "running");
+        assert!(activity.pending_approvals("ws-1").is_empty());
+
+        activity.observe(&json!({
+            "method": "error",
+            "params": { "threadId": "t2", "willRetry": true }
+        }));
+        activity.observe(&json!({ "method": "turn/completed", "params": { "threadId": "t1" } }));
+        assert_eq!(activity.snapshot("ws-1").running_threads, vec!["t2"]);
+        activity.observe(&json!({ "method": "error", "params": { "threadId": "t2" } }));
+        assert_eq!(activity.snapshot("ws-1").state, "idle");
+    }
+
     #[test]
     fn build_initialize_params_enables_experimental_api() {
         let params = build_initialize_params("1.2.3");
@@ -493,4 +994,106 @@ mod tests {
             Some(true)
         );
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_exited_event_classifies_exit_status() {
+        let exit_with = |code: i32| {
+            std::process::Command::new("sh")
+                .args(["-c", &format!("exit {code}")])
+                .status()
+                .expect("run sh")
+        };
+
+        let crashed = session_exited_event("ws-1".to_string(), Some(exit_with(3)), false);
+        assert_eq!(crashed.code, Some(3));
+        assert!(!crashed.clean);
+
+        let finished = session_exited_event("ws-1".to_string(), Some(exit_with(0)), false);
+        assert!(finished.clean);
+
+        let stopped = session_exited_event("ws-1".to_string(), None, true);
+        assert_eq!(stopped.code, None);
+        assert!(stopped.clean);
+    }
+
+    #[test]
+    fn turn_counts_stop_at_the_limit_and_take_back_unstarted_turns() {
+        let mut counts = ThreadTurnCounts::default();
+        counts.reserve("t1", Some(2)).expect("first turn");
+        counts.reserve("t1", Some(2)).expect("second turn");
+        assert_eq!(
+            counts.reserve("t1", Some(2)),
+            Err(
+                "thread turn limit reached (2/2); reset the thread's turn count to continue"
+                    .to_string()
+            )
+        );
+        counts.reserve("t2", Some(2)).expect("other thread");
+
+        counts.release("t1");
+        counts.reserve("t1", Some(2)).expect("released turn");
+        counts.reserve("t1", None).expect("no limit");
+        counts.reserve("t1", Some(0)).expect("zero is no limit");
+        assert_eq!(counts.reset("t1"), 4);
+        assert_eq!(counts.reset("t1"), 0);
+        counts.release("t1");
+        counts.reserve("t1", Some(1)).expect("after reset");
+    }
+
+    #[test]
+    fn concurrent_turn_reservations_never_pass_the_limit() {
+        let counts = Arc::new(Mutex::new(ThreadTurnCounts::default()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime");
+        let reserved = runtime.block_on(async {
+            let attempts = (0..32).map(|_| {
+                let counts = Arc::clone(&counts);
+                tokio::spawn(async move { counts.lock().await.reserve("t1", Some(5)).is_ok() })
+            });
+            let mut reserved = 0;
+            for attempt in attempts.collect::<Vec<_>>() {
+                reserved += usize::from(attempt.await.expect("task"));
+            }
+            reserved
+        });
+        assert_eq!(reserved, 5);
+    }
+
+    #[test]
+    fn queued_turns_keep_order_per_thread_and_can_be_cancelled() {
+        let mut queued = QueuedTurns::default();
+        let (first, position) = queued.push("t1", json!({ "n": 1 })).expect("queue first");
+        assert_eq!(position, 1);
+        let (second, _) = queued.push("t1", json!({ "n": 2 })).expect("queue second");
+        let (third, position) = queued.push("t1", json!({ "n": 3 })).expect("queue third");
+        assert_eq!(position, 3);
+        assert_eq!(queued.len("t2"), 0);
+
+        assert_eq!(queued.cancel(&second).as_deref(), Some("t1"));
+        assert_eq!(queued.cancel(&second), None);
+        assert_eq!(queued.pop("t1").map(|turn| turn.message_id), Some(first));
+        assert_eq!(queued.pop("t1").map(|turn| turn.message_id), Some(third));
+        assert!(queued.pop("t1").is_none());
+
+        for _ in 0..MAX_QUEUED_MESSAGES_PER_THREAD {
+            queued.push("t1", json!({})).expect("queue below the cap");
+        }
+        assert!(queued.push("t1", json!({})).is_err());
+        assert!(queued.push("t2", json!({})).is_ok());
+    }
+
+    #[test]
+    fn thread_activity_reports_the_thread_whose_turn_finished() {
+        let mut activity = ThreadActivity::default();
+        activity.observe(&json!({ "method": "turn/started", "params": { "threadId": "t1" } }));
+        assert!(activity.is_running("t1"));
+        let retrying =
+            json!({ "method": "error", "params": { "threadId": "t1", "willRetry": true } });
+        assert_eq!(activity.observe(&retrying), None);
+        let completed = json!({ "method": "turn/completed", "params": { "threadId": "t1" } });
+        assert_eq!(activity.observe(&completed).as_deref(), Some("t1"));
+        assert!(!activity.is_running("t1"));
+    }
 }
@@ -24,8 +24,50 @@ pub(crate) struct TerminalExit {
     pub(crate) terminal_id: String,
 }
 
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct SessionExited {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    /// `None` when the process was killed by a signal or its status is unknown.
+    pub(crate) code: Option<i32>,
+    /// True for exits we asked for (disconnect, restart) or a zero status.
+    pub(crate) clean: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct GitStatusChanged {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct ThreadRenamed {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    #[serde(rename = "threadId")]
+    pub(crate) thread_id: String,
+    pub(crate) name: String,
+}
+
+/// Lines appended to a file followed with `tail_workspace_file`. `reset` is
+/// set when the file was truncated or rotated and reading started over.
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct WorkspaceFileTail {
+    #[serde(rename = "workspaceId")]
+    pub(crate) workspace_id: String,
+    #[serde(rename = "tailId")]
+    pub(crate) tail_id: String,
+    pub(crate) path: String,
+    pub(crate) lines: Vec<String>,
+    pub(crate) reset: bool,
+}
+
 pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_app_server_event(&self, event: AppServerEvent);
     fn emit_terminal_output(&self, event: TerminalOutput);
     fn emit_terminal_exit(&self, event: TerminalExit);
+    fn emit_session_exited(&self, event: SessionExited);
+    fn emit_git_status_changed(&self, event: GitStatusChanged);
+    fn emit_thread_renamed(&self, event: ThreadRenamed);
+    fn emit_workspace_file_tail(&self, event: WorkspaceFileTail);
 }
@@ -1,3 +1,5 @@
+#[path = "codex_monitor_daemon/auth_lockout.rs"]
+mod auth_lockout;
 #[allow(dead_code)]
 #[path = "../backend/mod.rs"]
 mod backend;
@@ -7,6 +9,8 @@ mod codex_args;
 mod codex_config;
 #[path = "../codex/home.rs"]
 mod codex_home;
+#[path = "../files/detect.rs"]
+mod file_detect;
 #[path = "../files/io.rs"]
 mod file_io;
 #[path = "../files/ops.rs"]
@@ -15,14 +19,26 @@ mod file_ops;
 mod file_policy;
 #[path = "../git_utils.rs"]
 mod git_utils;
+#[path = "codex_monitor_daemon/latency.rs"]
+mod latency;
+#[path = "codex_monitor_daemon/orbit_status.rs"]
+mod orbit_status;
+#[path = "codex_monitor_daemon/rate_limit.rs"]
+mod rate_limit;
+#[path = "codex_monitor_daemon/resources.rs"]
+mod resources;
 #[path = "codex_monitor_daemon/rpc.rs"]
 mod rpc;
 #[path = "../rules.rs"]
 mod rules;
 #[path = "../shared/mod.rs"]
 mod shared;
+#[path = "codex_monitor_daemon/shutdown.rs"]
+mod shutdown;
 #[path = "../storage.rs"]
 mod storage;
+#[path = "codex_monitor_daemon/tls.rs"]
+mod tls;
 #[path = "codex_monitor_daemon/transport.rs"]
 mod transport;
 #[allow(dead_code)]
@@ -58,51 +74,75 @@ mod files {
     pub(crate) mod policy {
         pub(crate) use crate::file_policy::*;
     }
+    pub(crate) mod detect {
+        pub(crate) use crate::file_detect::*;
+    }
 }
 
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::env;
-use std::fs::File;
-use std::io::Read;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures_util::{SinkExt, StreamExt};
 use ignore::WalkBuilder;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::TcpListener;
 use tokio::sync::{broadcast, mpsc, Mutex, Semaphore};
 use tokio::time::sleep;
 use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::Message;
 
-use backend::app_server::{spawn_workspace_session, WorkspaceSession};
-use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
-use shared::codex_core::CodexLoginCancelState;
+use backend::app_server::{spawn_workspace_session, SessionRecords, WorkspaceSession};
+use backend::events::{
+    AppServerEvent, EventSink, GitStatusChanged, SessionExited, TerminalExit, TerminalOutput,
+    ThreadRenamed, WorkspaceFileTail,
+};
+use rate_limit::{
+    RateLimitConfig, RequestRateLimiter, DEFAULT_RATE_LIMIT_BURST, DEFAULT_RATE_LIMIT_PER_SEC,
+};
+use shared::codex_core::{CodexLoginCancelState, UserMessageInput};
 use shared::prompts_core::{self, CustomPromptEntry};
+use shared::skip_dirs::should_skip_dir;
 use shared::{
-    codex_aux_core, codex_core, files_core, git_core, git_ui_core, local_usage_core, settings_core,
-    workspaces_core, worktree_core,
+    codex_aux_core, codex_core, doctor_core, file_tail_core, files_core, git_core, git_ui_core,
+    git_watch_core, local_usage_core, search_core, session_error_core, settings_core,
+    timeline_core, workspace_files_core, workspaces_core, worktree_core,
 };
 use storage::{read_settings, read_workspaces};
 use types::{
-    AppSettings, GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse, LocalUsageSnapshot,
-    OrbitConnectTestResult, OrbitDeviceCodeStart, OrbitSignInPollResult, OrbitSignInStatus,
-    OrbitSignOutResult, WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
+    AppSettings, BulkArchiveThreadsResult, CodexDoctorReport, CodexLoginStatus, CodexToolState,
+    DaemonDoctorReport, ForkThreadToWorktreeResult, GitBranchPruneResult, GitCommitDiff,
+    GitFileDiff, GitHubIssuesResponse, GitHubPullRequestCheckout, GitHubPullRequestComment,
+    GitHubPullRequestDiff, GitHubPullRequestReviewThread, GitHubPullRequestsResponse,
+    GitHubRepoInfo, GitIncomingChanges, GitLastCommit, GitLogResponse, GitMergedBranches,
+    GitOperationProgress, GitPatchApplyResult, GitPatchExport, GitPushResult, GitReflogEntry,
+    GitReflogRestoreResult, GitRemote, GitRepairLocksResult, GitStatusDetailed,
+    GitWorkingTreeClean, LargeStagedFilesResponse, LocalUsageRefreshResult, LocalUsageSnapshot,
+    MessageCostEstimate, OrbitConnectTestResult, OrbitDeviceCodeStart, OrbitSignInPollResult,
+    OrbitSignInStatus, OrbitSignOutResult, PendingApproval, QueuedUserMessage, RecentWorkspaceInfo,
+    RepoWorktreesDiffSummary, ServerInfo, SessionCapabilities, SessionLastError, SessionPing,
+    ThreadInfo, WorkspaceActivity, WorkspaceCodexConfigPath, WorkspaceConnectResult,
+    WorkspaceEntry, WorkspaceFileDeleteResult, WorkspaceFileRenameResult, WorkspaceFileResponse,
+    WorkspaceFileTailStart, WorkspaceGrepResponse, WorkspaceInfo, WorkspaceSettings,
+    WorkspaceTimelineEntry, WorktreeBaseDiff, WorktreeFromStashResult, WorktreePathPreview,
+    WorktreeSetupStatus,
 };
 use workspace_settings::apply_workspace_settings_update;
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
 const MAX_IN_FLIGHT_RPC_PER_CONNECTION: usize = 32;
 const DAEMON_NAME: &str = "codex-monitor-daemon";
+const DEFAULT_MAX_AUTH_ATTEMPTS: u32 = 5;
+const DEFAULT_AUTH_FAILURE_DELAY_MS: u64 = 500;
+const DEFAULT_AUTH_TIMEOUT_SECS: u64 = 10;
 
 fn spawn_with_client(
-    event_sink: DaemonEventSink,
+    state: &DaemonState,
     client_version: String,
     entry: WorkspaceEntry,
     default_bin: Option<String>,
@@ -115,7 +155,11 @@ fn spawn_with_client(
         codex_args,
         codex_home,
         client_version,
-        event_sink,
+        state.event_sink.clone(),
+        SessionRecords {
+            events: state.session_events.clone(),
+            errors: state.session_errors.clone(),
+        },
     )
 }
 
@@ -131,6 +175,10 @@ enum DaemonEvent {
     TerminalOutput(TerminalOutput),
     #[allow(dead_code)]
     TerminalExit(TerminalExit),
+    SessionExited(SessionExited),
+    GitStatusChanged(GitStatusChanged),
+    ThreadRenamed(ThreadRenamed),
+    WorkspaceFileTail(WorkspaceFileTail),
 }
 
 impl EventSink for DaemonEventSink {
@@ -145,16 +193,83 @@ impl EventSink for DaemonEventSink {
     fn emit_terminal_exit(&self, event: TerminalExit) {
         let _ = self.tx.send(DaemonEvent::TerminalExit(event));
     }
+
+    fn emit_session_exited(&self, event: SessionExited) {
+        let _ = self.tx.send(DaemonEvent::SessionExited(event));
+    }
+
+    fn emit_git_status_changed(&self, event: GitStatusChanged) {
+        let _ = self.tx.send(DaemonEvent::GitStatusChanged(event));
+    }
+
+    fn emit_thread_renamed(&self, event: ThreadRenamed) {
+        let _ = self.tx.send(DaemonEvent::ThreadRenamed(event));
+    }
+
+    fn emit_workspace_file_tail(&self, event: WorkspaceFileTail) {
+        let _ = self.tx.send(DaemonEvent::WorkspaceFileTail(event));
+    }
 }
 
 struct DaemonConfig {
     listen: SocketAddr,
-    token: Option<String>,
+    /// Any of these authenticates a TCP client, so a new token can be rolled
+    /// out before the old one is dropped. Empty means no auth.
+    tokens: Vec<String>,
     data_dir: PathBuf,
     orbit_url: Option<String>,
     orbit_token: Option<String>,
     orbit_auth_url: Option<String>,
     orbit_runner_name: Option<String>,
+    idle_shutdown: Option<Duration>,
+    no_autoconnect: bool,
+    /// Per-connection request budget; `None` when `--rate-limit 0`.
+    rate_limit: Option<RateLimitConfig>,
+    /// Failed `auth` requests one peer IP gets, across connections, before it
+    /// is locked out; `None` when `--max-auth-attempts 0`.
+    max_auth_attempts: Option<u32>,
+    /// Pause before answering a failed `auth`, to slow down guessing.
+    auth_failure_delay: Duration,
+    /// How long a TCP client may stay connected without authenticating;
+    /// `None` when `--auth-timeout 0`.
+    auth_timeout: Option<Duration>,
+    /// Serve the TCP listener over TLS only; `None` keeps it plaintext.
+    tls: Option<tls::TlsPaths>,
+}
+
+/// Tracks connected TCP clients so the idle-shutdown watchdog can tell how long
+/// the daemon has gone unattended.
+struct ClientActivity {
+    connected: usize,
+    idle_since: Instant,
+}
+
+impl ClientActivity {
+    fn new(now: Instant) -> Self {
+        Self {
+            connected: 0,
+            idle_since: now,
+        }
+    }
+
+    fn client_connected(&mut self, now: Instant) {
+        self.connected += 1;
+        self.idle_since = now;
+    }
+
+    fn client_disconnected(&mut self, now: Instant) {
+        self.connected = self.connected.saturating_sub(1);
+        if self.connected == 0 {
+            self.idle_since = now;
+        }
+    }
+
+    fn idle_for(&self, now: Instant) -> Option<Duration> {
+        if self.connected > 0 {
+            return None;
+        }
+        Some(now.saturating_duration_since(self.idle_since))
+    }
 }
 
 struct DaemonState {
@@ -166,14 +281,24 @@ struct DaemonState {
     app_settings: Mutex<AppSettings>,
     event_sink: DaemonEventSink,
     codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    walk_cancellations: search_core::WalkCancellations,
+    git_watchers: git_watch_core::GitStatusWatchers,
+    file_tails: file_tail_core::FileTails,
+    session_events: timeline_core::SessionEvents,
+    session_errors: session_error_core::SessionErrors,
     daemon_mode: String,
     daemon_binary_path: Option<String>,
-}
-
-#[derive(Serialize, Deserialize)]
-struct WorkspaceFileResponse {
-    content: String,
-    truncated: bool,
+    started_at: Instant,
+    client_activity: std::sync::Mutex<ClientActivity>,
+    orbit_connection: std::sync::Mutex<orbit_status::OrbitConnectionTracker>,
+    method_latency: std::sync::Mutex<latency::MethodLatencyTracker>,
+    auth_failures: std::sync::Mutex<auth_lockout::AuthFailures>,
+    cpu_tracker: std::sync::Mutex<resources::CpuTracker>,
+    running_requests: shutdown::RunningRequests,
+    shutdown_request: shutdown::ShutdownRequest,
+    /// `--no-autoconnect`: forces `autoConnectWorkspaces` off for this run
+    /// without touching the stored setting.
+    no_autoconnect: bool,
 }
 
 impl DaemonState {
@@ -199,8 +324,24 @@ impl DaemonState {
             app_settings: Mutex::new(app_settings),
             event_sink,
             codex_login_cancels: Mutex::new(HashMap::new()),
+            walk_cancellations: search_core::WalkCancellations::default(),
+            git_watchers: git_watch_core::GitStatusWatchers::default(),
+            file_tails: file_tail_core::FileTails::default(),
+            session_events: timeline_core::SessionEvents::default(),
+            session_errors: session_error_core::SessionErrors::default(),
             daemon_mode,
             daemon_binary_path,
+            started_at: Instant::now(),
+            client_activity: std::sync::Mutex::new(ClientActivity::new(Instant::now())),
+            orbit_connection: std::sync::Mutex::new(orbit_status::OrbitConnectionTracker::new(
+                config.orbit_url.is_some(),
+            )),
+            method_latency: std::sync::Mutex::new(latency::MethodLatencyTracker::default()),
+            auth_failures: std::sync::Mutex::new(auth_lockout::AuthFailures::default()),
+            cpu_tracker: std::sync::Mutex::new(resources::CpuTracker::new(Instant::now())),
+            running_requests: shutdown::RunningRequests::default(),
+            shutdown_request: shutdown::ShutdownRequest::default(),
+            no_autoconnect: config.no_autoconnect,
         }
     }
 
@@ -214,10 +355,88 @@ impl DaemonState {
         })
     }
 
+    fn client_connected(&self) {
+        let mut activity = self
+            .client_activity
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        activity.client_connected(Instant::now());
+    }
+
+    fn client_disconnected(&self) {
+        let mut activity = self
+            .client_activity
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        activity.client_disconnected(Instant::now());
+    }
+
+    fn idle_for(&self) -> Option<Duration> {
+        let activity = self
+            .client_activity
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        activity.idle_for(Instant::now())
+    }
+
+    fn update_orbit_connection(
+        &self,
+        update: impl FnOnce(&mut orbit_status::OrbitConnectionTracker),
+    ) {
+        let mut tracker = self
+            .orbit_connection
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        update(&mut tracker);
+    }
+
+    fn orbit_status(&self) -> orbit_status::OrbitConnectionStatus {
+        let tracker = self
+            .orbit_connection
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        tracker.snapshot(Instant::now())
+    }
+
+    fn auth_failures(&self) -> std::sync::MutexGuard<'_, auth_lockout::AuthFailures> {
+        self.auth_failures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn resource_usage(&self) -> resources::ResourceUsage {
+        resources::current_resource_usage(self.started_at, &self.cpu_tracker)
+    }
+
+    fn record_method_latency(&self, method: &str, elapsed: Duration) {
+        let mut tracker = self
+            .method_latency
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        tracker.record(method, elapsed);
+    }
+
+    /// With `reset`, returns the current numbers and then clears them.
+    fn method_latency(&self, reset: bool) -> latency::MethodLatencyReport {
+        let mut tracker = self
+            .method_latency
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let report = tracker.report();
+        if reset {
+            tracker.reset();
+        }
+        report
+    }
+
     async fn list_workspaces(&self) -> Vec<WorkspaceInfo> {
         workspaces_core::list_workspaces_core(&self.workspaces, &self.sessions).await
     }
 
+    async fn workspaces_etag(&self) -> String {
+        workspaces_core::workspaces_etag_core(&self.workspaces, &self.sessions).await
+    }
+
     async fn is_workspace_path_dir(&self, path: String) -> bool {
         workspaces_core::is_workspace_path_dir_core(&path)
     }
@@ -238,7 +457,7 @@ impl DaemonState {
             &self.storage_path,
             move |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -250,12 +469,27 @@ impl DaemonState {
         .await
     }
 
+    async fn preview_worktree_path(
+        &self,
+        parent_id: String,
+        branch: String,
+    ) -> Result<WorktreePathPreview, String> {
+        workspaces_core::preview_worktree_path_core(
+            &parent_id,
+            &branch,
+            &self.data_dir,
+            &self.workspaces,
+        )
+        .await
+    }
+
     async fn add_worktree(
         &self,
         parent_id: String,
         branch: String,
         name: Option<String>,
-        copy_agents_md: bool,
+        base_ref: Option<String>,
+        copy_agents_md: Option<bool>,
         client_version: String,
     ) -> Result<WorkspaceInfo, String> {
         let client_version = client_version.clone();
@@ -263,6 +497,7 @@ impl DaemonState {
             parent_id,
             branch,
             name,
+            base_ref,
             copy_agents_md,
             &self.data_dir,
             &self.workspaces,
@@ -286,7 +521,7 @@ impl DaemonState {
             },
             move |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -298,6 +533,61 @@ impl DaemonState {
         .await
     }
 
+    async fn fork_thread_to_worktree(
+        &self,
+        parent_id: String,
+        thread_id: String,
+        branch: String,
+        name: Option<String>,
+        copy_agents_md: Option<bool>,
+        client_version: String,
+    ) -> Result<ForkThreadToWorktreeResult, String> {
+        workspaces_core::fork_thread_to_worktree_core(
+            parent_id.clone(),
+            thread_id,
+            &self.workspaces,
+            &self.sessions,
+            || {
+                self.add_worktree(
+                    parent_id,
+                    branch,
+                    name,
+                    None,
+                    copy_agents_md,
+                    client_version,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn worktree_from_stash(
+        &self,
+        parent_id: String,
+        stash: String,
+        branch: String,
+        name: Option<String>,
+        copy_agents_md: Option<bool>,
+        client_version: String,
+    ) -> Result<WorktreeFromStashResult, String> {
+        workspaces_core::worktree_from_stash_core(
+            parent_id.clone(),
+            stash,
+            &self.workspaces,
+            |base_ref| {
+                self.add_worktree(
+                    parent_id,
+                    branch,
+                    name,
+                    Some(base_ref),
+                    copy_agents_md,
+                    client_version,
+                )
+            },
+        )
+        .await
+    }
+
     async fn worktree_setup_status(
         &self,
         workspace_id: String,
@@ -316,7 +606,7 @@ impl DaemonState {
     }
 
     async fn remove_workspace(&self, id: String) -> Result<(), String> {
-        workspaces_core::remove_workspace_core(
+        let result = workspaces_core::remove_workspace_core(
             id,
             &self.workspaces,
             &self.sessions,
@@ -332,11 +622,16 @@ impl DaemonState {
             true,
             true,
         )
-        .await
+        .await;
+        git_watch_core::prune_git_watchers_core(&self.workspaces, &self.git_watchers).await;
+        file_tail_core::prune_file_tails_core(&self.workspaces, &self.file_tails).await;
+        timeline_core::prune_session_events_core(&self.workspaces, &self.session_events).await;
+        session_error_core::prune_session_errors_core(&self.workspaces, &self.session_errors).await;
+        result
     }
 
     async fn remove_worktree(&self, id: String) -> Result<(), String> {
-        workspaces_core::remove_worktree_core(
+        let result = workspaces_core::remove_worktree_core(
             id,
             &self.workspaces,
             &self.sessions,
@@ -350,7 +645,12 @@ impl DaemonState {
                     .map_err(|err| format!("Failed to remove worktree folder: {err}"))
             },
         )
-        .await
+        .await;
+        git_watch_core::prune_git_watchers_core(&self.workspaces, &self.git_watchers).await;
+        file_tail_core::prune_file_tails_core(&self.workspaces, &self.file_tails).await;
+        timeline_core::prune_session_events_core(&self.workspaces, &self.session_events).await;
+        session_error_core::prune_session_errors_core(&self.workspaces, &self.session_errors).await;
+        result
     }
 
     async fn rename_worktree(
@@ -387,7 +687,38 @@ impl DaemonState {
             },
             move |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
+    async fn move_worktree(
+        &self,
+        id: String,
+        destination: String,
+        client_version: String,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::move_worktree_core(
+            id,
+            destination,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            |entry| Ok(PathBuf::from(entry.path.clone())),
+            |root, args| {
+                workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
+            },
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -441,6 +772,38 @@ impl DaemonState {
         .await
     }
 
+    async fn get_codex_tools(&self, workspace_id: String) -> Result<Vec<CodexToolState>, String> {
+        workspaces_core::get_codex_tools_core(&self.workspaces, &workspace_id).await
+    }
+
+    async fn set_codex_tool_enabled(
+        &self,
+        workspace_id: String,
+        tool: String,
+        enabled: bool,
+        client_version: String,
+    ) -> Result<Vec<CodexToolState>, String> {
+        workspaces_core::set_codex_tool_enabled_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &workspace_id,
+            &tool,
+            enabled,
+            move |entry, default_bin, codex_args, codex_home| {
+                spawn_with_client(
+                    self,
+                    client_version.clone(),
+                    entry,
+                    default_bin,
+                    codex_args,
+                    codex_home,
+                )
+            },
+        )
+        .await
+    }
+
     async fn update_workspace_settings(
         &self,
         id: String,
@@ -460,7 +823,7 @@ impl DaemonState {
             },
             move |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -487,23 +850,139 @@ impl DaemonState {
         .await
     }
 
+    async fn set_workspace_auto_connect(
+        &self,
+        id: String,
+        auto_connect: bool,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::set_workspace_auto_connect_core(
+            id,
+            auto_connect,
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+        )
+        .await
+    }
+
+    async fn set_workspace_pinned(
+        &self,
+        id: String,
+        pinned: bool,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::set_workspace_pinned_core(
+            id,
+            pinned,
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+        )
+        .await
+    }
+
+    async fn workspace_timeline(
+        &self,
+        workspace_id: String,
+        limit: Option<usize>,
+    ) -> Result<Vec<WorkspaceTimelineEntry>, String> {
+        timeline_core::workspace_timeline_core(
+            &self.workspaces,
+            &self.session_events,
+            workspace_id,
+            limit,
+        )
+        .await
+    }
+
+    async fn session_last_error(
+        &self,
+        workspace_id: String,
+    ) -> Result<Option<SessionLastError>, String> {
+        session_error_core::session_last_error_core(
+            &self.workspaces,
+            &self.session_errors,
+            workspace_id,
+        )
+        .await
+    }
+
+    async fn set_default_collaboration_mode(
+        &self,
+        id: String,
+        mode: Option<String>,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::set_default_collaboration_mode_core(
+            id,
+            mode,
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+        )
+        .await
+    }
+
+    async fn set_default_effort(
+        &self,
+        id: String,
+        effort: Option<String>,
+        model: Option<String>,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::set_default_effort_core(
+            id,
+            effort,
+            model,
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+        )
+        .await
+    }
+
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
-        {
-            let sessions = self.sessions.lock().await;
-            if sessions.contains_key(&id) {
-                return Ok(());
-            }
+        let connected = self.sessions.lock().await.contains_key(&id);
+        if !connected {
+            let client_version = client_version.clone();
+            workspaces_core::connect_workspace_core(
+                id,
+                &self.workspaces,
+                &self.sessions,
+                &self.app_settings,
+                move |entry, default_bin, codex_args, codex_home| {
+                    spawn_with_client(
+                        self,
+                        client_version.clone(),
+                        entry,
+                        default_bin,
+                        codex_args,
+                        codex_home,
+                    )
+                },
+            )
+            .await?;
         }
+        Ok(())
+    }
 
-        let client_version = client_version.clone();
-        workspaces_core::connect_workspace_core(
-            id,
+    async fn record_recent_workspace(&self, id: String) -> Result<(), String> {
+        settings_core::record_recent_workspace_core(&self.app_settings, &self.settings_path, &id)
+            .await
+    }
+
+    async fn list_recent_workspaces(&self) -> Vec<RecentWorkspaceInfo> {
+        settings_core::list_recent_workspaces_core(&self.app_settings, &self.workspaces).await
+    }
+
+    async fn connect_all_workspaces(
+        &self,
+        client_version: String,
+    ) -> HashMap<String, WorkspaceConnectResult> {
+        workspaces_core::connect_all_workspaces_core(
             &self.workspaces,
             &self.sessions,
             &self.app_settings,
-            move |entry, default_bin, codex_args, codex_home| {
+            |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -515,13 +994,35 @@ impl DaemonState {
         .await
     }
 
+    fn with_autoconnect_override(&self, mut settings: AppSettings) -> AppSettings {
+        if self.no_autoconnect {
+            settings.auto_connect_workspaces = false;
+        }
+        settings
+    }
+
+    async fn auto_connect_enabled(&self) -> bool {
+        !self.no_autoconnect && self.app_settings.lock().await.auto_connect_workspaces
+    }
+
     async fn get_app_settings(&self) -> AppSettings {
-        settings_core::get_app_settings_core(&self.app_settings).await
+        let settings = settings_core::get_app_settings_core(&self.app_settings).await;
+        self.with_autoconnect_override(settings)
     }
 
-    async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
-        settings_core::update_app_settings_core(settings, &self.app_settings, &self.settings_path)
-            .await
+    async fn update_app_settings(&self, mut settings: AppSettings) -> Result<AppSettings, String> {
+        if self.no_autoconnect {
+            // Clients echo back the forced `false`; keep the stored preference.
+            settings.auto_connect_workspaces =
+                self.app_settings.lock().await.auto_connect_workspaces;
+        }
+        let settings = settings_core::update_app_settings_core(
+            settings,
+            &self.app_settings,
+            &self.settings_path,
+        )
+        .await?;
+        Ok(self.with_autoconnect_override(settings))
     }
 
     async fn orbit_connect_test(&self) -> Result<OrbitConnectTestResult, String> {
@@ -594,32 +1095,125 @@ impl DaemonState {
     }
 
     async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
+        let skip_dirs = self.app_settings.lock().await.file_list_skip_dirs.clone();
         workspaces_core::list_workspace_files_core(&self.workspaces, &workspace_id, |root| {
-            list_workspace_files_inner(root, 20000)
+            list_workspace_files_inner(root, 20000, &skip_dirs)
         })
         .await
     }
 
+    async fn grep_workspace(
+        &self,
+        workspace_id: String,
+        options: search_core::WorkspaceGrepOptions,
+    ) -> Result<WorkspaceGrepResponse, String> {
+        search_core::grep_workspace_core(
+            &self.workspaces,
+            &self.walk_cancellations,
+            &workspace_id,
+            options,
+        )
+        .await
+    }
+
+    fn cancel_workspace_walk(&self, connection: u64, request_id: &str) -> bool {
+        search_core::cancel_workspace_walk_core(
+            &self.walk_cancellations,
+            Some(connection),
+            request_id,
+        )
+    }
+
     async fn read_workspace_file(
         &self,
         workspace_id: String,
         path: String,
     ) -> Result<WorkspaceFileResponse, String> {
-        workspaces_core::read_workspace_file_core(
+        workspace_files_core::read_workspace_file_core(
             &self.workspaces,
+            &self.app_settings,
             &workspace_id,
             &path,
-            |root, rel_path| read_workspace_file_inner(root, rel_path),
         )
         .await
     }
 
-    async fn file_read(
+    async fn rename_workspace_file(
         &self,
-        scope: file_policy::FileScope,
-        kind: file_policy::FileKind,
-        workspace_id: Option<String>,
-    ) -> Result<file_io::TextFileResponse, String> {
+        workspace_id: String,
+        source: String,
+        destination: String,
+        force: bool,
+    ) -> Result<WorkspaceFileRenameResult, String> {
+        workspace_files_core::rename_workspace_file_core(
+            &self.workspaces,
+            &workspace_id,
+            &source,
+            &destination,
+            force,
+        )
+        .await
+    }
+
+    async fn create_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        content: String,
+    ) -> Result<String, String> {
+        workspace_files_core::create_workspace_file_core(
+            &self.workspaces,
+            &workspace_id,
+            &path,
+            &content,
+        )
+        .await
+    }
+
+    async fn delete_workspace_file(
+        &self,
+        workspace_id: String,
+        path: String,
+        recursive: bool,
+    ) -> Result<WorkspaceFileDeleteResult, String> {
+        workspace_files_core::delete_workspace_file_core(
+            &self.workspaces,
+            &workspace_id,
+            &path,
+            recursive,
+        )
+        .await
+    }
+
+    async fn tail_workspace_file(
+        &self,
+        connection: u64,
+        workspace_id: String,
+        path: String,
+        lines: Option<u32>,
+    ) -> Result<WorkspaceFileTailStart, String> {
+        file_tail_core::tail_workspace_file_core(
+            &self.workspaces,
+            &self.file_tails,
+            self.event_sink.clone(),
+            Some(connection),
+            workspace_id,
+            path,
+            lines,
+        )
+        .await
+    }
+
+    fn untail_workspace_file(&self, tail_id: &str) -> bool {
+        self.file_tails.untail(tail_id)
+    }
+
+    async fn file_read(
+        &self,
+        scope: file_policy::FileScope,
+        kind: file_policy::FileKind,
+        workspace_id: Option<String>,
+    ) -> Result<file_io::TextFileResponse, String> {
         files_core::file_read_core(&self.workspaces, scope, kind, workspace_id).await
     }
 
@@ -629,8 +1223,24 @@ impl DaemonState {
         kind: file_policy::FileKind,
         workspace_id: Option<String>,
         content: String,
-    ) -> Result<(), String> {
-        files_core::file_write_core(&self.workspaces, scope, kind, workspace_id, content).await
+        expected_hash: Option<String>,
+    ) -> Result<file_io::FileWriteResponse, String> {
+        files_core::file_write_core(
+            &self.workspaces,
+            scope,
+            kind,
+            workspace_id,
+            content,
+            expected_hash,
+        )
+        .await
+    }
+
+    async fn get_effective_agents_md(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<files_core::AgentsMdFile>, String> {
+        files_core::get_effective_agents_md_core(&self.workspaces, &workspace_id).await
     }
 
     async fn start_thread(&self, workspace_id: String) -> Result<Value, String> {
@@ -655,8 +1265,58 @@ impl DaemonState {
         cursor: Option<String>,
         limit: Option<u32>,
         sort_key: Option<String>,
+        status: Option<String>,
+    ) -> Result<Value, String> {
+        codex_core::list_threads_core(
+            &self.sessions,
+            workspace_id,
+            cursor,
+            limit,
+            sort_key,
+            status,
+        )
+        .await
+    }
+
+    async fn refresh_threads(
+        &self,
+        workspace_id: String,
+        limit: Option<u32>,
+        sort_key: Option<String>,
+    ) -> Result<Value, String> {
+        codex_core::refresh_threads_core(
+            &self.sessions,
+            workspace_id,
+            limit,
+            sort_key,
+            |workspace_id, message| {
+                self.event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: workspace_id.to_string(),
+                    message,
+                });
+            },
+        )
+        .await
+    }
+
+    async fn list_all_threads(
+        &self,
+        cursor: Option<String>,
+        limit: Option<u32>,
+        connect: bool,
+        client_version: String,
     ) -> Result<Value, String> {
-        codex_core::list_threads_core(&self.sessions, workspace_id, cursor, limit, sort_key).await
+        let connect = connect && self.auto_connect_enabled().await;
+        codex_core::list_all_threads_core(
+            &self.workspaces,
+            &self.sessions,
+            cursor,
+            limit,
+            connect.then_some(|workspace_id: String| {
+                self.connect_workspace(workspace_id, client_version.clone())
+            }),
+        )
+        .await
     }
 
     async fn list_mcp_server_status(
@@ -676,6 +1336,28 @@ impl DaemonState {
         codex_core::archive_thread_core(&self.sessions, workspace_id, thread_id).await
     }
 
+    async fn bulk_archive_threads(
+        &self,
+        workspace_id: String,
+        inactive_before: i64,
+        dry_run: bool,
+    ) -> Result<BulkArchiveThreadsResult, String> {
+        codex_core::bulk_archive_threads_core(
+            &self.workspaces,
+            &self.sessions,
+            workspace_id,
+            inactive_before,
+            dry_run,
+            |workspace_id, message| {
+                self.event_sink.emit_app_server_event(AppServerEvent {
+                    workspace_id: workspace_id.to_string(),
+                    message,
+                });
+            },
+        )
+        .await
+    }
+
     async fn compact_thread(
         &self,
         workspace_id: String,
@@ -684,13 +1366,47 @@ impl DaemonState {
         codex_core::compact_thread_core(&self.sessions, workspace_id, thread_id).await
     }
 
+    async fn get_thread_info(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<ThreadInfo, String> {
+        codex_core::get_thread_info_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn session_capabilities(
+        &self,
+        workspace_id: String,
+    ) -> Result<SessionCapabilities, String> {
+        codex_core::session_capabilities_core(&self.sessions, workspace_id).await
+    }
+
+    async fn session_ping(&self, workspace_id: String) -> Result<SessionPing, String> {
+        codex_core::session_ping_core(&self.sessions, workspace_id).await
+    }
+
+    async fn reset_thread_context(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        codex_core::reset_thread_context_core(&self.sessions, workspace_id, thread_id).await
+    }
+
     async fn set_thread_name(
         &self,
         workspace_id: String,
         thread_id: String,
         name: String,
     ) -> Result<Value, String> {
-        codex_core::set_thread_name_core(&self.sessions, workspace_id, thread_id, name).await
+        codex_core::set_thread_name_core(
+            &self.sessions,
+            &self.event_sink,
+            workspace_id,
+            thread_id,
+            name,
+        )
+        .await
     }
 
     async fn send_user_message(
@@ -706,6 +1422,9 @@ impl DaemonState {
     ) -> Result<Value, String> {
         codex_core::send_user_message_core(
             &self.sessions,
+            &self.workspaces,
+            &self.app_settings,
+            &self.settings_path,
             workspace_id,
             thread_id,
             text,
@@ -718,6 +1437,44 @@ impl DaemonState {
         .await
     }
 
+    async fn queue_user_message(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        message: UserMessageInput,
+    ) -> Result<QueuedUserMessage, String> {
+        codex_core::queue_user_message_core(
+            &self.sessions,
+            &self.workspaces,
+            &self.app_settings,
+            &self.settings_path,
+            workspace_id,
+            thread_id,
+            message,
+        )
+        .await
+    }
+
+    async fn cancel_queued_message(
+        &self,
+        workspace_id: String,
+        message_id: String,
+    ) -> Result<Value, String> {
+        codex_core::cancel_queued_message_core(&self.sessions, workspace_id, message_id).await
+    }
+
+    async fn recent_models(&self, workspace_id: String) -> Vec<String> {
+        settings_core::recent_models_core(&self.app_settings, &workspace_id).await
+    }
+
+    async fn reset_thread_turn_count(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        codex_core::reset_thread_turn_count_core(&self.sessions, workspace_id, thread_id).await
+    }
+
     async fn turn_steer(
         &self,
         workspace_id: String,
@@ -762,7 +1519,25 @@ impl DaemonState {
     }
 
     async fn collaboration_mode_list(&self, workspace_id: String) -> Result<Value, String> {
-        codex_core::collaboration_mode_list_core(&self.sessions, workspace_id).await
+        codex_core::collaboration_mode_list_core(&self.sessions, &self.workspaces, workspace_id)
+            .await
+    }
+
+    async fn set_thread_collaboration_mode(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+        collaboration_mode: Option<Value>,
+    ) -> Result<Value, String> {
+        codex_core::set_thread_collaboration_mode_core(
+            &self.workspaces,
+            &self.sessions,
+            &self.storage_path,
+            workspace_id,
+            thread_id,
+            collaboration_mode,
+        )
+        .await
     }
 
     async fn account_rate_limits(&self, workspace_id: String) -> Result<Value, String> {
@@ -773,6 +1548,10 @@ impl DaemonState {
         codex_core::account_read_core(&self.sessions, &self.workspaces, workspace_id).await
     }
 
+    async fn codex_login_status(&self, workspace_id: String) -> Result<CodexLoginStatus, String> {
+        codex_core::codex_login_status_core(&self.sessions, &self.workspaces, workspace_id).await
+    }
+
     async fn codex_login(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::codex_login_core(&self.sessions, &self.codex_login_cancels, workspace_id).await
     }
@@ -811,6 +1590,14 @@ impl DaemonState {
         Ok(json!({ "ok": true }))
     }
 
+    async fn list_pending_approvals(&self, workspace_id: Option<String>) -> Vec<PendingApproval> {
+        codex_core::list_pending_approvals_core(&self.sessions, workspace_id).await
+    }
+
+    async fn workspace_activity(&self) -> Vec<WorkspaceActivity> {
+        codex_core::workspace_activity_core(&self.sessions).await
+    }
+
     async fn remember_approval_rule(
         &self,
         workspace_id: String,
@@ -823,6 +1610,29 @@ impl DaemonState {
         codex_core::get_config_model_core(&self.workspaces, workspace_id).await
     }
 
+    async fn get_workspace_codex_config_path(
+        &self,
+        workspace_id: String,
+    ) -> Result<WorkspaceCodexConfigPath, String> {
+        codex_core::get_workspace_codex_config_path_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn estimate_message_cost(
+        &self,
+        workspace_id: String,
+        text: String,
+        model: Option<String>,
+    ) -> Result<MessageCostEstimate, String> {
+        codex_core::estimate_message_cost_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            text,
+            model,
+        )
+        .await
+    }
+
     async fn add_clone(
         &self,
         source_workspace_id: String,
@@ -840,7 +1650,7 @@ impl DaemonState {
             &self.storage_path,
             |entry, default_bin, codex_args, codex_home| {
                 spawn_with_client(
-                    self.event_sink.clone(),
+                    self,
                     client_version.clone(),
                     entry,
                     default_bin,
@@ -866,6 +1676,10 @@ impl DaemonState {
         workspaces_core::open_workspace_in_core(path, app, args, command).await
     }
 
+    async fn reveal_workspace_path(&self, workspace_id: String) -> Result<(), String> {
+        workspaces_core::reveal_workspace_path_core(&self.workspaces, &workspace_id).await
+    }
+
     async fn get_open_app_icon(&self, app_name: String) -> Result<Option<String>, String> {
         #[cfg(target_os = "macos")]
         {
@@ -885,6 +1699,40 @@ impl DaemonState {
         git_ui_core::get_git_status_core(&self.workspaces, workspace_id).await
     }
 
+    async fn is_working_tree_clean(
+        &self,
+        workspace_id: String,
+        include_untracked: bool,
+    ) -> Result<GitWorkingTreeClean, String> {
+        git_ui_core::is_working_tree_clean_core(&self.workspaces, workspace_id, include_untracked)
+            .await
+    }
+
+    async fn repo_worktrees_diff_summary(
+        &self,
+        workspace_id: String,
+    ) -> Result<RepoWorktreesDiffSummary, String> {
+        git_ui_core::repo_worktrees_diff_summary_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn get_git_status_detailed(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitStatusDetailed, String> {
+        git_ui_core::get_git_status_detailed_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn watch_git_status(&self, workspace_id: String, enabled: bool) -> Result<bool, String> {
+        git_watch_core::watch_git_status_core(
+            &self.workspaces,
+            &self.git_watchers,
+            self.event_sink.clone(),
+            workspace_id,
+            enabled,
+        )
+        .await
+    }
+
     async fn list_git_roots(
         &self,
         workspace_id: String,
@@ -893,16 +1741,71 @@ impl DaemonState {
         git_ui_core::list_git_roots_core(&self.workspaces, workspace_id, depth).await
     }
 
-    async fn get_git_diffs(&self, workspace_id: String) -> Result<Vec<GitFileDiff>, String> {
-        git_ui_core::get_git_diffs_core(&self.workspaces, &self.app_settings, workspace_id).await
+    async fn get_git_diffs(
+        &self,
+        workspace_id: String,
+        include_untracked: bool,
+        max_diff_bytes: Option<usize>,
+    ) -> Result<Vec<GitFileDiff>, String> {
+        git_ui_core::get_git_diffs_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            include_untracked,
+            max_diff_bytes,
+        )
+        .await
+    }
+
+    async fn get_git_file_diff(
+        &self,
+        workspace_id: String,
+        path: String,
+        staged: Option<bool>,
+    ) -> Result<Option<GitFileDiff>, String> {
+        git_ui_core::get_git_file_diff_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            path,
+            staged,
+        )
+        .await
     }
 
     async fn get_git_log(
         &self,
         workspace_id: String,
         limit: Option<usize>,
+        include_stats: bool,
     ) -> Result<GitLogResponse, String> {
-        git_ui_core::get_git_log_core(&self.workspaces, workspace_id, limit).await
+        git_ui_core::get_git_log_core(&self.workspaces, workspace_id, limit, include_stats).await
+    }
+
+    async fn worktree_diff_vs_base(
+        &self,
+        workspace_id: String,
+    ) -> Result<WorktreeBaseDiff, String> {
+        git_ui_core::worktree_diff_vs_base_core(&self.workspaces, &self.app_settings, workspace_id)
+            .await
+    }
+
+    async fn preview_incoming_changes(
+        &self,
+        workspace_id: String,
+        fetch: bool,
+        summary_only: bool,
+        max_diff_bytes: Option<usize>,
+    ) -> Result<GitIncomingChanges, String> {
+        git_ui_core::preview_incoming_changes_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            fetch,
+            summary_only,
+            max_diff_bytes,
+        )
+        .await
     }
 
     async fn get_git_commit_diff(
@@ -919,20 +1822,79 @@ impl DaemonState {
         .await
     }
 
-    async fn get_git_remote(&self, workspace_id: String) -> Result<Option<String>, String> {
-        git_ui_core::get_git_remote_core(&self.workspaces, workspace_id).await
+    async fn get_stash_diff(
+        &self,
+        workspace_id: String,
+        stash: String,
+    ) -> Result<Vec<GitCommitDiff>, String> {
+        git_ui_core::get_stash_diff_core(&self.workspaces, &self.app_settings, workspace_id, stash)
+            .await
     }
 
-    async fn stage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
-        git_ui_core::stage_git_file_core(&self.workspaces, workspace_id, path).await
+    async fn export_git_patch(
+        &self,
+        workspace_id: String,
+        revision: String,
+    ) -> Result<GitPatchExport, String> {
+        git_ui_core::export_git_patch_core(&self.workspaces, workspace_id, revision).await
     }
 
-    async fn stage_git_all(&self, workspace_id: String) -> Result<(), String> {
-        git_ui_core::stage_git_all_core(&self.workspaces, workspace_id).await
+    async fn apply_git_patch(
+        &self,
+        workspace_id: String,
+        patch: String,
+        check: bool,
+        three_way: bool,
+    ) -> Result<GitPatchApplyResult, String> {
+        git_ui_core::apply_git_patch_core(&self.workspaces, workspace_id, patch, check, three_way)
+            .await
     }
 
-    async fn unstage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
-        git_ui_core::unstage_git_file_core(&self.workspaces, workspace_id, path).await
+    async fn get_last_commit(&self, workspace_id: String) -> Result<GitLastCommit, String> {
+        git_ui_core::get_last_commit_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn git_operation_progress(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitOperationProgress, String> {
+        git_ui_core::git_operation_progress_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn git_reflog(
+        &self,
+        workspace_id: String,
+        ref_name: Option<String>,
+        limit: Option<usize>,
+    ) -> Result<Vec<GitReflogEntry>, String> {
+        git_ui_core::git_reflog_core(&self.workspaces, workspace_id, ref_name, limit).await
+    }
+
+    async fn restore_from_reflog(
+        &self,
+        workspace_id: String,
+        sha: String,
+        branch: Option<String>,
+        confirm: bool,
+    ) -> Result<GitReflogRestoreResult, String> {
+        git_ui_core::restore_from_reflog_core(&self.workspaces, workspace_id, sha, branch, confirm)
+            .await
+    }
+
+    async fn get_git_remote(&self, workspace_id: String) -> Result<Option<String>, String> {
+        git_ui_core::get_git_remote_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn stage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+        git_ui_core::stage_git_file_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn stage_git_all(&self, workspace_id: String) -> Result<(), String> {
+        git_ui_core::stage_git_all_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn unstage_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
+        git_ui_core::unstage_git_file_core(&self.workspaces, workspace_id, path).await
     }
 
     async fn revert_git_file(&self, workspace_id: String, path: String) -> Result<(), String> {
@@ -947,34 +1909,71 @@ impl DaemonState {
         git_ui_core::commit_git_core(&self.workspaces, workspace_id, message).await
     }
 
-    async fn push_git(&self, workspace_id: String) -> Result<(), String> {
-        git_ui_core::push_git_core(&self.workspaces, workspace_id).await
+    async fn check_large_staged_files(
+        &self,
+        workspace_id: String,
+        threshold_bytes: Option<u64>,
+    ) -> Result<LargeStagedFilesResponse, String> {
+        git_ui_core::check_large_staged_files_core(&self.workspaces, workspace_id, threshold_bytes)
+            .await
+    }
+
+    async fn git_repair_locks(
+        &self,
+        workspace_id: String,
+        remove: bool,
+    ) -> Result<GitRepairLocksResult, String> {
+        git_ui_core::git_repair_locks_core(&self.workspaces, workspace_id, remove).await
+    }
+
+    async fn push_git(
+        &self,
+        workspace_id: String,
+        force: Option<String>,
+        remote: Option<String>,
+    ) -> Result<GitPushResult, String> {
+        git_ui_core::push_git_core(&self.workspaces, workspace_id, force, remote).await
+    }
+
+    async fn pull_git(&self, workspace_id: String, remote: Option<String>) -> Result<(), String> {
+        git_ui_core::pull_git_core(&self.workspaces, workspace_id, remote).await
     }
 
-    async fn pull_git(&self, workspace_id: String) -> Result<(), String> {
-        git_ui_core::pull_git_core(&self.workspaces, workspace_id).await
+    async fn fetch_git(&self, workspace_id: String, remote: Option<String>) -> Result<(), String> {
+        git_ui_core::fetch_git_core(&self.workspaces, workspace_id, remote).await
     }
 
-    async fn fetch_git(&self, workspace_id: String) -> Result<(), String> {
-        git_ui_core::fetch_git_core(&self.workspaces, workspace_id).await
+    async fn list_git_remotes(&self, workspace_id: String) -> Result<Vec<GitRemote>, String> {
+        git_ui_core::list_git_remotes_core(&self.workspaces, workspace_id).await
     }
 
     async fn sync_git(&self, workspace_id: String) -> Result<(), String> {
         git_ui_core::sync_git_core(&self.workspaces, workspace_id).await
     }
 
+    async fn get_github_repo_info(&self, workspace_id: String) -> Result<GitHubRepoInfo, String> {
+        git_ui_core::get_github_repo_info_core(&self.workspaces, &self.app_settings, workspace_id)
+            .await
+    }
+
     async fn get_github_issues(
         &self,
         workspace_id: String,
     ) -> Result<GitHubIssuesResponse, String> {
-        git_ui_core::get_github_issues_core(&self.workspaces, workspace_id).await
+        git_ui_core::get_github_issues_core(&self.workspaces, &self.app_settings, workspace_id)
+            .await
     }
 
     async fn get_github_pull_requests(
         &self,
         workspace_id: String,
     ) -> Result<GitHubPullRequestsResponse, String> {
-        git_ui_core::get_github_pull_requests_core(&self.workspaces, workspace_id).await
+        git_ui_core::get_github_pull_requests_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+        )
+        .await
     }
 
     async fn get_github_pull_request_diff(
@@ -982,8 +1981,13 @@ impl DaemonState {
         workspace_id: String,
         pr_number: u64,
     ) -> Result<Vec<GitHubPullRequestDiff>, String> {
-        git_ui_core::get_github_pull_request_diff_core(&self.workspaces, workspace_id, pr_number)
-            .await
+        git_ui_core::get_github_pull_request_diff_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            pr_number,
+        )
+        .await
     }
 
     async fn get_github_pull_request_comments(
@@ -992,9 +1996,39 @@ impl DaemonState {
         pr_number: u64,
     ) -> Result<Vec<GitHubPullRequestComment>, String> {
         git_ui_core::get_github_pull_request_comments_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            pr_number,
+        )
+        .await
+    }
+
+    async fn get_github_pull_request_review_threads(
+        &self,
+        workspace_id: String,
+        pr_number: u64,
+    ) -> Result<Vec<GitHubPullRequestReviewThread>, String> {
+        git_ui_core::get_github_pull_request_review_threads_core(
+            &self.workspaces,
+            &self.app_settings,
+            workspace_id,
+            pr_number,
+        )
+        .await
+    }
+
+    async fn checkout_github_pull_request(
+        &self,
+        workspace_id: String,
+        pr_number: u64,
+        update: bool,
+    ) -> Result<GitHubPullRequestCheckout, String> {
+        git_ui_core::checkout_github_pull_request_core(
             &self.workspaces,
             workspace_id,
             pr_number,
+            update,
         )
         .await
     }
@@ -1011,6 +2045,31 @@ impl DaemonState {
         git_ui_core::create_git_branch_core(&self.workspaces, workspace_id, name).await
     }
 
+    async fn list_merged_branches(
+        &self,
+        workspace_id: String,
+    ) -> Result<GitMergedBranches, String> {
+        git_ui_core::list_merged_branches_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn prune_merged_branches(
+        &self,
+        workspace_id: String,
+        branches: Vec<String>,
+    ) -> Result<Vec<GitBranchPruneResult>, String> {
+        git_ui_core::prune_merged_branches_core(&self.workspaces, workspace_id, branches).await
+    }
+
+    async fn create_and_publish_branch(
+        &self,
+        workspace_id: String,
+        name: String,
+        remote: Option<String>,
+    ) -> Result<GitPushResult, String> {
+        git_ui_core::create_and_publish_branch_core(&self.workspaces, workspace_id, name, remote)
+            .await
+    }
+
     async fn prompts_list(&self, workspace_id: String) -> Result<Vec<CustomPromptEntry>, String> {
         prompts_core::prompts_list_core(&self.workspaces, &self.settings_path, workspace_id).await
     }
@@ -1093,14 +2152,34 @@ impl DaemonState {
         .await
     }
 
+    async fn daemon_doctor(&self) -> DaemonDoctorReport {
+        doctor_core::daemon_doctor_core(&self.app_settings, &self.data_dir).await
+    }
+
     async fn codex_doctor(
         &self,
         codex_bin: Option<String>,
         codex_args: Option<String>,
-    ) -> Result<Value, String> {
+    ) -> Result<CodexDoctorReport, String> {
         codex_aux_core::codex_doctor_core(&self.app_settings, codex_bin, codex_args).await
     }
 
+    async fn get_default_codex_bin(&self) -> Option<String> {
+        codex_aux_core::get_default_codex_bin_core(&self.app_settings).await
+    }
+
+    async fn set_default_codex_bin(
+        &self,
+        codex_bin: Option<String>,
+    ) -> Result<CodexDoctorReport, String> {
+        codex_aux_core::set_default_codex_bin_core(
+            &self.app_settings,
+            &self.settings_path,
+            codex_bin,
+        )
+        .await
+    }
+
     async fn generate_commit_message(&self, workspace_id: String) -> Result<String, String> {
         let repo_root = git_ui_core::resolve_repo_root_for_workspace_core(
             &self.workspaces,
@@ -1148,6 +2227,14 @@ impl DaemonState {
         local_usage_core::local_usage_snapshot_core(&self.workspaces, days, workspace_path).await
     }
 
+    async fn refresh_local_usage(
+        &self,
+        days: Option<u32>,
+        workspace_path: Option<String>,
+    ) -> Result<LocalUsageRefreshResult, String> {
+        local_usage_core::refresh_local_usage_core(&self.workspaces, days, workspace_path).await
+    }
+
     async fn menu_set_accelerators(&self, _updates: Vec<Value>) -> Result<(), String> {
         // Daemon has no native menu runtime; treat as no-op for remote parity.
         Ok(())
@@ -1162,21 +2249,34 @@ impl DaemonState {
     }
 }
 
-fn should_skip_dir(name: &str) -> bool {
-    matches!(
-        name,
-        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
-    )
-}
-
 fn normalize_git_path(path: &str) -> String {
     path.replace('\\', "/")
 }
 
-fn parse_optional_u64(value: &Value, key: &str) -> Option<u64> {
-    match value {
-        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()),
-        _ => None,
+/// Asks `main` to shut the daemon down gracefully, the same way as on
+/// SIGTERM, logging `reason`.
+fn schedule_daemon_shutdown(state: &DaemonState, reason: String) {
+    state.shutdown_request.request(reason);
+}
+
+async fn run_idle_shutdown_watchdog(state: Arc<DaemonState>, idle_timeout: Duration) {
+    let check_interval = (idle_timeout / 4).clamp(Duration::from_secs(1), Duration::from_secs(30));
+    loop {
+        sleep(check_interval).await;
+        let Some(idle) = state.idle_for() else {
+            continue;
+        };
+        if idle >= idle_timeout {
+            schedule_daemon_shutdown(
+                &state,
+                format!(
+                    "no clients connected for {}s (--idle-shutdown {}s)",
+                    idle.as_secs(),
+                    idle_timeout.as_secs()
+                ),
+            );
+            return;
+        }
     }
 }
 
@@ -1222,19 +2322,24 @@ fn send_notification_fallback_inner(title: String, body: String) -> Result<(), S
     }
 }
 
-fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
+fn list_workspace_files_inner(
+    root: &PathBuf,
+    max_files: usize,
+    extra_skip_dirs: &[String],
+) -> Vec<String> {
     let mut results = Vec::new();
+    let extra_skip_dirs = extra_skip_dirs.to_vec();
     let walker = WalkBuilder::new(root)
         .hidden(false)
         .follow_links(false)
         .require_git(false)
-        .filter_entry(|entry| {
+        .filter_entry(move |entry| {
             if entry.depth() == 0 {
                 return true;
             }
             if entry.file_type().is_some_and(|ft| ft.is_dir()) {
                 let name = entry.file_name().to_string_lossy();
-                return !should_skip_dir(&name);
+                return !should_skip_dir(&name, &extra_skip_dirs);
             }
             true
         })
@@ -1263,43 +2368,6 @@ fn list_workspace_files_inner(root: &PathBuf, max_files: usize) -> Vec<String> {
     results
 }
 
-const MAX_WORKSPACE_FILE_BYTES: u64 = 400_000;
-
-fn read_workspace_file_inner(
-    root: &PathBuf,
-    relative_path: &str,
-) -> Result<WorkspaceFileResponse, String> {
-    let canonical_root = root
-        .canonicalize()
-        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
-    let candidate = canonical_root.join(relative_path);
-    let canonical_path = candidate
-        .canonicalize()
-        .map_err(|err| format!("Failed to open file: {err}"))?;
-    if !canonical_path.starts_with(&canonical_root) {
-        return Err("Invalid file path".to_string());
-    }
-    let metadata = std::fs::metadata(&canonical_path)
-        .map_err(|err| format!("Failed to read file metadata: {err}"))?;
-    if !metadata.is_file() {
-        return Err("Path is not a file".to_string());
-    }
-
-    let file = File::open(&canonical_path).map_err(|err| format!("Failed to open file: {err}"))?;
-    let mut buffer = Vec::new();
-    file.take(MAX_WORKSPACE_FILE_BYTES + 1)
-        .read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read file: {err}"))?;
-
-    let truncated = buffer.len() > MAX_WORKSPACE_FILE_BYTES as usize;
-    if truncated {
-        buffer.truncate(MAX_WORKSPACE_FILE_BYTES as usize);
-    }
-
-    let content = String::from_utf8(buffer).map_err(|_| "File is not valid UTF-8".to_string())?;
-    Ok(WorkspaceFileResponse { content, truncated })
-}
-
 fn default_data_dir() -> PathBuf {
     if let Ok(xdg) = env::var("XDG_DATA_HOME") {
         let trimmed = xdg.trim();
@@ -1317,22 +2385,43 @@ fn default_data_dir() -> PathBuf {
 fn usage() -> String {
     format!(
         "\
-USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n  codex-monitor-daemon --orbit-url <ws-url> [--orbit-token <token>] [--orbit-auth-url <url>] [--orbit-runner-name <name>] [--data-dir <path>]\n\n\
-OPTIONS:\n  --listen <addr>          Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>        Data dir holding workspaces.json/settings.json\n  --token <token>          Shared token required by TCP clients\n  --insecure-no-auth       Disable TCP auth (dev only)\n  --orbit-url <ws-url>     Run in Orbit runner mode and connect outbound to this WS URL\n  --orbit-token <token>    Orbit auth token (optional if URL already includes token)\n  --orbit-auth-url <url>   Orbit auth base URL (metadata only, optional)\n  --orbit-runner-name <n>  Runner display name (metadata only, optional)\n  -h, --help               Show this help\n"
+USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth] [--idle-shutdown <secs>] [--no-autoconnect] [--rate-limit <rps>] [--rate-limit-burst <n>] [--max-auth-attempts <n>] [--auth-failure-delay-ms <ms>] [--auth-timeout <secs>] [--tls-cert <path> --tls-key <path>]\n  codex-monitor-daemon --orbit-url <ws-url> [--orbit-token <token>] [--orbit-auth-url <url>] [--orbit-runner-name <name>] [--data-dir <path>]\n\n\
+OPTIONS:\n  --listen <addr>          Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>        Data dir holding workspaces.json/settings.json\n  --token <token>          Token TCP clients must send; repeat to accept several\n  --insecure-no-auth       Disable TCP auth (dev only)\n  --idle-shutdown <secs>   Exit after this long with no connected TCP clients\n  --no-autoconnect         Only spawn sessions on explicit connect_workspace calls\n  --rate-limit <rps>       Requests per second per connection (default: {DEFAULT_RATE_LIMIT_PER_SEC}, 0 disables)\n  --rate-limit-burst <n>   Requests a connection may send at once (default: {DEFAULT_RATE_LIMIT_BURST})\n  --max-auth-attempts <n>  Failed auths from one IP before it is locked out for 15 minutes (default: {DEFAULT_MAX_AUTH_ATTEMPTS}, 0 disables)\n  --auth-failure-delay-ms <ms>  Pause before answering a failed auth (default: {DEFAULT_AUTH_FAILURE_DELAY_MS})\n  --auth-timeout <secs>    Close connections that have not authenticated by then (default: {DEFAULT_AUTH_TIMEOUT_SECS}, 0 disables)\n  --tls-cert <path>        PEM certificate chain; serve TLS instead of plaintext (needs --tls-key)\n  --tls-key <path>         PEM private key for --tls-cert\n  --orbit-url <ws-url>     Run in Orbit runner mode and connect outbound to this WS URL\n  --orbit-token <token>    Orbit auth token (optional if URL already includes token)\n  --orbit-auth-url <url>   Orbit auth base URL (metadata only, optional)\n  --orbit-runner-name <n>  Runner display name (metadata only, optional)\n  -h, --help               Show this help\n"
     )
 }
 
+/// Splits `CODEX_MONITOR_DAEMON_TOKEN`'s comma-separated list, dropping
+/// blanks and repeats.
+fn parse_token_list(value: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    for token in value.split(',').map(str::trim) {
+        if !token.is_empty() && !tokens.iter().any(|existing| existing == token) {
+            tokens.push(token.to_string());
+        }
+    }
+    tokens
+}
+
 fn parse_args() -> Result<DaemonConfig, String> {
     let mut listen = DEFAULT_LISTEN_ADDR
         .parse::<SocketAddr>()
         .map_err(|err| err.to_string())?;
-    let mut token = env::var("CODEX_MONITOR_DAEMON_TOKEN")
-        .ok()
-        .map(|value| value.trim().to_string())
-        .filter(|value| !value.is_empty());
+    let mut tokens = env::var("CODEX_MONITOR_DAEMON_TOKEN")
+        .map(|value| parse_token_list(&value))
+        .unwrap_or_default();
+    // The first `--token` replaces the environment's list; later ones add.
+    let mut tokens_from_args = false;
     let mut insecure_no_auth = false;
     let mut data_dir: Option<PathBuf> = None;
     let mut orbit_url: Option<String> = None;
+    let mut idle_shutdown: Option<Duration> = None;
+    let mut no_autoconnect = false;
+    let mut rate_limit = RateLimitConfig::default();
+    let mut max_auth_attempts = DEFAULT_MAX_AUTH_ATTEMPTS;
+    let mut auth_failure_delay = Duration::from_millis(DEFAULT_AUTH_FAILURE_DELAY_MS);
+    let mut auth_timeout_secs = DEFAULT_AUTH_TIMEOUT_SECS;
+    let mut tls_cert: Option<PathBuf> = None;
+    let mut tls_key: Option<PathBuf> = None;
     let mut orbit_token: Option<String> = env::var("CODEX_MONITOR_ORBIT_TOKEN")
         .ok()
         .map(|value| value.trim().to_string())
@@ -1363,7 +2452,13 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 if trimmed.is_empty() {
                     return Err("--token requires a non-empty value".to_string());
                 }
-                token = Some(trimmed.to_string());
+                if !tokens_from_args {
+                    tokens.clear();
+                    tokens_from_args = true;
+                }
+                if !tokens.iter().any(|token| token == trimmed) {
+                    tokens.push(trimmed.to_string());
+                }
             }
             "--data-dir" => {
                 let value = args.next().ok_or("--data-dir requires a value")?;
@@ -1375,7 +2470,76 @@ fn parse_args() -> Result<DaemonConfig, String> {
             }
             "--insecure-no-auth" => {
                 insecure_no_auth = true;
-                token = None;
+                tokens.clear();
+            }
+            "--idle-shutdown" => {
+                let value = args.next().ok_or("--idle-shutdown requires a value")?;
+                let secs = value
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .ok_or("--idle-shutdown requires a positive number of seconds")?;
+                idle_shutdown = Some(Duration::from_secs(secs));
+            }
+            "--no-autoconnect" => {
+                no_autoconnect = true;
+            }
+            "--rate-limit" => {
+                let value = args.next().ok_or("--rate-limit requires a value")?;
+                rate_limit.per_second = value
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| "--rate-limit requires a number of requests per second")?;
+            }
+            "--rate-limit-burst" => {
+                let value = args.next().ok_or("--rate-limit-burst requires a value")?;
+                rate_limit.burst = value
+                    .trim()
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|burst| *burst > 0)
+                    .ok_or("--rate-limit-burst requires a positive number")?;
+            }
+            "--max-auth-attempts" => {
+                let value = args.next().ok_or("--max-auth-attempts requires a value")?;
+                max_auth_attempts = value
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| "--max-auth-attempts requires a number")?;
+            }
+            "--auth-failure-delay-ms" => {
+                let value = args
+                    .next()
+                    .ok_or("--auth-failure-delay-ms requires a value")?;
+                let millis = value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| "--auth-failure-delay-ms requires a number of milliseconds")?;
+                auth_failure_delay = Duration::from_millis(millis);
+            }
+            "--auth-timeout" => {
+                let value = args.next().ok_or("--auth-timeout requires a value")?;
+                auth_timeout_secs = value
+                    .trim()
+                    .parse::<u64>()
+                    .map_err(|_| "--auth-timeout requires a number of seconds")?;
+            }
+            "--tls-cert" => {
+                let value = args.next().ok_or("--tls-cert requires a value")?;
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    return Err("--tls-cert requires a non-empty value".to_string());
+                }
+                tls_cert = Some(PathBuf::from(trimmed));
+            }
+            "--tls-key" => {
+                let value = args.next().ok_or("--tls-key requires a value")?;
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    return Err("--tls-key requires a non-empty value".to_string());
+                }
+                tls_key = Some(PathBuf::from(trimmed));
             }
             "--orbit-url" => {
                 let value = args.next().ok_or("--orbit-url requires a value")?;
@@ -1413,8 +2577,14 @@ fn parse_args() -> Result<DaemonConfig, String> {
         }
     }
 
+    let tls = match (tls_cert, tls_key) {
+        (Some(cert), Some(key)) => Some(tls::TlsPaths { cert, key }),
+        (None, None) => None,
+        _ => return Err("--tls-cert and --tls-key must be given together".to_string()),
+    };
+
     let is_orbit_mode = orbit_url.is_some();
-    if !is_orbit_mode && token.is_none() && !insecure_no_auth {
+    if !is_orbit_mode && tokens.is_empty() && !insecure_no_auth {
         return Err(
             "Missing --token (or set CODEX_MONITOR_DAEMON_TOKEN). Use --insecure-no-auth for local dev only."
                 .to_string(),
@@ -1423,12 +2593,19 @@ fn parse_args() -> Result<DaemonConfig, String> {
 
     Ok(DaemonConfig {
         listen,
-        token,
+        tokens,
         data_dir: data_dir.unwrap_or_else(default_data_dir),
         orbit_url,
         orbit_token,
         orbit_auth_url,
         orbit_runner_name,
+        idle_shutdown,
+        no_autoconnect,
+        rate_limit: (rate_limit.per_second > 0).then_some(rate_limit),
+        max_auth_attempts: (max_auth_attempts > 0).then_some(max_auth_attempts),
+        auth_failure_delay,
+        auth_timeout: (auth_timeout_secs > 0).then_some(Duration::from_secs(auth_timeout_secs)),
+        tls,
     })
 }
 
@@ -1441,6 +2618,12 @@ mod tests {
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
 
+    const TEST_ORIGIN: rpc::RequestOrigin = rpc::RequestOrigin {
+        connection: 0,
+        id: None,
+        transport: rpc::Transport::Tcp,
+    };
+
     fn run_async_test<F>(future: F)
     where
         F: Future<Output = ()>,
@@ -1476,8 +2659,24 @@ mod tests {
             app_settings: Mutex::new(AppSettings::default()),
             event_sink: DaemonEventSink { tx },
             codex_login_cancels: Mutex::new(HashMap::new()),
+            walk_cancellations: search_core::WalkCancellations::default(),
+            git_watchers: git_watch_core::GitStatusWatchers::default(),
+            file_tails: file_tail_core::FileTails::default(),
+            session_events: timeline_core::SessionEvents::default(),
+            session_errors: session_error_core::SessionErrors::default(),
             daemon_mode: "tcp".to_string(),
             daemon_binary_path: Some("/tmp/codex-monitor-daemon".to_string()),
+            started_at: Instant::now(),
+            client_activity: std::sync::Mutex::new(ClientActivity::new(Instant::now())),
+            orbit_connection: std::sync::Mutex::new(orbit_status::OrbitConnectionTracker::new(
+                false,
+            )),
+            method_latency: std::sync::Mutex::new(latency::MethodLatencyTracker::default()),
+            auth_failures: std::sync::Mutex::new(auth_lockout::AuthFailures::default()),
+            cpu_tracker: std::sync::Mutex::new(resources::CpuTracker::new(Instant::now())),
+            running_requests: shutdown::RunningRequests::default(),
+            shutdown_request: shutdown::ShutdownRequest::default(),
+            no_autoconnect: false,
         }
     }
 
@@ -1490,6 +2689,8 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings {
                 codex_home: Some(format!("{workspace_path}/.codex-home")),
                 ..WorkspaceSettings::default()
@@ -1502,6 +2703,112 @@ mod tests {
             .insert(workspace_id.to_string(), entry);
     }
 
+    #[test]
+    fn rpc_preview_worktree_path_skips_taken_paths_without_creating() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-preview-worktree-path");
+            let state = test_state(&tmp);
+            let repo_path = tmp.join("repo");
+            insert_workspace(&state, "ws-1", repo_path.to_string_lossy().as_ref()).await;
+            let worktree_root = tmp.join("worktrees").join("ws-1");
+            std::fs::create_dir_all(worktree_root.join("feat-login")).expect("create taken path");
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "preview_worktree_path",
+                json!({ "parentId": "ws-1", "branch": "feat/login" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("preview should succeed");
+
+            assert_eq!(
+                result.get("sanitizedName").and_then(Value::as_str),
+                Some("feat-login")
+            );
+            let expected = worktree_root.join("feat-login-2");
+            assert_eq!(
+                result.get("path").and_then(Value::as_str),
+                Some(expected.to_string_lossy().as_ref())
+            );
+            assert!(!expected.exists());
+
+            let invalid = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "preview_worktree_path",
+                json!({ "parentId": "ws-1", "branch": "bad name" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect_err("invalid branch should fail");
+            assert!(invalid.message().contains("cannot contain spaces"));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_codex_tools_read_defaults_and_toggle_config_flags() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-codex-tools");
+            let state = test_state(&tmp);
+            let workspace_path = tmp.join("repo");
+            let workspace_path = workspace_path.to_string_lossy().to_string();
+            insert_workspace(&state, "ws-1", &workspace_path).await;
+            let codex_home = PathBuf::from(format!("{workspace_path}/.codex-home"));
+            std::fs::create_dir_all(&codex_home).expect("create codex home");
+
+            let enabled_of = |tools: &Value, name: &str| {
+                tools
+                    .as_array()
+                    .and_then(|tools| {
+                        tools
+                            .iter()
+                            .find(|tool| tool.get("name").and_then(Value::as_str) == Some(name))
+                    })
+                    .and_then(|tool| tool.get("enabled").and_then(Value::as_bool))
+            };
+
+            let tools = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "get_codex_tools",
+                json!({ "workspaceId": "ws-1" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("get_codex_tools should succeed");
+            assert_eq!(enabled_of(&tools, "web_search_request"), Some(false));
+            assert_eq!(enabled_of(&tools, "shell_tool"), Some(true));
+
+            let tools = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "set_codex_tool_enabled",
+                json!({ "workspaceId": "ws-1", "tool": "web_search_request", "enabled": true }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("set_codex_tool_enabled should succeed");
+            assert_eq!(enabled_of(&tools, "web_search_request"), Some(true));
+            let config = std::fs::read_to_string(codex_home.join("config.toml")).expect("config");
+            assert!(config.contains("web_search_request = true"));
+
+            let unknown = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "set_codex_tool_enabled",
+                json!({ "workspaceId": "ws-1", "tool": "rm_rf", "enabled": true }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect_err("unknown tools should be rejected");
+            assert!(unknown.message().contains("Unknown codex tool"));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
     #[test]
     fn rpc_add_clone_uses_workspace_core_validation() {
         run_async_test(async {
@@ -1510,6 +2817,7 @@ mod tests {
 
             let err = rpc::handle_rpc_request(
                 &state,
+                &TEST_ORIGIN,
                 "add_clone",
                 json!({
                     "sourceWorkspaceId": "source",
@@ -1521,7 +2829,7 @@ mod tests {
             .await
             .expect_err("expected validation error");
 
-            assert_eq!(err, "Copy name is required.");
+            assert_eq!(err.message(), "Copy name is required.");
             let _ = std::fs::remove_dir_all(&tmp);
         });
     }
@@ -1543,6 +2851,7 @@ mod tests {
 
             let result = rpc::handle_rpc_request(
                 &state,
+                &TEST_ORIGIN,
                 "prompts_list",
                 json!({ "workspaceId": workspace_id }),
                 "daemon-test".to_string(),
@@ -1572,6 +2881,7 @@ mod tests {
 
             let result = rpc::handle_rpc_request(
                 &state,
+                &TEST_ORIGIN,
                 "local_usage_snapshot",
                 json!({ "days": 7 }),
                 "daemon-test".to_string(),
@@ -1585,6 +2895,56 @@ mod tests {
         });
     }
 
+    #[test]
+    fn rpc_cancel_workspace_walk_is_noop_for_unknown_requests() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-cancel-walk");
+            let state = test_state(&tmp);
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "cancel_workspace_walk",
+                json!({ "requestId": 42 }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("cancel_workspace_walk should succeed");
+
+            assert_eq!(result, json!({ "cancelled": false }));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_refresh_local_usage_reports_scan_stats() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-refresh-local-usage");
+            let state = test_state(&tmp);
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "refresh_local_usage",
+                json!({ "days": 7 }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("refresh_local_usage should succeed");
+
+            assert!(result
+                .get("snapshot")
+                .and_then(|snapshot| snapshot.get("totals"))
+                .is_some());
+            assert!(result.get("filesScanned").and_then(Value::as_u64).is_some());
+            assert!(result
+                .get("sessionsRoots")
+                .and_then(Value::as_array)
+                .is_some());
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
     #[test]
     fn rpc_daemon_info_reports_identity() {
         run_async_test(async {
@@ -1593,6 +2953,7 @@ mod tests {
 
             let result = rpc::handle_rpc_request(
                 &state,
+                &TEST_ORIGIN,
                 "daemon_info",
                 json!({}),
                 "daemon-test".to_string(),
@@ -1612,26 +2973,1230 @@ mod tests {
             let _ = std::fs::remove_dir_all(&tmp);
         });
     }
-}
 
-fn main() {
-    let config = match parse_args() {
-        Ok(config) => config,
-        Err(err) => {
-            eprintln!("{err}\n\n{}", usage());
-            std::process::exit(2);
-        }
-    };
+    #[test]
+    fn no_autoconnect_forces_setting_off_without_persisting_it() {
+        run_async_test(async {
+            let tmp = make_temp_dir("no-autoconnect");
+            let mut state = test_state(&tmp);
+            state.no_autoconnect = true;
+
+            let settings = state.get_app_settings().await;
+            assert!(!settings.auto_connect_workspaces);
+            assert!(!state.auto_connect_enabled().await);
+
+            let updated = state
+                .update_app_settings(settings)
+                .await
+                .expect("update settings");
+            assert!(!updated.auto_connect_workspaces);
+            assert!(state.app_settings.lock().await.auto_connect_workspaces);
+            let stored = read_settings(&state.settings_path).expect("read settings");
+            assert!(stored.auto_connect_workspaces);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
 
-    let runtime = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build()
-        .expect("failed to build tokio runtime");
+    #[test]
+    fn client_activity_resets_idle_timer_on_connect() {
+        let start = Instant::now();
+        let mut activity = ClientActivity::new(start);
+        let later = start + Duration::from_secs(60);
+        assert_eq!(activity.idle_for(later), Some(Duration::from_secs(60)));
+
+        activity.client_connected(later);
+        assert_eq!(activity.idle_for(later + Duration::from_secs(5)), None);
+
+        let disconnected_at = later + Duration::from_secs(10);
+        activity.client_disconnected(disconnected_at);
+        assert_eq!(
+            activity.idle_for(disconnected_at + Duration::from_secs(3)),
+            Some(Duration::from_secs(3))
+        );
+    }
 
-    runtime.block_on(async move {
-        let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
-        let event_sink = DaemonEventSink {
-            tx: events_tx.clone(),
+    #[test]
+    fn running_requests_drain_once_the_last_one_finishes() {
+        run_async_test(async {
+            let requests = shutdown::RunningRequests::default();
+            assert!(requests.wait_drained(Duration::from_millis(10)).await);
+
+            let first = requests.start().expect("first");
+            let second = requests.start().expect("second");
+            drop(first);
+            assert_eq!(requests.count(), 1);
+            assert!(!requests.wait_drained(Duration::from_millis(20)).await);
+
+            let finish_later = async {
+                sleep(Duration::from_millis(20)).await;
+                drop(second);
+            };
+            let (drained, ()) = futures_util::future::join(
+                requests.wait_drained(Duration::from_secs(5)),
+                finish_later,
+            )
+            .await;
+            assert!(drained);
+            assert_eq!(requests.count(), 0);
+        });
+    }
+
+    #[test]
+    fn requests_are_refused_once_shutdown_begins() {
+        run_async_test(async {
+            let tmp = make_temp_dir("shutdown-refuses-requests");
+            let state = test_state(&tmp);
+            let running = state.running_requests.start().expect("running");
+            state.running_requests.close();
+            assert!(state.running_requests.start().is_none());
+            assert_eq!(state.running_requests.count(), 1);
+            drop(running);
+            assert!(
+                state
+                    .running_requests
+                    .wait_drained(Duration::from_millis(10))
+                    .await
+            );
+
+            // Requested before anything waits, yet still ends the serve loop;
+            // the first reason is kept.
+            schedule_daemon_shutdown(&state, "requested by client".to_string());
+            schedule_daemon_shutdown(&state, "idle".to_string());
+            let reason =
+                shutdown::serve_until_shutdown(&state, futures_util::future::pending()).await;
+            assert_eq!(reason.as_deref(), Some("requested by client"));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rate_limiter_allows_a_burst_then_refills_over_time() {
+        let start = Instant::now();
+        let mut limiter = RequestRateLimiter::new(
+            RateLimitConfig {
+                per_second: 2,
+                burst: 3,
+            },
+            start,
+        );
+        assert!((0..3).all(|_| limiter.try_acquire(start)));
+        assert!(!limiter.try_acquire(start));
+
+        let half_second = start + Duration::from_millis(500);
+        assert!(limiter.try_acquire(half_second));
+        assert!(!limiter.try_acquire(half_second));
+
+        let much_later = start + Duration::from_secs(60);
+        assert!((0..3).all(|_| limiter.try_acquire(much_later)));
+        assert!(!limiter.try_acquire(much_later));
+    }
+
+    #[test]
+    fn orbit_connection_tracker_reports_retry_countdown() {
+        let start = Instant::now();
+        let mut tracker = orbit_status::OrbitConnectionTracker::new(true);
+        assert_eq!(tracker.snapshot(start).state, "connecting");
+
+        tracker.connect_failed("refused".to_string(), Duration::from_secs(8), start);
+        let status = tracker.snapshot(start + Duration::from_secs(3));
+        assert_eq!(status.state, "failed");
+        assert_eq!(status.last_error.as_deref(), Some("refused"));
+        assert_eq!(status.retry_in_secs, Some(5));
+        assert_eq!(status.failed_attempts, 1);
+
+        tracker.connected(start + Duration::from_secs(10));
+        let status = tracker.snapshot(start + Duration::from_secs(12));
+        assert_eq!(status.state, "connected");
+        assert_eq!(status.last_error, None);
+        assert_eq!(status.connected_for_secs, Some(2));
+        assert_eq!(status.failed_attempts, 0);
+
+        tracker.disconnected(
+            None,
+            Duration::from_secs(1),
+            start + Duration::from_secs(20),
+        );
+        let status = tracker.snapshot(start + Duration::from_secs(20));
+        assert_eq!(status.state, "reconnecting");
+        assert_eq!(status.retry_in_secs, Some(1));
+        assert_eq!(status.connected_for_secs, None);
+    }
+
+    #[test]
+    fn method_latency_keeps_a_bounded_window_per_method() {
+        let mut tracker = latency::MethodLatencyTracker::default();
+        for ms in 1..=200u64 {
+            tracker.record("get_git_diffs", Duration::from_millis(ms));
+        }
+        tracker.record("ping", Duration::from_micros(250));
+
+        let report = tracker.report();
+        assert_eq!(report.window_size, latency::LATENCY_WINDOW);
+        assert_eq!(report.methods.len(), 2);
+        let diffs = &report.methods[0];
+        assert_eq!(diffs.method, "get_git_diffs");
+        assert_eq!(diffs.calls, 200);
+        assert_eq!(diffs.samples, latency::LATENCY_WINDOW);
+        // The window holds the last 128 calls: 73ms..=200ms.
+        assert_eq!(diffs.avg_ms, 136.5);
+        assert_eq!(diffs.p95_ms, 194.0);
+        assert_eq!(diffs.max_ms, 200.0);
+        assert_eq!(report.methods[1].avg_ms, 0.25);
+
+        tracker.reset();
+        assert!(tracker.report().methods.is_empty());
+    }
+
+    #[test]
+    fn rpc_version_reports_build_identity() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-version");
+            let state = test_state(&tmp);
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "version",
+                json!({}),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("version should succeed");
+
+            assert_eq!(
+                result.get("version").and_then(Value::as_str),
+                Some(env!("CARGO_PKG_VERSION"))
+            );
+            let expected_profile = if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            };
+            assert_eq!(
+                result.get("profile").and_then(Value::as_str),
+                Some(expected_profile)
+            );
+            assert!(result.get("target").is_some());
+            assert!(result.get("gitHash").is_some());
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_resource_usage_reports_process_numbers() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-resource-usage");
+            let state = test_state(&tmp);
+
+            let result = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "resource_usage",
+                json!({}),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("resource_usage should succeed");
+
+            assert_eq!(
+                result.get("pid").and_then(Value::as_u64),
+                Some(u64::from(std::process::id()))
+            );
+            for key in ["rssBytes", "cpuPercent", "threadCount", "openFds"] {
+                assert!(result.get(key).is_some(), "missing `{key}`");
+            }
+            #[cfg(target_os = "linux")]
+            assert!(result.get("rssBytes").and_then(Value::as_u64).is_some());
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn cpu_percent_covers_only_the_interval_since_the_last_sample() {
+        let start = Instant::now();
+        let mut tracker = resources::CpuTracker::starting_at(start, Some(1.0));
+        assert_eq!(
+            tracker.sample(start + Duration::from_secs(2), Some(2.0)),
+            Some(50.0)
+        );
+        // Idle since the last sample, even though the lifetime average is 25%.
+        assert_eq!(
+            tracker.sample(start + Duration::from_secs(4), Some(2.0)),
+            Some(0.0)
+        );
+        assert_eq!(
+            tracker.sample(start + Duration::from_secs(4), Some(3.0)),
+            None
+        );
+        assert_eq!(tracker.sample(start + Duration::from_secs(5), None), None);
+    }
+
+    #[test]
+    fn rpc_estimate_message_cost_counts_tokens_and_prices_known_models() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-estimate-cost");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", tmp.to_string_lossy().as_ref()).await;
+            state
+                .app_settings
+                .lock()
+                .await
+                .model_input_prices
+                .insert("GPT-5".to_string(), 1.25);
+
+            let priced = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "estimate_message_cost",
+                json!({ "workspaceId": "ws-1", "text": "hello world", "model": "gpt-5" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("estimate should succeed");
+            assert_eq!(priced["tokenizer"], "o200k_base");
+            assert_eq!(priced["inputTokens"], 2);
+            assert_eq!(priced["inputPricePerMillion"], 1.25);
+            assert_eq!(priced["estimatedCostUsd"], 2.0 * 1.25 / 1_000_000.0);
+
+            let unpriced = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "estimate_message_cost",
+                json!({ "workspaceId": "ws-1", "text": "hello world", "model": "local-llm" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("estimate should succeed");
+            assert_eq!(unpriced["inputTokens"], 2);
+            assert!(unpriced["estimatedCostUsd"].is_null());
+
+            let missing = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "estimate_message_cost",
+                json!({ "workspaceId": "missing", "text": "hi", "model": "gpt-5" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect_err("unknown workspaces should fail");
+            assert_eq!(missing.code(), rpc::WORKSPACE_NOT_FOUND);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn responses_echo_numeric_and_string_ids_unchanged() {
+        let numeric: Value = serde_json::from_str(r#"{"id":42,"method":"ping"}"#).expect("json");
+        let string: Value =
+            serde_json::from_str(r#"{"id":"req-42","method":"ping"}"#).expect("json");
+        let missing: Value = serde_json::from_str(r#"{"method":"ping"}"#).expect("json");
+        let null: Value = serde_json::from_str(r#"{"id":null,"method":"ping"}"#).expect("json");
+
+        let reply = |message: &Value| {
+            rpc::build_result_response(rpc::parse_request_id(message), json!({ "ok": true }))
+                .map(|line| serde_json::from_str::<Value>(&line).expect("response json"))
+        };
+        assert_eq!(reply(&numeric).expect("numeric reply")["id"], json!(42));
+        assert_eq!(reply(&string).expect("string reply")["id"], json!("req-42"));
+        assert!(reply(&missing).is_none());
+        assert!(reply(&null).is_none());
+
+        let error =
+            rpc::build_error_response(rpc::parse_request_id(&string), &rpc::RpcError::InvalidToken)
+                .expect("error reply");
+        let error: Value = serde_json::from_str(&error).expect("error json");
+        assert_eq!(error["id"], json!("req-42"));
+        assert_eq!(error["error"]["message"], "invalid token");
+        assert!(rpc::build_error_response(None, &rpc::RpcError::Unauthorized).is_none());
+    }
+
+    #[test]
+    fn rpc_session_ping_requires_a_connected_session() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-session-ping");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+
+            let err = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "session_ping",
+                json!({ "workspaceId": "ws-1" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect_err("ping without a session should fail");
+            assert_eq!(err.message(), "workspace not connected");
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rpc_session_ping_sends_model_list_past_the_cache() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-session-ping-model-list");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let entry = state.workspaces.lock().await["ws-1"].clone();
+            let codex_bin = write_fake_codex(&tmp, ANSWER_EVERY_REQUEST);
+            let session = spawn_with_client(
+                &state,
+                "daemon-test".to_string(),
+                entry,
+                Some(codex_bin.clone()),
+                None,
+                None,
+            )
+            .await
+            .expect("spawn fake session");
+            state
+                .sessions
+                .lock()
+                .await
+                .insert("ws-1".to_string(), Arc::clone(&session));
+
+            for _ in 0..2 {
+                let ping = rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    "session_ping",
+                    json!({ "workspaceId": "ws-1" }),
+                    "daemon-test".to_string(),
+                )
+                .await
+                .expect("ping");
+                assert!(ping["latencyMs"].is_u64());
+            }
+            let log = std::fs::read_to_string(format!("{codex_bin}.log")).expect("request log");
+            assert_eq!(log.matches("\"model/list\"").count(), 2);
+
+            session.terminate().await;
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_record_recent_workspace_feeds_the_recents_list() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-recent-workspaces");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let call = |method: &'static str, params: Value| {
+                let state = &state;
+                async move {
+                    rpc::handle_rpc_request(
+                        state,
+                        &TEST_ORIGIN,
+                        method,
+                        params,
+                        "daemon-test".to_string(),
+                    )
+                    .await
+                }
+            };
+
+            let recents = call("list_recent_workspaces", json!({}))
+                .await
+                .expect("list recents");
+            assert_eq!(recents, json!([]));
+
+            call("record_recent_workspace", json!({ "id": "ws-1" }))
+                .await
+                .expect("record recent");
+            let recents = call("list_recent_workspaces", json!({}))
+                .await
+                .expect("list recents");
+            assert_eq!(recents[0]["id"], "ws-1");
+
+            let err = call("record_recent_workspace", json!({ "id": "missing" }))
+                .await
+                .expect_err("unknown workspace");
+            assert_eq!(err.message(), "workspace not found");
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_update_workspace_settings_validates_only_changed_defaults() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-default-effort");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let settings = |effort: Option<&str>, mode: Option<&str>| {
+                json!({
+                    "id": "ws-1",
+                    "settings": {
+                        "sortOrder": 2,
+                        "codexHome": format!("{}/.codex-home", tmp.to_string_lossy()),
+                        "defaultEffort": effort,
+                        "defaultCollaborationMode": mode,
+                    },
+                })
+            };
+
+            for params in [settings(Some("high"), None), settings(None, Some("plan"))] {
+                let err = rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    "update_workspace_settings",
+                    params,
+                    "daemon-test".to_string(),
+                )
+                .await
+                .expect_err("a new default needs a session to validate against");
+                assert_eq!(err.message(), "workspace not connected");
+            }
+
+            let updated = rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "update_workspace_settings",
+                settings(None, None),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("unchanged defaults save without a session");
+            assert_eq!(updated["settings"]["sortOrder"], 2);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    /// Writes a stand-in `codex` that reports a version and runs `app_server`
+    /// as its `app-server` command.
+    #[cfg(unix)]
+    fn write_fake_codex(dir: &std::path::Path, app_server: &str) -> String {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("fake-codex");
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo \"codex-cli 0.99.0\"; exit 0; fi\n{app_server}\n"
+            ),
+        )
+        .expect("write fake codex");
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .expect("make fake codex executable");
+        path.to_string_lossy().to_string()
+    }
+
+    /// Fake app-server loop that logs every request to `<script>.log` and
+    /// answers it with an empty result.
+    #[cfg(unix)]
+    const ANSWER_EVERY_REQUEST: &str = r#"while IFS= read -r line; do
+  printf '%s\n' "$line" >> "$0.log"
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  if [ -n "$id" ]; then printf '{"id":%s,"result":{}}\n' "$id"; fi
+done"#;
+
+    #[cfg(unix)]
+    #[test]
+    fn app_server_dying_during_initialize_is_reported_as_an_unclean_exit() {
+        run_async_test(async {
+            let tmp = make_temp_dir("spawn-init-crash");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let entry = state.workspaces.lock().await["ws-1"].clone();
+            let mut events = state.event_sink.tx.subscribe();
+
+            let error = spawn_with_client(
+                &state,
+                "daemon-test".to_string(),
+                entry,
+                Some(write_fake_codex(&tmp, "read -r line; exit 3")),
+                None,
+                None,
+            )
+            .await
+            .err()
+            .expect("initialize never answered");
+            assert!(error.contains("did not respond to initialize"), "{error}");
+
+            let exited = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    if let Ok(DaemonEvent::SessionExited(event)) = events.recv().await {
+                        break event;
+                    }
+                }
+            })
+            .await
+            .expect("session-exited event");
+            assert_eq!(exited.workspace_id, "ws-1");
+            assert!(!exited.clean);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn set_thread_name_broadcasts_thread_renamed_to_every_client() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-thread-renamed");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let entry = state.workspaces.lock().await["ws-1"].clone();
+            let session = spawn_with_client(
+                &state,
+                "daemon-test".to_string(),
+                entry,
+                Some(write_fake_codex(&tmp, ANSWER_EVERY_REQUEST)),
+                None,
+                None,
+            )
+            .await
+            .expect("spawn fake session");
+            state
+                .sessions
+                .lock()
+                .await
+                .insert("ws-1".to_string(), Arc::clone(&session));
+            let mut events = state.event_sink.tx.subscribe();
+
+            rpc::handle_rpc_request(
+                &state,
+                &TEST_ORIGIN,
+                "set_thread_name",
+                json!({ "workspaceId": "ws-1", "threadId": "thread-1", "name": "Renamed" }),
+                "daemon-test".to_string(),
+            )
+            .await
+            .expect("rename");
+
+            let renamed = loop {
+                match events.try_recv().expect("thread-renamed event") {
+                    DaemonEvent::ThreadRenamed(event) => break event,
+                    _ => continue,
+                }
+            };
+            assert_eq!(renamed.workspace_id, "ws-1");
+            assert_eq!(renamed.thread_id, "thread-1");
+            assert_eq!(renamed.name, "Renamed");
+
+            session.terminate().await;
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn session_capabilities_are_probed_once_per_session() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-session-capabilities");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let entry = state.workspaces.lock().await["ws-1"].clone();
+            // Logs every request and rejects `thread/fork` the way an
+            // app-server that predates it does.
+            let app_server = r#"while IFS= read -r line; do
+  printf '%s\n' "$line" >> "$0.log"
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  [ -n "$id" ] || continue
+  case "$line" in
+    *'"thread/fork"'*) printf '{"id":%s,"error":{"code":-32600,"message":"Invalid request: unknown variant `thread/fork`"}}\n' "$id" ;;
+    *) printf '{"id":%s,"result":{}}\n' "$id" ;;
+  esac
+done"#;
+            let codex_bin = write_fake_codex(&tmp, app_server);
+            let session = spawn_with_client(
+                &state,
+                "daemon-test".to_string(),
+                entry,
+                Some(codex_bin.clone()),
+                None,
+                None,
+            )
+            .await
+            .expect("spawn fake session");
+            state
+                .sessions
+                .lock()
+                .await
+                .insert("ws-1".to_string(), Arc::clone(&session));
+            let capabilities = || {
+                rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    "session_capabilities",
+                    json!({ "workspaceId": "ws-1" }),
+                    "daemon-test".to_string(),
+                )
+            };
+
+            let first = capabilities().await.expect("capabilities");
+            assert_eq!(first["codexVersion"], "codex-cli 0.99.0");
+            assert_eq!(first["operations"]["forkThread"], false);
+            assert_eq!(first["operations"]["renameThread"], true);
+            assert_eq!(capabilities().await.expect("cached"), first);
+            let log = std::fs::read_to_string(format!("{codex_bin}.log")).expect("request log");
+            assert_eq!(log.matches("\"thread/fork\"").count(), 1);
+
+            session.terminate().await;
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn sticky_collaboration_modes_persist_with_the_workspace_settings() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-sticky-mode");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let set_mode = |mode: Value| json!({ "workspaceId": "ws-1", "threadId": "thread-1", "collaborationMode": mode });
+            let call = |method: &'static str, params: Value| {
+                rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    method,
+                    params,
+                    "daemon-test".to_string(),
+                )
+            };
+
+            call("set_thread_collaboration_mode", set_mode(json!("plan")))
+                .await
+                .expect("set without a running session");
+            let stored = read_workspaces(&state.storage_path).expect("read storage");
+            assert_eq!(
+                stored["ws-1"]
+                    .settings
+                    .thread_collaboration_modes
+                    .get("thread-1"),
+                Some(&json!("plan"))
+            );
+
+            // Clients that do not know the field must not wipe it.
+            call(
+                "update_workspace_settings",
+                json!({ "id": "ws-1", "settings": { "sortOrder": 3 } }),
+            )
+            .await
+            .expect("update settings");
+            let listed = call("list_workspaces", json!({})).await.expect("list");
+            assert_eq!(
+                listed[0]["settings"]["threadCollaborationModes"]["thread-1"],
+                "plan"
+            );
+
+            call("set_thread_collaboration_mode", set_mode(Value::Null))
+                .await
+                .expect("clear");
+            let stored = read_workspaces(&state.storage_path).expect("read storage");
+            assert!(stored["ws-1"]
+                .settings
+                .thread_collaboration_modes
+                .is_empty());
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn cancel_aborts_an_in_flight_request_and_ignores_unknown_ids() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-cancel");
+            let state = Arc::new(test_state(&tmp));
+            // No permits, so the request waits until it is cancelled.
+            let requests = Arc::new(rpc::InFlightRequests::with_limit(0));
+            let (out_tx, mut out_rx) = mpsc::unbounded_channel();
+            let read = |line: String| serde_json::from_str::<Value>(&line).expect("response json");
+
+            rpc::spawn_rpc_response_task(
+                state,
+                out_tx.clone(),
+                Some(json!("slow")),
+                "ping".to_string(),
+                json!({}),
+                "daemon-test".to_string(),
+                Arc::clone(&requests),
+            );
+            tokio::task::yield_now().await;
+
+            rpc::handle_cancel_request(
+                &requests,
+                &out_tx,
+                Some(json!(2)),
+                &json!({ "requestId": "slow" }),
+            );
+            let cancelled = read(out_rx.recv().await.expect("cancelled reply"));
+            assert_eq!(cancelled["id"], "slow");
+            assert_eq!(cancelled["error"]["code"], -32800);
+            let ack = read(out_rx.recv().await.expect("cancel reply"));
+            assert_eq!(
+                ack,
+                json!({ "id": 2, "result": { "ok": true, "cancelled": true } })
+            );
+
+            rpc::handle_cancel_request(
+                &requests,
+                &out_tx,
+                Some(json!(3)),
+                &json!({ "requestId": "slow" }),
+            );
+            let ack = read(out_rx.recv().await.expect("cancel reply"));
+            assert_eq!(ack["result"], json!({ "ok": true, "cancelled": false }));
+
+            drop(out_tx);
+            assert!(
+                out_rx.recv().await.is_none(),
+                "the aborted request must not answer"
+            );
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    fn test_config(data_dir: &std::path::Path, tokens: &[&str]) -> DaemonConfig {
+        DaemonConfig {
+            listen: "127.0.0.1:0".parse().expect("listen addr"),
+            tokens: tokens.iter().map(|token| token.to_string()).collect(),
+            data_dir: data_dir.to_path_buf(),
+            orbit_url: None,
+            orbit_token: None,
+            orbit_auth_url: None,
+            orbit_runner_name: None,
+            idle_shutdown: None,
+            no_autoconnect: true,
+            rate_limit: None,
+            max_auth_attempts: None,
+            auth_failure_delay: Duration::ZERO,
+            auth_timeout: None,
+            tls: None,
+        }
+    }
+
+    /// Serves one TCP client with `handle_client` and returns its line reader
+    /// and writer.
+    async fn connect_test_client(
+        state: impl Into<Arc<DaemonState>>,
+        config: DaemonConfig,
+    ) -> (
+        tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        tokio::net::tcp::OwnedWriteHalf,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local addr");
+        let (events, _) = broadcast::channel::<DaemonEvent>(32);
+        let (state, config) = (state.into(), Arc::new(config));
+        tokio::spawn(async move {
+            let (socket, peer) = listener.accept().await.expect("accept");
+            transport::handle_client(
+                socket,
+                rpc::Transport::Tcp,
+                peer.ip(),
+                config,
+                state,
+                events,
+            )
+            .await;
+        });
+        let (reader, writer) = tokio::net::TcpStream::connect(addr)
+            .await
+            .expect("connect")
+            .into_split();
+        (BufReader::new(reader).lines(), writer)
+    }
+
+    async fn send_test_request(
+        lines: &mut tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>,
+        writer: &mut tokio::net::tcp::OwnedWriteHalf,
+        request: Value,
+    ) -> Value {
+        writer
+            .write_all(format!("{request}\n").as_bytes())
+            .await
+            .expect("write request");
+        let line = lines
+            .next_line()
+            .await
+            .expect("read")
+            .expect("response line");
+        serde_json::from_str(&line).expect("response json")
+    }
+
+    #[test]
+    fn server_info_is_answered_before_auth() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-server-info");
+            let (mut lines, mut writer) =
+                connect_test_client(test_state(&tmp), test_config(&tmp, &["secret"])).await;
+
+            let info = send_test_request(
+                &mut lines,
+                &mut writer,
+                json!({ "id": 1, "method": "server_info" }),
+            )
+            .await;
+            let info = &info["result"];
+            assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+            assert_eq!(info["protocolVersion"], rpc::PROTOCOL_VERSION);
+            assert_eq!(info["os"], std::env::consts::OS);
+            assert_eq!(info["websocket"], false);
+            assert_eq!(info["transport"], "tcp");
+            let methods = info["methods"].as_array().expect("methods");
+            assert!(methods.contains(&json!("cancel")));
+            assert!(methods.contains(&json!("server_info")));
+
+            let denied = send_test_request(
+                &mut lines,
+                &mut writer,
+                json!({ "id": 2, "method": "ping" }),
+            )
+            .await;
+            assert_eq!(denied["error"]["code"], rpc::UNAUTHORIZED);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn server_info_reports_the_daemon_mode_and_the_asking_transport() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-server-info-transport");
+            let state = DaemonState {
+                daemon_mode: "orbit".to_string(),
+                ..test_state(&tmp)
+            };
+            let info = |transport: rpc::Transport| {
+                let origin = rpc::RequestOrigin {
+                    transport,
+                    ..rpc::RequestOrigin::default()
+                };
+                let state = &state;
+                async move {
+                    rpc::handle_rpc_request(
+                        state,
+                        &origin,
+                        "server_info",
+                        json!({}),
+                        "daemon-test".to_string(),
+                    )
+                    .await
+                    .expect("server_info")
+                }
+            };
+            let over_websocket = info(rpc::Transport::WebSocket).await;
+            assert_eq!(over_websocket["websocket"], true);
+            assert_eq!(over_websocket["transport"], "websocket");
+            // The daemon still serves Orbit when a TLS client asks.
+            let over_tls = info(rpc::Transport::Tls).await;
+            assert_eq!(over_tls["websocket"], true);
+            assert_eq!(over_tls["transport"], "tls");
+            assert_eq!(
+                rpc::InFlightRequests::new(rpc::Transport::WebSocket).transport(),
+                rpc::Transport::WebSocket
+            );
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn auth_tokens_match_only_when_identical() {
+        assert!(transport::tokens_match("s3cret-token", "s3cret-token"));
+        assert!(!transport::tokens_match("s3cret-token", "s3cret-tokem"));
+        assert!(!transport::tokens_match("s3cret-token", "s3cret"));
+        assert!(!transport::tokens_match("s3cret", "s3cret-token"));
+        assert!(!transport::tokens_match("s3cret-token", ""));
+        // Trailing NULs must not pass for the missing bytes.
+        assert!(!transport::tokens_match("abc", "abc\0"));
+
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-auth-token");
+            let (mut lines, mut writer) =
+                connect_test_client(test_state(&tmp), test_config(&tmp, &["s3cret-token"])).await;
+            let auth = |id: u64, token: &str| json!({ "id": id, "method": "auth", "params": { "token": token } });
+
+            let rejected =
+                send_test_request(&mut lines, &mut writer, auth(1, "s3cret-tokem")).await;
+            assert_eq!(rejected["error"]["code"], rpc::INVALID_TOKEN);
+            let accepted =
+                send_test_request(&mut lines, &mut writer, auth(2, "s3cret-token")).await;
+            assert_eq!(accepted["result"], json!({ "ok": true }));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn rpc_list_workspaces_honors_if_none_match_until_the_listing_changes() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-workspaces-etag");
+            let state = test_state(&tmp);
+            insert_workspace(&state, "ws-1", &tmp.to_string_lossy()).await;
+            let call = |method: &'static str, params: Value| {
+                rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    method,
+                    params,
+                    "daemon-test".to_string(),
+                )
+            };
+
+            let etag = call("workspaces_etag", json!({})).await.expect("etag");
+            let etag = etag.as_str().expect("etag string").to_string();
+            let unchanged = call("list_workspaces", json!({ "ifNoneMatch": &etag }))
+                .await
+                .expect("list");
+            assert_eq!(unchanged, json!({ "notModified": true, "etag": &etag }));
+
+            call(
+                "set_workspace_pinned",
+                json!({ "id": "ws-1", "pinned": true }),
+            )
+            .await
+            .expect("pin");
+            let listing = call("list_workspaces", json!({ "ifNoneMatch": &etag }))
+                .await
+                .expect("list");
+            assert_eq!(listing[0]["pinned"], true);
+            let next = call("workspaces_etag", json!({})).await.expect("etag");
+            assert_ne!(next, json!(etag));
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn auth_accepts_any_configured_token() {
+        assert_eq!(
+            parse_token_list(" new-token , old-token,,new-token "),
+            vec!["new-token".to_string(), "old-token".to_string()]
+        );
+
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-auth-rotation");
+            let config = || test_config(&tmp, &["new-token", "old-token"]);
+            for token in ["old-token", "new-token"] {
+                let (mut lines, mut writer) = connect_test_client(test_state(&tmp), config()).await;
+                let request = json!({ "id": 1, "method": "auth", "params": { "token": token } });
+                let response = send_test_request(&mut lines, &mut writer, request).await;
+                assert_eq!(response["result"], json!({ "ok": true }), "{token}");
+            }
+
+            let (mut lines, mut writer) = connect_test_client(test_state(&tmp), config()).await;
+            let request = json!({ "id": 1, "method": "auth", "params": { "token": "retired" } });
+            let response = send_test_request(&mut lines, &mut writer, request).await;
+            assert_eq!(response["error"]["code"], rpc::INVALID_TOKEN);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn repeated_failed_auth_locks_out_the_peer_across_connections() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-auth-lockout");
+            let config = || DaemonConfig {
+                max_auth_attempts: Some(2),
+                ..test_config(&tmp, &["s3cret-token"])
+            };
+            let state = Arc::new(test_state(&tmp));
+            let auth = |id: u64, token: &str| json!({ "id": id, "method": "auth", "params": { "token": token } });
+
+            let (mut lines, mut writer) = connect_test_client(Arc::clone(&state), config()).await;
+            let first = send_test_request(&mut lines, &mut writer, auth(1, "guess")).await;
+            assert_eq!(first["error"]["code"], rpc::INVALID_TOKEN);
+            drop((lines, writer));
+
+            // Reconnecting keeps the count, so the next guess locks the IP out.
+            let (mut lines, mut writer) = connect_test_client(Arc::clone(&state), config()).await;
+            let second = send_test_request(&mut lines, &mut writer, auth(2, "guess")).await;
+            assert_eq!(second["error"]["code"], rpc::TOO_MANY_AUTH_ATTEMPTS);
+            assert!(lines.next_line().await.expect("read").is_none());
+
+            // Even the right token is refused while locked out.
+            let (mut lines, mut writer) = connect_test_client(Arc::clone(&state), config()).await;
+            let third = send_test_request(&mut lines, &mut writer, auth(3, "s3cret-token")).await;
+            assert_eq!(third["error"]["code"], rpc::TOO_MANY_AUTH_ATTEMPTS);
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn auth_failures_expire_after_the_lockout_window_and_clear_on_success() {
+        let peer: std::net::IpAddr = "100.64.0.7".parse().expect("ip");
+        let other: std::net::IpAddr = "100.64.0.8".parse().expect("ip");
+        let start = Instant::now();
+        let mut failures = auth_lockout::AuthFailures::default();
+        assert_eq!(failures.record(peer, start), 1);
+        assert_eq!(failures.record(peer, start + Duration::from_secs(60)), 2);
+        assert_eq!(failures.count(other, start), 0);
+        let later = start + Duration::from_secs(60) + auth_lockout::AUTH_LOCKOUT_WINDOW;
+        assert_eq!(failures.count(peer, later), 0);
+        assert_eq!(failures.record(peer, later), 1);
+        failures.clear(peer);
+        assert_eq!(failures.count(peer, later), 0);
+    }
+
+    #[test]
+    fn unauthenticated_connection_is_closed_after_the_auth_timeout() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-auth-timeout");
+            let config = DaemonConfig {
+                auth_timeout: Some(Duration::from_millis(50)),
+                ..test_config(&tmp, &["s3cret-token"])
+            };
+            let (mut lines, _writer) = connect_test_client(test_state(&tmp), config).await;
+
+            let line = lines
+                .next_line()
+                .await
+                .expect("read")
+                .expect("timeout error");
+            let response: Value = serde_json::from_str(&line).expect("error json");
+            assert_eq!(response["id"], Value::Null);
+            assert_eq!(response["error"]["code"], rpc::AUTH_TIMEOUT);
+            assert!(lines.next_line().await.expect("read").is_none());
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn tls_load_errors_name_the_offending_file() {
+        let tmp = make_temp_dir("tls-load");
+        let missing = tls::TlsPaths {
+            cert: tmp.join("missing.pem"),
+            key: tmp.join("key.pem"),
+        };
+        let err = tls::load_tls_acceptor(&missing)
+            .err()
+            .expect("missing cert");
+        assert!(err.starts_with("failed to load TLS certificate"));
+        assert!(err.contains("missing.pem"));
+
+        let empty = tmp.join("empty.pem");
+        std::fs::write(&empty, "not a certificate\n").expect("write pem");
+        let err = tls::load_tls_acceptor(&tls::TlsPaths {
+            cert: empty,
+            key: tmp.join("key.pem"),
+        })
+        .err()
+        .expect("no certificates");
+        assert!(err.ends_with("no certificates found"));
+        let _ = std::fs::remove_dir_all(&tmp);
+    }
+
+    #[test]
+    fn rpc_errors_carry_json_rpc_codes_and_param_details() {
+        run_async_test(async {
+            let tmp = make_temp_dir("rpc-error-codes");
+            let state = test_state(&tmp);
+            let call = |method: &'static str, params: Value| {
+                rpc::handle_rpc_request(
+                    &state,
+                    &TEST_ORIGIN,
+                    method,
+                    params,
+                    "daemon-test".to_string(),
+                )
+            };
+
+            let unknown = call("no_such_method", json!({}))
+                .await
+                .expect_err("unknown");
+            assert_eq!(unknown.code(), -32601);
+            assert_eq!(unknown.message(), "unknown method: no_such_method");
+
+            let invalid = call("connect_workspace", json!({}))
+                .await
+                .expect_err("invalid");
+            assert_eq!(invalid.code(), -32602);
+            let response = rpc::build_error_response(Some(json!(1)), &invalid).expect("reply");
+            let response: Value = serde_json::from_str(&response).expect("error json");
+            assert_eq!(
+                response["error"],
+                json!({
+                    "code": -32602,
+                    "message": "missing or invalid `id`",
+                    "data": { "param": "id" }
+                })
+            );
+
+            // Wrong types are invalid params too, optional ones included.
+            let mistyped = call("list_workspace_files", json!({ "workspaceId": 7 }))
+                .await
+                .expect_err("mistyped");
+            assert_eq!(
+                mistyped,
+                rpc::RpcError::InvalidParams {
+                    message: "missing or invalid `workspaceId`".to_string(),
+                    param: Some("workspaceId".to_string()),
+                }
+            );
+            let mistyped_optional = call("list_all_threads", json!({ "limit": "ten" }))
+                .await
+                .expect_err("mistyped optional");
+            assert_eq!(mistyped_optional.code(), -32602);
+
+            let unknown_workspace = call("remove_workspace", json!({ "id": "missing" }))
+                .await
+                .expect_err("unknown workspace");
+            assert_eq!(unknown_workspace.code(), rpc::WORKSPACE_NOT_FOUND);
+
+            let rate_limited =
+                rpc::build_error_response(Some(json!(2)), &rpc::RpcError::RateLimited)
+                    .expect("reply");
+            let rate_limited: Value = serde_json::from_str(&rate_limited).expect("error json");
+            assert_eq!(
+                rate_limited["error"],
+                json!({ "code": -32003, "message": "rate limited" })
+            );
+            let _ = std::fs::remove_dir_all(&tmp);
+        });
+    }
+
+    #[test]
+    fn gzip_frames_round_trip_large_diff_responses() {
+        use base64::Engine;
+        use std::io::Write;
+
+        let diff: String = (0..2_000)
+            .map(|index| format!("+    let value_{index} = compute(\"{index}\");\n"))
+            .collect();
+        let response = rpc::build_result_response(
+            Some(json!(7)),
+            json!([{ "path": "src/lib.rs", "diff": format!("@@ -1,0 +1,2000 @@\n{diff}") }]),
+        )
+        .expect("response");
+        assert!(response.len() > transport::GZIP_FRAME_THRESHOLD_BYTES);
+
+        let framed = transport::encode_outgoing_line(response.clone(), true);
+        assert!(
+            framed.len() * 4 < response.len(),
+            "expected a real size reduction"
+        );
+        let frame: Value = serde_json::from_str(&framed).expect("frame json");
+        assert_eq!(frame["encoding"], "gzip");
+        let compressed = base64::engine::general_purpose::STANDARD
+            .decode(frame["payload"].as_str().expect("payload"))
+            .expect("base64 payload");
+        let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+        decoder.write_all(&compressed).expect("inflate");
+        let inflated = decoder.finish().expect("finish inflate");
+        assert_eq!(String::from_utf8(inflated).expect("utf-8"), response);
+
+        assert_eq!(
+            transport::encode_outgoing_line(response.clone(), false),
+            response
+        );
+        let small =
+            rpc::build_result_response(Some(json!(8)), json!({ "ok": true })).expect("small");
+        assert_eq!(transport::encode_outgoing_line(small.clone(), true), small);
+    }
+}
+
+fn main() {
+    let config = match parse_args() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}\n\n{}", usage());
+            std::process::exit(2);
+        }
+    };
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+
+    runtime.block_on(async move {
+        let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
+        let event_sink = DaemonEventSink {
+            tx: events_tx.clone(),
         };
         let state = Arc::new(DaemonState::load(&config, event_sink));
         let config = Arc::new(config);
@@ -1645,10 +4210,20 @@ fn main() {
                     .unwrap_or(&state.storage_path)
                     .display()
             );
-            transport::run_orbit_mode(config, state, events_tx).await;
+            let orbit = transport::run_orbit_mode(config, Arc::clone(&state), events_tx);
+            if let Some(reason) = shutdown::serve_until_shutdown(&state, orbit).await {
+                shutdown::shutdown_gracefully(&state, &reason).await;
+            }
             return;
         }
 
+        let tls_acceptor = match config.tls.as_ref().map(tls::load_tls_acceptor).transpose() {
+            Ok(acceptor) => acceptor,
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(2);
+            }
+        };
         let listener = match TcpListener::bind(config.listen).await {
             Ok(listener) => listener,
             Err(err) => {
@@ -1657,8 +4232,13 @@ fn main() {
             }
         };
         eprintln!(
-            "codex-monitor-daemon listening on {} (data dir: {})",
+            "codex-monitor-daemon listening on {}{} (data dir: {})",
             config.listen,
+            if tls_acceptor.is_some() {
+                " over TLS"
+            } else {
+                ""
+            },
             state
                 .storage_path
                 .parent()
@@ -1666,18 +4246,57 @@ fn main() {
                 .display()
         );
 
-        loop {
-            match listener.accept().await {
-                Ok((socket, _addr)) => {
-                    let config = Arc::clone(&config);
-                    let state = Arc::clone(&state);
-                    let events = events_tx.clone();
-                    tokio::spawn(async move {
-                        transport::handle_client(socket, config, state, events).await;
-                    });
+        if let Some(idle_timeout) = config.idle_shutdown {
+            eprintln!(
+                "codex-monitor-daemon will exit after {}s without connected clients",
+                idle_timeout.as_secs()
+            );
+            tokio::spawn(run_idle_shutdown_watchdog(Arc::clone(&state), idle_timeout));
+        }
+
+        let accept_loop = async {
+            loop {
+                match listener.accept().await {
+                    Ok((socket, addr)) => {
+                        let config = Arc::clone(&config);
+                        let state = Arc::clone(&state);
+                        let events = events_tx.clone();
+                        let tls_acceptor = tls_acceptor.clone();
+                        tokio::spawn(async move {
+                            match tls_acceptor {
+                                Some(acceptor) => {
+                                    tls::handle_tls_client(
+                                        acceptor,
+                                        socket,
+                                        addr.ip(),
+                                        config,
+                                        state,
+                                        events,
+                                    )
+                                    .await
+                                }
+                                None => {
+                                    transport::handle_client(
+                                        socket,
+                                        rpc::Transport::Tcp,
+                                        addr.ip(),
+                                        config,
+                                        state,
+                                        events,
+                                    )
+                                    .await
+                                }
+                            }
+                        });
+                    }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
             }
+        };
+        if let Some(reason) = shutdown::serve_until_shutdown(&state, accept_loop).await {
+            // Stop accepting before draining so no new client slips in.
+            drop(listener);
+            shutdown::shutdown_gracefully(&state, &reason).await;
         }
     });
 }
@@ -0,0 +1,38 @@
+use super::*;
+
+use std::net::IpAddr;
+
+/// How long a peer's failed attempts are remembered after its latest one.
+pub(super) const AUTH_LOCKOUT_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+/// Failed `auth` attempts per peer IP. Kept on `DaemonState` rather than per
+/// connection, so reconnecting doesn't start a guesser over with a fresh
+/// count. A peer is forgotten once `AUTH_LOCKOUT_WINDOW` passes without a
+/// failure, or as soon as it authenticates.
+#[derive(Default)]
+pub(super) struct AuthFailures {
+    by_peer: HashMap<IpAddr, (u32, Instant)>,
+}
+
+impl AuthFailures {
+    /// Failed attempts still counted against `peer` at `now`.
+    pub(super) fn count(&self, peer: IpAddr, now: Instant) -> u32 {
+        self.by_peer
+            .get(&peer)
+            .filter(|(_, last)| now.saturating_duration_since(*last) < AUTH_LOCKOUT_WINDOW)
+            .map_or(0, |(count, _)| *count)
+    }
+
+    /// Records a failure and returns the peer's count including it.
+    pub(super) fn record(&mut self, peer: IpAddr, now: Instant) -> u32 {
+        let count = self.count(peer, now).saturating_add(1);
+        self.by_peer
+            .retain(|_, (_, last)| now.saturating_duration_since(*last) < AUTH_LOCKOUT_WINDOW);
+        self.by_peer.insert(peer, (count, now));
+        count
+    }
+
+    pub(super) fn clear(&mut self, peer: IpAddr) {
+        self.by_peer.remove(&peer);
+    }
+}
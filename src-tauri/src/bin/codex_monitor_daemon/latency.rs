@@ -0,0 +1,109 @@
+use super::*;
+
+/// Calls kept per method for the rolling average and p95.
+pub(super) const LATENCY_WINDOW: usize = 128;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MethodLatency {
+    pub(super) method: String,
+    /// Calls since the last reset, including ones that fell out of the window.
+    pub(super) calls: u64,
+    pub(super) samples: usize,
+    pub(super) avg_ms: f64,
+    pub(super) p95_ms: f64,
+    pub(super) max_ms: f64,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct MethodLatencyReport {
+    pub(super) window_size: usize,
+    pub(super) methods: Vec<MethodLatency>,
+}
+
+/// Ring buffer of the most recent handling times, in microseconds.
+struct LatencyWindow {
+    samples: [u32; LATENCY_WINDOW],
+    next: usize,
+    len: usize,
+    calls: u64,
+}
+
+impl LatencyWindow {
+    fn new() -> Self {
+        Self {
+            samples: [0; LATENCY_WINDOW],
+            next: 0,
+            len: 0,
+            calls: 0,
+        }
+    }
+
+    fn record(&mut self, micros: u32) {
+        self.samples[self.next] = micros;
+        self.next = (self.next + 1) % LATENCY_WINDOW;
+        self.len = (self.len + 1).min(LATENCY_WINDOW);
+        self.calls += 1;
+    }
+
+    fn summary(&self, method: &str) -> MethodLatency {
+        let mut sorted = self.samples;
+        let sorted = &mut sorted[..self.len];
+        sorted.sort_unstable();
+        let to_ms = |micros: u64| (micros as f64 / 10.0).round() / 100.0;
+        let total: u64 = sorted.iter().map(|&micros| u64::from(micros)).sum();
+        let p95_index = (self.len * 95).div_ceil(100).saturating_sub(1);
+        MethodLatency {
+            method: method.to_string(),
+            calls: self.calls,
+            samples: self.len,
+            avg_ms: to_ms(total / self.len.max(1) as u64),
+            p95_ms: to_ms(sorted.get(p95_index).copied().map_or(0, u64::from)),
+            max_ms: to_ms(sorted.last().copied().map_or(0, u64::from)),
+        }
+    }
+}
+
+/// Per-method handling times for `method_latency`. Only the first call of a
+/// method allocates; later calls overwrite a slot in its fixed-size window.
+#[derive(Default)]
+pub(super) struct MethodLatencyTracker {
+    windows: HashMap<String, LatencyWindow>,
+}
+
+impl MethodLatencyTracker {
+    pub(super) fn record(&mut self, method: &str, elapsed: Duration) {
+        let micros = u32::try_from(elapsed.as_micros()).unwrap_or(u32::MAX);
+        match self.windows.get_mut(method) {
+            Some(window) => window.record(micros),
+            None => {
+                let mut window = LatencyWindow::new();
+                window.record(micros);
+                self.windows.insert(method.to_string(), window);
+            }
+        }
+    }
+
+    /// Slowest methods first, by average.
+    pub(super) fn report(&self) -> MethodLatencyReport {
+        let mut methods: Vec<MethodLatency> = self
+            .windows
+            .iter()
+            .map(|(method, window)| window.summary(method))
+            .collect();
+        methods.sort_by(|a, b| {
+            b.avg_ms
+                .total_cmp(&a.avg_ms)
+                .then_with(|| a.method.cmp(&b.method))
+        });
+        MethodLatencyReport {
+            window_size: LATENCY_WINDOW,
+            methods,
+        }
+    }
+
+    pub(super) fn reset(&mut self) {
+        self.windows.clear();
+    }
+}
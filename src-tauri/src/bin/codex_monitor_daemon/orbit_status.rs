@@ -0,0 +1,105 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OrbitConnectionState {
+    Disabled,
+    Connecting,
+    Connected,
+    Reconnecting,
+    Failed,
+}
+
+impl OrbitConnectionState {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Disabled => "disabled",
+            Self::Connecting => "connecting",
+            Self::Connected => "connected",
+            Self::Reconnecting => "reconnecting",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct OrbitConnectionStatus {
+    pub(super) state: &'static str,
+    pub(super) last_error: Option<String>,
+    pub(super) retry_in_secs: Option<u64>,
+    pub(super) connected_for_secs: Option<u64>,
+    pub(super) failed_attempts: u32,
+}
+
+/// Connection state of the outbound Orbit websocket, updated by
+/// `run_orbit_mode` as it connects, drops and backs off.
+pub(super) struct OrbitConnectionTracker {
+    state: OrbitConnectionState,
+    last_error: Option<String>,
+    connected_since: Option<Instant>,
+    retry_at: Option<Instant>,
+    failed_attempts: u32,
+}
+
+impl OrbitConnectionTracker {
+    pub(super) fn new(enabled: bool) -> Self {
+        Self {
+            state: if enabled {
+                OrbitConnectionState::Connecting
+            } else {
+                OrbitConnectionState::Disabled
+            },
+            last_error: None,
+            connected_since: None,
+            retry_at: None,
+            failed_attempts: 0,
+        }
+    }
+
+    pub(super) fn connecting(&mut self) {
+        self.state = OrbitConnectionState::Connecting;
+        self.connected_since = None;
+        self.retry_at = None;
+    }
+
+    pub(super) fn connected(&mut self, now: Instant) {
+        self.state = OrbitConnectionState::Connected;
+        self.last_error = None;
+        self.connected_since = Some(now);
+        self.retry_at = None;
+        self.failed_attempts = 0;
+    }
+
+    /// A connection attempt failed before the socket was established.
+    pub(super) fn connect_failed(&mut self, error: String, retry_in: Duration, now: Instant) {
+        self.state = OrbitConnectionState::Failed;
+        self.last_error = Some(error);
+        self.connected_since = None;
+        self.retry_at = Some(now + retry_in);
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+    }
+
+    /// An established connection dropped; `error` is `None` on a clean close.
+    pub(super) fn disconnected(&mut self, error: Option<String>, retry_in: Duration, now: Instant) {
+        self.state = OrbitConnectionState::Reconnecting;
+        if error.is_some() {
+            self.last_error = error;
+        }
+        self.connected_since = None;
+        self.retry_at = Some(now + retry_in);
+    }
+
+    pub(super) fn snapshot(&self, now: Instant) -> OrbitConnectionStatus {
+        OrbitConnectionStatus {
+            state: self.state.as_str(),
+            last_error: self.last_error.clone(),
+            retry_in_secs: self
+                .retry_at
+                .map(|retry_at| retry_at.saturating_duration_since(now).as_secs()),
+            connected_for_secs: self
+                .connected_since
+                .map(|since| now.saturating_duration_since(since).as_secs()),
+            failed_attempts: self.failed_attempts,
+        }
+    }
+}
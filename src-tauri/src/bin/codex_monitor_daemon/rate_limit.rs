@@ -0,0 +1,56 @@
+use super::*;
+
+/// Generous enough that the app's polling and bursts on reconnect never hit
+/// it; only a client stuck in a loop should.
+pub(super) const DEFAULT_RATE_LIMIT_PER_SEC: u32 = 50;
+pub(super) const DEFAULT_RATE_LIMIT_BURST: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct RateLimitConfig {
+    pub(super) per_second: u32,
+    pub(super) burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            per_second: DEFAULT_RATE_LIMIT_PER_SEC,
+            burst: DEFAULT_RATE_LIMIT_BURST,
+        }
+    }
+}
+
+/// Token bucket for one connection. Complements the in-flight `Semaphore`,
+/// which bounds concurrency but not how fast quick calls arrive.
+pub(super) struct RequestRateLimiter {
+    tokens: f64,
+    capacity: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl RequestRateLimiter {
+    pub(super) fn new(config: RateLimitConfig, now: Instant) -> Self {
+        let capacity = f64::from(config.burst.max(1));
+        Self {
+            tokens: capacity,
+            capacity,
+            refill_per_sec: f64::from(config.per_second),
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token for a request about to be dispatched. Whether the call
+    /// later succeeds doesn't matter; every dispatch counts.
+    pub(super) fn try_acquire(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
@@ -0,0 +1,134 @@
+use super::*;
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct ResourceUsage {
+    pub(super) pid: u32,
+    pub(super) uptime_secs: u64,
+    pub(super) rss_bytes: Option<u64>,
+    pub(super) cpu_percent: Option<f64>,
+    pub(super) thread_count: Option<u64>,
+    pub(super) open_fds: Option<u64>,
+}
+
+/// Process CPU time at the previous sample, so `cpuPercent` covers the
+/// interval since the last `resource_usage` call rather than the daemon's
+/// whole life.
+pub(super) struct CpuTracker {
+    last_at: Instant,
+    last_cpu_seconds: Option<f64>,
+}
+
+impl CpuTracker {
+    pub(super) fn new(now: Instant) -> Self {
+        Self::starting_at(now, process_cpu_seconds())
+    }
+
+    pub(super) fn starting_at(at: Instant, cpu_seconds: Option<f64>) -> Self {
+        Self {
+            last_at: at,
+            last_cpu_seconds: cpu_seconds,
+        }
+    }
+
+    /// Percent of one core used since the previous sample (or since the
+    /// tracker was created), then starts the next interval at `now`.
+    pub(super) fn sample(&mut self, now: Instant, cpu_seconds: Option<f64>) -> Option<f64> {
+        let previous = std::mem::replace(self, Self::starting_at(now, cpu_seconds));
+        let used = cpu_seconds? - previous.last_cpu_seconds?;
+        let wall_seconds = now
+            .saturating_duration_since(previous.last_at)
+            .as_secs_f64();
+        if wall_seconds <= 0.0 {
+            return None;
+        }
+        Some((used.max(0.0) / wall_seconds * 1000.0).round() / 10.0)
+    }
+}
+
+/// Samples process-level numbers for the daemon itself. Fields the current
+/// platform cannot report are left as `None`.
+pub(super) fn current_resource_usage(
+    started_at: Instant,
+    cpu: &std::sync::Mutex<CpuTracker>,
+) -> ResourceUsage {
+    let cpu_percent = cpu
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .sample(Instant::now(), process_cpu_seconds());
+    ResourceUsage {
+        pid: std::process::id(),
+        uptime_secs: started_at.elapsed().as_secs(),
+        rss_bytes: process_rss_bytes(),
+        cpu_percent,
+        thread_count: process_thread_count(),
+        open_fds: process_open_fds(),
+    }
+}
+
+#[cfg(unix)]
+fn process_cpu_seconds() -> Option<f64> {
+    let mut usage = std::mem::MaybeUninit::<libc::rusage>::uninit();
+    // SAFETY: getrusage fills the struct when it returns 0.
+    let result = unsafe { libc::getrusage(libc::RUSAGE_SELF, usage.as_mut_ptr()) };
+    if result != 0 {
+        return None;
+    }
+    // SAFETY: initialized by the successful getrusage call above.
+    let usage = unsafe { usage.assume_init() };
+    let to_seconds = |time: libc::timeval| time.tv_sec as f64 + time.tv_usec as f64 / 1_000_000.0;
+    Some(to_seconds(usage.ru_utime) + to_seconds(usage.ru_stime))
+}
+
+#[cfg(not(unix))]
+fn process_cpu_seconds() -> Option<f64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn process_rss_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages = statm.split_whitespace().nth(1)?.parse::<u64>().ok()?;
+    // SAFETY: sysconf has no preconditions.
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return None;
+    }
+    Some(resident_pages * page_size as u64)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_rss_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn process_thread_count() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Threads:"))
+        .and_then(|value| value.trim().parse::<u64>().ok())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_thread_count() -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn process_open_fds() -> Option<u64> {
+    let fd_dir = if cfg!(target_os = "linux") {
+        "/proc/self/fd"
+    } else {
+        "/dev/fd"
+    };
+    let entries = std::fs::read_dir(fd_dir).ok()?;
+    // The directory handle used for the listing shows up as one entry.
+    Some((entries.count() as u64).saturating_sub(1))
+}
+
+#[cfg(not(unix))]
+fn process_open_fds() -> Option<u64> {
+    None
+}
@@ -1,11 +1,135 @@
 use super::*;
 
-pub(super) fn build_error_response(id: Option<u64>, message: &str) -> Option<String> {
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex as StdMutex;
+
+use futures_util::future::{AbortHandle, AbortRegistration, Abortable};
+
+/// The request id exactly as the client sent it. JSON-RPC allows numbers and
+/// strings; anything else, or no id, makes the message a notification.
+pub(super) fn parse_request_id(message: &Value) -> Option<Value> {
+    message
+        .get("id")
+        .filter(|id| id.is_number() || id.is_string())
+        .cloned()
+}
+
+pub(super) const METHOD_NOT_FOUND: i64 = -32601;
+pub(super) const INVALID_PARAMS: i64 = -32602;
+/// Server-defined codes live in -32000..=-32099; -32000 is the catch-all.
+pub(super) const APP_ERROR: i64 = -32000;
+pub(super) const UNAUTHORIZED: i64 = -32001;
+pub(super) const INVALID_TOKEN: i64 = -32002;
+pub(super) const RATE_LIMITED: i64 = -32003;
+pub(super) const WORKSPACE_NOT_FOUND: i64 = -32004;
+pub(super) const TOO_MANY_AUTH_ATTEMPTS: i64 = -32005;
+pub(super) const AUTH_TIMEOUT: i64 = -32006;
+pub(super) const SHUTTING_DOWN: i64 = -32007;
+pub(super) const INTERNAL_ERROR: i64 = -32603;
+/// Same code as LSP's RequestCancelled.
+pub(super) const REQUEST_CANCELLED: i64 = -32800;
+
+/// A failed request, sent as a JSON-RPC 2.0 error object. `message` keeps the
+/// wording clients matched on before codes existed.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum RpcError {
+    MethodNotFound(String),
+    /// `param` names the offending parameter when it is known.
+    InvalidParams {
+        message: String,
+        param: Option<String>,
+    },
+    Unauthorized,
+    InvalidToken,
+    RateLimited,
+    /// Sent just before the connection is closed.
+    TooManyAuthAttempts,
+    /// Sent with a null id just before an unauthenticated connection that
+    /// outstayed `--auth-timeout` is closed.
+    AuthTimeout,
+    WorkspaceNotFound(String),
+    Cancelled,
+    /// The request arrived, or was still queued, once shutdown had begun.
+    ShuttingDown,
+    /// The daemon itself failed, e.g. a result that would not serialize.
+    Internal(String),
+    /// A handler failed; handlers report plain strings.
+    App(String),
+}
+
+impl RpcError {
+    /// A required parameter that is absent, or any parameter of the wrong type.
+    pub(super) fn invalid_param(key: &str) -> Self {
+        Self::InvalidParams {
+            message: format!("missing or invalid `{key}`"),
+            param: Some(key.to_string()),
+        }
+    }
+
+    pub(super) fn code(&self) -> i64 {
+        match self {
+            Self::MethodNotFound(_) => METHOD_NOT_FOUND,
+            Self::InvalidParams { .. } => INVALID_PARAMS,
+            Self::Unauthorized => UNAUTHORIZED,
+            Self::InvalidToken => INVALID_TOKEN,
+            Self::RateLimited => RATE_LIMITED,
+            Self::TooManyAuthAttempts => TOO_MANY_AUTH_ATTEMPTS,
+            Self::AuthTimeout => AUTH_TIMEOUT,
+            Self::WorkspaceNotFound(_) => WORKSPACE_NOT_FOUND,
+            Self::Cancelled => REQUEST_CANCELLED,
+            Self::ShuttingDown => SHUTTING_DOWN,
+            Self::Internal(_) => INTERNAL_ERROR,
+            Self::App(_) => APP_ERROR,
+        }
+    }
+
+    pub(super) fn message(&self) -> &str {
+        match self {
+            Self::MethodNotFound(message)
+            | Self::InvalidParams { message, .. }
+            | Self::WorkspaceNotFound(message)
+            | Self::Internal(message)
+            | Self::App(message) => message,
+            Self::Unauthorized => "unauthorized",
+            Self::InvalidToken => "invalid token",
+            Self::RateLimited => "rate limited",
+            Self::TooManyAuthAttempts => "too many failed auth attempts",
+            Self::AuthTimeout => "auth timed out",
+            Self::Cancelled => "request cancelled",
+            Self::ShuttingDown => "daemon is shutting down",
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let mut error = json!({ "code": self.code(), "message": self.message() });
+        if let Self::InvalidParams {
+            param: Some(param), ..
+        } = self
+        {
+            error["data"] = json!({ "param": param });
+        }
+        error
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self::App(message)
+    }
+}
+
+/// Serializes a handler's result; failing here is the daemon's fault, not the
+/// caller's.
+fn to_result<T: Serialize>(value: T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::Internal(err.to_string()))
+}
+
+pub(super) fn build_error_response(id: Option<Value>, error: &RpcError) -> Option<String> {
     let id = id?;
     Some(
         serde_json::to_string(&json!({
             "id": id,
-            "error": { "message": message }
+            "error": error.to_value()
         }))
         .unwrap_or_else(|_| {
             "{\"id\":0,\"error\":{\"message\":\"serialization failed\"}}".to_string()
@@ -13,7 +137,7 @@ pub(super) fn build_error_response(id: Option<u64>, message: &str) -> Option<Str
     )
 }
 
-pub(super) fn build_result_response(id: Option<u64>, result: Value) -> Option<String> {
+pub(super) fn build_result_response(id: Option<Value>, result: Value) -> Option<String> {
     let id = id?;
     Some(
         serde_json::to_string(&json!({ "id": id, "result": result })).unwrap_or_else(|_| {
@@ -36,6 +160,22 @@ fn build_event_notification(event: DaemonEvent) -> Option<String> {
             "method": "terminal-exit",
             "params": payload,
         }),
+        DaemonEvent::SessionExited(payload) => json!({
+            "method": "session-exited",
+            "params": payload,
+        }),
+        DaemonEvent::GitStatusChanged(payload) => json!({
+            "method": "git-status-changed",
+            "params": payload,
+        }),
+        DaemonEvent::ThreadRenamed(payload) => json!({
+            "method": "thread-renamed",
+            "params": payload,
+        }),
+        DaemonEvent::WorkspaceFileTail(payload) => json!({
+            "method": "workspace-file-tail",
+            "params": payload,
+        }),
     };
     serde_json::to_string(&payload).ok()
 }
@@ -51,64 +191,80 @@ pub(super) fn parse_auth_token(params: &Value) -> Option<String> {
     }
 }
 
-fn parse_string(value: &Value, key: &str) -> Result<String, String> {
-    match value {
-        Value::Object(map) => map
-            .get(key)
-            .and_then(|value| value.as_str())
-            .map(|value| value.to_string())
-            .ok_or_else(|| format!("missing or invalid `{key}`")),
-        _ => Err(format!("missing `{key}`")),
-    }
+/// Whether the auth params advertise support for gzip-framed responses.
+pub(super) fn parse_accepts_gzip(params: &Value) -> bool {
+    params
+        .get("acceptsGzip")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false)
 }
 
-fn parse_optional_string(value: &Value, key: &str) -> Option<String> {
-    match value {
-        Value::Object(map) => map
-            .get(key)
-            .and_then(|value| value.as_str())
-            .map(|v| v.to_string()),
-        _ => None,
+/// `Ok(None)` when `key` is absent or null. A value of the wrong type is an
+/// error instead of being treated as absent.
+fn parse_optional<T>(
+    value: &Value,
+    key: &str,
+    convert: impl FnOnce(&Value) -> Option<T>,
+) -> Result<Option<T>, RpcError> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(item) => convert(item)
+            .map(Some)
+            .ok_or_else(|| RpcError::invalid_param(key)),
     }
 }
 
-fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
-    match value {
-        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()).and_then(|v| {
-            if v > u32::MAX as u64 {
-                None
-            } else {
-                Some(v as u32)
-            }
-        }),
-        _ => None,
-    }
+fn parse_string(value: &Value, key: &str) -> Result<String, RpcError> {
+    parse_optional_string(value, key)?.ok_or_else(|| RpcError::invalid_param(key))
 }
 
-fn parse_optional_bool(value: &Value, key: &str) -> Option<bool> {
-    match value {
-        Value::Object(map) => map.get(key).and_then(|value| value.as_bool()),
-        _ => None,
-    }
+fn parse_optional_string(value: &Value, key: &str) -> Result<Option<String>, RpcError> {
+    parse_optional(value, key, |item| item.as_str().map(str::to_string))
 }
 
-fn parse_optional_string_array(value: &Value, key: &str) -> Option<Vec<String>> {
-    match value {
-        Value::Object(map) => map
-            .get(key)
-            .and_then(|value| value.as_array())
-            .map(|items| {
-                items
-                    .iter()
-                    .filter_map(|item| item.as_str().map(|value| value.to_string()))
-                    .collect::<Vec<_>>()
-            }),
+/// Accepts either a string or a numeric id, normalised to a string key.
+/// A request id as a plain string: `7` and `"7"` name the same request.
+fn request_id_string(id: &Value) -> Option<String> {
+    match id {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
         _ => None,
     }
 }
 
-fn parse_string_array(value: &Value, key: &str) -> Result<Vec<String>, String> {
-    parse_optional_string_array(value, key).ok_or_else(|| format!("missing `{key}`"))
+fn parse_optional_request_id(value: &Value, key: &str) -> Result<Option<String>, RpcError> {
+    parse_optional(value, key, request_id_string)
+}
+
+fn parse_optional_u32(value: &Value, key: &str) -> Result<Option<u32>, RpcError> {
+    parse_optional(value, key, |item| {
+        item.as_u64().and_then(|value| u32::try_from(value).ok())
+    })
+}
+
+fn parse_optional_u64(value: &Value, key: &str) -> Result<Option<u64>, RpcError> {
+    parse_optional(value, key, Value::as_u64)
+}
+
+fn parse_optional_bool(value: &Value, key: &str) -> Result<Option<bool>, RpcError> {
+    parse_optional(value, key, Value::as_bool)
+}
+
+fn parse_bool(value: &Value, key: &str) -> Result<bool, RpcError> {
+    parse_optional_bool(value, key)?.ok_or_else(|| RpcError::invalid_param(key))
+}
+
+fn parse_optional_string_array(value: &Value, key: &str) -> Result<Option<Vec<String>>, RpcError> {
+    parse_optional(value, key, |item| {
+        item.as_array()?
+            .iter()
+            .map(|item| item.as_str().map(str::to_string))
+            .collect()
+    })
+}
+
+fn parse_string_array(value: &Value, key: &str) -> Result<Vec<String>, RpcError> {
+    parse_optional_string_array(value, key)?.ok_or_else(|| RpcError::invalid_param(key))
 }
 
 fn parse_optional_value(value: &Value, key: &str) -> Option<Value> {
@@ -118,6 +274,37 @@ fn parse_optional_value(value: &Value, key: &str) -> Option<Value> {
     }
 }
 
+/// Parses a workspace id and checks it is known, so a stale id fails with
+/// `WorkspaceNotFound` before the handler runs.
+async fn parse_workspace_id(
+    state: &DaemonState,
+    value: &Value,
+    key: &str,
+) -> Result<String, RpcError> {
+    let id = parse_string(value, key)?;
+    if !state.workspaces.lock().await.contains_key(&id) {
+        let message = if key == "parentId" {
+            "parent workspace not found"
+        } else {
+            "workspace not found"
+        };
+        return Err(RpcError::WorkspaceNotFound(message.to_string()));
+    }
+    Ok(id)
+}
+
+/// Deserializes the whole params object, or `key` within it, reporting a
+/// mismatch as invalid params.
+fn parse_params<T: serde::de::DeserializeOwned>(
+    value: Value,
+    key: Option<&str>,
+) -> Result<T, RpcError> {
+    serde_json::from_value(value).map_err(|err| RpcError::InvalidParams {
+        message: err.to_string(),
+        param: key.map(str::to_string),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct FileReadRequest {
@@ -133,609 +320,1300 @@ struct FileWriteRequest {
     kind: file_policy::FileKind,
     workspace_id: Option<String>,
     content: String,
+    expected_hash: Option<String>,
+}
+
+fn parse_file_read_request(params: &Value) -> Result<FileReadRequest, RpcError> {
+    parse_params(params.clone(), None)
+}
+
+fn parse_file_write_request(params: &Value) -> Result<FileWriteRequest, RpcError> {
+    parse_params(params.clone(), None)
+}
+
+/// Bumped when an existing method or the framing changes incompatibly;
+/// additions show up in `server_info`'s method list instead.
+pub(super) const PROTOCOL_VERSION: u32 = 1;
+
+/// Methods the connection answers itself, ahead of `DISPATCHED_METHODS`.
+const CONNECTION_METHODS: &[&str] = &["auth", "cancel"];
+
+/// Expands one method table into both `DISPATCHED_METHODS` and the function
+/// that matches on it, so the advertised list can't drift from the dispatch.
+macro_rules! rpc_dispatch_table {
+    (
+        async fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty {
+            match $method:ident {
+                $($method_name:literal => $handler:expr,)*
+                _ => $fallback:expr $(,)?
+            }
+        }
+    ) => {
+        /// Every method `dispatch_rpc_request` handles, in table order.
+        pub(super) const DISPATCHED_METHODS: &[&str] = &[$($method_name),*];
+
+        async fn $name($($arg: $ty),*) -> $ret {
+            match $method {
+                $($method_name => $handler,)*
+                _ => $fallback,
+            }
+        }
+    };
+}
+
+/// Every method a client can call, `auth` and `cancel` included.
+pub(super) fn rpc_methods() -> impl Iterator<Item = &'static str> {
+    CONNECTION_METHODS.iter().chain(DISPATCHED_METHODS).copied()
+}
+
+/// `websocket` is the daemon's mode; `transport` is the asking connection.
+pub(super) fn server_info(state: &DaemonState, transport: Transport) -> ServerInfo {
+    ServerInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        protocol_version: PROTOCOL_VERSION,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        websocket: state.daemon_mode == "orbit",
+        transport: transport.as_str().to_string(),
+        methods: rpc_methods().map(str::to_string).collect(),
+    }
 }
 
-fn parse_file_read_request(params: &Value) -> Result<FileReadRequest, String> {
-    serde_json::from_value(params.clone()).map_err(|err| err.to_string())
+/// How a connection reaches the daemon.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) enum Transport {
+    #[default]
+    Tcp,
+    Tls,
+    WebSocket,
 }
 
-fn parse_file_write_request(params: &Value) -> Result<FileWriteRequest, String> {
-    serde_json::from_value(params.clone()).map_err(|err| err.to_string())
+impl Transport {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Tls => "tls",
+            Self::WebSocket => "websocket",
+        }
+    }
+}
+
+/// Where a request came from, for state scoped to its caller: file tails end
+/// when the connection does, and grep walks are cancelled per connection.
+#[derive(Debug, Clone, Default)]
+pub(super) struct RequestOrigin {
+    pub(super) connection: u64,
+    /// The JSON-RPC id; `None` for notifications.
+    pub(super) id: Option<Value>,
+    pub(super) transport: Transport,
 }
 
 pub(super) async fn handle_rpc_request(
     state: &DaemonState,
+    origin: &RequestOrigin,
     method: &str,
     params: Value,
     client_version: String,
-) -> Result<Value, String> {
-    match method {
-        "ping" => Ok(json!({ "ok": true })),
-        "daemon_info" => Ok(state.daemon_info()),
-        "daemon_shutdown" => {
-            tokio::spawn(async {
-                sleep(Duration::from_millis(100)).await;
-                std::process::exit(0);
-            });
-            Ok(json!({ "ok": true }))
-        }
-        "list_workspaces" => {
-            let workspaces = state.list_workspaces().await;
-            serde_json::to_value(workspaces).map_err(|err| err.to_string())
-        }
-        "is_workspace_path_dir" => {
-            let path = parse_string(&params, "path")?;
-            let is_dir = state.is_workspace_path_dir(path).await;
-            serde_json::to_value(is_dir).map_err(|err| err.to_string())
-        }
-        "add_workspace" => {
-            let path = parse_string(&params, "path")?;
-            let codex_bin = parse_optional_string(&params, "codex_bin");
-            let workspace = state.add_workspace(path, codex_bin, client_version).await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "add_worktree" => {
-            let parent_id = parse_string(&params, "parentId")?;
-            let branch = parse_string(&params, "branch")?;
-            let name = parse_optional_string(&params, "name");
-            let copy_agents_md = parse_optional_bool(&params, "copyAgentsMd").unwrap_or(true);
-            let workspace = state
-                .add_worktree(parent_id, branch, name, copy_agents_md, client_version)
-                .await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "worktree_setup_status" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let status = state.worktree_setup_status(workspace_id).await?;
-            serde_json::to_value(status).map_err(|err| err.to_string())
-        }
-        "worktree_setup_mark_ran" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.worktree_setup_mark_ran(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "connect_workspace" => {
-            let id = parse_string(&params, "id")?;
-            state.connect_workspace(id, client_version).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "remove_workspace" => {
-            let id = parse_string(&params, "id")?;
-            state.remove_workspace(id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "remove_worktree" => {
-            let id = parse_string(&params, "id")?;
-            state.remove_worktree(id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "rename_worktree" => {
-            let id = parse_string(&params, "id")?;
-            let branch = parse_string(&params, "branch")?;
-            let workspace = state.rename_worktree(id, branch, client_version).await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "rename_worktree_upstream" => {
-            let id = parse_string(&params, "id")?;
-            let old_branch = parse_string(&params, "oldBranch")?;
-            let new_branch = parse_string(&params, "newBranch")?;
-            state
-                .rename_worktree_upstream(id, old_branch, new_branch)
-                .await?;
-            Ok(json!({ "ok": true }))
-        }
-        "update_workspace_settings" => {
-            let id = parse_string(&params, "id")?;
-            let settings_value = match params {
-                Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
-                _ => Value::Null,
-            };
-            let settings: WorkspaceSettings =
-                serde_json::from_value(settings_value).map_err(|err| err.to_string())?;
-            let workspace = state
-                .update_workspace_settings(id, settings, client_version)
-                .await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "update_workspace_codex_bin" => {
-            let id = parse_string(&params, "id")?;
-            let codex_bin = parse_optional_string(&params, "codex_bin");
-            let workspace = state.update_workspace_codex_bin(id, codex_bin).await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "list_workspace_files" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let files = state.list_workspace_files(workspace_id).await?;
-            serde_json::to_value(files).map_err(|err| err.to_string())
-        }
-        "read_workspace_file" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            let response = state.read_workspace_file(workspace_id, path).await?;
-            serde_json::to_value(response).map_err(|err| err.to_string())
-        }
-        "file_read" => {
-            let request = parse_file_read_request(&params)?;
-            let response = state
-                .file_read(request.scope, request.kind, request.workspace_id)
-                .await?;
-            serde_json::to_value(response).map_err(|err| err.to_string())
-        }
-        "file_write" => {
-            let request = parse_file_write_request(&params)?;
-            state
-                .file_write(
-                    request.scope,
-                    request.kind,
-                    request.workspace_id,
-                    request.content,
-                )
-                .await?;
-            serde_json::to_value(json!({ "ok": true })).map_err(|err| err.to_string())
-        }
-        "get_app_settings" => {
-            let settings = state.get_app_settings().await;
-            serde_json::to_value(settings).map_err(|err| err.to_string())
-        }
-        "update_app_settings" => {
-            let settings_value = match params {
-                Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
-                _ => Value::Null,
-            };
-            let settings: AppSettings =
-                serde_json::from_value(settings_value).map_err(|err| err.to_string())?;
-            let updated = state.update_app_settings(settings).await?;
-            serde_json::to_value(updated).map_err(|err| err.to_string())
-        }
-        "orbit_connect_test" => {
-            let result = state.orbit_connect_test().await?;
-            serde_json::to_value(result).map_err(|err| err.to_string())
-        }
-        "orbit_sign_in_start" => {
-            let result = state.orbit_sign_in_start().await?;
-            serde_json::to_value(result).map_err(|err| err.to_string())
-        }
-        "orbit_sign_in_poll" => {
-            let device_code = parse_string(&params, "deviceCode")?;
-            let result = state.orbit_sign_in_poll(device_code).await?;
-            serde_json::to_value(result).map_err(|err| err.to_string())
-        }
-        "orbit_sign_out" => {
-            let result = state.orbit_sign_out().await?;
-            serde_json::to_value(result).map_err(|err| err.to_string())
-        }
-        "get_codex_config_path" => {
-            let path = settings_core::get_codex_config_path_core()?;
-            Ok(Value::String(path))
-        }
-        "get_config_model" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.get_config_model(workspace_id).await
-        }
-        "start_thread" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.start_thread(workspace_id).await
-        }
-        "resume_thread" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            state.resume_thread(workspace_id, thread_id).await
-        }
-        "fork_thread" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            state.fork_thread(workspace_id, thread_id).await
-        }
-        "list_threads" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let cursor = parse_optional_string(&params, "cursor");
-            let limit = parse_optional_u32(&params, "limit");
-            let sort_key = parse_optional_string(&params, "sortKey");
-            state
-                .list_threads(workspace_id, cursor, limit, sort_key)
-                .await
-        }
-        "list_mcp_server_status" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let cursor = parse_optional_string(&params, "cursor");
-            let limit = parse_optional_u32(&params, "limit");
-            state
-                .list_mcp_server_status(workspace_id, cursor, limit)
-                .await
-        }
-        "archive_thread" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            state.archive_thread(workspace_id, thread_id).await
-        }
-        "compact_thread" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            state.compact_thread(workspace_id, thread_id).await
-        }
-        "set_thread_name" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            let name = parse_string(&params, "name")?;
-            state.set_thread_name(workspace_id, thread_id, name).await
-        }
-        "send_user_message" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            let text = parse_string(&params, "text")?;
-            let model = parse_optional_string(&params, "model");
-            let effort = parse_optional_string(&params, "effort");
-            let access_mode = parse_optional_string(&params, "accessMode");
-            let images = parse_optional_string_array(&params, "images");
-            let collaboration_mode = parse_optional_value(&params, "collaborationMode");
-            state
-                .send_user_message(
-                    workspace_id,
-                    thread_id,
-                    text,
-                    model,
-                    effort,
-                    access_mode,
-                    images,
-                    collaboration_mode,
-                )
-                .await
-        }
-        "turn_interrupt" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            let turn_id = parse_string(&params, "turnId")?;
-            state.turn_interrupt(workspace_id, thread_id, turn_id).await
-        }
-        "turn_steer" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            let turn_id = parse_string(&params, "turnId")?;
-            let text = parse_string(&params, "text")?;
-            let images = parse_optional_string_array(&params, "images");
-            state
-                .turn_steer(workspace_id, thread_id, turn_id, text, images)
-                .await
-        }
-        "start_review" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let thread_id = parse_string(&params, "threadId")?;
-            let target = params
-                .as_object()
-                .and_then(|map| map.get("target"))
-                .cloned()
-                .ok_or("missing `target`")?;
-            let delivery = parse_optional_string(&params, "delivery");
-            state
-                .start_review(workspace_id, thread_id, target, delivery)
-                .await
-        }
-        "model_list" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.model_list(workspace_id).await
-        }
-        "collaboration_mode_list" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.collaboration_mode_list(workspace_id).await
-        }
-        "account_rate_limits" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.account_rate_limits(workspace_id).await
-        }
-        "account_read" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.account_read(workspace_id).await
-        }
-        "codex_login" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.codex_login(workspace_id).await
-        }
-        "codex_login_cancel" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.codex_login_cancel(workspace_id).await
-        }
-        "skills_list" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.skills_list(workspace_id).await
-        }
-        "apps_list" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let cursor = parse_optional_string(&params, "cursor");
-            let limit = parse_optional_u32(&params, "limit");
-            state.apps_list(workspace_id, cursor, limit).await
-        }
-        "respond_to_server_request" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let map = params.as_object().ok_or("missing requestId")?;
-            let request_id = map
-                .get("requestId")
-                .cloned()
-                .filter(|value| value.is_number() || value.is_string())
-                .ok_or("missing requestId")?;
-            let result = map.get("result").cloned().ok_or("missing `result`")?;
-            state
-                .respond_to_server_request(workspace_id, request_id, result)
-                .await
-        }
-        "remember_approval_rule" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let command = parse_string_array(&params, "command")?;
-            state.remember_approval_rule(workspace_id, command).await
-        }
-        "add_clone" => {
-            let source_workspace_id = parse_string(&params, "sourceWorkspaceId")?;
-            let copies_folder = parse_string(&params, "copiesFolder")?;
-            let copy_name = parse_string(&params, "copyName")?;
-            let workspace = state
-                .add_clone(
-                    source_workspace_id,
-                    copies_folder,
-                    copy_name,
-                    client_version,
-                )
-                .await?;
-            serde_json::to_value(workspace).map_err(|err| err.to_string())
-        }
-        "apply_worktree_changes" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.apply_worktree_changes(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "open_workspace_in" => {
-            let path = parse_string(&params, "path")?;
-            let app = parse_optional_string(&params, "app");
-            let command = parse_optional_string(&params, "command");
-            let args = parse_optional_string_array(&params, "args").unwrap_or_default();
-            state.open_workspace_in(path, app, args, command).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "get_open_app_icon" => {
-            let app_name = parse_string(&params, "appName")?;
-            let icon = state.get_open_app_icon(app_name).await?;
-            serde_json::to_value(icon).map_err(|err| err.to_string())
-        }
-        "get_git_status" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.get_git_status(workspace_id).await
-        }
-        "list_git_roots" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let depth = parse_optional_u32(&params, "depth").map(|value| value as usize);
-            let roots = state.list_git_roots(workspace_id, depth).await?;
-            serde_json::to_value(roots).map_err(|err| err.to_string())
-        }
-        "get_git_diffs" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let diffs = state.get_git_diffs(workspace_id).await?;
-            serde_json::to_value(diffs).map_err(|err| err.to_string())
-        }
-        "get_git_log" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let limit = parse_optional_u32(&params, "limit").map(|value| value as usize);
-            let log = state.get_git_log(workspace_id, limit).await?;
-            serde_json::to_value(log).map_err(|err| err.to_string())
-        }
-        "get_git_commit_diff" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let sha = parse_string(&params, "sha")?;
-            let diff = state.get_git_commit_diff(workspace_id, sha).await?;
-            serde_json::to_value(diff).map_err(|err| err.to_string())
-        }
-        "get_git_remote" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let remote = state.get_git_remote(workspace_id).await?;
-            serde_json::to_value(remote).map_err(|err| err.to_string())
-        }
-        "stage_git_file" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            state.stage_git_file(workspace_id, path).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "stage_git_all" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.stage_git_all(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "unstage_git_file" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            state.unstage_git_file(workspace_id, path).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "revert_git_file" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            state.revert_git_file(workspace_id, path).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "revert_git_all" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.revert_git_all(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "commit_git" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let message = parse_string(&params, "message")?;
-            state.commit_git(workspace_id, message).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "push_git" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.push_git(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "pull_git" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.pull_git(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "fetch_git" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.fetch_git(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "sync_git" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.sync_git(workspace_id).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "get_github_issues" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let issues = state.get_github_issues(workspace_id).await?;
-            serde_json::to_value(issues).map_err(|err| err.to_string())
-        }
-        "get_github_pull_requests" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let prs = state.get_github_pull_requests(workspace_id).await?;
-            serde_json::to_value(prs).map_err(|err| err.to_string())
-        }
-        "get_github_pull_request_diff" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let pr_number =
-                parse_optional_u64(&params, "prNumber").ok_or("missing or invalid `prNumber`")?;
-            let diff = state
-                .get_github_pull_request_diff(workspace_id, pr_number)
-                .await?;
-            serde_json::to_value(diff).map_err(|err| err.to_string())
-        }
-        "get_github_pull_request_comments" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let pr_number =
-                parse_optional_u64(&params, "prNumber").ok_or("missing or invalid `prNumber`")?;
-            let comments = state
-                .get_github_pull_request_comments(workspace_id, pr_number)
-                .await?;
-            serde_json::to_value(comments).map_err(|err| err.to_string())
-        }
-        "list_git_branches" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            state.list_git_branches(workspace_id).await
-        }
-        "checkout_git_branch" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let name = parse_string(&params, "name")?;
-            state.checkout_git_branch(workspace_id, name).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "create_git_branch" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let name = parse_string(&params, "name")?;
-            state.create_git_branch(workspace_id, name).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "prompts_list" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let prompts = state.prompts_list(workspace_id).await?;
-            serde_json::to_value(prompts).map_err(|err| err.to_string())
-        }
-        "prompts_workspace_dir" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let dir = state.prompts_workspace_dir(workspace_id).await?;
-            Ok(Value::String(dir))
-        }
-        "prompts_global_dir" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let dir = state.prompts_global_dir(workspace_id).await?;
-            Ok(Value::String(dir))
-        }
-        "prompts_create" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let scope = parse_string(&params, "scope")?;
-            let name = parse_string(&params, "name")?;
-            let description = parse_optional_string(&params, "description");
-            let argument_hint = parse_optional_string(&params, "argumentHint");
-            let content = parse_string(&params, "content")?;
-            let prompt = state
-                .prompts_create(
-                    workspace_id,
-                    scope,
-                    name,
-                    description,
-                    argument_hint,
-                    content,
-                )
-                .await?;
-            serde_json::to_value(prompt).map_err(|err| err.to_string())
-        }
-        "prompts_update" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            let name = parse_string(&params, "name")?;
-            let description = parse_optional_string(&params, "description");
-            let argument_hint = parse_optional_string(&params, "argumentHint");
-            let content = parse_string(&params, "content")?;
-            let prompt = state
-                .prompts_update(
-                    workspace_id,
-                    path,
-                    name,
-                    description,
-                    argument_hint,
-                    content,
-                )
-                .await?;
-            serde_json::to_value(prompt).map_err(|err| err.to_string())
-        }
-        "prompts_delete" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            state.prompts_delete(workspace_id, path).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "prompts_move" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let path = parse_string(&params, "path")?;
-            let scope = parse_string(&params, "scope")?;
-            let prompt = state.prompts_move(workspace_id, path, scope).await?;
-            serde_json::to_value(prompt).map_err(|err| err.to_string())
-        }
-        "codex_doctor" => {
-            let codex_bin = parse_optional_string(&params, "codexBin");
-            let codex_args = parse_optional_string(&params, "codexArgs");
-            state.codex_doctor(codex_bin, codex_args).await
-        }
-        "generate_commit_message" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let message = state.generate_commit_message(workspace_id).await?;
-            Ok(Value::String(message))
-        }
-        "generate_run_metadata" => {
-            let workspace_id = parse_string(&params, "workspaceId")?;
-            let prompt = parse_string(&params, "prompt")?;
-            state.generate_run_metadata(workspace_id, prompt).await
-        }
-        "local_usage_snapshot" => {
-            let days = parse_optional_u32(&params, "days");
-            let workspace_path = parse_optional_string(&params, "workspacePath");
-            let snapshot = state.local_usage_snapshot(days, workspace_path).await?;
-            serde_json::to_value(snapshot).map_err(|err| err.to_string())
-        }
-        "menu_set_accelerators" => {
-            let updates: Vec<Value> = match &params {
-                Value::Object(map) => map
-                    .get("updates")
+) -> Result<Value, RpcError> {
+    dispatch_rpc_request(state, origin, method, params, client_version).await
+}
+
+rpc_dispatch_table! {
+    async fn dispatch_rpc_request(
+        state: &DaemonState,
+        origin: &RequestOrigin,
+        method: &str,
+        params: Value,
+        client_version: String,
+    ) -> Result<Value, RpcError> {
+        match method {
+            "ping" => Ok(json!({ "ok": true })),
+            "server_info" => to_result(server_info(state, origin.transport)),
+            "daemon_info" => Ok(state.daemon_info()),
+            "version" => to_result(utils::build_info()),
+            "orbit_status" => to_result(state.orbit_status()),
+            "resource_usage" => to_result(state.resource_usage()),
+            "method_latency" => {
+                let reset = parse_optional_bool(&params, "reset")?.unwrap_or(false);
+                to_result(state.method_latency(reset))
+            },
+            "daemon_shutdown" => {
+                schedule_daemon_shutdown(state, "requested by client".to_string());
+                Ok(json!({ "ok": true }))
+            },
+            "list_workspaces" => {
+                let workspaces = state.list_workspaces().await;
+                // Same etag as `workspaces_etag`; a match skips the listing.
+                if let Some(if_none_match) = parse_optional_string(&params, "ifNoneMatch")? {
+                    let etag = workspaces_core::workspaces_etag(&workspaces);
+                    if etag == if_none_match {
+                        return Ok(json!({ "notModified": true, "etag": etag }));
+                    }
+                }
+                to_result(workspaces)
+            },
+            "workspaces_etag" => Ok(Value::String(state.workspaces_etag().await)),
+            "is_workspace_path_dir" => {
+                let path = parse_string(&params, "path")?;
+                let is_dir = state.is_workspace_path_dir(path).await;
+                to_result(is_dir)
+            },
+            "add_workspace" => {
+                let path = parse_string(&params, "path")?;
+                let codex_bin = parse_optional_string(&params, "codex_bin")?;
+                let workspace = state.add_workspace(path, codex_bin, client_version).await?;
+                to_result(workspace)
+            },
+            "preview_worktree_path" => {
+                let parent_id = parse_workspace_id(state, &params, "parentId").await?;
+                let branch = parse_string(&params, "branch")?;
+                let preview = state.preview_worktree_path(parent_id, branch).await?;
+                to_result(preview)
+            },
+            "add_worktree" => {
+                let parent_id = parse_workspace_id(state, &params, "parentId").await?;
+                let branch = parse_string(&params, "branch")?;
+                let name = parse_optional_string(&params, "name")?;
+                let base_ref = parse_optional_string(&params, "baseRef")?;
+                let copy_agents_md = parse_optional_bool(&params, "copyAgentsMd")?;
+                let workspace = state
+                    .add_worktree(
+                        parent_id,
+                        branch,
+                        name,
+                        base_ref,
+                        copy_agents_md,
+                        client_version,
+                    )
+                    .await?;
+                to_result(workspace)
+            },
+            "worktree_from_stash" => {
+                let parent_id = parse_workspace_id(state, &params, "parentId").await?;
+                // `stash` (index, ref or message) wins over the older `stashIndex`.
+                let stash = match parse_optional_string(&params, "stash")? {
+                    Some(stash) => stash,
+                    None => parse_optional_u32(&params, "stashIndex")?
+                        .unwrap_or(0)
+                        .to_string(),
+                };
+                let branch = parse_string(&params, "branch")?;
+                let name = parse_optional_string(&params, "name")?;
+                let copy_agents_md = parse_optional_bool(&params, "copyAgentsMd")?;
+                let result = state
+                    .worktree_from_stash(
+                        parent_id,
+                        stash,
+                        branch,
+                        name,
+                        copy_agents_md,
+                        client_version,
+                    )
+                    .await?;
+                to_result(result)
+            },
+            "fork_thread_to_worktree" => {
+                let parent_id = parse_workspace_id(state, &params, "parentId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let branch = parse_string(&params, "branch")?;
+                let name = parse_optional_string(&params, "name")?;
+                let copy_agents_md = parse_optional_bool(&params, "copyAgentsMd")?;
+                let result = state
+                    .fork_thread_to_worktree(
+                        parent_id,
+                        thread_id,
+                        branch,
+                        name,
+                        copy_agents_md,
+                        client_version,
+                    )
+                    .await?;
+                to_result(result)
+            },
+            "worktree_setup_status" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let status = state.worktree_setup_status(workspace_id).await?;
+                to_result(status)
+            },
+            "worktree_setup_mark_ran" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.worktree_setup_mark_ran(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "connect_workspace" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                state.connect_workspace(id, client_version).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "list_recent_workspaces" => {
+                let recents = state.list_recent_workspaces().await;
+                to_result(recents)
+            },
+            "record_recent_workspace" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                state.record_recent_workspace(id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "connect_all_workspaces" => {
+                let results = state.connect_all_workspaces(client_version).await;
+                to_result(results)
+            },
+            "remove_workspace" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                state.remove_workspace(id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "remove_worktree" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                state.remove_worktree(id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "rename_worktree" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let branch = parse_string(&params, "branch")?;
+                let workspace = state.rename_worktree(id, branch, client_version).await?;
+                to_result(workspace)
+            },
+            "move_worktree" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let destination = parse_string(&params, "destination")?;
+                let workspace = state.move_worktree(id, destination, client_version).await?;
+                to_result(workspace)
+            },
+            "rename_worktree_upstream" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let old_branch = parse_string(&params, "oldBranch")?;
+                let new_branch = parse_string(&params, "newBranch")?;
+                state
+                    .rename_worktree_upstream(id, old_branch, new_branch)
+                    .await?;
+                Ok(json!({ "ok": true }))
+            },
+            "get_codex_tools" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let tools = state.get_codex_tools(workspace_id).await?;
+                to_result(tools)
+            },
+            "set_codex_tool_enabled" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let tool = parse_string(&params, "tool")?;
+                let enabled = parse_bool(&params, "enabled")?;
+                let tools = state
+                    .set_codex_tool_enabled(workspace_id, tool, enabled, client_version)
+                    .await?;
+                to_result(tools)
+            },
+            "update_workspace_settings" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let settings_value = match params {
+                    Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
+                    _ => Value::Null,
+                };
+                let settings: WorkspaceSettings = parse_params(settings_value, Some("settings"))?;
+                let workspace = state
+                    .update_workspace_settings(id, settings, client_version)
+                    .await?;
+                to_result(workspace)
+            },
+            "update_workspace_codex_bin" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let codex_bin = parse_optional_string(&params, "codex_bin")?;
+                let workspace = state.update_workspace_codex_bin(id, codex_bin).await?;
+                to_result(workspace)
+            },
+            "set_workspace_pinned" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let pinned = parse_bool(&params, "pinned")?;
+                let workspace = state.set_workspace_pinned(id, pinned).await?;
+                to_result(workspace)
+            },
+            "set_workspace_auto_connect" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let auto_connect = parse_bool(&params, "autoConnect")?;
+                let workspace = state.set_workspace_auto_connect(id, auto_connect).await?;
+                to_result(workspace)
+            },
+            "workspace_timeline" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let limit = parse_optional_u64(&params, "limit")?.map(|value| value as usize);
+                let timeline = state.workspace_timeline(workspace_id, limit).await?;
+                to_result(timeline)
+            },
+            "session_last_error" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let error = state.session_last_error(workspace_id).await?;
+                to_result(error)
+            },
+            "set_default_effort" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let effort = parse_optional_string(&params, "effort")?;
+                let model = parse_optional_string(&params, "model")?;
+                let workspace = state.set_default_effort(id, effort, model).await?;
+                to_result(workspace)
+            },
+            "set_default_collaboration_mode" => {
+                let id = parse_workspace_id(state, &params, "id").await?;
+                let mode = parse_optional_string(&params, "mode")?;
+                let workspace = state.set_default_collaboration_mode(id, mode).await?;
+                to_result(workspace)
+            },
+            "list_workspace_files" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let files = state.list_workspace_files(workspace_id).await?;
+                to_result(files)
+            },
+            "grep_workspace" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let options = search_core::WorkspaceGrepOptions {
+                    query: parse_string(&params, "query")?,
+                    is_regex: parse_optional_bool(&params, "isRegex")?.unwrap_or(false),
+                    case_sensitive: parse_optional_bool(&params, "caseSensitive")?.unwrap_or(false),
+                    globs: parse_optional_string_array(&params, "globs")?.unwrap_or_default(),
+                    max_results: parse_optional_u64(&params, "maxResults")?.map(|value| value as usize),
+                    skip_dirs: state.app_settings.lock().await.file_list_skip_dirs.clone(),
+                    // Cancellable by its own JSON-RPC id unless the client named
+                    // another one, as the app does when forwarding a local id.
+                    walk_key: parse_optional_request_id(&params, "requestId")?
+                        .or_else(|| origin.id.as_ref().and_then(request_id_string))
+                        .map(|request_id| (Some(origin.connection), request_id)),
+                };
+                let response = state.grep_workspace(workspace_id, options).await?;
+                to_result(response)
+            },
+            "cancel_workspace_walk" => {
+                let request_id = parse_optional_request_id(&params, "requestId")?
+                    .ok_or_else(|| RpcError::invalid_param("requestId"))?;
+                let cancelled = state.cancel_workspace_walk(origin.connection, &request_id);
+                Ok(json!({ "cancelled": cancelled }))
+            },
+            "read_workspace_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let response = state.read_workspace_file(workspace_id, path).await?;
+                to_result(response)
+            },
+            "rename_workspace_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let source = parse_string(&params, "source")?;
+                let destination = parse_string(&params, "destination")?;
+                let force = parse_optional_bool(&params, "force")?.unwrap_or(false);
+                let result = state
+                    .rename_workspace_file(workspace_id, source, destination, force)
+                    .await?;
+                to_result(result)
+            },
+            "create_workspace_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let content = parse_optional_string(&params, "content")?.unwrap_or_default();
+                let path = state
+                    .create_workspace_file(workspace_id, path, content)
+                    .await?;
+                Ok(Value::String(path))
+            },
+            "delete_workspace_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let recursive = parse_optional_bool(&params, "recursive")?.unwrap_or(false);
+                let result = state
+                    .delete_workspace_file(workspace_id, path, recursive)
+                    .await?;
+                to_result(result)
+            },
+            "tail_workspace_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let lines = parse_optional_u32(&params, "lines")?;
+                let tail = state
+                    .tail_workspace_file(origin.connection, workspace_id, path, lines)
+                    .await?;
+                to_result(tail)
+            },
+            "untail_workspace_file" => {
+                let tail_id = parse_string(&params, "tailId")?;
+                let stopped = state.untail_workspace_file(&tail_id);
+                Ok(json!({ "stopped": stopped }))
+            },
+            "file_read" => {
+                let request = parse_file_read_request(&params)?;
+                let response = state
+                    .file_read(request.scope, request.kind, request.workspace_id)
+                    .await?;
+                to_result(response)
+            },
+            "file_write" => {
+                let request = parse_file_write_request(&params)?;
+                let response = state
+                    .file_write(
+                        request.scope,
+                        request.kind,
+                        request.workspace_id,
+                        request.content,
+                        request.expected_hash,
+                    )
+                    .await?;
+                Ok(json!({ "ok": true, "hash": response.hash }))
+            },
+            "get_effective_agents_md" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let files = state.get_effective_agents_md(workspace_id).await?;
+                to_result(files)
+            },
+            "get_app_settings" => {
+                let settings = state.get_app_settings().await;
+                to_result(settings)
+            },
+            "update_app_settings" => {
+                let settings_value = match params {
+                    Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
+                    _ => Value::Null,
+                };
+                let settings: AppSettings = parse_params(settings_value, Some("settings"))?;
+                let updated = state.update_app_settings(settings).await?;
+                to_result(updated)
+            },
+            "orbit_connect_test" => {
+                let result = state.orbit_connect_test().await?;
+                to_result(result)
+            },
+            "orbit_sign_in_start" => {
+                let result = state.orbit_sign_in_start().await?;
+                to_result(result)
+            },
+            "orbit_sign_in_poll" => {
+                let device_code = parse_string(&params, "deviceCode")?;
+                let result = state.orbit_sign_in_poll(device_code).await?;
+                to_result(result)
+            },
+            "orbit_sign_out" => {
+                let result = state.orbit_sign_out().await?;
+                to_result(result)
+            },
+            "validate_app_settings" => {
+                let settings = parse_optional_value(&params, "settings").unwrap_or(Value::Null);
+                let validation = settings_core::validate_app_settings_core(settings)?;
+                to_result(validation)
+            },
+            "get_codex_config_path" => {
+                let path = settings_core::get_codex_config_path_core()?;
+                Ok(Value::String(path))
+            },
+            "get_workspace_codex_config_path" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let paths = state.get_workspace_codex_config_path(workspace_id).await?;
+                to_result(paths)
+            },
+            "get_config_model" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .get_config_model(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "estimate_message_cost" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let text = parse_string(&params, "text")?;
+                let model = parse_optional_string(&params, "model")?;
+                let estimate = state
+                    .estimate_message_cost(workspace_id, text, model)
+                    .await?;
+                to_result(estimate)
+            },
+            "start_thread" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .start_thread(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "resume_thread" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .resume_thread(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "fork_thread" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .fork_thread(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "list_threads" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let cursor = parse_optional_string(&params, "cursor")?;
+                let limit = parse_optional_u32(&params, "limit")?;
+                let sort_key = parse_optional_string(&params, "sortKey")?;
+                let status = parse_optional_string(&params, "status")?;
+                state
+                    .list_threads(workspace_id, cursor, limit, sort_key, status)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "refresh_threads" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let limit = parse_optional_u32(&params, "limit")?;
+                let sort_key = parse_optional_string(&params, "sortKey")?;
+                state
+                    .refresh_threads(workspace_id, limit, sort_key)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "list_all_threads" => {
+                let cursor = parse_optional_string(&params, "cursor")?;
+                let limit = parse_optional_u32(&params, "limit")?;
+                let connect = parse_optional_bool(&params, "connect")?.unwrap_or(false);
+                state
+                    .list_all_threads(cursor, limit, connect, client_version)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "list_mcp_server_status" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let cursor = parse_optional_string(&params, "cursor")?;
+                let limit = parse_optional_u32(&params, "limit")?;
+                state
+                    .list_mcp_server_status(workspace_id, cursor, limit)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "archive_thread" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .archive_thread(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "bulk_archive_threads" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let inactive_before = params
+                    .get("inactiveBefore")
+                    .and_then(Value::as_i64)
+                    .ok_or_else(|| RpcError::invalid_param("inactiveBefore"))?;
+                let dry_run = parse_optional_bool(&params, "dryRun")?.unwrap_or(false);
+                let result = state
+                    .bulk_archive_threads(workspace_id, inactive_before, dry_run)
+                    .await?;
+                to_result(result)
+            },
+            "compact_thread" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .compact_thread(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "get_thread_info" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let info = state.get_thread_info(workspace_id, thread_id).await?;
+                to_result(info)
+            },
+            "session_capabilities" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let capabilities = state.session_capabilities(workspace_id).await?;
+                to_result(capabilities)
+            },
+            "session_ping" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let ping = state.session_ping(workspace_id).await?;
+                to_result(ping)
+            },
+            "reset_thread_context" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .reset_thread_context(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "set_thread_name" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let name = parse_string(&params, "name")?;
+                state
+                    .set_thread_name(workspace_id, thread_id, name)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "send_user_message" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let text = parse_string(&params, "text")?;
+                let model = parse_optional_string(&params, "model")?;
+                let effort = parse_optional_string(&params, "effort")?;
+                let access_mode = parse_optional_string(&params, "accessMode")?;
+                let images = parse_optional_string_array(&params, "images")?;
+                let collaboration_mode = parse_optional_value(&params, "collaborationMode");
+                state
+                    .send_user_message(
+                        workspace_id,
+                        thread_id,
+                        text,
+                        model,
+                        effort,
+                        access_mode,
+                        images,
+                        collaboration_mode,
+                    )
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "queue_user_message" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let message = UserMessageInput {
+                    text: parse_string(&params, "text")?,
+                    model: parse_optional_string(&params, "model")?,
+                    effort: parse_optional_string(&params, "effort")?,
+                    access_mode: parse_optional_string(&params, "accessMode")?,
+                    images: parse_optional_string_array(&params, "images")?,
+                    collaboration_mode: parse_optional_value(&params, "collaborationMode"),
+                };
+                let queued = state
+                    .queue_user_message(workspace_id, thread_id, message)
+                    .await?;
+                to_result(queued)
+            },
+            "cancel_queued_message" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let message_id = parse_string(&params, "messageId")?;
+                state
+                    .cancel_queued_message(workspace_id, message_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "turn_interrupt" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let turn_id = parse_string(&params, "turnId")?;
+                state
+                    .turn_interrupt(workspace_id, thread_id, turn_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "turn_steer" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let turn_id = parse_string(&params, "turnId")?;
+                let text = parse_string(&params, "text")?;
+                let images = parse_optional_string_array(&params, "images")?;
+                state
+                    .turn_steer(workspace_id, thread_id, turn_id, text, images)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "start_review" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let target = params
+                    .as_object()
+                    .and_then(|map| map.get("target"))
                     .cloned()
-                    .map(serde_json::from_value)
-                    .transpose()
-                    .map_err(|err| err.to_string())?
-                    .unwrap_or_default(),
-                _ => Vec::new(),
-            };
-            state.menu_set_accelerators(updates).await?;
-            Ok(json!({ "ok": true }))
-        }
-        "is_macos_debug_build" => {
-            let is_debug = state.is_macos_debug_build().await;
-            Ok(Value::Bool(is_debug))
-        }
-        "send_notification_fallback" => {
-            let title = parse_string(&params, "title")?;
-            let body = parse_string(&params, "body")?;
-            state.send_notification_fallback(title, body).await?;
-            Ok(json!({ "ok": true }))
+                    .ok_or_else(|| RpcError::invalid_param("target"))?;
+                let delivery = parse_optional_string(&params, "delivery")?;
+                state
+                    .start_review(workspace_id, thread_id, target, delivery)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "recent_models" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                Ok(json!(state.recent_models(workspace_id).await))
+            },
+            "model_list" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.model_list(workspace_id).await.map_err(RpcError::from)
+            },
+            "collaboration_mode_list" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .collaboration_mode_list(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "set_thread_collaboration_mode" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                let collaboration_mode = parse_optional_value(&params, "collaborationMode");
+                state
+                    .set_thread_collaboration_mode(workspace_id, thread_id, collaboration_mode)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "reset_thread_turn_count" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let thread_id = parse_string(&params, "threadId")?;
+                state
+                    .reset_thread_turn_count(workspace_id, thread_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "account_rate_limits" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .account_rate_limits(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "account_read" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .account_read(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "codex_login_status" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let status = state.codex_login_status(workspace_id).await?;
+                to_result(status)
+            },
+            "codex_login" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .codex_login(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "codex_login_cancel" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .codex_login_cancel(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "skills_list" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .skills_list(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "apps_list" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let cursor = parse_optional_string(&params, "cursor")?;
+                let limit = parse_optional_u32(&params, "limit")?;
+                state
+                    .apps_list(workspace_id, cursor, limit)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "workspace_activity" => {
+                let activity = state.workspace_activity().await;
+                to_result(activity)
+            },
+            "list_pending_approvals" => {
+                let workspace_id = parse_optional_string(&params, "workspaceId")?;
+                let approvals = state.list_pending_approvals(workspace_id).await;
+                to_result(approvals)
+            },
+            "respond_to_server_request" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let map = params
+                    .as_object()
+                    .ok_or_else(|| RpcError::invalid_param("requestId"))?;
+                let request_id = map
+                    .get("requestId")
+                    .cloned()
+                    .filter(|value| value.is_number() || value.is_string())
+                    .ok_or_else(|| RpcError::invalid_param("requestId"))?;
+                let result = map
+                    .get("result")
+                    .cloned()
+                    .ok_or_else(|| RpcError::invalid_param("result"))?;
+                state
+                    .respond_to_server_request(workspace_id, request_id, result)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "remember_approval_rule" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let command = parse_string_array(&params, "command")?;
+                state
+                    .remember_approval_rule(workspace_id, command)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "add_clone" => {
+                let source_workspace_id = parse_string(&params, "sourceWorkspaceId")?;
+                let copies_folder = parse_string(&params, "copiesFolder")?;
+                let copy_name = parse_string(&params, "copyName")?;
+                let workspace = state
+                    .add_clone(
+                        source_workspace_id,
+                        copies_folder,
+                        copy_name,
+                        client_version,
+                    )
+                    .await?;
+                to_result(workspace)
+            },
+            "apply_worktree_changes" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.apply_worktree_changes(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "open_workspace_in" => {
+                let path = parse_string(&params, "path")?;
+                let app = parse_optional_string(&params, "app")?;
+                let command = parse_optional_string(&params, "command")?;
+                let args = parse_optional_string_array(&params, "args")?.unwrap_or_default();
+                state.open_workspace_in(path, app, args, command).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "reveal_workspace_path" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.reveal_workspace_path(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "get_open_app_icon" => {
+                let app_name = parse_string(&params, "appName")?;
+                let icon = state.get_open_app_icon(app_name).await?;
+                to_result(icon)
+            },
+            "get_git_status" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .get_git_status(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "is_working_tree_clean" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let include_untracked =
+                    parse_optional_bool(&params, "includeUntracked")?.unwrap_or(true);
+                let status = state
+                    .is_working_tree_clean(workspace_id, include_untracked)
+                    .await?;
+                to_result(status)
+            },
+            "repo_worktrees_diff_summary" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let summary = state.repo_worktrees_diff_summary(workspace_id).await?;
+                to_result(summary)
+            },
+            "get_git_status_detailed" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let status = state.get_git_status_detailed(workspace_id).await?;
+                to_result(status)
+            },
+            "watch_git_status" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let enabled = parse_optional_bool(&params, "enabled")?.unwrap_or(true);
+                let watching = state.watch_git_status(workspace_id, enabled).await?;
+                Ok(json!({ "watching": watching }))
+            },
+            "list_git_roots" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let depth = parse_optional_u32(&params, "depth")?.map(|value| value as usize);
+                let roots = state.list_git_roots(workspace_id, depth).await?;
+                to_result(roots)
+            },
+            "get_git_diffs" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let include_untracked =
+                    parse_optional_bool(&params, "includeUntracked")?.unwrap_or(true);
+                let max_diff_bytes =
+                    parse_optional_u64(&params, "maxDiffBytes")?.map(|value| value as usize);
+                let diffs = state
+                    .get_git_diffs(workspace_id, include_untracked, max_diff_bytes)
+                    .await?;
+                to_result(diffs)
+            },
+            "get_git_file_diff" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let staged = parse_optional_bool(&params, "staged")?;
+                let diff = state.get_git_file_diff(workspace_id, path, staged).await?;
+                to_result(diff)
+            },
+            "get_git_log" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let limit = parse_optional_u32(&params, "limit")?.map(|value| value as usize);
+                let include_stats = parse_optional_bool(&params, "includeStats")?.unwrap_or(false);
+                let log = state
+                    .get_git_log(workspace_id, limit, include_stats)
+                    .await?;
+                to_result(log)
+            },
+            "worktree_diff_vs_base" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let diff = state.worktree_diff_vs_base(workspace_id).await?;
+                to_result(diff)
+            },
+            "preview_incoming_changes" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let fetch = parse_optional_bool(&params, "fetch")?.unwrap_or(true);
+                let summary_only = parse_optional_bool(&params, "summaryOnly")?.unwrap_or(false);
+                let max_diff_bytes =
+                    parse_optional_u64(&params, "maxDiffBytes")?.map(|value| value as usize);
+                let changes = state
+                    .preview_incoming_changes(workspace_id, fetch, summary_only, max_diff_bytes)
+                    .await?;
+                to_result(changes)
+            },
+            "get_git_commit_diff" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let sha = parse_string(&params, "sha")?;
+                let diff = state.get_git_commit_diff(workspace_id, sha).await?;
+                to_result(diff)
+            },
+            "get_stash_diff" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let stash = parse_string(&params, "stash")?;
+                let diff = state.get_stash_diff(workspace_id, stash).await?;
+                to_result(diff)
+            },
+            "export_git_patch" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let revision = parse_string(&params, "revision")?;
+                let export = state.export_git_patch(workspace_id, revision).await?;
+                to_result(export)
+            },
+            "apply_git_patch" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let patch = parse_string(&params, "patch")?;
+                let check = parse_optional_bool(&params, "check")?.unwrap_or(false);
+                let three_way = parse_optional_bool(&params, "threeWay")?.unwrap_or(false);
+                let result = state
+                    .apply_git_patch(workspace_id, patch, check, three_way)
+                    .await?;
+                to_result(result)
+            },
+            "get_last_commit" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let commit = state.get_last_commit(workspace_id).await?;
+                to_result(commit)
+            },
+            "git_operation_progress" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let progress = state.git_operation_progress(workspace_id).await?;
+                to_result(progress)
+            },
+            "git_reflog" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let ref_name = parse_optional_string(&params, "refName")?;
+                let limit = parse_optional_u32(&params, "limit")?.map(|value| value as usize);
+                let entries = state.git_reflog(workspace_id, ref_name, limit).await?;
+                to_result(entries)
+            },
+            "restore_from_reflog" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let sha = parse_string(&params, "sha")?;
+                let branch = parse_optional_string(&params, "branch")?;
+                let confirm = parse_optional_bool(&params, "confirm")?.unwrap_or(false);
+                let result = state
+                    .restore_from_reflog(workspace_id, sha, branch, confirm)
+                    .await?;
+                to_result(result)
+            },
+            "get_git_remote" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let remote = state.get_git_remote(workspace_id).await?;
+                to_result(remote)
+            },
+            "stage_git_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                state.stage_git_file(workspace_id, path).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "stage_git_all" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.stage_git_all(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "unstage_git_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                state.unstage_git_file(workspace_id, path).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "revert_git_file" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                state.revert_git_file(workspace_id, path).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "revert_git_all" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.revert_git_all(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "commit_git" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let message = parse_string(&params, "message")?;
+                state.commit_git(workspace_id, message).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "check_large_staged_files" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let threshold_bytes = parse_optional_u64(&params, "thresholdBytes")?;
+                let response = state
+                    .check_large_staged_files(workspace_id, threshold_bytes)
+                    .await?;
+                to_result(response)
+            },
+            "git_repair_locks" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let remove = parse_optional_bool(&params, "remove")?.unwrap_or(false);
+                let result = state.git_repair_locks(workspace_id, remove).await?;
+                to_result(result)
+            },
+            "push_git" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let force = parse_optional_string(&params, "force")?;
+                let remote = parse_optional_string(&params, "remote")?;
+                let result = state.push_git(workspace_id, force, remote).await?;
+                to_result(result)
+            },
+            "pull_git" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let remote = parse_optional_string(&params, "remote")?;
+                state.pull_git(workspace_id, remote).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "fetch_git" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let remote = parse_optional_string(&params, "remote")?;
+                state.fetch_git(workspace_id, remote).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "list_git_remotes" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let remotes = state.list_git_remotes(workspace_id).await?;
+                to_result(remotes)
+            },
+            "sync_git" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state.sync_git(workspace_id).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "get_github_repo_info" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let info = state.get_github_repo_info(workspace_id).await?;
+                to_result(info)
+            },
+            "get_github_issues" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let issues = state.get_github_issues(workspace_id).await?;
+                to_result(issues)
+            },
+            "get_github_pull_requests" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let prs = state.get_github_pull_requests(workspace_id).await?;
+                to_result(prs)
+            },
+            "get_github_pull_request_diff" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let pr_number = parse_optional_u64(&params, "prNumber")?
+                    .ok_or_else(|| RpcError::invalid_param("prNumber"))?;
+                let diff = state
+                    .get_github_pull_request_diff(workspace_id, pr_number)
+                    .await?;
+                to_result(diff)
+            },
+            "get_github_pull_request_comments" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let pr_number = parse_optional_u64(&params, "prNumber")?
+                    .ok_or_else(|| RpcError::invalid_param("prNumber"))?;
+                let comments = state
+                    .get_github_pull_request_comments(workspace_id, pr_number)
+                    .await?;
+                to_result(comments)
+            },
+            "get_github_pull_request_review_threads" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let pr_number = parse_optional_u64(&params, "prNumber")?
+                    .ok_or_else(|| RpcError::invalid_param("prNumber"))?;
+                let threads = state
+                    .get_github_pull_request_review_threads(workspace_id, pr_number)
+                    .await?;
+                to_result(threads)
+            },
+            "checkout_github_pull_request" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let pr_number = parse_optional_u64(&params, "prNumber")?
+                    .ok_or_else(|| RpcError::invalid_param("prNumber"))?;
+                let update = parse_optional_bool(&params, "update")?.unwrap_or(false);
+                let checkout = state
+                    .checkout_github_pull_request(workspace_id, pr_number, update)
+                    .await?;
+                to_result(checkout)
+            },
+            "list_git_branches" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                state
+                    .list_git_branches(workspace_id)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "checkout_git_branch" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let name = parse_string(&params, "name")?;
+                state.checkout_git_branch(workspace_id, name).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "create_git_branch" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let name = parse_string(&params, "name")?;
+                state.create_git_branch(workspace_id, name).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "list_merged_branches" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let merged = state.list_merged_branches(workspace_id).await?;
+                to_result(merged)
+            },
+            "prune_merged_branches" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let branches = parse_string_array(&params, "branches")?;
+                let results = state.prune_merged_branches(workspace_id, branches).await?;
+                to_result(results)
+            },
+            "create_and_publish_branch" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let name = parse_string(&params, "name")?;
+                let remote = parse_optional_string(&params, "remote")?;
+                let result = state
+                    .create_and_publish_branch(workspace_id, name, remote)
+                    .await?;
+                to_result(result)
+            },
+            "prompts_list" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let prompts = state.prompts_list(workspace_id).await?;
+                to_result(prompts)
+            },
+            "prompts_workspace_dir" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let dir = state.prompts_workspace_dir(workspace_id).await?;
+                Ok(Value::String(dir))
+            },
+            "prompts_global_dir" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let dir = state.prompts_global_dir(workspace_id).await?;
+                Ok(Value::String(dir))
+            },
+            "prompts_create" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let scope = parse_string(&params, "scope")?;
+                let name = parse_string(&params, "name")?;
+                let description = parse_optional_string(&params, "description")?;
+                let argument_hint = parse_optional_string(&params, "argumentHint")?;
+                let content = parse_string(&params, "content")?;
+                let prompt = state
+                    .prompts_create(
+                        workspace_id,
+                        scope,
+                        name,
+                        description,
+                        argument_hint,
+                        content,
+                    )
+                    .await?;
+                to_result(prompt)
+            },
+            "prompts_update" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let name = parse_string(&params, "name")?;
+                let description = parse_optional_string(&params, "description")?;
+                let argument_hint = parse_optional_string(&params, "argumentHint")?;
+                let content = parse_string(&params, "content")?;
+                let prompt = state
+                    .prompts_update(
+                        workspace_id,
+                        path,
+                        name,
+                        description,
+                        argument_hint,
+                        content,
+                    )
+                    .await?;
+                to_result(prompt)
+            },
+            "prompts_delete" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                state.prompts_delete(workspace_id, path).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "prompts_move" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let path = parse_string(&params, "path")?;
+                let scope = parse_string(&params, "scope")?;
+                let prompt = state.prompts_move(workspace_id, path, scope).await?;
+                to_result(prompt)
+            },
+            "codex_doctor" => {
+                let codex_bin = parse_optional_string(&params, "codexBin")?;
+                let codex_args = parse_optional_string(&params, "codexArgs")?;
+                let report = state.codex_doctor(codex_bin, codex_args).await?;
+                to_result(report)
+            },
+            "get_default_codex_bin" => Ok(json!(state.get_default_codex_bin().await)),
+            "set_default_codex_bin" => {
+                let codex_bin = parse_optional_string(&params, "codexBin")?;
+                let report = state.set_default_codex_bin(codex_bin).await?;
+                to_result(report)
+            },
+            "daemon_doctor" => {
+                let report = state.daemon_doctor().await;
+                to_result(report)
+            },
+            "generate_commit_message" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let message = state.generate_commit_message(workspace_id).await?;
+                Ok(Value::String(message))
+            },
+            "generate_run_metadata" => {
+                let workspace_id = parse_workspace_id(state, &params, "workspaceId").await?;
+                let prompt = parse_string(&params, "prompt")?;
+                state
+                    .generate_run_metadata(workspace_id, prompt)
+                    .await
+                    .map_err(RpcError::from)
+            },
+            "local_usage_snapshot" => {
+                let days = parse_optional_u32(&params, "days")?;
+                let workspace_path = parse_optional_string(&params, "workspacePath")?;
+                let snapshot = state.local_usage_snapshot(days, workspace_path).await?;
+                to_result(snapshot)
+            },
+            "refresh_local_usage" => {
+                let days = parse_optional_u32(&params, "days")?;
+                let workspace_path = parse_optional_string(&params, "workspacePath")?;
+                let result = state.refresh_local_usage(days, workspace_path).await?;
+                to_result(result)
+            },
+            "menu_set_accelerators" => {
+                let updates: Vec<Value> = parse_optional_value(&params, "updates")
+                    .map(|updates| parse_params(updates, Some("updates")))
+                    .transpose()?
+                    .unwrap_or_default();
+                state.menu_set_accelerators(updates).await?;
+                Ok(json!({ "ok": true }))
+            },
+            "is_macos_debug_build" => {
+                let is_debug = state.is_macos_debug_build().await;
+                Ok(Value::Bool(is_debug))
+            },
+            "send_notification_fallback" => {
+                let title = parse_string(&params, "title")?;
+                let body = parse_string(&params, "body")?;
+                state.send_notification_fallback(title, body).await?;
+                Ok(json!({ "ok": true }))
+            },
+            _ => Err(RpcError::MethodNotFound(format!(
+                "unknown method: {method}"
+            ))),
         }
-        _ => Err(format!("unknown method: {method}")),
     }
 }
 
@@ -760,26 +1638,205 @@ pub(super) async fn forward_events(
     }
 }
 
+/// Re-sends approval requests that are still unanswered, so a client that
+/// (re)connects mid-turn sees them instead of leaving the turn stuck.
+pub(super) async fn replay_pending_approvals(
+    state: &DaemonState,
+    out_tx: &mpsc::UnboundedSender<String>,
+) {
+    for approval in state.list_pending_approvals(None).await {
+        let event = DaemonEvent::AppServer(AppServerEvent {
+            workspace_id: approval.workspace_id,
+            message: json!({
+                "id": approval.request_id,
+                "method": approval.method,
+                "params": approval.params,
+            }),
+        });
+        let Some(payload) = build_event_notification(event) else {
+            continue;
+        };
+        if out_tx.send(payload).is_err() {
+            break;
+        }
+    }
+}
+
+/// Key for an in-flight request. Serialized JSON, so `1` and `"1"` differ.
+fn request_key(id: &Value) -> String {
+    id.to_string()
+}
+
+#[derive(Default)]
+struct InFlightTasks {
+    next_token: u64,
+    by_key: HashMap<String, (u64, AbortHandle)>,
+}
+
+/// Ids handed to connections as they open; never reused within a run.
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// A connection's requests that have not been answered yet. The semaphore
+/// caps how many run at once; the abort handles let `cancel` stop one by id.
+pub(super) struct InFlightRequests {
+    connection: u64,
+    transport: Transport,
+    limiter: Arc<Semaphore>,
+    tasks: StdMutex<InFlightTasks>,
+}
+
+impl InFlightRequests {
+    pub(super) fn new(transport: Transport) -> Self {
+        Self {
+            transport,
+            ..Self::with_limit(MAX_IN_FLIGHT_RPC_PER_CONNECTION)
+        }
+    }
+
+    pub(super) fn with_limit(limit: usize) -> Self {
+        Self {
+            connection: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            transport: Transport::Tcp,
+            limiter: Arc::new(Semaphore::new(limit)),
+            tasks: StdMutex::new(InFlightTasks::default()),
+        }
+    }
+
+    /// The id of the connection these requests arrived on.
+    pub(super) fn connection(&self) -> u64 {
+        self.connection
+    }
+
+    pub(super) fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// Registers a cancellable request. `None` when another request with the
+    /// same id is still running; the newcomer then runs but can't be cancelled.
+    fn track(&self, key: &str) -> Option<(u64, AbortRegistration)> {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|err| err.into_inner());
+        if tasks.by_key.contains_key(key) {
+            return None;
+        }
+        let (handle, registration) = AbortHandle::new_pair();
+        tasks.next_token += 1;
+        let token = tasks.next_token;
+        tasks.by_key.insert(key.to_string(), (token, handle));
+        Some((token, registration))
+    }
+
+    /// Whoever removes the entry answers the request: the task itself here,
+    /// or `cancel` with an error. False means it was cancelled first.
+    fn finish(&self, key: &str, token: u64) -> bool {
+        let mut tasks = self.tasks.lock().unwrap_or_else(|err| err.into_inner());
+        if !matches!(tasks.by_key.get(key), Some((current, _)) if *current == token) {
+            return false;
+        }
+        tasks.by_key.remove(key);
+        true
+    }
+
+    fn cancel(&self, key: &str) -> bool {
+        let removed = self
+            .tasks
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .by_key
+            .remove(key);
+        match removed {
+            Some((_, handle)) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Handles `cancel` on the connection itself, since only it knows which
+/// requests are in flight. The cancelled request is answered with
+/// `RpcError::Cancelled`; an unknown or finished id is a no-op.
+pub(super) fn handle_cancel_request(
+    requests: &InFlightRequests,
+    out_tx: &mpsc::UnboundedSender<String>,
+    id: Option<Value>,
+    params: &Value,
+) {
+    let target = params
+        .get("requestId")
+        .filter(|target| target.is_number() || target.is_string());
+    let response = match target {
+        Some(target) => {
+            let cancelled = requests.cancel(&request_key(target));
+            if cancelled {
+                if let Some(response) =
+                    build_error_response(Some(target.clone()), &RpcError::Cancelled)
+                {
+                    let _ = out_tx.send(response);
+                }
+            }
+            build_result_response(id, json!({ "ok": true, "cancelled": cancelled }))
+        }
+        None => build_error_response(id, &RpcError::invalid_param("requestId")),
+    };
+    if let Some(response) = response {
+        let _ = out_tx.send(response);
+    }
+}
+
 pub(super) fn spawn_rpc_response_task(
     state: Arc<DaemonState>,
     out_tx: mpsc::UnboundedSender<String>,
-    id: Option<u64>,
+    id: Option<Value>,
     method: String,
     params: Value,
     client_version: String,
-    request_limiter: Arc<Semaphore>,
+    requests: Arc<InFlightRequests>,
 ) {
-    tokio::spawn(async move {
-        let Ok(_permit) = request_limiter.acquire_owned().await else {
+    let key = id.as_ref().map(request_key);
+    let tracked = key.as_deref().and_then(|key| requests.track(key));
+    let token = tracked.as_ref().map(|(token, _)| *token);
+    let limiter = Arc::clone(&requests.limiter);
+    let task = async move {
+        let Ok(_permit) = limiter.acquire_owned().await else {
+            return;
+        };
+        let Some(_running) = state.running_requests.start() else {
+            if let Some(response) = build_error_response(id, &RpcError::ShuttingDown) {
+                let _ = out_tx.send(response);
+            }
             return;
         };
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
+        let started = Instant::now();
+        let origin = RequestOrigin {
+            connection: requests.connection(),
+            id: id.clone(),
+            transport: requests.transport(),
+        };
+        let result = handle_rpc_request(&state, &origin, &method, params, client_version).await;
+        // Unknown names are not recorded so stray calls cannot grow the table.
+        if !matches!(&result, Err(RpcError::MethodNotFound(_))) {
+            state.record_method_latency(&method, started.elapsed());
+        }
+        if let (Some(key), Some(token)) = (key.as_deref(), token) {
+            if !requests.finish(key, token) {
+                return;
+            }
+        }
         let response = match result {
             Ok(result) => build_result_response(id, result),
-            Err(message) => build_error_response(id, &message),
+            Err(error) => build_error_response(id, &error),
         };
         if let Some(response) = response {
             let _ = out_tx.send(response);
         }
-    });
+    };
+    match tracked {
+        Some((_, registration)) => {
+            tokio::spawn(Abortable::new(task, registration));
+        }
+        None => {
+            tokio::spawn(task);
+        }
+    }
 }
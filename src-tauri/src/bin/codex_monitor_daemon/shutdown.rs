@@ -0,0 +1,211 @@
+use super::*;
+
+use std::fs::File;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use futures_util::future::{self, Either};
+use tokio::sync::Notify;
+use tokio::time::timeout;
+
+/// How long shutdown waits for running requests before exiting anyway.
+pub(super) const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long shutdown waits for each storage file's lock, i.e. for a write in
+/// progress in this or another process sharing the data dir.
+const SHUTDOWN_STORAGE_LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+/// Pause after the drain so responses from the last requests (such as the
+/// `daemon_shutdown` reply itself) reach their sockets.
+const SHUTDOWN_FLUSH_DELAY: Duration = Duration::from_millis(100);
+
+/// Requests across all connections that hold a limiter permit, i.e. are
+/// running rather than queued. Shutdown closes it so no further request
+/// starts, then waits for the count to reach zero.
+#[derive(Default)]
+pub(super) struct RunningRequests {
+    count: AtomicUsize,
+    closed: AtomicBool,
+    drained: Notify,
+}
+
+impl RunningRequests {
+    /// `None` once shutdown has begun; the request must be refused.
+    pub(super) fn start(&self) -> Option<RunningRequestGuard<'_>> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        // Checked after counting, so a request either sees the close or is
+        // counted before the drain looks.
+        let guard = RunningRequestGuard { requests: self };
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+
+    pub(super) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    pub(super) fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// False when requests were still running after `limit`.
+    pub(super) async fn wait_drained(&self, limit: Duration) -> bool {
+        let drained = async {
+            loop {
+                // Created before the check so a finish in between still wakes it.
+                let notified = self.drained.notified();
+                if self.count() == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        timeout(limit, drained).await.is_ok()
+    }
+}
+
+pub(super) struct RunningRequestGuard<'a> {
+    requests: &'a RunningRequests,
+}
+
+impl Drop for RunningRequestGuard<'_> {
+    fn drop(&mut self) {
+        if self.requests.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.requests.drained.notify_waiters();
+        }
+    }
+}
+
+/// A shutdown asked for from inside the daemon, by the `daemon_shutdown` RPC
+/// or the idle watchdog. `main` waits on it alongside the OS signals so every
+/// path runs the same graceful shutdown.
+#[derive(Default)]
+pub(super) struct ShutdownRequest {
+    reason: std::sync::Mutex<Option<String>>,
+    requested: Notify,
+}
+
+impl ShutdownRequest {
+    /// The first reason wins; later requests are no-ops.
+    pub(super) fn request(&self, reason: String) {
+        let mut current = self.reason.lock().unwrap_or_else(|err| err.into_inner());
+        if current.is_none() {
+            *current = Some(reason);
+            // Stores a permit, so a request made before `main` waits still counts.
+            self.requested.notify_one();
+        }
+    }
+
+    async fn requested(&self) -> String {
+        self.requested.notified().await;
+        self.reason
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+/// Resolves with the signal's name once SIGTERM or SIGINT arrives.
+#[cfg(unix)]
+pub(super) async fn wait_for_shutdown_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let Ok(mut sigterm) = signal(SignalKind::terminate()) else {
+        let _ = tokio::signal::ctrl_c().await;
+        return "SIGINT";
+    };
+    let ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
+    let sigterm = std::pin::pin!(sigterm.recv());
+    match future::select(ctrl_c, sigterm).await {
+        Either::Left(_) => "SIGINT",
+        Either::Right(_) => "SIGTERM",
+    }
+}
+
+/// Resolves once Ctrl+C (or a console close) arrives.
+#[cfg(not(unix))]
+pub(super) async fn wait_for_shutdown_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl+C"
+}
+
+/// Runs `serve` until a shutdown signal arrives or one is requested through
+/// `state`, then returns the reason. `None` means `serve` ended on its own.
+pub(super) async fn serve_until_shutdown<F>(state: &DaemonState, serve: F) -> Option<String>
+where
+    F: std::future::Future<Output = ()>,
+{
+    let serve = std::pin::pin!(serve);
+    let signal = std::pin::pin!(async {
+        let signal = std::pin::pin!(wait_for_shutdown_signal());
+        let requested = std::pin::pin!(state.shutdown_request.requested());
+        match future::select(signal, requested).await {
+            Either::Left((signal, _)) => format!("received {signal}"),
+            Either::Right((reason, _)) => reason,
+        }
+    });
+    match future::select(serve, signal).await {
+        Either::Left(_) => None,
+        Either::Right((reason, _)) => Some(reason),
+    }
+}
+
+/// Takes each storage file's exclusive lock, waiting out a workspace or
+/// settings write in progress in this or another process. The returned locks
+/// are held until exit so no new write starts.
+async fn lock_storage_files(paths: [PathBuf; 2]) -> Vec<File> {
+    let mut locks = Vec::new();
+    for path in paths {
+        let blocking_path = path.clone();
+        let lock =
+            tokio::task::spawn_blocking(move || storage::lock_storage_file(&blocking_path, true));
+        match timeout(SHUTDOWN_STORAGE_LOCK_TIMEOUT, lock).await {
+            Ok(Ok(Ok(lock))) => locks.push(lock),
+            Ok(Ok(Err(err))) => {
+                eprintln!(
+                    "codex-monitor-daemon could not lock {}: {err}",
+                    path.display()
+                )
+            }
+            Ok(Err(err)) => {
+                eprintln!(
+                    "codex-monitor-daemon could not lock {}: {err}",
+                    path.display()
+                )
+            }
+            Err(_) => eprintln!(
+                "codex-monitor-daemon exiting while {} is still locked after {}s",
+                path.display(),
+                SHUTDOWN_STORAGE_LOCK_TIMEOUT.as_secs()
+            ),
+        }
+    }
+    locks
+}
+
+/// Called once the listener has been dropped: refuses new requests, lets
+/// running ones finish, waits out any storage write still holding its lock,
+/// then exits 0.
+pub(super) async fn shutdown_gracefully(state: &DaemonState, reason: &str) -> ! {
+    eprintln!("codex-monitor-daemon shutting down: {reason}");
+    state.running_requests.close();
+    let running = state.running_requests.count();
+    if running > 0 {
+        eprintln!("codex-monitor-daemon waiting for {running} running request(s)");
+        if !state
+            .running_requests
+            .wait_drained(SHUTDOWN_DRAIN_TIMEOUT)
+            .await
+        {
+            eprintln!(
+                "codex-monitor-daemon exiting with {} request(s) still running after {}s",
+                state.running_requests.count(),
+                SHUTDOWN_DRAIN_TIMEOUT.as_secs()
+            );
+        }
+    }
+    sleep(SHUTDOWN_FLUSH_DELAY).await;
+    let _locks =
+        lock_storage_files([state.storage_path.clone(), state.settings_path.clone()]).await;
+    std::process::exit(0);
+}
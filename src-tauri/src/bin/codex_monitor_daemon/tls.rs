@@ -0,0 +1,73 @@
+use super::*;
+
+use std::net::IpAddr;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// A client that connects but never finishes the handshake is dropped after
+/// this, before it counts as a connected client.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// PEM files given with `--tls-cert` and `--tls-key`.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct TlsPaths {
+    /// Leaf certificate first, followed by any intermediates.
+    pub(super) cert: PathBuf,
+    pub(super) key: PathBuf,
+}
+
+/// Reads the certificate chain and key once at startup so a bad path or
+/// mismatched pair stops the daemon before it listens.
+pub(super) fn load_tls_acceptor(paths: &TlsPaths) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(&paths.cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| {
+            format!(
+                "failed to load TLS certificate {}: {err}",
+                paths.cert.display()
+            )
+        })?;
+    if certs.is_empty() {
+        return Err(format!(
+            "failed to load TLS certificate {}: no certificates found",
+            paths.cert.display()
+        ));
+    }
+    let key = PrivateKeyDer::from_pem_file(&paths.key).map_err(|err| {
+        format!(
+            "failed to load TLS private key {}: {err}",
+            paths.key.display()
+        )
+    })?;
+    let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| format!("failed to configure TLS: {err}"))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|err| format!("TLS certificate and key do not match: {err}"))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Completes the TLS handshake, then serves the connection exactly like a
+/// plaintext one.
+pub(super) async fn handle_tls_client<S>(
+    acceptor: TlsAcceptor,
+    socket: S,
+    peer_ip: IpAddr,
+    config: Arc<DaemonConfig>,
+    state: Arc<DaemonState>,
+    events: broadcast::Sender<DaemonEvent>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let Ok(Ok(stream)) = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(socket)).await
+    else {
+        return;
+    };
+    transport::handle_client(stream, rpc::Transport::Tls, peer_ip, config, state, events).await;
+}
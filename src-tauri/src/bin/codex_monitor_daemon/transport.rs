@@ -1,21 +1,69 @@
 use super::rpc::{
-    build_error_response, build_result_response, forward_events, parse_auth_token,
-    spawn_rpc_response_task,
+    build_error_response, build_result_response, forward_events, handle_cancel_request,
+    parse_accepts_gzip, parse_auth_token, parse_request_id, replay_pending_approvals, server_info,
+    spawn_rpc_response_task, InFlightRequests, RpcError, Transport,
 };
 use super::*;
 
-pub(super) async fn handle_client(
-    socket: TcpStream,
+use std::io::Write;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use base64::Engine;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+/// Outbound lines smaller than this are sent as plain JSON even when the
+/// client negotiated gzip; compressing them costs more than it saves.
+pub(super) const GZIP_FRAME_THRESHOLD_BYTES: usize = 16 * 1024;
+
+/// Wraps a large outbound line in a `{"encoding":"gzip","payload":...}`
+/// frame whose payload is the base64 of the gzipped original line.
+pub(super) fn encode_outgoing_line(message: String, gzip: bool) -> String {
+    if !gzip || message.len() < GZIP_FRAME_THRESHOLD_BYTES {
+        return message;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    if encoder.write_all(message.as_bytes()).is_err() {
+        return message;
+    }
+    let Ok(compressed) = encoder.finish() else {
+        return message;
+    };
+    let framed = json!({
+        "encoding": "gzip",
+        "payload": base64::engine::general_purpose::STANDARD.encode(compressed),
+    })
+    .to_string();
+    if framed.len() >= message.len() {
+        return message;
+    }
+    framed
+}
+
+/// Serves one client over a plain TCP stream or an accepted TLS stream.
+/// `peer_ip` keys the failed-auth count shared across its connections.
+pub(super) async fn handle_client<S>(
+    socket: S,
+    transport: Transport,
+    peer_ip: IpAddr,
     config: Arc<DaemonConfig>,
     state: Arc<DaemonState>,
     events: broadcast::Sender<DaemonEvent>,
-) {
-    let (reader, mut writer) = socket.into_split();
+) where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    state.client_connected();
+    let (reader, mut writer) = tokio::io::split(socket);
     let mut lines = BufReader::new(reader).lines();
 
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
-    let write_task = tokio::spawn(async move {
+    let gzip_enabled = Arc::new(AtomicBool::new(false));
+    let writer_gzip_enabled = Arc::clone(&gzip_enabled);
+    let mut write_task = tokio::spawn(async move {
         while let Some(message) = out_rx.recv().await {
+            let message = encode_outgoing_line(message, writer_gzip_enabled.load(Ordering::SeqCst));
             if writer.write_all(message.as_bytes()).await.is_err() {
                 break;
             }
@@ -25,18 +73,47 @@ pub(super) async fn handle_client(
         }
     });
 
-    let mut authenticated = config.token.is_none();
+    let mut authenticated = config.tokens.is_empty();
+    let mut locked_out = false;
+    let mut auth_timed_out = false;
+    // Only the handshake is timed; an authenticated client may sit idle.
+    let auth_deadline = config.auth_timeout.map(|limit| Instant::now() + limit);
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
-    let request_limiter = Arc::new(Semaphore::new(MAX_IN_FLIGHT_RPC_PER_CONNECTION));
+    let requests = Arc::new(InFlightRequests::new(transport));
+    let mut rate_limiter = config
+        .rate_limit
+        .map(|limit| RequestRateLimiter::new(limit, Instant::now()));
     let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
 
     if authenticated {
         let rx = events.subscribe();
         let out_tx_events = out_tx.clone();
         events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+        replay_pending_approvals(&state, &out_tx).await;
     }
 
-    while let Ok(Some(line)) = lines.next_line().await {
+    loop {
+        let next_line = match auth_deadline.filter(|_| !authenticated) {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match tokio::time::timeout(remaining, lines.next_line()).await {
+                    Ok(next_line) => next_line,
+                    Err(_) => {
+                        auth_timed_out = true;
+                        if let Some(response) =
+                            build_error_response(Some(Value::Null), &RpcError::AuthTimeout)
+                        {
+                            let _ = out_tx.send(response);
+                        }
+                        break;
+                    }
+                }
+            }
+            None => lines.next_line().await,
+        };
+        let Ok(Some(line)) = next_line else {
+            break;
+        };
         let line = line.trim();
         if line.is_empty() {
             continue;
@@ -47,7 +124,7 @@ pub(super) async fn handle_client(
             Err(_) => continue,
         };
 
-        let id = message.get("id").and_then(|value| value.as_u64());
+        let id = parse_request_id(&message);
         let method = message
             .get("method")
             .and_then(|value| value.as_str())
@@ -56,23 +133,52 @@ pub(super) async fn handle_client(
         let params = message.get("params").cloned().unwrap_or(Value::Null);
 
         if !authenticated {
+            if method == "server_info" {
+                let info =
+                    serde_json::to_value(server_info(&state, transport)).unwrap_or(Value::Null);
+                if let Some(response) = build_result_response(id, info) {
+                    let _ = out_tx.send(response);
+                }
+                continue;
+            }
             if method != "auth" {
-                if let Some(response) = build_error_response(id, "unauthorized") {
+                if let Some(response) = build_error_response(id, &RpcError::Unauthorized) {
                     let _ = out_tx.send(response);
                 }
                 continue;
             }
 
-            let expected = config.token.clone().unwrap_or_default();
             let provided = parse_auth_token(&params).unwrap_or_default();
-            if expected != provided {
-                if let Some(response) = build_error_response(id, "invalid token") {
+            let already_locked_out = config
+                .max_auth_attempts
+                .is_some_and(|max| state.auth_failures().count(peer_ip, Instant::now()) >= max);
+            if already_locked_out || !token_accepted(&config.tokens, &provided) {
+                // A locked-out peer's attempts still count, so guessing keeps
+                // it locked out instead of waiting out the window.
+                let failed_auth_attempts = state.auth_failures().record(peer_ip, Instant::now());
+                if !config.auth_failure_delay.is_zero() {
+                    sleep(config.auth_failure_delay).await;
+                }
+                locked_out = config
+                    .max_auth_attempts
+                    .is_some_and(|max| failed_auth_attempts >= max);
+                let error = if locked_out {
+                    RpcError::TooManyAuthAttempts
+                } else {
+                    RpcError::InvalidToken
+                };
+                if let Some(response) = build_error_response(id, &error) {
                     let _ = out_tx.send(response);
                 }
+                if locked_out {
+                    break;
+                }
                 continue;
             }
 
             authenticated = true;
+            state.auth_failures().clear(peer_ip);
+            gzip_enabled.store(parse_accepts_gzip(&params), Ordering::SeqCst);
             if let Some(response) = build_result_response(id, json!({ "ok": true })) {
                 let _ = out_tx.send(response);
             }
@@ -80,10 +186,33 @@ pub(super) async fn handle_client(
             let rx = events.subscribe();
             let out_tx_events = out_tx.clone();
             events_task = Some(tokio::spawn(forward_events(rx, out_tx_events)));
+            replay_pending_approvals(&state, &out_tx).await;
 
             continue;
         }
 
+        if method == "auth" {
+            // Already authenticated (or no token configured): only renegotiate
+            // compression so clients can always send the same handshake.
+            gzip_enabled.store(parse_accepts_gzip(&params), Ordering::SeqCst);
+            if let Some(response) = build_result_response(id, json!({ "ok": true })) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
+        if method == "cancel" {
+            handle_cancel_request(&requests, &out_tx, id, &params);
+            continue;
+        }
+
+        if is_rate_limited(&mut rate_limiter) {
+            if let Some(response) = build_error_response(id, &RpcError::RateLimited) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
         spawn_rpc_response_task(
             Arc::clone(&state),
             out_tx.clone(),
@@ -91,7 +220,7 @@ pub(super) async fn handle_client(
             method,
             params,
             client_version.clone(),
-            Arc::clone(&request_limiter),
+            Arc::clone(&requests),
         );
     }
 
@@ -99,7 +228,42 @@ pub(super) async fn handle_client(
     if let Some(task) = events_task {
         task.abort();
     }
+    if locked_out || auth_timed_out {
+        // Nothing else holds a sender before auth, so the writer ends once
+        // the closing error is flushed.
+        let _ = tokio::time::timeout(Duration::from_secs(1), &mut write_task).await;
+    }
     write_task.abort();
+    state.file_tails.untail_connection(requests.connection());
+    state.client_disconnected();
+}
+
+/// Compares in time that depends only on the lengths, never on how long a
+/// prefix matches, so the token can't be recovered byte by byte from response
+/// timings. A length mismatch still walks the longer input.
+pub(super) fn tokens_match(expected: &str, provided: &str) -> bool {
+    let (expected, provided) = (expected.as_bytes(), provided.as_bytes());
+    let mut diff = expected.len() ^ provided.len();
+    for index in 0..expected.len().max(provided.len()) {
+        let left = expected.get(index).copied().unwrap_or(0);
+        let right = provided.get(index).copied().unwrap_or(0);
+        diff |= usize::from(left ^ right);
+        diff = std::hint::black_box(diff);
+    }
+    diff == 0
+}
+
+/// Checks every configured token, so timing doesn't tell which one matched.
+fn token_accepted(tokens: &[String], provided: &str) -> bool {
+    tokens.iter().fold(false, |accepted, expected| {
+        accepted | tokens_match(expected, provided)
+    })
+}
+
+fn is_rate_limited(rate_limiter: &mut Option<RequestRateLimiter>) -> bool {
+    rate_limiter
+        .as_mut()
+        .is_some_and(|limiter| !limiter.try_acquire(Instant::now()))
 }
 
 fn handle_orbit_line(
@@ -107,7 +271,8 @@ fn handle_orbit_line(
     state: Arc<DaemonState>,
     out_tx: mpsc::UnboundedSender<String>,
     client_version: String,
-    request_limiter: Arc<Semaphore>,
+    requests: Arc<InFlightRequests>,
+    rate_limiter: &mut Option<RequestRateLimiter>,
 ) {
     let message: Value = match serde_json::from_str(line) {
         Ok(value) => value,
@@ -121,7 +286,7 @@ fn handle_orbit_line(
         return;
     }
 
-    let id = message.get("id").and_then(|value| value.as_u64());
+    let id = parse_request_id(&message);
     let method = message
         .get("method")
         .and_then(|value| value.as_str())
@@ -139,15 +304,19 @@ fn handle_orbit_line(
         return;
     }
 
-    spawn_rpc_response_task(
-        state,
-        out_tx,
-        id,
-        method,
-        params,
-        client_version,
-        request_limiter,
-    );
+    if method == "cancel" {
+        handle_cancel_request(&requests, &out_tx, id, &params);
+        return;
+    }
+
+    if is_rate_limited(rate_limiter) {
+        if let Some(response) = build_error_response(id, &RpcError::RateLimited) {
+            let _ = out_tx.send(response);
+        }
+        return;
+    }
+
+    spawn_rpc_response_task(state, out_tx, id, method, params, client_version, requests);
 }
 
 pub(super) async fn run_orbit_mode(
@@ -169,12 +338,20 @@ pub(super) async fn run_orbit_mode(
                 Ok(value) => value,
                 Err(err) => {
                     eprintln!("invalid orbit url: {err}");
+                    state.update_orbit_connection(|tracker| {
+                        tracker.connect_failed(
+                            format!("invalid orbit url: {err}"),
+                            reconnect_delay,
+                            Instant::now(),
+                        )
+                    });
                     sleep(reconnect_delay).await;
                     reconnect_delay = (reconnect_delay * 2).min(Duration::from_secs(20));
                     continue;
                 }
             };
 
+        state.update_orbit_connection(|tracker| tracker.connecting());
         let stream = match connect_async(&ws_url).await {
             Ok((stream, _response)) => stream,
             Err(err) => {
@@ -184,6 +361,9 @@ pub(super) async fn run_orbit_mode(
                     err,
                     reconnect_delay.as_secs()
                 );
+                state.update_orbit_connection(|tracker| {
+                    tracker.connect_failed(err.to_string(), reconnect_delay, Instant::now())
+                });
                 sleep(reconnect_delay).await;
                 reconnect_delay = (reconnect_delay * 2).min(Duration::from_secs(20));
                 continue;
@@ -192,6 +372,7 @@ pub(super) async fn run_orbit_mode(
 
         reconnect_delay = Duration::from_secs(1);
         eprintln!("orbit runner connected to {}", ws_url);
+        state.update_orbit_connection(|tracker| tracker.connected(Instant::now()));
 
         let (mut writer, mut reader) = stream.split();
         let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
@@ -219,9 +400,14 @@ pub(super) async fn run_orbit_mode(
             })
             .to_string(),
         );
+        replay_pending_approvals(&state, &out_tx).await;
 
         let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
-        let request_limiter = Arc::new(Semaphore::new(MAX_IN_FLIGHT_RPC_PER_CONNECTION));
+        let requests = Arc::new(InFlightRequests::new(Transport::WebSocket));
+        let mut rate_limiter = config
+            .rate_limit
+            .map(|limit| RequestRateLimiter::new(limit, Instant::now()));
+        let mut disconnect_error = None;
         while let Some(frame) = reader.next().await {
             match frame {
                 Ok(Message::Text(text)) => {
@@ -231,7 +417,8 @@ pub(super) async fn run_orbit_mode(
                             Arc::clone(&state),
                             out_tx.clone(),
                             client_version.clone(),
-                            Arc::clone(&request_limiter),
+                            Arc::clone(&requests),
+                            &mut rate_limiter,
                         );
                     }
                 }
@@ -243,7 +430,8 @@ pub(super) async fn run_orbit_mode(
                                 Arc::clone(&state),
                                 out_tx.clone(),
                                 client_version.clone(),
-                                Arc::clone(&request_limiter),
+                                Arc::clone(&requests),
+                                &mut rate_limiter,
                             );
                         }
                     }
@@ -253,6 +441,7 @@ pub(super) async fn run_orbit_mode(
                 Ok(Message::Frame(_)) => {}
                 Err(err) => {
                     eprintln!("orbit runner connection error: {err}");
+                    disconnect_error = Some(err.to_string());
                     break;
                 }
             }
@@ -261,11 +450,15 @@ pub(super) async fn run_orbit_mode(
         drop(out_tx);
         events_task.abort();
         write_task.abort();
+        state.file_tails.untail_connection(requests.connection());
 
         eprintln!(
             "orbit runner disconnected. reconnecting in {}s",
             reconnect_delay.as_secs()
         );
+        state.update_orbit_connection(|tracker| {
+            tracker.disconnected(disconnect_error, reconnect_delay, Instant::now())
+        });
         sleep(reconnect_delay).await;
         reconnect_delay = (reconnect_delay * 2).min(Duration::from_secs(20));
     }
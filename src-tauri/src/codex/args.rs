@@ -84,6 +84,8 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings {
                 codex_args: Some("--profile parent".to_string()),
                 ..WorkspaceSettings::default()
@@ -98,6 +100,8 @@ mod tests {
             kind: WorkspaceKind::Worktree,
             parent_id: Some(parent.id.clone()),
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
 
@@ -118,6 +122,8 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
         let resolved_main = resolve_workspace_codex_args(&main, None, Some(&app_settings));
@@ -8,6 +8,13 @@ use crate::files::policy::{policy_for, FileKind, FileScope};
 
 const FEATURES_TABLE: &str = "[features]";
 
+/// Codex `[features]` flags that gate model tools, with Codex's default for each.
+pub(crate) const CODEX_TOOL_FEATURES: &[(&str, bool)] = &[
+    ("web_search_request", false),
+    ("view_image_tool", true),
+    ("shell_tool", true),
+];
+
 pub(crate) fn read_steer_enabled() -> Result<Option<bool>, String> {
     read_feature_flag("steer")
 }
@@ -89,19 +96,28 @@ fn read_feature_flag(key: &str) -> Result<Option<bool>, String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(None);
     };
-    let contents = read_config_contents_from_root(&root)?;
-    Ok(contents
-        .as_deref()
-        .and_then(|value| find_feature_flag(value, key)))
+    read_feature_flag_in(&root, key)
 }
 
 fn write_feature_flag(key: &str, enabled: bool) -> Result<(), String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(());
     };
+    write_feature_flag_in(&root, key, enabled)
+}
+
+/// Reads a `[features]` flag from the config.toml under `root`.
+pub(crate) fn read_feature_flag_in(root: &Path, key: &str) -> Result<Option<bool>, String> {
+    let contents = read_config_contents_from_root(root)?;
+    Ok(contents
+        .as_deref()
+        .and_then(|value| find_feature_flag(value, key)))
+}
+
+pub(crate) fn write_feature_flag_in(root: &Path, key: &str, enabled: bool) -> Result<(), String> {
     let policy = config_policy()?;
     let response = read_text_file_within(
-        &root,
+        root,
         policy.filename,
         policy.root_may_be_missing,
         policy.root_context,
@@ -114,11 +130,15 @@ fn write_feature_flag(key: &str, enabled: bool) -> Result<(), String> {
         String::new()
     };
     let updated = upsert_feature_flag(&contents, key, enabled);
-    write_with_policy(&root, policy, &updated)
+    write_with_policy(&root.to_path_buf(), policy, &updated)
 }
 
 pub(crate) fn config_toml_path() -> Option<PathBuf> {
-    resolve_default_codex_home().map(|home| home.join("config.toml"))
+    resolve_default_codex_home().map(|home| config_toml_path_in(&home))
+}
+
+pub(crate) fn config_toml_path_in(codex_home: &Path) -> PathBuf {
+    codex_home.join("config.toml")
 }
 
 pub(crate) fn read_config_model(codex_home: Option<PathBuf>) -> Result<Option<String>, String> {
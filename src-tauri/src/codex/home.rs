@@ -212,6 +212,8 @@ mod tests {
             kind,
             parent_id: None,
             worktree,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings {
                 codex_home: codex_home.map(|value| value.to_string()),
                 ..WorkspaceSettings::default()
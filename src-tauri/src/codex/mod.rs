@@ -2,20 +2,26 @@ use serde_json::{json, Map, Value};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 pub(crate) mod args;
 pub(crate) mod config;
 pub(crate) mod home;
 
 use crate::backend::app_server::spawn_workspace_session as spawn_workspace_session_inner;
+use crate::backend::app_server::SessionRecords;
 pub(crate) use crate::backend::app_server::WorkspaceSession;
 use crate::backend::events::AppServerEvent;
 use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
-use crate::shared::codex_core;
+use crate::shared::codex_core::UserMessageInput;
+use crate::shared::{codex_core, workspaces_core};
 use crate::state::AppState;
-use crate::types::WorkspaceEntry;
+use crate::types::{
+    BuildInfo, BulkArchiveThreadsResult, CodexDoctorReport, CodexLoginStatus, DaemonDoctorReport,
+    MessageCostEstimate, PendingApproval, QueuedUserMessage, SessionCapabilities, SessionPing,
+    ThreadInfo, WorkspaceActivity, WorkspaceCodexConfigPath, WorkspaceEntry,
+};
 
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
@@ -25,6 +31,13 @@ pub(crate) async fn spawn_workspace_session(
     codex_home: Option<PathBuf>,
 ) -> Result<Arc<WorkspaceSession>, String> {
     let client_version = app_handle.package_info().version.to_string();
+    let records = {
+        let state = app_handle.state::<AppState>();
+        SessionRecords {
+            events: state.session_events.clone(),
+            errors: state.session_errors.clone(),
+        }
+    };
     let event_sink = TauriEventSink::new(app_handle);
     spawn_workspace_session_inner(
         entry,
@@ -33,6 +46,7 @@ pub(crate) async fn spawn_workspace_session(
         codex_home,
         client_version,
         event_sink,
+        records,
     )
     .await
 }
@@ -42,11 +56,82 @@ pub(crate) async fn codex_doctor(
     codex_bin: Option<String>,
     codex_args: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
+) -> Result<CodexDoctorReport, String> {
     crate::shared::codex_aux_core::codex_doctor_core(&state.app_settings, codex_bin, codex_args)
         .await
 }
 
+#[tauri::command]
+pub(crate) async fn get_default_codex_bin(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "get_default_codex_bin", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(crate::shared::codex_aux_core::get_default_codex_bin_core(&state.app_settings).await)
+}
+
+#[tauri::command]
+pub(crate) async fn set_default_codex_bin(
+    codex_bin: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CodexDoctorReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_default_codex_bin",
+            json!({ "codexBin": codex_bin }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    crate::shared::codex_aux_core::set_default_codex_bin_core(
+        &state.app_settings,
+        &state.settings_path,
+        codex_bin,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn daemon_doctor(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<DaemonDoctorReport, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "daemon_doctor", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let data_dir = state
+        .settings_path
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."));
+    Ok(crate::shared::doctor_core::daemon_doctor_core(&state.app_settings, &data_dir).await)
+}
+
+#[tauri::command]
+pub(crate) async fn version(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BuildInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "version", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(crate::utils::build_info())
+}
+
 #[tauri::command]
 pub(crate) async fn codex_update(
     codex_bin: Option<String>,
@@ -122,6 +207,7 @@ pub(crate) async fn list_threads(
     cursor: Option<String>,
     limit: Option<u32>,
     sort_key: Option<String>,
+    status: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Value, String> {
@@ -134,13 +220,108 @@ pub(crate) async fn list_threads(
                 "workspaceId": workspace_id,
                 "cursor": cursor,
                 "limit": limit,
-                "sortKey": sort_key
+                "sortKey": sort_key,
+                "status": status
             }),
         )
         .await;
     }
 
-    codex_core::list_threads_core(&state.sessions, workspace_id, cursor, limit, sort_key).await
+    codex_core::list_threads_core(
+        &state.sessions,
+        workspace_id,
+        cursor,
+        limit,
+        sort_key,
+        status,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn refresh_threads(
+    workspace_id: String,
+    limit: Option<u32>,
+    sort_key: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "refresh_threads",
+            json!({ "workspaceId": workspace_id, "limit": limit, "sortKey": sort_key }),
+        )
+        .await;
+    }
+
+    codex_core::refresh_threads_core(
+        &state.sessions,
+        workspace_id,
+        limit,
+        sort_key,
+        |workspace_id, message| {
+            let _ = app.emit(
+                "app-server-event",
+                AppServerEvent {
+                    workspace_id: workspace_id.to_string(),
+                    message,
+                },
+            );
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn list_all_threads(
+    cursor: Option<String>,
+    limit: Option<u32>,
+    connect: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let connect = connect.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "list_all_threads",
+            json!({ "cursor": cursor, "limit": limit, "connect": connect }),
+        )
+        .await;
+    }
+
+    let app_state = &*state;
+    codex_core::list_all_threads_core(
+        &state.workspaces,
+        &state.sessions,
+        cursor,
+        limit,
+        connect.then_some(move |workspace_id: String| {
+            let app = app.clone();
+            async move {
+                workspaces_core::connect_workspace_core(
+                    workspace_id,
+                    &app_state.workspaces,
+                    &app_state.sessions,
+                    &app_state.app_settings,
+                    |entry, default_bin, codex_args, codex_home| {
+                        spawn_workspace_session(
+                            entry,
+                            default_bin,
+                            codex_args,
+                            app.clone(),
+                            codex_home,
+                        )
+                    },
+                )
+                .await
+            }
+        }),
+    )
+    .await
 }
 
 #[tauri::command]
@@ -184,6 +365,49 @@ pub(crate) async fn archive_thread(
     codex_core::archive_thread_core(&state.sessions, workspace_id, thread_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn bulk_archive_threads(
+    workspace_id: String,
+    inactive_before: i64,
+    dry_run: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BulkArchiveThreadsResult, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "bulk_archive_threads",
+            json!({
+                "workspaceId": workspace_id,
+                "inactiveBefore": inactive_before,
+                "dryRun": dry_run,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::bulk_archive_threads_core(
+        &state.workspaces,
+        &state.sessions,
+        workspace_id,
+        inactive_before,
+        dry_run,
+        |workspace_id, message| {
+            let _ = app.emit(
+                "app-server-event",
+                AppServerEvent {
+                    workspace_id: workspace_id.to_string(),
+                    message,
+                },
+            );
+        },
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn compact_thread(
     workspace_id: String,
@@ -204,6 +428,87 @@ pub(crate) async fn compact_thread(
     codex_core::compact_thread_core(&state.sessions, workspace_id, thread_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn get_thread_info(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ThreadInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_thread_info",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::get_thread_info_core(&state.sessions, workspace_id, thread_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn session_capabilities(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SessionCapabilities, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "session_capabilities",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::session_capabilities_core(&state.sessions, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn session_ping(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<SessionPing, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "session_ping",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::session_ping_core(&state.sessions, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn reset_thread_context(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "reset_thread_context",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    codex_core::reset_thread_context_core(&state.sessions, workspace_id, thread_id).await
+}
+
 #[tauri::command]
 pub(crate) async fn set_thread_name(
     workspace_id: String,
@@ -222,7 +527,14 @@ pub(crate) async fn set_thread_name(
         .await;
     }
 
-    codex_core::set_thread_name_core(&state.sessions, workspace_id, thread_id, name).await
+    codex_core::set_thread_name_core(
+        &state.sessions,
+        &TauriEventSink::new(app),
+        workspace_id,
+        thread_id,
+        name,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -269,6 +581,9 @@ pub(crate) async fn send_user_message(
 
     codex_core::send_user_message_core(
         &state.sessions,
+        &state.workspaces,
+        &state.app_settings,
+        &state.settings_path,
         workspace_id,
         thread_id,
         text,
@@ -281,6 +596,84 @@ pub(crate) async fn send_user_message(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn queue_user_message(
+    workspace_id: String,
+    thread_id: String,
+    text: String,
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    images: Option<Vec<String>>,
+    collaboration_mode: Option<Value>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<QueuedUserMessage, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let images = images.map(|paths| {
+            paths
+                .into_iter()
+                .map(remote_backend::normalize_path_for_remote)
+                .collect::<Vec<_>>()
+        });
+        let mut payload = Map::new();
+        payload.insert("workspaceId".to_string(), json!(workspace_id));
+        payload.insert("threadId".to_string(), json!(thread_id));
+        payload.insert("text".to_string(), json!(text));
+        payload.insert("model".to_string(), json!(model));
+        payload.insert("effort".to_string(), json!(effort));
+        payload.insert("accessMode".to_string(), json!(access_mode));
+        payload.insert("images".to_string(), json!(images));
+        if let Some(mode) = collaboration_mode {
+            if !mode.is_null() {
+                payload.insert("collaborationMode".to_string(), mode);
+            }
+        }
+        let response =
+            remote_backend::call_remote(&*state, app, "queue_user_message", Value::Object(payload))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::queue_user_message_core(
+        &state.sessions,
+        &state.workspaces,
+        &state.app_settings,
+        &state.settings_path,
+        workspace_id,
+        thread_id,
+        UserMessageInput {
+            text,
+            model,
+            effort,
+            access_mode,
+            images,
+            collaboration_mode,
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn cancel_queued_message(
+    workspace_id: String,
+    message_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_queued_message",
+            json!({ "workspaceId": workspace_id, "messageId": message_id }),
+        )
+        .await;
+    }
+
+    codex_core::cancel_queued_message_core(&state.sessions, workspace_id, message_id).await
+}
+
 #[tauri::command]
 pub(crate) async fn turn_steer(
     workspace_id: String,
@@ -340,7 +733,60 @@ pub(crate) async fn collaboration_mode_list(
         .await;
     }
 
-    codex_core::collaboration_mode_list_core(&state.sessions, workspace_id).await
+    codex_core::collaboration_mode_list_core(&state.sessions, &state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn set_thread_collaboration_mode(
+    workspace_id: String,
+    thread_id: String,
+    collaboration_mode: Option<Value>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "set_thread_collaboration_mode",
+            json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "collaborationMode": collaboration_mode,
+            }),
+        )
+        .await;
+    }
+
+    codex_core::set_thread_collaboration_mode_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+        workspace_id,
+        thread_id,
+        collaboration_mode,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn reset_thread_turn_count(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "reset_thread_turn_count",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await;
+    }
+
+    codex_core::reset_thread_turn_count_core(&state.sessions, workspace_id, thread_id).await
 }
 
 #[tauri::command]
@@ -410,6 +856,26 @@ pub(crate) async fn model_list(
     codex_core::model_list_core(&state.sessions, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn recent_models(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "recent_models",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(crate::shared::settings_core::recent_models_core(&state.app_settings, &workspace_id).await)
+}
+
 #[tauri::command]
 pub(crate) async fn account_rate_limits(
     workspace_id: String,
@@ -448,6 +914,26 @@ pub(crate) async fn account_read(
     codex_core::account_read_core(&state.sessions, &state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn codex_login_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<CodexLoginStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_login_status",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::codex_login_status_core(&state.sessions, &state.workspaces, workspace_id).await
+}
+
 #[tauri::command]
 pub(crate) async fn codex_login(
     workspace_id: String,
@@ -550,6 +1036,40 @@ pub(crate) async fn respond_to_server_request(
         .await
 }
 
+#[tauri::command]
+pub(crate) async fn workspace_activity(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceActivity>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workspace_activity", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(codex_core::workspace_activity_core(&state.sessions).await)
+}
+
+#[tauri::command]
+pub(crate) async fn list_pending_approvals(
+    workspace_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<PendingApproval>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "list_pending_approvals",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(codex_core::list_pending_approvals_core(&state.sessions, workspace_id).await)
+}
+
 #[tauri::command]
 pub(crate) async fn remember_approval_rule(
     workspace_id: String,
@@ -578,6 +1098,55 @@ pub(crate) async fn get_config_model(
     codex_core::get_config_model_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn get_workspace_codex_config_path(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceCodexConfigPath, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_workspace_codex_config_path",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::get_workspace_codex_config_path_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn estimate_message_cost(
+    workspace_id: String,
+    text: String,
+    model: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<MessageCostEstimate, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "estimate_message_cost",
+            json!({ "workspaceId": workspace_id, "text": text, "model": model }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    codex_core::estimate_message_cost_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        text,
+        model,
+    )
+    .await
+}
+
 /// Generates a commit message in the background without showing in the main chat
 #[tauri::command]
 pub(crate) async fn generate_commit_message(
@@ -1,6 +1,9 @@
 use tauri::{AppHandle, Emitter};
 
-use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::backend::events::{
+    AppServerEvent, EventSink, GitStatusChanged, SessionExited, TerminalExit, TerminalOutput,
+    ThreadRenamed, WorkspaceFileTail,
+};
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -25,4 +28,20 @@ impl EventSink for TauriEventSink {
     fn emit_terminal_exit(&self, event: TerminalExit) {
         let _ = self.app.emit("terminal-exit", event);
     }
+
+    fn emit_session_exited(&self, event: SessionExited) {
+        let _ = self.app.emit("session-exited", event);
+    }
+
+    fn emit_git_status_changed(&self, event: GitStatusChanged) {
+        let _ = self.app.emit("git-status-changed", event);
+    }
+
+    fn emit_thread_renamed(&self, event: ThreadRenamed) {
+        let _ = self.app.emit("thread-renamed", event);
+    }
+
+    fn emit_workspace_file_tail(&self, event: WorkspaceFileTail) {
+        let _ = self.app.emit("workspace-file-tail", event);
+    }
 }
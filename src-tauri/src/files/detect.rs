@@ -0,0 +1,185 @@
+use std::path::Path;
+
+pub(crate) const FALLBACK_MIME_TYPE: &str = "text/plain";
+
+/// Advisory type hint for a workspace file; viewers may ignore it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DetectedFileType {
+    pub(crate) mime_type: &'static str,
+    pub(crate) language: Option<&'static str>,
+}
+
+const EXTENSION_TYPES: &[(&str, &str, Option<&str>)] = &[
+    ("md", "text/markdown", Some("markdown")),
+    ("markdown", "text/markdown", Some("markdown")),
+    ("mdx", "text/markdown", Some("mdx")),
+    ("txt", "text/plain", None),
+    ("rs", "text/x-rust", Some("rust")),
+    ("ts", "text/typescript", Some("typescript")),
+    ("tsx", "text/tsx", Some("tsx")),
+    ("js", "text/javascript", Some("javascript")),
+    ("mjs", "text/javascript", Some("javascript")),
+    ("cjs", "text/javascript", Some("javascript")),
+    ("jsx", "text/jsx", Some("jsx")),
+    ("json", "application/json", Some("json")),
+    ("jsonc", "application/json", Some("jsonc")),
+    ("toml", "application/toml", Some("toml")),
+    ("yaml", "application/yaml", Some("yaml")),
+    ("yml", "application/yaml", Some("yaml")),
+    ("xml", "application/xml", Some("xml")),
+    ("html", "text/html", Some("html")),
+    ("htm", "text/html", Some("html")),
+    ("css", "text/css", Some("css")),
+    ("scss", "text/x-scss", Some("scss")),
+    ("py", "text/x-python", Some("python")),
+    ("rb", "text/x-ruby", Some("ruby")),
+    ("go", "text/x-go", Some("go")),
+    ("java", "text/x-java", Some("java")),
+    ("kt", "text/x-kotlin", Some("kotlin")),
+    ("swift", "text/x-swift", Some("swift")),
+    ("c", "text/x-c", Some("c")),
+    ("h", "text/x-c", Some("c")),
+    ("cc", "text/x-c++", Some("cpp")),
+    ("cpp", "text/x-c++", Some("cpp")),
+    ("hpp", "text/x-c++", Some("cpp")),
+    ("cs", "text/x-csharp", Some("csharp")),
+    ("php", "text/x-php", Some("php")),
+    ("sh", "text/x-shellscript", Some("bash")),
+    ("bash", "text/x-shellscript", Some("bash")),
+    ("zsh", "text/x-shellscript", Some("bash")),
+    ("sql", "application/sql", Some("sql")),
+    ("svg", "image/svg+xml", Some("xml")),
+    ("csv", "text/csv", None),
+    ("png", "image/png", None),
+    ("jpg", "image/jpeg", None),
+    ("jpeg", "image/jpeg", None),
+    ("gif", "image/gif", None),
+    ("webp", "image/webp", None),
+    ("pdf", "application/pdf", None),
+];
+
+const FILE_NAME_TYPES: &[(&str, &str, Option<&str>)] = &[
+    ("Dockerfile", "text/x-dockerfile", Some("dockerfile")),
+    ("Makefile", "text/x-makefile", Some("makefile")),
+    ("Cargo.lock", "application/toml", Some("toml")),
+];
+
+const MAGIC_TYPES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x7fELF", "application/x-elf"),
+    (b"\0asm", "application/wasm"),
+];
+
+const SHEBANG_TYPES: &[(&str, &str, &str)] = &[
+    ("python", "text/x-python", "python"),
+    ("node", "text/javascript", "javascript"),
+    ("bash", "text/x-shellscript", "bash"),
+    ("zsh", "text/x-shellscript", "bash"),
+    ("sh", "text/x-shellscript", "bash"),
+    ("ruby", "text/x-ruby", "ruby"),
+];
+
+fn sniff_magic(content: &[u8]) -> Option<&'static str> {
+    if content.len() >= 12 && &content[..4] == b"RIFF" && &content[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    MAGIC_TYPES
+        .iter()
+        .find(|(magic, _)| content.starts_with(magic))
+        .map(|(_, mime_type)| *mime_type)
+}
+
+fn sniff_shebang(content: &[u8]) -> Option<DetectedFileType> {
+    let first_line = content
+        .strip_prefix(b"#!")?
+        .split(|byte| *byte == b'\n')
+        .next()?;
+    let first_line = std::str::from_utf8(first_line).ok()?;
+    // `#!/usr/bin/env python3` and `#!/bin/bash -e` both name the interpreter
+    // in the last path segment of one of the first two words.
+    let interpreter = first_line
+        .split_whitespace()
+        .take(2)
+        .filter_map(|word| word.rsplit('/').next())
+        .find(|word| *word != "env")?;
+    SHEBANG_TYPES
+        .iter()
+        .find(|(prefix, _, _)| interpreter.starts_with(prefix))
+        .map(|(_, mime_type, language)| DetectedFileType {
+            mime_type,
+            language: Some(language),
+        })
+}
+
+/// Picks a type from the file name, then from the leading bytes (binary
+/// signatures and shebang lines), falling back to `text/plain`.
+pub(crate) fn detect_file_type(path: &Path, content: &[u8]) -> DetectedFileType {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let by_name = FILE_NAME_TYPES
+        .iter()
+        .find(|(name, _, _)| *name == file_name)
+        .or_else(|| {
+            let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+            EXTENSION_TYPES.iter().find(|(ext, _, _)| *ext == extension)
+        });
+    if let Some((_, mime_type, language)) = by_name {
+        return DetectedFileType {
+            mime_type,
+            language: *language,
+        };
+    }
+    if let Some(mime_type) = sniff_magic(content) {
+        return DetectedFileType {
+            mime_type,
+            language: None,
+        };
+    }
+    sniff_shebang(content).unwrap_or(DetectedFileType {
+        mime_type: FALLBACK_MIME_TYPE,
+        language: None,
+    })
+}
+
+/// Same heuristic as git: a NUL byte in the first 8000 bytes means binary.
+pub(crate) fn looks_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|byte| *byte == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_by_extension_then_content_and_falls_back_to_plain_text() {
+        let markdown = detect_file_type(Path::new("docs/README.MD"), b"# Title");
+        assert_eq!(markdown.mime_type, "text/markdown");
+        assert_eq!(markdown.language, Some("markdown"));
+
+        let dockerfile = detect_file_type(Path::new("Dockerfile"), b"FROM rust");
+        assert_eq!(dockerfile.language, Some("dockerfile"));
+
+        let png = detect_file_type(Path::new("assets/logo"), b"\x89PNG\r\n\x1a\n\0\0");
+        assert_eq!(png.mime_type, "image/png");
+        assert_eq!(png.language, None);
+
+        let script = detect_file_type(Path::new("bin/release"), b"#!/usr/bin/env python3\n");
+        assert_eq!(script.mime_type, "text/x-python");
+        assert_eq!(script.language, Some("python"));
+
+        let unknown = detect_file_type(Path::new("LICENSE"), b"MIT License");
+        assert_eq!(unknown.mime_type, FALLBACK_MIME_TYPE);
+        assert_eq!(unknown.language, None);
+
+        assert!(looks_binary(b"\x89PNG\r\n\x1a\n\0\0"));
+        assert!(!looks_binary("caf\u{e9}\n".as_bytes()));
+    }
+}
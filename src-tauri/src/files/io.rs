@@ -9,6 +9,15 @@ pub(crate) struct TextFileResponse {
     pub exists: bool,
     pub content: String,
     pub truncated: bool,
+    /// `content_hash` of what was read; pass it back as `expectedHash` when
+    /// writing. `None` when the file doesn't exist.
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FileWriteResponse {
+    pub hash: String,
 }
 
 fn missing_response() -> TextFileResponse {
@@ -16,9 +25,17 @@ fn missing_response() -> TextFileResponse {
         exists: false,
         content: String::new(),
         truncated: false,
+        hash: None,
     }
 }
 
+/// The git blob id of `content`, so it matches `git hash-object`.
+pub(crate) fn content_hash(content: &str) -> String {
+    git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
 fn resolve_root(
     root: &Path,
     root_context: &str,
@@ -88,6 +105,7 @@ pub(crate) fn read_text_file_within(
 
     Ok(TextFileResponse {
         exists: true,
+        hash: Some(content_hash(&content)),
         content,
         truncated: false,
     })
@@ -1,12 +1,15 @@
 use serde_json::json;
 use tauri::{AppHandle, State};
 
-use self::io::TextFileResponse;
+use self::io::{FileWriteResponse, TextFileResponse};
 use self::policy::{FileKind, FileScope};
 use crate::remote_backend;
-use crate::shared::files_core::{file_read_core, file_write_core};
+use crate::shared::files_core::{
+    file_read_core, file_write_core, get_effective_agents_md_core, AgentsMdFile,
+};
 use crate::state::AppState;
 
+pub(crate) mod detect;
 pub(crate) mod io;
 pub(crate) mod ops;
 pub(crate) mod policy;
@@ -37,11 +40,12 @@ async fn file_write_impl(
     kind: FileKind,
     workspace_id: Option<String>,
     content: String,
+    expected_hash: Option<String>,
     state: &AppState,
     app: &AppHandle,
-) -> Result<(), String> {
+) -> Result<FileWriteResponse, String> {
     if remote_backend::is_remote_mode(state).await {
-        remote_backend::call_remote(
+        let response = remote_backend::call_remote(
             state,
             app.clone(),
             "file_write",
@@ -50,13 +54,22 @@ async fn file_write_impl(
                 "kind": kind,
                 "workspaceId": workspace_id,
                 "content": content,
+                "expectedHash": expected_hash,
             }),
         )
         .await?;
-        return Ok(());
+        return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    file_write_core(&state.workspaces, scope, kind, workspace_id, content).await
+    file_write_core(
+        &state.workspaces,
+        scope,
+        kind,
+        workspace_id,
+        content,
+        expected_hash,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -76,8 +89,38 @@ pub(crate) async fn file_write(
     kind: FileKind,
     workspace_id: Option<String>,
     content: String,
+    expected_hash: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<FileWriteResponse, String> {
+    file_write_impl(
+        scope,
+        kind,
+        workspace_id,
+        content,
+        expected_hash,
+        &*state,
+        &app,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_effective_agents_md(
+    workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
-    file_write_impl(scope, kind, workspace_id, content, &*state, &app).await
+) -> Result<Vec<AgentsMdFile>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_effective_agents_md",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    get_effective_agents_md_core(&state.workspaces, &workspace_id).await
 }
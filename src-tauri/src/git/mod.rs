@@ -2,12 +2,18 @@ use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use tauri::{AppHandle, State};
 
+use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
-use crate::shared::git_ui_core;
+use crate::shared::{git_ui_core, git_watch_core};
 use crate::state::AppState;
 use crate::types::{
-    GitCommitDiff, GitFileDiff, GitHubIssuesResponse, GitHubPullRequestComment,
-    GitHubPullRequestDiff, GitHubPullRequestsResponse, GitLogResponse,
+    GitBranchPruneResult, GitCommitDiff, GitFileDiff, GitHubIssuesResponse,
+    GitHubPullRequestCheckout, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestReviewThread, GitHubPullRequestsResponse, GitHubRepoInfo, GitIncomingChanges,
+    GitLastCommit, GitLogResponse, GitMergedBranches, GitOperationProgress, GitPatchApplyResult,
+    GitPatchExport, GitPushResult, GitReflogEntry, GitReflogRestoreResult, GitRemote,
+    GitRepairLocksResult, GitStatusDetailed, GitWorkingTreeClean, LargeStagedFilesResponse,
+    RepoWorktreesDiffSummary, WorktreeBaseDiff,
 };
 
 async fn call_remote_if_enabled(
@@ -84,6 +90,82 @@ pub(crate) async fn get_git_status(
     git_ui_core::get_git_status_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn get_git_status_detailed(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitStatusDetailed, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_git_status_detailed",
+        json!({ "workspaceId": &workspace_id }),
+        GitStatusDetailed
+    );
+    git_ui_core::get_git_status_detailed_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn is_working_tree_clean(
+    workspace_id: String,
+    include_untracked: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitWorkingTreeClean, String> {
+    let include_untracked = include_untracked.unwrap_or(true);
+    try_remote_typed!(
+        state,
+        app,
+        "is_working_tree_clean",
+        json!({ "workspaceId": &workspace_id, "includeUntracked": include_untracked }),
+        GitWorkingTreeClean
+    );
+    git_ui_core::is_working_tree_clean_core(&state.workspaces, workspace_id, include_untracked)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn repo_worktrees_diff_summary(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<RepoWorktreesDiffSummary, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "repo_worktrees_diff_summary",
+        json!({ "workspaceId": &workspace_id }),
+        RepoWorktreesDiffSummary
+    );
+    git_ui_core::repo_worktrees_diff_summary_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn watch_git_status(
+    workspace_id: String,
+    enabled: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    let enabled = enabled.unwrap_or(true);
+    try_remote_value!(
+        state,
+        app,
+        "watch_git_status",
+        json!({ "workspaceId": &workspace_id, "enabled": enabled })
+    );
+    let watching = git_watch_core::watch_git_status_core(
+        &state.workspaces,
+        &state.git_watchers,
+        TauriEventSink::new(app),
+        workspace_id,
+        enabled,
+    )
+    .await?;
+    Ok(json!({ "watching": watching }))
+}
+
 #[tauri::command]
 pub(crate) async fn stage_git_file(
     workspace_id: String,
@@ -178,24 +260,64 @@ pub(crate) async fn commit_git(
     git_ui_core::commit_git_core(&state.workspaces, workspace_id, message).await
 }
 
+#[tauri::command]
+pub(crate) async fn check_large_staged_files(
+    workspace_id: String,
+    threshold_bytes: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<LargeStagedFilesResponse, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "check_large_staged_files",
+        json!({ "workspaceId": &workspace_id, "thresholdBytes": threshold_bytes }),
+        LargeStagedFilesResponse
+    );
+    git_ui_core::check_large_staged_files_core(&state.workspaces, workspace_id, threshold_bytes)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn git_repair_locks(
+    workspace_id: String,
+    remove: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitRepairLocksResult, String> {
+    let remove = remove.unwrap_or(false);
+    try_remote_typed!(
+        state,
+        app,
+        "git_repair_locks",
+        json!({ "workspaceId": &workspace_id, "remove": remove }),
+        GitRepairLocksResult
+    );
+    git_ui_core::git_repair_locks_core(&state.workspaces, workspace_id, remove).await
+}
+
 #[tauri::command]
 pub(crate) async fn push_git(
     workspace_id: String,
+    force: Option<String>,
+    remote: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
-    try_remote_unit!(
+) -> Result<GitPushResult, String> {
+    try_remote_typed!(
         state,
         app,
         "push_git",
-        json!({ "workspaceId": &workspace_id })
+        json!({ "workspaceId": &workspace_id, "force": &force, "remote": &remote }),
+        GitPushResult
     );
-    git_ui_core::push_git_core(&state.workspaces, workspace_id).await
+    git_ui_core::push_git_core(&state.workspaces, workspace_id, force, remote).await
 }
 
 #[tauri::command]
 pub(crate) async fn pull_git(
     workspace_id: String,
+    remote: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -203,14 +325,15 @@ pub(crate) async fn pull_git(
         state,
         app,
         "pull_git",
-        json!({ "workspaceId": &workspace_id })
+        json!({ "workspaceId": &workspace_id, "remote": &remote })
     );
-    git_ui_core::pull_git_core(&state.workspaces, workspace_id).await
+    git_ui_core::pull_git_core(&state.workspaces, workspace_id, remote).await
 }
 
 #[tauri::command]
 pub(crate) async fn fetch_git(
     workspace_id: String,
+    remote: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<(), String> {
@@ -218,9 +341,25 @@ pub(crate) async fn fetch_git(
         state,
         app,
         "fetch_git",
-        json!({ "workspaceId": &workspace_id })
+        json!({ "workspaceId": &workspace_id, "remote": &remote })
+    );
+    git_ui_core::fetch_git_core(&state.workspaces, workspace_id, remote).await
+}
+
+#[tauri::command]
+pub(crate) async fn list_git_remotes(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitRemote>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "list_git_remotes",
+        json!({ "workspaceId": &workspace_id }),
+        Vec<GitRemote>
     );
-    git_ui_core::fetch_git_core(&state.workspaces, workspace_id).await
+    git_ui_core::list_git_remotes_core(&state.workspaces, workspace_id).await
 }
 
 #[tauri::command]
@@ -271,23 +410,63 @@ pub(crate) async fn get_workspace_diff(
 #[tauri::command]
 pub(crate) async fn get_git_diffs(
     workspace_id: String,
+    include_untracked: Option<bool>,
+    max_diff_bytes: Option<usize>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<Vec<GitFileDiff>, String> {
+    let include_untracked = include_untracked.unwrap_or(true);
     try_remote_typed!(
         state,
         app,
         "get_git_diffs",
-        json!({ "workspaceId": &workspace_id }),
+        json!({
+            "workspaceId": &workspace_id,
+            "includeUntracked": include_untracked,
+            "maxDiffBytes": max_diff_bytes,
+        }),
         Vec<GitFileDiff>
     );
-    git_ui_core::get_git_diffs_core(&state.workspaces, &state.app_settings, workspace_id).await
+    git_ui_core::get_git_diffs_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        include_untracked,
+        max_diff_bytes,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_git_file_diff(
+    workspace_id: String,
+    path: String,
+    staged: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<GitFileDiff>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_git_file_diff",
+        json!({ "workspaceId": &workspace_id, "path": &path, "staged": staged }),
+        Option<GitFileDiff>
+    );
+    git_ui_core::get_git_file_diff_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        path,
+        staged,
+    )
+    .await
 }
 
 #[tauri::command]
 pub(crate) async fn get_git_log(
     workspace_id: String,
     limit: Option<usize>,
+    include_stats: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<GitLogResponse, String> {
@@ -295,10 +474,67 @@ pub(crate) async fn get_git_log(
         state,
         app,
         "get_git_log",
-        json!({ "workspaceId": &workspace_id, "limit": limit }),
+        json!({ "workspaceId": &workspace_id, "limit": limit, "includeStats": include_stats }),
         GitLogResponse
     );
-    git_ui_core::get_git_log_core(&state.workspaces, workspace_id, limit).await
+    git_ui_core::get_git_log_core(
+        &state.workspaces,
+        workspace_id,
+        limit,
+        include_stats.unwrap_or(false),
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn worktree_diff_vs_base(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorktreeBaseDiff, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "worktree_diff_vs_base",
+        json!({ "workspaceId": &workspace_id }),
+        WorktreeBaseDiff
+    );
+    git_ui_core::worktree_diff_vs_base_core(&state.workspaces, &state.app_settings, workspace_id)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn preview_incoming_changes(
+    workspace_id: String,
+    fetch: Option<bool>,
+    summary_only: Option<bool>,
+    max_diff_bytes: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitIncomingChanges, String> {
+    let fetch = fetch.unwrap_or(true);
+    let summary_only = summary_only.unwrap_or(false);
+    try_remote_typed!(
+        state,
+        app,
+        "preview_incoming_changes",
+        json!({
+            "workspaceId": &workspace_id,
+            "fetch": fetch,
+            "summaryOnly": summary_only,
+            "maxDiffBytes": max_diff_bytes,
+        }),
+        GitIncomingChanges
+    );
+    git_ui_core::preview_incoming_changes_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        fetch,
+        summary_only,
+        max_diff_bytes,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -319,6 +555,144 @@ pub(crate) async fn get_git_commit_diff(
         .await
 }
 
+#[tauri::command]
+pub(crate) async fn get_stash_diff(
+    workspace_id: String,
+    stash: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitCommitDiff>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_stash_diff",
+        json!({ "workspaceId": &workspace_id, "stash": &stash }),
+        Vec<GitCommitDiff>
+    );
+    git_ui_core::get_stash_diff_core(&state.workspaces, &state.app_settings, workspace_id, stash)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn export_git_patch(
+    workspace_id: String,
+    revision: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitPatchExport, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "export_git_patch",
+        json!({ "workspaceId": &workspace_id, "revision": &revision }),
+        GitPatchExport
+    );
+    git_ui_core::export_git_patch_core(&state.workspaces, workspace_id, revision).await
+}
+
+#[tauri::command]
+pub(crate) async fn apply_git_patch(
+    workspace_id: String,
+    patch: String,
+    check: Option<bool>,
+    three_way: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitPatchApplyResult, String> {
+    let check = check.unwrap_or(false);
+    let three_way = three_way.unwrap_or(false);
+    try_remote_typed!(
+        state,
+        app,
+        "apply_git_patch",
+        json!({
+            "workspaceId": &workspace_id,
+            "patch": &patch,
+            "check": check,
+            "threeWay": three_way,
+        }),
+        GitPatchApplyResult
+    );
+    git_ui_core::apply_git_patch_core(&state.workspaces, workspace_id, patch, check, three_way)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_last_commit(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitLastCommit, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_last_commit",
+        json!({ "workspaceId": &workspace_id }),
+        GitLastCommit
+    );
+    git_ui_core::get_last_commit_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn git_operation_progress(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitOperationProgress, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "git_operation_progress",
+        json!({ "workspaceId": &workspace_id }),
+        GitOperationProgress
+    );
+    git_ui_core::git_operation_progress_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn git_reflog(
+    workspace_id: String,
+    ref_name: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitReflogEntry>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "git_reflog",
+        json!({ "workspaceId": &workspace_id, "refName": &ref_name, "limit": limit }),
+        Vec<GitReflogEntry>
+    );
+    git_ui_core::git_reflog_core(&state.workspaces, workspace_id, ref_name, limit).await
+}
+
+#[tauri::command]
+pub(crate) async fn restore_from_reflog(
+    workspace_id: String,
+    sha: String,
+    branch: Option<String>,
+    confirm: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitReflogRestoreResult, String> {
+    let confirm = confirm.unwrap_or(false);
+    try_remote_typed!(
+        state,
+        app,
+        "restore_from_reflog",
+        json!({
+            "workspaceId": &workspace_id,
+            "sha": &sha,
+            "branch": &branch,
+            "confirm": confirm,
+        }),
+        GitReflogRestoreResult
+    );
+    git_ui_core::restore_from_reflog_core(&state.workspaces, workspace_id, sha, branch, confirm)
+        .await
+}
+
 #[tauri::command]
 pub(crate) async fn get_git_remote(
     workspace_id: String,
@@ -335,6 +709,23 @@ pub(crate) async fn get_git_remote(
     git_ui_core::get_git_remote_core(&state.workspaces, workspace_id).await
 }
 
+#[tauri::command]
+pub(crate) async fn get_github_repo_info(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitHubRepoInfo, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_github_repo_info",
+        json!({ "workspaceId": &workspace_id }),
+        GitHubRepoInfo
+    );
+    git_ui_core::get_github_repo_info_core(&state.workspaces, &state.app_settings, workspace_id)
+        .await
+}
+
 #[tauri::command]
 pub(crate) async fn get_github_issues(
     workspace_id: String,
@@ -348,7 +739,7 @@ pub(crate) async fn get_github_issues(
         json!({ "workspaceId": &workspace_id }),
         GitHubIssuesResponse
     );
-    git_ui_core::get_github_issues_core(&state.workspaces, workspace_id).await
+    git_ui_core::get_github_issues_core(&state.workspaces, &state.app_settings, workspace_id).await
 }
 
 #[tauri::command]
@@ -364,7 +755,8 @@ pub(crate) async fn get_github_pull_requests(
         json!({ "workspaceId": &workspace_id }),
         GitHubPullRequestsResponse
     );
-    git_ui_core::get_github_pull_requests_core(&state.workspaces, workspace_id).await
+    git_ui_core::get_github_pull_requests_core(&state.workspaces, &state.app_settings, workspace_id)
+        .await
 }
 
 #[tauri::command]
@@ -381,7 +773,13 @@ pub(crate) async fn get_github_pull_request_diff(
         json!({ "workspaceId": &workspace_id, "prNumber": pr_number }),
         Vec<GitHubPullRequestDiff>
     );
-    git_ui_core::get_github_pull_request_diff_core(&state.workspaces, workspace_id, pr_number).await
+    git_ui_core::get_github_pull_request_diff_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        pr_number,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -398,8 +796,61 @@ pub(crate) async fn get_github_pull_request_comments(
         json!({ "workspaceId": &workspace_id, "prNumber": pr_number }),
         Vec<GitHubPullRequestComment>
     );
-    git_ui_core::get_github_pull_request_comments_core(&state.workspaces, workspace_id, pr_number)
-        .await
+    git_ui_core::get_github_pull_request_comments_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        pr_number,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn get_github_pull_request_review_threads(
+    workspace_id: String,
+    pr_number: u64,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitHubPullRequestReviewThread>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "get_github_pull_request_review_threads",
+        json!({ "workspaceId": &workspace_id, "prNumber": pr_number }),
+        Vec<GitHubPullRequestReviewThread>
+    );
+    git_ui_core::get_github_pull_request_review_threads_core(
+        &state.workspaces,
+        &state.app_settings,
+        workspace_id,
+        pr_number,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn checkout_github_pull_request(
+    workspace_id: String,
+    pr_number: u64,
+    update: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitHubPullRequestCheckout, String> {
+    let update = update.unwrap_or(false);
+    try_remote_typed!(
+        state,
+        app,
+        "checkout_github_pull_request",
+        json!({ "workspaceId": &workspace_id, "prNumber": pr_number, "update": update }),
+        GitHubPullRequestCheckout
+    );
+    git_ui_core::checkout_github_pull_request_core(
+        &state.workspaces,
+        workspace_id,
+        pr_number,
+        update,
+    )
+    .await
 }
 
 #[tauri::command]
@@ -448,3 +899,54 @@ pub(crate) async fn create_git_branch(
     );
     git_ui_core::create_git_branch_core(&state.workspaces, workspace_id, name).await
 }
+
+#[tauri::command]
+pub(crate) async fn list_merged_branches(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitMergedBranches, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "list_merged_branches",
+        json!({ "workspaceId": &workspace_id }),
+        GitMergedBranches
+    );
+    git_ui_core::list_merged_branches_core(&state.workspaces, workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn prune_merged_branches(
+    workspace_id: String,
+    branches: Vec<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<GitBranchPruneResult>, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "prune_merged_branches",
+        json!({ "workspaceId": &workspace_id, "branches": &branches }),
+        Vec<GitBranchPruneResult>
+    );
+    git_ui_core::prune_merged_branches_core(&state.workspaces, workspace_id, branches).await
+}
+
+#[tauri::command]
+pub(crate) async fn create_and_publish_branch(
+    workspace_id: String,
+    name: String,
+    remote: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<GitPushResult, String> {
+    try_remote_typed!(
+        state,
+        app,
+        "create_and_publish_branch",
+        json!({ "workspaceId": &workspace_id, "name": &name, "remote": &remote }),
+        GitPushResult
+    );
+    git_ui_core::create_and_publish_branch_core(&state.workspaces, workspace_id, name, remote).await
+}
@@ -4,7 +4,8 @@ use std::path::{Path, PathBuf};
 use git2::{DiffOptions, Repository, Tree};
 use ignore::WalkBuilder;
 
-use crate::types::{GitLogEntry, WorkspaceEntry};
+use crate::shared::skip_dirs::should_skip_dir;
+use crate::types::{GitHubRepoSlug, GitLogEntry, WorkspaceEntry};
 use crate::utils::normalize_git_path;
 
 pub(crate) fn image_mime_type(path: &str) -> Option<&'static str> {
@@ -33,6 +34,7 @@ pub(crate) fn commit_to_entry(commit: git2::Commit) -> GitLogEntry {
         summary,
         author,
         timestamp,
+        stats: None,
     }
 }
 
@@ -92,7 +94,7 @@ pub(crate) fn diff_patch_to_string(patch: &mut git2::Patch) -> Result<String, gi
 
 #[cfg(test)]
 mod tests {
-    use super::{checkout_branch, image_mime_type};
+    use super::{checkout_branch, image_mime_type, parse_github_remote};
     use git2::Repository;
     use std::fs;
     use std::path::Path;
@@ -106,6 +108,60 @@ mod tests {
         assert_eq!(image_mime_type("readme.txt"), None);
     }
 
+    #[test]
+    fn parse_github_remote_handles_ssh_https_and_enterprise() {
+        let cases = [
+            (
+                "git@github.com:openai/codex.git",
+                "github.com",
+                "openai",
+                "codex",
+            ),
+            (
+                "ssh://git@ssh.github.com:443/openai/codex",
+                "github.com",
+                "openai",
+                "codex",
+            ),
+            (
+                "https://token@github.com/openai/codex.git/",
+                "github.com",
+                "openai",
+                "codex",
+            ),
+            (
+                "https://Git.Corp.example/team/app",
+                "git.corp.example",
+                "team",
+                "app",
+            ),
+            (
+                "git@acme.ghe.com:team/app.git",
+                "acme.ghe.com",
+                "team",
+                "app",
+            ),
+        ];
+        let github_hosts = vec![" git.corp.example ".to_string()];
+        for (url, host, owner, repo) in cases {
+            let slug = parse_github_remote(url, &github_hosts).expect(url);
+            assert_eq!(
+                (slug.host.as_str(), slug.owner.as_str(), slug.repo.as_str()),
+                (host, owner, repo)
+            );
+        }
+        for url in [
+            "https://gitlab.com/team/app.git",
+            "https://github.example.com/team/app",
+            "https://my-github-mirror.example/team/app",
+            "https://git.corp.example/team/app",
+            "/srv/git/app.git",
+            "https://github.com/only-owner",
+        ] {
+            assert!(parse_github_remote(url, &[]).is_none(), "{url}");
+        }
+    }
+
     #[test]
     fn checkout_branch_missing_does_not_change_head() {
         let root = std::env::temp_dir().join(format!(
@@ -136,31 +192,58 @@ mod tests {
     }
 }
 
-pub(crate) fn parse_github_repo(remote_url: &str) -> Option<String> {
+/// github.com, GitHub Enterprise Cloud's `*.ghe.com`, and the GitHub
+/// Enterprise Server hosts in `github_hosts`. Other Enterprise Server hosts
+/// can't be told apart from any other git host by name.
+fn is_github_host(host: &str, github_hosts: &[String]) -> bool {
+    host == "github.com"
+        || host.ends_with(".github.com")
+        || host.ends_with(".ghe.com")
+        || github_hosts
+            .iter()
+            .any(|configured| configured.trim().eq_ignore_ascii_case(host))
+}
+
+/// Parses the host and `owner/repo` out of a GitHub remote URL. Accepts
+/// scp-style SSH (`git@host:owner/repo.git`) as well as `ssh://`, `https://`
+/// and `git://` URLs, on github.com and GitHub Enterprise hosts;
+/// `github_hosts` is the `githubEnterpriseHosts` setting. GitHub's
+/// SSH-over-443 host `ssh.github.com` is reported as `github.com`, the host
+/// its API and `gh` expect.
+pub(crate) fn parse_github_remote(
+    remote_url: &str,
+    github_hosts: &[String],
+) -> Option<GitHubRepoSlug> {
     let trimmed = remote_url.trim();
-    if trimmed.is_empty() {
+    let (authority, path) = match trimmed.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        None => {
+            let (authority, path) = trimmed.split_once(':')?;
+            if authority.contains('/') {
+                return None;
+            }
+            (authority, path)
+        }
+    };
+    let host = authority.rsplit('@').next()?;
+    let mut host = host.split(':').next()?.to_ascii_lowercase();
+    if host == "ssh.github.com" {
+        host = "github.com".to_string();
+    }
+    if !is_github_host(&host, github_hosts) {
         return None;
     }
-    let mut path = if trimmed.starts_with("git@github.com:") {
-        trimmed.trim_start_matches("git@github.com:").to_string()
-    } else if trimmed.starts_with("ssh://git@github.com/") {
-        trimmed
-            .trim_start_matches("ssh://git@github.com/")
-            .to_string()
-    } else if let Some(index) = trimmed.find("github.com/") {
-        trimmed[index + "github.com/".len()..].to_string()
-    } else {
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
         return None;
-    };
-    path = path
-        .trim_end_matches(".git")
-        .trim_end_matches('/')
-        .to_string();
-    if path.is_empty() {
-        None
-    } else {
-        Some(path)
     }
+    Some(GitHubRepoSlug {
+        host,
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+    })
 }
 
 pub(crate) fn resolve_git_root(entry: &WorkspaceEntry) -> Result<PathBuf, String> {
@@ -186,13 +269,6 @@ pub(crate) fn resolve_git_root(entry: &WorkspaceEntry) -> Result<PathBuf, String
     }
 }
 
-fn should_skip_dir(name: &str) -> bool {
-    matches!(
-        name,
-        ".git" | "node_modules" | "dist" | "target" | "release-artifacts"
-    )
-}
-
 pub(crate) fn list_git_roots(root: &Path, max_depth: usize, max_results: usize) -> Vec<String> {
     if !root.is_dir() {
         return Vec::new();
@@ -211,7 +287,7 @@ pub(crate) fn list_git_roots(root: &Path, max_depth: usize, max_results: usize)
             }
             if entry.file_type().is_some_and(|ft| ft.is_dir()) {
                 let name = entry.file_name().to_string_lossy();
-                if should_skip_dir(&name) {
+                if should_skip_dir(&name, &[]) {
                     return false;
                 }
             }
@@ -201,50 +201,101 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             settings::get_app_settings,
             settings::update_app_settings,
+            settings::validate_app_settings,
             settings::get_codex_config_path,
             files::file_read,
             files::file_write,
+            files::get_effective_agents_md,
             codex::get_config_model,
+            codex::get_workspace_codex_config_path,
+            codex::estimate_message_cost,
             menu::menu_set_accelerators,
             codex::codex_doctor,
+            codex::get_default_codex_bin,
+            codex::set_default_codex_bin,
+            codex::daemon_doctor,
+            codex::version,
             codex::codex_update,
             workspaces::list_workspaces,
+            workspaces::workspaces_etag,
             workspaces::is_workspace_path_dir,
             workspaces::add_workspace,
             workspaces::add_clone,
+            workspaces::preview_worktree_path,
             workspaces::add_worktree,
+            workspaces::fork_thread_to_worktree,
+            workspaces::worktree_from_stash,
             workspaces::worktree_setup_status,
             workspaces::worktree_setup_mark_ran,
             workspaces::remove_workspace,
             workspaces::remove_worktree,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
+            workspaces::move_worktree,
             workspaces::apply_worktree_changes,
             workspaces::update_workspace_settings,
+            workspaces::get_codex_tools,
+            workspaces::set_codex_tool_enabled,
             workspaces::update_workspace_codex_bin,
+            workspaces::set_workspace_pinned,
+            workspaces::set_workspace_auto_connect,
+            workspaces::set_default_effort,
+            workspaces::set_default_collaboration_mode,
+            workspaces::workspace_timeline,
+            workspaces::session_last_error,
             codex::start_thread,
             codex::send_user_message,
+            codex::queue_user_message,
+            codex::cancel_queued_message,
             codex::turn_steer,
             codex::turn_interrupt,
             codex::start_review,
             codex::respond_to_server_request,
+            codex::workspace_activity,
+            codex::list_pending_approvals,
             codex::remember_approval_rule,
             codex::generate_commit_message,
             codex::generate_run_metadata,
             codex::resume_thread,
             codex::fork_thread,
             codex::list_threads,
+            codex::list_all_threads,
+            codex::refresh_threads,
             codex::list_mcp_server_status,
             codex::archive_thread,
+            codex::bulk_archive_threads,
             codex::compact_thread,
+            codex::get_thread_info,
+            codex::session_capabilities,
+            codex::session_ping,
+            codex::reset_thread_context,
             codex::set_thread_name,
             codex::collaboration_mode_list,
+            codex::set_thread_collaboration_mode,
+            codex::reset_thread_turn_count,
             workspaces::connect_workspace,
+            workspaces::list_recent_workspaces,
+            workspaces::record_recent_workspace,
+            workspaces::connect_all_workspaces,
             git::get_git_status,
+            git::get_git_status_detailed,
+            git::is_working_tree_clean,
+            git::repo_worktrees_diff_summary,
+            git::watch_git_status,
             git::list_git_roots,
             git::get_git_diffs,
+            git::get_git_file_diff,
             git::get_git_log,
+            git::preview_incoming_changes,
+            git::worktree_diff_vs_base,
             git::get_git_commit_diff,
+            git::get_stash_diff,
+            git::export_git_patch,
+            git::apply_git_patch,
+            git::get_last_commit,
+            git::git_operation_progress,
+            git::git_reflog,
+            git::restore_from_reflog,
             git::get_git_remote,
             git::stage_git_file,
             git::stage_git_all,
@@ -252,24 +303,43 @@ pub fn run() {
             git::revert_git_file,
             git::revert_git_all,
             git::commit_git,
+            git::check_large_staged_files,
+            git::git_repair_locks,
             git::push_git,
             git::pull_git,
             git::fetch_git,
+            git::list_git_remotes,
             git::sync_git,
+            git::get_github_repo_info,
             git::get_github_issues,
             git::get_github_pull_requests,
             git::get_github_pull_request_diff,
             git::get_github_pull_request_comments,
+            git::get_github_pull_request_review_threads,
+            git::checkout_github_pull_request,
             workspaces::list_workspace_files,
             workspaces::read_workspace_file,
+            workspaces::rename_workspace_file,
+            workspaces::create_workspace_file,
+            workspaces::delete_workspace_file,
+            workspaces::tail_workspace_file,
+            workspaces::untail_workspace_file,
+            workspaces::grep_workspace,
+            workspaces::cancel_workspace_walk,
             workspaces::open_workspace_in,
+            workspaces::reveal_workspace_path,
             workspaces::get_open_app_icon,
             git::list_git_branches,
             git::checkout_git_branch,
             git::create_git_branch,
+            git::create_and_publish_branch,
+            git::list_merged_branches,
+            git::prune_merged_branches,
             codex::model_list,
+            codex::recent_models,
             codex::account_rate_limits,
             codex::account_read,
+            codex::codex_login_status,
             codex::codex_login,
             codex::codex_login_cancel,
             codex::skills_list,
@@ -294,6 +364,7 @@ pub fn run() {
             dictation::dictation_stop,
             dictation::dictation_cancel,
             local_usage::local_usage_snapshot,
+            local_usage::refresh_local_usage,
             notifications::is_macos_debug_build,
             notifications::send_notification_fallback,
             orbit::orbit_connect_test,
@@ -304,6 +375,7 @@ pub fn run() {
             orbit::orbit_runner_stop,
             orbit::orbit_runner_status,
             tailscale::tailscale_status,
+            tailscale::tailscale_verify_remote_host,
             tailscale::tailscale_daemon_command_preview,
             tailscale::tailscale_daemon_start,
             tailscale::tailscale_daemon_stop,
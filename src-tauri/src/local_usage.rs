@@ -4,7 +4,7 @@ use tauri::{AppHandle, State};
 use crate::remote_backend;
 use crate::shared::local_usage_core;
 use crate::state::AppState;
-use crate::types::LocalUsageSnapshot;
+use crate::types::{LocalUsageRefreshResult, LocalUsageSnapshot};
 
 #[tauri::command]
 pub(crate) async fn local_usage_snapshot(
@@ -26,3 +26,24 @@ pub(crate) async fn local_usage_snapshot(
 
     local_usage_core::local_usage_snapshot_core(&state.workspaces, days, workspace_path).await
 }
+
+#[tauri::command]
+pub(crate) async fn refresh_local_usage(
+    days: Option<u32>,
+    workspace_path: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<LocalUsageRefreshResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "refresh_local_usage",
+            json!({ "days": days, "workspacePath": workspace_path }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    local_usage_core::refresh_local_usage_core(&state.workspaces, days, workspace_path).await
+}
@@ -124,31 +124,67 @@ fn can_retry_after_disconnect(method: &str) -> bool {
         method,
         "account_rate_limits"
             | "account_read"
+            | "codex_login_status"
             | "apps_list"
             | "collaboration_mode_list"
             | "connect_workspace"
+            | "daemon_doctor"
+            | "version"
+            | "workspace_activity"
+            | "list_pending_approvals"
+            | "workspace_timeline"
+            | "estimate_message_cost"
+            | "export_git_patch"
             | "file_read"
+            | "get_effective_agents_md"
             | "get_config_model"
+            | "get_default_codex_bin"
+            | "get_thread_info"
+            | "get_workspace_codex_config_path"
             | "get_git_commit_diff"
+            | "get_stash_diff"
+            | "get_last_commit"
+            | "git_operation_progress"
+            | "git_reflog"
             | "get_git_diffs"
+            | "get_git_file_diff"
             | "get_git_log"
+            | "list_git_remotes"
+            | "list_merged_branches"
             | "get_git_remote"
             | "get_git_status"
+            | "get_git_status_detailed"
+            | "is_working_tree_clean"
+            | "repo_worktrees_diff_summary"
+            | "check_large_staged_files"
             | "get_github_issues"
+            | "get_github_repo_info"
             | "get_github_pull_request_comments"
+            | "get_github_pull_request_review_threads"
             | "get_github_pull_request_diff"
             | "get_github_pull_requests"
+            | "grep_workspace"
+            | "recent_models"
+            | "get_codex_tools"
             | "is_workspace_path_dir"
             | "list_git_branches"
             | "list_git_roots"
             | "list_mcp_server_status"
             | "list_threads"
+            | "list_all_threads"
             | "local_usage_snapshot"
+            | "refresh_local_usage"
+            | "worktree_diff_vs_base"
             | "list_workspace_files"
             | "list_workspaces"
+            | "workspaces_etag"
+            | "list_recent_workspaces"
             | "model_list"
             | "read_workspace_file"
             | "resume_thread"
+            | "session_last_error"
+            | "session_capabilities"
+            | "session_ping"
             | "skills_list"
             | "worktree_setup_status"
     )
@@ -187,7 +223,7 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     if matches!(transport_kind, RemoteTransportKind::Tcp) {
         if let Some(token) = auth_token {
             client
-                .call("auth", json!({ "token": token }))
+                .call("auth", json!({ "token": token, "acceptsGzip": true }))
                 .await
                 .map(|_| ())?;
         }
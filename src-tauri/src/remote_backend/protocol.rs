@@ -1,3 +1,7 @@
+use std::io::Read;
+
+use base64::Engine;
+use flate2::read::GzDecoder;
 use serde_json::{json, Value};
 
 pub(crate) const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
@@ -23,8 +27,25 @@ pub(crate) fn build_request_line(id: u64, method: &str, params: Value) -> Result
     serde_json::to_string(&request).map_err(|err| err.to_string())
 }
 
+/// Unwraps a `{"encoding":"gzip","payload":...}` frame sent by the daemon
+/// once `acceptsGzip` was negotiated during auth.
+fn inflate_gzip_frame(frame: &Value) -> Option<Value> {
+    let payload = frame.get("payload").and_then(|value| value.as_str())?;
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(payload)
+        .ok()?;
+    let mut decoded = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut decoded)
+        .ok()?;
+    serde_json::from_str(&decoded).ok()
+}
+
 pub(crate) fn parse_incoming_line(line: &str) -> Option<IncomingMessage> {
-    let message: Value = serde_json::from_str(line).ok()?;
+    let mut message: Value = serde_json::from_str(line).ok()?;
+    if message.get("encoding").and_then(|value| value.as_str()) == Some("gzip") {
+        message = inflate_gzip_frame(&message)?;
+    }
 
     if let Some(id) = message.get("id").and_then(|value| value.as_u64()) {
         if let Some(error) = message.get("error") {
@@ -147,6 +147,18 @@ pub(crate) async fn dispatch_incoming_line(
             "terminal-exit" => {
                 let _ = app.emit("terminal-exit", params);
             }
+            "session-exited" => {
+                let _ = app.emit("session-exited", params);
+            }
+            "git-status-changed" => {
+                let _ = app.emit("git-status-changed", params);
+            }
+            "thread-renamed" => {
+                let _ = app.emit("thread-renamed", params);
+            }
+            "workspace-file-tail" => {
+                let _ = app.emit("workspace-file-tail", params);
+            }
             _ => {}
         },
     }
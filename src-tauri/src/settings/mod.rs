@@ -1,10 +1,12 @@
+use serde_json::Value;
 use tauri::{State, Window};
 
 use crate::shared::settings_core::{
     get_app_settings_core, get_codex_config_path_core, update_app_settings_core,
+    validate_app_settings_core,
 };
 use crate::state::AppState;
-use crate::types::{AppSettings, BackendMode, RemoteBackendProvider};
+use crate::types::{AppSettings, AppSettingsValidation, BackendMode, RemoteBackendProvider};
 use crate::window;
 
 #[tauri::command]
@@ -34,6 +36,13 @@ pub(crate) async fn update_app_settings(
     Ok(updated)
 }
 
+#[tauri::command]
+pub(crate) async fn validate_app_settings(
+    settings: Value,
+) -> Result<AppSettingsValidation, String> {
+    validate_app_settings_core(settings)
+}
+
 #[tauri::command]
 pub(crate) async fn get_codex_config_path() -> Result<String, String> {
     get_codex_config_path_core()
@@ -3,6 +3,8 @@ use serde_json::{Map, Value};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::types::CodexLoginStatus;
+
 #[derive(Clone, Debug)]
 pub(crate) struct AuthAccount {
     pub(crate) email: Option<String>,
@@ -60,6 +62,24 @@ pub(crate) fn build_account_response(
     Value::Object(result)
 }
 
+/// Reduces a `build_account_response` value to a login status; a null
+/// account means not authenticated rather than an error.
+pub(crate) fn login_status_from_account(account_response: &Value) -> CodexLoginStatus {
+    let account = account_response
+        .get("account")
+        .filter(|value| !value.is_null());
+    let field = |key: &str| normalize_string(account.and_then(|account| account.get(key)));
+    CodexLoginStatus {
+        authenticated: account.is_some(),
+        auth_mode: field("type").map(|value| value.to_ascii_lowercase()),
+        email: field("email"),
+        plan_type: field("planType"),
+        requires_openai_auth: account_response
+            .get("requiresOpenaiAuth")
+            .and_then(Value::as_bool),
+    }
+}
+
 pub(crate) fn read_auth_account(codex_home: Option<PathBuf>) -> Option<AuthAccount> {
     let codex_home = codex_home?;
     let auth_path = codex_home.join("auth.json");
@@ -198,6 +218,28 @@ mod tests {
         assert_eq!(account.get("type").and_then(Value::as_str), Some("chatgpt"));
     }
 
+    #[test]
+    fn login_status_reports_unauthenticated_without_an_account() {
+        let status = login_status_from_account(&build_account_response(None, None));
+        assert!(!status.authenticated);
+        assert_eq!(status.auth_mode, None);
+
+        let response = Some(json!({
+            "account": { "type": "apikey" },
+            "requiresOpenaiAuth": true
+        }));
+        let status = login_status_from_account(&build_account_response(response, None));
+        assert!(status.authenticated);
+        assert_eq!(status.auth_mode.as_deref(), Some("apikey"));
+        assert_eq!(status.email, None);
+        assert_eq!(status.requires_openai_auth, Some(true));
+
+        let status =
+            login_status_from_account(&build_account_response(None, Some(fallback_account())));
+        assert_eq!(status.email.as_deref(), Some("chatgpt@example.com"));
+        assert_eq!(status.plan_type.as_deref(), Some("plus"));
+    }
+
     #[test]
     fn build_account_response_allows_fallback_for_chatgpt_type() {
         let response = Some(json!({
@@ -1,6 +1,7 @@
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::io::ErrorKind;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{mpsc, Mutex};
@@ -9,8 +10,10 @@ use tokio::time::timeout;
 use crate::backend::app_server::{
     build_codex_command_with_bin, build_codex_path_env, check_codex_installation, WorkspaceSession,
 };
+use crate::shared::codex_core::app_server_result;
 use crate::shared::process_core::tokio_command;
-use crate::types::AppSettings;
+use crate::shared::settings_core;
+use crate::types::{AppSettings, CodexDoctorReport, DoctorCheck};
 
 const DEFAULT_COMMIT_MESSAGE_PROMPT: &str = "Generate a concise git commit message for the following changes. \
 Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
@@ -148,11 +151,115 @@ pub(crate) fn sanitize_run_worktree_name(value: &str) -> String {
     format!("feat/{}", cleaned.trim_start_matches('/'))
 }
 
+fn doctor_check(
+    name: &str,
+    status: &str,
+    message: String,
+    remediation: Option<&str>,
+) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status: status.to_string(),
+        message,
+        remediation: remediation.map(str::to_string),
+    }
+}
+
+/// Parses config.toml when present; `(valid, details)`.
+fn check_codex_config(path: Option<&std::path::Path>) -> (bool, Option<String>) {
+    let Some(path) = path else {
+        return (false, Some("Unable to resolve CODEX_HOME.".to_string()));
+    };
+    match std::fs::read_to_string(path) {
+        Ok(contents) => match toml::from_str::<toml::Value>(&contents) {
+            Ok(_) => (true, None),
+            Err(err) => (false, Some(format!("Invalid config.toml: {err}"))),
+        },
+        Err(err) if err.kind() == ErrorKind::NotFound => (true, None),
+        Err(err) => (false, Some(format!("Failed to read config.toml: {err}"))),
+    }
+}
+
+fn codex_doctor_checks(report: &CodexDoctorReport) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    checks.push(match report.version.as_deref() {
+        Some(version) => doctor_check("codex", "pass", format!("Codex {version}"), None),
+        None if report.binary_found => doctor_check(
+            "codex",
+            "warn",
+            "Codex CLI ran but did not report a version.".to_string(),
+            None,
+        ),
+        None => doctor_check(
+            "codex",
+            "fail",
+            report
+                .details
+                .clone()
+                .unwrap_or_else(|| "Codex CLI not found.".to_string()),
+            Some("Install the Codex CLI or set its path in settings."),
+        ),
+    });
+    if report.binary_found {
+        checks.push(if report.app_server_ok {
+            doctor_check(
+                "appServer",
+                "pass",
+                "`codex app-server` is available.".to_string(),
+                None,
+            )
+        } else {
+            doctor_check(
+                "appServer",
+                "fail",
+                "Failed to run `codex app-server --help`.".to_string(),
+                Some("Update the Codex CLI to a version that ships app-server."),
+            )
+        });
+    }
+    checks.push(if report.node_ok {
+        doctor_check(
+            "node",
+            "pass",
+            format!("Node {}", report.node_version.as_deref().unwrap_or("found")),
+            None,
+        )
+    } else {
+        doctor_check(
+            "node",
+            "warn",
+            report
+                .node_details
+                .clone()
+                .unwrap_or_else(|| "Node is unavailable.".to_string()),
+            Some("Install Node.js and make sure it is on PATH."),
+        )
+    });
+    checks.push(if report.config_valid {
+        let message = match report.config_path.as_deref() {
+            Some(path) if std::path::Path::new(path).exists() => format!("{path} is valid."),
+            _ => "No config.toml; using Codex defaults.".to_string(),
+        };
+        doctor_check("config", "pass", message, None)
+    } else {
+        doctor_check(
+            "config",
+            "warn",
+            report
+                .config_details
+                .clone()
+                .unwrap_or_else(|| "config.toml could not be checked.".to_string()),
+            Some("Fix the syntax error in config.toml or remove the file."),
+        )
+    });
+    checks
+}
+
 pub(crate) async fn codex_doctor_core(
     app_settings: &Mutex<AppSettings>,
     codex_bin: Option<String>,
     codex_args: Option<String>,
-) -> Result<Value, String> {
+) -> Result<CodexDoctorReport, String> {
     let (default_bin, default_args) = {
         let settings = app_settings.lock().await;
         (settings.codex_bin.clone(), settings.codex_args.clone())
@@ -166,19 +273,27 @@ pub(crate) async fn codex_doctor_core(
         .filter(|value| !value.trim().is_empty())
         .or(default_args);
     let path_env = build_codex_path_env(resolved.as_deref());
-    let version = check_codex_installation(resolved.clone()).await?;
-    let mut command = build_codex_command_with_bin(
-        resolved.clone(),
-        resolved_args.as_deref(),
-        vec!["app-server".to_string(), "--help".to_string()],
-    )?;
-    command.stdout(std::process::Stdio::piped());
-    command.stderr(std::process::Stdio::piped());
-    let app_server_ok = match timeout(Duration::from_secs(5), command.output()).await {
-        Ok(result) => result
-            .map(|output| output.status.success())
-            .unwrap_or(false),
-        Err(_) => false,
+    let (binary_found, version, install_error) =
+        match check_codex_installation(resolved.clone()).await {
+            Ok(version) => (true, version, None),
+            Err(err) => (false, None, Some(err)),
+        };
+    let app_server_ok = if binary_found {
+        let mut command = build_codex_command_with_bin(
+            resolved.clone(),
+            resolved_args.as_deref(),
+            vec!["app-server".to_string(), "--help".to_string()],
+        )?;
+        command.stdout(std::process::Stdio::piped());
+        command.stderr(std::process::Stdio::piped());
+        match timeout(Duration::from_secs(5), command.output()).await {
+            Ok(result) => result
+                .map(|output| output.status.success())
+                .unwrap_or(false),
+            Err(_) => false,
+        }
+    } else {
+        false
     };
     let (node_ok, node_version, node_details) = {
         let mut node_command = tokio_command("node");
@@ -236,22 +351,78 @@ pub(crate) async fn codex_doctor_core(
             ),
         }
     };
-    let details = if app_server_ok {
+    let details = if install_error.is_some() {
+        install_error
+    } else if app_server_ok {
         None
     } else {
         Some("Failed to run `codex app-server --help`.".to_string())
     };
-    Ok(json!({
-        "ok": version.is_some() && app_server_ok,
-        "codexBin": resolved,
-        "version": version,
-        "appServerOk": app_server_ok,
-        "details": details,
-        "path": path_env,
-        "nodeOk": node_ok,
-        "nodeVersion": node_version,
-        "nodeDetails": node_details,
-    }))
+    let config_path = crate::codex::config::config_toml_path();
+    let (config_valid, config_details) = check_codex_config(config_path.as_deref());
+    let mut report = CodexDoctorReport {
+        ok: version.is_some() && app_server_ok,
+        codex_bin: resolved,
+        binary_found,
+        version,
+        app_server_ok,
+        details,
+        path: path_env,
+        node_ok,
+        node_version,
+        node_details,
+        config_path: config_path.map(|path| path.to_string_lossy().to_string()),
+        config_valid,
+        config_details,
+        checks: Vec::new(),
+    };
+    report.checks = codex_doctor_checks(&report);
+    Ok(report)
+}
+
+fn normalize_codex_bin(codex_bin: Option<String>) -> Option<String> {
+    codex_bin
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// The codex binary used by workspaces without their own; `None` means
+/// `codex` from PATH.
+pub(crate) async fn get_default_codex_bin_core(
+    app_settings: &Mutex<AppSettings>,
+) -> Option<String> {
+    app_settings.lock().await.codex_bin.clone()
+}
+
+/// Saves a new default codex binary once `codex_doctor_core` accepts it, and
+/// returns that report. Running sessions keep their binary; sessions started
+/// or reconnected afterwards use the new one.
+pub(crate) async fn set_default_codex_bin_core(
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    codex_bin: Option<String>,
+) -> Result<CodexDoctorReport, String> {
+    let codex_bin = normalize_codex_bin(codex_bin);
+    // Clearing the default has to validate `codex` on PATH, not the current
+    // default the doctor would otherwise fall back to.
+    let candidate = codex_bin.clone().or_else(|| Some("codex".to_string()));
+    let report = codex_doctor_core(app_settings, candidate.clone(), None).await?;
+    if !report.ok {
+        let reason = report
+            .details
+            .clone()
+            .unwrap_or_else(|| "codex doctor checks failed".to_string());
+        return Err(format!(
+            "Rejected codex binary `{}`: {reason}",
+            candidate.unwrap_or_default()
+        ));
+    }
+    let mut next = app_settings.lock().await.clone();
+    if next.codex_bin != codex_bin {
+        next.codex_bin = codex_bin;
+        settings_core::update_app_settings_core(next, app_settings, settings_path).await?;
+    }
+    Ok(report)
 }
 
 pub(crate) async fn run_background_prompt_core<F>(
@@ -279,13 +450,7 @@ where
     });
     let thread_result = session.send_request("thread/start", thread_params).await?;
 
-    if let Some(error) = thread_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or("Unknown error starting thread");
-        return Err(error_msg.to_string());
-    }
+    app_server_result(&thread_result, "Unknown error starting thread")?;
 
     let thread_id = thread_result
         .get("result")
@@ -336,18 +501,14 @@ where
         }
     };
 
-    if let Some(error) = turn_result.get("error") {
-        let error_msg = error
-            .get("message")
-            .and_then(|m| m.as_str())
-            .unwrap_or(turn_error_fallback);
+    if let Err(error_msg) = app_server_result(&turn_result, turn_error_fallback) {
         {
             let mut callbacks = session.background_thread_callbacks.lock().await;
             callbacks.remove(&thread_id);
         }
         let archive_params = json!({ "threadId": thread_id.as_str() });
         let _ = session.send_request("thread/archive", archive_params).await;
-        return Err(error_msg.to_string());
+        return Err(error_msg);
     }
 
     let mut response_text = String::new();
@@ -452,7 +613,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{build_commit_message_prompt_for_diff, parse_run_metadata_value};
+    use super::{
+        build_commit_message_prompt_for_diff, check_codex_config, parse_run_metadata_value,
+        set_default_codex_bin_core,
+    };
+    use crate::types::AppSettings;
+    use tokio::sync::Mutex;
 
     #[test]
     fn build_commit_message_prompt_for_diff_requires_changes() {
@@ -465,7 +631,8 @@ mod tests {
 
     #[test]
     fn parse_run_metadata_value_normalizes_worktree_name_alias() {
-        let raw = r#"{"title":"Fix Login Redirect Loop","worktree_name":"fix-login-redirect-loop"}"#;
+        let raw =
+            r#"{"title":"Fix Login Redirect Loop","worktree_name":"fix-login-redirect-loop"}"#;
         let parsed = parse_run_metadata_value(raw).expect("parse metadata");
         assert_eq!(parsed["title"], "Fix Login Redirect Loop");
         assert_eq!(parsed["worktreeName"], "fix/login-redirect-loop");
@@ -480,4 +647,57 @@ mod tests {
             "Missing title in metadata"
         );
     }
+
+    #[test]
+    fn set_default_codex_bin_rejects_a_binary_that_fails_the_doctor() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-default-bin-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let settings_path = dir.join("settings.json");
+        let app_settings = Mutex::new(AppSettings {
+            codex_bin: Some("/usr/local/bin/codex".to_string()),
+            ..AppSettings::default()
+        });
+        let missing = dir.join("no-such-codex").to_string_lossy().to_string();
+
+        let err = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime")
+            .block_on(set_default_codex_bin_core(
+                &app_settings,
+                &settings_path,
+                Some(missing),
+            ))
+            .expect_err("missing binary is rejected");
+        assert!(err.starts_with("Rejected codex binary"), "{err}");
+        assert_eq!(
+            app_settings.blocking_lock().codex_bin.as_deref(),
+            Some("/usr/local/bin/codex")
+        );
+        assert!(!settings_path.exists());
+    }
+
+    #[test]
+    fn check_codex_config_accepts_missing_and_rejects_invalid_toml() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-config-check-{}",
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("config.toml");
+
+        assert_eq!(check_codex_config(Some(&path)), (true, None));
+
+        std::fs::write(&path, "model = \"gpt-5\"\n").expect("write config");
+        assert_eq!(check_codex_config(Some(&path)), (true, None));
+
+        std::fs::write(&path, "model = \n").expect("write config");
+        let (valid, details) = check_codex_config(Some(&path));
+        assert!(!valid);
+        assert!(details.expect("details").starts_with("Invalid config.toml"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
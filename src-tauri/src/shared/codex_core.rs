@@ -1,23 +1,40 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
 
+use futures_util::future::join_all;
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton, CoreBPE};
 use tokio::sync::oneshot::error::TryRecvError;
 use tokio::sync::{oneshot, Mutex};
 use tokio::time::timeout;
 use tokio::time::Instant;
 
 use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{EventSink, ThreadRenamed};
 use crate::codex::config as codex_config;
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::rules;
-use crate::shared::account::{build_account_response, read_auth_account};
-use crate::types::WorkspaceEntry;
+use crate::shared::account::{
+    build_account_response, login_status_from_account, read_auth_account,
+};
+use crate::shared::settings_core;
+use crate::types::{
+    AppSettings, BulkArchiveThreadsResult, CodexLoginStatus, MessageCostEstimate, PendingApproval,
+    QueuedUserMessage, SessionCapabilities, SessionPing, ThreadArchiveFailure, ThreadInfo,
+    WorkspaceActivity, WorkspaceCodexConfigPath, WorkspaceEntry,
+};
 
 const LOGIN_START_TIMEOUT: Duration = Duration::from_secs(30);
+const ALL_THREADS_DEFAULT_LIMIT: usize = 50;
+const ALL_THREADS_MAX_LIMIT: usize = 200;
+const ALL_THREADS_PAGE_SIZE: u32 = 50;
 
 pub(crate) enum CodexLoginCancelState {
     PendingStart(oneshot::Sender<()>),
@@ -94,23 +111,459 @@ pub(crate) async fn fork_thread_core(
     session.send_request("thread/fork", params).await
 }
 
+/// `list_threads` status filter. `Active` (non-archived threads) is what
+/// `thread/list` returns on its own and stays the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ThreadStatusFilter {
+    Active,
+    Archived,
+    All,
+    Running,
+}
+
+fn parse_thread_status_filter(status: Option<&str>) -> Result<ThreadStatusFilter, String> {
+    match status.map(str::trim).filter(|value| !value.is_empty()) {
+        None | Some("active") => Ok(ThreadStatusFilter::Active),
+        Some("archived") => Ok(ThreadStatusFilter::Archived),
+        Some("all") => Ok(ThreadStatusFilter::All),
+        Some("running") => Ok(ThreadStatusFilter::Running),
+        Some(other) => Err(format!(
+            "invalid status `{other}`; expected active, archived, all or running"
+        )),
+    }
+}
+
+/// `all` pages through active threads, then archived ones. Cursors into the
+/// archived half carry this prefix so the next call knows which list to read.
+const ARCHIVED_THREADS_CURSOR_PREFIX: &str = "archived:";
+
+/// Splits an incoming cursor into whether to read archived threads and the
+/// cursor to hand to `thread/list`.
+fn thread_list_segment(
+    status: ThreadStatusFilter,
+    cursor: Option<String>,
+) -> (bool, Option<String>) {
+    match status {
+        ThreadStatusFilter::Archived => (true, cursor),
+        ThreadStatusFilter::All => {
+            match cursor
+                .as_deref()
+                .and_then(|cursor| cursor.strip_prefix(ARCHIVED_THREADS_CURSOR_PREFIX))
+            {
+                Some(inner) => (
+                    true,
+                    Some(inner.to_string()).filter(|inner| !inner.is_empty()),
+                ),
+                None => (false, cursor),
+            }
+        }
+        ThreadStatusFilter::Active | ThreadStatusFilter::Running => (false, cursor),
+    }
+}
+
+fn apply_thread_status_filter(response: &mut Value, status: ThreadStatusFilter, archived: bool) {
+    if response.get("error").is_some() {
+        return;
+    }
+    let payload = match response.get_mut("result") {
+        Some(result) => result,
+        None => response,
+    };
+    let Some(payload) = payload.as_object_mut() else {
+        return;
+    };
+    match status {
+        ThreadStatusFilter::All => {
+            let next = payload
+                .get("nextCursor")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let next = match (archived, next) {
+                (false, Some(next)) => Value::String(next),
+                (false, None) => Value::String(ARCHIVED_THREADS_CURSOR_PREFIX.to_string()),
+                (true, Some(next)) => {
+                    Value::String(format!("{ARCHIVED_THREADS_CURSOR_PREFIX}{next}"))
+                }
+                (true, None) => Value::Null,
+            };
+            payload.insert("nextCursor".to_string(), next);
+        }
+        ThreadStatusFilter::Active | ThreadStatusFilter::Archived | ThreadStatusFilter::Running => {
+        }
+    }
+}
+
+/// The `thread/read` summary of a running thread, or just its id when the
+/// read fails, so a thread with a turn in flight is never left out.
+fn running_thread_entry(thread_id: &str, response: &Value) -> Value {
+    app_server_result(response, "")
+        .ok()
+        .and_then(|result| result.get("thread"))
+        .filter(|thread| thread.is_object())
+        .cloned()
+        .unwrap_or_else(|| json!({ "id": thread_id }))
+}
+
+/// `running` is answered from the turns this session is tracking rather than
+/// by filtering `thread/list` pages, which would miss running threads past
+/// the first page. The result is a single page.
+async fn list_running_threads(
+    session: &WorkspaceSession,
+    workspace_id: &str,
+    limit: Option<u32>,
+) -> Value {
+    let running_threads = session
+        .activity
+        .lock()
+        .await
+        .snapshot(workspace_id)
+        .running_threads;
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    let mut threads = Vec::new();
+    for thread_id in running_threads.into_iter().take(limit) {
+        let response = session
+            .send_request(
+                "thread/read",
+                json!({ "threadId": &thread_id, "includeTurns": false }),
+            )
+            .await
+            .unwrap_or(Value::Null);
+        threads.push(running_thread_entry(&thread_id, &response));
+    }
+    json!({ "result": { "data": threads, "nextCursor": null } })
+}
+
+/// One page of `thread/list`, optionally narrowed by `status` (see
+/// [`ThreadStatusFilter`]). `running` lists the threads with a turn in flight
+/// in one page; `all` returns every active page before the archived ones,
+/// each half in `sort_key` order.
 pub(crate) async fn list_threads_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     cursor: Option<String>,
     limit: Option<u32>,
     sort_key: Option<String>,
+    status: Option<String>,
 ) -> Result<Value, String> {
+    let status = parse_thread_status_filter(status.as_deref())?;
     let session = get_session_clone(sessions, &workspace_id).await?;
+    if status == ThreadStatusFilter::Running {
+        return Ok(list_running_threads(&session, &workspace_id, limit).await);
+    }
+    let (archived, cursor) = thread_list_segment(status, cursor);
     let params = json!({
         "cursor": cursor,
         "limit": limit,
         "sortKey": sort_key,
+        "archived": archived,
         // Keep spawned sub-agent sessions visible in thread/list so UI refreshes
         // do not drop parent -> child sidebar relationships.
         "sourceKinds": ["cli", "vscode", "subAgentThreadSpawn"]
     });
-    session.send_request("thread/list", params).await
+    let mut response = session.send_request("thread/list", params).await?;
+    apply_thread_status_filter(&mut response, status, archived);
+    Ok(response)
+}
+
+/// Re-reads the first page of `thread/list` straight from codex and notifies
+/// every client through `emit` so they can drop stale thread lists.
+pub(crate) async fn refresh_threads_core<F>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    limit: Option<u32>,
+    sort_key: Option<String>,
+    emit: F,
+) -> Result<Value, String>
+where
+    F: FnOnce(&str, Value),
+{
+    let response =
+        list_threads_core(sessions, workspace_id.clone(), None, limit, sort_key, None).await?;
+    app_server_result(&response, "thread/list failed")?;
+    emit(
+        &workspace_id,
+        json!({
+            "method": "codex/threadsRefreshed",
+            "params": { "workspaceId": workspace_id }
+        }),
+    );
+    Ok(response)
+}
+
+fn normalize_thread_cwd(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+fn thread_recency(thread: &Value) -> i64 {
+    ["updatedAt", "updated_at", "createdAt", "created_at"]
+        .iter()
+        .find_map(|key| thread.get(*key).and_then(Value::as_i64))
+        .unwrap_or(0)
+}
+
+/// Cursor for `list_all_threads`, handed to clients base64-encoded. Each
+/// workspace resumes from the upstream page holding its first thread not yet
+/// returned, so paging reaches back through the whole history.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AllThreadsCursor {
+    /// `(recency, threadId)` of the last returned thread. Later pages only
+    /// contain strictly older threads.
+    after: (i64, String),
+    /// Upstream `thread/list` cursor to re-read per workspace; a workspace
+    /// missing here starts from its first page.
+    #[serde(default)]
+    pages: HashMap<String, String>,
+    /// Workspaces with no older threads left.
+    #[serde(default)]
+    exhausted: Vec<String>,
+}
+
+fn encode_all_threads_cursor(cursor: &AllThreadsCursor) -> String {
+    let json = serde_json::to_vec(cursor).unwrap_or_default();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+}
+
+fn parse_all_threads_cursor(cursor: Option<&str>) -> Result<Option<AllThreadsCursor>, String> {
+    let Some(cursor) = cursor.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok())
+        .map(Some)
+        .ok_or_else(|| "invalid cursor".to_string())
+}
+
+fn is_before_cursor(key: &(i64, String), cursor: Option<&(i64, String)>) -> bool {
+    cursor.is_none_or(|cursor| key < cursor)
+}
+
+fn thread_key(thread: &Value) -> Option<(i64, String)> {
+    let id = thread.get("id").and_then(Value::as_str)?;
+    Some((thread_recency(thread), id.to_string()))
+}
+
+/// One workspace's threads read from `thread/list`, newest first.
+struct WorkspaceThreads {
+    /// Each thread with the upstream cursor of the page it was read from.
+    threads: Vec<(Option<String>, Value)>,
+    /// Upstream cursor after the last page read; `None` once exhausted.
+    next_page: Option<String>,
+}
+
+/// Reads `thread/list` pages from `start` through `fetch_page`, keeping
+/// threads whose cwd is the workspace root and that sort before `before`.
+/// Stops once `want` threads matched or at the end.
+async fn read_workspace_threads<F, Fut>(
+    mut fetch_page: F,
+    workspace_path: &str,
+    start: Option<String>,
+    before: Option<&(i64, String)>,
+    want: usize,
+) -> Result<WorkspaceThreads, String>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let workspace_path = normalize_thread_cwd(workspace_path);
+    let mut threads = Vec::new();
+    let mut page = start;
+    loop {
+        let response = fetch_page(page.clone()).await?;
+        let result = app_server_result(&response, "thread/list failed")?;
+        for thread in result
+            .get("data")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let cwd = thread.get("cwd").and_then(Value::as_str).unwrap_or("");
+            let Some(key) = thread_key(thread) else {
+                continue;
+            };
+            if normalize_thread_cwd(cwd) == workspace_path && is_before_cursor(&key, before) {
+                threads.push((page.clone(), thread.clone()));
+            }
+        }
+        page = result
+            .get("nextCursor")
+            .or_else(|| result.get("next_cursor"))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if page.is_none() || threads.len() >= want {
+            break;
+        }
+    }
+    Ok(WorkspaceThreads {
+        threads,
+        next_page: page,
+    })
+}
+
+async fn thread_list_page(
+    session: Arc<WorkspaceSession>,
+    cursor: Option<String>,
+) -> Result<Value, String> {
+    session
+        .send_request(
+            "thread/list",
+            json!({
+                "cursor": cursor,
+                "limit": ALL_THREADS_PAGE_SIZE,
+                "sortKey": "updated_at",
+                "sourceKinds": ["cli", "vscode", "subAgentThreadSpawn"]
+            }),
+        )
+        .await
+}
+
+/// Merges per-workspace listings newest first and keeps `limit` of them.
+/// The returned cursor records where each workspace resumes; `None` when
+/// nothing is left anywhere.
+fn merge_workspace_threads(
+    listed: Vec<(String, WorkspaceThreads)>,
+    limit: usize,
+    previous: Option<&AllThreadsCursor>,
+) -> (Vec<(String, Value)>, Option<AllThreadsCursor>) {
+    let mut candidates = Vec::new();
+    let mut next_pages = Vec::new();
+    for (workspace_id, listing) in listed {
+        for (page, thread) in listing.threads {
+            if let Some(key) = thread_key(&thread) {
+                candidates.push((key, workspace_id.clone(), page, thread));
+            }
+        }
+        next_pages.push((workspace_id, listing.next_page));
+    }
+    candidates.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut seen = HashSet::new();
+    let mut returned = Vec::new();
+    let mut after = None;
+    let mut resume: HashMap<String, Option<String>> = HashMap::new();
+    for (key, workspace_id, page, thread) in candidates {
+        if returned.len() < limit {
+            if seen.insert(key.1.clone()) {
+                after = Some(key);
+                returned.push((workspace_id, thread));
+            }
+        } else {
+            // The first leftover per workspace is the newest one not returned.
+            resume.entry(workspace_id).or_insert(page);
+        }
+    }
+
+    let mut cursor = AllThreadsCursor {
+        exhausted: previous.map_or_else(Vec::new, |previous| previous.exhausted.clone()),
+        ..Default::default()
+    };
+    let mut has_more = false;
+    for (workspace_id, next_page) in next_pages {
+        let page = match resume.remove(&workspace_id) {
+            Some(page) => page,
+            None if next_page.is_some() => next_page,
+            None => {
+                cursor.exhausted.push(workspace_id);
+                continue;
+            }
+        };
+        has_more = true;
+        if let Some(page) = page {
+            cursor.pages.insert(workspace_id, page);
+        }
+    }
+    match after {
+        Some(after) if has_more => {
+            cursor.after = after;
+            cursor.exhausted.sort();
+            cursor.exhausted.dedup();
+            (returned, Some(cursor))
+        }
+        _ => (returned, None),
+    }
+}
+
+/// Aggregates threads across workspaces, newest first. Workspaces without a
+/// session are skipped unless `connect_workspace` is provided, in which case
+/// they are connected first; those with `auto_connect` off stay skipped.
+pub(crate) async fn list_all_threads_core<F, Fut>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+    connect_workspace: Option<F>,
+) -> Result<Value, String>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let cursor = parse_all_threads_cursor(cursor.as_deref())?;
+    let limit = limit
+        .map(|value| value as usize)
+        .unwrap_or(ALL_THREADS_DEFAULT_LIMIT)
+        .clamp(1, ALL_THREADS_MAX_LIMIT);
+    let mut entries: Vec<WorkspaceEntry> = workspaces.lock().await.values().cloned().collect();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    if let Some(cursor) = cursor.as_ref() {
+        entries.retain(|entry| !cursor.exhausted.contains(&entry.id));
+    }
+
+    let mut skipped = Vec::new();
+    let mut targets = Vec::new();
+    for entry in entries {
+        let mut session = sessions.lock().await.get(&entry.id).cloned();
+        if session.is_none() {
+            if let Some(connect) = connect_workspace.as_ref().filter(|_| entry.auto_connect) {
+                if connect(entry.id.clone()).await.is_ok() {
+                    session = sessions.lock().await.get(&entry.id).cloned();
+                }
+            }
+        }
+        match session {
+            Some(session) => targets.push((entry, session)),
+            None => skipped.push(entry.id),
+        }
+    }
+
+    let after = cursor.as_ref().map(|cursor| &cursor.after);
+    let results = join_all(targets.into_iter().map(|(entry, session)| {
+        let start = cursor
+            .as_ref()
+            .and_then(|cursor| cursor.pages.get(&entry.id).cloned());
+        async move {
+            let listed = read_workspace_threads(
+                |cursor| thread_list_page(Arc::clone(&session), cursor),
+                &entry.path,
+                start,
+                after,
+                limit,
+            )
+            .await;
+            (entry.id, listed)
+        }
+    }))
+    .await;
+
+    let mut listed = Vec::new();
+    for (workspace_id, result) in results {
+        match result {
+            Ok(threads) => listed.push((workspace_id, threads)),
+            Err(_) => skipped.push(workspace_id),
+        }
+    }
+    let (threads, next_cursor) = merge_workspace_threads(listed, limit, cursor.as_ref());
+    let data: Vec<Value> = threads
+        .into_iter()
+        .map(|(workspace_id, thread)| json!({ "workspaceId": workspace_id, "thread": thread }))
+        .collect();
+    skipped.sort();
+    Ok(json!({
+        "data": data,
+        "nextCursor": next_cursor.as_ref().map(encode_all_threads_cursor),
+        "skippedWorkspaceIds": skipped,
+    }))
 }
 
 pub(crate) async fn list_mcp_server_status_core(
@@ -134,6 +587,98 @@ pub(crate) async fn archive_thread_core(
     session.send_request("thread/archive", params).await
 }
 
+/// Ids of the workspace's threads last updated before `inactive_before`, read
+/// from every `thread/list` page. Stale threads sit at the end of the
+/// `updated_at` order, so stopping early would miss exactly those.
+async fn read_stale_thread_ids<F, Fut>(
+    fetch_page: F,
+    workspace_path: &str,
+    inactive_before: i64,
+) -> Result<Vec<String>, String>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    // An empty id sorts first, so this keeps exactly the threads older than
+    // the cutoff.
+    let cutoff = (inactive_before, String::new());
+    let listing =
+        read_workspace_threads(fetch_page, workspace_path, None, Some(&cutoff), usize::MAX).await?;
+    Ok(listing
+        .threads
+        .iter()
+        .map(|(_, thread)| thread)
+        .filter(|thread| thread_recency(thread) > 0)
+        .filter_map(|thread| thread.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Archives the workspace's threads last updated before `inactive_before`
+/// (unix seconds, the unit of a thread's `updatedAt`). Threads without a
+/// timestamp are never picked. A dry run only reports the matching ids; a
+/// real run notifies every client through `emit` once anything was archived.
+pub(crate) async fn bulk_archive_threads_core<F>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    inactive_before: i64,
+    dry_run: bool,
+    emit: F,
+) -> Result<BulkArchiveThreadsResult, String>
+where
+    F: FnOnce(&str, Value),
+{
+    let workspace_path = workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .map(|entry| entry.path.clone())
+        .ok_or_else(|| "workspace not found".to_string())?;
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let stale = read_stale_thread_ids(
+        |cursor| thread_list_page(Arc::clone(&session), cursor),
+        &workspace_path,
+        inactive_before,
+    )
+    .await?;
+    if dry_run {
+        return Ok(BulkArchiveThreadsResult {
+            dry_run,
+            count: stale.len(),
+            archived: stale,
+            failed: Vec::new(),
+        });
+    }
+
+    let mut archived = Vec::new();
+    let mut failed = Vec::new();
+    for thread_id in stale {
+        let outcome = archive_thread_core(sessions, workspace_id.clone(), thread_id.clone())
+            .await
+            .and_then(|response| app_server_result(&response, "thread/archive failed").map(|_| ()));
+        match outcome {
+            Ok(()) => archived.push(thread_id),
+            Err(error) => failed.push(ThreadArchiveFailure { thread_id, error }),
+        }
+    }
+    if !archived.is_empty() {
+        emit(
+            &workspace_id,
+            json!({
+                "method": "codex/threadsRefreshed",
+                "params": { "workspaceId": workspace_id }
+            }),
+        );
+    }
+    Ok(BulkArchiveThreadsResult {
+        dry_run,
+        count: archived.len(),
+        archived,
+        failed,
+    })
+}
+
 pub(crate) async fn compact_thread_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -144,15 +689,258 @@ pub(crate) async fn compact_thread_core(
     session.send_request("thread/compact/start", params).await
 }
 
-pub(crate) async fn set_thread_name_core(
+/// Splits an app-server response into its `result`, or its `error.message`
+/// (`fallback` when the error carries none).
+pub(crate) fn app_server_result<'a>(
+    response: &'a Value,
+    fallback: &str,
+) -> Result<&'a Value, String> {
+    if let Some(error) = response.get("error") {
+        return Err(error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or(fallback)
+            .to_string());
+    }
+    Ok(response.get("result").unwrap_or(response))
+}
+
+fn thread_turn_total(thread: Option<&Value>) -> usize {
+    thread
+        .and_then(|thread| thread.get("turns"))
+        .and_then(Value::as_array)
+        .map_or(0, Vec::len)
+}
+
+/// JSON-RPC code the app-server answers `thread/read` with when it has no
+/// rollout for the thread id.
+const THREAD_READ_NOT_FOUND_CODE: i64 = -32600;
+
+fn thread_info_from_read(thread_id: &str, response: &Value) -> Result<ThreadInfo, String> {
+    let error_code = response
+        .get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(Value::as_i64);
+    if error_code == Some(THREAD_READ_NOT_FOUND_CODE) {
+        return Err(format!("thread not found: {thread_id}"));
+    }
+    let payload = app_server_result(response, "thread/read failed")?;
+    let thread = payload
+        .get("thread")
+        .filter(|thread| thread.is_object())
+        .ok_or_else(|| format!("thread not found: {thread_id}"))?;
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| thread.get(*key).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+    let timestamp = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| thread.get(*key).and_then(Value::as_i64))
+    };
+    Ok(ThreadInfo {
+        id: text(&["id"]).unwrap_or_else(|| thread_id.to_string()),
+        name: text(&["name"]),
+        preview: text(&["preview"]),
+        cwd: text(&["cwd"]),
+        created_at: timestamp(&["createdAt", "created_at"]),
+        updated_at: timestamp(&["updatedAt", "updated_at"]),
+        turn_count: thread
+            .get("turnCount")
+            .and_then(Value::as_u64)
+            .map(|count| count as usize)
+            .or_else(|| thread.get("turns").map(|_| thread_turn_total(Some(thread)))),
+        model: text(&["model"]).or_else(|| {
+            payload
+                .get("model")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        }),
+        model_provider: text(&["modelProvider", "model_provider"]),
+    })
+}
+
+/// Name, timestamps, turn count and model for a single thread, read without
+/// its turns.
+pub(crate) async fn get_thread_info_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<ThreadInfo, String> {
+    if thread_id.trim().is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let response = session
+        .send_request("thread/read", json!({ "threadId": &thread_id }))
+        .await?;
+    thread_info_from_read(&thread_id, &response)
+}
+
+/// Operations the UI hides when unsupported, and the app-server method each
+/// one needs.
+const SESSION_CAPABILITY_METHODS: &[(&str, &str)] = &[
+    ("forkThread", "thread/fork"),
+    ("compactThread", "thread/compact/start"),
+    ("rollbackThread", "thread/rollback"),
+    ("readThread", "thread/read"),
+    ("renameThread", "thread/name/set"),
+    ("steerTurn", "turn/steer"),
+    ("review", "review/start"),
+    ("collaborationModes", "collaborationMode/list"),
+    ("skills", "skills/list"),
+    ("apps", "app/list"),
+    ("mcpServerStatus", "mcpServerStatus/list"),
+];
+const SESSION_CAPABILITY_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a probe's reply says about its method. The app-server rejects a
+/// method it doesn't know before looking at the params, with JSON-RPC's
+/// "method not found" code or an "unknown variant", "unknown request" or
+/// "unknown method" message (the same ones the composer checks before falling
+/// back from `turn/steer`). Any other reply, including a missing-params error,
+/// means the method exists. `None` when there was no reply.
+fn probed_method_support(response: &Result<Value, String>) -> Option<bool> {
+    let response = response.as_ref().ok()?;
+    let Some(error) = response.get("error") else {
+        return Some(true);
+    };
+    let code = error.get("code").and_then(Value::as_i64);
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let unknown_method = code == Some(-32601)
+        || [
+            "unknown variant",
+            "unknown request",
+            "unknown method",
+            "method not found",
+        ]
+        .iter()
+        .any(|marker| message.contains(marker));
+    Some(!unknown_method)
+}
+
+/// Which operations the workspace's connected codex supports, found by
+/// sending each operation's method with empty params: the mutating ones need
+/// a thread id and are rejected without doing anything, the rest only list.
+/// Answers are cached on the session, so each method is probed once per
+/// session and `restart_session` probes again. An operation whose probe got
+/// no reply is reported as `null` and probed again on the next call.
+pub(crate) async fn session_capabilities_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+) -> Result<SessionCapabilities, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let mut cached = session.capabilities.lock().await;
+    let capabilities = cached.get_or_insert_with(|| SessionCapabilities {
+        codex_version: session.codex_version.clone(),
+        operations: Default::default(),
+    });
+    let unprobed = SESSION_CAPABILITY_METHODS
+        .iter()
+        .filter(|(operation, _)| {
+            capabilities
+                .operations
+                .get(*operation)
+                .is_none_or(Option::is_none)
+        })
+        .collect::<Vec<_>>();
+    let probes = join_all(unprobed.iter().map(|(_, method)| {
+        session.send_request_with_timeout(method, json!({}), SESSION_CAPABILITY_PROBE_TIMEOUT)
+    }))
+    .await;
+    for ((operation, _), response) in unprobed.into_iter().zip(probes) {
+        capabilities
+            .operations
+            .insert(operation.to_string(), probed_method_support(&response));
+    }
+    Ok(capabilities.clone())
+}
+
+/// A read-only request the app-server answers from its own model presets,
+/// so nothing reaches the model.
+const SESSION_PING_METHOD: &str = "model/list";
+const SESSION_PING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Times a `model/list` round trip, which measures the session's process and
+/// pipe overhead apart from the model. It is sent directly rather than through
+/// the session's cached list so every ping reaches the app-server.
+pub(crate) async fn session_ping_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+) -> Result<SessionPing, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let started = Instant::now();
+    let response = session
+        .send_request_with_timeout(SESSION_PING_METHOD, json!({}), SESSION_PING_TIMEOUT)
+        .await
+        .map_err(|err| format!("Codex session did not answer: {err}"))?;
+    app_server_result(&response, "model/list failed")?;
+    Ok(SessionPing {
+        latency_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Starts a thread over without replacing it. Kept: the thread id, its name
+/// and its collaboration mode. Cleared: every turn (user messages, agent
+/// replies and tool calls) via `thread/rollback`, plus the turn counter.
+/// Unlike `compact_thread` nothing is summarized. File changes made by the
+/// cleared turns stay on disk.
+pub(crate) async fn reset_thread_context_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
     thread_id: String,
+) -> Result<Value, String> {
+    if thread_id.trim().is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let resumed = session
+        .send_request("thread/resume", json!({ "threadId": &thread_id }))
+        .await?;
+    let resumed = app_server_result(&resumed, "thread/resume failed")?;
+    let mut thread = resumed.get("thread").cloned().unwrap_or(Value::Null);
+    let cleared_turns = thread_turn_total(Some(&thread));
+    if cleared_turns > 0 {
+        let rolled_back = session
+            .send_request(
+                "thread/rollback",
+                json!({ "threadId": &thread_id, "numTurns": cleared_turns }),
+            )
+            .await?;
+        let rolled_back = app_server_result(&rolled_back, "thread/rollback failed")?;
+        if let Some(updated) = rolled_back.get("thread") {
+            thread = updated.clone();
+        }
+    }
+    session.thread_turn_counts.lock().await.reset(&thread_id);
+    Ok(json!({
+        "threadId": thread_id,
+        "clearedTurns": cleared_turns,
+        "thread": thread,
+    }))
+}
+
+/// Renames a thread and tells every connected client, not just the caller.
+pub(crate) async fn set_thread_name_core<E: EventSink>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: &E,
+    workspace_id: String,
+    thread_id: String,
     name: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "threadId": thread_id, "name": name });
-    session.send_request("thread/name/set", params).await
+    let params = json!({ "threadId": &thread_id, "name": &name });
+    let response = session.send_request("thread/name/set", params).await?;
+    event_sink.emit_thread_renamed(ThreadRenamed {
+        workspace_id,
+        thread_id,
+        name,
+    });
+    Ok(response)
 }
 
 fn build_turn_input_items(text: String, images: Option<Vec<String>>) -> Result<Vec<Value>, String> {
@@ -183,19 +971,39 @@ fn build_turn_input_items(text: String, images: Option<Vec<String>>) -> Result<V
     Ok(input)
 }
 
-pub(crate) async fn send_user_message_core(
-    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
-    workspace_id: String,
-    thread_id: String,
-    text: String,
-    model: Option<String>,
-    effort: Option<String>,
-    access_mode: Option<String>,
-    images: Option<Vec<String>>,
-    collaboration_mode: Option<Value>,
+/// What a user message carries besides its workspace and thread.
+pub(crate) struct UserMessageInput {
+    pub(crate) text: String,
+    pub(crate) model: Option<String>,
+    pub(crate) effort: Option<String>,
+    pub(crate) access_mode: Option<String>,
+    pub(crate) images: Option<Vec<String>>,
+    pub(crate) collaboration_mode: Option<Value>,
+}
+
+/// Counts the turn against `maxTurnsPerThread` before it is sent or queued;
+/// a queued message holds its turn until it is sent or cancelled.
+async fn reserve_turn(
+    session: &WorkspaceSession,
+    app_settings: &Mutex<AppSettings>,
+    thread_id: &str,
+) -> Result<(), String> {
+    let limit = app_settings.lock().await.max_turns_per_thread;
+    session
+        .thread_turn_counts
+        .lock()
+        .await
+        .reserve(thread_id, limit)
+}
+
+async fn build_turn_start_params(
+    session: &WorkspaceSession,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    thread_id: &str,
+    message: UserMessageInput,
 ) -> Result<Value, String> {
-    let session = get_session_clone(sessions, &workspace_id).await?;
-    let access_mode = access_mode.unwrap_or_else(|| "current".to_string());
+    let access_mode = message.access_mode.unwrap_or_else(|| "current".to_string());
     let sandbox_policy = match access_mode.as_str() {
         "full-access" => json!({ "type": "dangerFullAccess" }),
         "read-only" => json!({ "type": "readOnly" }),
@@ -212,7 +1020,11 @@ pub(crate) async fn send_user_message_core(
         "on-request"
     };
 
-    let input = build_turn_input_items(text, images)?;
+    let input = build_turn_input_items(message.text, message.images)?;
+    let effort = match message.effort {
+        Some(effort) => Some(effort),
+        None => default_effort(session, workspaces, workspace_id, message.model.as_deref()).await,
+    };
 
     let mut params = Map::new();
     params.insert("threadId".to_string(), json!(thread_id));
@@ -220,16 +1032,205 @@ pub(crate) async fn send_user_message_core(
     params.insert("cwd".to_string(), json!(session.entry.path));
     params.insert("approvalPolicy".to_string(), json!(approval_policy));
     params.insert("sandboxPolicy".to_string(), json!(sandbox_policy));
-    params.insert("model".to_string(), json!(model));
+    params.insert("model".to_string(), json!(&message.model));
     params.insert("effort".to_string(), json!(effort));
+    let sticky_mode = session
+        .thread_collaboration_modes
+        .lock()
+        .await
+        .get(thread_id)
+        .cloned();
+    let collaboration_mode = match message.collaboration_mode.filter(|mode| !mode.is_null()) {
+        Some(mode) => Some(mode),
+        None if sticky_mode.is_some() => sticky_mode,
+        None => {
+            default_collaboration_mode(
+                session,
+                workspaces,
+                workspace_id,
+                message.model.as_deref(),
+                effort.as_deref(),
+            )
+            .await?
+        }
+    };
     if let Some(mode) = collaboration_mode {
-        if !mode.is_null() {
-            params.insert("collaborationMode".to_string(), mode);
+        params.insert("collaborationMode".to_string(), mode);
+    }
+    Ok(Value::Object(params))
+}
+
+async fn record_recent_model(
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    workspace_id: &str,
+    model: Option<&str>,
+) {
+    if let Some(model) = model {
+        // Recents are a convenience; a failed write must not fail the turn.
+        let _ = settings_core::record_recent_model_core(
+            app_settings,
+            settings_path,
+            workspace_id,
+            model,
+        )
+        .await;
+    }
+}
+
+pub(crate) async fn send_user_message_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    workspace_id: String,
+    thread_id: String,
+    text: String,
+    model: Option<String>,
+    effort: Option<String>,
+    access_mode: Option<String>,
+    images: Option<Vec<String>>,
+    collaboration_mode: Option<Value>,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let message = UserMessageInput {
+        text,
+        model: model.clone(),
+        effort,
+        access_mode,
+        images,
+        collaboration_mode,
+    };
+    let params =
+        build_turn_start_params(&session, workspaces, &workspace_id, &thread_id, message).await?;
+    reserve_turn(&session, app_settings, &thread_id).await?;
+    let response = session.start_turn(&thread_id, params).await?;
+    record_recent_model(app_settings, settings_path, &workspace_id, model.as_deref()).await;
+    Ok(response)
+}
+
+/// Sends the message now if the thread is idle; otherwise holds it until the
+/// running turn finishes, when it is sent and `codex/queuedMessageSent` is
+/// emitted.
+pub(crate) async fn queue_user_message_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    workspace_id: String,
+    thread_id: String,
+    message: UserMessageInput,
+) -> Result<QueuedUserMessage, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let model = message.model.clone();
+    let params =
+        build_turn_start_params(&session, workspaces, &workspace_id, &thread_id, message).await?;
+    reserve_turn(&session, app_settings, &thread_id).await?;
+
+    let queued = {
+        // Held across the check and the push so a turn finishing in between
+        // can't leave the message queued behind an idle thread.
+        let mut activity = session.activity.lock().await;
+        let mut queued_turns = session.queued_turns.lock().await;
+        if activity.is_running(&thread_id) || queued_turns.len(&thread_id) > 0 {
+            Some(queued_turns.push(&thread_id, params.clone()))
+        } else {
+            activity.mark_running(&thread_id);
+            None
+        }
+    };
+    let queued = match queued.transpose() {
+        Ok(queued) => queued,
+        Err(err) => {
+            session.thread_turn_counts.lock().await.release(&thread_id);
+            return Err(err);
         }
+    };
+    record_recent_model(app_settings, settings_path, &workspace_id, model.as_deref()).await;
+    if let Some((message_id, position)) = queued {
+        return Ok(QueuedUserMessage {
+            queued: true,
+            message_id: Some(message_id),
+            position: Some(position),
+            response: None,
+        });
     }
-    session
-        .send_request("turn/start", Value::Object(params))
-        .await
+
+    let response = session.start_turn(&thread_id, params).await;
+    if !matches!(&response, Ok(response) if response.get("error").is_none()) {
+        session.activity.lock().await.clear_running(&thread_id);
+    }
+    Ok(QueuedUserMessage {
+        queued: false,
+        message_id: None,
+        position: None,
+        response: Some(response?),
+    })
+}
+
+pub(crate) async fn cancel_queued_message_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    message_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let cancelled = session.queued_turns.lock().await.cancel(&message_id);
+    if let Some(thread_id) = &cancelled {
+        session.thread_turn_counts.lock().await.release(thread_id);
+    }
+    let cancelled = cancelled.is_some();
+    Ok(json!({ "messageId": message_id, "cancelled": cancelled }))
+}
+
+pub(crate) async fn reset_thread_turn_count_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<Value, String> {
+    if thread_id.trim().is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let previous = session.thread_turn_counts.lock().await.reset(&thread_id);
+    Ok(json!({ "threadId": thread_id, "previousTurnCount": previous }))
+}
+
+/// Stores the thread's sticky mode in the workspace settings, so a respawned
+/// session picks it up, and in the live session when one is running.
+pub(crate) async fn set_thread_collaboration_mode_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+    workspace_id: String,
+    thread_id: String,
+    collaboration_mode: Option<Value>,
+) -> Result<Value, String> {
+    if thread_id.trim().is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    let effective = collaboration_mode
+        .filter(|mode| !mode.is_null())
+        .unwrap_or(Value::Null);
+    let apply = |modes: &mut HashMap<String, Value>| {
+        if effective.is_null() {
+            modes.remove(&thread_id);
+        } else {
+            modes.insert(thread_id.clone(), effective.clone());
+        }
+    };
+    crate::shared::workspaces_core::update_workspace_entry(
+        workspace_id.clone(),
+        workspaces,
+        sessions,
+        storage_path,
+        |entry| apply(&mut entry.settings.thread_collaboration_modes),
+    )
+    .await?;
+    let session = sessions.lock().await.get(&workspace_id).cloned();
+    if let Some(session) = session {
+        apply(&mut *session.thread_collaboration_modes.lock().await);
+    }
+    Ok(json!({ "threadId": thread_id, "collaborationMode": effective }))
 }
 
 pub(crate) async fn turn_steer_core(
@@ -253,14 +1254,201 @@ pub(crate) async fn turn_steer_core(
     session.send_request("turn/steer", params).await
 }
 
+/// Where a `collaborationMode/list` response keeps its modes; older servers
+/// return `modes` instead of `data`, or the bare array.
+const COLLABORATION_MODE_LIST_POINTERS: [&str; 6] = [
+    "/result/data",
+    "/result/modes",
+    "/result",
+    "/data",
+    "/modes",
+    "",
+];
+
+fn collaboration_mode_list_pointer(response: &Value) -> Option<&'static str> {
+    COLLABORATION_MODE_LIST_POINTERS
+        .into_iter()
+        .find(|pointer| response.pointer(pointer).is_some_and(Value::is_array))
+}
+
+fn collaboration_modes(response: &Value) -> &[Value] {
+    collaboration_mode_list_pointer(response)
+        .and_then(|pointer| response.pointer(pointer))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn collaboration_mode_id(mode: &Value) -> Option<&str> {
+    mode.get("mode")
+        .or_else(|| mode.get("name"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+}
+
+fn collaboration_mode_description(mode: &Value) -> Option<String> {
+    [
+        mode.get("description"),
+        mode.get("settings")
+            .and_then(|settings| settings.get("description")),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(Value::as_str)
+    .map(str::trim)
+    .find(|description| !description.is_empty())
+    .map(str::to_string)
+}
+
+/// Gives every listed mode a top-level `description` (null when codex has
+/// none) and an `isDefault` flag for the workspace's default mode.
+fn describe_collaboration_modes(response: &mut Value, default_mode: Option<&str>) {
+    let Some(modes) = collaboration_mode_list_pointer(response)
+        .and_then(|pointer| response.pointer_mut(pointer))
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+    for mode in modes {
+        let description = collaboration_mode_description(mode);
+        let is_default = default_mode.is_some() && collaboration_mode_id(mode) == default_mode;
+        if let Some(mode) = mode.as_object_mut() {
+            mode.insert("description".to_string(), json!(description));
+            mode.insert("isDefault".to_string(), json!(is_default));
+        }
+    }
+}
+
 pub(crate) async fn collaboration_mode_list_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    session
-        .send_request("collaborationMode/list", json!({}))
+    let mut response = cached_list(
+        &session,
+        &session.collaboration_mode_list,
+        "collaborationMode/list",
+    )
+    .await?;
+    let default_mode = workspaces
+        .lock()
+        .await
+        .get(&workspace_id)
+        .and_then(|entry| entry.settings.default_collaboration_mode.clone());
+    describe_collaboration_modes(&mut response, default_mode.as_deref());
+    Ok(response)
+}
+
+fn validate_collaboration_mode(mode_list: &Value, mode: &str) -> Result<String, String> {
+    let available: Vec<&str> = collaboration_modes(mode_list)
+        .iter()
+        .filter_map(collaboration_mode_id)
+        .collect();
+    available
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(mode))
+        .map(|candidate| candidate.to_string())
+        .ok_or_else(|| {
+            format!(
+                "Collaboration mode `{mode}` is not available; expected one of: {}.",
+                available.join(", ")
+            )
+        })
+}
+
+/// Checks `mode` against the session's `collaborationMode/list` before it is
+/// saved as a workspace default. An empty mode clears the default.
+pub(crate) async fn validate_default_collaboration_mode_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: &str,
+    mode: Option<String>,
+) -> Result<Option<String>, String> {
+    let Some(mode) = mode
+        .map(|mode| mode.trim().to_string())
+        .filter(|mode| !mode.is_empty())
+    else {
+        return Ok(None);
+    };
+    let session = get_session_clone(sessions, workspace_id).await?;
+    let mode_list = cached_list(
+        &session,
+        &session.collaboration_mode_list,
+        "collaborationMode/list",
+    )
+    .await?;
+    validate_collaboration_mode(&mode_list, &mode).map(Some)
+}
+
+/// The `collaborationMode` turn parameter for a listed mode. The turn's own
+/// model and effort win over the mode's presets, as they do in the composer.
+fn collaboration_mode_payload(mode: &Value, model: Option<&str>, effort: Option<&str>) -> Value {
+    let settings = mode.get("settings").unwrap_or(mode);
+    let setting = |camel: &str, snake: &str| {
+        settings
+            .get(snake)
+            .or_else(|| settings.get(camel))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    let model = model
+        .map(|model| json!(model))
+        .unwrap_or_else(|| setting("model", "model"));
+    let effort = effort
+        .map(|effort| json!(effort))
+        .unwrap_or_else(|| setting("reasoningEffort", "reasoning_effort"));
+    json!({
+        "mode": collaboration_mode_id(mode),
+        "settings": {
+            "model": model,
+            "reasoning_effort": effort,
+            "developer_instructions": setting("developerInstructions", "developer_instructions"),
+        },
+    })
+}
+
+/// The workspace's default collaboration mode as a turn parameter; `None`
+/// when no default is set. A default the session no longer lists fails the
+/// turn rather than silently running it in another mode.
+async fn default_collaboration_mode(
+    session: &WorkspaceSession,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    model: Option<&str>,
+    effort: Option<&str>,
+) -> Result<Option<Value>, String> {
+    let Some(default_mode) = workspaces
+        .lock()
         .await
+        .get(workspace_id)
+        .and_then(|entry| entry.settings.default_collaboration_mode.clone())
+    else {
+        return Ok(None);
+    };
+    let mode_list = cached_list(
+        session,
+        &session.collaboration_mode_list,
+        "collaborationMode/list",
+    )
+    .await
+    .map_err(|err| format!("Could not apply default collaboration mode: {err}"))?;
+    default_collaboration_mode_payload(&mode_list, &default_mode, model, effort).map(Some)
+}
+
+fn default_collaboration_mode_payload(
+    mode_list: &Value,
+    default_mode: &str,
+    model: Option<&str>,
+    effort: Option<&str>,
+) -> Result<Value, String> {
+    let mode_id = validate_collaboration_mode(mode_list, default_mode)
+        .map_err(|err| format!("Default collaboration mode is unavailable: {err}"))?;
+    collaboration_modes(mode_list)
+        .iter()
+        .find(|mode| collaboration_mode_id(mode) == Some(mode_id.as_str()))
+        .map(|mode| collaboration_mode_payload(mode, model, effort))
+        .ok_or_else(|| format!("Collaboration mode `{mode_id}` is not available."))
 }
 
 pub(crate) async fn turn_interrupt_core(
@@ -301,6 +1489,146 @@ pub(crate) async fn model_list_core(
     session.send_request("model/list", json!({})).await
 }
 
+/// Reasoning efforts `model` accepts according to a `model/list` response;
+/// without a model, the entry marked `isDefault` is used.
+fn supported_efforts_for_model(
+    model_list: &Value,
+    model: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    let entries = model_list
+        .get("result")
+        .and_then(|result| result.get("data"))
+        .or_else(|| model_list.get("data"))
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let field = |entry: &Value, camel: &str, snake: &str| {
+        entry
+            .get(camel)
+            .or_else(|| entry.get(snake))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+    let entry = entries
+        .iter()
+        .find(|entry| match model {
+            Some(model) => [entry.get("model"), entry.get("id")]
+                .into_iter()
+                .flatten()
+                .any(|value| value.as_str() == Some(model)),
+            None => field(entry, "isDefault", "is_default")
+                .as_bool()
+                .unwrap_or(false),
+        })
+        .ok_or_else(|| match model {
+            Some(model) => format!("Model `{model}` is not in the model list."),
+            None => "No default model reported; pass a model to validate against.".to_string(),
+        })?;
+    let name = entry
+        .get("model")
+        .or_else(|| entry.get("id"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let efforts = field(
+        entry,
+        "supportedReasoningEfforts",
+        "supported_reasoning_efforts",
+    )
+    .as_array()
+    .map(|efforts| {
+        efforts
+            .iter()
+            .filter_map(|effort| {
+                field(effort, "reasoningEffort", "reasoning_effort")
+                    .as_str()
+                    .map(str::to_string)
+            })
+            .collect()
+    })
+    .unwrap_or_default();
+    Ok((name, efforts))
+}
+
+fn validate_effort(
+    model_list: &Value,
+    model: Option<&str>,
+    effort: &str,
+) -> Result<String, String> {
+    let (model, supported) = supported_efforts_for_model(model_list, model)?;
+    if supported.is_empty() {
+        return Err(format!(
+            "Model `{model}` does not support reasoning effort."
+        ));
+    }
+    supported
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(effort))
+        .cloned()
+        .ok_or_else(|| {
+            format!(
+                "Effort `{effort}` is not supported by `{model}`; expected one of: {}.",
+                supported.join(", ")
+            )
+        })
+}
+
+/// A list response kept on the session after its first successful fetch;
+/// the lists don't change for the life of the app-server process.
+async fn cached_list(
+    session: &WorkspaceSession,
+    cache: &Mutex<Option<Value>>,
+    method: &str,
+) -> Result<Value, String> {
+    let mut cached = cache.lock().await;
+    if let Some(list) = cached.as_ref() {
+        return Ok(list.clone());
+    }
+    let list = session.send_request(method, json!({})).await?;
+    app_server_result(&list, &format!("{method} failed"))?;
+    *cached = Some(list.clone());
+    Ok(list)
+}
+
+/// The workspace's default effort when the turn's model supports it. A
+/// default saved for another model is left out so the turn falls back to the
+/// model's own default instead of being rejected.
+async fn default_effort(
+    session: &WorkspaceSession,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    model: Option<&str>,
+) -> Option<String> {
+    let effort = workspaces
+        .lock()
+        .await
+        .get(workspace_id)
+        .and_then(|entry| entry.settings.default_effort.clone())?;
+    let model_list = cached_list(session, &session.model_list, "model/list")
+        .await
+        .ok()?;
+    validate_effort(&model_list, model, &effort).ok()
+}
+
+/// Checks `effort` against the session's `model/list` before it is saved as
+/// a workspace default. An empty effort clears the default.
+pub(crate) async fn validate_default_effort_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: &str,
+    effort: Option<String>,
+    model: Option<String>,
+) -> Result<Option<String>, String> {
+    let Some(effort) = effort
+        .map(|effort| effort.trim().to_string())
+        .filter(|effort| !effort.is_empty())
+    else {
+        return Ok(None);
+    };
+    let session = get_session_clone(sessions, workspace_id).await?;
+    let model_list = cached_list(&session, &session.model_list, "model/list").await?;
+    validate_effort(&model_list, model.as_deref(), &effort).map(Some)
+}
+
 pub(crate) async fn account_rate_limits_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -334,6 +1662,17 @@ pub(crate) async fn account_read_core(
     Ok(build_account_response(response, fallback))
 }
 
+/// Current auth state from `account/read` (or auth.json when no session is
+/// running). Never starts a login.
+pub(crate) async fn codex_login_status_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<CodexLoginStatus, String> {
+    let account = account_read_core(sessions, workspaces, workspace_id).await?;
+    Ok(login_status_from_account(&account))
+}
+
 pub(crate) async fn codex_login_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     codex_login_cancels: &Mutex<HashMap<String, CodexLoginCancelState>>,
@@ -502,6 +1841,54 @@ pub(crate) async fn respond_to_server_request_core(
     session.send_response(request_id, result).await
 }
 
+/// Turn state of every connected workspace, sorted by workspace id. Only
+/// takes the session locks, so it is cheap enough to poll.
+pub(crate) async fn workspace_activity_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+) -> Vec<WorkspaceActivity> {
+    let sessions: Vec<(String, Arc<WorkspaceSession>)> = {
+        let sessions = sessions.lock().await;
+        sessions
+            .iter()
+            .map(|(id, session)| (id.clone(), Arc::clone(session)))
+            .collect()
+    };
+    let mut activity = Vec::with_capacity(sessions.len());
+    for (workspace_id, session) in sessions {
+        activity.push(session.activity.lock().await.snapshot(&workspace_id));
+    }
+    activity.sort_by(|a, b| a.workspace_id.cmp(&b.workspace_id));
+    activity
+}
+
+/// Approval requests still waiting on `respond_to_server_request`, for one
+/// workspace or (with `None`) every connected one.
+pub(crate) async fn list_pending_approvals_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: Option<String>,
+) -> Vec<PendingApproval> {
+    let mut sessions: Vec<(String, Arc<WorkspaceSession>)> = {
+        let sessions = sessions.lock().await;
+        sessions
+            .iter()
+            .filter(|(id, _)| workspace_id.as_ref().is_none_or(|wanted| wanted == *id))
+            .map(|(id, session)| (id.clone(), Arc::clone(session)))
+            .collect()
+    };
+    sessions.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut approvals = Vec::new();
+    for (workspace_id, session) in sessions {
+        approvals.extend(
+            session
+                .activity
+                .lock()
+                .await
+                .pending_approvals(&workspace_id),
+        );
+    }
+    approvals
+}
+
 pub(crate) async fn remember_approval_rule_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -534,3 +1921,513 @@ pub(crate) async fn get_config_model_core(
     let model = codex_config::read_config_model(Some(codex_home))?;
     Ok(json!({ "model": model }))
 }
+
+pub(crate) async fn get_workspace_codex_config_path_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<WorkspaceCodexConfigPath, String> {
+    let codex_home = resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
+    let config_path = codex_config::config_toml_path_in(&codex_home);
+    Ok(WorkspaceCodexConfigPath {
+        codex_home: codex_home.to_string_lossy().to_string(),
+        config_path: config_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Picks the BPE for a model. Unknown names (including newer codex models)
+/// fall back to o200k, which every current OpenAI model uses.
+fn tokenizer_for_model(model: Option<&str>) -> (&'static str, &'static CoreBPE) {
+    match model.and_then(get_tokenizer) {
+        Some(Tokenizer::Cl100kBase) => ("cl100k_base", cl100k_base_singleton()),
+        _ => ("o200k_base", o200k_base_singleton()),
+    }
+}
+
+fn model_input_price(prices: &HashMap<String, f64>, model: &str) -> Option<f64> {
+    let model = model.trim();
+    prices
+        .iter()
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(model))
+        .map(|(_, price)| *price)
+}
+
+/// Counts tokens with the model's BPE. Draft text is never a special token,
+/// so special-token markup is counted as ordinary text. The cost uses the
+/// user's `modelInputPrices`.
+pub(crate) fn estimate_text_cost(
+    text: &str,
+    model: Option<&str>,
+    prices: &HashMap<String, f64>,
+) -> MessageCostEstimate {
+    let (tokenizer, bpe) = tokenizer_for_model(model);
+    let input_tokens = bpe.encode_ordinary(text).len() as u64;
+    let input_price_per_million = model.and_then(|model| model_input_price(prices, model));
+    MessageCostEstimate {
+        model: model.map(str::to_string),
+        tokenizer: tokenizer.to_string(),
+        input_tokens,
+        input_price_per_million,
+        estimated_cost_usd: input_price_per_million
+            .map(|price| input_tokens as f64 * price / 1_000_000.0),
+    }
+}
+
+/// Estimates input tokens for a draft without contacting the model. When no
+/// model is given, the workspace's configured model is used.
+pub(crate) async fn estimate_message_cost_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    text: String,
+    model: Option<String>,
+) -> Result<MessageCostEstimate, String> {
+    let model = model
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let model = match model {
+        Some(model) => {
+            resolve_workspace_and_parent(workspaces, &workspace_id).await?;
+            Some(model)
+        }
+        None => {
+            let codex_home =
+                resolve_codex_home_for_workspace_core(workspaces, &workspace_id).await?;
+            codex_config::read_config_model(Some(codex_home)).unwrap_or(None)
+        }
+    };
+    let prices = app_settings.lock().await.model_input_prices.clone();
+    Ok(estimate_text_cost(&text, model.as_deref(), &prices))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{run, workspace_entry};
+
+    fn model_list() -> Value {
+        json!({
+            "data": [
+                {
+                    "id": "gpt-5",
+                    "model": "gpt-5",
+                    "isDefault": true,
+                    "supportedReasoningEfforts": [
+                        { "reasoningEffort": "low" },
+                        { "reasoningEffort": "medium" },
+                        { "reasoningEffort": "high" }
+                    ]
+                },
+                { "id": "mini", "model": "mini", "supportedReasoningEfforts": [] }
+            ]
+        })
+    }
+
+    #[test]
+    fn workspace_config_path_follows_the_parents_codex_home_for_worktrees() {
+        let main = WorkspaceEntry {
+            id: "main".to_string(),
+            settings: crate::types::WorkspaceSettings {
+                codex_home: Some("/tmp/custom-codex-home".to_string()),
+                ..Default::default()
+            },
+            ..workspace_entry(std::path::Path::new("/tmp/codex-config-path-test/main"))
+        };
+        let worktree = WorkspaceEntry {
+            id: "wt".to_string(),
+            kind: crate::types::WorkspaceKind::Worktree,
+            parent_id: Some("main".to_string()),
+            ..workspace_entry(std::path::Path::new("/tmp/codex-config-path-test/wt"))
+        };
+        let workspaces = Mutex::new(HashMap::from([
+            (main.id.clone(), main),
+            (worktree.id.clone(), worktree),
+        ]));
+        let resolved = run(get_workspace_codex_config_path_core(
+            &workspaces,
+            "wt".to_string(),
+        ))
+        .expect("config path");
+        let expected_home = std::path::Path::new("/tmp/custom-codex-home");
+        assert_eq!(resolved.codex_home, expected_home.to_string_lossy());
+        assert_eq!(
+            resolved.config_path,
+            expected_home.join("config.toml").to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn thread_status_filter_reads_running_threads_and_chains_all_into_archived() {
+        assert_eq!(
+            parse_thread_status_filter(None),
+            Ok(ThreadStatusFilter::Active)
+        );
+        assert!(parse_thread_status_filter(Some("stale")).is_err());
+
+        let read = json!({ "result": { "thread": { "id": "t2", "preview": "fix it" } } });
+        assert_eq!(
+            running_thread_entry("t2", &read),
+            json!({ "id": "t2", "preview": "fix it" })
+        );
+        let failed = json!({ "error": { "message": "thread not found" } });
+        assert_eq!(running_thread_entry("t3", &failed), json!({ "id": "t3" }));
+
+        let mut last_active = json!({ "result": { "data": [], "nextCursor": null } });
+        apply_thread_status_filter(&mut last_active, ThreadStatusFilter::All, false);
+        let next = last_active["result"]["nextCursor"]
+            .as_str()
+            .map(str::to_string);
+        assert_eq!(
+            thread_list_segment(ThreadStatusFilter::All, next),
+            (true, None)
+        );
+
+        let mut archived = json!({ "result": { "data": [], "nextCursor": "c2" } });
+        apply_thread_status_filter(&mut archived, ThreadStatusFilter::All, true);
+        let next = archived["result"]["nextCursor"]
+            .as_str()
+            .map(str::to_string);
+        assert_eq!(
+            thread_list_segment(ThreadStatusFilter::All, next),
+            (true, Some("c2".to_string()))
+        );
+    }
+
+    /// Request cursor, `nextCursor`, and `(id, cwd, updatedAt)` per thread.
+    type ThreadPage<'a> = (
+        Option<&'a str>,
+        Option<&'a str>,
+        &'a [(&'a str, &'a str, i64)],
+    );
+
+    fn thread_pages(pages: &[ThreadPage]) -> HashMap<Option<String>, Value> {
+        pages
+            .iter()
+            .map(|(cursor, next, threads)| {
+                let data: Vec<Value> = threads
+                    .iter()
+                    .map(|(id, cwd, updated_at)| {
+                        json!({ "id": id, "cwd": cwd, "updatedAt": updated_at })
+                    })
+                    .collect();
+                (
+                    cursor.map(str::to_string),
+                    json!({ "result": { "data": data, "nextCursor": next } }),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn all_threads_cursor_resumes_each_workspace_from_its_upstream_page() {
+        let a = thread_pages(&[
+            (None, Some("p1"), &[("a60", "/a", 60), ("a50", "/a", 50)]),
+            (
+                Some("p1"),
+                Some("p2"),
+                &[("a40", "/a", 40), ("a30", "/a", 30)],
+            ),
+            (Some("p2"), None, &[("a20", "/a", 20), ("a10", "/a/", 10)]),
+        ]);
+        let b = thread_pages(&[(
+            None,
+            None,
+            &[
+                ("b55", "/b", 55),
+                ("elsewhere", "/c", 45),
+                ("b35", "/b", 35),
+            ],
+        )]);
+        let fetches = std::cell::Cell::new(0);
+        let list_page = |cursor: Option<String>| {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+            let mut listed = Vec::new();
+            let cursor = parse_all_threads_cursor(cursor.as_deref()).expect("cursor");
+            for (workspace_id, path, pages) in [("a", "/a", &a), ("b", "/b", &b)] {
+                if cursor
+                    .as_ref()
+                    .is_some_and(|cursor| cursor.exhausted.iter().any(|id| id == workspace_id))
+                {
+                    continue;
+                }
+                let start = cursor
+                    .as_ref()
+                    .and_then(|cursor| cursor.pages.get(workspace_id).cloned());
+                let fetch = |page: Option<String>| {
+                    fetches.set(fetches.get() + 1);
+                    let response = pages.get(&page).cloned().ok_or("unknown page".to_string());
+                    async move { response }
+                };
+                let threads = runtime
+                    .block_on(read_workspace_threads(
+                        fetch,
+                        path,
+                        start,
+                        cursor.as_ref().map(|cursor| &cursor.after),
+                        3,
+                    ))
+                    .expect("threads");
+                listed.push((workspace_id.to_string(), threads));
+            }
+            let (threads, next) = merge_workspace_threads(listed, 3, cursor.as_ref());
+            let ids: Vec<String> = threads
+                .iter()
+                .map(|(_, thread)| thread["id"].as_str().unwrap_or("").to_string())
+                .collect();
+            (ids, next.as_ref().map(encode_all_threads_cursor))
+        };
+
+        let (first, cursor) = list_page(None);
+        assert_eq!(first, ["a60", "b55", "a50"]);
+        let (second, cursor) = list_page(cursor);
+        assert_eq!(second, ["a40", "b35", "a30"]);
+        let (third, cursor) = list_page(cursor);
+        assert_eq!(third, ["a20", "a10"]);
+        assert_eq!(cursor, None);
+        // a: two pages, then p1 and p2, then p2 again; b: its only page twice.
+        assert_eq!(fetches.get(), 7);
+        assert_eq!(
+            parse_all_threads_cursor(Some("not a cursor")),
+            Err("invalid cursor".to_string())
+        );
+    }
+
+    #[test]
+    fn bulk_archive_reads_every_page_for_stale_threads() {
+        let pages = thread_pages(&[
+            (None, Some("p1"), &[("new", "/a", 90), ("other", "/b", 10)]),
+            (
+                Some("p1"),
+                Some("p2"),
+                &[("old1", "/a", 30), ("recent", "/a", 50)],
+            ),
+            (
+                Some("p2"),
+                None,
+                &[("old2", "/a", 20), ("untimed", "/a", 0)],
+            ),
+        ]);
+        let fetches = std::cell::Cell::new(0);
+        let fetch = |page: Option<String>| {
+            fetches.set(fetches.get() + 1);
+            let response = pages.get(&page).cloned().ok_or("unknown page".to_string());
+            async move { response }
+        };
+        let stale = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime")
+            .block_on(read_stale_thread_ids(fetch, "/a", 40))
+            .expect("stale threads");
+        assert_eq!(stale, ["old1", "old2"]);
+        assert_eq!(fetches.get(), 3);
+    }
+
+    #[test]
+    fn bulk_archive_cutoff_keeps_only_strictly_older_threads() {
+        let cutoff = (1_700_000_000, String::new());
+        assert!(is_before_cursor(
+            &(1_699_999_999, "zzz".to_string()),
+            Some(&cutoff)
+        ));
+        assert!(!is_before_cursor(
+            &(1_700_000_000, "a".to_string()),
+            Some(&cutoff)
+        ));
+    }
+
+    #[test]
+    fn estimate_text_cost_counts_with_the_models_bpe() {
+        let prices = HashMap::from([("gpt-4".to_string(), 30.0)]);
+        let legacy = estimate_text_cost("hello world", Some("gpt-4"), &prices);
+        assert_eq!(legacy.tokenizer, "cl100k_base");
+        assert_eq!(legacy.input_tokens, 2);
+        assert_eq!(legacy.estimated_cost_usd, Some(2.0 * 30.0 / 1_000_000.0));
+
+        let unknown = estimate_text_cost("hello world", Some("local-llm"), &prices);
+        assert_eq!(unknown.tokenizer, "o200k_base");
+        assert_eq!(unknown.estimated_cost_usd, None);
+
+        // Special-token markup in a draft is plain text, not one control token.
+        let markup = estimate_text_cost("<|endoftext|>", None, &prices);
+        assert!(markup.input_tokens > 1, "{markup:?}");
+    }
+
+    #[test]
+    fn thread_info_drops_turns_and_reports_unknown_threads() {
+        let info = thread_info_from_read(
+            "t1",
+            &json!({
+                "result": {
+                    "thread": {
+                        "id": "t1",
+                        "preview": "Fix the parser",
+                        "createdAt": 1_700_000_000,
+                        "updatedAt": 1_700_000_500,
+                        "modelProvider": "openai",
+                        "turns": [{ "items": [] }, { "items": [] }]
+                    },
+                    "model": "gpt-5"
+                }
+            }),
+        )
+        .expect("thread info");
+        assert_eq!(info.turn_count, Some(2));
+        assert_eq!(info.updated_at, Some(1_700_000_500));
+        assert_eq!(info.model.as_deref(), Some("gpt-5"));
+        assert_eq!(info.name, None);
+
+        let counted = thread_info_from_read(
+            "t2",
+            &json!({ "result": { "thread": { "id": "t2", "turnCount": 7, "turns": [] } } }),
+        )
+        .expect("thread info");
+        assert_eq!(counted.turn_count, Some(7));
+        let uncounted =
+            thread_info_from_read("t3", &json!({ "result": { "thread": { "id": "t3" } } }))
+                .expect("thread info");
+        assert_eq!(uncounted.turn_count, None);
+
+        assert_eq!(
+            thread_info_from_read(
+                "missing",
+                &json!({ "error": { "code": -32600, "message": "no rollout found for thread id missing" } })
+            ),
+            Err("thread not found: missing".to_string())
+        );
+        // Other failures keep the app-server's message, whatever it says.
+        assert_eq!(
+            thread_info_from_read(
+                "t1",
+                &json!({ "error": { "code": -32603, "message": "rollout file not found on disk" } })
+            ),
+            Err("rollout file not found on disk".to_string())
+        );
+    }
+
+    #[test]
+    fn probed_method_support_tells_unknown_methods_from_bad_params() {
+        let error = |code: i64, message: &str| {
+            Ok(json!({ "id": 1, "error": { "code": code, "message": message } }))
+        };
+        assert_eq!(
+            probed_method_support(&Ok(json!({ "id": 1, "result": { "data": [] } }))),
+            Some(true)
+        );
+        assert_eq!(
+            probed_method_support(&error(-32600, "Invalid request: missing field `threadId`")),
+            Some(true)
+        );
+        assert_eq!(
+            probed_method_support(&error(
+                -32600,
+                "Invalid request: unknown variant `thread/fork`, expected one of `thread/start`"
+            )),
+            Some(false)
+        );
+        assert_eq!(
+            probed_method_support(&error(-32601, "Method not found")),
+            Some(false)
+        );
+        assert_eq!(
+            probed_method_support(&error(-32000, "Unknown method: turn/steer")),
+            Some(false)
+        );
+        assert_eq!(
+            probed_method_support(&Err("thread/fork timed out after 10s".to_string())),
+            None
+        );
+    }
+
+    #[test]
+    fn reset_reads_turns_from_the_resumed_thread_and_surfaces_errors() {
+        let resumed = json!({ "result": { "thread": { "id": "t1", "turns": [{}, {}, {}] } } });
+        let thread = app_server_result(&resumed, "thread/resume failed")
+            .expect("result")
+            .get("thread");
+        assert_eq!(thread_turn_total(thread), 3);
+        assert_eq!(thread_turn_total(Some(&json!({ "id": "t1" }))), 0);
+        assert_eq!(
+            app_server_result(&json!({ "error": {} }), "thread/rollback failed"),
+            Err("thread/rollback failed".to_string())
+        );
+    }
+
+    #[test]
+    fn validate_effort_checks_the_models_supported_levels() {
+        let list = model_list();
+        assert_eq!(validate_effort(&list, None, "HIGH").as_deref(), Ok("high"));
+        assert_eq!(
+            validate_effort(&list, Some("gpt-5"), "xhigh"),
+            Err(
+                "Effort `xhigh` is not supported by `gpt-5`; expected one of: low, medium, high."
+                    .to_string()
+            )
+        );
+        assert!(validate_effort(&list, Some("mini"), "low")
+            .is_err_and(|err| err.contains("does not support")));
+        assert!(validate_effort(&list, Some("other"), "low")
+            .is_err_and(|err| err.contains("not in the model list")));
+    }
+
+    #[test]
+    fn collaboration_modes_are_described_validated_and_turned_into_turn_params() {
+        let mut list = json!({
+            "id": 3,
+            "result": {
+                "data": [
+                    {
+                        "mode": "plan",
+                        "description": "Plans before editing.",
+                        "settings": { "model": "gpt-5", "reasoning_effort": "high" }
+                    },
+                    { "name": "code", "model": "gpt-5-codex" }
+                ]
+            }
+        });
+        assert_eq!(
+            validate_collaboration_mode(&list, "PLAN").as_deref(),
+            Ok("plan")
+        );
+        assert_eq!(
+            validate_collaboration_mode(&list, "review"),
+            Err(
+                "Collaboration mode `review` is not available; expected one of: plan, code."
+                    .to_string()
+            )
+        );
+
+        describe_collaboration_modes(&mut list, Some("code"));
+        let modes = collaboration_modes(&list);
+        assert_eq!(modes[0]["description"], "Plans before editing.");
+        assert_eq!(modes[0]["isDefault"], false);
+        assert_eq!(modes[1]["description"], Value::Null);
+        assert_eq!(modes[1]["isDefault"], true);
+
+        assert_eq!(
+            collaboration_mode_payload(&modes[0], None, Some("low")),
+            json!({
+                "mode": "plan",
+                "settings": {
+                    "model": "gpt-5",
+                    "reasoning_effort": "low",
+                    "developer_instructions": null
+                }
+            })
+        );
+        assert_eq!(
+            collaboration_mode_payload(&modes[1], Some("o3"), None)["settings"]["model"],
+            "o3"
+        );
+
+        assert_eq!(
+            default_collaboration_mode_payload(&list, "code", None, None).expect("listed default")
+                ["mode"],
+            "code"
+        );
+        assert_eq!(
+            default_collaboration_mode_payload(&list, "review", None, None),
+            Err("Default collaboration mode is unavailable: Collaboration mode `review` is not available; expected one of: plan, code.".to_string())
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use std::io::ErrorKind;
+use std::path::Path;
+use std::time::Duration;
+
+use futures_util::future::join3;
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+
+use crate::shared::codex_aux_core::codex_doctor_core;
+use crate::shared::process_core::tokio_command;
+use crate::types::{AppSettings, DaemonDoctorReport, DoctorCheck};
+use crate::utils::{git_env_path, resolve_git_binary};
+
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+const DISK_SPACE_WARN_BYTES: u64 = 1024 * 1024 * 1024;
+const DISK_SPACE_FAIL_BYTES: u64 = 100 * 1024 * 1024;
+
+fn check(name: &str, status: &str, message: impl Into<String>) -> DoctorCheck {
+    DoctorCheck {
+        name: name.to_string(),
+        status: status.to_string(),
+        message: message.into(),
+        remediation: None,
+    }
+}
+
+fn check_with_hint(
+    name: &str,
+    status: &str,
+    message: impl Into<String>,
+    remediation: &str,
+) -> DoctorCheck {
+    DoctorCheck {
+        remediation: Some(remediation.to_string()),
+        ..check(name, status, message)
+    }
+}
+
+fn output_detail(output: &std::process::Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stderr.trim().is_empty() {
+        stdout.trim().to_string()
+    } else {
+        stderr.trim().to_string()
+    }
+}
+
+async fn check_git() -> DoctorCheck {
+    const HINT: &str = "Install git and make sure it is on PATH.";
+    let git_bin = match resolve_git_binary() {
+        Ok(git_bin) => git_bin,
+        Err(err) => return check_with_hint("git", "fail", err, HINT),
+    };
+    let mut command = tokio_command(git_bin);
+    command.arg("--version").env("PATH", git_env_path());
+    match timeout(CHECK_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => check(
+            "git",
+            "pass",
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ),
+        Ok(Ok(output)) => check_with_hint("git", "fail", output_detail(&output), HINT),
+        Ok(Err(err)) => check_with_hint("git", "fail", format!("Failed to run git: {err}"), HINT),
+        Err(_) => check_with_hint("git", "fail", "Timed out while running git.", HINT),
+    }
+}
+
+async fn check_github_auth() -> DoctorCheck {
+    let mut command = tokio_command("gh");
+    command.args(["auth", "status"]);
+    match timeout(CHECK_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => {
+            check("githubAuth", "pass", "GitHub CLI is authenticated.")
+        }
+        Ok(Ok(output)) => check_with_hint(
+            "githubAuth",
+            "warn",
+            format!(
+                "GitHub CLI is not authenticated: {}",
+                output_detail(&output)
+            ),
+            "Run `gh auth login` to enable issues and pull requests.",
+        ),
+        Ok(Err(err)) if err.kind() == ErrorKind::NotFound => check_with_hint(
+            "githubAuth",
+            "warn",
+            "GitHub CLI (gh) not found on PATH.",
+            "Install gh from https://cli.github.com to enable GitHub features.",
+        ),
+        Ok(Err(err)) => check("githubAuth", "warn", format!("Failed to run gh: {err}")),
+        Err(_) => check("githubAuth", "warn", "Timed out while running gh."),
+    }
+}
+
+async fn check_codex(app_settings: &Mutex<AppSettings>) -> DoctorCheck {
+    const HINT: &str = "Install the Codex CLI or set its path in settings.";
+    let report = match codex_doctor_core(app_settings, None, None).await {
+        Ok(report) => report,
+        Err(err) => return check_with_hint("codex", "fail", err, HINT),
+    };
+    let version = report.version.as_deref().unwrap_or("unknown version");
+    if !report.ok {
+        let details = report
+            .details
+            .as_deref()
+            .unwrap_or("Codex CLI is not usable.");
+        return check_with_hint("codex", "fail", details, HINT);
+    }
+    if !report.node_ok {
+        return check_with_hint(
+            "codex",
+            "warn",
+            format!("Codex {version} found, but Node is unavailable."),
+            "Install Node.js and make sure it is on PATH.",
+        );
+    }
+    check("codex", "pass", format!("Codex {version}"))
+}
+
+fn check_data_dir_writable(data_dir: &Path) -> DoctorCheck {
+    const HINT: &str = "Fix permissions on the data directory or choose another --data-dir.";
+    if let Err(err) = std::fs::create_dir_all(data_dir) {
+        return check_with_hint(
+            "dataDir",
+            "fail",
+            format!("Cannot create {}: {err}", data_dir.display()),
+            HINT,
+        );
+    }
+    let probe = data_dir.join(format!(".doctor-probe-{}", std::process::id()));
+    let result = std::fs::write(&probe, b"ok");
+    let _ = std::fs::remove_file(&probe);
+    match result {
+        Ok(()) => check(
+            "dataDir",
+            "pass",
+            format!("{} is writable.", data_dir.display()),
+        ),
+        Err(err) => check_with_hint(
+            "dataDir",
+            "fail",
+            format!("{} is not writable: {err}", data_dir.display()),
+            HINT,
+        ),
+    }
+}
+
+#[cfg(unix)]
+fn available_disk_bytes(path: &Path) -> Result<u64, String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path =
+        std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|err| err.to_string())?;
+    let mut stats = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: statvfs fills the struct when it returns 0.
+    let result = unsafe { libc::statvfs(c_path.as_ptr(), stats.as_mut_ptr()) };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    // SAFETY: initialized by the successful statvfs call above.
+    let stats = unsafe { stats.assume_init() };
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+#[cfg(not(unix))]
+fn available_disk_bytes(_path: &Path) -> Result<u64, String> {
+    Err("Disk space checks are not supported on this platform.".to_string())
+}
+
+fn disk_space_check(available: u64) -> DoctorCheck {
+    const HINT: &str = "Free up disk space on the volume holding the data directory.";
+    let message = format!("{} MiB free", available / (1024 * 1024));
+    if available < DISK_SPACE_FAIL_BYTES {
+        check_with_hint("diskSpace", "fail", message, HINT)
+    } else if available < DISK_SPACE_WARN_BYTES {
+        check_with_hint("diskSpace", "warn", message, HINT)
+    } else {
+        check("diskSpace", "pass", message)
+    }
+}
+
+fn check_disk_space(data_dir: &Path) -> DoctorCheck {
+    match available_disk_bytes(data_dir) {
+        Ok(available) => disk_space_check(available),
+        Err(err) => check("diskSpace", "warn", err),
+    }
+}
+
+/// Runs every environment check a support flow needs to triage an install.
+/// Checks never error; failures are reported per check.
+pub(crate) async fn daemon_doctor_core(
+    app_settings: &Mutex<AppSettings>,
+    data_dir: &Path,
+) -> DaemonDoctorReport {
+    let (git, github_auth, codex) =
+        join3(check_git(), check_github_auth(), check_codex(app_settings)).await;
+    let checks = vec![
+        git,
+        github_auth,
+        codex,
+        check_data_dir_writable(data_dir),
+        check_disk_space(data_dir),
+    ];
+    DaemonDoctorReport {
+        ok: checks.iter().all(|check| check.status != "fail"),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disk_space_check_grades_thresholds() {
+        assert_eq!(disk_space_check(10 * 1024 * 1024).status, "fail");
+        assert_eq!(disk_space_check(512 * 1024 * 1024).status, "warn");
+        let healthy = disk_space_check(8 * 1024 * 1024 * 1024);
+        assert_eq!(healthy.status, "pass");
+        assert!(healthy.remediation.is_none());
+    }
+
+    #[test]
+    fn data_dir_check_creates_and_probes_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-monitor-doctor-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        let result = check_data_dir_writable(&dir.join("nested"));
+        assert_eq!(result.status, "pass");
+        assert_eq!(
+            std::fs::read_dir(dir.join("nested"))
+                .expect("read dir")
+                .count(),
+            0
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
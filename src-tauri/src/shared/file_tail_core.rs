@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::sync::Mutex;
+use tokio::time::{timeout, Instant};
+use uuid::Uuid;
+
+use crate::backend::events::{EventSink, WorkspaceFileTail};
+use crate::shared::workspace_files_core::{
+    canonical_workspace_root, relative_to_root, resolve_readable_path,
+};
+use crate::types::{WorkspaceEntry, WorkspaceFileTailStart};
+
+const TAIL_DEFAULT_LINES: usize = 100;
+const TAIL_MAX_LINES: usize = 5_000;
+/// Each tail holds a watcher and a task; forgotten tails must not pile up.
+const MAX_ACTIVE_TAILS: usize = 8;
+/// Bytes read back from the end of the file to find the initial lines.
+const TAIL_INITIAL_READ_BYTES: u64 = 1024 * 1024;
+/// Bytes read per change. A file growing faster than this skips ahead, and an
+/// unterminated line longer than this is sent as it is.
+const TAIL_MAX_READ_BYTES: u64 = 1024 * 1024;
+const TAIL_DEBOUNCE: Duration = Duration::from_millis(100);
+const TAIL_MAX_DELAY: Duration = Duration::from_millis(500);
+
+/// A running tail and who it belongs to.
+struct ActiveTail {
+    workspace_id: String,
+    /// The daemon connection that started it; `None` for the app's own
+    /// webview, which never disconnects.
+    connection: Option<u64>,
+    /// Only held: dropping it stops the watcher and ends the follow task.
+    _watcher: RecommendedWatcher,
+}
+
+/// Active tails keyed by tail id.
+#[derive(Default)]
+pub(crate) struct FileTails {
+    watchers: StdMutex<HashMap<String, ActiveTail>>,
+}
+
+impl FileTails {
+    fn is_full(&self) -> bool {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+            >= MAX_ACTIVE_TAILS
+    }
+
+    fn try_insert(&self, tail_id: String, tail: ActiveTail) -> Result<(), String> {
+        let mut watchers = self.watchers.lock().unwrap_or_else(|err| err.into_inner());
+        if watchers.len() >= MAX_ACTIVE_TAILS {
+            return Err(too_many_tails());
+        }
+        watchers.insert(tail_id, tail);
+        Ok(())
+    }
+
+    /// Stops a tail; returns false when no tail has that id.
+    pub(crate) fn untail(&self, tail_id: &str) -> bool {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(tail_id)
+            .is_some()
+    }
+
+    /// Stops every tail a daemon connection started, once it disconnects.
+    pub(crate) fn untail_connection(&self, connection: u64) {
+        self.retain(|tail| tail.connection != Some(connection));
+    }
+
+    fn retain(&self, keep: impl Fn(&ActiveTail) -> bool) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|_, tail| keep(tail));
+    }
+}
+
+fn too_many_tails() -> String {
+    format!("Too many active tails (max {MAX_ACTIVE_TAILS}); untail one first")
+}
+
+/// Device and inode, so a log rotated to a new file under the same name is
+/// told apart from one that was appended to.
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+fn split_lines(bytes: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(bytes)
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect()
+}
+
+/// Where a tail has read up to. `pending` holds the unterminated end of the
+/// file until its newline arrives.
+struct TailCursor {
+    path: PathBuf,
+    offset: u64,
+    identity: Option<(u64, u64)>,
+    pending: Vec<u8>,
+}
+
+impl TailCursor {
+    /// Opens `path` and returns its last `lines` lines with a cursor at the end.
+    fn open(path: PathBuf, lines: usize) -> io::Result<(Vec<String>, Self)> {
+        let mut file = File::open(&path)?;
+        let metadata = file.metadata()?;
+        let start = metadata.len().saturating_sub(TAIL_INITIAL_READ_BYTES);
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(metadata.len() - start).read_to_end(&mut bytes)?;
+        let offset = start + bytes.len() as u64;
+
+        // An unterminated last line may still be being written; it is held
+        // back like in `read_new_lines` and sent once its newline arrives.
+        let (mut last, pending) = match bytes.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => {
+                let pending = bytes.split_off(end + 1);
+                (split_lines(&bytes[..end]), pending)
+            }
+            None => (Vec::new(), bytes),
+        };
+        if start > 0 && !last.is_empty() {
+            // Starting mid-file almost certainly cut the first line short.
+            last.remove(0);
+        }
+        let last = last.split_off(last.len().saturating_sub(lines));
+        let cursor = Self {
+            path,
+            offset,
+            identity: file_identity(&metadata),
+            pending,
+        };
+        Ok((last, cursor))
+    }
+
+    /// Complete lines appended since the last read, and whether the file was
+    /// rotated or truncated first. A rotation shows up as a new inode and a
+    /// truncation as a file shorter than what was already read; either way
+    /// reading restarts from the top. `None` while the file is missing, e.g.
+    /// between a rotation's rename and the new file appearing.
+    fn read_new_lines(&mut self) -> io::Result<Option<(Vec<String>, bool)>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let metadata = file.metadata()?;
+        let identity = file_identity(&metadata);
+        let reset = identity != self.identity || metadata.len() < self.offset;
+        if reset {
+            self.identity = identity;
+            self.offset = 0;
+            self.pending.clear();
+        }
+
+        let start = self
+            .offset
+            .max(metadata.len().saturating_sub(TAIL_MAX_READ_BYTES));
+        if start > self.offset {
+            self.pending.clear();
+        }
+        file.seek(SeekFrom::Start(start))?;
+        let mut bytes = Vec::new();
+        file.take(metadata.len() - start).read_to_end(&mut bytes)?;
+        self.offset = start + bytes.len() as u64;
+        self.pending.extend_from_slice(&bytes);
+
+        let lines = match self.pending.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => {
+                let rest = self.pending.split_off(end + 1);
+                let complete = std::mem::replace(&mut self.pending, rest);
+                split_lines(&complete[..end])
+            }
+            None if self.pending.len() as u64 >= TAIL_MAX_READ_BYTES => {
+                split_lines(&std::mem::take(&mut self.pending))
+            }
+            None => Vec::new(),
+        };
+        Ok(Some((lines, reset)))
+    }
+}
+
+async fn follow_tail<E: EventSink>(
+    mut rx: mpsc::UnboundedReceiver<()>,
+    mut cursor: TailCursor,
+    event_sink: E,
+    event: WorkspaceFileTail,
+) {
+    while rx.recv().await.is_some() {
+        let deadline = Instant::now() + TAIL_MAX_DELAY;
+        loop {
+            let wait = TAIL_DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+            match timeout(wait, rx.recv()).await {
+                Ok(Some(())) => continue,
+                // The watcher was dropped by `untail`.
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        let Ok(Some((lines, reset))) = cursor.read_new_lines() else {
+            continue;
+        };
+        if lines.is_empty() && !reset {
+            continue;
+        }
+        event_sink.emit_workspace_file_tail(WorkspaceFileTail {
+            lines,
+            reset,
+            ..event.clone()
+        });
+    }
+}
+
+/// Watches the file's directory rather than the file, so the watch survives
+/// the file being renamed away and recreated by log rotation.
+fn start_tail_watcher<E: EventSink>(
+    cursor: TailCursor,
+    event_sink: E,
+    event: WorkspaceFileTail,
+) -> Result<RecommendedWatcher, String> {
+    let file_path = cursor.path.clone();
+    let directory = file_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Invalid file path".to_string())?;
+    let (tx, rx) = mpsc::unbounded_channel();
+    let watched = file_path.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result {
+            if !matches!(event.kind, EventKind::Access(_)) && event.paths.contains(&watched) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|err| format!("Failed to create watcher: {err}"))?;
+    watcher
+        .watch(&directory, RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", directory.display()))?;
+    tokio::spawn(follow_tail(rx, cursor, event_sink, event));
+    Ok(watcher)
+}
+
+/// Returns the last `lines` lines of a workspace file, then keeps pushing
+/// `workspace-file-tail` events with newly appended lines until
+/// `untail_workspace_file` is called with the returned tail id, `connection`
+/// disconnects or the workspace is removed.
+pub(crate) async fn tail_workspace_file_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    tails: &FileTails,
+    event_sink: E,
+    connection: Option<u64>,
+    workspace_id: String,
+    path: String,
+    lines: Option<u32>,
+) -> Result<WorkspaceFileTailStart, String> {
+    if tails.is_full() {
+        return Err(too_many_tails());
+    }
+    let root = canonical_workspace_root(workspaces, &workspace_id).await?;
+    let file_path = resolve_readable_path(&root, &path)?;
+    if !file_path.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    let relative_path = relative_to_root(&root, &file_path);
+    let lines = lines
+        .map(|lines| lines as usize)
+        .unwrap_or(TAIL_DEFAULT_LINES)
+        .min(TAIL_MAX_LINES);
+    let (initial_lines, cursor) =
+        TailCursor::open(file_path, lines).map_err(|err| format!("Failed to read file: {err}"))?;
+
+    let tail_id = Uuid::new_v4().to_string();
+    let event = WorkspaceFileTail {
+        workspace_id: workspace_id.clone(),
+        tail_id: tail_id.clone(),
+        path: relative_path.clone(),
+        lines: Vec::new(),
+        reset: false,
+    };
+    let watcher = start_tail_watcher(cursor, event_sink, event)?;
+    tails.try_insert(
+        tail_id.clone(),
+        ActiveTail {
+            workspace_id,
+            connection,
+            _watcher: watcher,
+        },
+    )?;
+    Ok(WorkspaceFileTailStart {
+        tail_id,
+        path: relative_path,
+        lines: initial_lines,
+    })
+}
+
+/// Stops tails of workspaces that no longer exist, e.g. after a removal that
+/// also took its worktrees with it.
+pub(crate) async fn prune_file_tails_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    tails: &FileTails,
+) {
+    let workspaces = workspaces.lock().await;
+    tails.retain(|tail| workspaces.contains_key(&tail.workspace_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    fn append(path: &Path, text: &str) {
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .expect("open log")
+            .write_all(text.as_bytes())
+            .expect("append log");
+    }
+
+    #[test]
+    fn tail_cursor_follows_appends_and_restarts_after_truncation_or_rotation() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-tail-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let log = dir.join("app.log");
+        fs::write(&log, "one\ntwo\nthree\n").expect("write log");
+
+        let (initial, mut cursor) = TailCursor::open(log.clone(), 2).expect("open tail");
+        assert_eq!(initial, vec!["two", "three"]);
+
+        append(&log, "four\nfi");
+        let (lines, reset) = cursor.read_new_lines().expect("read").expect("file");
+        assert_eq!((lines, reset), (vec!["four".to_string()], false));
+        append(&log, "ve\r\n");
+        let (lines, _) = cursor.read_new_lines().expect("read").expect("file");
+        assert_eq!(lines, vec!["five"]);
+
+        fs::write(&log, "fresh\n").expect("truncate log");
+        let (lines, reset) = cursor.read_new_lines().expect("read").expect("file");
+        assert_eq!((lines, reset), (vec!["fresh".to_string()], true));
+
+        fs::rename(&log, dir.join("app.log.1")).expect("rotate log");
+        assert!(cursor.read_new_lines().expect("read").is_none());
+        fs::write(&log, "rotated\nlonger than before\n").expect("recreate log");
+        let (lines, reset) = cursor.read_new_lines().expect("read").expect("file");
+        assert_eq!(lines, vec!["rotated", "longer than before"]);
+        assert_eq!(reset, cfg!(unix));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tail_cursor_holds_back_an_unterminated_last_line() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-tail-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let log = dir.join("app.log");
+        fs::write(&log, "one\ntwo\nthr").expect("write log");
+
+        let (initial, mut cursor) = TailCursor::open(log.clone(), 5).expect("open tail");
+        assert_eq!(initial, vec!["one", "two"]);
+        append(&log, "ee\n");
+        let (lines, reset) = cursor.read_new_lines().expect("read").expect("file");
+        assert_eq!((lines, reset), (vec!["three".to_string()], false));
+
+        fs::write(&log, "partial").expect("rewrite log");
+        let (initial, _) = TailCursor::open(log.clone(), 5).expect("open tail");
+        assert!(initial.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn tails_stop_with_their_connection_or_workspace() {
+        let tails = FileTails::default();
+        let tail = |workspace_id: &str, connection: Option<u64>| ActiveTail {
+            workspace_id: workspace_id.to_string(),
+            connection,
+            _watcher: notify::recommended_watcher(|_: notify::Result<Event>| {}).expect("watcher"),
+        };
+        tails
+            .try_insert("a".into(), tail("ws-1", Some(1)))
+            .expect("a");
+        tails
+            .try_insert("b".into(), tail("ws-1", Some(2)))
+            .expect("b");
+        tails
+            .try_insert("c".into(), tail("ws-2", Some(1)))
+            .expect("c");
+        tails.try_insert("d".into(), tail("ws-2", None)).expect("d");
+
+        tails.untail_connection(1);
+        assert!(!tails.untail("a"));
+        assert!(!tails.untail("c"));
+
+        tails.retain(|tail| tail.workspace_id != "ws-1");
+        assert!(!tails.untail("b"));
+        assert!(tails.untail("d"));
+    }
+}
@@ -1,14 +1,24 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use git2::Repository;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
 use crate::codex::home as codex_home;
-use crate::files::io::TextFileResponse;
+use crate::files::io::{content_hash, FileWriteResponse, TextFileResponse};
 use crate::files::ops::{read_with_policy, write_with_policy};
 use crate::files::policy::{policy_for, FileKind, FileScope};
 use crate::types::WorkspaceEntry;
 
+/// One AGENTS.md that Codex loads for a workspace.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AgentsMdFile {
+    pub path: String,
+    pub scope: FileScope,
+    pub content: String,
+}
+
 fn resolve_default_codex_home() -> Result<PathBuf, String> {
     codex_home::resolve_default_codex_home()
         .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
@@ -50,14 +60,183 @@ pub(crate) async fn file_read_core(
     read_with_policy(&root, policy)
 }
 
+/// Writes the file, first checking `expected_hash` against what is on disk
+/// when given, so an edit made since the client's read is not overwritten.
+/// A file that was missing when read has no hash; leave `expected_hash` out
+/// to write it unconditionally.
 pub(crate) async fn file_write_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
     content: String,
-) -> Result<(), String> {
+    expected_hash: Option<String>,
+) -> Result<FileWriteResponse, String> {
     let policy = policy_for(scope, kind)?;
     let root = resolve_root_core(workspaces, scope, workspace_id.as_deref()).await?;
-    write_with_policy(&root, policy, &content)
+    if let Some(expected_hash) = expected_hash {
+        let current = read_with_policy(&root, policy)?;
+        if current.hash.as_deref() != Some(expected_hash.as_str()) {
+            return Err(format!(
+                "conflict: {} changed since it was read",
+                policy.filename
+            ));
+        }
+    }
+    write_with_policy(&root, policy, &content)?;
+    Ok(FileWriteResponse {
+        hash: content_hash(&content),
+    })
+}
+
+/// Directories Codex reads project AGENTS.md files from, outermost first: the
+/// repository root down to the workspace. Never walks above the repository, or
+/// above the workspace itself when it is not inside one.
+fn project_agents_md_dirs(workspace_root: &Path) -> Result<Vec<PathBuf>, String> {
+    let workspace_root = workspace_root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let repo_root = Repository::discover(&workspace_root)
+        .ok()
+        .and_then(|repo| repo.workdir().and_then(|dir| dir.canonicalize().ok()))
+        .filter(|repo_root| workspace_root.starts_with(repo_root));
+    let Some(repo_root) = repo_root else {
+        return Ok(vec![workspace_root]);
+    };
+    let mut dirs: Vec<PathBuf> = workspace_root
+        .ancestors()
+        .take_while(|dir| dir.starts_with(&repo_root))
+        .map(Path::to_path_buf)
+        .collect();
+    dirs.reverse();
+    Ok(dirs)
+}
+
+/// AGENTS.md files in precedence order, lowest first: the global one from
+/// CODEX_HOME, then each project directory down to the workspace. Missing
+/// files are left out.
+pub(crate) fn collect_agents_md(
+    workspace_root: &Path,
+    codex_home: Option<&Path>,
+) -> Result<Vec<AgentsMdFile>, String> {
+    let mut files = Vec::new();
+    let mut push_if_exists = |root: PathBuf, scope: FileScope| -> Result<(), String> {
+        let policy = policy_for(scope, FileKind::Agents)?;
+        let response = read_with_policy(&root, policy)?;
+        if response.exists {
+            files.push(AgentsMdFile {
+                path: root.join(policy.filename).to_string_lossy().to_string(),
+                scope,
+                content: response.content,
+            });
+        }
+        Ok(())
+    };
+    if let Some(codex_home) = codex_home {
+        push_if_exists(codex_home.to_path_buf(), FileScope::Global)?;
+    }
+    for dir in project_agents_md_dirs(workspace_root)? {
+        push_if_exists(dir, FileScope::Workspace)?;
+    }
+    Ok(files)
+}
+
+pub(crate) async fn get_effective_agents_md_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<Vec<AgentsMdFile>, String> {
+    let (entry, parent_entry) = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let parent_entry = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent_entry)
+    };
+    let codex_home = codex_home::resolve_workspace_codex_home(&entry, parent_entry.as_ref())
+        .or_else(codex_home::resolve_default_codex_home);
+    collect_agents_md(Path::new(&entry.path), codex_home.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::shared::test_support::{run, workspaces_for};
+
+    #[test]
+    fn file_write_refuses_when_the_file_changed_since_it_was_read() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-write-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create workspace root");
+        let workspaces = workspaces_for(&root);
+        let write = |content: &str, expected_hash: Option<String>| {
+            run(file_write_core(
+                &workspaces,
+                FileScope::Workspace,
+                FileKind::Agents,
+                Some("w1".to_string()),
+                content.to_string(),
+                expected_hash,
+            ))
+        };
+
+        let first = write("v1", None).expect("first write");
+        let read = run(file_read_core(
+            &workspaces,
+            FileScope::Workspace,
+            FileKind::Agents,
+            Some("w1".to_string()),
+        ))
+        .expect("read");
+        assert_eq!(read.hash.as_deref(), Some(first.hash.as_str()));
+
+        fs::write(root.join("AGENTS.md"), "external edit").expect("external edit");
+        let err = write("v2", Some(first.hash.clone())).expect_err("stale hash");
+        assert!(err.starts_with("conflict:"), "{err}");
+        assert_eq!(
+            fs::read_to_string(root.join("AGENTS.md")).expect("read back"),
+            "external edit"
+        );
+
+        let current = content_hash("external edit");
+        write("v2", Some(current)).expect("write with current hash");
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn collect_agents_md_stops_at_the_repository_root() {
+        let base = std::env::temp_dir().join(format!("codex-monitor-agents-{}", Uuid::new_v4()));
+        let repo = base.join("repo");
+        let nested = repo.join("packages").join("app");
+        let codex_home = base.join("codex-home");
+        fs::create_dir_all(&nested).expect("create nested dir");
+        fs::create_dir_all(&codex_home).expect("create codex home");
+        Repository::init(&repo).expect("init repo");
+        fs::write(base.join("AGENTS.md"), "outside").expect("write outside");
+        fs::write(codex_home.join("AGENTS.md"), "global").expect("write global");
+        fs::write(repo.join("AGENTS.md"), "repo").expect("write repo");
+        fs::write(nested.join("AGENTS.md"), "app").expect("write nested");
+
+        let files = collect_agents_md(&nested, Some(&codex_home)).expect("collect");
+        let contents: Vec<&str> = files.iter().map(|file| file.content.as_str()).collect();
+        assert_eq!(contents, vec!["global", "repo", "app"]);
+        assert_eq!(files[0].scope, FileScope::Global);
+        assert!(files[2].path.ends_with("app/AGENTS.md"));
+
+        let outside = base.join("loose");
+        fs::create_dir_all(&outside).expect("create loose dir");
+        let files = collect_agents_md(&outside, None).expect("collect outside repo");
+        assert!(files.is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
 }
@@ -1,6 +1,9 @@
 #![allow(dead_code)]
 
 use std::path::PathBuf;
+use std::process::Stdio;
+
+use tokio::io::AsyncReadExt;
 
 use crate::shared::process_core::tokio_command;
 use crate::utils::{git_env_path, resolve_git_binary};
@@ -64,6 +67,52 @@ pub(crate) async fn run_git_command_bytes(
     Err(format_git_error(&output.stdout, &output.stderr))
 }
 
+/// Runs a git command with `-z` output only until it prints its first
+/// NUL-terminated record, then kills it. Returns whether there was a record,
+/// for checks that only need to know whether a listing is empty.
+pub(crate) async fn git_prints_any_record(
+    repo_path: &PathBuf,
+    args: &[&str],
+) -> Result<bool, String> {
+    let git_bin = resolve_git_binary().map_err(|err| format!("Failed to run git: {err}"))?;
+    let mut child = tokio_command(git_bin)
+        .args(args)
+        .current_dir(repo_path)
+        .env("PATH", git_env_path())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("Failed to run git: {err}"))?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to run git: missing stdout")?;
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = stdout
+            .read(&mut buffer)
+            .await
+            .map_err(|err| format!("Failed to run git: {err}"))?;
+        if read == 0 {
+            break;
+        }
+        if buffer[..read].contains(&0) {
+            let _ = child.kill().await;
+            return Ok(true);
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|err| format!("Failed to run git: {err}"))?;
+    if output.status.success() {
+        return Ok(false);
+    }
+    Err(format_git_error(&output.stdout, &output.stderr))
+}
+
 pub(crate) async fn run_git_diff(repo_path: &PathBuf, args: &[&str]) -> Result<Vec<u8>, String> {
     let git_bin = resolve_git_binary().map_err(|err| format!("Failed to run git: {err}"))?;
     let output = tokio_command(git_bin)
@@ -239,3 +288,84 @@ pub(crate) async fn git_get_origin_url(repo_path: &PathBuf) -> Option<String> {
         .await
         .ok()
 }
+
+fn parse_stash_index(stash: &str) -> Option<u32> {
+    stash
+        .strip_prefix("stash@{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .unwrap_or(stash)
+        .parse()
+        .ok()
+}
+
+/// The message part of a stash subject, `On main: message` or
+/// `WIP on main: abc1234 last commit`. Branch names can't contain `:`.
+fn stash_message(subject: &str) -> &str {
+    subject
+        .split_once(": ")
+        .map_or(subject, |(_, message)| message)
+}
+
+/// Finds the one entry of `git stash list --format=%gd%x1f%H%x1f%gs` whose
+/// message (or whole subject) is `message`.
+pub(crate) fn find_stash_by_message(
+    stash_list: &str,
+    message: &str,
+) -> Result<(String, String), String> {
+    let matches: Vec<(&str, &str)> = stash_list
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\u{1f}');
+            Some((fields.next()?, fields.next()?, fields.next()?))
+        })
+        .filter(|(_, _, subject)| *subject == message || stash_message(subject) == message)
+        .map(|(stash_ref, sha, _)| (stash_ref, sha))
+        .collect();
+    match matches.as_slice() {
+        [(stash_ref, sha)] => Ok((stash_ref.to_string(), sha.to_string())),
+        [] => Err(format!(
+            "No stash matches `{message}`; it may have been applied or dropped."
+        )),
+        _ => Err(format!(
+            "Stash message `{message}` is ambiguous; it matches {}. Use the stash index instead.",
+            matches
+                .iter()
+                .map(|(stash_ref, _)| *stash_ref)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
+
+/// Resolves a stash given by index (`2` or `stash@{2}`) or by message to its
+/// current `stash@{n}` ref and commit. Messages are looked up in the stash
+/// list on every call, so they survive indices shifting as stashes come and
+/// go; callers acting on the ref later should check it still names the
+/// returned commit.
+pub(crate) async fn resolve_stash(
+    repo_path: &PathBuf,
+    stash: &str,
+) -> Result<(String, String), String> {
+    let stash = stash.trim();
+    if stash.is_empty() {
+        return Err("stash is required".to_string());
+    }
+    if let Some(index) = parse_stash_index(stash) {
+        let stash_ref = format!("stash@{{{index}}}");
+        let sha = run_git_command(
+            repo_path,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{stash_ref}^{{commit}}"),
+            ],
+        )
+        .await
+        .map_err(|_| format!("Stash {stash_ref} not found."))?;
+        return Ok((stash_ref, sha));
+    }
+    let stash_list =
+        run_git_command(repo_path, &["stash", "list", "--format=%gd%x1f%H%x1f%gs"]).await?;
+    find_stash_by_message(&stash_list, stash)
+}
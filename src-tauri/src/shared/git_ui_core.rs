@@ -1,21 +1,32 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
+use futures_util::future::join_all;
+use git2::{BranchType, DiffOptions, Repository, RepositoryState, Sort, Status, StatusOptions};
 use serde_json::{json, Value};
 use tokio::sync::Mutex;
 
 use crate::git_utils::{
     checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path, image_mime_type,
-    list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
+    list_git_roots as scan_git_roots, parse_github_remote, resolve_git_root,
 };
+use crate::shared::git_core;
 use crate::shared::process_core::tokio_command;
 use crate::types::{
-    AppSettings, BranchInfo, GitCommitDiff, GitFileDiff, GitFileStatus, GitHubIssue,
-    GitHubIssuesResponse, GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff,
-    GitHubPullRequestsResponse, GitLogResponse, WorkspaceEntry,
+    AppSettings, BranchInfo, GitBranchPruneResult, GitCommitDiff, GitCommitStats, GitFileDiff,
+    GitFileStatus, GitHubIssue, GitHubIssuesResponse, GitHubPullRequest, GitHubPullRequestAuthor,
+    GitHubPullRequestCheckout, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequestReviewComment, GitHubPullRequestReviewThread, GitHubPullRequestsResponse,
+    GitHubRepoInfo, GitHubRepoSlug, GitIncomingChanges, GitIncomingFile, GitLargeFile,
+    GitLastCommit, GitLockFile, GitLogEntry, GitLogResponse, GitMergedBranch, GitMergedBranches,
+    GitOperationProgress, GitPatchApplyResult, GitPatchExport, GitPushResult, GitReflogEntry,
+    GitReflogRestoreResult, GitRejectedHunk, GitRemote, GitRemoteInfo, GitRepairLocksResult,
+    GitStatusBranch, GitStatusDetailed, GitStatusEntry, GitSubmoduleStatus, GitWorkingTreeClean,
+    LargeStagedFilesResponse, RepoWorktreesDiffSummary, WorkspaceEntry, WorktreeBaseDiff,
+    WorktreeDirtySummary,
 };
 use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
 
@@ -81,20 +92,50 @@ fn read_text_lines(path: &Path) -> Option<Vec<String>> {
     Some(split_lines_preserving_newlines(content.as_ref()))
 }
 
-async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
+async fn run_git_command_output(
+    repo_root: &Path,
+    args: &[&str],
+) -> Result<std::process::Output, String> {
     let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
-    let output = tokio_command(git_bin)
+    tokio_command(git_bin)
         .args(args)
         .current_dir(repo_root)
         .env("PATH", git_env_path())
         .output()
         .await
-        .map_err(|e| format!("Failed to run git: {e}"))?;
+        .map_err(|e| format!("Failed to run git: {e}"))
+}
 
-    if output.status.success() {
-        return Ok(());
+async fn run_git_command_with_input(
+    repo_root: &Path,
+    args: &[&str],
+    input: &[u8],
+) -> Result<std::process::Output, String> {
+    use tokio::io::AsyncWriteExt;
+
+    let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
+    let mut child = tokio_command(git_bin)
+        .args(args)
+        .current_dir(repo_root)
+        .env("PATH", git_env_path())
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .await
+            .map_err(|e| format!("Failed to write to git: {e}"))?;
     }
+    child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run git: {e}"))
+}
 
+fn git_failure_detail(output: &std::process::Output) -> String {
     let stderr = String::from_utf8_lossy(&output.stderr);
     let stdout = String::from_utf8_lossy(&output.stdout);
     let detail = if stderr.trim().is_empty() {
@@ -103,9 +144,17 @@ async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String>
         stderr.trim()
     };
     if detail.is_empty() {
-        return Err("Git command failed.".to_string());
+        return "Git command failed.".to_string();
+    }
+    detail.to_string()
+}
+
+async fn run_git_command(repo_root: &Path, args: &[&str]) -> Result<(), String> {
+    let output = run_git_command_output(repo_root, args).await?;
+    if output.status.success() {
+        return Ok(());
     }
-    Err(detail.to_string())
+    Err(git_failure_detail(&output))
 }
 
 fn action_paths_for_file(repo_root: &Path, path: &str) -> Vec<String> {
@@ -206,17 +255,211 @@ fn upstream_remote_and_branch(repo_root: &Path) -> Result<Option<(String, String
     Ok(upstream_name.and_then(parse_upstream_ref))
 }
 
-async fn push_with_upstream(repo_root: &Path) -> Result<(), String> {
-    let upstream = upstream_remote_and_branch(repo_root)?;
-    if let Some((remote, branch)) = upstream {
+fn preferred_remote_name(repo: &Repository) -> Result<String, String> {
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    let remote = if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin".to_string()
+    } else {
+        remotes.iter().flatten().next().unwrap_or("").to_string()
+    };
+    if remote.is_empty() {
+        return Err("No git remote configured.".to_string());
+    }
+    Ok(remote)
+}
+
+fn current_branch_name(repo: &Repository) -> Result<String, String> {
+    let head = repo.head().map_err(|e| e.to_string())?;
+    if !head.is_branch() {
+        return Err("Cannot push a detached HEAD without an upstream branch.".to_string());
+    }
+    Ok(head
+        .shorthand()
+        .ok_or("Current branch name is not valid UTF-8.")?
+        .to_string())
+}
+
+fn default_push_target(repo_root: &Path) -> Result<(String, String), String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let branch = current_branch_name(&repo)?;
+    let remote = preferred_remote_name(&repo)?;
+    Ok((remote, branch))
+}
+
+/// Checks that `remote` names a configured remote and returns it trimmed.
+fn validate_remote_name(repo_root: &Path, remote: &str) -> Result<String, String> {
+    let remote = remote.trim();
+    if remote.is_empty() || remote.starts_with('-') {
+        return Err(format!("Invalid remote name: {remote}"));
+    }
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    if repo.find_remote(remote).is_err() {
+        return Err(format!("Remote `{remote}` does not exist."));
+    }
+    Ok(remote.to_string())
+}
+
+/// Remote and branch for an explicit `remote`: the upstream branch when the
+/// upstream lives on that remote, otherwise the current branch's name.
+fn explicit_remote_target(
+    repo_root: &Path,
+    remote: &str,
+) -> Result<(String, String, bool), String> {
+    let remote = validate_remote_name(repo_root, remote)?;
+    match upstream_remote_and_branch(repo_root)? {
+        Some((upstream_remote, branch)) if upstream_remote == remote => Ok((remote, branch, true)),
+        upstream => {
+            let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+            let branch = current_branch_name(&repo)?;
+            Ok((remote, branch, upstream.is_some()))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct PushRefUpdate {
+    flag: char,
+    summary: String,
+}
+
+/// Parses the per-ref lines of `git push --porcelain`, skipping the
+/// `To <url>` header and trailing `Done`.
+fn parse_push_porcelain(stdout: &str) -> Vec<PushRefUpdate> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let flag_field = fields.next()?;
+            let _refs = fields.next()?;
+            let summary = fields.next()?;
+            let mut flag_chars = flag_field.chars();
+            let flag = flag_chars.next()?;
+            if flag_chars.next().is_some() {
+                return None;
+            }
+            Some(PushRefUpdate {
+                flag,
+                summary: summary.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn push_rejection_reason(summary: &str) -> Option<String> {
+    let start = summary.find('(')?;
+    let end = summary.rfind(')')?;
+    (end > start + 1).then(|| summary[start + 1..end].to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GitPushForce {
+    WithLease,
+    Force,
+}
+
+impl GitPushForce {
+    pub(crate) fn parse(value: Option<&str>) -> Result<Option<Self>, String> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(None),
+            Some("with-lease") => Ok(Some(Self::WithLease)),
+            Some("force") => Ok(Some(Self::Force)),
+            Some(other) => Err(format!(
+                "Invalid force mode `{other}`; expected `with-lease` or `force`."
+            )),
+        }
+    }
+
+    fn flag(self) -> &'static str {
+        match self {
+            Self::WithLease => "--force-with-lease",
+            Self::Force => "--force",
+        }
+    }
+}
+
+async fn push_with_upstream(
+    repo_root: &Path,
+    force: Option<GitPushForce>,
+    remote: Option<&str>,
+) -> Result<GitPushResult, String> {
+    let (remote, branch, upstream_set) = match remote {
+        Some(remote) => {
+            let (remote, branch, has_upstream) = explicit_remote_target(repo_root, remote)?;
+            (remote, branch, !has_upstream)
+        }
+        None => match upstream_remote_and_branch(repo_root)? {
+            Some((remote, branch)) => (remote, branch, false),
+            None => {
+                let (remote, branch) = default_push_target(repo_root)?;
+                (remote, branch, true)
+            }
+        },
+    };
+    // The lease compares against our remote-tracking ref, so refreshing it
+    // right before pushing would make the lease always succeed.
+    if force != Some(GitPushForce::WithLease) {
         let _ = run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
-        let refspec = format!("HEAD:{branch}");
-        return run_git_command(repo_root, &["push", remote.as_str(), refspec.as_str()]).await;
     }
-    run_git_command(repo_root, &["push"]).await
+    let refspec = format!("HEAD:{branch}");
+    let mut args = vec!["push", "--porcelain"];
+    if let Some(force) = force {
+        args.push(force.flag());
+    }
+    if upstream_set {
+        args.push("--set-upstream");
+    }
+    args.push(remote.as_str());
+    args.push(refspec.as_str());
+
+    let output = run_git_command_output(repo_root, &args).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let updates = parse_push_porcelain(&stdout);
+    let rejected = updates.iter().find(|update| update.flag == '!');
+    if !output.status.success() && rejected.is_none() {
+        return Err(git_failure_detail(&output));
+    }
+    if force == Some(GitPushForce::WithLease)
+        && rejected.is_some_and(|update| update.summary.contains("stale info"))
+    {
+        return Err(format!(
+            "Force push rejected: {remote}/{branch} has commits you have not fetched. \
+Fetch and review them before pushing again."
+        ));
+    }
+
+    let summary = updates
+        .iter()
+        .map(|update| update.summary.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    Ok(GitPushResult {
+        remote,
+        branch,
+        created_remote_branch: updates.iter().any(|update| update.flag == '*'),
+        forced: updates.iter().any(|update| update.flag == '+'),
+        upstream_set: upstream_set && rejected.is_none(),
+        up_to_date: !updates.is_empty() && updates.iter().all(|update| update.flag == '='),
+        rejected: rejected.is_some(),
+        rejection_reason: rejected.and_then(|update| push_rejection_reason(&update.summary)),
+        summary,
+    })
+}
+
+fn push_result_to_unit(result: GitPushResult) -> Result<(), String> {
+    if result.rejected {
+        return Err(format!(
+            "Push to {}/{} was rejected: {}",
+            result.remote, result.branch, result.summary
+        ));
+    }
+    Ok(())
 }
 
-async fn fetch_with_default_remote(repo_root: &Path) -> Result<(), String> {
+async fn fetch_with_default_remote(repo_root: &Path, remote: Option<&str>) -> Result<(), String> {
+    if let Some(remote) = remote {
+        let remote = validate_remote_name(repo_root, remote)?;
+        return run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
+    }
     let upstream = upstream_remote_and_branch(repo_root)?;
     if let Some((remote, _)) = upstream {
         return run_git_command(repo_root, &["fetch", "--prune", remote.as_str()]).await;
@@ -224,7 +467,9 @@ async fn fetch_with_default_remote(repo_root: &Path) -> Result<(), String> {
     run_git_command(repo_root, &["fetch", "--prune"]).await
 }
 
-async fn pull_with_default_strategy(repo_root: &Path) -> Result<(), String> {
+/// `remote` pulls the same-named branch from that remote instead of the
+/// configured upstream.
+async fn pull_with_default_strategy(repo_root: &Path, remote: Option<&str>) -> Result<(), String> {
     fn autostash_unsupported(lower: &str) -> bool {
         lower.contains("unknown option") && lower.contains("autostash")
     }
@@ -234,28 +479,42 @@ async fn pull_with_default_strategy(repo_root: &Path) -> Result<(), String> {
             || lower.contains("you have divergent branches")
     }
 
-    match run_git_command(repo_root, &["pull", "--autostash"]).await {
+    let target = match remote {
+        Some(remote) => {
+            let (remote, branch, _) = explicit_remote_target(repo_root, remote)?;
+            vec![remote, branch]
+        }
+        None => Vec::new(),
+    };
+    let pull = |flags: &'static [&'static str]| {
+        let mut args = vec!["pull"];
+        args.extend_from_slice(flags);
+        args.extend(target.iter().map(String::as_str));
+        async move { run_git_command(repo_root, &args).await }
+    };
+
+    match pull(&["--autostash"]).await {
         Ok(()) => Ok(()),
         Err(err) => {
             let lower = err.to_lowercase();
             if autostash_unsupported(&lower) {
-                match run_git_command(repo_root, &["pull"]).await {
+                match pull(&[]).await {
                     Ok(()) => Ok(()),
                     Err(no_autostash_err) => {
                         let no_autostash_lower = no_autostash_err.to_lowercase();
                         if needs_reconcile_strategy(&no_autostash_lower) {
-                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                            return pull(&["--no-rebase"]).await;
                         }
                         Err(no_autostash_err)
                     }
                 }
             } else if needs_reconcile_strategy(&lower) {
-                match run_git_command(repo_root, &["pull", "--no-rebase", "--autostash"]).await {
+                match pull(&["--no-rebase", "--autostash"]).await {
                     Ok(()) => Ok(()),
                     Err(merge_err) => {
                         let merge_lower = merge_err.to_lowercase();
                         if autostash_unsupported(&merge_lower) {
-                            return run_git_command(repo_root, &["pull", "--no-rebase"]).await;
+                            return pull(&["--no-rebase"]).await;
                         }
                         Err(merge_err)
                     }
@@ -375,20 +634,43 @@ fn collect_workspace_diff(repo_root: &Path) -> Result<String, String> {
     Ok(build_combined_diff(&diff))
 }
 
-fn github_repo_from_path(path: &Path) -> Result<String, String> {
+/// `github_hosts` is the `githubEnterpriseHosts` setting.
+fn github_repo_info_for_repo(
+    repo: &Repository,
+    github_hosts: &[String],
+) -> Result<GitHubRepoInfo, String> {
+    let names = repo.remotes().map_err(|e| e.to_string())?;
+    let mut remotes = Vec::new();
+    for name in names.iter().flatten() {
+        let remote = repo.find_remote(name).map_err(|e| e.to_string())?;
+        let Some(url) = remote.url() else {
+            continue;
+        };
+        remotes.push(GitRemoteInfo {
+            name: name.to_string(),
+            url: url.to_string(),
+            github: parse_github_remote(url, github_hosts),
+        });
+    }
+    let primary = remotes
+        .iter()
+        .filter(|remote| remote.github.is_some())
+        .min_by_key(|remote| remote.name != "origin");
+    Ok(GitHubRepoInfo {
+        primary_remote: primary.map(|remote| remote.name.clone()),
+        primary: primary.and_then(|remote| remote.github.clone()),
+        remotes,
+    })
+}
+
+fn github_repo_from_path(path: &Path, github_hosts: &[String]) -> Result<GitHubRepoSlug, String> {
     let repo = Repository::open(path).map_err(|e| e.to_string())?;
-    let remotes = repo.remotes().map_err(|e| e.to_string())?;
-    let name = if remotes.iter().any(|remote| remote == Some("origin")) {
-        "origin".to_string()
-    } else {
-        remotes.iter().flatten().next().unwrap_or("").to_string()
-    };
-    if name.is_empty() {
+    let info = github_repo_info_for_repo(&repo, github_hosts)?;
+    if info.remotes.is_empty() {
         return Err("No git remote configured.".to_string());
     }
-    let remote = repo.find_remote(&name).map_err(|e| e.to_string())?;
-    let remote_url = remote.url().ok_or("Remote has no URL configured.")?;
-    parse_github_repo(remote_url).ok_or("Remote is not a GitHub repository.".to_string())
+    info.primary
+        .ok_or_else(|| "Remote is not a GitHub repository.".to_string())
 }
 
 fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
@@ -708,31 +990,225 @@ async fn commit_git_inner(
     run_git_command(&repo_root, &["commit", "-m", &message]).await
 }
 
+const DEFAULT_LARGE_FILE_THRESHOLD_BYTES: u64 = 5 * 1024 * 1024;
+
+fn large_staged_files_for_repo(
+    repo: &Repository,
+    threshold_bytes: u64,
+) -> Result<Vec<GitLargeFile>, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, None)
+        .map_err(|e| e.to_string())?;
+    let odb = repo.odb().map_err(|e| e.to_string())?;
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        if matches!(delta.status(), git2::Delta::Deleted) {
+            continue;
+        }
+        let Some(path) = delta.new_file().path() else {
+            continue;
+        };
+        let is_lfs = repo
+            .get_attr(path, "filter", git2::AttrCheckFlags::default())
+            .ok()
+            .flatten()
+            == Some("lfs");
+        if is_lfs {
+            continue;
+        }
+        let Ok((size, _)) = odb.read_header(delta.new_file().id()) else {
+            continue;
+        };
+        let size_bytes = size as u64;
+        if size_bytes > threshold_bytes {
+            files.push(GitLargeFile {
+                path: normalize_git_path(&path.to_string_lossy()),
+                size_bytes,
+            });
+        }
+    }
+    files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then(a.path.cmp(&b.path)));
+    Ok(files)
+}
+
+async fn check_large_staged_files_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    threshold_bytes: Option<u64>,
+) -> Result<LargeStagedFilesResponse, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let threshold_bytes = threshold_bytes
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_LARGE_FILE_THRESHOLD_BYTES);
+    tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let files = large_staged_files_for_repo(&repo, threshold_bytes)?;
+        Ok(LargeStagedFilesResponse {
+            threshold_bytes,
+            files,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Locks git writes next to a repository's (or worktree's) own git dir.
+const GIT_DIR_LOCK_FILES: &[&str] = &["index.lock", "HEAD.lock", "ORIG_HEAD.lock"];
+/// Locks git writes in the shared common dir.
+const GIT_COMMON_LOCK_FILES: &[&str] = &["config.lock", "packed-refs.lock", "shallow.lock"];
+/// Locks younger than this may belong to a git command that just started.
+const GIT_LOCK_MIN_AGE: Duration = Duration::from_secs(10);
+
+fn collect_ref_locks(dir: &Path, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_ref_locks(&path, found);
+        } else if path.extension().is_some_and(|ext| ext == "lock") {
+            found.push(path);
+        }
+    }
+}
+
+fn find_git_lock_files(repo: &Repository) -> Vec<PathBuf> {
+    let git_dir = repo.path();
+    let common_dir = repo.commondir();
+    let mut candidates: Vec<PathBuf> = GIT_DIR_LOCK_FILES
+        .iter()
+        .map(|name| git_dir.join(name))
+        .collect();
+    candidates.extend(
+        GIT_COMMON_LOCK_FILES
+            .iter()
+            .map(|name| common_dir.join(name)),
+    );
+    // Linked worktrees keep their own index and HEAD under <common>/worktrees.
+    if let Ok(entries) = fs::read_dir(common_dir.join("worktrees")) {
+        for entry in entries.flatten() {
+            candidates.extend(
+                GIT_DIR_LOCK_FILES
+                    .iter()
+                    .map(|name| entry.path().join(name)),
+            );
+        }
+    }
+    let mut found: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|path| path.is_file())
+        .collect();
+    collect_ref_locks(&common_dir.join("refs"), &mut found);
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn repair_git_locks_for_repo(
+    repo: &Repository,
+    remove: bool,
+    git_process_running: bool,
+    now: SystemTime,
+) -> GitRepairLocksResult {
+    let mut locks = Vec::new();
+    for path in find_git_lock_files(repo) {
+        let age = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        let stale = !git_process_running && age >= GIT_LOCK_MIN_AGE;
+        let removed = remove && stale && fs::remove_file(&path).is_ok();
+        locks.push(GitLockFile {
+            path: path.to_string_lossy().to_string(),
+            age_seconds: age.as_secs(),
+            stale,
+            removed,
+        });
+    }
+    GitRepairLocksResult {
+        git_process_running,
+        locks,
+    }
+}
+
+/// Whether any git process is alive on this machine. Errs on the side of
+/// "running" when the process list can't be read.
+async fn git_process_running() -> bool {
+    #[cfg(windows)]
+    let output = tokio_command("tasklist")
+        .args(["/FI", "IMAGENAME eq git.exe", "/NH"])
+        .output()
+        .await;
+    #[cfg(not(windows))]
+    let output = tokio_command("ps")
+        .args(["-A", "-o", "comm="])
+        .output()
+        .await;
+
+    let Ok(output) = output else {
+        return true;
+    };
+    if !output.status.success() {
+        return true;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout.lines().any(|line| {
+        let name = line.trim().rsplit(['/', '\\']).next().unwrap_or_default();
+        let name = name.to_ascii_lowercase();
+        name == "git" || name == "git.exe" || name.starts_with("git-")
+    })
+}
+
+async fn git_repair_locks_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    remove: bool,
+) -> Result<GitRepairLocksResult, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let git_process_running = git_process_running().await;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    Ok(repair_git_locks_for_repo(
+        &repo,
+        remove,
+        git_process_running,
+        SystemTime::now(),
+    ))
+}
+
 async fn push_git_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
-) -> Result<(), String> {
+    force: Option<GitPushForce>,
+    remote: Option<String>,
+) -> Result<GitPushResult, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    push_with_upstream(&repo_root).await
+    push_with_upstream(&repo_root, force, remote.as_deref()).await
 }
 
 async fn pull_git_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
+    remote: Option<String>,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    pull_with_default_strategy(&repo_root).await
+    pull_with_default_strategy(&repo_root, remote.as_deref()).await
 }
 
 async fn fetch_git_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
+    remote: Option<String>,
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    fetch_with_default_remote(&repo_root).await
+    fetch_with_default_remote(&repo_root, remote.as_deref()).await
 }
 
 async fn sync_git_inner(
@@ -741,8 +1217,8 @@ async fn sync_git_inner(
 ) -> Result<(), String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    pull_with_default_strategy(&repo_root).await?;
-    push_with_upstream(&repo_root).await
+    pull_with_default_strategy(&repo_root, None).await?;
+    push_result_to_unit(push_with_upstream(&repo_root, None, None).await?)
 }
 
 async fn list_git_roots_inner(
@@ -756,158 +1232,830 @@ async fn list_git_roots_inner(
     Ok(scan_git_roots(&root, depth, 200))
 }
 
-async fn get_git_diffs_inner(
-    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    app_settings: &Mutex<AppSettings>,
-    workspace_id: String,
-) -> Result<Vec<GitFileDiff>, String> {
-    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
-    let repo_root = resolve_git_root(&entry)?;
-    let ignore_whitespace_changes = {
-        let settings = app_settings.lock().await;
-        settings.git_diff_ignore_whitespace_changes
+/// Reason an untracked file is listed without a synthesized diff, if any.
+fn untracked_skip_reason(path: &Path) -> Option<&'static str> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_TEXT_DIFF_BYTES as u64 {
+        return Some("tooLarge");
+    }
+    let mut head = Vec::with_capacity(8192);
+    fs::File::open(path)
+        .and_then(|file| {
+            std::io::Read::read_to_end(&mut std::io::Read::take(file, 8192), &mut head)
+        })
+        .ok()?;
+    bytes_look_binary(&head).then_some("binary")
+}
+
+/// One side of a file diff: a tree (absent for an unborn HEAD), the index,
+/// or the working directory.
+#[derive(Clone, Copy)]
+enum DiffSide<'a> {
+    Tree(Option<&'a git2::Tree<'a>>),
+    Index(&'a git2::Index),
+    Workdir,
+}
+
+fn side_blob<'r>(repo: &'r Repository, side: DiffSide<'_>, path: &Path) -> Option<git2::Blob<'r>> {
+    let id = match side {
+        DiffSide::Tree(tree) => tree?.get_path(path).ok()?.id(),
+        DiffSide::Index(index) => index.get_path(path, 0)?.id,
+        DiffSide::Workdir => return None,
     };
+    repo.find_blob(id).ok()
+}
 
-    tokio::task::spawn_blocking(move || {
-        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+fn side_lines(
+    repo: &Repository,
+    repo_root: &Path,
+    side: DiffSide<'_>,
+    path: &Path,
+) -> Option<Vec<String>> {
+    match side {
+        DiffSide::Workdir => read_text_lines(&repo_root.join(path)),
+        _ => side_blob(repo, side, path).and_then(blob_to_lines),
+    }
+}
 
-        let mut options = DiffOptions::new();
-        options
-            .include_untracked(true)
-            .recurse_untracked_dirs(true)
-            .show_untracked_content(true);
-        options.ignore_whitespace_change(ignore_whitespace_changes);
-
-        let diff = match head_tree.as_ref() {
-            Some(tree) => repo
-                .diff_tree_to_workdir_with_index(Some(tree), Some(&mut options))
-                .map_err(|e| e.to_string())?,
-            None => repo
-                .diff_tree_to_workdir_with_index(None, Some(&mut options))
-                .map_err(|e| e.to_string())?,
+fn side_image_base64(
+    repo: &Repository,
+    repo_root: &Path,
+    side: DiffSide<'_>,
+    path: &Path,
+) -> Option<String> {
+    match side {
+        DiffSide::Workdir => read_image_base64(&repo_root.join(path)),
+        _ => side_blob(repo, side, path).and_then(blob_to_base64),
+    }
+}
+
+/// File diffs from `base_tree` (or an empty tree) to the working directory,
+/// staged and unstaged changes combined.
+fn diff_tree_to_workdir_files(
+    repo: &Repository,
+    repo_root: &Path,
+    base_tree: Option<&git2::Tree<'_>>,
+    include_untracked: bool,
+    ignore_whitespace_changes: bool,
+) -> Result<Vec<GitFileDiff>, String> {
+    let mut options = DiffOptions::new();
+    options
+        .include_untracked(include_untracked)
+        .recurse_untracked_dirs(include_untracked)
+        .show_untracked_content(include_untracked);
+    options.ignore_whitespace_change(ignore_whitespace_changes);
+
+    let diff = repo
+        .diff_tree_to_workdir_with_index(base_tree, Some(&mut options))
+        .map_err(|e| e.to_string())?;
+    Ok(file_diffs_for_diff(
+        repo,
+        repo_root,
+        &diff,
+        DiffSide::Tree(base_tree),
+        DiffSide::Workdir,
+    ))
+}
+
+/// Builds a `GitFileDiff` per delta, reading full old/new contents from the
+/// sides the diff compared.
+fn file_diffs_for_diff(
+    repo: &Repository,
+    repo_root: &Path,
+    diff: &git2::Diff<'_>,
+    old_side: DiffSide<'_>,
+    new_side: DiffSide<'_>,
+) -> Vec<GitFileDiff> {
+    let mut results = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let old_path = delta.old_file().path();
+        let new_path = delta.new_file().path();
+        let display_path = new_path.or(old_path);
+        let Some(display_path) = display_path else {
+            continue;
         };
+        let old_path_str = old_path.map(|path| path.to_string_lossy());
+        let new_path_str = new_path.map(|path| path.to_string_lossy());
+        let display_path_str = display_path.to_string_lossy();
+        let normalized_path = normalize_git_path(&display_path_str);
+        let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
+        let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
+        let is_image = old_image_mime.is_some() || new_image_mime.is_some();
+        let is_deleted = delta.status() == git2::Delta::Deleted;
+        let is_added = delta.status() == git2::Delta::Added;
+        let is_untracked = delta.status() == git2::Delta::Untracked;
 
-        let mut results = Vec::new();
-        for (index, delta) in diff.deltas().enumerate() {
-            let old_path = delta.old_file().path();
-            let new_path = delta.new_file().path();
-            let display_path = new_path.or(old_path);
-            let Some(display_path) = display_path else {
-                continue;
-            };
-            let old_path_str = old_path.map(|path| path.to_string_lossy());
-            let new_path_str = new_path.map(|path| path.to_string_lossy());
-            let display_path_str = display_path.to_string_lossy();
-            let normalized_path = normalize_git_path(&display_path_str);
-            let old_image_mime = old_path_str.as_deref().and_then(image_mime_type);
-            let new_image_mime = new_path_str.as_deref().and_then(image_mime_type);
-            let is_image = old_image_mime.is_some() || new_image_mime.is_some();
-            let is_deleted = delta.status() == git2::Delta::Deleted;
-            let is_added = delta.status() == git2::Delta::Added;
-
-            let old_lines = if !is_added {
-                head_tree
-                    .as_ref()
-                    .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
-                    .and_then(|entry| repo.find_blob(entry.id()).ok())
-                    .and_then(blob_to_lines)
+        let old_lines = if !is_added {
+            old_path.and_then(|path| side_lines(repo, repo_root, old_side, path))
+        } else {
+            None
+        };
+
+        let new_lines = if !is_deleted {
+            new_path.and_then(|path| side_lines(repo, repo_root, new_side, path))
+        } else {
+            None
+        };
+
+        if is_image {
+            let old_image_data = if !is_added && old_image_mime.is_some() {
+                old_path.and_then(|path| side_image_base64(repo, repo_root, old_side, path))
             } else {
                 None
             };
 
-            let new_lines = if !is_deleted {
-                match new_path {
-                    Some(path) => {
-                        let full_path = repo_root.join(path);
-                        read_text_lines(&full_path)
-                    }
-                    None => None,
-                }
+            let new_image_data = if !is_deleted && new_image_mime.is_some() {
+                new_path.and_then(|path| side_image_base64(repo, repo_root, new_side, path))
             } else {
                 None
             };
 
-            if is_image {
-                let old_image_data = if !is_added && old_image_mime.is_some() {
-                    head_tree
-                        .as_ref()
-                        .and_then(|tree| old_path.and_then(|path| tree.get_path(path).ok()))
-                        .and_then(|entry| repo.find_blob(entry.id()).ok())
-                        .and_then(blob_to_base64)
-                } else {
-                    None
-                };
-
-                let new_image_data = if !is_deleted && new_image_mime.is_some() {
-                    match new_path {
-                        Some(path) => {
-                            let full_path = repo_root.join(path);
-                            read_image_base64(&full_path)
-                        }
-                        None => None,
-                    }
-                } else {
-                    None
-                };
+            results.push(GitFileDiff {
+                path: normalized_path,
+                diff: String::new(),
+                old_lines: None,
+                new_lines: None,
+                is_binary: true,
+                is_image: true,
+                old_image_data,
+                new_image_data,
+                old_image_mime: old_image_mime.map(str::to_string),
+                new_image_mime: new_image_mime.map(str::to_string),
+                is_untracked,
+                skipped_reason: None,
+                truncated: false,
+                full_diff_bytes: None,
+            });
+            continue;
+        }
 
+        if is_untracked {
+            let skipped_reason =
+                new_path.and_then(|path| untracked_skip_reason(&repo_root.join(path)));
+            if let Some(reason) = skipped_reason {
                 results.push(GitFileDiff {
                     path: normalized_path,
                     diff: String::new(),
                     old_lines: None,
                     new_lines: None,
-                    is_binary: true,
-                    is_image: true,
-                    old_image_data,
-                    new_image_data,
-                    old_image_mime: old_image_mime.map(str::to_string),
-                    new_image_mime: new_image_mime.map(str::to_string),
+                    is_binary: reason == "binary",
+                    is_image: false,
+                    old_image_data: None,
+                    new_image_data: None,
+                    old_image_mime: None,
+                    new_image_mime: None,
+                    is_untracked,
+                    skipped_reason: Some(reason.to_string()),
+                    truncated: false,
+                    full_diff_bytes: None,
                 });
                 continue;
             }
+        }
 
-            let patch = match git2::Patch::from_diff(&diff, index) {
-                Ok(patch) => patch,
-                Err(_) => continue,
-            };
-            let Some(mut patch) = patch else {
-                continue;
-            };
-            let content = match diff_patch_to_string(&mut patch) {
-                Ok(content) => content,
-                Err(_) => continue,
-            };
-            if content.trim().is_empty() {
-                continue;
-            }
-            results.push(GitFileDiff {
-                path: normalized_path,
-                diff: content,
-                old_lines,
-                new_lines,
-                is_binary: false,
-                is_image: false,
-                old_image_data: None,
-                new_image_data: None,
-                old_image_mime: None,
-                new_image_mime: None,
-            });
+        let patch = match git2::Patch::from_diff(diff, index) {
+            Ok(patch) => patch,
+            Err(_) => continue,
+        };
+        let Some(mut patch) = patch else {
+            continue;
+        };
+        let content = match diff_patch_to_string(&mut patch) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        if content.trim().is_empty() {
+            continue;
         }
+        results.push(GitFileDiff {
+            path: normalized_path,
+            diff: content,
+            old_lines,
+            new_lines,
+            is_binary: false,
+            is_image: false,
+            old_image_data: None,
+            new_image_data: None,
+            old_image_mime: None,
+            new_image_mime: None,
+            is_untracked,
+            skipped_reason: None,
+            truncated: false,
+            full_diff_bytes: None,
+        });
+    }
+
+    results
+}
+
+/// Cuts an oversized file diff at a line boundary within `max_bytes`, and
+/// drops the full-file line arrays that would otherwise defeat the cap.
+fn truncate_file_diff(file: &mut GitFileDiff, max_bytes: usize) {
+    if max_bytes == 0 || file.diff.len() <= max_bytes {
+        return;
+    }
+    let full_bytes = file.diff.len();
+    let mut cut = max_bytes;
+    while !file.diff.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    if let Some(newline) = file.diff[..cut].rfind('\n') {
+        cut = newline + 1;
+    }
+    file.diff.truncate(cut);
+    file.old_lines = None;
+    file.new_lines = None;
+    file.truncated = true;
+    file.full_diff_bytes = Some(full_bytes);
+}
+
+async fn get_git_diffs_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    include_untracked: bool,
+    max_diff_bytes: Option<usize>,
+) -> Result<Vec<GitFileDiff>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let (ignore_whitespace_changes, max_diff_bytes) = {
+        let settings = app_settings.lock().await;
+        (
+            settings.git_diff_ignore_whitespace_changes,
+            max_diff_bytes.unwrap_or(settings.max_diff_bytes),
+        )
+    };
 
-        Ok(results)
+    tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let mut files = diff_tree_to_workdir_files(
+            &repo,
+            &repo_root,
+            head_tree.as_ref(),
+            include_untracked,
+            ignore_whitespace_changes,
+        )?;
+        for file in &mut files {
+            truncate_file_diff(file, max_diff_bytes);
+        }
+        Ok(files)
     })
     .await
     .map_err(|e| e.to_string())?
 }
 
-async fn get_git_log_inner(
+async fn get_git_file_diff_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
-    limit: Option<usize>,
-) -> Result<GitLogResponse, String> {
+    path: String,
+    staged: Option<bool>,
+) -> Result<Option<GitFileDiff>, String> {
+    let path = normalize_git_path(&path).trim().to_string();
+    if path.is_empty() {
+        return Err("A file path is required.".to_string());
+    }
+    if Path::new(&path).is_absolute() || path.split('/').any(|part| part == "..") {
+        return Err(format!("Invalid file path: {path}"));
+    }
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let ignore_whitespace_changes = app_settings.lock().await.git_diff_ignore_whitespace_changes;
+
+    tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let index = repo.index().map_err(|e| e.to_string())?;
+        let mut options = DiffOptions::new();
+        options
+            .pathspec(&path)
+            .disable_pathspec_match(true)
+            .ignore_whitespace_change(ignore_whitespace_changes);
+        if staged != Some(true) {
+            options
+                .include_untracked(true)
+                .recurse_untracked_dirs(true)
+                .show_untracked_content(true);
+        }
+
+        let files = match staged {
+            Some(true) => {
+                let diff = repo
+                    .diff_tree_to_index(head_tree.as_ref(), Some(&index), Some(&mut options))
+                    .map_err(|e| e.to_string())?;
+                file_diffs_for_diff(
+                    &repo,
+                    &repo_root,
+                    &diff,
+                    DiffSide::Tree(head_tree.as_ref()),
+                    DiffSide::Index(&index),
+                )
+            }
+            Some(false) => {
+                let diff = repo
+                    .diff_index_to_workdir(Some(&index), Some(&mut options))
+                    .map_err(|e| e.to_string())?;
+                file_diffs_for_diff(
+                    &repo,
+                    &repo_root,
+                    &diff,
+                    DiffSide::Index(&index),
+                    DiffSide::Workdir,
+                )
+            }
+            None => {
+                let diff = repo
+                    .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))
+                    .map_err(|e| e.to_string())?;
+                file_diffs_for_diff(
+                    &repo,
+                    &repo_root,
+                    &diff,
+                    DiffSide::Tree(head_tree.as_ref()),
+                    DiffSide::Workdir,
+                )
+            }
+        };
+        Ok(files.into_iter().next())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The ref worktrees are measured against: the remote's HEAD when known,
+/// then a local `main`/`master`, then whatever the parent has checked out.
+fn default_branch_ref(repo: &Repository) -> Result<String, String> {
+    if let Ok(remote) = preferred_remote_name(repo) {
+        let remote_head = repo
+            .find_reference(&format!("refs/remotes/{remote}/HEAD"))
+            .ok()
+            .and_then(|reference| reference.symbolic_target().map(str::to_string));
+        if let Some(target) = remote_head {
+            return Ok(target);
+        }
+    }
+    for name in ["main", "master"] {
+        let refname = format!("refs/heads/{name}");
+        if repo.find_reference(&refname).is_ok() {
+            return Ok(refname);
+        }
+    }
+    repo.head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.name().map(str::to_string))
+        .ok_or_else(|| "Could not determine the parent's default branch.".to_string())
+}
+
+fn parse_porcelain_submodule(field: &str) -> Option<GitSubmoduleStatus> {
+    let flags = field.strip_prefix('S')?.as_bytes();
+    if flags.len() != 3 {
+        return None;
+    }
+    Some(GitSubmoduleStatus {
+        commit_changed: flags[0] == b'C',
+        tracked_changes: flags[1] == b'M',
+        untracked_changes: flags[2] == b'U',
+    })
+}
+
+fn porcelain_tracked_entry(
+    kind: &str,
+    xy: &str,
+    submodule: &str,
+    path: &str,
+) -> Result<GitStatusEntry, String> {
+    let mut codes = xy.chars();
+    let (Some(index_status), Some(worktree_status), None) =
+        (codes.next(), codes.next(), codes.next())
+    else {
+        return Err(format!("Unexpected status code `{xy}`"));
+    };
+    Ok(GitStatusEntry {
+        kind: kind.to_string(),
+        path: path.to_string(),
+        orig_path: None,
+        index_status: index_status.to_string(),
+        worktree_status: worktree_status.to_string(),
+        similarity: None,
+        submodule: parse_porcelain_submodule(submodule),
+    })
+}
+
+/// Parses `git status --porcelain=v2 --branch -z`. With `-z` every record is
+/// NUL-terminated and a rename/copy record is followed by a separate record
+/// holding its source path.
+fn parse_porcelain_v2_status(output: &[u8]) -> Result<GitStatusDetailed, String> {
+    let text = String::from_utf8_lossy(output);
+    let mut records = text.split('\0').filter(|record| !record.is_empty());
+    let mut branch = GitStatusBranch::default();
+    let mut entries = Vec::new();
+    let malformed = |record: &str| format!("Unexpected git status record `{record}`");
+
+    while let Some(record) = records.next() {
+        let (tag, rest) = record.split_once(' ').ok_or_else(|| malformed(record))?;
+        match tag {
+            "#" => {
+                let (key, value) = rest.split_once(' ').unwrap_or((rest, ""));
+                match key {
+                    "branch.oid" if value != "(initial)" => branch.oid = Some(value.to_string()),
+                    "branch.head" if value != "(detached)" => branch.head = Some(value.to_string()),
+                    "branch.upstream" => branch.upstream = Some(value.to_string()),
+                    "branch.ab" => {
+                        let mut counts = value.split(' ');
+                        branch.ahead = counts
+                            .next()
+                            .and_then(|ahead| ahead.strip_prefix('+'))
+                            .and_then(|ahead| ahead.parse().ok());
+                        branch.behind = counts
+                            .next()
+                            .and_then(|behind| behind.strip_prefix('-'))
+                            .and_then(|behind| behind.parse().ok());
+                    }
+                    _ => {}
+                }
+            }
+            // 1 XY sub mH mI mW hH hI path
+            "1" => {
+                let fields: Vec<&str> = rest.splitn(8, ' ').collect();
+                let [xy, sub, _, _, _, _, _, path] = fields[..] else {
+                    return Err(malformed(record));
+                };
+                entries.push(porcelain_tracked_entry("changed", xy, sub, path)?);
+            }
+            // 2 XY sub mH mI mW hH hI Xscore path NUL origPath
+            "2" => {
+                let fields: Vec<&str> = rest.splitn(9, ' ').collect();
+                let [xy, sub, _, _, _, _, _, score, path] = fields[..] else {
+                    return Err(malformed(record));
+                };
+                let kind = if score.starts_with('C') {
+                    "copied"
+                } else {
+                    "renamed"
+                };
+                let mut entry = porcelain_tracked_entry(kind, xy, sub, path)?;
+                entry.similarity = score.get(1..).and_then(|score| score.parse().ok());
+                entry.orig_path =
+                    Some(records.next().ok_or_else(|| malformed(record))?.to_string());
+                entries.push(entry);
+            }
+            // u XY sub m1 m2 m3 mW h1 h2 h3 path
+            "u" => {
+                let fields: Vec<&str> = rest.splitn(10, ' ').collect();
+                let [xy, sub, _, _, _, _, _, _, _, path] = fields[..] else {
+                    return Err(malformed(record));
+                };
+                entries.push(porcelain_tracked_entry("unmerged", xy, sub, path)?);
+            }
+            "?" | "!" => entries.push(GitStatusEntry {
+                kind: if tag == "?" { "untracked" } else { "ignored" }.to_string(),
+                path: rest.to_string(),
+                orig_path: None,
+                index_status: tag.to_string(),
+                worktree_status: tag.to_string(),
+                similarity: None,
+                submodule: None,
+            }),
+            _ => return Err(malformed(record)),
+        }
+    }
+
+    Ok(GitStatusDetailed { branch, entries })
+}
+
+/// Counts `git status --porcelain -z --no-renames` entries, one record per
+/// dirty file.
+fn count_porcelain_v1_entries(output: &[u8]) -> usize {
+    output
+        .split(|byte| *byte == 0)
+        .filter(|record| !record.is_empty())
+        .count()
+}
+
+/// A worktree as listed by `git worktree list --porcelain`.
+#[derive(Debug, Default, PartialEq)]
+struct ListedWorktree {
+    path: String,
+    head: Option<String>,
+    branch: Option<String>,
+    bare: bool,
+    prunable: bool,
+}
+
+fn parse_worktree_list(output: &str) -> Vec<ListedWorktree> {
+    let mut worktrees: Vec<ListedWorktree> = Vec::new();
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.push(ListedWorktree {
+                path: path.to_string(),
+                ..ListedWorktree::default()
+            });
+            continue;
+        }
+        let Some(worktree) = worktrees.last_mut() else {
+            continue;
+        };
+        if let Some(head) = line.strip_prefix("HEAD ") {
+            worktree.head = Some(head.to_string());
+        } else if let Some(branch) = line.strip_prefix("branch ") {
+            let branch = branch.strip_prefix("refs/heads/").unwrap_or(branch);
+            worktree.branch = Some(branch.to_string());
+        } else if line == "bare" {
+            worktree.bare = true;
+        } else if line == "prunable" || line.starts_with("prunable ") {
+            worktree.prunable = true;
+        }
+    }
+    worktrees
+}
+
+fn same_path(left: &Path, right: &Path) -> bool {
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    canonical(left) == canonical(right)
+}
+
+async fn worktree_dirty_count(path: &Path) -> Result<usize, String> {
+    let output = git_core::run_git_command_bytes(
+        &path.to_path_buf(),
+        &[
+            "status",
+            "--porcelain",
+            "-z",
+            "--no-renames",
+            "--untracked-files=normal",
+        ],
+    )
+    .await?;
+    Ok(count_porcelain_v1_entries(&output))
+}
+
+async fn summarize_listed_worktree(
+    worktree: ListedWorktree,
+    repo_root: &Path,
+    workspaces: &[(String, PathBuf)],
+) -> WorktreeDirtySummary {
+    let path = PathBuf::from(&worktree.path);
+    let (dirty_count, error) = if worktree.prunable || !path.is_dir() {
+        (None, Some("Worktree directory is missing".to_string()))
+    } else {
+        match worktree_dirty_count(&path).await {
+            Ok(count) => (Some(count), None),
+            Err(err) => (None, Some(err)),
+        }
+    };
+    WorktreeDirtySummary {
+        workspace_id: workspaces
+            .iter()
+            .find(|(_, workspace_path)| same_path(workspace_path, &path))
+            .map(|(id, _)| id.clone()),
+        is_current: same_path(repo_root, &path),
+        path: worktree.path,
+        branch: worktree.branch,
+        head: worktree.head,
+        dirty_count,
+        error,
+    }
+}
+
+/// Local branches merged into the default branch, leaving out the default
+/// branch itself and whatever is checked out.
+fn merged_branches_for_repo(repo: &Repository) -> Result<GitMergedBranches, String> {
+    let base_ref = default_branch_ref(repo)?;
+    let base_oid = repo.refname_to_id(&base_ref).map_err(|e| e.to_string())?;
+    let default_branch = base_ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| {
+            base_ref
+                .strip_prefix("refs/remotes/")
+                .and_then(|name| name.split_once('/'))
+                .map(|(_, branch)| branch)
+        })
+        .unwrap_or(&base_ref)
+        .to_string();
+    let current_branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+
+    let mut branches = Vec::new();
+    for branch in repo
+        .branches(Some(BranchType::Local))
+        .map_err(|e| e.to_string())?
+    {
+        let (branch, _) = branch.map_err(|e| e.to_string())?;
+        let Some(name) = branch.name().ok().flatten().map(str::to_string) else {
+            continue;
+        };
+        if name == default_branch || current_branch.as_deref() == Some(name.as_str()) {
+            continue;
+        }
+        let Some(tip) = branch.get().target() else {
+            continue;
+        };
+        let merged = tip == base_oid
+            || repo
+                .graph_descendant_of(base_oid, tip)
+                .map_err(|e| e.to_string())?;
+        if !merged {
+            continue;
+        }
+        let last_commit = repo
+            .find_commit(tip)
+            .map(|commit| commit.time().seconds())
+            .unwrap_or(0);
+        branches.push(GitMergedBranch {
+            name,
+            sha: tip.to_string(),
+            last_commit,
+        });
+    }
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(GitMergedBranches {
+        default_branch,
+        branches,
+    })
+}
+
+/// Deletes the requested branches that `merged_branches_for_repo` reports;
+/// anything else, including the current and default branch, is refused.
+fn prune_merged_branches_for_repo(
+    repo: &Repository,
+    names: &[String],
+) -> Result<Vec<GitBranchPruneResult>, String> {
+    let merged = merged_branches_for_repo(repo)?;
+    let current_branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(str::to_string));
+    let results = names
+        .iter()
+        .map(|name| {
+            let refused = if current_branch.as_deref() == Some(name.as_str()) {
+                Some("Refusing to delete the current branch.".to_string())
+            } else if *name == merged.default_branch {
+                Some("Refusing to delete the default branch.".to_string())
+            } else if !merged.branches.iter().any(|branch| branch.name == *name) {
+                Some(format!("Not merged into {}.", merged.default_branch))
+            } else {
+                None
+            };
+            let error = refused.or_else(|| {
+                repo.find_branch(name, BranchType::Local)
+                    .and_then(|mut branch| branch.delete())
+                    .err()
+                    .map(|err| err.message().to_string())
+            });
+            GitBranchPruneResult {
+                name: name.clone(),
+                deleted: error.is_none(),
+                error,
+            }
+        })
+        .collect();
+    Ok(results)
+}
+
+fn worktree_diff_vs_base_for_repos(
+    parent_repo: &Repository,
+    worktree_repo: &Repository,
+    worktree_root: &Path,
+    ignore_whitespace_changes: bool,
+) -> Result<WorktreeBaseDiff, String> {
+    let base_ref = default_branch_ref(parent_repo)?;
+    // Worktrees share the parent's object database and refs, so the base
+    // resolves the same way from either repository.
+    let base_oid = parent_repo
+        .refname_to_id(&base_ref)
+        .map_err(|e| e.to_string())?;
+    let head_oid = worktree_repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .ok_or_else(|| "Worktree HEAD does not point to a commit.".to_string())?;
+    let merge_base = worktree_repo
+        .merge_base(head_oid, base_oid)
+        .map_err(|_| format!("Worktree has no common history with {base_ref}."))?;
+    let base_tree = worktree_repo
+        .find_commit(merge_base)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let files = diff_tree_to_workdir_files(
+        worktree_repo,
+        worktree_root,
+        Some(&base_tree),
+        true,
+        ignore_whitespace_changes,
+    )?;
+    let base_ref = base_ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| base_ref.strip_prefix("refs/remotes/"))
+        .unwrap_or(&base_ref)
+        .to_string();
+    Ok(WorktreeBaseDiff {
+        base_ref,
+        merge_base: merge_base.to_string(),
+        files,
+    })
+}
+
+async fn worktree_diff_vs_base_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+) -> Result<WorktreeBaseDiff, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    if !entry.kind.is_worktree() {
+        return Err("Diff against base is only available for worktree workspaces.".to_string());
+    }
+    let parent_id = entry
+        .parent_id
+        .clone()
+        .ok_or_else(|| "Worktree has no parent workspace.".to_string())?;
+    let parent_entry = workspace_entry_for_id(workspaces, &parent_id)
+        .await
+        .map_err(|_| "parent workspace not found".to_string())?;
+    let worktree_root = resolve_git_root(&entry)?;
+    let parent_root = resolve_git_root(&parent_entry)?;
+    let ignore_whitespace_changes = app_settings.lock().await.git_diff_ignore_whitespace_changes;
+
+    tokio::task::spawn_blocking(move || {
+        let parent_repo = Repository::open(&parent_root).map_err(|e| e.to_string())?;
+        let worktree_repo = Repository::open(&worktree_root).map_err(|e| e.to_string())?;
+        worktree_diff_vs_base_for_repos(
+            &parent_repo,
+            &worktree_repo,
+            &worktree_root,
+            ignore_whitespace_changes,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Parses `git log --numstat --format=%x1e%H` output into stats per sha.
+/// Binary files show up as `-\t-\tpath`.
+fn parse_numstat_log(output: &str) -> HashMap<String, GitCommitStats> {
+    let mut stats_by_sha = HashMap::new();
+    for record in output.split('\x1e') {
+        let mut lines = record.lines();
+        let Some(sha) = lines.next().map(str::trim).filter(|sha| !sha.is_empty()) else {
+            continue;
+        };
+        let mut stats = GitCommitStats::default();
+        for line in lines {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(added), Some(removed), Some(_path)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            stats.files_changed += 1;
+            if added == "-" && removed == "-" {
+                stats.binary_files += 1;
+                continue;
+            }
+            stats.insertions += added.parse::<usize>().unwrap_or(0);
+            stats.deletions += removed.parse::<usize>().unwrap_or(0);
+        }
+        stats_by_sha.insert(sha.to_string(), stats);
+    }
+    stats_by_sha
+}
+
+/// Fills in `stats` with a single `git log --numstat` over the listed
+/// commits. Merge commits are diffed by git against nothing, so they report
+/// no changes.
+async fn attach_commit_stats(
+    repo_root: &PathBuf,
+    lists: [&mut Vec<GitLogEntry>; 3],
+) -> Result<(), String> {
+    let mut shas: Vec<String> = lists
+        .iter()
+        .flat_map(|entries| entries.iter().map(|entry| entry.sha.clone()))
+        .collect();
+    shas.sort();
+    shas.dedup();
+    if shas.is_empty() {
+        return Ok(());
+    }
+    let mut args = vec!["log", "--no-walk=unsorted", "--numstat", "--format=%x1e%H"];
+    args.extend(shas.iter().map(String::as_str));
+    let output = git_core::run_git_command_bytes(repo_root, &args).await?;
+    let stats_by_sha = parse_numstat_log(&String::from_utf8_lossy(&output));
+    for entries in lists {
+        for entry in entries.iter_mut() {
+            entry.stats = Some(stats_by_sha.get(&entry.sha).cloned().unwrap_or_default());
+        }
+    }
+    Ok(())
+}
+
+fn git_log_for_repo(repo_root: &Path, limit: Option<usize>) -> Result<GitLogResponse, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
     let max_items = limit.unwrap_or(40);
     let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
     revwalk.push_head().map_err(|e| e.to_string())?;
@@ -992,33 +2140,207 @@ async fn get_git_log_inner(
     })
 }
 
-async fn get_git_commit_diff_inner(
+async fn get_git_log_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
-    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
-    sha: String,
-) -> Result<Vec<GitCommitDiff>, String> {
+    limit: Option<usize>,
+    include_stats: bool,
+) -> Result<GitLogResponse, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let mut log = git_log_for_repo(&repo_root, limit)?;
+    if include_stats {
+        attach_commit_stats(
+            &repo_root,
+            [
+                &mut log.entries,
+                &mut log.ahead_entries,
+                &mut log.behind_entries,
+            ],
+        )
+        .await?;
+    }
+    Ok(log)
+}
 
-    let ignore_whitespace_changes = {
-        let settings = app_settings.lock().await;
-        settings.git_diff_ignore_whitespace_changes
-    };
+const INCOMING_COMMITS_LIMIT: usize = 200;
+const DEFAULT_INCOMING_DIFF_MAX_BYTES: usize = 512 * 1024;
 
-    let repo_root = resolve_git_root(&entry)?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
-    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
-    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+fn incoming_changes_for_repo(
+    repo: &Repository,
+    summary_only: bool,
+    max_diff_bytes: usize,
+    ignore_whitespace_changes: bool,
+) -> Result<GitIncomingChanges, String> {
+    let head = repo.head().map_err(|e| e.to_string())?;
+    if !head.is_branch() {
+        return Err(
+            "HEAD is detached; check out a branch to preview incoming changes.".to_string(),
+        );
+    }
+    let branch_name = head
+        .shorthand()
+        .ok_or_else(|| "Current branch name is not valid UTF-8.".to_string())?;
+    let branch = repo
+        .find_branch(branch_name, BranchType::Local)
+        .map_err(|e| e.to_string())?;
+    let upstream_branch = branch
+        .upstream()
+        .map_err(|_| format!("Branch {branch_name} has no upstream."))?;
+    let upstream_ref = upstream_branch.get();
+    let upstream = upstream_ref
+        .shorthand()
+        .or_else(|| upstream_ref.name())
+        .unwrap_or_default()
+        .to_string();
+    let head_oid = head
+        .target()
+        .ok_or_else(|| "HEAD does not point to a commit.".to_string())?;
+    let upstream_oid = upstream_ref
+        .target()
+        .ok_or_else(|| format!("Upstream {upstream} does not point to a commit."))?;
+
+    let (_, behind) = repo
+        .graph_ahead_behind(head_oid, upstream_oid)
+        .map_err(|e| e.to_string())?;
+    let mut revwalk = repo.revwalk().map_err(|e| e.to_string())?;
+    revwalk.push(upstream_oid).map_err(|e| e.to_string())?;
+    revwalk.hide(head_oid).map_err(|e| e.to_string())?;
+    revwalk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+    let mut commits = Vec::new();
+    for oid_result in revwalk.take(INCOMING_COMMITS_LIMIT) {
+        let oid = oid_result.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        commits.push(commit_to_entry(commit));
+    }
 
+    // Diff from the merge base so commits that only exist locally do not
+    // show up as reverted.
+    let merge_base = repo
+        .merge_base(head_oid, upstream_oid)
+        .map_err(|e| e.to_string())?;
+    let base_tree = repo
+        .find_commit(merge_base)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let upstream_tree = repo
+        .find_commit(upstream_oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
     let mut options = DiffOptions::new();
     options.ignore_whitespace_change(ignore_whitespace_changes);
     let diff = repo
-        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
+        .diff_tree_to_tree(Some(&base_tree), Some(&upstream_tree), Some(&mut options))
         .map_err(|e| e.to_string())?;
 
-    let mut results = Vec::new();
+    let mut files = Vec::new();
+    let mut diff_bytes = 0usize;
+    let mut diff_truncated = false;
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let Ok(Some(mut patch)) = git2::Patch::from_diff(&diff, index) else {
+            continue;
+        };
+        let (_, additions, deletions) = patch.line_stats().map_err(|e| e.to_string())?;
+        let diff_text = if summary_only || diff_truncated {
+            None
+        } else {
+            let content = diff_patch_to_string(&mut patch).map_err(|e| e.to_string())?;
+            if diff_bytes + content.len() > max_diff_bytes {
+                diff_truncated = true;
+                None
+            } else {
+                diff_bytes += content.len();
+                Some(content)
+            }
+        };
+        files.push(GitIncomingFile {
+            path: normalize_git_path(&path.to_string_lossy()),
+            status: status_for_delta(delta.status()).to_string(),
+            additions,
+            deletions,
+            diff: diff_text,
+        });
+    }
+
+    Ok(GitIncomingChanges {
+        upstream,
+        fetch_error: None,
+        behind,
+        commits,
+        additions: files.iter().map(|file| file.additions).sum(),
+        deletions: files.iter().map(|file| file.deletions).sum(),
+        files,
+        diff_truncated,
+    })
+}
+
+async fn preview_incoming_changes_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    fetch: bool,
+    summary_only: bool,
+    max_diff_bytes: Option<usize>,
+) -> Result<GitIncomingChanges, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let ignore_whitespace_changes = app_settings.lock().await.git_diff_ignore_whitespace_changes;
+    // A failed fetch still lets the user review what was fetched earlier.
+    let fetch_error = if fetch {
+        fetch_with_default_remote(&repo_root, None).await.err()
+    } else {
+        None
+    };
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let mut changes = incoming_changes_for_repo(
+        &repo,
+        summary_only,
+        max_diff_bytes.unwrap_or(DEFAULT_INCOMING_DIFF_MAX_BYTES),
+        ignore_whitespace_changes,
+    )?;
+    changes.fetch_error = fetch_error;
+    Ok(changes)
+}
+
+async fn get_git_commit_diff_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    sha: String,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+
+    let ignore_whitespace_changes = {
+        let settings = app_settings.lock().await;
+        settings.git_diff_ignore_whitespace_changes
+    };
+
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
+    commit_diff_for_repo(&repo, oid, ignore_whitespace_changes)
+}
+
+/// Per-file diffs of a commit against its first parent.
+fn commit_diff_for_repo(
+    repo: &Repository,
+    oid: git2::Oid,
+    ignore_whitespace_changes: bool,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+
+    let mut options = DiffOptions::new();
+    options.ignore_whitespace_change(ignore_whitespace_changes);
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), Some(&mut options))
+        .map_err(|e| e.to_string())?;
+
+    let mut results = Vec::new();
     for (index, delta) in diff.deltas().enumerate() {
         let old_path = delta.old_file().path();
         let new_path = delta.new_file().path();
@@ -1123,6 +2445,587 @@ async fn get_git_commit_diff_inner(
     Ok(results)
 }
 
+async fn git_output_text(repo_root: &Path, args: &[&str]) -> Result<String, String> {
+    let output = run_git_command_output(repo_root, args).await?;
+    if !output.status.success() {
+        return Err(git_failure_detail(&output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Pulls the hunk starting at `line` (pre-image numbering) for `path` out of a
+/// unified diff.
+fn find_patch_hunk(patch: &str, path: &str, line: u32) -> Option<String> {
+    let mut in_header = true;
+    let mut in_file = false;
+    let mut hunk: Option<String> = None;
+    for text in patch.split_inclusive('\n') {
+        if text.starts_with("diff --git ") {
+            if hunk.is_some() {
+                break;
+            }
+            in_header = true;
+            in_file = false;
+            continue;
+        }
+        if in_header {
+            if let Some(name) = text
+                .strip_prefix("--- ")
+                .or_else(|| text.strip_prefix("+++ "))
+            {
+                let name = name.trim_end();
+                let name = name
+                    .strip_prefix("a/")
+                    .or_else(|| name.strip_prefix("b/"))
+                    .unwrap_or(name);
+                in_file = in_file || name == path;
+                continue;
+            }
+        }
+        if text.starts_with("@@") {
+            if hunk.is_some() {
+                break;
+            }
+            in_header = false;
+            let start = text
+                .trim_start_matches("@@ -")
+                .split([',', ' '])
+                .next()
+                .and_then(|value| value.parse::<u32>().ok());
+            if in_file && start == Some(line) {
+                hunk = Some(text.to_string());
+            }
+            continue;
+        }
+        if let Some(hunk) = hunk.as_mut() {
+            if text.starts_with("-- \n") {
+                // format-patch signature separator.
+                break;
+            }
+            hunk.push_str(text);
+        }
+    }
+    hunk
+}
+
+/// Turns `git apply` stderr into per-hunk rejections and conflicted paths.
+fn parse_git_apply_output(patch: &str, stderr: &str) -> (Vec<GitRejectedHunk>, Vec<String>) {
+    let mut rejected: Vec<GitRejectedHunk> = Vec::new();
+    let mut conflicts = Vec::new();
+    for line in stderr.lines() {
+        if let Some(path) = line.strip_prefix("U ") {
+            conflicts.push(path.trim().to_string());
+            continue;
+        }
+        let Some(message) = line.strip_prefix("error: ") else {
+            continue;
+        };
+        if let Some(location) = message.strip_prefix("patch failed: ") {
+            let (path, line) = match location.rsplit_once(':') {
+                Some((path, line)) => (path, line.parse::<u32>().ok()),
+                None => (location, None),
+            };
+            rejected.push(GitRejectedHunk {
+                path: path.to_string(),
+                reason: "patch failed".to_string(),
+                line,
+                hunk: line.and_then(|line| find_patch_hunk(patch, path, line)),
+            });
+            continue;
+        }
+        if let Some((path, reason)) = message.split_once(": ") {
+            // "patch does not apply" repeats a preceding "patch failed" entry.
+            let repeated =
+                reason == "patch does not apply" && rejected.iter().any(|entry| entry.path == path);
+            if !repeated {
+                rejected.push(GitRejectedHunk {
+                    path: path.to_string(),
+                    reason: reason.to_string(),
+                    line: None,
+                    hunk: None,
+                });
+            }
+        }
+    }
+    (rejected, conflicts)
+}
+
+async fn apply_git_patch_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    patch: String,
+    check: bool,
+    three_way: bool,
+) -> Result<GitPatchApplyResult, String> {
+    if patch.trim().is_empty() {
+        return Err("Patch is empty.".to_string());
+    }
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+    let mut args = vec!["apply"];
+    if check {
+        args.push("--check");
+    }
+    if three_way {
+        args.push("--3way");
+    }
+    args.push("-");
+    let output = run_git_command_with_input(&repo_root, &args, patch.as_bytes()).await?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let (rejected, conflicts) = parse_git_apply_output(&patch, &stderr);
+    let applied = output.status.success();
+    Ok(GitPatchApplyResult {
+        applied,
+        check_only: check,
+        rejected,
+        conflicts,
+        error: (!applied).then(|| git_failure_detail(&output)),
+    })
+}
+
+async fn export_git_patch_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    revision: String,
+) -> Result<GitPatchExport, String> {
+    let revision = revision.trim();
+    if revision.is_empty() {
+        return Err("A commit or range is required.".to_string());
+    }
+    if revision.starts_with('-') {
+        return Err(format!("Invalid revision: {revision}"));
+    }
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+
+    if revision.contains("..") {
+        let count = git_output_text(&repo_root, &["rev-list", "--count", revision, "--"]).await?;
+        let commit_count = count
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("Unexpected commit count: {}", count.trim()))?;
+        if commit_count == 0 {
+            return Ok(GitPatchExport {
+                patch: String::new(),
+                commit_count,
+            });
+        }
+        let patch = git_output_text(
+            &repo_root,
+            &["format-patch", "--stdout", "--no-color", revision, "--"],
+        )
+        .await?;
+        return Ok(GitPatchExport {
+            patch,
+            commit_count,
+        });
+    }
+
+    let commit = format!("{revision}^{{commit}}");
+    git_output_text(&repo_root, &["rev-parse", "--verify", "--quiet", &commit])
+        .await
+        .map_err(|_| format!("Unknown commit: {revision}"))?;
+    let patch = git_output_text(
+        &repo_root,
+        &[
+            "format-patch",
+            "--stdout",
+            "--no-color",
+            "-1",
+            revision,
+            "--",
+        ],
+    )
+    .await?;
+    Ok(GitPatchExport {
+        patch,
+        commit_count: 1,
+    })
+}
+
+fn last_commit_for_repo(repo: &Repository) -> Result<GitLastCommit, String> {
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(err) if err.code() == git2::ErrorCode::UnbornBranch => {
+            return Err("Repository has no commits yet.".to_string());
+        }
+        Err(err) => return Err(err.to_string()),
+    };
+    let commit = head.peel_to_commit().map_err(|e| e.to_string())?;
+    let commit_tree = commit.tree().map_err(|e| e.to_string())?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)
+        .map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    for (index, delta) in diff.deltas().enumerate() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let (additions, deletions) = git2::Patch::from_diff(&diff, index)
+            .ok()
+            .flatten()
+            .and_then(|patch| patch.line_stats().ok())
+            .map(|(_, additions, deletions)| (additions as i64, deletions as i64))
+            .unwrap_or((0, 0));
+        files.push(GitFileStatus {
+            path: normalize_git_path(&path.to_string_lossy()),
+            status: status_for_delta(delta.status()).to_string(),
+            additions,
+            deletions,
+        });
+    }
+
+    let author = commit.author();
+    Ok(GitLastCommit {
+        sha: commit.id().to_string(),
+        summary: commit.summary().unwrap_or("").to_string(),
+        message: commit.message().unwrap_or("").to_string(),
+        author_name: author.name().unwrap_or("").to_string(),
+        author_email: author.email().unwrap_or("").to_string(),
+        timestamp: commit.time().seconds(),
+        files,
+    })
+}
+
+async fn get_last_commit_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitLastCommit, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    last_commit_for_repo(&repo)
+}
+
+fn read_git_state_file(dir: &Path, name: &str) -> Option<String> {
+    fs::read_to_string(dir.join(name))
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn read_git_state_number(dir: &Path, name: &str) -> Option<u32> {
+    read_git_state_file(dir, name).and_then(|value| value.parse().ok())
+}
+
+/// Last applied line of `rebase-merge/done`, e.g. `pick 1a2b3c4 Fix typo`.
+fn last_done_rebase_step(dir: &Path) -> Option<(String, Option<String>)> {
+    let done = read_git_state_file(dir, "done")?;
+    let line = done
+        .lines()
+        .map(str::trim)
+        .rev()
+        .find(|line| !line.is_empty() && !line.starts_with('#'))?;
+    let mut parts = line.splitn(3, char::is_whitespace);
+    let _action = parts.next()?;
+    let sha = parts.next()?.to_string();
+    let subject = parts
+        .next()
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+        .map(str::to_string);
+    Some((sha, subject))
+}
+
+fn git_operation_progress_for_repo(repo: &Repository) -> GitOperationProgress {
+    let git_dir = repo.path();
+    let mut progress = GitOperationProgress::default();
+    let operation = match repo.state() {
+        RepositoryState::Clean => return progress,
+        RepositoryState::Merge => "merge",
+        RepositoryState::Revert | RepositoryState::RevertSequence => "revert",
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => "cherry-pick",
+        RepositoryState::Bisect => "bisect",
+        RepositoryState::ApplyMailbox => "am",
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailboxOrRebase => "rebase",
+    };
+    progress.operation = Some(operation.to_string());
+
+    let rebase_merge_dir = git_dir.join("rebase-merge");
+    let rebase_apply_dir = git_dir.join("rebase-apply");
+    if rebase_merge_dir.is_dir() {
+        progress.interactive = rebase_merge_dir.join("interactive").exists();
+        progress.current_step = read_git_state_number(&rebase_merge_dir, "msgnum");
+        progress.total_steps = read_git_state_number(&rebase_merge_dir, "end");
+        progress.head_name = read_git_state_file(&rebase_merge_dir, "head-name");
+        progress.onto = read_git_state_file(&rebase_merge_dir, "onto");
+        let last_step = last_done_rebase_step(&rebase_merge_dir);
+        progress.current_commit = read_git_state_file(&rebase_merge_dir, "stopped-sha")
+            .or_else(|| last_step.as_ref().map(|(sha, _)| sha.clone()));
+        progress.current_commit_summary = last_step.and_then(|(_, subject)| subject);
+    } else if rebase_apply_dir.is_dir() {
+        if !rebase_apply_dir.join("rebasing").exists() {
+            progress.operation = Some("am".to_string());
+        }
+        progress.current_step = read_git_state_number(&rebase_apply_dir, "next");
+        progress.total_steps = read_git_state_number(&rebase_apply_dir, "last");
+        progress.head_name = read_git_state_file(&rebase_apply_dir, "head-name");
+        progress.onto = read_git_state_file(&rebase_apply_dir, "onto");
+        progress.current_commit = read_git_state_file(&rebase_apply_dir, "original-commit");
+    } else {
+        let head_file = match operation {
+            "merge" => "MERGE_HEAD",
+            "revert" => "REVERT_HEAD",
+            "cherry-pick" => "CHERRY_PICK_HEAD",
+            _ => "",
+        };
+        if !head_file.is_empty() {
+            progress.current_commit = read_git_state_file(git_dir, head_file)
+                .and_then(|value| value.lines().next().map(str::to_string));
+        }
+    }
+
+    progress.head_name = progress.head_name.map(|name| {
+        name.strip_prefix("refs/heads/")
+            .map(str::to_string)
+            .unwrap_or(name)
+    });
+    if let Some(commit) = progress
+        .current_commit
+        .as_deref()
+        .and_then(|sha| repo.revparse_single(sha).ok())
+        .and_then(|object| object.peel_to_commit().ok())
+    {
+        progress.current_commit = Some(commit.id().to_string());
+        if let Some(summary) = commit.summary() {
+            progress.current_commit_summary = Some(summary.to_string());
+        }
+    }
+    progress
+}
+
+async fn git_operation_progress_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitOperationProgress, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    Ok(git_operation_progress_for_repo(&repo))
+}
+
+const DEFAULT_REFLOG_LIMIT: usize = 50;
+const MAX_REFLOG_LIMIT: usize = 500;
+
+fn split_reflog_message(message: &str) -> (String, String) {
+    match message.split_once(": ") {
+        Some((action, rest)) => (action.trim().to_string(), rest.trim().to_string()),
+        None => (String::new(), message.trim().to_string()),
+    }
+}
+
+fn format_relative_time(seconds_ago: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    let seconds_ago = seconds_ago.max(0);
+    let (value, unit) = if seconds_ago < MINUTE {
+        return "just now".to_string();
+    } else if seconds_ago < HOUR {
+        (seconds_ago / MINUTE, "minute")
+    } else if seconds_ago < DAY {
+        (seconds_ago / HOUR, "hour")
+    } else if seconds_ago < 30 * DAY {
+        (seconds_ago / DAY, "day")
+    } else if seconds_ago < 365 * DAY {
+        (seconds_ago / (30 * DAY), "month")
+    } else {
+        (seconds_ago / (365 * DAY), "year")
+    };
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{value} {unit}{plural} ago")
+}
+
+fn reflog_entries(
+    repo: &Repository,
+    ref_name: &str,
+    limit: usize,
+    now: i64,
+) -> Result<Vec<GitReflogEntry>, String> {
+    let full_name = if ref_name == "HEAD" {
+        ref_name.to_string()
+    } else {
+        let reference = repo
+            .resolve_reference_from_short_name(ref_name)
+            .map_err(|_| format!("Unknown ref `{ref_name}`."))?;
+        reference
+            .name()
+            .ok_or("Ref name is not valid UTF-8.")?
+            .to_string()
+    };
+    let reflog = repo.reflog(&full_name).map_err(|e| e.to_string())?;
+    Ok(reflog
+        .iter()
+        .take(limit)
+        .enumerate()
+        .map(|(index, entry)| {
+            let (action, message) = split_reflog_message(entry.message().unwrap_or(""));
+            let timestamp = entry.committer().when().seconds();
+            GitReflogEntry {
+                sha: entry.id_new().to_string(),
+                selector: format!("{ref_name}@{{{index}}}"),
+                action,
+                message,
+                timestamp,
+                relative_time: format_relative_time(now - timestamp),
+            }
+        })
+        .collect())
+}
+
+async fn git_reflog_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    ref_name: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<GitReflogEntry>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let ref_name = ref_name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| "HEAD".to_string());
+    let limit = limit
+        .unwrap_or(DEFAULT_REFLOG_LIMIT)
+        .clamp(1, MAX_REFLOG_LIMIT);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0);
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    reflog_entries(&repo, &ref_name, limit, now)
+}
+
+async fn restore_from_reflog_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    sha: String,
+    branch: Option<String>,
+    confirm: bool,
+) -> Result<GitReflogRestoreResult, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let (branch, is_current_branch, previous_sha, target_sha) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let target = repo
+            .revparse_single(sha.trim())
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| format!("Commit `{}` was not found.", sha.trim()))?;
+        let current_branch = repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(str::to_string));
+        let branch = branch
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .or_else(|| current_branch.clone())
+            .ok_or("HEAD is detached; choose a branch to restore.")?;
+        let previous = repo
+            .find_branch(&branch, BranchType::Local)
+            .map_err(|_| format!("Branch `{branch}` was not found."))?
+            .get()
+            .peel_to_commit()
+            .map_err(|e| e.to_string())?;
+        (
+            branch.clone(),
+            current_branch.as_deref() == Some(branch.as_str()),
+            previous.id().to_string(),
+            target.id().to_string(),
+        )
+    };
+
+    let mut result = GitReflogRestoreResult {
+        branch,
+        previous_sha,
+        target_sha,
+        applied: false,
+        autostashed: false,
+        warning: None,
+    };
+    // Without confirmation this only reports what would happen.
+    if !confirm {
+        return Ok(result);
+    }
+
+    if !is_current_branch {
+        run_git_command(
+            &repo_root,
+            &["branch", "-f", &result.branch, &result.target_sha],
+        )
+        .await?;
+        result.applied = true;
+        return Ok(result);
+    }
+
+    let status = run_git_command_output(&repo_root, &["status", "--porcelain"]).await?;
+    if !status.status.success() {
+        return Err(git_failure_detail(&status));
+    }
+    if !String::from_utf8_lossy(&status.stdout).trim().is_empty() {
+        run_git_command(
+            &repo_root,
+            &[
+                "stash",
+                "push",
+                "--include-untracked",
+                "-m",
+                "codex-monitor: before reflog restore",
+            ],
+        )
+        .await?;
+        result.autostashed = true;
+    }
+    if let Err(error) = run_git_command(&repo_root, &["reset", "--hard", &result.target_sha]).await
+    {
+        if result.autostashed {
+            let _ = run_git_command(&repo_root, &["stash", "pop"]).await;
+        }
+        return Err(error);
+    }
+    result.applied = true;
+    if result.autostashed {
+        if let Err(error) = run_git_command(&repo_root, &["stash", "pop"]).await {
+            result.warning = Some(format!(
+                "Restored, but your local changes could not be reapplied cleanly and were kept in the stash: {error}"
+            ));
+        }
+    }
+    Ok(result)
+}
+
+fn list_git_remotes_for_repo(repo: &Repository) -> Result<Vec<GitRemote>, String> {
+    let tracking_remote = repo
+        .workdir()
+        .and_then(|root| upstream_remote_and_branch(root).ok().flatten())
+        .map(|(remote, _)| remote);
+    let names = repo.remotes().map_err(|e| e.to_string())?;
+    let mut remotes = Vec::new();
+    for name in names.iter().flatten() {
+        let remote = repo.find_remote(name).map_err(|e| e.to_string())?;
+        let fetch_url = remote.url().map(str::to_string);
+        remotes.push(GitRemote {
+            name: name.to_string(),
+            push_url: remote.pushurl().map(str::to_string).or(fetch_url.clone()),
+            fetch_url,
+            tracking: tracking_remote.as_deref() == Some(name),
+        });
+    }
+    Ok(remotes)
+}
+
+async fn list_git_remotes_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Vec<GitRemote>, String> {
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    list_git_remotes_for_repo(&repo)
+}
+
 async fn get_git_remote_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1143,20 +3046,75 @@ async fn get_git_remote_inner(
     Ok(remote.url().map(|url| url.to_string()))
 }
 
+/// The workspace's GitHub token; a worktree without one of its own uses its
+/// parent's, since it is a checkout of the same repository.
+fn github_token_for_entry(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    entry: &WorkspaceEntry,
+) -> Option<String> {
+    let token = |entry: &WorkspaceEntry| {
+        entry
+            .settings
+            .github_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+    };
+    token(entry).or_else(|| {
+        entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .and_then(token)
+    })
+}
+
+/// `gh` prefers `GH_TOKEN` (github.com) and `GH_ENTERPRISE_TOKEN` (GHES hosts)
+/// over its stored login, so a workspace token takes over without touching
+/// the global auth.
+async fn gh_command(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    entry: &WorkspaceEntry,
+) -> tokio::process::Command {
+    let mut command = tokio_command("gh");
+    if let Some(token) = github_token_for_entry(&*workspaces.lock().await, entry) {
+        command
+            .env("GH_TOKEN", &token)
+            .env("GH_ENTERPRISE_TOKEN", &token);
+    }
+    command
+}
+
+async fn get_github_repo_info_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+) -> Result<GitHubRepoInfo, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    github_repo_info_for_repo(&repo, &github_hosts)
+}
+
 async fn get_github_issues_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<GitHubIssuesResponse, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let github_repo = github_repo_from_path(&repo_root, &github_hosts)?;
 
-    let output = tokio_command("gh")
+    let output = gh_command(workspaces, &entry)
+        .await
         .args([
             "issue",
             "list",
             "--repo",
-            &repo_name,
+            &github_repo.gh_repo_arg(),
             "--limit",
             "50",
             "--json",
@@ -1184,10 +3142,14 @@ async fn get_github_issues_inner(
     let issues: Vec<GitHubIssue> =
         serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
 
-    let search_query = format!("repo:{repo_name} is:issue is:open").replace(' ', "+");
-    let total = match tokio_command("gh")
+    let search_query =
+        format!("repo:{} is:issue is:open", github_repo.name_with_owner()).replace(' ', "+");
+    let total = match gh_command(workspaces, &entry)
+        .await
         .args([
             "api",
+            "--hostname",
+            &github_repo.host,
             &format!("/search/issues?q={search_query}"),
             "--jq",
             ".total_count",
@@ -1208,18 +3170,21 @@ async fn get_github_issues_inner(
 
 async fn get_github_pull_requests_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<GitHubPullRequestsResponse, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let github_repo = github_repo_from_path(&repo_root, &github_hosts)?;
 
-    let output = tokio_command("gh")
+    let output = gh_command(workspaces, &entry)
+        .await
         .args([
             "pr",
             "list",
             "--repo",
-            &repo_name,
+            &github_repo.gh_repo_arg(),
             "--state",
             "open",
             "--limit",
@@ -1249,10 +3214,14 @@ async fn get_github_pull_requests_inner(
     let pull_requests: Vec<GitHubPullRequest> =
         serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
 
-    let search_query = format!("repo:{repo_name} is:pr is:open").replace(' ', "+");
-    let total = match tokio_command("gh")
+    let search_query =
+        format!("repo:{} is:pr is:open", github_repo.name_with_owner()).replace(' ', "+");
+    let total = match gh_command(workspaces, &entry)
+        .await
         .args([
             "api",
+            "--hostname",
+            &github_repo.host,
             &format!("/search/issues?q={search_query}"),
             "--jq",
             ".total_count",
@@ -1276,20 +3245,23 @@ async fn get_github_pull_requests_inner(
 
 async fn get_github_pull_request_diff_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let github_repo = github_repo_from_path(&repo_root, &github_hosts)?;
 
-    let output = tokio_command("gh")
+    let output = gh_command(workspaces, &entry)
+        .await
         .args([
             "pr",
             "diff",
             &pr_number.to_string(),
             "--repo",
-            &repo_name,
+            &github_repo.gh_repo_arg(),
             "--color",
             "never",
         ])
@@ -1318,18 +3290,31 @@ async fn get_github_pull_request_diff_inner(
 
 async fn get_github_pull_request_comments_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestComment>, String> {
     let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
     let repo_root = resolve_git_root(&entry)?;
-    let repo_name = github_repo_from_path(&repo_root)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let github_repo = github_repo_from_path(&repo_root, &github_hosts)?;
 
-    let comments_endpoint = format!("/repos/{repo_name}/issues/{pr_number}/comments?per_page=30");
+    let comments_endpoint = format!(
+        "/repos/{}/issues/{pr_number}/comments?per_page=30",
+        github_repo.name_with_owner()
+    );
     let jq_filter = r#"[.[] | {id, body, createdAt: .created_at, url: .html_url, author: (if .user then {login: .user.login} else null end)}]"#;
 
-    let output = tokio_command("gh")
-        .args(["api", &comments_endpoint, "--jq", jq_filter])
+    let output = gh_command(workspaces, &entry)
+        .await
+        .args([
+            "api",
+            "--hostname",
+            &github_repo.host,
+            &comments_endpoint,
+            "--jq",
+            jq_filter,
+        ])
         .current_dir(&repo_root)
         .output()
         .await
@@ -1355,6 +3340,308 @@ async fn get_github_pull_request_comments_inner(
     Ok(comments)
 }
 
+const REVIEW_THREADS_QUERY: &str = r#"query($owner: String!, $name: String!, $number: Int!, $after: String) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      reviewThreads(first: 100, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes {
+          id
+          isResolved
+          isOutdated
+          path
+          line
+          originalLine
+          comments(first: 100) {
+            pageInfo { hasNextPage endCursor }
+            nodes { id body createdAt url author { login } }
+          }
+        }
+      }
+    }
+  }
+}"#;
+
+/// Later pages of a thread with more than 100 comments.
+const REVIEW_THREAD_COMMENTS_QUERY: &str = r#"query($id: ID!, $after: String) {
+  node(id: $id) {
+    ... on PullRequestReviewThread {
+      comments(first: 100, after: $after) {
+        pageInfo { hasNextPage endCursor }
+        nodes { id body createdAt url author { login } }
+      }
+    }
+  }
+}"#;
+
+/// GraphQL reports query errors in the body with a zero exit code, so a
+/// response carrying `errors` is a failure even when `data` is present.
+fn graphql_errors(response: &Value) -> Option<String> {
+    let errors = response.get("errors")?.as_array()?;
+    if errors.is_empty() {
+        return None;
+    }
+    let messages: Vec<&str> = errors
+        .iter()
+        .filter_map(|error| error.get("message").and_then(Value::as_str))
+        .collect();
+    Some(if messages.is_empty() {
+        "GitHub GraphQL request failed.".to_string()
+    } else {
+        messages.join("; ")
+    })
+}
+
+/// Cursor for the connection's next page, if it has one.
+fn next_page_cursor(connection: Option<&Value>) -> Option<String> {
+    let page_info = connection?.get("pageInfo")?;
+    if !page_info.get("hasNextPage")?.as_bool()? {
+        return None;
+    }
+    page_info
+        .get("endCursor")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn parse_review_thread_comment(node: &Value) -> Option<GitHubPullRequestReviewComment> {
+    Some(GitHubPullRequestReviewComment {
+        id: node.get("id")?.as_str()?.to_string(),
+        body: node
+            .get("body")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        created_at: node
+            .get("createdAt")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        url: node
+            .get("url")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        author: node
+            .get("author")
+            .and_then(|author| author.get("login"))
+            .and_then(Value::as_str)
+            .map(|login| GitHubPullRequestAuthor {
+                login: login.to_string(),
+            }),
+    })
+}
+
+fn parse_review_thread_comments(connection: Option<&Value>) -> Vec<GitHubPullRequestReviewComment> {
+    connection
+        .and_then(|connection| connection.get("nodes"))
+        .and_then(Value::as_array)
+        .map(|comments| {
+            comments
+                .iter()
+                .filter_map(parse_review_thread_comment)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A thread with its first page of comments, oldest first.
+fn parse_review_thread(node: &Value) -> Option<GitHubPullRequestReviewThread> {
+    let mut comments = parse_review_thread_comments(node.get("comments"));
+    // RFC 3339 timestamps from GitHub sort lexically.
+    comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+    Some(GitHubPullRequestReviewThread {
+        id: node.get("id")?.as_str()?.to_string(),
+        path: node
+            .get("path")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        line: node.get("line").and_then(Value::as_u64),
+        original_line: node.get("originalLine").and_then(Value::as_u64),
+        is_resolved: node
+            .get("isResolved")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        is_outdated: node
+            .get("isOutdated")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        comments,
+    })
+}
+
+/// Runs `gh api graphql` with `fields` as `-f`/`-F` flag pairs.
+async fn run_github_graphql(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    entry: &WorkspaceEntry,
+    repo_root: &Path,
+    hostname: &str,
+    query: &str,
+    fields: &[(&str, String)],
+) -> Result<Value, String> {
+    let mut command = gh_command(workspaces, entry).await;
+    command.args([
+        "api",
+        "graphql",
+        "--hostname",
+        hostname,
+        "-f",
+        &format!("query={query}"),
+    ]);
+    for (flag, field) in fields {
+        command.args([flag, field.as_str()]);
+    }
+    let output = command
+        .current_dir(repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+
+    let response: Value = serde_json::from_slice(&output.stdout).map_err(|e| e.to_string())?;
+    if let Some(errors) = graphql_errors(&response) {
+        return Err(errors);
+    }
+    Ok(response)
+}
+
+async fn get_github_pull_request_review_threads_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    pr_number: u64,
+) -> Result<Vec<GitHubPullRequestReviewThread>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let github_hosts = app_settings.lock().await.github_enterprise_hosts.clone();
+    let github_repo = github_repo_from_path(&repo_root, &github_hosts)?;
+
+    let mut threads = Vec::new();
+    let mut after: Option<String> = None;
+    loop {
+        let mut fields = vec![
+            ("-f", format!("owner={}", github_repo.owner)),
+            ("-f", format!("name={}", github_repo.repo)),
+            ("-F", format!("number={pr_number}")),
+        ];
+        if let Some(cursor) = &after {
+            fields.push(("-f", format!("after={cursor}")));
+        }
+        let response = run_github_graphql(
+            workspaces,
+            &entry,
+            &repo_root,
+            &github_repo.host,
+            REVIEW_THREADS_QUERY,
+            &fields,
+        )
+        .await?;
+        let connection = response.pointer("/data/repository/pullRequest/reviewThreads");
+        let nodes = connection
+            .and_then(|connection| connection.get("nodes"))
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for node in &nodes {
+            let Some(mut thread) = parse_review_thread(node) else {
+                continue;
+            };
+            let mut comments_after = next_page_cursor(node.get("comments"));
+            while let Some(cursor) = comments_after {
+                let response = run_github_graphql(
+                    workspaces,
+                    &entry,
+                    &repo_root,
+                    &github_repo.host,
+                    REVIEW_THREAD_COMMENTS_QUERY,
+                    &[
+                        ("-f", format!("id={}", thread.id)),
+                        ("-f", format!("after={cursor}")),
+                    ],
+                )
+                .await?;
+                let comments = response.pointer("/data/node/comments");
+                thread
+                    .comments
+                    .extend(parse_review_thread_comments(comments));
+                comments_after = next_page_cursor(comments);
+            }
+            thread
+                .comments
+                .sort_by(|a, b| a.created_at.cmp(&b.created_at));
+            threads.push(thread);
+        }
+        match next_page_cursor(connection) {
+            Some(cursor) => after = Some(cursor),
+            None => break,
+        }
+    }
+    Ok(threads)
+}
+
+async fn checkout_github_pull_request_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    pr_number: u64,
+    update: bool,
+) -> Result<GitHubPullRequestCheckout, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let branch = format!("pr-{pr_number}");
+    let (remote, existed, is_current) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let remote = preferred_remote_name(&repo)?;
+        let existed = repo.find_branch(&branch, BranchType::Local).is_ok();
+        let is_current = repo
+            .head()
+            .ok()
+            .and_then(|head| head.shorthand().map(|name| name == branch))
+            .unwrap_or(false);
+        (remote, existed, is_current)
+    };
+
+    let pull_ref = format!("pull/{pr_number}/head");
+    let mut updated = false;
+    if !existed {
+        let refspec = format!("{pull_ref}:{branch}");
+        run_git_command(&repo_root, &["fetch", &remote, &refspec]).await?;
+    } else if update {
+        if is_current {
+            run_git_command(&repo_root, &["fetch", &remote, &pull_ref]).await?;
+            run_git_command(&repo_root, &["merge", "--ff-only", "FETCH_HEAD"]).await?;
+        } else {
+            // No leading `+`: refuse to drop local commits made on the PR branch.
+            let refspec = format!("{pull_ref}:{branch}");
+            run_git_command(&repo_root, &["fetch", &remote, &refspec]).await?;
+        }
+        updated = true;
+    }
+
+    if !is_current {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        checkout_branch(&repo, &branch).map_err(|e| e.to_string())?;
+    }
+    Ok(GitHubPullRequestCheckout {
+        branch,
+        existed,
+        updated,
+    })
+}
+
 async fn list_git_branches_inner(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1410,6 +3697,36 @@ async fn create_git_branch_inner(
     checkout_branch(&repo, &name).map_err(|e| e.to_string())
 }
 
+/// Creates `name` from HEAD, checks it out and pushes it with upstream set.
+/// Refuses when the remote already has a branch of that name.
+async fn create_and_publish_branch_inner(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+    remote: Option<String>,
+) -> Result<GitPushResult, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let name = name.trim().to_string();
+    if name.is_empty() || name.starts_with('-') {
+        return Err(format!("Invalid branch name: {name}"));
+    }
+    let remote = match remote {
+        Some(remote) => validate_remote_name(&repo_root, &remote)?,
+        None => {
+            let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+            preferred_remote_name(&repo)?
+        }
+    };
+    if git_core::git_remote_branch_exists_live(&repo_root, &remote, &name).await? {
+        return Err(format!(
+            "Branch `{name}` already exists on `{remote}`. Check it out instead."
+        ));
+    }
+    create_git_branch_inner(workspaces, workspace_id, name).await?;
+    push_with_upstream(&repo_root, None, Some(&remote)).await
+}
+
 pub(crate) async fn resolve_repo_root_for_workspace_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1428,6 +3745,80 @@ pub(crate) async fn get_git_status_core(
     get_git_status_inner(workspaces, workspace_id).await
 }
 
+/// Full `git status --porcelain=v2` detail; `get_git_status_core` stays the
+/// summarized shape.
+pub(crate) async fn get_git_status_detailed_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitStatusDetailed, String> {
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+    let output = git_core::run_git_command_bytes(
+        &repo_root,
+        &[
+            "status",
+            "--porcelain=v2",
+            "--branch",
+            "--untracked-files=all",
+            "-z",
+        ],
+    )
+    .await?;
+    parse_porcelain_v2_status(&output)
+}
+
+/// Cheap pre-flight for destructive operations. Untracked files only count as
+/// dirty when `include_untracked` is set. git is killed as soon as it prints
+/// its first entry, so a dirty tree is reported without a full status run.
+pub(crate) async fn is_working_tree_clean_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    include_untracked: bool,
+) -> Result<GitWorkingTreeClean, String> {
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+    let untracked = if include_untracked {
+        "--untracked-files=normal"
+    } else {
+        "--untracked-files=no"
+    };
+    let dirty = git_core::git_prints_any_record(
+        &repo_root,
+        &["status", "--porcelain", "-z", "--no-renames", untracked],
+    )
+    .await?;
+    Ok(GitWorkingTreeClean { clean: !dirty })
+}
+
+/// Branch and uncommitted file count for every worktree of the workspace's
+/// repo, main checkout included. The worktrees are checked concurrently; one
+/// that can't be read reports an error instead of failing the whole call.
+pub(crate) async fn repo_worktrees_diff_summary_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<RepoWorktreesDiffSummary, String> {
+    let repo_root = resolve_repo_root_for_workspace(workspaces, workspace_id).await?;
+    let output =
+        git_core::run_git_command(&repo_root, &["worktree", "list", "--porcelain"]).await?;
+    let workspace_paths: Vec<(String, PathBuf)> = workspaces
+        .lock()
+        .await
+        .values()
+        .map(|entry| (entry.id.clone(), PathBuf::from(&entry.path)))
+        .collect();
+    let summaries = parse_worktree_list(&output)
+        .into_iter()
+        .filter(|worktree| !worktree.bare)
+        .map(|worktree| summarize_listed_worktree(worktree, &repo_root, &workspace_paths));
+    let worktrees = join_all(summaries).await;
+    let total_dirty_count = worktrees
+        .iter()
+        .filter_map(|worktree| worktree.dirty_count)
+        .sum();
+    Ok(RepoWorktreesDiffSummary {
+        worktrees,
+        total_dirty_count,
+    })
+}
+
 pub(crate) async fn list_git_roots_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1436,20 +3827,71 @@ pub(crate) async fn list_git_roots_core(
     list_git_roots_inner(workspaces, workspace_id, depth).await
 }
 
+/// `max_diff_bytes` overrides the `maxDiffBytes` setting for this call.
 pub(crate) async fn get_git_diffs_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     app_settings: &Mutex<AppSettings>,
     workspace_id: String,
+    include_untracked: bool,
+    max_diff_bytes: Option<usize>,
 ) -> Result<Vec<GitFileDiff>, String> {
-    get_git_diffs_inner(workspaces, app_settings, workspace_id).await
+    get_git_diffs_inner(
+        workspaces,
+        app_settings,
+        workspace_id,
+        include_untracked,
+        max_diff_bytes,
+    )
+    .await
+}
+
+/// Full, untruncated diff for one file. `staged` picks HEAD vs index (`true`)
+/// or index vs working tree (`false`); omitted, it matches `get_git_diffs`.
+/// Returns `None` when the file has no changes.
+pub(crate) async fn get_git_file_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    path: String,
+    staged: Option<bool>,
+) -> Result<Option<GitFileDiff>, String> {
+    get_git_file_diff_inner(workspaces, app_settings, workspace_id, path, staged).await
+}
+
+pub(crate) async fn worktree_diff_vs_base_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+) -> Result<WorktreeBaseDiff, String> {
+    worktree_diff_vs_base_inner(workspaces, app_settings, workspace_id).await
 }
 
 pub(crate) async fn get_git_log_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
     limit: Option<usize>,
+    include_stats: bool,
 ) -> Result<GitLogResponse, String> {
-    get_git_log_inner(workspaces, workspace_id, limit).await
+    get_git_log_inner(workspaces, workspace_id, limit, include_stats).await
+}
+
+pub(crate) async fn preview_incoming_changes_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    fetch: bool,
+    summary_only: bool,
+    max_diff_bytes: Option<usize>,
+) -> Result<GitIncomingChanges, String> {
+    preview_incoming_changes_inner(
+        workspaces,
+        app_settings,
+        workspace_id,
+        fetch,
+        summary_only,
+        max_diff_bytes,
+    )
+    .await
 }
 
 pub(crate) async fn get_git_commit_diff_core(
@@ -1461,6 +3903,78 @@ pub(crate) async fn get_git_commit_diff_core(
     get_git_commit_diff_inner(workspaces, app_settings, workspace_id, sha).await
 }
 
+/// Tracked changes a stash holds, as per-file diffs against the commit it was
+/// taken from. `stash` is an index, a `stash@{n}` ref or the stash's message.
+pub(crate) async fn get_stash_diff_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    stash: String,
+) -> Result<Vec<GitCommitDiff>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let ignore_whitespace_changes = app_settings.lock().await.git_diff_ignore_whitespace_changes;
+    let repo_root = resolve_git_root(&entry)?;
+    let (_, sha) = git_core::resolve_stash(&repo_root, &stash).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(&sha).map_err(|e| e.to_string())?;
+    commit_diff_for_repo(&repo, oid, ignore_whitespace_changes)
+}
+
+/// Exports a single commit, or every commit in an `a..b` range, as the text
+/// `git format-patch --stdout` would write.
+pub(crate) async fn export_git_patch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    revision: String,
+) -> Result<GitPatchExport, String> {
+    export_git_patch_inner(workspaces, workspace_id, revision).await
+}
+
+/// Applies patch text to the working tree. `check` only reports whether it
+/// would apply; `three_way` falls back to a merge for hunks that do not apply.
+pub(crate) async fn apply_git_patch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    patch: String,
+    check: bool,
+    three_way: bool,
+) -> Result<GitPatchApplyResult, String> {
+    apply_git_patch_inner(workspaces, workspace_id, patch, check, three_way).await
+}
+
+pub(crate) async fn get_last_commit_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitLastCommit, String> {
+    get_last_commit_inner(workspaces, workspace_id).await
+}
+
+pub(crate) async fn git_operation_progress_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitOperationProgress, String> {
+    git_operation_progress_inner(workspaces, workspace_id).await
+}
+
+pub(crate) async fn git_reflog_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    ref_name: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<GitReflogEntry>, String> {
+    git_reflog_inner(workspaces, workspace_id, ref_name, limit).await
+}
+
+pub(crate) async fn restore_from_reflog_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    sha: String,
+    branch: Option<String>,
+    confirm: bool,
+) -> Result<GitReflogRestoreResult, String> {
+    restore_from_reflog_inner(workspaces, workspace_id, sha, branch, confirm).await
+}
+
 pub(crate) async fn get_git_remote_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1514,25 +4028,54 @@ pub(crate) async fn commit_git_core(
     commit_git_inner(workspaces, workspace_id, message).await
 }
 
+pub(crate) async fn check_large_staged_files_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    threshold_bytes: Option<u64>,
+) -> Result<LargeStagedFilesResponse, String> {
+    check_large_staged_files_inner(workspaces, workspace_id, threshold_bytes).await
+}
+
+pub(crate) async fn git_repair_locks_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    remove: bool,
+) -> Result<GitRepairLocksResult, String> {
+    git_repair_locks_inner(workspaces, workspace_id, remove).await
+}
+
+/// `remote` targets a specific remote; omitted, the tracking remote is used.
 pub(crate) async fn push_git_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
-) -> Result<(), String> {
-    push_git_inner(workspaces, workspace_id).await
+    force: Option<String>,
+    remote: Option<String>,
+) -> Result<GitPushResult, String> {
+    let force = GitPushForce::parse(force.as_deref())?;
+    push_git_inner(workspaces, workspace_id, force, remote).await
 }
 
 pub(crate) async fn pull_git_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
+    remote: Option<String>,
 ) -> Result<(), String> {
-    pull_git_inner(workspaces, workspace_id).await
+    pull_git_inner(workspaces, workspace_id, remote).await
 }
 
 pub(crate) async fn fetch_git_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
+    remote: Option<String>,
 ) -> Result<(), String> {
-    fetch_git_inner(workspaces, workspace_id).await
+    fetch_git_inner(workspaces, workspace_id, remote).await
+}
+
+pub(crate) async fn list_git_remotes_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<Vec<GitRemote>, String> {
+    list_git_remotes_inner(workspaces, workspace_id).await
 }
 
 pub(crate) async fn sync_git_core(
@@ -1542,34 +4085,65 @@ pub(crate) async fn sync_git_core(
     sync_git_inner(workspaces, workspace_id).await
 }
 
+pub(crate) async fn get_github_repo_info_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+) -> Result<GitHubRepoInfo, String> {
+    get_github_repo_info_inner(workspaces, app_settings, workspace_id).await
+}
+
 pub(crate) async fn get_github_issues_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<GitHubIssuesResponse, String> {
-    get_github_issues_inner(workspaces, workspace_id).await
+    get_github_issues_inner(workspaces, app_settings, workspace_id).await
 }
 
 pub(crate) async fn get_github_pull_requests_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
 ) -> Result<GitHubPullRequestsResponse, String> {
-    get_github_pull_requests_inner(workspaces, workspace_id).await
+    get_github_pull_requests_inner(workspaces, app_settings, workspace_id).await
 }
 
 pub(crate) async fn get_github_pull_request_diff_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestDiff>, String> {
-    get_github_pull_request_diff_inner(workspaces, workspace_id, pr_number).await
+    get_github_pull_request_diff_inner(workspaces, app_settings, workspace_id, pr_number).await
 }
 
 pub(crate) async fn get_github_pull_request_comments_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
     workspace_id: String,
     pr_number: u64,
 ) -> Result<Vec<GitHubPullRequestComment>, String> {
-    get_github_pull_request_comments_inner(workspaces, workspace_id, pr_number).await
+    get_github_pull_request_comments_inner(workspaces, app_settings, workspace_id, pr_number).await
+}
+
+pub(crate) async fn get_github_pull_request_review_threads_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: String,
+    pr_number: u64,
+) -> Result<Vec<GitHubPullRequestReviewThread>, String> {
+    get_github_pull_request_review_threads_inner(workspaces, app_settings, workspace_id, pr_number)
+        .await
+}
+
+pub(crate) async fn checkout_github_pull_request_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    pr_number: u64,
+    update: bool,
+) -> Result<GitHubPullRequestCheckout, String> {
+    checkout_github_pull_request_inner(workspaces, workspace_id, pr_number, update).await
 }
 
 pub(crate) async fn list_git_branches_core(
@@ -1587,6 +4161,36 @@ pub(crate) async fn checkout_git_branch_core(
     checkout_git_branch_inner(workspaces, workspace_id, name).await
 }
 
+pub(crate) async fn create_and_publish_branch_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    name: String,
+    remote: Option<String>,
+) -> Result<GitPushResult, String> {
+    create_and_publish_branch_inner(workspaces, workspace_id, name, remote).await
+}
+
+pub(crate) async fn list_merged_branches_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<GitMergedBranches, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    merged_branches_for_repo(&repo)
+}
+
+pub(crate) async fn prune_merged_branches_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    branches: Vec<String>,
+) -> Result<Vec<GitBranchPruneResult>, String> {
+    let entry = workspace_entry_for_id(workspaces, &workspace_id).await?;
+    let repo_root = resolve_git_root(&entry)?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    prune_merged_branches_for_repo(&repo, &branches)
+}
+
 pub(crate) async fn create_git_branch_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -1598,6 +4202,7 @@ pub(crate) async fn create_git_branch_core(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shared::test_support::{run, workspaces_for};
     use std::fs;
     use std::path::Path;
 
@@ -1609,6 +4214,32 @@ mod tests {
         (root, repo)
     }
 
+    /// Writes `name`, stages it on top of the current index and commits it
+    /// without moving any ref.
+    fn commit_file_with_parents(
+        repo: &Repository,
+        root: &Path,
+        name: &str,
+        content: &str,
+        message: &str,
+        parents: &[git2::Oid],
+    ) -> git2::Oid {
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        fs::write(root.join(name), content).expect("write file");
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new(name)).expect("add path");
+        index.write().expect("write index");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let parents = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).expect("parent"))
+            .collect::<Vec<_>>();
+        let parent_refs = parents.iter().collect::<Vec<_>>();
+        repo.commit(None, &sig, &sig, message, &tree, &parent_refs)
+            .expect("commit")
+    }
+
     #[test]
     fn collect_workspace_diff_prefers_staged_changes() {
         let (root, repo) = create_temp_repo();
@@ -1659,4 +4290,976 @@ mod tests {
         let paths = action_paths_for_file(&root, "b.txt");
         assert_eq!(paths, vec!["a.txt".to_string(), "b.txt".to_string()]);
     }
+
+    #[test]
+    fn parse_review_threads_orders_replies_and_keeps_outdated() {
+        let response = json!({
+            "data": { "repository": { "pullRequest": { "reviewThreads": { "nodes": [
+                {
+                    "id": "T1",
+                    "isResolved": true,
+                    "isOutdated": true,
+                    "path": "src/lib.rs",
+                    "line": null,
+                    "originalLine": 12,
+                    "comments": { "nodes": [
+                        { "id": "C2", "body": "reply", "createdAt": "2024-01-02T00:00:00Z",
+                          "url": "u2", "author": null },
+                        { "id": "C1", "body": "first", "createdAt": "2024-01-01T00:00:00Z",
+                          "url": "u1", "author": { "login": "octocat" } }
+                    ] }
+                }
+            ] } } } }
+        });
+
+        let connection = response.pointer("/data/repository/pullRequest/reviewThreads");
+        assert_eq!(next_page_cursor(connection), None);
+        let node = &connection.unwrap()["nodes"][0];
+        assert_eq!(next_page_cursor(node.get("comments")), None);
+        let thread = parse_review_thread(node).expect("thread");
+        assert_eq!(thread.path, "src/lib.rs");
+        assert!(thread.is_resolved);
+        assert!(thread.is_outdated);
+        assert_eq!(thread.line, None);
+        assert_eq!(thread.original_line, Some(12));
+        let ids: Vec<_> = thread.comments.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, vec!["C1", "C2"]);
+        assert_eq!(
+            thread.comments[0].author.as_ref().map(|a| a.login.as_str()),
+            Some("octocat")
+        );
+        assert!(thread.comments[1].author.is_none());
+    }
+
+    #[test]
+    fn review_thread_pages_follow_cursors_and_surface_graphql_errors() {
+        let connection = json!({
+            "pageInfo": { "hasNextPage": true, "endCursor": "Y3Vyc29yOjEwMA==" },
+            "nodes": []
+        });
+        assert_eq!(
+            next_page_cursor(Some(&connection)).as_deref(),
+            Some("Y3Vyc29yOjEwMA==")
+        );
+        let last = json!({ "pageInfo": { "hasNextPage": false, "endCursor": "Y3Vyc29yOjE1MA==" } });
+        assert_eq!(next_page_cursor(Some(&last)), None);
+
+        let failed = json!({
+            "data": { "repository": { "pullRequest": null } },
+            "errors": [
+                { "message": "Could not resolve to a PullRequest with the number of 9." },
+                { "message": "Something else went wrong." }
+            ]
+        });
+        assert_eq!(
+            graphql_errors(&failed).as_deref(),
+            Some("Could not resolve to a PullRequest with the number of 9.; Something else went wrong.")
+        );
+        assert_eq!(
+            graphql_errors(&json!({ "errors": [{}] })).as_deref(),
+            Some("GitHub GraphQL request failed.")
+        );
+        assert_eq!(graphql_errors(&json!({ "data": {}, "errors": [] })), None);
+        assert_eq!(graphql_errors(&json!({ "data": {} })), None);
+    }
+
+    #[test]
+    fn parse_push_porcelain_reads_ref_updates() {
+        let stdout = "To github.com:owner/repo.git\n\
+*\trefs/heads/feature:refs/heads/feature\t[new branch]\n\
+!\tHEAD:refs/heads/main\t[rejected] (non-fast-forward)\n\
+Done\n";
+        let updates = parse_push_porcelain(stdout);
+        assert_eq!(
+            updates,
+            vec![
+                PushRefUpdate {
+                    flag: '*',
+                    summary: "[new branch]".to_string(),
+                },
+                PushRefUpdate {
+                    flag: '!',
+                    summary: "[rejected] (non-fast-forward)".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            push_rejection_reason(&updates[1].summary).as_deref(),
+            Some("non-fast-forward")
+        );
+        assert_eq!(push_rejection_reason(&updates[0].summary), None);
+    }
+
+    #[test]
+    fn git_push_force_parses_modes() {
+        assert_eq!(GitPushForce::parse(None), Ok(None));
+        assert_eq!(GitPushForce::parse(Some("")), Ok(None));
+        assert_eq!(
+            GitPushForce::parse(Some("with-lease")),
+            Ok(Some(GitPushForce::WithLease))
+        );
+        assert_eq!(
+            GitPushForce::parse(Some("force")),
+            Ok(Some(GitPushForce::Force))
+        );
+        assert!(GitPushForce::parse(Some("--force")).is_err());
+    }
+
+    #[test]
+    fn last_commit_reports_message_and_files() {
+        let (root, repo) = create_temp_repo();
+        assert_eq!(
+            last_commit_for_repo(&repo).unwrap_err(),
+            "Repository has no commits yet."
+        );
+
+        fs::write(root.join("a.txt"), "one\ntwo\n").expect("write file");
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "Add a\n\nLonger body.\n",
+            &tree,
+            &[],
+        )
+        .expect("commit");
+
+        let last = last_commit_for_repo(&repo).expect("last commit");
+        assert_eq!(last.summary, "Add a");
+        assert_eq!(last.message, "Add a\n\nLonger body.\n");
+        assert_eq!(last.author_name, "Test");
+        assert_eq!(last.files.len(), 1);
+        assert_eq!(last.files[0].path, "a.txt");
+        assert_eq!(last.files[0].status, "A");
+        assert_eq!(last.files[0].additions, 2);
+    }
+
+    #[test]
+    fn git_operation_progress_reads_rebase_merge_state() {
+        let (root, repo) = create_temp_repo();
+        assert!(git_operation_progress_for_repo(&repo).operation.is_none());
+
+        fs::write(root.join("a.txt"), "one\n").expect("write file");
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let commit_id = repo
+            .commit(Some("HEAD"), &sig, &sig, "Add a", &tree, &[])
+            .expect("commit");
+
+        let state_dir = repo.path().join("rebase-merge");
+        fs::create_dir_all(&state_dir).expect("create rebase-merge");
+        fs::write(state_dir.join("interactive"), "").expect("write interactive");
+        fs::write(state_dir.join("msgnum"), "3\n").expect("write msgnum");
+        fs::write(state_dir.join("end"), "7\n").expect("write end");
+        fs::write(state_dir.join("head-name"), "refs/heads/feature\n").expect("write head-name");
+        fs::write(state_dir.join("onto"), format!("{commit_id}\n")).expect("write onto");
+        fs::write(
+            state_dir.join("done"),
+            format!("pick 0000000 Earlier step\npick {commit_id} Add a\n"),
+        )
+        .expect("write done");
+
+        let progress = git_operation_progress_for_repo(&repo);
+        assert_eq!(progress.operation.as_deref(), Some("rebase"));
+        assert!(progress.interactive);
+        assert_eq!(progress.current_step, Some(3));
+        assert_eq!(progress.total_steps, Some(7));
+        assert_eq!(progress.head_name.as_deref(), Some("feature"));
+        assert_eq!(progress.current_commit, Some(commit_id.to_string()));
+        assert_eq!(progress.current_commit_summary.as_deref(), Some("Add a"));
+    }
+
+    #[test]
+    fn worktree_diff_vs_base_includes_commits_and_uncommitted_changes() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        let feature = commit_file_with_parents(&repo, &root, "b.txt", "new\n", "feature", &[base]);
+        repo.reference("refs/heads/feature", feature, true, "test")
+            .expect("feature ref");
+        repo.set_head("refs/heads/feature").expect("set head");
+        fs::write(root.join("a.txt"), "one\nchanged\n").expect("edit file");
+
+        let result =
+            worktree_diff_vs_base_for_repos(&repo, &repo, &root, false).expect("worktree diff");
+        assert_eq!(result.base_ref, "main");
+        assert_eq!(result.merge_base, base.to_string());
+        let paths: Vec<_> = result.files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["a.txt", "b.txt"]);
+        assert!(result.files[0].diff.contains("+changed"));
+    }
+
+    #[test]
+    fn worktree_list_porcelain_parses_branches_detached_and_prunable_entries() {
+        let output = "worktree /repo\nHEAD 1111\nbranch refs/heads/main\n\n\
+                      worktree /repo-wt/feature\nHEAD 2222\nbranch refs/heads/feat/login\n\n\
+                      worktree /repo-wt/detached\nHEAD 3333\ndetached\n\n\
+                      worktree /repo-wt/gone\nHEAD 4444\nbranch refs/heads/old\n\
+                      prunable gitdir file points to non-existent location\n";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 4);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].path, "/repo-wt/feature");
+        assert_eq!(worktrees[1].branch.as_deref(), Some("feat/login"));
+        assert_eq!(worktrees[2].branch, None);
+        assert_eq!(worktrees[2].head.as_deref(), Some("3333"));
+        assert!(worktrees[3].prunable);
+        assert!(!worktrees[0].prunable && !worktrees[0].bare);
+    }
+
+    #[test]
+    fn repair_git_locks_removes_only_stale_locks_when_git_is_idle() {
+        let (root, repo) = create_temp_repo();
+        let index_lock = root.join(".git/index.lock");
+        fs::write(&index_lock, "").expect("write index lock");
+        fs::create_dir_all(root.join(".git/refs/heads")).expect("refs dir");
+        let ref_lock = root.join(".git/refs/heads/main.lock");
+        fs::write(&ref_lock, "").expect("write ref lock");
+        let now = SystemTime::now() + Duration::from_secs(60);
+
+        let busy = repair_git_locks_for_repo(&repo, true, true, now);
+        assert_eq!(busy.locks.len(), 2);
+        assert!(busy.locks.iter().all(|lock| !lock.stale && !lock.removed));
+        assert!(index_lock.exists());
+
+        let fresh = repair_git_locks_for_repo(&repo, true, false, SystemTime::now());
+        assert!(fresh.locks.iter().all(|lock| !lock.removed));
+
+        let dry_run = repair_git_locks_for_repo(&repo, false, false, now);
+        assert!(dry_run.locks.iter().all(|lock| lock.stale && !lock.removed));
+
+        let repaired = repair_git_locks_for_repo(&repo, true, false, now);
+        assert!(repaired.locks.iter().all(|lock| lock.removed));
+        assert!(!index_lock.exists());
+        assert!(!ref_lock.exists());
+    }
+
+    #[test]
+    fn incoming_changes_diff_from_merge_base_to_upstream() {
+        let (root, repo) = create_temp_repo();
+        let commit_file = |name: &str, content: &str, message: &str, parents: &[git2::Oid]| {
+            commit_file_with_parents(&repo, &root, name, content, message, parents)
+        };
+
+        let base = commit_file("shared.txt", "one\n", "base", &[]);
+        let upstream_tip = commit_file("shared.txt", "one\ntwo\n", "upstream change", &[base]);
+        repo.reference("refs/remotes/origin/main", upstream_tip, true, "test")
+            .expect("remote ref");
+        let local = commit_file("local.txt", "mine\n", "local change", &[base]);
+        repo.reference("refs/heads/main", local, true, "test")
+            .expect("branch ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        repo.remote("origin", "https://example.com/repo.git")
+            .expect("remote");
+        let mut config = repo.config().expect("config");
+        config
+            .set_str("branch.main.remote", "origin")
+            .expect("remote");
+        config
+            .set_str("branch.main.merge", "refs/heads/main")
+            .expect("merge");
+
+        let changes = incoming_changes_for_repo(&repo, false, 1024, false).expect("incoming");
+        assert_eq!(changes.upstream, "origin/main");
+        assert_eq!(changes.behind, 1);
+        assert_eq!(changes.commits.len(), 1);
+        assert_eq!(changes.commits[0].summary, "upstream change");
+        assert_eq!(changes.files.len(), 1);
+        assert_eq!(changes.files[0].path, "shared.txt");
+        assert_eq!((changes.additions, changes.deletions), (1, 0));
+        assert!(changes.files[0]
+            .diff
+            .as_deref()
+            .is_some_and(|diff| diff.contains("+two")));
+
+        let summary = incoming_changes_for_repo(&repo, true, 1024, false).expect("summary");
+        assert!(summary.files[0].diff.is_none());
+        assert!(!summary.diff_truncated);
+        let capped = incoming_changes_for_repo(&repo, false, 4, false).expect("capped");
+        assert!(capped.files[0].diff.is_none());
+        assert!(capped.diff_truncated);
+    }
+
+    #[test]
+    fn reflog_entries_split_action_and_message() {
+        let (root, repo) = create_temp_repo();
+        fs::write(root.join("a.txt"), "one\n").expect("write file");
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = repo.find_tree(tree_id).expect("find tree");
+        let sig = git2::Signature::now("Test", "test@example.com").expect("signature");
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .expect("commit");
+
+        let now = sig.when().seconds() + 120;
+        let entries = reflog_entries(&repo, "HEAD", 10, now).expect("reflog");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].sha, oid.to_string());
+        assert_eq!(entries[0].selector, "HEAD@{0}");
+        assert_eq!(entries[0].message, "init");
+        assert_eq!(entries[0].relative_time, "2 minutes ago");
+        assert!(reflog_entries(&repo, "no-such-branch", 10, now).is_err());
+    }
+
+    #[test]
+    fn split_reflog_message_handles_missing_action() {
+        assert_eq!(
+            split_reflog_message("reset: moving to HEAD~1"),
+            ("reset".to_string(), "moving to HEAD~1".to_string())
+        );
+        assert_eq!(
+            split_reflog_message("plain"),
+            (String::new(), "plain".to_string())
+        );
+        assert_eq!(format_relative_time(3 * 24 * 3600), "3 days ago");
+        assert_eq!(format_relative_time(5), "just now");
+    }
+
+    #[test]
+    fn get_git_file_diff_scopes_to_one_path_and_side() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        fs::write(root.join("a.txt"), "staged\n").expect("write staged");
+        let mut index = repo.index().expect("repo index");
+        index.add_path(Path::new("a.txt")).expect("add path");
+        index.write().expect("write index");
+        fs::write(root.join("a.txt"), "unstaged\n").expect("write unstaged");
+        fs::write(root.join("other.txt"), "other\n").expect("write other");
+
+        let workspaces = workspaces_for(&root);
+        let app_settings = Mutex::new(AppSettings::default());
+        let file_diff = |path: &str, staged: Option<bool>| {
+            get_git_file_diff_core(
+                &workspaces,
+                &app_settings,
+                "w1".to_string(),
+                path.to_string(),
+                staged,
+            )
+        };
+
+        run(async {
+            let combined = file_diff("a.txt", None)
+                .await
+                .expect("diff")
+                .expect("a.txt");
+            assert_eq!(combined.path, "a.txt");
+            assert!(combined.diff.contains("-one") && combined.diff.contains("+unstaged"));
+
+            let staged = file_diff("a.txt", Some(true))
+                .await
+                .expect("diff")
+                .expect("a.txt");
+            assert!(staged.diff.contains("-one") && staged.diff.contains("+staged"));
+            assert_eq!(staged.new_lines, Some(vec!["staged\n".to_string()]));
+
+            let unstaged = file_diff("a.txt", Some(false))
+                .await
+                .expect("diff")
+                .expect("a.txt");
+            assert!(unstaged.diff.contains("-staged") && unstaged.diff.contains("+unstaged"));
+
+            let untracked = file_diff("other.txt", None).await.expect("diff");
+            assert!(untracked.is_some_and(|file| file.is_untracked));
+            assert!(file_diff("other.txt", Some(true))
+                .await
+                .expect("diff")
+                .is_none());
+            assert!(file_diff("../a.txt", None).await.is_err());
+        });
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn list_git_remotes_reports_urls_and_tracking_remote() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        repo.remote("origin", "https://example.com/me/repo.git")
+            .expect("origin");
+        repo.remote("upstream", "https://example.com/org/repo.git")
+            .expect("upstream");
+        repo.remote_set_pushurl("upstream", Some("git@example.com:org/repo.git"))
+            .expect("push url");
+        repo.reference("refs/remotes/origin/main", base, true, "test")
+            .expect("remote ref");
+        repo.find_branch("main", BranchType::Local)
+            .expect("main branch")
+            .set_upstream(Some("origin/main"))
+            .expect("set upstream");
+
+        let remotes = list_git_remotes_for_repo(&repo).expect("remotes");
+        let origin = remotes.iter().find(|r| r.name == "origin").expect("origin");
+        assert!(origin.tracking);
+        assert_eq!(origin.push_url, origin.fetch_url);
+        let upstream = remotes
+            .iter()
+            .find(|r| r.name == "upstream")
+            .expect("upstream");
+        assert!(!upstream.tracking);
+        assert_eq!(
+            upstream.push_url.as_deref(),
+            Some("git@example.com:org/repo.git")
+        );
+
+        assert_eq!(
+            explicit_remote_target(&root, "origin").expect("origin target"),
+            ("origin".to_string(), "main".to_string(), true)
+        );
+        assert_eq!(
+            explicit_remote_target(&root, " upstream ").expect("upstream target"),
+            ("upstream".to_string(), "main".to_string(), true)
+        );
+        assert!(validate_remote_name(&root, "missing").is_err());
+        assert!(validate_remote_name(&root, "--upload-pack=x").is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn create_and_publish_branch_pushes_with_upstream_and_refuses_existing() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        let remote_dir = root.with_extension("remote.git");
+        Repository::init_bare(&remote_dir).expect("bare remote");
+        repo.remote("origin", remote_dir.to_str().expect("remote path"))
+            .expect("origin");
+        let workspaces = workspaces_for(&root);
+
+        run(async {
+            let publish = |name: &str| {
+                create_and_publish_branch_core(
+                    &workspaces,
+                    "w1".to_string(),
+                    name.to_string(),
+                    None,
+                )
+            };
+            let result = publish("feature").await.expect("publish");
+            assert_eq!(result.remote, "origin");
+            assert_eq!(result.branch, "feature");
+            assert!(result.upstream_set);
+            assert!(!result.rejected);
+            assert_eq!(current_branch_name(&repo).expect("branch"), "feature");
+            assert_eq!(
+                upstream_remote_and_branch(&root).expect("upstream"),
+                Some(("origin".to_string(), "feature".to_string()))
+            );
+
+            checkout_branch(&repo, "main").expect("checkout main");
+            repo.find_branch("feature", BranchType::Local)
+                .expect("feature")
+                .delete()
+                .expect("delete feature");
+            let error = publish("feature").await.expect_err("remote branch exists");
+            assert!(error.contains("already exists on `origin`"), "{error}");
+            assert!(repo.find_branch("feature", BranchType::Local).is_err());
+        });
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&remote_dir);
+    }
+
+    #[test]
+    fn checkout_pull_request_creates_then_fast_forwards_the_local_branch() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .expect("checkout main");
+        let (upstream_root, upstream) = create_temp_repo();
+        let pr_head = |message: &str, parents: &[git2::Oid]| {
+            let oid = commit_file_with_parents(
+                &upstream,
+                &upstream_root,
+                "pr.txt",
+                message,
+                message,
+                parents,
+            );
+            upstream
+                .reference("refs/pull/7/head", oid, true, "test")
+                .expect("pull ref");
+            oid
+        };
+        let first = pr_head("first", &[]);
+        let workspaces = workspaces_for(&root);
+        let branch_tip = || {
+            repo.find_branch("pr-7", BranchType::Local)
+                .expect("pr branch")
+                .get()
+                .target()
+                .expect("branch target")
+        };
+
+        run(async {
+            let checkout = |update: bool| {
+                checkout_github_pull_request_core(&workspaces, "w1".to_string(), 7, update)
+            };
+            let error = checkout(false).await.expect_err("no remote yet");
+            assert_eq!(error, "No git remote configured.");
+            repo.remote("origin", upstream_root.to_str().expect("upstream path"))
+                .expect("origin");
+
+            let created = checkout(false).await.expect("create");
+            assert_eq!(created.branch, "pr-7");
+            assert!(!created.existed && !created.updated);
+            assert_eq!(current_branch_name(&repo).expect("branch"), "pr-7");
+            assert_eq!(branch_tip(), first);
+
+            let second = pr_head("second", &[first]);
+            let kept = checkout(false).await.expect("switch without update");
+            assert!(kept.existed && !kept.updated);
+            assert_eq!(branch_tip(), first);
+
+            let pulled = checkout(true).await.expect("update current branch");
+            assert!(pulled.existed && pulled.updated);
+            assert_eq!(branch_tip(), second);
+            assert_eq!(
+                fs::read_to_string(root.join("pr.txt")).expect("pr file"),
+                "second"
+            );
+
+            checkout_branch(&repo, "main").expect("checkout main");
+            let third = pr_head("third", &[second]);
+            let switched = checkout(true).await.expect("update other branch");
+            assert!(switched.existed && switched.updated);
+            assert_eq!(current_branch_name(&repo).expect("branch"), "pr-7");
+            assert_eq!(branch_tip(), third);
+        });
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&upstream_root);
+    }
+
+    #[test]
+    fn numstat_log_counts_binary_files_without_lines() {
+        let output = "\x1eaaa111\n\n3\t1\tsrc/main.rs\n-\t-\tassets/logo.png\n10\t0\tdocs/{old => new}.md\n\x1ebbb222\n";
+        let stats = parse_numstat_log(output);
+        assert_eq!(
+            stats["aaa111"],
+            GitCommitStats {
+                files_changed: 3,
+                insertions: 13,
+                deletions: 1,
+                binary_files: 1,
+            }
+        );
+        assert_eq!(stats["bbb222"], GitCommitStats::default());
+    }
+
+    #[test]
+    fn porcelain_v2_status_keeps_renames_submodules_and_both_sides() {
+        let output = b"# branch.oid 1234abcd\0# branch.head main\0# branch.upstream origin/main\0\
+# branch.ab +2 -1\0\
+1 MM N... 100644 100644 100644 aaaa bbbb src/both sides.rs\0\
+2 R. N... 100644 100644 100644 aaaa aaaa R86 src/new.rs\0src/old.rs\0\
+1 .M SC.U 160000 160000 160000 cccc cccc vendor/lib\0\
+u UU N... 100644 100644 100644 100644 aaaa bbbb cccc conflict.txt\0\
+? notes.txt\0";
+
+        let status = parse_porcelain_v2_status(output).expect("parse status");
+        assert_eq!(status.branch.head.as_deref(), Some("main"));
+        assert_eq!(status.branch.upstream.as_deref(), Some("origin/main"));
+        assert_eq!(
+            (status.branch.ahead, status.branch.behind),
+            (Some(2), Some(1))
+        );
+
+        let both = &status.entries[0];
+        assert_eq!(both.path, "src/both sides.rs");
+        assert_eq!(
+            (both.index_status.as_str(), both.worktree_status.as_str()),
+            ("M", "M")
+        );
+
+        let renamed = &status.entries[1];
+        assert_eq!(renamed.kind, "renamed");
+        assert_eq!(renamed.path, "src/new.rs");
+        assert_eq!(renamed.orig_path.as_deref(), Some("src/old.rs"));
+        assert_eq!(renamed.similarity, Some(86));
+
+        let submodule = status.entries[2].submodule.as_ref().expect("submodule");
+        assert!(submodule.commit_changed);
+        assert!(!submodule.tracked_changes);
+        assert!(submodule.untracked_changes);
+
+        assert_eq!(status.entries[3].kind, "unmerged");
+        assert_eq!(status.entries[4].kind, "untracked");
+        assert_eq!(status.entries.len(), 5);
+    }
+
+    #[test]
+    fn detailed_status_core_reads_an_unborn_repository() {
+        let (root, _repo) = create_temp_repo();
+        fs::write(root.join("untracked.txt"), "x").expect("write file");
+        let workspaces = workspaces_for(&root);
+        let status = run(get_git_status_detailed_core(&workspaces, "w1".to_string()))
+            .expect("detailed status");
+        assert_eq!(status.branch.oid, None);
+        assert_eq!(status.entries.len(), 1);
+        assert_eq!(status.entries[0].path, "untracked.txt");
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn working_tree_clean_counts_untracked_only_when_asked() {
+        assert_eq!(
+            count_porcelain_v1_entries(b"D  old.rs\0A  new.rs\0 M lib.rs\0?? notes.txt\0"),
+            4
+        );
+
+        let (root, repo) = create_temp_repo();
+        let head = commit_file_with_parents(&repo, &root, "tracked.txt", "one\n", "init", &[]);
+        repo.set_head_detached(head).expect("set head");
+        let workspaces = workspaces_for(&root);
+        let check = |include_untracked| {
+            run(is_working_tree_clean_core(
+                &workspaces,
+                "w1".to_string(),
+                include_untracked,
+            ))
+            .expect("clean check")
+        };
+
+        assert!(check(true).clean);
+        fs::create_dir_all(root.join("scratch")).expect("create dir");
+        fs::write(root.join("scratch/a.txt"), "a").expect("write untracked");
+        fs::write(root.join("scratch/b.txt"), "b").expect("write untracked");
+        assert!(check(false).clean);
+        assert!(!check(true).clean);
+        fs::write(root.join("tracked.txt"), "two\n").expect("modify tracked");
+        assert!(!check(false).clean);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn merged_branches_skip_current_and_default_and_prune_only_merged() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        let base_commit = repo.find_commit(base).expect("base commit");
+        repo.branch("merged", &base_commit, false).expect("merged");
+        repo.branch("topic", &base_commit, false).expect("topic");
+        let unmerged =
+            commit_file_with_parents(&repo, &root, "b.txt", "two\n", "unmerged", &[base]);
+        repo.reference("refs/heads/unmerged", unmerged, true, "test")
+            .expect("unmerged ref");
+        repo.set_head("refs/heads/topic").expect("checkout topic");
+
+        let merged = merged_branches_for_repo(&repo).expect("merged branches");
+        assert_eq!(merged.default_branch, "main");
+        let names: Vec<&str> = merged.branches.iter().map(|b| b.name.as_str()).collect();
+        assert_eq!(names, vec!["merged"]);
+
+        let requested = ["merged", "main", "topic", "unmerged"].map(String::from);
+        let results = prune_merged_branches_for_repo(&repo, &requested).expect("prune");
+        let deleted: Vec<&str> = results
+            .iter()
+            .filter(|result| result.deleted)
+            .map(|result| result.name.as_str())
+            .collect();
+        assert_eq!(deleted, vec!["merged"]);
+        assert!(results[1]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("default")));
+        assert!(results[2]
+            .error
+            .as_deref()
+            .is_some_and(|e| e.contains("current")));
+        assert!(repo.find_branch("merged", BranchType::Local).is_err());
+        assert!(repo.find_branch("unmerged", BranchType::Local).is_ok());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn truncate_file_diff_cuts_at_line_boundary_and_records_full_size() {
+        let diff = "@@ -1,3 +1,3 @@\n-one\n+uno\n two\n".to_string();
+        let full_bytes = diff.len();
+        let mut file = GitFileDiff {
+            path: "a.txt".to_string(),
+            diff,
+            old_lines: Some(vec!["one\n".to_string()]),
+            new_lines: Some(vec!["uno\n".to_string()]),
+            is_binary: false,
+            is_image: false,
+            old_image_data: None,
+            new_image_data: None,
+            old_image_mime: None,
+            new_image_mime: None,
+            is_untracked: false,
+            skipped_reason: None,
+            truncated: false,
+            full_diff_bytes: None,
+        };
+
+        truncate_file_diff(&mut file, 0);
+        assert!(!file.truncated);
+        truncate_file_diff(&mut file, full_bytes);
+        assert!(!file.truncated);
+
+        truncate_file_diff(&mut file, 22);
+        assert!(file.truncated);
+        assert_eq!(file.diff, "@@ -1,3 +1,3 @@\n-one\n");
+        assert_eq!(file.full_diff_bytes, Some(full_bytes));
+        assert!(file.old_lines.is_none() && file.new_lines.is_none());
+    }
+
+    #[test]
+    fn untracked_skip_reason_flags_binary_and_large_files() {
+        let (root, _repo) = create_temp_repo();
+        fs::write(root.join("text.txt"), "hello\n").expect("write text");
+        fs::write(root.join("blob.bin"), [0u8, 1, 2, 3]).expect("write binary");
+        fs::write(root.join("large.txt"), vec![b'a'; MAX_TEXT_DIFF_BYTES + 1])
+            .expect("write large");
+
+        assert_eq!(untracked_skip_reason(&root.join("text.txt")), None);
+        assert_eq!(
+            untracked_skip_reason(&root.join("blob.bin")),
+            Some("binary")
+        );
+        assert_eq!(
+            untracked_skip_reason(&root.join("large.txt")),
+            Some("tooLarge")
+        );
+    }
+
+    #[test]
+    fn large_staged_files_skips_small_and_lfs_files() {
+        let (root, repo) = create_temp_repo();
+        fs::write(
+            root.join(".gitattributes"),
+            "*.psd filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .expect("write gitattributes");
+        fs::write(root.join("small.txt"), "tiny\n").expect("write small");
+        fs::write(root.join("big.bin"), vec![b'x'; 2048]).expect("write big");
+        fs::write(root.join("art.psd"), vec![b'y'; 4096]).expect("write lfs");
+        let mut index = repo.index().expect("repo index");
+        for path in [".gitattributes", "small.txt", "big.bin", "art.psd"] {
+            index.add_path(Path::new(path)).expect("add path");
+        }
+        index.write().expect("write index");
+
+        let files = large_staged_files_for_repo(&repo, 1024).expect("large files");
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "big.bin");
+        assert_eq!(files[0].size_bytes, 2048);
+    }
+
+    #[test]
+    fn export_git_patch_handles_commits_ranges_and_empty_ranges() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\n", "base", &[]);
+        let second = commit_file_with_parents(&repo, &root, "a.txt", "two\n", "second", &[base]);
+        repo.reference("refs/heads/main", second, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        let workspaces = workspaces_for(&root);
+        let export = |revision: &str| {
+            export_git_patch_core(&workspaces, "w1".to_string(), revision.to_string())
+        };
+
+        run(async {
+            let single = export("main").await.expect("single commit");
+            assert_eq!(single.commit_count, 1);
+            assert!(single.patch.contains("Subject: [PATCH] second"));
+            assert!(single.patch.contains("+two"));
+
+            let range = export(&format!("{base}..main")).await.expect("range");
+            assert_eq!(range.commit_count, 1);
+            assert!(range.patch.contains("Subject: [PATCH] second"));
+
+            let empty = export("main..main").await.expect("empty range");
+            assert_eq!(empty.commit_count, 0);
+            assert!(empty.patch.is_empty());
+
+            assert!(export("no-such-ref").await.is_err());
+            assert!(export("--output=/tmp/x").await.is_err());
+        });
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn get_stash_diff_resolves_stashes_by_message_and_index() {
+        let (root, _repo) = create_temp_repo();
+        let workspaces = workspaces_for(&root);
+        let app_settings = Mutex::new(AppSettings::default());
+        let stash_diff = |stash: &str| {
+            get_stash_diff_core(
+                &workspaces,
+                &app_settings,
+                "w1".to_string(),
+                stash.to_string(),
+            )
+        };
+
+        run(async {
+            let git = |args: &'static [&'static str]| git_core::run_git_command(&root, args);
+            git(&["config", "user.email", "test@example.com"])
+                .await
+                .expect("email");
+            git(&["config", "user.name", "Test"]).await.expect("name");
+            fs::write(root.join("a.txt"), "base\n").expect("write base");
+            git(&["add", "."]).await.expect("add");
+            git(&["commit", "-qm", "base"]).await.expect("commit");
+            for (content, message) in [
+                ("first\n", "wip: parser"),
+                ("second\n", "retry"),
+                ("third\n", "retry"),
+            ] {
+                fs::write(root.join("a.txt"), content).expect("write change");
+                git_core::run_git_command(&root, &["stash", "push", "-q", "-m", message])
+                    .await
+                    .expect("stash");
+            }
+
+            // Now stash@{2}; found by message as newer stashes push it down.
+            let diff = stash_diff("wip: parser").await.expect("diff by message");
+            assert_eq!(diff.len(), 1);
+            assert_eq!(diff[0].path, "a.txt");
+            assert!(diff[0].diff.contains("+first"));
+            let by_index = stash_diff("stash@{2}").await.expect("diff by ref");
+            assert_eq!(by_index[0].diff, diff[0].diff);
+            assert!(stash_diff("0").await.expect("diff by index")[0]
+                .diff
+                .contains("+third"));
+
+            assert_eq!(
+                stash_diff("retry").await.err().as_deref(),
+                Some(
+                    "Stash message `retry` is ambiguous; it matches stash@{0}, stash@{1}. Use the stash index instead."
+                )
+            );
+            git(&["stash", "drop", "-q", "stash@{2}"])
+                .await
+                .expect("drop");
+            assert!(stash_diff("wip: parser")
+                .await
+                .is_err_and(|err| err.starts_with("No stash matches `wip: parser`")));
+            assert_eq!(
+                stash_diff("5").await.err().as_deref(),
+                Some("Stash stash@{5} not found.")
+            );
+        });
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn apply_git_patch_checks_applies_and_reports_rejected_hunks() {
+        let (root, repo) = create_temp_repo();
+        let base = commit_file_with_parents(&repo, &root, "a.txt", "one\ntwo\n", "base", &[]);
+        repo.reference("refs/heads/main", base, true, "test")
+            .expect("main ref");
+        repo.set_head("refs/heads/main").expect("set head");
+        let workspaces = workspaces_for(&root);
+        let patch = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+three\n";
+        let apply = |check: bool| {
+            apply_git_patch_core(
+                &workspaces,
+                "w1".to_string(),
+                patch.to_string(),
+                check,
+                false,
+            )
+        };
+
+        run(async {
+            let checked = apply(true).await.expect("check");
+            assert!(checked.applied && checked.check_only);
+            assert_eq!(
+                fs::read_to_string(root.join("a.txt")).expect("read"),
+                "one\ntwo\n"
+            );
+
+            let applied = apply(false).await.expect("apply");
+            assert!(applied.applied);
+            assert!(applied.rejected.is_empty());
+            assert_eq!(
+                fs::read_to_string(root.join("a.txt")).expect("read"),
+                "one\nthree\n"
+            );
+
+            let rejected = apply(false).await.expect("reapply");
+            assert!(!rejected.applied);
+            assert!(rejected.error.is_some());
+            assert_eq!(rejected.rejected.len(), 1);
+            assert_eq!(rejected.rejected[0].path, "a.txt");
+            assert_eq!(rejected.rejected[0].line, Some(1));
+            assert_eq!(
+                rejected.rejected[0].hunk.as_deref(),
+                Some("@@ -1,2 +1,2 @@\n one\n-two\n+three\n")
+            );
+        });
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn worktrees_inherit_their_parents_github_token() {
+        let entry = |id: &str, parent_id: Option<&str>, token: Option<&str>| WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: format!("/tmp/{id}"),
+            codex_bin: None,
+            kind: if parent_id.is_some() {
+                crate::types::WorkspaceKind::Worktree
+            } else {
+                crate::types::WorkspaceKind::Main
+            },
+            parent_id: parent_id.map(str::to_string),
+            worktree: None,
+            pinned: false,
+            auto_connect: true,
+            settings: crate::types::WorkspaceSettings {
+                github_token: token.map(str::to_string),
+                ..Default::default()
+            },
+        };
+        let workspaces = HashMap::from([
+            (
+                "main".to_string(),
+                entry("main", None, Some(" parent-token ")),
+            ),
+            ("plain".to_string(), entry("plain", None, None)),
+        ]);
+
+        let inherited = entry("wt", Some("main"), None);
+        assert_eq!(
+            github_token_for_entry(&workspaces, &inherited).as_deref(),
+            Some("parent-token")
+        );
+        let blank = entry("wt", Some("main"), Some("  "));
+        assert_eq!(
+            github_token_for_entry(&workspaces, &blank).as_deref(),
+            Some("parent-token")
+        );
+        let own = entry("wt", Some("main"), Some("own-token"));
+        assert_eq!(
+            github_token_for_entry(&workspaces, &own).as_deref(),
+            Some("own-token")
+        );
+        let without = entry("wt", Some("plain"), None);
+        assert_eq!(github_token_for_entry(&workspaces, &without), None);
+    }
 }
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex, Weak};
+use std::time::Duration;
+
+use git2::Repository;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::sync::Mutex;
+use tokio::time::{timeout, Instant};
+
+use crate::backend::events::{EventSink, GitStatusChanged};
+use crate::git_utils::resolve_git_root;
+use crate::shared::skip_dirs::should_skip_dir;
+use crate::types::WorkspaceEntry;
+
+/// Quiet period before a burst of filesystem events becomes one event.
+const GIT_WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+/// Upper bound on how long sustained churn (builds, checkouts) can delay an event.
+const GIT_WATCH_MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Files directly inside a git dir whose changes affect `git status`.
+const GIT_DIR_STATUS_FILES: &[&str] = &[
+    "HEAD",
+    "index",
+    "packed-refs",
+    "MERGE_HEAD",
+    "CHERRY_PICK_HEAD",
+    "REVERT_HEAD",
+    "REBASE_HEAD",
+];
+
+type SharedWatcher = Arc<StdMutex<RecommendedWatcher>>;
+
+/// A workspace's watcher and how many subscribers asked for it.
+struct GitWatch {
+    /// Only held: dropping it stops the watcher.
+    _watcher: SharedWatcher,
+    subscribers: usize,
+}
+
+/// Active filesystem watchers keyed by workspace id. Each one lives until its
+/// last subscriber unwatches; dropping it stops it and ends its debounce task.
+#[derive(Default)]
+pub(crate) struct GitStatusWatchers {
+    watchers: StdMutex<HashMap<String, GitWatch>>,
+}
+
+impl GitStatusWatchers {
+    /// Adds a subscriber to an existing watcher; false when there is none.
+    fn subscribe(&self, workspace_id: &str) -> bool {
+        let mut watchers = self.watchers.lock().unwrap_or_else(|err| err.into_inner());
+        match watchers.get_mut(workspace_id) {
+            Some(watch) => {
+                watch.subscribers += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Registers a freshly started watcher with one subscriber. If another
+    /// subscriber won the race, `watcher` is dropped and theirs is shared.
+    fn insert(&self, workspace_id: String, watcher: SharedWatcher) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .entry(workspace_id)
+            .and_modify(|watch| watch.subscribers += 1)
+            .or_insert(GitWatch {
+                _watcher: watcher,
+                subscribers: 1,
+            });
+    }
+
+    /// Drops one subscriber and stops the watcher once none are left.
+    /// Returns false when `workspace_id` was not watched.
+    pub(crate) fn unwatch(&self, workspace_id: &str) -> bool {
+        let mut watchers = self.watchers.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(watch) = watchers.get_mut(workspace_id) else {
+            return false;
+        };
+        watch.subscribers -= 1;
+        if watch.subscribers == 0 {
+            watchers.remove(workspace_id);
+        }
+        true
+    }
+
+    fn retain(&self, keep: impl Fn(&str) -> bool) {
+        self.watchers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|workspace_id, _| keep(workspace_id));
+    }
+}
+
+struct GitWatchPaths {
+    workdir: PathBuf,
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+}
+
+fn is_skipped_workdir_path(relative: &Path) -> bool {
+    relative
+        .components()
+        .any(|component| should_skip_dir(&component.as_os_str().to_string_lossy(), &[]))
+}
+
+/// Whether git ignores the working tree directory at `relative`. The trailing
+/// separator lets directory-only patterns such as `build/` match.
+fn is_ignored_dir(repo: &Repository, relative: &Path) -> bool {
+    repo.is_path_ignored(relative.join("")).unwrap_or(false)
+}
+
+fn is_git_status_file(relative: &Path) -> bool {
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    if relative.ends_with(".lock") {
+        return false;
+    }
+    GIT_DIR_STATUS_FILES.contains(&relative.as_str()) || relative.starts_with("refs/")
+}
+
+/// Whether a changed path can affect `git status`. Object writes, reflogs and
+/// lock files inside the git dir are ignored, as are skipped working tree
+/// dirs; other working tree paths are kept and checked against gitignore
+/// separately.
+fn is_relevant_git_path(paths: &GitWatchPaths, path: &Path) -> bool {
+    if let Ok(relative) = path.strip_prefix(&paths.git_dir) {
+        return is_git_status_file(relative);
+    }
+    if let Ok(relative) = path.strip_prefix(&paths.common_dir) {
+        return is_git_status_file(relative);
+    }
+    path.strip_prefix(&paths.workdir)
+        .is_ok_and(|relative| !is_skipped_workdir_path(relative))
+}
+
+fn is_relevant_event(paths: &GitWatchPaths, repo: &Repository, event: &Event) -> bool {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return false;
+    }
+    event.paths.iter().any(|path| {
+        if !is_relevant_git_path(paths, path) {
+            return false;
+        }
+        if path.starts_with(&paths.git_dir) || path.starts_with(&paths.common_dir) {
+            return true;
+        }
+        match path.strip_prefix(&paths.workdir) {
+            Ok(relative) if relative.as_os_str().is_empty() => true,
+            Ok(relative) => !repo.is_path_ignored(relative).unwrap_or(false),
+            Err(_) => false,
+        }
+    })
+}
+
+/// What the watcher callback forwards to the debounce task.
+enum GitWatchSignal {
+    Changed,
+    /// A working tree directory appeared and needs watches of its own. The
+    /// callback can't add them: it runs on the watcher's own event thread.
+    NewDir(PathBuf),
+}
+
+/// Watches `dir` and the working tree directories below it, one
+/// non-recursive watch each, skipping skipped and gitignored dirs so their
+/// churn never reaches the watcher. Subdirectories that vanish or can't be
+/// watched are left out rather than failing the whole watch.
+fn watch_workdir_dirs(
+    watcher: &mut RecommendedWatcher,
+    repo: &Repository,
+    workdir: &Path,
+    dir: &Path,
+) -> Result<(), String> {
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", dir.display()))?;
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        // `file_type` does not follow symlinks, so linked dirs are not walked.
+        if !entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            continue;
+        }
+        let path = entry.path();
+        let Ok(relative) = path.strip_prefix(workdir) else {
+            continue;
+        };
+        if is_skipped_workdir_path(relative) || is_ignored_dir(repo, relative) {
+            continue;
+        }
+        let _ = watch_workdir_dirs(watcher, repo, workdir, &path);
+    }
+    Ok(())
+}
+
+/// Adds watches for working tree directories created after the watcher
+/// started. Holds only a weak handle so that dropping the watcher still ends
+/// the debounce task.
+struct WorkdirWatches {
+    watcher: Weak<StdMutex<RecommendedWatcher>>,
+    repo: Repository,
+    workdir: PathBuf,
+}
+
+impl WorkdirWatches {
+    fn handle(&self, signal: GitWatchSignal) {
+        let GitWatchSignal::NewDir(dir) = signal else {
+            return;
+        };
+        if let Some(watcher) = self.watcher.upgrade() {
+            let mut watcher = watcher.lock().unwrap_or_else(|err| err.into_inner());
+            let _ = watch_workdir_dirs(&mut watcher, &self.repo, &self.workdir, &dir);
+        }
+    }
+}
+
+async fn debounce_git_events<E: EventSink>(
+    mut rx: mpsc::UnboundedReceiver<GitWatchSignal>,
+    event_sink: E,
+    workspace_id: String,
+    workdir_watches: WorkdirWatches,
+) {
+    while let Some(signal) = rx.recv().await {
+        workdir_watches.handle(signal);
+        let deadline = Instant::now() + GIT_WATCH_MAX_DELAY;
+        loop {
+            let wait = GIT_WATCH_DEBOUNCE.min(deadline.saturating_duration_since(Instant::now()));
+            match timeout(wait, rx.recv()).await {
+                Ok(Some(signal)) => workdir_watches.handle(signal),
+                // The watcher was dropped; nobody is listening anymore.
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+        event_sink.emit_git_status_changed(GitStatusChanged {
+            workspace_id: workspace_id.clone(),
+        });
+    }
+}
+
+/// Watches the git dir's status files and refs plus the working tree minus
+/// skipped and gitignored dirs. The working tree is watched directory by
+/// directory instead of recursively so ignored build output costs nothing.
+fn start_git_watcher<E: EventSink>(
+    repo_root: &Path,
+    event_sink: E,
+    workspace_id: String,
+) -> Result<SharedWatcher, String> {
+    let repo = Repository::open(repo_root).map_err(|err| err.to_string())?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| "Cannot watch a bare repository.".to_string())?
+        .to_path_buf();
+    let paths = GitWatchPaths {
+        workdir: workdir.clone(),
+        git_dir: repo.path().to_path_buf(),
+        common_dir: repo.commondir().to_path_buf(),
+    };
+    let git_dir = paths.git_dir.clone();
+    let common_dir = paths.common_dir.clone();
+    // The callback and the debounce task each need their own handle: a
+    // `Repository` can't be shared between threads.
+    let callback_repo = Repository::open(repo_root).map_err(|err| err.to_string())?;
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if !is_relevant_event(&paths, &callback_repo, &event) {
+            return;
+        }
+        if matches!(event.kind, EventKind::Create(_)) {
+            for path in event.paths.iter().filter(|path| path.is_dir()) {
+                let _ = tx.send(GitWatchSignal::NewDir(path.clone()));
+            }
+        }
+        let _ = tx.send(GitWatchSignal::Changed);
+    })
+    .map_err(|err| format!("Failed to create watcher: {err}"))?;
+
+    watch_workdir_dirs(&mut watcher, &repo, &workdir, &workdir)?;
+    watcher
+        .watch(&git_dir, RecursiveMode::NonRecursive)
+        .map_err(|err| format!("Failed to watch {}: {err}", git_dir.display()))?;
+    let mut refs_dirs = vec![git_dir.join("refs")];
+    // Linked worktrees keep HEAD and the index in their own git dir but share
+    // refs with the main one.
+    if common_dir != git_dir {
+        watcher
+            .watch(&common_dir, RecursiveMode::NonRecursive)
+            .map_err(|err| format!("Failed to watch {}: {err}", common_dir.display()))?;
+        refs_dirs.push(common_dir.join("refs"));
+    }
+    for refs_dir in refs_dirs.into_iter().filter(|dir| dir.is_dir()) {
+        watcher
+            .watch(&refs_dir, RecursiveMode::Recursive)
+            .map_err(|err| format!("Failed to watch {}: {err}", refs_dir.display()))?;
+    }
+
+    let watcher = Arc::new(StdMutex::new(watcher));
+    let workdir_watches = WorkdirWatches {
+        watcher: Arc::downgrade(&watcher),
+        repo,
+        workdir,
+    };
+    tokio::spawn(debounce_git_events(
+        rx,
+        event_sink,
+        workspace_id,
+        workdir_watches,
+    ));
+    Ok(watcher)
+}
+
+/// Subscribes to (or with `enabled == false`, unsubscribes from)
+/// `git-status-changed` events for a workspace. The watcher is shared by all
+/// subscribers and stops with the last one. Returns whether the caller is now
+/// subscribed.
+pub(crate) async fn watch_git_status_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    watchers: &GitStatusWatchers,
+    event_sink: E,
+    workspace_id: String,
+    enabled: bool,
+) -> Result<bool, String> {
+    if !enabled {
+        watchers.unwatch(&workspace_id);
+        return Ok(false);
+    }
+    if watchers.subscribe(&workspace_id) {
+        return Ok(true);
+    }
+    let entry = {
+        let workspaces = workspaces.lock().await;
+        workspaces
+            .get(&workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let watcher = start_git_watcher(&repo_root, event_sink, workspace_id.clone())?;
+    watchers.insert(workspace_id, watcher);
+    Ok(true)
+}
+
+/// Drops watchers for workspaces that no longer exist, e.g. after a removal
+/// that also took its worktrees with it.
+pub(crate) async fn prune_git_watchers_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    watchers: &GitStatusWatchers,
+) {
+    let workspaces = workspaces.lock().await;
+    watchers.retain(|workspace_id| workspaces.contains_key(workspace_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watch_paths() -> GitWatchPaths {
+        GitWatchPaths {
+            workdir: PathBuf::from("/repo"),
+            git_dir: PathBuf::from("/repo/.git"),
+            common_dir: PathBuf::from("/repo/.git"),
+        }
+    }
+
+    #[test]
+    fn relevant_git_paths_skip_object_and_lock_churn() {
+        let paths = watch_paths();
+        let relevant = |path: &str| is_relevant_git_path(&paths, Path::new(path));
+
+        assert!(relevant("/repo/src/main.rs"));
+        assert!(!relevant("/repo/node_modules/pkg/index.js"));
+        assert!(!relevant("/repo/target/debug/build.log"));
+        assert!(relevant("/repo/.git/index"));
+        assert!(relevant("/repo/.git/HEAD"));
+        assert!(relevant("/repo/.git/refs/heads/main"));
+        assert!(!relevant("/repo/.git/index.lock"));
+        assert!(!relevant("/repo/.git/refs/heads/main.lock"));
+        assert!(!relevant("/repo/.git/objects/ab/cdef"));
+        assert!(!relevant("/repo/.git/logs/HEAD"));
+        assert!(!relevant("/elsewhere/file.txt"));
+    }
+
+    #[test]
+    fn relevant_git_paths_cover_linked_worktree_dirs() {
+        let paths = GitWatchPaths {
+            workdir: PathBuf::from("/worktrees/feature"),
+            git_dir: PathBuf::from("/repo/.git/worktrees/feature"),
+            common_dir: PathBuf::from("/repo/.git"),
+        };
+        let relevant = |path: &str| is_relevant_git_path(&paths, Path::new(path));
+
+        assert!(relevant("/repo/.git/worktrees/feature/HEAD"));
+        assert!(relevant("/repo/.git/worktrees/feature/index"));
+        assert!(relevant("/repo/.git/refs/heads/feature"));
+        assert!(!relevant("/repo/.git/objects/pack/pack-1.pack"));
+        assert!(relevant("/worktrees/feature/README.md"));
+    }
+
+    #[test]
+    fn watchers_stop_with_their_last_subscriber() {
+        let watchers = GitStatusWatchers::default();
+        let new_watcher = || {
+            let watcher =
+                notify::recommended_watcher(|_: notify::Result<Event>| {}).expect("watcher");
+            Arc::new(StdMutex::new(watcher))
+        };
+
+        assert!(!watchers.subscribe("ws"));
+        watchers.insert("ws".to_string(), new_watcher());
+        assert!(watchers.subscribe("ws"));
+        // A racing subscriber that started its own watcher joins the first.
+        watchers.insert("ws".to_string(), new_watcher());
+
+        assert!(watchers.unwatch("ws"));
+        assert!(watchers.unwatch("ws"));
+        assert!(watchers.subscribe("ws"));
+        assert!(watchers.unwatch("ws"));
+        assert!(watchers.unwatch("ws"));
+        assert!(!watchers.unwatch("ws"));
+        assert!(!watchers.subscribe("ws"));
+    }
+}
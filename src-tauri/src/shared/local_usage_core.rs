@@ -4,12 +4,13 @@ use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex;
 
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::types::{
-    LocalUsageDay, LocalUsageModel, LocalUsageSnapshot, LocalUsageTotals, WorkspaceEntry,
+    LocalUsageDay, LocalUsageModel, LocalUsageRefreshResult, LocalUsageSnapshot, LocalUsageTotals,
+    WorkspaceEntry,
 };
 
 #[derive(Default, Clone, Copy)]
@@ -35,6 +36,36 @@ pub(crate) async fn local_usage_snapshot_core(
     days: Option<u32>,
     workspace_path: Option<String>,
 ) -> Result<LocalUsageSnapshot, String> {
+    let (snapshot, _, _) = scan_local_usage_for_request(workspaces, days, workspace_path).await?;
+    Ok(snapshot)
+}
+
+/// Rescans every session file from scratch and reports how much was read,
+/// so a "recalculate" action can tell parsing issues apart from stale data.
+pub(crate) async fn refresh_local_usage_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    days: Option<u32>,
+    workspace_path: Option<String>,
+) -> Result<LocalUsageRefreshResult, String> {
+    let started = Instant::now();
+    let (snapshot, files_scanned, sessions_roots) =
+        scan_local_usage_for_request(workspaces, days, workspace_path).await?;
+    Ok(LocalUsageRefreshResult {
+        snapshot,
+        files_scanned,
+        sessions_roots: sessions_roots
+            .iter()
+            .map(|root| root.to_string_lossy().to_string())
+            .collect(),
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+async fn scan_local_usage_for_request(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    days: Option<u32>,
+    workspace_path: Option<String>,
+) -> Result<(LocalUsageSnapshot, usize, Vec<PathBuf>), String> {
     let days = days.unwrap_or(30).clamp(1, 90);
     let workspace_path = workspace_path.and_then(|value| {
         let trimmed = value.trim();
@@ -48,19 +79,21 @@ pub(crate) async fn local_usage_snapshot_core(
         let workspaces = workspaces.lock().await;
         resolve_sessions_roots(&workspaces, workspace_path.as_deref())
     };
-    let snapshot = tokio::task::spawn_blocking(move || {
-        scan_local_usage(days, workspace_path.as_deref(), &sessions_roots)
+    tokio::task::spawn_blocking(move || {
+        let (snapshot, files_scanned) =
+            scan_local_usage(days, workspace_path.as_deref(), &sessions_roots)?;
+        Ok((snapshot, files_scanned, sessions_roots))
     })
     .await
-    .map_err(|err| err.to_string())??;
-    Ok(snapshot)
+    .map_err(|err| err.to_string())?
 }
 
+/// Returns the snapshot along with the number of session files read.
 fn scan_local_usage(
     days: u32,
     workspace_path: Option<&Path>,
     sessions_roots: &[PathBuf],
-) -> Result<LocalUsageSnapshot, String> {
+) -> Result<(LocalUsageSnapshot, usize), String> {
     let updated_at = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
@@ -74,9 +107,14 @@ fn scan_local_usage(
     let mut model_totals: HashMap<String, i64> = HashMap::new();
 
     if sessions_roots.is_empty() {
-        return Ok(build_snapshot(updated_at, day_keys, daily, HashMap::new()));
+        return Ok((
+            build_snapshot(updated_at, day_keys, daily, HashMap::new()),
+            0,
+        ));
     }
 
+    let mut files_scanned = 0;
+
     for root in sessions_roots {
         for day_key in &day_keys {
             let day_dir = day_dir_for_key(root, day_key);
@@ -93,11 +131,15 @@ fn scan_local_usage(
                     continue;
                 }
                 scan_file(&path, &mut daily, &mut model_totals, workspace_path)?;
+                files_scanned += 1;
             }
         }
     }
 
-    Ok(build_snapshot(updated_at, day_keys, daily, model_totals))
+    Ok((
+        build_snapshot(updated_at, day_keys, daily, model_totals),
+        files_scanned,
+    ))
 }
 
 fn build_snapshot(
@@ -795,7 +837,9 @@ mod tests {
         write_session_file(&root_a, &day_key, &[line_a]);
         write_session_file(&root_b, &day_key, &[line_b]);
 
-        let snapshot = scan_local_usage(2, None, &[root_a, root_b]).expect("scan usage");
+        let (snapshot, files_scanned) =
+            scan_local_usage(2, None, &[root_a, root_b]).expect("scan usage");
+        assert_eq!(files_scanned, 2);
         let day = snapshot
             .days
             .iter()
@@ -825,6 +869,8 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: settings_a,
         };
         let mut settings_b = WorkspaceSettings::default();
@@ -842,6 +888,8 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: settings_b,
         };
         workspaces.insert(entry_a.id.clone(), entry_a.clone());
@@ -1,14 +1,24 @@
 pub(crate) mod account;
 pub(crate) mod codex_aux_core;
-pub(crate) mod codex_update_core;
 pub(crate) mod codex_core;
+pub(crate) mod codex_update_core;
+pub(crate) mod doctor_core;
+pub(crate) mod file_tail_core;
 pub(crate) mod files_core;
 pub(crate) mod git_core;
 pub(crate) mod git_ui_core;
+pub(crate) mod git_watch_core;
 pub(crate) mod local_usage_core;
 pub(crate) mod orbit_core;
 pub(crate) mod process_core;
 pub(crate) mod prompts_core;
+pub(crate) mod search_core;
+pub(crate) mod session_error_core;
 pub(crate) mod settings_core;
+pub(crate) mod skip_dirs;
+#[cfg(test)]
+pub(crate) mod test_support;
+pub(crate) mod timeline_core;
+pub(crate) mod workspace_files_core;
 pub(crate) mod workspaces_core;
 pub(crate) mod worktree_core;
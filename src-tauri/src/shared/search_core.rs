@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+
+use ignore::overrides::OverrideBuilder;
+use ignore::{WalkBuilder, WalkState};
+use regex::{Regex, RegexBuilder};
+use tokio::sync::Mutex;
+
+use crate::shared::skip_dirs::should_skip_dir;
+use crate::types::{WorkspaceEntry, WorkspaceGrepMatch, WorkspaceGrepResponse};
+use crate::utils::normalize_git_path;
+
+const DEFAULT_GREP_MAX_RESULTS: usize = 500;
+const MAX_GREP_RESULTS: usize = 5000;
+const MAX_GREP_FILE_BYTES: u64 = 2 * 1024 * 1024;
+const MAX_GREP_SNIPPET_CHARS: usize = 240;
+const MAX_GREP_THREADS: usize = 8;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WorkspaceGrepOptions {
+    pub(crate) query: String,
+    pub(crate) is_regex: bool,
+    pub(crate) case_sensitive: bool,
+    pub(crate) globs: Vec<String>,
+    pub(crate) max_results: Option<usize>,
+    /// Directory names left out on top of the built-in ones: the
+    /// `fileListSkipDirs` setting, so search covers what the file list shows.
+    pub(crate) skip_dirs: Vec<String>,
+    /// Key that `cancel_workspace_walk` can target while the walk is running.
+    pub(crate) walk_key: Option<WalkKey>,
+}
+
+/// A grep walk's cancellation key: the daemon connection it runs for (`None`
+/// in the app itself) and the request id on that connection. Scoping by
+/// connection keeps one client from cancelling another's walk with a
+/// colliding id.
+pub(crate) type WalkKey = (Option<u64>, String);
+
+type WalkFlags = Arc<StdMutex<HashMap<WalkKey, Arc<AtomicBool>>>>;
+
+/// Cancellation flags for in-flight `grep_workspace` walks, the only walks
+/// that run on a blocking thread; file listings are bounded and not
+/// cancellable. Aborting the task would not stop the thread, so the walk
+/// polls its flag between entries instead.
+#[derive(Default)]
+pub(crate) struct WalkCancellations {
+    flags: WalkFlags,
+}
+
+impl WalkCancellations {
+    /// The registration owns its entry, so it can move onto the blocking
+    /// thread and stay cancellable for as long as the walk runs.
+    fn register(&self, key: Option<WalkKey>) -> WalkRegistration {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Some(key) = key.clone() {
+            self.flags
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .insert(key, Arc::clone(&flag));
+        }
+        WalkRegistration {
+            flags: Arc::clone(&self.flags),
+            key,
+            flag,
+        }
+    }
+
+    /// Flags the walk registered under `key`; returns false when no such walk
+    /// is running (unknown, already finished or another connection's).
+    pub(crate) fn cancel(&self, key: &WalkKey) -> bool {
+        let flags = self.flags.lock().unwrap_or_else(|err| err.into_inner());
+        match flags.get(key) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+struct WalkRegistration {
+    flags: WalkFlags,
+    key: Option<WalkKey>,
+    flag: Arc<AtomicBool>,
+}
+
+impl Drop for WalkRegistration {
+    fn drop(&mut self) {
+        let Some(key) = self.key.as_ref() else {
+            return;
+        };
+        let mut flags = self.flags.lock().unwrap_or_else(|err| err.into_inner());
+        // A newer walk may have reused the id; only remove our own flag.
+        if flags
+            .get(key)
+            .is_some_and(|flag| Arc::ptr_eq(flag, &self.flag))
+        {
+            flags.remove(key);
+        }
+    }
+}
+
+/// Flags a walk when the request awaiting it goes away, e.g. when a JSON-RPC
+/// `cancel` aborts it, so the blocking thread stops at its next entry instead
+/// of running to the end for nobody.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+fn build_matcher(options: &WorkspaceGrepOptions) -> Result<Regex, String> {
+    if options.query.is_empty() {
+        return Err("query is required".to_string());
+    }
+    let pattern = if options.is_regex {
+        options.query.clone()
+    } else {
+        regex::escape(&options.query)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|err| format!("Invalid search pattern: {err}"))
+}
+
+fn read_searchable_text(path: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if metadata.len() > MAX_GREP_FILE_BYTES {
+        return None;
+    }
+    let mut buffer = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(MAX_GREP_FILE_BYTES)
+        .read_to_end(&mut buffer)
+        .ok()?;
+    if buffer.iter().take(8192).any(|byte| *byte == 0) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+fn build_snippet(line: &str) -> String {
+    let trimmed = line.trim_end_matches(['\r', '\n']);
+    if trimmed.chars().count() <= MAX_GREP_SNIPPET_CHARS {
+        return trimmed.to_string();
+    }
+    trimmed.chars().take(MAX_GREP_SNIPPET_CHARS).collect()
+}
+
+fn grep_file(path: &Path, relative_path: &str, matcher: &Regex) -> Vec<WorkspaceGrepMatch> {
+    let Some(content) = read_searchable_text(path) else {
+        return Vec::new();
+    };
+    let mut matches = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        for found in matcher.find_iter(line) {
+            if found.as_str().is_empty() {
+                continue;
+            }
+            matches.push(WorkspaceGrepMatch {
+                path: relative_path.to_string(),
+                line: index + 1,
+                column: line[..found.start()].chars().count() + 1,
+                snippet: build_snippet(line),
+            });
+        }
+    }
+    matches
+}
+
+pub(crate) fn grep_workspace_inner(
+    root: &Path,
+    options: &WorkspaceGrepOptions,
+    cancelled: &Arc<AtomicBool>,
+) -> Result<WorkspaceGrepResponse, String> {
+    let matcher = build_matcher(options)?;
+    let max_results = options
+        .max_results
+        .unwrap_or(DEFAULT_GREP_MAX_RESULTS)
+        .clamp(1, MAX_GREP_RESULTS);
+
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in options.globs.iter().map(|glob| glob.trim()) {
+        if glob.is_empty() {
+            continue;
+        }
+        overrides
+            .add(glob)
+            .map_err(|err| format!("Invalid glob `{glob}`: {err}"))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|err| format!("Invalid glob filters: {err}"))?;
+
+    let skip_dirs = options.skip_dirs.clone();
+    let threads = std::thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .min(MAX_GREP_THREADS);
+    let walker = WalkBuilder::new(root)
+        // Allow hidden entries.
+        .hidden(false)
+        // Avoid crawling symlink targets.
+        .follow_links(false)
+        // Don't require git to be present to apply git-related ignore rules.
+        .require_git(false)
+        .overrides(overrides)
+        .threads(threads)
+        .filter_entry(move |entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name, &skip_dirs);
+            }
+            true
+        })
+        .build_parallel();
+
+    let results = Arc::new(StdMutex::new(Vec::<WorkspaceGrepMatch>::new()));
+    let truncated = Arc::new(AtomicBool::new(false));
+    let root = root.to_path_buf();
+    walker.run(|| {
+        let matcher = matcher.clone();
+        let results = Arc::clone(&results);
+        let truncated = Arc::clone(&truncated);
+        let cancelled = Arc::clone(cancelled);
+        let root = root.clone();
+        Box::new(move |entry| {
+            if truncated.load(Ordering::Relaxed) || cancelled.load(Ordering::Relaxed) {
+                return WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return WalkState::Continue;
+            }
+            let Ok(rel_path) = entry.path().strip_prefix(&root) else {
+                return WalkState::Continue;
+            };
+            let relative_path = normalize_git_path(&rel_path.to_string_lossy());
+            let file_matches = grep_file(entry.path(), &relative_path, &matcher);
+            if file_matches.is_empty() {
+                return WalkState::Continue;
+            }
+            let mut results = results.lock().unwrap_or_else(|err| err.into_inner());
+            let remaining = max_results.saturating_sub(results.len());
+            if file_matches.len() > remaining {
+                results.extend(file_matches.into_iter().take(remaining));
+                truncated.store(true, Ordering::Relaxed);
+                return WalkState::Quit;
+            }
+            results.extend(file_matches);
+            WalkState::Continue
+        })
+    });
+
+    let mut matches = std::mem::take(&mut *results.lock().unwrap_or_else(|err| err.into_inner()));
+    matches.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.line.cmp(&b.line))
+            .then(a.column.cmp(&b.column))
+    });
+    Ok(WorkspaceGrepResponse {
+        matches,
+        truncated: truncated.load(Ordering::Relaxed),
+        cancelled: cancelled.load(Ordering::Relaxed),
+    })
+}
+
+pub(crate) async fn grep_workspace_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    cancellations: &WalkCancellations,
+    workspace_id: &str,
+    options: WorkspaceGrepOptions,
+) -> Result<WorkspaceGrepResponse, String> {
+    let root = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let registration = cancellations.register(options.walk_key.clone());
+    let cancelled = Arc::clone(&registration.flag);
+    let _cancel_on_drop = CancelOnDrop(Arc::clone(&cancelled));
+    tokio::task::spawn_blocking(move || {
+        let result = grep_workspace_inner(&root, &options, &cancelled);
+        drop(registration);
+        result
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+/// Cancels the grep walk `request_id` that `connection` started.
+pub(crate) fn cancel_workspace_walk_core(
+    cancellations: &WalkCancellations,
+    connection: Option<u64>,
+    request_id: &str,
+) -> bool {
+    cancellations.cancel(&(connection, request_id.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn make_temp_root() -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("codex-monitor-grep-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create temp root");
+        root
+    }
+
+    fn not_cancelled() -> Arc<AtomicBool> {
+        Arc::new(AtomicBool::new(false))
+    }
+
+    fn options(query: &str) -> WorkspaceGrepOptions {
+        WorkspaceGrepOptions {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn grep_workspace_reports_line_and_column() {
+        let root = make_temp_root();
+        fs::create_dir_all(root.join("src")).expect("create src");
+        fs::write(
+            root.join("src/main.rs"),
+            "fn main() {\n    let Needle = 1;\n}\n",
+        )
+        .expect("write file");
+
+        let response =
+            grep_workspace_inner(&root, &options("needle"), &not_cancelled()).expect("grep");
+        assert!(!response.truncated);
+        assert_eq!(
+            response.matches,
+            vec![WorkspaceGrepMatch {
+                path: "src/main.rs".to_string(),
+                line: 2,
+                column: 9,
+                snippet: "    let Needle = 1;".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn grep_workspace_respects_gitignore_and_globs() {
+        let root = make_temp_root();
+        fs::write(root.join(".gitignore"), "ignored.txt\n").expect("write gitignore");
+        fs::write(root.join("ignored.txt"), "needle\n").expect("write ignored");
+        fs::write(root.join("kept.md"), "needle\n").expect("write md");
+        fs::write(root.join("kept.rs"), "needle\n").expect("write rs");
+
+        let mut filtered = options("needle");
+        filtered.globs = vec!["*.rs".to_string()];
+        let response = grep_workspace_inner(&root, &filtered, &not_cancelled()).expect("grep");
+        let paths: Vec<_> = response.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["kept.rs"]);
+    }
+
+    #[test]
+    fn grep_workspace_skips_configured_directories() {
+        let root = make_temp_root();
+        for dir in ["venv", "node_modules", "src"] {
+            fs::create_dir_all(root.join(dir)).expect("create dir");
+            fs::write(root.join(dir).join("hit.py"), "needle\n").expect("write file");
+        }
+
+        let response =
+            grep_workspace_inner(&root, &options("needle"), &not_cancelled()).expect("grep");
+        let paths: Vec<_> = response.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/hit.py", "venv/hit.py"]);
+
+        let mut skipping = options("needle");
+        skipping.skip_dirs = vec![" venv ".to_string()];
+        let response = grep_workspace_inner(&root, &skipping, &not_cancelled()).expect("grep");
+        let paths: Vec<_> = response.matches.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["src/hit.py"]);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn grep_workspace_caps_results() {
+        let root = make_temp_root();
+        fs::write(root.join("many.txt"), "hit\n".repeat(20)).expect("write file");
+
+        let mut capped = options("h.t");
+        capped.is_regex = true;
+        capped.max_results = Some(5);
+        let response = grep_workspace_inner(&root, &capped, &not_cancelled()).expect("grep");
+        assert_eq!(response.matches.len(), 5);
+        assert!(response.truncated);
+    }
+
+    #[test]
+    fn grep_workspace_rejects_invalid_regex() {
+        let root = make_temp_root();
+        let mut invalid = options("(");
+        invalid.is_regex = true;
+        assert!(grep_workspace_inner(&root, &invalid, &not_cancelled()).is_err());
+    }
+
+    #[test]
+    fn grep_workspace_stops_when_cancelled() {
+        let root = make_temp_root();
+        fs::write(root.join("a.txt"), "needle\n").expect("write file");
+
+        let response =
+            grep_workspace_inner(&root, &options("needle"), &Arc::new(AtomicBool::new(true)))
+                .expect("grep");
+        assert!(response.cancelled);
+        assert!(response.matches.is_empty());
+    }
+
+    #[test]
+    fn walk_cancellations_track_registered_requests() {
+        let cancellations = WalkCancellations::default();
+        let key = (Some(1), "req-1".to_string());
+        assert!(!cancellations.cancel(&key));
+        let registration = cancellations.register(Some(key.clone()));
+        let other = cancellations.register(Some((Some(2), "req-1".to_string())));
+        assert!(!cancel_workspace_walk_core(&cancellations, None, "req-1"));
+        assert!(cancel_workspace_walk_core(&cancellations, Some(1), "req-1"));
+        assert!(registration.flag.load(Ordering::Relaxed));
+        // The same id on another connection is a different walk.
+        assert!(!other.flag.load(Ordering::Relaxed));
+        drop(registration);
+        assert!(!cancellations.cancel(&key));
+    }
+
+    #[test]
+    fn aborted_grep_stops_its_walk_and_stays_cancellable_until_it_returns() {
+        let cancellations = WalkCancellations::default();
+        let key = (None, "req-1".to_string());
+        let registration = cancellations.register(Some(key.clone()));
+        let flag = Arc::clone(&registration.flag);
+        // The awaiting request is aborted: its guard flags the walk.
+        drop(CancelOnDrop(Arc::clone(&flag)));
+        assert!(flag.load(Ordering::Relaxed));
+
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let walk = std::thread::spawn(move || {
+            let _registration = registration;
+            let _ = wait.recv();
+        });
+        assert!(cancellations.cancel(&key));
+        release.send(()).expect("release walk");
+        walk.join().expect("walk thread");
+        assert!(!cancellations.cancel(&key));
+    }
+}
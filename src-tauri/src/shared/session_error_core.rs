@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+
+use crate::types::{SessionLastError, WorkspaceEntry};
+
+/// Last session error per workspace, owned by the app or daemon state and
+/// handed to each session it spawns. Kept outside `WorkspaceSession` because
+/// a spawn failure never produces a session, and an exited session is
+/// replaced on reconnect.
+#[derive(Clone, Default)]
+pub(crate) struct SessionErrors {
+    errors: Arc<StdMutex<HashMap<String, SessionLastError>>>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+impl SessionErrors {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, SessionLastError>> {
+        self.errors.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// `kind` is `spawn`, `protocol` or `exit`.
+    pub(crate) fn record(&self, workspace_id: &str, kind: &str, message: &str) {
+        self.lock().insert(
+            workspace_id.to_string(),
+            SessionLastError {
+                kind: kind.to_string(),
+                message: message.to_string(),
+                timestamp: now_ms(),
+            },
+        );
+    }
+
+    /// Called when a session starts and on every successful response, so only
+    /// a failure nothing has recovered from is reported.
+    pub(crate) fn clear(&self, workspace_id: &str) {
+        self.lock().remove(workspace_id);
+    }
+
+    fn get(&self, workspace_id: &str) -> Option<SessionLastError> {
+        self.lock().get(workspace_id).cloned()
+    }
+}
+
+pub(crate) async fn session_last_error_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    session_errors: &SessionErrors,
+    workspace_id: String,
+) -> Result<Option<SessionLastError>, String> {
+    if !workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    Ok(session_errors.get(&workspace_id))
+}
+
+/// Drops the last errors of workspaces that no longer exist.
+pub(crate) async fn prune_session_errors_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    session_errors: &SessionErrors,
+) {
+    let workspaces = workspaces.lock().await;
+    session_errors
+        .lock()
+        .retain(|workspace_id, _| workspaces.contains_key(workspace_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_error_is_replaced_and_then_cleared() {
+        let session_errors = SessionErrors::default();
+        session_errors.record("w1", "protocol", "expected value at line 1");
+        session_errors.record("w1", "exit", "Codex session exited with code 3");
+        let error = session_errors.get("w1").expect("recorded error");
+        assert_eq!(error.kind, "exit");
+        assert_eq!(error.message, "Codex session exited with code 3");
+
+        session_errors.clear("w1");
+        assert!(session_errors.get("w1").is_none());
+    }
+
+    #[test]
+    fn errors_go_with_their_workspace() {
+        let session_errors = SessionErrors::default();
+        session_errors.record("w1", "spawn", "codex not found");
+        session_errors.record("w2", "spawn", "codex not found");
+        let workspaces = Mutex::new(HashMap::new());
+
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime")
+            .block_on(prune_session_errors_core(&workspaces, &session_errors));
+        assert!(session_errors.get("w1").is_none());
+        assert!(session_errors.get("w2").is_none());
+    }
+}
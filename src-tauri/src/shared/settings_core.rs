@@ -1,10 +1,26 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde_json::{Map, Value};
 use tokio::sync::Mutex;
 
 use crate::codex::config as codex_config;
-use crate::storage::write_settings;
-use crate::types::AppSettings;
+use crate::storage::update_settings;
+use crate::types::{
+    AppSettings, AppSettingsValidation, RecentWorkspace, RecentWorkspaceInfo,
+    SettingsValidationIssue, WorkspaceEntry,
+};
+
+const SETTINGS_FIELD_ALIASES: &[&str] =
+    &["experimentalSteerEnabled", "experimentalUnifiedExecEnabled"];
+const UI_SCALE_RANGE: (f64, f64) = (0.1, 3.0);
+const CODE_FONT_SIZE_RANGE: (u8, u8) = (9, 16);
+const ACCESS_MODES: &[&str] = &["read-only", "current", "full-access"];
+const REVIEW_DELIVERY_MODES: &[&str] = &["inline", "detached"];
+const RECENT_MODELS_LIMIT: usize = 5;
+const MAX_RECENT_WORKSPACES_LIMIT: u32 = 100;
 
 fn normalize_personality(value: &str) -> Option<&'static str> {
     match value.trim() {
@@ -42,23 +58,161 @@ pub(crate) async fn get_app_settings_core(app_settings: &Mutex<AppSettings>) ->
     settings
 }
 
+/// An empty template means "use the built-in prompt"; anything else must say
+/// where the staged diff goes.
+fn validate_commit_message_prompt(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() || template.contains("{diff}") {
+        Ok(())
+    } else {
+        Err("commitMessagePrompt must contain the {diff} placeholder".to_string())
+    }
+}
+
 pub(crate) async fn update_app_settings_core(
     settings: AppSettings,
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
 ) -> Result<AppSettings, String> {
+    // Only check templates being changed, so a legacy template saved before
+    // this rule does not block unrelated settings updates.
+    if settings.commit_message_prompt != app_settings.lock().await.commit_message_prompt {
+        validate_commit_message_prompt(&settings.commit_message_prompt)?;
+    }
     let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = codex_config::write_collaboration_modes_enabled(settings.collaboration_modes_enabled);
     let _ = codex_config::write_steer_enabled(settings.steer_enabled);
     let _ = codex_config::write_unified_exec_enabled(settings.unified_exec_enabled);
     let _ = codex_config::write_apps_enabled(settings.experimental_apps_enabled);
     let _ = codex_config::write_personality(settings.personality.as_str());
-    write_settings(settings_path, &settings)?;
     let mut current = app_settings.lock().await;
+    let settings = update_settings(settings_path, |stored| {
+        // Recents are recorded server-side; a client's copy may be stale.
+        let mut settings = settings;
+        settings.recent_models = std::mem::take(&mut stored.recent_models);
+        settings.recent_workspaces = std::mem::take(&mut stored.recent_workspaces);
+        trim_recent_workspaces(&mut settings);
+        *stored = settings;
+    })?;
     *current = settings.clone();
     Ok(settings)
 }
 
+/// Moves `model` to the front of `recents`, trimming to the limit. Returns
+/// whether the list changed.
+fn push_recent_model(recents: &mut Vec<String>, model: &str) -> bool {
+    if recents.first().map(String::as_str) == Some(model) {
+        return false;
+    }
+    recents.retain(|existing| existing != model);
+    recents.insert(0, model.to_string());
+    recents.truncate(RECENT_MODELS_LIMIT);
+    true
+}
+
+pub(crate) async fn record_recent_model_core(
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    workspace_id: &str,
+    model: &str,
+) -> Result<(), String> {
+    let model = model.trim();
+    if model.is_empty() {
+        return Ok(());
+    }
+    let mut current = app_settings.lock().await;
+    // Skip the storage write when the model is already the most recent one.
+    let mut recents = current
+        .recent_models
+        .get(workspace_id)
+        .cloned()
+        .unwrap_or_default();
+    if !push_recent_model(&mut recents, model) {
+        return Ok(());
+    }
+    *current = update_settings(settings_path, |stored| {
+        let recents = stored
+            .recent_models
+            .entry(workspace_id.to_string())
+            .or_default();
+        push_recent_model(recents, model);
+    })?;
+    Ok(())
+}
+
+fn trim_recent_workspaces(settings: &mut AppSettings) {
+    let limit = settings
+        .recent_workspaces_limit
+        .min(MAX_RECENT_WORKSPACES_LIMIT) as usize;
+    settings.recent_workspaces.truncate(limit);
+}
+
+fn push_recent_workspace(settings: &mut AppSettings, workspace_id: &str, opened_at: i64) {
+    settings
+        .recent_workspaces
+        .retain(|recent| recent.workspace_id != workspace_id);
+    settings.recent_workspaces.insert(
+        0,
+        RecentWorkspace {
+            workspace_id: workspace_id.to_string(),
+            opened_at,
+        },
+    );
+    trim_recent_workspaces(settings);
+}
+
+/// Moves a workspace to the front of the recents list. Clients call this when
+/// the user opens a workspace; background connects don't count.
+pub(crate) async fn record_recent_workspace_core(
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    workspace_id: &str,
+) -> Result<(), String> {
+    let opened_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0);
+    let mut current = app_settings.lock().await;
+    *current = update_settings(settings_path, |stored| {
+        push_recent_workspace(stored, workspace_id, opened_at);
+    })?;
+    Ok(())
+}
+
+/// Recent workspaces, most recent first. Entries for removed workspaces are
+/// skipped rather than pruned; they fall off the end as new ones are opened.
+pub(crate) async fn list_recent_workspaces_core(
+    app_settings: &Mutex<AppSettings>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+) -> Vec<RecentWorkspaceInfo> {
+    let recents = app_settings.lock().await.recent_workspaces.clone();
+    let workspaces = workspaces.lock().await;
+    recents
+        .into_iter()
+        .filter_map(|recent| {
+            let entry = workspaces.get(&recent.workspace_id)?;
+            Some(RecentWorkspaceInfo {
+                id: entry.id.clone(),
+                name: entry.name.clone(),
+                path: entry.path.clone(),
+                opened_at: recent.opened_at,
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn recent_models_core(
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: &str,
+) -> Vec<String> {
+    app_settings
+        .lock()
+        .await
+        .recent_models
+        .get(workspace_id)
+        .cloned()
+        .unwrap_or_default()
+}
+
 pub(crate) async fn update_remote_backend_token_core(
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
@@ -76,6 +230,159 @@ pub(crate) async fn update_remote_backend_token_core(
     update_app_settings_core(next_settings, app_settings, settings_path).await
 }
 
+fn settings_issue(field: &str, kind: &str, message: impl Into<String>) -> SettingsValidationIssue {
+    SettingsValidationIssue {
+        field: field.to_string(),
+        kind: kind.to_string(),
+        message: message.into(),
+    }
+}
+
+fn range_issues(
+    settings: &AppSettings,
+    fields: &Map<String, Value>,
+) -> Vec<SettingsValidationIssue> {
+    let mut issues = Vec::new();
+    if fields.contains_key("uiScale")
+        && !(UI_SCALE_RANGE.0..=UI_SCALE_RANGE.1).contains(&settings.ui_scale)
+    {
+        issues.push(settings_issue(
+            "uiScale",
+            "outOfRange",
+            format!(
+                "uiScale must be between {} and {}",
+                UI_SCALE_RANGE.0, UI_SCALE_RANGE.1
+            ),
+        ));
+    }
+    if fields.contains_key("codeFontSize")
+        && !(CODE_FONT_SIZE_RANGE.0..=CODE_FONT_SIZE_RANGE.1).contains(&settings.code_font_size)
+    {
+        issues.push(settings_issue(
+            "codeFontSize",
+            "outOfRange",
+            format!(
+                "codeFontSize must be between {} and {}",
+                CODE_FONT_SIZE_RANGE.0, CODE_FONT_SIZE_RANGE.1
+            ),
+        ));
+    }
+    if fields.contains_key("remoteBackendHost") {
+        let host = settings.remote_backend_host.trim();
+        let port = host
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .filter(|port| *port > 0);
+        if host.parse::<SocketAddr>().is_err() && port.is_none() {
+            issues.push(settings_issue(
+                "remoteBackendHost",
+                "outOfRange",
+                "remoteBackendHost must be host:port with a port between 1 and 65535",
+            ));
+        }
+    }
+    if fields.contains_key("defaultAccessMode")
+        && !ACCESS_MODES.contains(&settings.default_access_mode.as_str())
+    {
+        issues.push(settings_issue(
+            "defaultAccessMode",
+            "outOfRange",
+            format!(
+                "defaultAccessMode must be one of: {}",
+                ACCESS_MODES.join(", ")
+            ),
+        ));
+    }
+    if fields.contains_key("maxTurnsPerThread") && settings.max_turns_per_thread == Some(0) {
+        issues.push(settings_issue(
+            "maxTurnsPerThread",
+            "outOfRange",
+            "maxTurnsPerThread must be at least 1, or null for no limit",
+        ));
+    }
+    if fields.contains_key("modelInputPrices")
+        && settings
+            .model_input_prices
+            .values()
+            .any(|price| !(price.is_finite() && *price >= 0.0))
+    {
+        issues.push(settings_issue(
+            "modelInputPrices",
+            "outOfRange",
+            "modelInputPrices must map model ids to non-negative prices",
+        ));
+    }
+    if fields.contains_key("commitMessagePrompt") {
+        if let Err(message) = validate_commit_message_prompt(&settings.commit_message_prompt) {
+            issues.push(settings_issue(
+                "commitMessagePrompt",
+                "missingPlaceholder",
+                message,
+            ));
+        }
+    }
+    if fields.contains_key("reviewDeliveryMode")
+        && !REVIEW_DELIVERY_MODES.contains(&settings.review_delivery_mode.as_str())
+    {
+        issues.push(settings_issue(
+            "reviewDeliveryMode",
+            "outOfRange",
+            format!(
+                "reviewDeliveryMode must be one of: {}",
+                REVIEW_DELIVERY_MODES.join(", ")
+            ),
+        ));
+    }
+    issues
+}
+
+/// Checks a raw settings blob without persisting it. Unknown fields are
+/// reported but do not make the blob invalid, since they are ignored on load.
+pub(crate) fn validate_app_settings_core(value: Value) -> Result<AppSettingsValidation, String> {
+    let Value::Object(fields) = value else {
+        return Ok(AppSettingsValidation {
+            valid: false,
+            issues: vec![settings_issue(
+                "",
+                "typeMismatch",
+                "settings must be a JSON object",
+            )],
+        });
+    };
+    let defaults = serde_json::to_value(AppSettings::default()).map_err(|err| err.to_string())?;
+    let known_fields = defaults
+        .as_object()
+        .ok_or_else(|| "default settings did not serialize to an object".to_string())?;
+
+    let mut issues = Vec::new();
+    let mut accepted = Map::new();
+    for (key, field_value) in &fields {
+        if !known_fields.contains_key(key) && !SETTINGS_FIELD_ALIASES.contains(&key.as_str()) {
+            issues.push(settings_issue(
+                key,
+                "unknownField",
+                format!("unknown field `{key}`"),
+            ));
+            continue;
+        }
+        let mut probe = Map::new();
+        probe.insert(key.clone(), field_value.clone());
+        match serde_json::from_value::<AppSettings>(Value::Object(probe)) {
+            Ok(_) => {
+                accepted.insert(key.clone(), field_value.clone());
+            }
+            Err(err) => issues.push(settings_issue(key, "typeMismatch", err.to_string())),
+        }
+    }
+
+    let settings = serde_json::from_value::<AppSettings>(Value::Object(accepted.clone()))
+        .map_err(|err| err.to_string())?;
+    issues.extend(range_issues(&settings, &accepted));
+
+    let valid = issues.iter().all(|issue| issue.kind == "unknownField");
+    Ok(AppSettingsValidation { valid, issues })
+}
+
 pub(crate) fn get_codex_config_path_core() -> Result<String, String> {
     codex_config::config_toml_path()
         .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
@@ -85,3 +392,126 @@ pub(crate) fn get_codex_config_path_core() -> Result<String, String> {
                 .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn issue_kinds(validation: &AppSettingsValidation) -> Vec<(&str, &str)> {
+        validation
+            .issues
+            .iter()
+            .map(|issue| (issue.field.as_str(), issue.kind.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn push_recent_model_keeps_most_recent_first_without_duplicates() {
+        let mut recents = Vec::new();
+        for model in ["a", "b", "c", "b"] {
+            push_recent_model(&mut recents, model);
+        }
+        assert_eq!(recents, vec!["b", "c", "a"]);
+        assert!(!push_recent_model(&mut recents, "b"));
+
+        for model in ["d", "e", "f", "g"] {
+            push_recent_model(&mut recents, model);
+        }
+        assert_eq!(recents.len(), RECENT_MODELS_LIMIT);
+        assert_eq!(recents.first().map(String::as_str), Some("g"));
+    }
+
+    #[test]
+    fn push_recent_workspace_moves_to_front_and_trims_to_the_limit() {
+        let mut settings = AppSettings {
+            recent_workspaces_limit: 3,
+            ..AppSettings::default()
+        };
+        for (opened_at, id) in ["a", "b", "c", "a", "d"].into_iter().enumerate() {
+            push_recent_workspace(&mut settings, id, opened_at as i64);
+        }
+        let ids: Vec<&str> = settings
+            .recent_workspaces
+            .iter()
+            .map(|recent| recent.workspace_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["d", "a", "c"]);
+        assert_eq!(settings.recent_workspaces[1].opened_at, 3);
+
+        settings.recent_workspaces_limit = 0;
+        push_recent_workspace(&mut settings, "e", 5);
+        assert!(settings.recent_workspaces.is_empty());
+    }
+
+    #[test]
+    fn validate_app_settings_accepts_defaults() {
+        let value = serde_json::to_value(AppSettings::default()).expect("serialize defaults");
+        let validation = validate_app_settings_core(value).expect("validate");
+        assert!(validation.valid);
+        assert!(validation.issues.is_empty());
+    }
+
+    #[test]
+    fn validate_app_settings_reports_each_problem_field() {
+        let validation = validate_app_settings_core(json!({
+            "uiScale": "big",
+            "codeFontSize": 40,
+            "remoteBackendHost": "localhost:99999",
+            "maxTurnsPerThread": 0,
+            "modelInputPrices": { "gpt-5": -1.25 },
+            "notARealSetting": true,
+            "experimentalSteerEnabled": true,
+        }))
+        .expect("validate");
+        assert!(!validation.valid);
+        let mut kinds = issue_kinds(&validation);
+        kinds.sort();
+        assert_eq!(
+            kinds,
+            vec![
+                ("codeFontSize", "outOfRange"),
+                ("maxTurnsPerThread", "outOfRange"),
+                ("modelInputPrices", "outOfRange"),
+                ("notARealSetting", "unknownField"),
+                ("remoteBackendHost", "outOfRange"),
+                ("uiScale", "typeMismatch"),
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_app_settings_requires_diff_placeholder_in_commit_prompt() {
+        let validation = validate_app_settings_core(json!({
+            "commitMessagePrompt": "feat: summarize these changes",
+        }))
+        .expect("validate");
+        assert!(!validation.valid);
+        assert_eq!(
+            issue_kinds(&validation),
+            vec![("commitMessagePrompt", "missingPlaceholder")]
+        );
+
+        for template in ["", "[TICKET-1] Describe:\n{diff}"] {
+            let validation = validate_app_settings_core(json!({ "commitMessagePrompt": template }))
+                .expect("validate");
+            assert!(validation.valid, "{template:?} should be accepted");
+        }
+    }
+
+    #[test]
+    fn validate_app_settings_treats_unknown_fields_as_warnings() {
+        let validation = validate_app_settings_core(json!({ "legacyFlag": 1 })).expect("validate");
+        assert!(validation.valid);
+        assert_eq!(
+            issue_kinds(&validation),
+            vec![("legacyFlag", "unknownField")]
+        );
+    }
+
+    #[test]
+    fn validate_app_settings_rejects_non_objects() {
+        let validation = validate_app_settings_core(json!([1, 2])).expect("validate");
+        assert!(!validation.valid);
+    }
+}
@@ -0,0 +1,17 @@
+/// Directories every walk of a workspace leaves out: git internals, plus
+/// dependencies and build output that churn constantly and are rarely what
+/// anyone is looking for.
+pub(crate) const DEFAULT_SKIP_DIRS: &[&str] = &[
+    ".git",
+    "node_modules",
+    "dist",
+    "target",
+    "release-artifacts",
+];
+
+/// Whether a walk should leave out the directory `name`: one of
+/// `DEFAULT_SKIP_DIRS` or of `extra_skip_dirs` (the `fileListSkipDirs`
+/// setting).
+pub(crate) fn should_skip_dir(name: &str, extra_skip_dirs: &[String]) -> bool {
+    DEFAULT_SKIP_DIRS.contains(&name) || extra_skip_dirs.iter().any(|dir| dir.trim() == name)
+}
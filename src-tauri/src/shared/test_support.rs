@@ -0,0 +1,38 @@
+//! Fixtures shared by the `shared` modules' unit tests.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+
+use tokio::sync::Mutex;
+
+use crate::types::WorkspaceEntry;
+
+/// A main workspace `w1` rooted at `root`.
+pub(crate) fn workspace_entry(root: &Path) -> WorkspaceEntry {
+    WorkspaceEntry {
+        id: "w1".to_string(),
+        name: "w1".to_string(),
+        path: root.to_string_lossy().to_string(),
+        codex_bin: None,
+        kind: crate::types::WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        pinned: false,
+        auto_connect: true,
+        settings: Default::default(),
+    }
+}
+
+pub(crate) fn workspaces_for(root: &Path) -> Mutex<HashMap<String, WorkspaceEntry>> {
+    let entry = workspace_entry(root);
+    Mutex::new(HashMap::from([(entry.id.clone(), entry)]))
+}
+
+pub(crate) fn run<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("runtime")
+        .block_on(future)
+}
@@ -0,0 +1,277 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::shared::git_ui_core;
+use crate::types::{GitReflogEntry, WorkspaceEntry, WorkspaceTimelineEntry};
+
+/// Codex events kept per workspace.
+const SESSION_EVENT_CAPACITY: usize = 200;
+const DEFAULT_TIMELINE_LIMIT: usize = 50;
+const MAX_TIMELINE_LIMIT: usize = 500;
+
+/// Codex events per workspace, owned by the app or daemon state and handed to
+/// each session it spawns. A workspace's history starts over when its session
+/// is respawned and is dropped when the workspace is removed.
+#[derive(Clone, Default)]
+pub(crate) struct SessionEvents {
+    events: Arc<StdMutex<HashMap<String, VecDeque<WorkspaceTimelineEntry>>>>,
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+impl SessionEvents {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, VecDeque<WorkspaceTimelineEntry>>> {
+        self.events.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn record(&self, workspace_id: &str, entry: WorkspaceTimelineEntry) {
+        let mut events = self.lock();
+        let events = events.entry(workspace_id.to_string()).or_default();
+        if events.len() == SESSION_EVENT_CAPACITY {
+            events.pop_front();
+        }
+        events.push_back(entry);
+    }
+
+    fn entries(&self, workspace_id: &str) -> Vec<WorkspaceTimelineEntry> {
+        self.lock()
+            .get(workspace_id)
+            .map(|events| events.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the workspace's history with a single start event.
+    pub(crate) fn record_session_started(&self, workspace_id: &str) {
+        self.lock().remove(workspace_id);
+        self.record(
+            workspace_id,
+            WorkspaceTimelineEntry {
+                kind: "session-started".to_string(),
+                timestamp: now_ms(),
+                summary: "Codex session started".to_string(),
+                thread_id: None,
+                sha: None,
+            },
+        );
+    }
+
+    pub(crate) fn record_session_exited(&self, workspace_id: &str, code: Option<i32>, clean: bool) {
+        let summary = session_exit_summary(code, clean);
+        self.record(
+            workspace_id,
+            WorkspaceTimelineEntry {
+                kind: "session-exited".to_string(),
+                timestamp: now_ms(),
+                summary,
+                thread_id: None,
+                sha: None,
+            },
+        );
+    }
+
+    /// Records `turn/completed` notifications; everything else is ignored.
+    pub(crate) fn record_app_server_message(&self, workspace_id: &str, value: &Value) {
+        if value.get("method").and_then(Value::as_str) != Some("turn/completed") {
+            return;
+        }
+        let params = value.get("params");
+        let thread_id = params
+            .and_then(|params| params.get("threadId").or_else(|| params.get("thread_id")))
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let status = params
+            .and_then(|params| params.get("turn"))
+            .and_then(|turn| turn.get("status"))
+            .and_then(Value::as_str)
+            .unwrap_or("completed");
+        self.record(
+            workspace_id,
+            WorkspaceTimelineEntry {
+                kind: "turn-completed".to_string(),
+                timestamp: now_ms(),
+                summary: format!("Turn {status}"),
+                thread_id,
+                sha: None,
+            },
+        );
+    }
+}
+
+pub(crate) fn session_exit_summary(code: Option<i32>, clean: bool) -> String {
+    match (clean, code) {
+        (true, _) => "Codex session stopped".to_string(),
+        (false, Some(code)) => format!("Codex session exited with code {code}"),
+        (false, None) => "Codex session exited unexpectedly".to_string(),
+    }
+}
+
+fn reflog_timeline_entry(entry: GitReflogEntry) -> WorkspaceTimelineEntry {
+    let kind = if entry.action.starts_with("commit") {
+        "commit"
+    } else if entry.action == "checkout" {
+        "branch-switch"
+    } else {
+        "git"
+    };
+    let summary = if kind == "git" && !entry.action.is_empty() {
+        format!("{}: {}", entry.action, entry.message)
+    } else {
+        entry.message
+    };
+    WorkspaceTimelineEntry {
+        kind: kind.to_string(),
+        timestamp: entry.timestamp * 1000,
+        summary,
+        thread_id: None,
+        sha: Some(entry.sha),
+    }
+}
+
+/// Newest first, at most `limit` entries.
+fn merge_timeline(
+    git_entries: Vec<GitReflogEntry>,
+    session_entries: Vec<WorkspaceTimelineEntry>,
+    limit: usize,
+) -> Vec<WorkspaceTimelineEntry> {
+    let mut timeline: Vec<WorkspaceTimelineEntry> = git_entries
+        .into_iter()
+        .map(reflog_timeline_entry)
+        .chain(session_entries)
+        .collect();
+    // Stable, so same-timestamp entries keep reflog/recording order.
+    timeline.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    timeline.truncate(limit);
+    timeline
+}
+
+/// Commits, branch switches and other HEAD moves from the reflog, merged with
+/// the current session's start, turn completions and exit.
+pub(crate) async fn workspace_timeline_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    session_events: &SessionEvents,
+    workspace_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<WorkspaceTimelineEntry>, String> {
+    if !workspaces.lock().await.contains_key(&workspace_id) {
+        return Err("workspace not found".to_string());
+    }
+    let limit = limit
+        .unwrap_or(DEFAULT_TIMELINE_LIMIT)
+        .clamp(1, MAX_TIMELINE_LIMIT);
+    // Not every workspace is a git repository; those only get codex events.
+    let git_entries =
+        git_ui_core::git_reflog_core(workspaces, workspace_id.clone(), None, Some(limit))
+            .await
+            .unwrap_or_default();
+    let session_entries = session_events.entries(&workspace_id);
+    Ok(merge_timeline(git_entries, session_entries, limit))
+}
+
+/// Drops the history of workspaces that no longer exist, e.g. after a
+/// removal that also took its worktrees with it.
+pub(crate) async fn prune_session_events_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    session_events: &SessionEvents,
+) {
+    let workspaces = workspaces.lock().await;
+    session_events
+        .lock()
+        .retain(|workspace_id, _| workspaces.contains_key(workspace_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn reflog(action: &str, message: &str, timestamp: i64) -> GitReflogEntry {
+        GitReflogEntry {
+            sha: format!("sha-{timestamp}"),
+            selector: String::new(),
+            action: action.to_string(),
+            message: message.to_string(),
+            timestamp,
+            relative_time: String::new(),
+        }
+    }
+
+    #[test]
+    fn merge_timeline_orders_git_and_session_events_newest_first() {
+        let session = vec![WorkspaceTimelineEntry {
+            kind: "turn-completed".to_string(),
+            timestamp: 2_500,
+            summary: "Turn completed".to_string(),
+            thread_id: Some("t1".to_string()),
+            sha: None,
+        }];
+        let git = vec![
+            reflog("checkout", "moving from main to feature", 3),
+            reflog("commit", "add parser", 2),
+            reflog("reset", "moving to HEAD~1", 1),
+        ];
+
+        let timeline = merge_timeline(git, session, 3);
+        let kinds: Vec<&str> = timeline.iter().map(|entry| entry.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["branch-switch", "turn-completed", "commit"]);
+        assert_eq!(timeline[0].timestamp, 3_000);
+        assert_eq!(timeline[2].summary, "add parser");
+        assert_eq!(
+            reflog_timeline_entry(reflog("reset", "moving to HEAD~1", 1)).summary,
+            "reset: moving to HEAD~1"
+        );
+    }
+
+    #[test]
+    fn session_events_are_bounded_and_only_track_turn_completions() {
+        let session_events = SessionEvents::default();
+        let workspace_id = "timeline-test-workspace";
+        session_events
+            .record_app_server_message(workspace_id, &json!({ "method": "turn/started" }));
+        for _ in 0..SESSION_EVENT_CAPACITY + 5 {
+            session_events.record_app_server_message(
+                workspace_id,
+                &json!({
+                    "method": "turn/completed",
+                    "params": { "threadId": "t1", "turn": { "status": "interrupted" } }
+                }),
+            );
+        }
+        let events = session_events.entries(workspace_id);
+        assert_eq!(events.len(), SESSION_EVENT_CAPACITY);
+        assert!(events
+            .iter()
+            .all(|entry| entry.summary == "Turn interrupted"));
+    }
+
+    #[test]
+    fn session_events_start_over_on_respawn_and_go_with_their_workspace() {
+        let session_events = SessionEvents::default();
+        session_events.record_session_started("w1");
+        session_events.record_session_exited("w1", Some(3), false);
+        session_events.record_session_started("w2");
+        session_events.record_session_started("w1");
+        let kinds: Vec<String> = session_events
+            .entries("w1")
+            .into_iter()
+            .map(|entry| entry.kind)
+            .collect();
+        assert_eq!(kinds, vec!["session-started"]);
+
+        let workspaces = Mutex::new(HashMap::new());
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime")
+            .block_on(prune_session_events_core(&workspaces, &session_events));
+        assert!(session_events.entries("w1").is_empty());
+        assert!(session_events.entries("w2").is_empty());
+    }
+}
@@ -0,0 +1,574 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use tokio::sync::Mutex;
+
+use crate::files::detect::{detect_file_type, looks_binary};
+use crate::shared::git_core;
+use crate::types::{
+    AppSettings, WorkspaceEntry, WorkspaceFileDeleteResult, WorkspaceFileRenameResult,
+    WorkspaceFileResponse,
+};
+use crate::utils::normalize_git_path;
+
+pub(crate) async fn canonical_workspace_root(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<PathBuf, String> {
+    let root = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    root.canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))
+}
+
+/// Resolves a path that must already exist for reading, following symlinks,
+/// and refuses anything that ends up outside the workspace.
+pub(crate) fn resolve_readable_path(
+    canonical_root: &Path,
+    relative_path: &str,
+) -> Result<PathBuf, String> {
+    let canonical_path = canonical_root
+        .join(relative_path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to open file: {err}"))?;
+    if !canonical_path.starts_with(canonical_root) || canonical_path == canonical_root {
+        return Err("Invalid file path".to_string());
+    }
+    Ok(canonical_path)
+}
+
+/// Resolves an entry that must already exist without following it: only the
+/// parent is canonicalized, so a symlink resolves to the link itself and can
+/// be renamed or deleted as a link.
+fn resolve_existing_path(canonical_root: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    let candidate = resolve_new_path(canonical_root, relative_path)?;
+    let (Some(parent), Some(name)) = (candidate.parent(), candidate.file_name()) else {
+        return Err("Invalid file path".to_string());
+    };
+    let canonical_parent = parent
+        .canonicalize()
+        .map_err(|err| format!("Failed to open file: {err}"))?;
+    let path = canonical_parent.join(name);
+    std::fs::symlink_metadata(&path).map_err(|err| format!("Failed to open file: {err}"))?;
+    Ok(path)
+}
+
+/// Resolves a path that may not exist yet. The relative path itself may only
+/// contain normal components, and its closest existing ancestor must resolve
+/// inside the workspace so a symlinked directory can't redirect the write.
+fn resolve_new_path(canonical_root: &Path, relative_path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(relative_path);
+    if relative.as_os_str().is_empty()
+        || relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err("Invalid file path".to_string());
+    }
+    let candidate = canonical_root.join(relative);
+    let mut ancestor = candidate.parent();
+    while let Some(dir) = ancestor {
+        if dir.exists() {
+            let canonical_dir = dir
+                .canonicalize()
+                .map_err(|err| format!("Failed to resolve {}: {err}", dir.display()))?;
+            if !canonical_dir.starts_with(canonical_root) {
+                return Err("Invalid file path".to_string());
+            }
+            return Ok(candidate);
+        }
+        ancestor = dir.parent();
+    }
+    Err("Invalid file path".to_string())
+}
+
+pub(crate) fn relative_to_root(canonical_root: &Path, path: &Path) -> String {
+    path.strip_prefix(canonical_root)
+        .map(|relative| normalize_git_path(&relative.to_string_lossy()))
+        .unwrap_or_default()
+}
+
+async fn is_tracked_by_git(canonical_root: &PathBuf, relative_path: &str) -> bool {
+    git_core::run_git_command(
+        canonical_root,
+        &["ls-files", "--error-unmatch", "--", relative_path],
+    )
+    .await
+    .is_ok()
+}
+
+/// Reads a workspace file for display, capped at the `maxWorkspaceFileBytes`
+/// setting.
+pub(crate) async fn read_workspace_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: &str,
+    path: &str,
+) -> Result<WorkspaceFileResponse, String> {
+    let canonical_root = canonical_workspace_root(workspaces, workspace_id).await?;
+    let max_bytes = app_settings.lock().await.max_workspace_file_bytes;
+    read_workspace_file(&canonical_root, path, max_bytes)
+}
+
+/// Reads the raw bytes first, then detects the type from them, and only then
+/// decodes: binary content is refused by type instead of as bad UTF-8, and a
+/// cut that lands inside a multi-byte character drops that partial character.
+fn read_workspace_file(
+    canonical_root: &Path,
+    relative_path: &str,
+    max_bytes: u64,
+) -> Result<WorkspaceFileResponse, String> {
+    let file_path = resolve_readable_path(canonical_root, relative_path)?;
+    if !file_path.is_file() {
+        return Err("Path is not a file".to_string());
+    }
+    let file = File::open(&file_path).map_err(|err| format!("Failed to open file: {err}"))?;
+    let mut buffer = Vec::new();
+    file.take(max_bytes.saturating_add(1))
+        .read_to_end(&mut buffer)
+        .map_err(|err| format!("Failed to read file: {err}"))?;
+    let truncated = buffer.len() as u64 > max_bytes;
+    if truncated {
+        buffer.truncate(max_bytes as usize);
+    }
+
+    let detected = detect_file_type(&file_path, &buffer);
+    if looks_binary(&buffer) {
+        return Err(format!("File is binary ({})", detected.mime_type));
+    }
+    if truncated {
+        if let Err(err) = std::str::from_utf8(&buffer) {
+            if err.error_len().is_none() {
+                buffer.truncate(err.valid_up_to());
+            }
+        }
+    }
+    let content = String::from_utf8(buffer)
+        .map_err(|_| format!("File is not valid UTF-8 ({})", detected.mime_type))?;
+    Ok(WorkspaceFileResponse {
+        content,
+        truncated,
+        mime_type: detected.mime_type.to_string(),
+        language: detected.language.map(str::to_string),
+    })
+}
+
+/// Renames a file inside the workspace. Tracked files go through `git mv` so
+/// the rename shows up staged; everything else is a plain filesystem rename.
+pub(crate) async fn rename_workspace_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    source: &str,
+    destination: &str,
+    force: bool,
+) -> Result<WorkspaceFileRenameResult, String> {
+    let canonical_root = canonical_workspace_root(workspaces, workspace_id).await?;
+    let source_path = resolve_existing_path(&canonical_root, source)?;
+    let source_metadata = std::fs::symlink_metadata(&source_path)
+        .map_err(|err| format!("Failed to open file: {err}"))?;
+    if source_metadata.is_dir() {
+        return Err("Path is not a file".to_string());
+    }
+    let destination_path = resolve_new_path(&canonical_root, destination)?;
+    if destination_path == source_path {
+        return Err("Source and destination are the same file".to_string());
+    }
+    if let Ok(metadata) = std::fs::symlink_metadata(&destination_path) {
+        if metadata.is_dir() {
+            return Err("Destination is a directory".to_string());
+        }
+        if !force {
+            return Err("Destination already exists".to_string());
+        }
+    }
+    if let Some(parent) = destination_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+
+    let source_relative = relative_to_root(&canonical_root, &source_path);
+    let destination_relative = relative_to_root(&canonical_root, &destination_path);
+    let staged = is_tracked_by_git(&canonical_root, &source_relative).await;
+    if staged {
+        let mut args = vec!["mv"];
+        if force {
+            args.push("-f");
+        }
+        args.extend(["--", &source_relative, &destination_relative]);
+        git_core::run_git_command(&canonical_root, &args).await?;
+    } else {
+        std::fs::rename(&source_path, &destination_path)
+            .map_err(|err| format!("Failed to rename file: {err}"))?;
+    }
+    Ok(WorkspaceFileRenameResult {
+        path: destination_relative,
+        staged,
+    })
+}
+
+/// Creates a new file, along with any missing parent directories. Never
+/// overwrites: an existing file at the path is an error. Returns the created
+/// path relative to the workspace root.
+pub(crate) async fn create_workspace_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    path: &str,
+    content: &str,
+) -> Result<String, String> {
+    let canonical_root = canonical_workspace_root(workspaces, workspace_id).await?;
+    let file_path = resolve_new_path(&canonical_root, path)?;
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+    }
+    // `create_new` makes the existence check and the create a single step.
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file_path)
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::AlreadyExists => "File already exists".to_string(),
+            _ => format!("Failed to create file: {err}"),
+        })?;
+    file.write_all(content.as_bytes())
+        .map_err(|err| format!("Failed to write file: {err}"))?;
+    Ok(relative_to_root(&canonical_root, &file_path))
+}
+
+/// Deletes a file, or a directory when `recursive` is set. The path itself is
+/// not followed: a symlink is removed as a link, and only its parent has to
+/// resolve inside the workspace. Tracked content goes through `git rm`, so
+/// the deletion is staged and recoverable; git refuses (and we report) when
+/// that would drop uncommitted changes.
+pub(crate) async fn delete_workspace_file_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    path: &str,
+    recursive: bool,
+) -> Result<WorkspaceFileDeleteResult, String> {
+    let canonical_root = canonical_workspace_root(workspaces, workspace_id).await?;
+    let target = resolve_existing_path(&canonical_root, path)?;
+    let relative = relative_to_root(&canonical_root, &target);
+    if relative == ".git" || relative.starts_with(".git/") {
+        return Err("Refusing to delete git metadata".to_string());
+    }
+    let metadata =
+        std::fs::symlink_metadata(&target).map_err(|err| format!("Failed to open file: {err}"))?;
+    let is_dir = metadata.is_dir();
+    if is_dir && !recursive {
+        return Err("Path is a directory; pass recursive to delete it".to_string());
+    }
+
+    let staged = is_tracked_by_git(&canonical_root, &relative).await;
+    if staged {
+        let mut args = vec!["rm", "--quiet"];
+        if is_dir {
+            args.push("-r");
+        }
+        args.extend(["--", &relative]);
+        git_core::run_git_command(&canonical_root, &args).await?;
+    }
+    // `git rm -r` leaves untracked files behind in the directory.
+    if std::fs::symlink_metadata(&target).is_ok() {
+        let removed = if is_dir {
+            std::fs::remove_dir_all(&target)
+        } else {
+            std::fs::remove_file(&target)
+        };
+        removed.map_err(|err| format!("Failed to delete {relative}: {err}"))?;
+    }
+    Ok(WorkspaceFileDeleteResult { staged })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::test_support::{run, workspaces_for};
+    use git2::Repository;
+    use uuid::Uuid;
+
+    #[test]
+    fn rename_workspace_file_stages_tracked_files_and_guards_paths() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-rename-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        let repo = Repository::init(&root).expect("init repo");
+        std::fs::write(root.join("tracked.txt"), "tracked").expect("write tracked");
+        std::fs::write(root.join("untracked.txt"), "untracked").expect("write untracked");
+        std::fs::write(root.join("existing.txt"), "existing").expect("write existing");
+        let mut index = repo.index().expect("index");
+        index
+            .add_path(Path::new("tracked.txt"))
+            .expect("add tracked");
+        index.write().expect("write index");
+        let workspaces = workspaces_for(&root);
+
+        let renamed = run(rename_workspace_file_core(
+            &workspaces,
+            "w1",
+            "tracked.txt",
+            "src/moved.txt",
+            false,
+        ))
+        .expect("rename tracked");
+        assert_eq!(renamed.path, "src/moved.txt");
+        assert!(renamed.staged);
+        let mut index = repo.index().expect("index");
+        index.read(true).expect("reload index");
+        assert!(index.get_path(Path::new("src/moved.txt"), 0).is_some());
+        assert!(index.get_path(Path::new("tracked.txt"), 0).is_none());
+
+        let error = run(rename_workspace_file_core(
+            &workspaces,
+            "w1",
+            "untracked.txt",
+            "existing.txt",
+            false,
+        ))
+        .expect_err("refuse overwrite");
+        assert_eq!(error, "Destination already exists");
+
+        let forced = run(rename_workspace_file_core(
+            &workspaces,
+            "w1",
+            "untracked.txt",
+            "existing.txt",
+            true,
+        ))
+        .expect("forced rename");
+        assert!(!forced.staged);
+        assert_eq!(
+            std::fs::read_to_string(root.join("existing.txt")).expect("read"),
+            "untracked"
+        );
+
+        for destination in ["../escaped.txt", "/tmp/escaped.txt"] {
+            let error = run(rename_workspace_file_core(
+                &workspaces,
+                "w1",
+                "existing.txt",
+                destination,
+                false,
+            ))
+            .expect_err("escape rejected");
+            assert_eq!(error, "Invalid file path");
+        }
+        let error = run(rename_workspace_file_core(
+            &workspaces,
+            "w1",
+            "../outside.txt",
+            "inside.txt",
+            false,
+        ))
+        .expect_err("source escape rejected");
+        assert!(error.starts_with("Failed to open file") || error == "Invalid file path");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn create_workspace_file_creates_parents_and_never_overwrites() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-create-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        let workspaces = workspaces_for(&root);
+
+        let created = run(create_workspace_file_core(
+            &workspaces,
+            "w1",
+            "src/nested/new.rs",
+            "fn main() {}\n",
+        ))
+        .expect("create file");
+        assert_eq!(created, "src/nested/new.rs");
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/nested/new.rs")).expect("read"),
+            "fn main() {}\n"
+        );
+
+        let error = run(create_workspace_file_core(
+            &workspaces,
+            "w1",
+            "src/nested/new.rs",
+            "replaced",
+        ))
+        .expect_err("refuse overwrite");
+        assert_eq!(error, "File already exists");
+        assert_eq!(
+            std::fs::read_to_string(root.join("src/nested/new.rs")).expect("read"),
+            "fn main() {}\n"
+        );
+
+        let error = run(create_workspace_file_core(
+            &workspaces,
+            "w1",
+            "src/../../escaped.txt",
+            "",
+        ))
+        .expect_err("escape rejected");
+        assert_eq!(error, "Invalid file path");
+        assert!(!root.join("../escaped.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn delete_workspace_file_stages_tracked_files_and_requires_recursive_for_dirs() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-delete-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("dir")).expect("create root");
+        let repo = Repository::init(&root).expect("init repo");
+        std::fs::write(root.join("tracked.txt"), "tracked").expect("write tracked");
+        std::fs::write(root.join("dir/tracked.txt"), "tracked").expect("write nested");
+        std::fs::write(root.join("dir/untracked.txt"), "untracked").expect("write untracked");
+        let mut index = repo.index().expect("index");
+        index
+            .add_path(Path::new("tracked.txt"))
+            .expect("add tracked");
+        index
+            .add_path(Path::new("dir/tracked.txt"))
+            .expect("add nested");
+        index.write().expect("write index");
+        let tree = repo
+            .find_tree(index.write_tree().expect("write tree"))
+            .expect("find tree");
+        let signature = git2::Signature::now("Test", "test@example.com").expect("signature");
+        repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+            .expect("commit");
+        let workspaces = workspaces_for(&root);
+
+        let deleted = run(delete_workspace_file_core(
+            &workspaces,
+            "w1",
+            "tracked.txt",
+            false,
+        ))
+        .expect("delete tracked");
+        assert!(deleted.staged);
+        assert!(!root.join("tracked.txt").exists());
+
+        let error = run(delete_workspace_file_core(&workspaces, "w1", "dir", false))
+            .expect_err("directory needs recursive");
+        assert_eq!(error, "Path is a directory; pass recursive to delete it");
+        let deleted = run(delete_workspace_file_core(&workspaces, "w1", "dir", true))
+            .expect("delete directory");
+        assert!(deleted.staged);
+        assert!(!root.join("dir").exists());
+        let mut index = repo.index().expect("index");
+        index.read(true).expect("reload index");
+        assert!(index.get_path(Path::new("dir/tracked.txt"), 0).is_none());
+
+        for path in ["../outside.txt", ".git", ".git/HEAD"] {
+            assert!(run(delete_workspace_file_core(&workspaces, "w1", path, true)).is_err());
+        }
+        assert!(root.join(".git/HEAD").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn delete_workspace_file_does_not_follow_symlinks_out_of_the_workspace() {
+        let base = std::env::temp_dir().join(format!("codex-monitor-delete-{}", Uuid::new_v4()));
+        let root = base.join("workspace");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        std::fs::write(outside.join("keep.txt"), "keep").expect("write outside");
+        std::os::unix::fs::symlink(&outside, root.join("link")).expect("symlink");
+        let workspaces = workspaces_for(&root);
+
+        let error = run(delete_workspace_file_core(
+            &workspaces,
+            "w1",
+            "link/keep.txt",
+            false,
+        ))
+        .expect_err("escape through symlink rejected");
+        assert_eq!(error, "Invalid file path");
+
+        let deleted =
+            run(delete_workspace_file_core(&workspaces, "w1", "link", false)).expect("delete link");
+        assert!(!deleted.staged);
+        assert!(std::fs::symlink_metadata(root.join("link")).is_err());
+        assert!(outside.join("keep.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn read_workspace_file_detects_before_decoding_and_honors_the_limit() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-read-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("notes.md"), "# caf\u{e9}\n").expect("write notes");
+        std::fs::write(root.join("logo.png"), b"\x89PNG\r\n\x1a\n\0\0").expect("write png");
+        let canonical_root = root.canonicalize().expect("canonical root");
+
+        let full = read_workspace_file(&canonical_root, "notes.md", 1024).expect("read notes");
+        assert_eq!(full.content, "# caf\u{e9}\n");
+        assert!(!full.truncated);
+        assert_eq!(full.mime_type, "text/markdown");
+        assert_eq!(full.language.as_deref(), Some("markdown"));
+
+        // The cap lands between the two bytes of `é`.
+        let cut = read_workspace_file(&canonical_root, "notes.md", 6).expect("read cut");
+        assert_eq!(cut.content, "# caf");
+        assert!(cut.truncated);
+
+        let error = read_workspace_file(&canonical_root, "logo.png", 1024).expect_err("binary");
+        assert_eq!(error, "File is binary (image/png)");
+
+        let workspaces = workspaces_for(&root);
+        let settings = Mutex::new(AppSettings {
+            max_workspace_file_bytes: 3,
+            ..AppSettings::default()
+        });
+        let limited = run(read_workspace_file_core(
+            &workspaces,
+            &settings,
+            "w1",
+            "notes.md",
+        ))
+        .expect("read with setting");
+        assert_eq!(limited.content, "# c");
+        assert!(limited.truncated);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rename_workspace_file_moves_symlinks_as_links() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-rename-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("target.txt"), "target").expect("write target");
+        std::os::unix::fs::symlink(root.join("target.txt"), root.join("link.txt"))
+            .expect("symlink");
+        let workspaces = workspaces_for(&root);
+
+        let renamed = run(rename_workspace_file_core(
+            &workspaces,
+            "w1",
+            "link.txt",
+            "renamed-link.txt",
+            false,
+        ))
+        .expect("rename link");
+        assert_eq!(renamed.path, "renamed-link.txt");
+        assert!(std::fs::symlink_metadata(root.join("link.txt")).is_err());
+        assert!(std::fs::symlink_metadata(root.join("renamed-link.txt"))
+            .expect("link metadata")
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(root.join("target.txt")).expect("read target"),
+            "target"
+        );
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
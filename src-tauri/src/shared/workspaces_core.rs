@@ -1,53 +1,85 @@
 use std::collections::HashMap;
 use std::future::Future;
-#[cfg(target_os = "windows")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
 use tokio::io::AsyncWriteExt;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Semaphore};
 
 use crate::backend::app_server::WorkspaceSession;
 use crate::codex::args::resolve_workspace_codex_args;
-use crate::codex::home::resolve_workspace_codex_home;
+use crate::codex::config as codex_config;
+use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::git_utils::resolve_git_root;
+use crate::shared::process_core::tokio_command;
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
-use crate::shared::process_core::{kill_child_process_tree, tokio_command};
-use crate::shared::{git_core, worktree_core};
-use crate::storage::write_workspaces;
+use crate::shared::{codex_core, git_core, worktree_core};
+use crate::storage::update_workspaces;
 use crate::types::{
-    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
-    WorktreeSetupStatus,
+    AppSettings, CodexToolState, ForkThreadToWorktreeResult, WorkspaceConnectResult,
+    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeFromStashResult,
+    WorktreeInfo, WorktreePathPreview, WorktreeSetupStatus,
 };
 use uuid::Uuid;
 
 pub(crate) const WORKTREE_SETUP_MARKERS_DIR: &str = "worktree-setup";
 pub(crate) const WORKTREE_SETUP_MARKER_EXT: &str = "ran";
 const AGENTS_MD_FILE_NAME: &str = "AGENTS.md";
-
-fn copy_agents_md_from_parent_to_worktree(
-    parent_repo_root: &PathBuf,
-    worktree_root: &PathBuf,
+/// Sessions `connect_all_workspaces` spawns at once; each one is a codex process.
+const CONNECT_ALL_CONCURRENCY: usize = 4;
+
+/// Copies a repo-relative file from the parent checkout into a new worktree,
+/// leaving any copy the worktree already has (e.g. a tracked file) alone.
+fn copy_file_from_parent_to_worktree(
+    parent_repo_root: &Path,
+    worktree_root: &Path,
+    relative_path: &str,
 ) -> Result<(), String> {
-    let source_path = parent_repo_root.join(AGENTS_MD_FILE_NAME);
+    let relative = Path::new(relative_path);
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "Refusing to copy {relative_path}: path must be relative to the repository root."
+        ));
+    }
+
+    let source_path = parent_repo_root.join(relative);
     if !source_path.is_file() {
         return Ok(());
     }
 
-    let destination_path = worktree_root.join(AGENTS_MD_FILE_NAME);
+    let destination_path = worktree_root.join(relative);
     if destination_path.is_file() {
         return Ok(());
     }
+    if let Some(destination_dir) = destination_path.parent() {
+        std::fs::create_dir_all(destination_dir).map_err(|err| {
+            format!(
+                "Failed to create {} for {relative_path}: {err}",
+                destination_dir.display()
+            )
+        })?;
+    }
 
-    let temp_path = worktree_root.join(format!("{AGENTS_MD_FILE_NAME}.tmp"));
+    let temp_path = destination_path.with_file_name(format!(
+        "{}.tmp",
+        relative
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default()
+    ));
 
     std::fs::copy(&source_path, &temp_path).map_err(|err| {
         format!(
             "Failed to copy {} from {} to {}: {err}",
-            AGENTS_MD_FILE_NAME,
+            relative_path,
             source_path.display(),
             temp_path.display()
         )
@@ -57,7 +89,7 @@ fn copy_agents_md_from_parent_to_worktree(
         let _ = std::fs::remove_file(&temp_path);
         format!(
             "Failed to finalize {} copy to {}: {err}",
-            AGENTS_MD_FILE_NAME,
+            relative_path,
             destination_path.display()
         )
     })?;
@@ -65,6 +97,22 @@ fn copy_agents_md_from_parent_to_worktree(
     Ok(())
 }
 
+/// Files copied into a new worktree: AGENTS.md when requested (falling back
+/// to the `defaultCopyAgentsMd` setting), then `worktreeCopyFiles`.
+fn worktree_copy_files(copy_agents_md: Option<bool>, settings: &AppSettings) -> Vec<String> {
+    let mut files = Vec::new();
+    if copy_agents_md.unwrap_or(settings.default_copy_agents_md) {
+        files.push(AGENTS_MD_FILE_NAME.to_string());
+    }
+    for file in &settings.worktree_copy_files {
+        let file = file.trim();
+        if !file.is_empty() && !files.iter().any(|existing| existing == file) {
+            files.push(file.to_string());
+        }
+    }
+    files
+}
+
 pub(crate) fn normalize_setup_script(script: Option<String>) -> Option<String> {
     match script {
         Some(value) if value.trim().is_empty() => None,
@@ -100,6 +148,8 @@ pub(crate) async fn list_workspaces_core(
             kind: entry.kind.clone(),
             parent_id: entry.parent_id.clone(),
             worktree: entry.worktree.clone(),
+            pinned: entry.pinned,
+            auto_connect: entry.auto_connect,
             settings: entry.settings.clone(),
         });
     }
@@ -107,6 +157,24 @@ pub(crate) async fn list_workspaces_core(
     result
 }
 
+/// Hash of a `list_workspaces` response. It covers everything serialized, so
+/// any change a client could see (names, settings, connection state, order)
+/// gives a new etag.
+pub(crate) fn workspaces_etag(workspaces: &[WorkspaceInfo]) -> String {
+    let listing = serde_json::to_vec(workspaces).unwrap_or_default();
+    Sha256::digest(&listing)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub(crate) async fn workspaces_etag_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+) -> String {
+    workspaces_etag(&list_workspaces_core(workspaces, sessions).await)
+}
+
 async fn resolve_entry_and_parent(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
@@ -219,6 +287,8 @@ where
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
+        pinned: false,
+        auto_connect: true,
         settings: WorkspaceSettings::default(),
     };
 
@@ -235,15 +305,17 @@ where
     if let Err(error) = {
         let mut workspaces = workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)
+        persist_workspaces(
+            &mut workspaces,
+            storage_path,
+            std::slice::from_ref(&entry.id),
+        )
     } {
         {
             let mut workspaces = workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.terminate().await;
         return Err(error);
     }
 
@@ -258,6 +330,8 @@ where
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        pinned: entry.pinned,
+        auto_connect: entry.auto_connect,
         settings: entry.settings,
     })
 }
@@ -341,6 +415,8 @@ where
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
+        pinned: false,
+        auto_connect: true,
         settings: WorkspaceSettings {
             group_id: inherited_group_id,
             ..WorkspaceSettings::default()
@@ -366,15 +442,17 @@ where
     if let Err(error) = {
         let mut workspaces = workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)
+        persist_workspaces(
+            &mut workspaces,
+            storage_path,
+            std::slice::from_ref(&entry.id),
+        )
     } {
         {
             let mut workspaces = workspaces.lock().await;
             workspaces.remove(&entry.id);
         }
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.terminate().await;
         let _ = tokio::fs::remove_dir_all(&destination_path).await;
         return Err(error);
     }
@@ -390,6 +468,8 @@ where
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        pinned: entry.pinned,
+        auto_connect: entry.auto_connect,
         settings: entry.settings,
     })
 }
@@ -661,6 +741,68 @@ pub(crate) async fn open_workspace_in_core(
     }
 }
 
+pub(crate) async fn reveal_workspace_path_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<(), String> {
+    let path = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    if !path.exists() {
+        return Err(format!(
+            "Workspace path no longer exists: {}",
+            path.display()
+        ));
+    }
+
+    let output = reveal_path_command(&path)
+        .output()
+        .await
+        .map_err(|error| format!("Failed to reveal workspace: {error}"))?;
+    // explorer.exe exits non-zero even when the window opens, so only trust
+    // the status elsewhere.
+    if cfg!(target_os = "windows") || output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr.trim();
+    if detail.is_empty() {
+        Err("Failed to reveal workspace.".to_string())
+    } else {
+        Err(format!("Failed to reveal workspace: {detail}"))
+    }
+}
+
+/// Builds the platform's "show in file manager" command for `path`.
+fn reveal_path_command(path: &Path) -> tokio::process::Command {
+    #[cfg(target_os = "macos")]
+    let cmd = {
+        let mut cmd = tokio_command("open");
+        cmd.arg("-R").arg(path);
+        cmd
+    };
+
+    #[cfg(target_os = "windows")]
+    let cmd = {
+        let mut cmd = tokio_command("explorer");
+        cmd.raw_arg(format!("/select,\"{}\"", path.display()));
+        cmd
+    };
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let cmd = {
+        let mut cmd = tokio_command("xdg-open");
+        cmd.arg(path);
+        cmd
+    };
+
+    cmd
+}
+
 #[cfg(target_os = "macos")]
 pub(crate) async fn get_open_app_icon_core<F>(
     app_name: String,
@@ -714,6 +856,39 @@ where
     }
 }
 
+/// Resolves where `add_worktree` would place a worktree for `branch` without
+/// creating anything. The path can change if another worktree claims it first.
+pub(crate) async fn preview_worktree_path_core(
+    parent_id: &str,
+    branch: &str,
+    data_dir: &Path,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+) -> Result<WorktreePathPreview, String> {
+    let branch = branch.trim();
+    if branch.is_empty() {
+        return Err("Branch name is required.".to_string());
+    }
+    worktree_core::validate_branch_name(branch)?;
+    let parent_entry = {
+        let workspaces = workspaces.lock().await;
+        workspaces
+            .get(parent_id)
+            .cloned()
+            .ok_or_else(|| "parent workspace not found".to_string())?
+    };
+    if parent_entry.kind.is_worktree() {
+        return Err("Cannot create a worktree from another worktree.".to_string());
+    }
+
+    let worktree_root = data_dir.join("worktrees").join(&parent_entry.id);
+    let sanitized_name = worktree_core::sanitize_worktree_name(branch);
+    let path = worktree_core::unique_worktree_path_strict(&worktree_root, &sanitized_name)?;
+    Ok(WorktreePathPreview {
+        sanitized_name,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
 pub(crate) async fn add_worktree_core<
     FSpawn,
     FutSpawn,
@@ -729,7 +904,8 @@ pub(crate) async fn add_worktree_core<
     parent_id: String,
     branch: String,
     name: Option<String>,
-    copy_agents_md: bool,
+    base_ref: Option<String>,
+    copy_agents_md: Option<bool>,
     data_dir: &PathBuf,
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
@@ -758,9 +934,19 @@ where
     if branch.is_empty() {
         return Err("Branch name is required.".to_string());
     }
+    worktree_core::validate_branch_name(&branch)?;
     let name = name
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty());
+    let base_ref = base_ref
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if base_ref
+        .as_deref()
+        .is_some_and(|value| value.starts_with('-'))
+    {
+        return Err("Base ref cannot start with '-'.".to_string());
+    }
 
     let parent_entry = {
         let workspaces = workspaces.lock().await;
@@ -774,6 +960,23 @@ where
         return Err("Cannot create a worktree from another worktree.".to_string());
     }
 
+    let repo_path = PathBuf::from(&parent_entry.path);
+    let branch_exists = git_branch_exists(&repo_path, &branch).await?;
+    if let Some(base_ref) = base_ref.as_deref() {
+        if branch_exists {
+            return Err(format!(
+                "Branch {branch} already exists; a base ref only applies to new branches."
+            ));
+        }
+        let base_commit = format!("{base_ref}^{{commit}}");
+        run_git_command(
+            &repo_path,
+            &["rev-parse", "--verify", "--quiet", &base_commit],
+        )
+        .await
+        .map_err(|_| format!("Base ref {base_ref} does not resolve to a commit."))?;
+    }
+
     let worktree_root = data_dir.join("worktrees").join(&parent_entry.id);
     std::fs::create_dir_all(&worktree_root)
         .map_err(|err| format!("Failed to create worktree directory: {err}"))?;
@@ -782,14 +985,25 @@ where
     let worktree_path = unique_worktree_path(&worktree_root, &safe_name)?;
     let worktree_path_string = worktree_path.to_string_lossy().to_string();
 
-    let repo_path = PathBuf::from(&parent_entry.path);
-    let branch_exists = git_branch_exists(&repo_path, &branch).await?;
     if branch_exists {
         run_git_command(
             &repo_path,
             &["worktree", "add", &worktree_path_string, &branch],
         )
         .await?;
+    } else if let Some(base_ref) = base_ref.as_deref() {
+        run_git_command(
+            &repo_path,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                &branch,
+                &worktree_path_string,
+                base_ref,
+            ],
+        )
+        .await?;
     } else if let Some(find_remote_tracking) = git_find_remote_tracking_branch {
         if let Some(remote_ref) = find_remote_tracking(&repo_path, &branch).await? {
             run_git_command(
@@ -819,11 +1033,12 @@ where
         .await?;
     }
 
-    if copy_agents_md {
-        if let Err(error) = copy_agents_md_from_parent_to_worktree(&repo_path, &worktree_path) {
+    let copy_files = worktree_copy_files(copy_agents_md, &*app_settings.lock().await);
+    for file in copy_files {
+        if let Err(error) = copy_file_from_parent_to_worktree(&repo_path, &worktree_path, &file) {
             eprintln!(
                 "add_worktree: optional {} copy failed for {}: {}",
-                AGENTS_MD_FILE_NAME,
+                file,
                 worktree_path.display(),
                 error
             );
@@ -838,6 +1053,8 @@ where
         kind: WorkspaceKind::Worktree,
         parent_id: Some(parent_entry.id.clone()),
         worktree: Some(WorktreeInfo { branch }),
+        pinned: false,
+        auto_connect: true,
         settings: WorkspaceSettings {
             worktree_setup_script: normalize_setup_script(
                 parent_entry.settings.worktree_setup_script.clone(),
@@ -859,8 +1076,11 @@ where
     {
         let mut workspaces = workspaces.lock().await;
         workspaces.insert(entry.id.clone(), entry.clone());
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
+        persist_workspaces(
+            &mut workspaces,
+            storage_path,
+            std::slice::from_ref(&entry.id),
+        )?;
     }
 
     sessions.lock().await.insert(entry.id.clone(), session);
@@ -874,10 +1094,189 @@ where
         kind: entry.kind,
         parent_id: entry.parent_id,
         worktree: entry.worktree,
+        pinned: entry.pinned,
+        auto_connect: entry.auto_connect,
         settings: entry.settings,
     })
 }
 
+fn check_app_server_response(response: &Value, action: &str) -> Result<(), String> {
+    match response.get("error") {
+        Some(error) => Err(format!(
+            "Failed to {action}: {}",
+            error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+        )),
+        None => Ok(()),
+    }
+}
+
+fn app_server_thread_id(response: &Value, action: &str) -> Result<String, String> {
+    check_app_server_response(response, action)?;
+    let payload = response.get("result").unwrap_or(response);
+    payload
+        .get("thread")
+        .and_then(|thread| thread.get("id"))
+        .or_else(|| payload.get("threadId"))
+        .and_then(Value::as_str)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| format!("Failed to {action}: response did not include a thread id"))
+}
+
+/// Forks `thread_id` from the parent workspace and continues the fork in a
+/// freshly created worktree. The fork is archived again if the worktree
+/// cannot be created or the new session cannot resume it.
+pub(crate) async fn fork_thread_to_worktree_core<F, Fut>(
+    parent_id: String,
+    thread_id: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    add_worktree: F,
+) -> Result<ForkThreadToWorktreeResult, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<WorkspaceInfo, String>>,
+{
+    let thread_id = thread_id.trim().to_string();
+    if thread_id.is_empty() {
+        return Err("threadId is required".to_string());
+    }
+    let parent_entry = workspaces
+        .lock()
+        .await
+        .get(&parent_id)
+        .cloned()
+        .ok_or_else(|| "parent workspace not found".to_string())?;
+    if parent_entry.kind.is_worktree() {
+        return Err("Cannot create a worktree from another worktree.".to_string());
+    }
+    let parent_session = sessions
+        .lock()
+        .await
+        .get(&parent_id)
+        .cloned()
+        .ok_or_else(|| "workspace not connected".to_string())?;
+
+    let fork_response = parent_session
+        .send_request("thread/fork", json!({ "threadId": thread_id }))
+        .await?;
+    let forked_thread_id = app_server_thread_id(&fork_response, "fork thread")?;
+
+    let archive_fork = || async {
+        let _ = parent_session
+            .send_request("thread/archive", json!({ "threadId": forked_thread_id }))
+            .await;
+    };
+
+    let workspace = match add_worktree().await {
+        Ok(workspace) => workspace,
+        Err(err) => {
+            archive_fork().await;
+            return Err(err);
+        }
+    };
+
+    let worktree_session = sessions.lock().await.get(&workspace.id).cloned();
+    let resumed = match worktree_session {
+        Some(session) => session
+            .send_request(
+                "thread/resume",
+                json!({ "threadId": forked_thread_id, "cwd": workspace.path }),
+            )
+            .await
+            .and_then(|response| check_app_server_response(&response, "resume forked thread")),
+        None => Err("worktree session not connected".to_string()),
+    };
+    if let Err(err) = resumed {
+        archive_fork().await;
+        return Err(format!(
+            "Worktree {} was created, but the forked thread could not be moved into it: {err}",
+            workspace.name
+        ));
+    }
+
+    Ok(ForkThreadToWorktreeResult {
+        workspace,
+        thread_id: forked_thread_id,
+    })
+}
+
+/// Creates a worktree at the commit a stash was taken from and applies the
+/// stash there. The stash is dropped only after a clean apply; on conflicts it
+/// is kept and the conflicted paths are reported with the new worktree.
+/// `stash` is an index, a `stash@{n}` ref or the stash's message.
+pub(crate) async fn worktree_from_stash_core<F, Fut>(
+    parent_id: String,
+    stash: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    add_worktree: F,
+) -> Result<WorktreeFromStashResult, String>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<WorkspaceInfo, String>>,
+{
+    let parent_entry = workspaces
+        .lock()
+        .await
+        .get(&parent_id)
+        .cloned()
+        .ok_or_else(|| "parent workspace not found".to_string())?;
+    let repo_root = resolve_git_root(&parent_entry)?;
+    let (stash_ref, stash_sha) = git_core::resolve_stash(&repo_root, &stash).await?;
+    let base_sha =
+        git_core::run_git_command(&repo_root, &["rev-parse", &format!("{stash_sha}^1")]).await?;
+
+    let workspace = add_worktree(base_sha).await?;
+    let worktree_root = PathBuf::from(&workspace.path);
+
+    if let Err(error) =
+        git_core::run_git_command(&worktree_root, &["stash", "apply", &stash_sha]).await
+    {
+        let conflicts =
+            git_core::run_git_command(&worktree_root, &["diff", "--name-only", "--diff-filter=U"])
+                .await
+                .map(|output| {
+                    output
+                        .lines()
+                        .map(str::trim)
+                        .filter(|line| !line.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+        return Ok(WorktreeFromStashResult {
+            workspace,
+            stash_ref,
+            applied: false,
+            conflicts,
+            stash_dropped: false,
+            error: Some(error),
+        });
+    }
+
+    // Only drop the entry we applied; the index may point elsewhere if the
+    // stash list changed while the worktree was being created.
+    let still_same = git_core::run_git_command(&repo_root, &["rev-parse", &stash_ref])
+        .await
+        .is_ok_and(|current| current == stash_sha);
+    let stash_dropped = still_same
+        && git_core::run_git_command(&repo_root, &["stash", "drop", &stash_ref])
+            .await
+            .is_ok();
+
+    Ok(WorktreeFromStashResult {
+        workspace,
+        stash_ref,
+        applied: true,
+        conflicts: Vec::new(),
+        stash_dropped,
+        error: None,
+    })
+}
+
 pub(crate) async fn connect_workspace_core<F, Fut>(
     workspace_id: String,
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
@@ -903,10 +1302,63 @@ where
     Ok(())
 }
 
+/// Connects every workspace without a session, a few at a time, leaving out
+/// those with `auto_connect` off. Failures are reported per workspace instead
+/// of aborting the rest.
+pub(crate) async fn connect_all_workspaces_core<F, Fut>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    spawn_session: F,
+) -> HashMap<String, WorkspaceConnectResult>
+where
+    F: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let workspace_ids: Vec<String> = workspaces
+        .lock()
+        .await
+        .values()
+        .filter(|entry| entry.auto_connect)
+        .map(|entry| entry.id.clone())
+        .collect();
+    let limiter = Semaphore::new(CONNECT_ALL_CONCURRENCY);
+    let spawn_session = &spawn_session;
+    let limiter = &limiter;
+    let results = join_all(workspace_ids.into_iter().map(|id| async move {
+        if sessions.lock().await.contains_key(&id) {
+            let result = WorkspaceConnectResult {
+                ok: true,
+                already_connected: true,
+                error: None,
+            };
+            return (id, result);
+        }
+        let _permit = limiter.acquire().await;
+        let connected = connect_workspace_core(
+            id.clone(),
+            workspaces,
+            sessions,
+            app_settings,
+            |entry, default_bin, codex_args, codex_home| {
+                spawn_session(entry, default_bin, codex_args, codex_home)
+            },
+        )
+        .await;
+        let result = WorkspaceConnectResult {
+            ok: connected.is_ok(),
+            already_connected: false,
+            error: connected.err(),
+        };
+        (id, result)
+    }))
+    .await;
+    results.into_iter().collect()
+}
+
 async fn kill_session_by_id(sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>, id: &str) {
     if let Some(session) = sessions.lock().await.remove(id) {
-        let mut child = session.child.lock().await;
-        kill_child_process_tree(&mut child).await;
+        session.terminate().await;
     }
 }
 
@@ -988,11 +1440,10 @@ where
 
     {
         let mut workspaces = workspaces.lock().await;
-        for workspace_id in ids_to_remove {
-            workspaces.remove(&workspace_id);
+        for workspace_id in &ids_to_remove {
+            workspaces.remove(workspace_id);
         }
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
+        persist_workspaces(&mut workspaces, storage_path, &ids_to_remove)?;
     }
 
     if failures.is_empty() {
@@ -1071,13 +1522,50 @@ where
     {
         let mut workspaces = workspaces.lock().await;
         workspaces.remove(&entry.id);
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        write_workspaces(storage_path, &list)?;
+        persist_workspaces(
+            &mut workspaces,
+            storage_path,
+            std::slice::from_ref(&entry.id),
+        )?;
     }
 
     Ok(())
 }
 
+async fn respawn_connected_worktree_session<FSpawn, FutSpawn>(
+    entry: &WorkspaceEntry,
+    parent: &WorkspaceEntry,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    operation: &str,
+    spawn_session: &FSpawn,
+) where
+    FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
+    FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let was_connected = sessions.lock().await.contains_key(&entry.id);
+    if !was_connected {
+        return;
+    }
+    kill_session_by_id(sessions, &entry.id).await;
+    let (default_bin, codex_args) = {
+        let settings = app_settings.lock().await;
+        (
+            settings.codex_bin.clone(),
+            resolve_workspace_codex_args(entry, Some(parent), Some(&settings)),
+        )
+    };
+    let codex_home = resolve_workspace_codex_home(entry, Some(parent));
+    match spawn_session(entry.clone(), default_bin, codex_args, codex_home).await {
+        Ok(session) => {
+            sessions.lock().await.insert(entry.id.clone(), session);
+        }
+        Err(error) => {
+            eprintln!("{operation}: respawn failed for {}: {error}", entry.id);
+        }
+    }
+}
+
 pub(crate) async fn rename_worktree_core<
     FSpawn,
     FutSpawn,
@@ -1177,7 +1665,7 @@ where
         }
     }
 
-    let (entry_snapshot, list) = {
+    let entry_snapshot = {
         let mut workspaces = workspaces.lock().await;
         let entry = match workspaces.get_mut(&id) {
             Some(entry) => entry,
@@ -1198,37 +1686,133 @@ where
             }
         }
         let snapshot = entry.clone();
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        (snapshot, list)
+        persist_workspaces(&mut workspaces, storage_path, std::slice::from_ref(&id))?;
+        snapshot
     };
-    write_workspaces(storage_path, &list)?;
 
-    let was_connected = sessions.lock().await.contains_key(&entry_snapshot.id);
-    if was_connected {
-        kill_session_by_id(sessions, &entry_snapshot.id).await;
-        let (default_bin, codex_args) = {
-            let settings = app_settings.lock().await;
-            (
-                settings.codex_bin.clone(),
-                resolve_workspace_codex_args(&entry_snapshot, Some(&parent), Some(&settings)),
-            )
-        };
-        let codex_home = resolve_workspace_codex_home(&entry_snapshot, Some(&parent));
-        match spawn_session(entry_snapshot.clone(), default_bin, codex_args, codex_home).await {
-            Ok(session) => {
-                sessions
-                    .lock()
-                    .await
-                    .insert(entry_snapshot.id.clone(), session);
-            }
-            Err(error) => {
-                eprintln!(
-                    "rename_worktree: respawn failed for {} after rename: {error}",
-                    entry_snapshot.id
-                );
-            }
+    respawn_connected_worktree_session(
+        &entry_snapshot,
+        &parent,
+        sessions,
+        app_settings,
+        "rename_worktree",
+        &spawn_session,
+    )
+    .await;
+
+    let connected = sessions.lock().await.contains_key(&entry_snapshot.id);
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        codex_bin: entry_snapshot.codex_bin,
+        connected,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        pinned: entry_snapshot.pinned,
+        auto_connect: entry_snapshot.auto_connect,
+        settings: entry_snapshot.settings,
+    })
+}
+
+pub(crate) async fn move_worktree_core<FSpawn, FutSpawn, FResolveGitRoot, FRunGit, FutRunGit>(
+    id: String,
+    destination: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    resolve_git_root: FResolveGitRoot,
+    run_git_command: FRunGit,
+    spawn_session: FSpawn,
+) -> Result<WorkspaceInfo, String>
+where
+    FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
+    FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+    FResolveGitRoot: Fn(&WorkspaceEntry) -> Result<PathBuf, String>,
+    FRunGit: Fn(&PathBuf, &[&str]) -> FutRunGit,
+    FutRunGit: Future<Output = Result<(), String>>,
+{
+    let trimmed = destination.trim();
+    if trimmed.is_empty() {
+        return Err("Destination path is required.".to_string());
+    }
+    if trimmed.contains(['\n', '\r', '\0']) {
+        return Err("Destination path contains unsupported characters.".to_string());
+    }
+    let next_path = PathBuf::from(trimmed);
+    if !next_path.is_absolute() {
+        return Err("Destination path must be absolute.".to_string());
+    }
+    if next_path.exists() {
+        return Err(format!(
+            "Destination already exists: {}",
+            next_path.display()
+        ));
+    }
+
+    let (entry, parent) = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?;
+        if !entry.kind.is_worktree() {
+            return Err("Not a worktree workspace.".to_string());
         }
+        let parent_id = entry
+            .parent_id
+            .clone()
+            .ok_or_else(|| "worktree parent not found".to_string())?;
+        let parent = workspaces
+            .get(&parent_id)
+            .cloned()
+            .ok_or_else(|| "worktree parent not found".to_string())?;
+        (entry, parent)
+    };
+
+    let current_path = PathBuf::from(&entry.path);
+    if next_path.starts_with(&current_path) {
+        return Err("Destination cannot be inside the worktree being moved.".to_string());
     }
+    let parent_root = resolve_git_root(&parent)?;
+    if next_path.starts_with(parent_root.join(".git")) {
+        return Err("Destination cannot be inside the repository's .git directory.".to_string());
+    }
+    if let Some(destination_parent) = next_path.parent() {
+        std::fs::create_dir_all(destination_parent)
+            .map_err(|err| format!("Failed to create destination directory: {err}"))?;
+    }
+
+    let next_path_string = next_path.to_string_lossy().to_string();
+    run_git_command(
+        &parent_root,
+        &["worktree", "move", &entry.path, &next_path_string],
+    )
+    .await?;
+
+    let entry_snapshot = {
+        let mut workspaces = workspaces.lock().await;
+        let entry = match workspaces.get_mut(&id) {
+            Some(entry) => entry,
+            None => return Err("workspace not found".to_string()),
+        };
+        entry.path = next_path_string;
+        let snapshot = entry.clone();
+        persist_workspaces(&mut workspaces, storage_path, std::slice::from_ref(&id))?;
+        snapshot
+    };
+
+    respawn_connected_worktree_session(
+        &entry_snapshot,
+        &parent,
+        sessions,
+        app_settings,
+        "move_worktree",
+        &spawn_session,
+    )
+    .await;
 
     let connected = sessions.lock().await.contains_key(&entry_snapshot.id);
     Ok(WorkspaceInfo {
@@ -1240,6 +1824,8 @@ where
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        pinned: entry_snapshot.pinned,
+        auto_connect: entry_snapshot.auto_connect,
         settings: entry_snapshot.settings,
     })
 }
@@ -1380,6 +1966,36 @@ where
     FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
 {
     settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
+    let (previous_effort, previous_collaboration_mode) = {
+        let workspaces = workspaces.lock().await;
+        let previous = &workspaces
+            .get(&id)
+            .ok_or_else(|| "workspace not found".to_string())?
+            .settings;
+        (
+            previous.default_effort.clone(),
+            previous.default_collaboration_mode.clone(),
+        )
+    };
+    // Only changed defaults are checked, so saving other settings works while
+    // the workspace is disconnected.
+    if settings.default_effort != previous_effort {
+        settings.default_effort = codex_core::validate_default_effort_core(
+            sessions,
+            &id,
+            settings.default_effort.take(),
+            None,
+        )
+        .await?;
+    }
+    if settings.default_collaboration_mode != previous_collaboration_mode {
+        settings.default_collaboration_mode = codex_core::validate_default_collaboration_mode_core(
+            sessions,
+            &id,
+            settings.default_collaboration_mode.take(),
+        )
+        .await?;
+    }
 
     let (
         previous_entry,
@@ -1398,6 +2014,15 @@ where
         let previous_codex_home = previous_entry.settings.codex_home.clone();
         let previous_codex_args = previous_entry.settings.codex_args.clone();
         let previous_worktree_setup_script = previous_entry.settings.worktree_setup_script.clone();
+        // Clients never see the token, so an absent one means "keep" and an
+        // empty one means "clear".
+        settings.github_token = match settings.github_token.take() {
+            None => previous_entry.settings.github_token.clone(),
+            Some(token) if token.trim().is_empty() => None,
+            Some(token) => Some(token.trim().to_string()),
+        };
+        settings.thread_collaboration_modes =
+            previous_entry.settings.thread_collaboration_modes.clone();
         let entry_snapshot = apply_settings_update(&mut workspaces, &id, settings)?;
         let parent_entry = entry_snapshot
             .parent_id
@@ -1459,8 +2084,7 @@ where
             .await
             .insert(entry_snapshot.id.clone(), new_session)
         {
-            let mut child = old_session.child.lock().await;
-            kill_child_process_tree(&mut child).await;
+            old_session.terminate().await;
         }
     }
     if codex_home_changed || codex_args_changed {
@@ -1504,31 +2128,24 @@ where
                 }
             };
             if let Some(old_session) = sessions.lock().await.insert(child.id.clone(), new_session) {
-                let mut child = old_session.child.lock().await;
-                kill_child_process_tree(&mut child).await;
+                old_session.terminate().await;
             }
         }
     }
-    if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
-        let child_ids = child_entries
-            .iter()
-            .map(|child| child.id.clone())
-            .collect::<Vec<_>>();
-        if !child_ids.is_empty() {
-            let mut workspaces = workspaces.lock().await;
-            for child_id in child_ids {
-                if let Some(child) = workspaces.get_mut(&child_id) {
+    {
+        let mut workspaces = workspaces.lock().await;
+        let mut changed = vec![id.clone()];
+        if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
+            for child in &child_entries {
+                if let Some(child) = workspaces.get_mut(&child.id) {
                     child.settings.worktree_setup_script =
                         entry_snapshot.settings.worktree_setup_script.clone();
+                    changed.push(child.id.clone());
                 }
             }
         }
+        persist_workspaces(&mut workspaces, storage_path, &changed)?;
     }
-    let list: Vec<_> = {
-        let workspaces = workspaces.lock().await;
-        workspaces.values().cloned().collect()
-    };
-    write_workspaces(storage_path, &list)?;
     Ok(WorkspaceInfo {
         id: entry_snapshot.id,
         name: entry_snapshot.name,
@@ -1538,6 +2155,8 @@ where
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        pinned: entry_snapshot.pinned,
+        auto_connect: entry_snapshot.auto_connect,
         settings: entry_snapshot.settings,
     })
 }
@@ -1549,7 +2168,7 @@ pub(crate) async fn update_workspace_codex_bin_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     storage_path: &PathBuf,
 ) -> Result<WorkspaceInfo, String> {
-    let (entry_snapshot, list) = {
+    let entry_snapshot = {
         let mut workspaces = workspaces.lock().await;
         let entry_snapshot = match workspaces.get_mut(&id) {
             Some(entry) => {
@@ -1558,10 +2177,9 @@ pub(crate) async fn update_workspace_codex_bin_core(
             }
             None => return Err("workspace not found".to_string()),
         };
-        let list: Vec<_> = workspaces.values().cloned().collect();
-        (entry_snapshot, list)
+        persist_workspaces(&mut workspaces, storage_path, std::slice::from_ref(&id))?;
+        entry_snapshot
     };
-    write_workspaces(storage_path, &list)?;
 
     let connected = sessions.lock().await.contains_key(&id);
     Ok(WorkspaceInfo {
@@ -1573,33 +2191,237 @@ pub(crate) async fn update_workspace_codex_bin_core(
         kind: entry_snapshot.kind,
         parent_id: entry_snapshot.parent_id,
         worktree: entry_snapshot.worktree,
+        pinned: entry_snapshot.pinned,
+        auto_connect: entry_snapshot.auto_connect,
         settings: entry_snapshot.settings,
     })
 }
 
-pub(crate) async fn list_workspace_files_core<F>(
+/// The workspace's CODEX_HOME (its override, its parent's, or the default).
+async fn workspace_codex_home(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
-    list_files: F,
-) -> Result<Vec<String>, String>
+) -> Result<(WorkspaceEntry, Option<WorkspaceEntry>, PathBuf), String> {
+    let (entry, parent) = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .cloned()
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let parent = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        (entry, parent)
+    };
+    let codex_home = resolve_workspace_codex_home(&entry, parent.as_ref())
+        .or_else(resolve_default_codex_home)
+        .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    Ok((entry, parent, codex_home))
+}
+
+fn read_codex_tools(codex_home: &Path) -> Result<Vec<CodexToolState>, String> {
+    codex_config::CODEX_TOOL_FEATURES
+        .iter()
+        .map(|(name, default_enabled)| {
+            let configured = codex_config::read_feature_flag_in(codex_home, name)?;
+            Ok(CodexToolState {
+                name: name.to_string(),
+                enabled: configured.unwrap_or(*default_enabled),
+                configured: configured.is_some(),
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn get_codex_tools_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<Vec<CodexToolState>, String> {
+    let (_, _, codex_home) = workspace_codex_home(workspaces, workspace_id).await?;
+    read_codex_tools(&codex_home)
+}
+
+/// Flips a tool flag in the workspace's Codex config and respawns its session
+/// so the change applies. Other workspaces sharing the same CODEX_HOME pick it
+/// up the next time they connect.
+pub(crate) async fn set_codex_tool_enabled_core<FSpawn, FutSpawn>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    workspace_id: &str,
+    tool: &str,
+    enabled: bool,
+    spawn_session: FSpawn,
+) -> Result<Vec<CodexToolState>, String>
 where
-    F: Fn(&PathBuf) -> Vec<String>,
+    FSpawn: Fn(WorkspaceEntry, Option<String>, Option<String>, Option<PathBuf>) -> FutSpawn,
+    FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
 {
-    let root = resolve_workspace_root(workspaces, workspace_id).await?;
-    Ok(list_files(&root))
+    if !codex_config::CODEX_TOOL_FEATURES
+        .iter()
+        .any(|(name, _)| *name == tool)
+    {
+        let known = codex_config::CODEX_TOOL_FEATURES
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(format!(
+            "Unknown codex tool `{tool}`; expected one of: {known}"
+        ));
+    }
+    let (entry, parent, codex_home) = workspace_codex_home(workspaces, workspace_id).await?;
+    codex_config::write_feature_flag_in(&codex_home, tool, enabled)?;
+
+    if sessions.lock().await.contains_key(workspace_id) {
+        let (default_bin, codex_args) = {
+            let settings = app_settings.lock().await;
+            (
+                settings.codex_bin.clone(),
+                resolve_workspace_codex_args(&entry, parent.as_ref(), Some(&settings)),
+            )
+        };
+        let spawn_home = resolve_workspace_codex_home(&entry, parent.as_ref());
+        let new_session = spawn_session(entry.clone(), default_bin, codex_args, spawn_home).await?;
+        if let Some(old_session) = sessions.lock().await.insert(entry.id.clone(), new_session) {
+            old_session.terminate().await;
+        }
+    }
+    read_codex_tools(&codex_home)
 }
 
-pub(crate) async fn read_workspace_file_core<F, T>(
+pub(crate) async fn set_workspace_pinned_core(
+    id: String,
+    pinned: bool,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+) -> Result<WorkspaceInfo, String> {
+    update_workspace_entry(id, workspaces, sessions, storage_path, |entry| {
+        entry.pinned = pinned;
+    })
+    .await
+}
+
+pub(crate) async fn set_workspace_auto_connect_core(
+    id: String,
+    auto_connect: bool,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+) -> Result<WorkspaceInfo, String> {
+    update_workspace_entry(id, workspaces, sessions, storage_path, |entry| {
+        entry.auto_connect = auto_connect;
+    })
+    .await
+}
+
+/// Saves a default reasoning effort for the workspace after checking it
+/// against the models the connected session reports.
+pub(crate) async fn set_default_effort_core(
+    id: String,
+    effort: Option<String>,
+    model: Option<String>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+) -> Result<WorkspaceInfo, String> {
+    let effort = codex_core::validate_default_effort_core(sessions, &id, effort, model).await?;
+    update_workspace_entry(id, workspaces, sessions, storage_path, |entry| {
+        entry.settings.default_effort = effort;
+    })
+    .await
+}
+
+/// Saves a default collaboration mode for the workspace after checking it
+/// against the modes the connected session lists.
+pub(crate) async fn set_default_collaboration_mode_core(
+    id: String,
+    mode: Option<String>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+) -> Result<WorkspaceInfo, String> {
+    let mode = codex_core::validate_default_collaboration_mode_core(sessions, &id, mode).await?;
+    update_workspace_entry(id, workspaces, sessions, storage_path, |entry| {
+        entry.settings.default_collaboration_mode = mode;
+    })
+    .await
+}
+
+/// Writes the `changed` entries into the stored list (dropping ids no longer
+/// in `workspaces`) under the storage lock, then adopts the stored list. Edits
+/// another process made to other workspaces are kept on disk and picked up
+/// here instead of being overwritten.
+fn persist_workspaces(
+    workspaces: &mut HashMap<String, WorkspaceEntry>,
+    storage_path: &PathBuf,
+    changed: &[String],
+) -> Result<(), String> {
+    let stored = update_workspaces(storage_path, |stored| {
+        for id in changed {
+            match workspaces.get(id) {
+                Some(entry) => {
+                    stored.insert(id.clone(), entry.clone());
+                }
+                None => {
+                    stored.remove(id);
+                }
+            }
+        }
+    })?;
+    *workspaces = stored;
+    Ok(())
+}
+
+pub(crate) async fn update_workspace_entry(
+    id: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    storage_path: &PathBuf,
+    update: impl FnOnce(&mut WorkspaceEntry),
+) -> Result<WorkspaceInfo, String> {
+    let entry_snapshot = {
+        let mut workspaces = workspaces.lock().await;
+        let entry_snapshot = match workspaces.get_mut(&id) {
+            Some(entry) => {
+                update(entry);
+                entry.clone()
+            }
+            None => return Err("workspace not found".to_string()),
+        };
+        persist_workspaces(&mut workspaces, storage_path, std::slice::from_ref(&id))?;
+        entry_snapshot
+    };
+
+    let connected = sessions.lock().await.contains_key(&id);
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        codex_bin: entry_snapshot.codex_bin,
+        connected,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        pinned: entry_snapshot.pinned,
+        auto_connect: entry_snapshot.auto_connect,
+        settings: entry_snapshot.settings,
+    })
+}
+
+pub(crate) async fn list_workspace_files_core<F>(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
-    path: &str,
-    read_file: F,
-) -> Result<T, String>
+    list_files: F,
+) -> Result<Vec<String>, String>
 where
-    F: Fn(&PathBuf, &str) -> Result<T, String>,
+    F: Fn(&PathBuf) -> Vec<String>,
 {
     let root = resolve_workspace_root(workspaces, workspace_id).await?;
-    read_file(&root, path)
+    Ok(list_files(&root))
 }
 
 fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
@@ -1615,8 +2437,16 @@ fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
 
 #[cfg(test)]
 mod tests {
-    use super::copy_agents_md_from_parent_to_worktree;
     use super::AGENTS_MD_FILE_NAME;
+    use super::{connect_all_workspaces_core, git_core, worktree_from_stash_core};
+    use super::{copy_file_from_parent_to_worktree, worktree_copy_files};
+    use super::{reveal_path_command, reveal_workspace_path_core};
+    use super::{WorkspaceSession, CONNECT_ALL_CONCURRENCY};
+    use crate::types::{
+        AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
+    };
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
     use uuid::Uuid;
 
     fn make_temp_dir() -> std::path::PathBuf {
@@ -1625,6 +2455,57 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn reveal_workspace_path_rejects_unknown_workspaces_and_missing_paths() {
+        let missing = make_temp_dir().join("gone");
+        let workspaces = Mutex::new(HashMap::from([(
+            "ws-1".to_string(),
+            WorkspaceEntry {
+                id: "ws-1".to_string(),
+                name: "Workspace".to_string(),
+                path: missing.to_string_lossy().to_string(),
+                codex_bin: None,
+                kind: WorkspaceKind::Main,
+                parent_id: None,
+                worktree: None,
+                pinned: false,
+                auto_connect: false,
+                settings: WorkspaceSettings::default(),
+            },
+        )]));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+
+        let unknown = runtime.block_on(reveal_workspace_path_core(&workspaces, "ws-2"));
+        assert_eq!(unknown, Err("workspace not found".to_string()));
+        let gone = runtime
+            .block_on(reveal_workspace_path_core(&workspaces, "ws-1"))
+            .expect_err("missing path");
+        assert!(
+            gone.starts_with("Workspace path no longer exists:"),
+            "{gone}"
+        );
+
+        let _ = std::fs::remove_dir_all(missing.parent().expect("temp dir"));
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn reveal_command_points_the_file_manager_at_the_path() {
+        let path = std::path::Path::new("/tmp/some workspace");
+        let command = reveal_path_command(path);
+        let command = command.as_std();
+        let expected = if cfg!(target_os = "macos") {
+            "open"
+        } else {
+            "xdg-open"
+        };
+        assert_eq!(command.get_program(), expected);
+        assert_eq!(command.get_args().last(), Some(path.as_os_str()));
+    }
+
     #[test]
     fn copies_agents_md_when_missing_in_worktree() {
         let parent = make_temp_dir();
@@ -1634,7 +2515,8 @@ mod tests {
 
         std::fs::write(&parent_agents, "parent").expect("failed to write parent AGENTS.md");
 
-        copy_agents_md_from_parent_to_worktree(&parent, &worktree).expect("copy should succeed");
+        copy_file_from_parent_to_worktree(&parent, &worktree, AGENTS_MD_FILE_NAME)
+            .expect("copy should succeed");
 
         let copied = std::fs::read_to_string(&worktree_agents)
             .expect("worktree AGENTS.md should exist after copy");
@@ -1655,7 +2537,8 @@ mod tests {
         std::fs::write(&worktree_agents, "branch-specific")
             .expect("failed to write worktree AGENTS.md");
 
-        copy_agents_md_from_parent_to_worktree(&parent, &worktree).expect("copy should succeed");
+        copy_file_from_parent_to_worktree(&parent, &worktree, AGENTS_MD_FILE_NAME)
+            .expect("copy should succeed");
 
         let retained = std::fs::read_to_string(&worktree_agents)
             .expect("worktree AGENTS.md should still exist");
@@ -1664,4 +2547,194 @@ mod tests {
         let _ = std::fs::remove_dir_all(parent);
         let _ = std::fs::remove_dir_all(worktree);
     }
+
+    #[test]
+    fn copies_nested_extra_files_and_rejects_escaping_paths() {
+        let parent = make_temp_dir();
+        let worktree = make_temp_dir();
+        std::fs::create_dir_all(parent.join("config")).expect("create config dir");
+        std::fs::write(parent.join("config/local.json"), "{}").expect("write local config");
+
+        copy_file_from_parent_to_worktree(&parent, &worktree, "config/local.json")
+            .expect("copy should succeed");
+        assert_eq!(
+            std::fs::read_to_string(worktree.join("config/local.json")).expect("copied file"),
+            "{}"
+        );
+        assert!(copy_file_from_parent_to_worktree(&parent, &worktree, "../secrets").is_err());
+        assert!(copy_file_from_parent_to_worktree(&parent, &worktree, "/etc/hosts").is_err());
+
+        let _ = std::fs::remove_dir_all(parent);
+        let _ = std::fs::remove_dir_all(worktree);
+    }
+
+    #[test]
+    fn worktree_copy_files_falls_back_to_settings_default() {
+        let mut settings = AppSettings {
+            default_copy_agents_md: false,
+            worktree_copy_files: vec![" .env ".to_string(), AGENTS_MD_FILE_NAME.to_string()],
+            ..AppSettings::default()
+        };
+
+        assert_eq!(
+            worktree_copy_files(None, &settings),
+            vec![".env", AGENTS_MD_FILE_NAME]
+        );
+        assert_eq!(
+            worktree_copy_files(Some(true), &settings),
+            vec![AGENTS_MD_FILE_NAME, ".env"]
+        );
+        settings.worktree_copy_files.clear();
+        assert!(worktree_copy_files(None, &settings).is_empty());
+    }
+
+    #[test]
+    fn worktree_from_stash_applies_changes_and_drops_stash() {
+        let repo = make_temp_dir();
+        let worktree_path = repo.with_extension("stash-worktree");
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime")
+            .block_on(async {
+                let git = |args: &'static [&'static str]| git_core::run_git_command(&repo, args);
+                git(&["init", "-q"]).await.expect("init");
+                git(&["config", "user.email", "test@example.com"])
+                    .await
+                    .expect("email");
+                git(&["config", "user.name", "Test"]).await.expect("name");
+                std::fs::write(repo.join("notes.txt"), "base\n").expect("write base");
+                git(&["add", "."]).await.expect("add");
+                git(&["commit", "-qm", "base"]).await.expect("commit");
+                std::fs::write(repo.join("notes.txt"), "shelved\n").expect("write change");
+                git(&["stash", "push", "-q"]).await.expect("stash");
+
+                let entry = WorkspaceEntry {
+                    id: "parent".to_string(),
+                    name: "Parent".to_string(),
+                    path: repo.to_string_lossy().to_string(),
+                    codex_bin: None,
+                    kind: WorkspaceKind::Main,
+                    parent_id: None,
+                    worktree: None,
+                    pinned: false,
+                    auto_connect: true,
+                    settings: WorkspaceSettings::default(),
+                };
+                let workspaces = Mutex::new(HashMap::from([("parent".to_string(), entry)]));
+
+                let (repo_root, worktree_root) = (&repo, &worktree_path);
+                let result = worktree_from_stash_core(
+                    "parent".to_string(),
+                    "0".to_string(),
+                    &workspaces,
+                    |base_ref| async move {
+                        let path = worktree_root.to_string_lossy().to_string();
+                        git_core::run_git_command(
+                            repo_root,
+                            &["worktree", "add", "-q", "-b", "resume", &path, &base_ref],
+                        )
+                        .await?;
+                        Ok(WorkspaceInfo {
+                            id: "wt".to_string(),
+                            name: "resume".to_string(),
+                            path,
+                            connected: false,
+                            codex_bin: None,
+                            kind: WorkspaceKind::Worktree,
+                            parent_id: Some("parent".to_string()),
+                            worktree: None,
+                            pinned: false,
+                            auto_connect: true,
+                            settings: WorkspaceSettings::default(),
+                        })
+                    },
+                )
+                .await
+                .expect("worktree from stash");
+
+                assert!(result.applied);
+                assert!(result.stash_dropped);
+                assert!(result.conflicts.is_empty());
+                assert_eq!(
+                    std::fs::read_to_string(worktree_path.join("notes.txt")).expect("read"),
+                    "shelved\n"
+                );
+                assert_eq!(
+                    std::fs::read_to_string(repo.join("notes.txt")).expect("read"),
+                    "base\n"
+                );
+                assert!(git(&["stash", "list"]).await.expect("list").is_empty());
+
+                let missing = worktree_from_stash_core(
+                    "parent".to_string(),
+                    "3".to_string(),
+                    &workspaces,
+                    |_| async { Err::<WorkspaceInfo, String>("should not be called".to_string()) },
+                )
+                .await;
+                assert_eq!(missing.err().as_deref(), Some("Stash stash@{3} not found."));
+            });
+        let _ = std::fs::remove_dir_all(&worktree_path);
+        let _ = std::fs::remove_dir_all(&repo);
+    }
+
+    #[test]
+    fn connect_all_workspaces_bounds_concurrency_skips_opted_out_and_reports_failures() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let workspaces: HashMap<String, WorkspaceEntry> = (0..CONNECT_ALL_CONCURRENCY * 2)
+            .map(|index| {
+                let id = format!("ws-{index}");
+                let entry = WorkspaceEntry {
+                    id: id.clone(),
+                    name: id.clone(),
+                    path: format!("/tmp/{id}"),
+                    codex_bin: None,
+                    kind: WorkspaceKind::Main,
+                    parent_id: None,
+                    worktree: None,
+                    pinned: false,
+                    auto_connect: index != 0,
+                    settings: WorkspaceSettings::default(),
+                };
+                (id, entry)
+            })
+            .collect();
+        let workspaces = Mutex::new(workspaces);
+        let sessions = Mutex::new(HashMap::new());
+        let app_settings = Mutex::new(AppSettings::default());
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+
+        let results = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime")
+            .block_on(connect_all_workspaces_core(
+                &workspaces,
+                &sessions,
+                &app_settings,
+                |entry, _, _, _| {
+                    let running = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(running, Ordering::SeqCst);
+                    let active = &active;
+                    async move {
+                        tokio::task::yield_now().await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        Err::<Arc<WorkspaceSession>, String>(format!("{} failed", entry.id))
+                    }
+                },
+            ));
+
+        assert_eq!(results.len(), CONNECT_ALL_CONCURRENCY * 2 - 1);
+        assert!(!results.contains_key("ws-0"));
+        let result = &results["ws-3"];
+        assert!(!result.ok);
+        assert!(!result.already_connected);
+        assert_eq!(result.error.as_deref(), Some("ws-3 failed"));
+        assert!(peak.load(Ordering::SeqCst) <= CONNECT_ALL_CONCURRENCY);
+        assert!(sessions.try_lock().expect("sessions").is_empty());
+    }
 }
@@ -23,6 +23,51 @@ pub(crate) fn sanitize_worktree_name(branch: &str) -> String {
     sanitize_name(branch, "worktree")
 }
 
+/// Checks a branch name against `git check-ref-format --branch` rules so
+/// callers get a precise error instead of git's generic one.
+pub(crate) fn validate_branch_name(branch: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("Invalid branch name \"{branch}\": {reason}."));
+    if branch.is_empty() {
+        return invalid("name is empty");
+    }
+    if branch.starts_with('-') {
+        return invalid("cannot start with '-'");
+    }
+    if branch == "HEAD" || branch == "@" {
+        return invalid("reserved name");
+    }
+    if let Some(ch) = branch.chars().find(|ch| {
+        ch.is_ascii_control() || matches!(ch, ' ' | '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+    }) {
+        return match ch {
+            ' ' => invalid("cannot contain spaces"),
+            ch if ch.is_ascii_control() => invalid("cannot contain control characters"),
+            ch => invalid(&format!("cannot contain '{ch}'")),
+        };
+    }
+    if branch.contains("..") {
+        return invalid("cannot contain '..'");
+    }
+    if branch.contains("@{") {
+        return invalid("cannot contain '@{'");
+    }
+    if branch.ends_with('.') {
+        return invalid("cannot end with '.'");
+    }
+    if branch.starts_with('/') || branch.ends_with('/') || branch.contains("//") {
+        return invalid("cannot have empty path components");
+    }
+    for component in branch.split('/') {
+        if component.starts_with('.') {
+            return invalid("path components cannot start with '.'");
+        }
+        if component.ends_with(".lock") {
+            return invalid("path components cannot end with '.lock'");
+        }
+    }
+    Ok(())
+}
+
 pub(crate) fn sanitize_clone_dir_name(name: &str) -> String {
     sanitize_name(name, "copy")
 }
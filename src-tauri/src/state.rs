@@ -65,6 +65,11 @@ pub(crate) struct AppState {
     pub(crate) app_settings: Mutex<AppSettings>,
     pub(crate) dictation: Mutex<DictationState>,
     pub(crate) codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    pub(crate) walk_cancellations: crate::shared::search_core::WalkCancellations,
+    pub(crate) git_watchers: crate::shared::git_watch_core::GitStatusWatchers,
+    pub(crate) file_tails: crate::shared::file_tail_core::FileTails,
+    pub(crate) session_events: crate::shared::timeline_core::SessionEvents,
+    pub(crate) session_errors: crate::shared::session_error_core::SessionErrors,
     pub(crate) orbit_runner: Mutex<OrbitRunnerRuntime>,
     pub(crate) tcp_daemon: Mutex<TcpDaemonRuntime>,
 }
@@ -89,6 +94,11 @@ impl AppState {
             app_settings: Mutex::new(app_settings),
             dictation: Mutex::new(DictationState::default()),
             codex_login_cancels: Mutex::new(HashMap::new()),
+            walk_cancellations: Default::default(),
+            git_watchers: Default::default(),
+            file_tails: Default::default(),
+            session_events: Default::default(),
+            session_errors: Default::default(),
             orbit_runner: Mutex::new(OrbitRunnerRuntime::default()),
             tcp_daemon: Mutex::new(TcpDaemonRuntime::default()),
         }
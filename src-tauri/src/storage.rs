@@ -1,50 +1,158 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 use crate::types::{AppSettings, WorkspaceEntry};
 
-pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
+/// Opens and locks `<file>.lock` next to `path`. The app and the daemon can
+/// share a data dir, so every read and update of a storage file holds this
+/// advisory lock: shared for reads, exclusive for updates. The data file
+/// itself can't carry the lock because updates replace it. Dropping the handle
+/// releases the lock.
+pub(crate) fn lock_storage_file(path: &Path, exclusive: bool) -> Result<File, String> {
+    let mut lock_name = path.file_name().unwrap_or_default().to_os_string();
+    lock_name.push(".lock");
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_file_name(lock_name))
+        .map_err(|e| e.to_string())?;
+    let locked = if exclusive {
+        lock_file.lock()
+    } else {
+        lock_file.lock_shared()
+    };
+    locked.map_err(|e| e.to_string())?;
+    Ok(lock_file)
+}
+
+fn read_storage_file(path: impl AsRef<Path>) -> Result<Option<String>, String> {
+    let path = path.as_ref();
     if !path.exists() {
-        return Ok(HashMap::new());
+        return Ok(None);
     }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let list: Vec<WorkspaceEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
-    Ok(list
-        .into_iter()
-        .map(|entry| (entry.id.clone(), entry))
-        .collect())
+    let _lock = lock_storage_file(path, false)?;
+    std::fs::read_to_string(path)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+/// Writes to a temp file and renames it over `path`, so readers only ever see
+/// a complete file even if the writer dies halfway through. The caller holds
+/// the exclusive lock.
+fn replace_storage_file<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+    let mut temp_file = File::create(&temp_path).map_err(|e| e.to_string())?;
+    temp_file
+        .write_all(data.as_bytes())
+        .and_then(|_| temp_file.sync_all())
+        .map_err(|e| e.to_string())?;
+    std::fs::rename(&temp_path, path).map_err(|e| e.to_string())
 }
 
-pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
+/// Re-reads `path`, applies `update` and replaces the file, all under one
+/// exclusive lock. Another process sharing the data dir can't write in
+/// between, so its changes are seen and kept instead of overwritten. A
+/// missing file starts from `T::default()`; an unreadable one is an error
+/// rather than being replaced.
+fn with_locked_storage<T, F>(path: impl AsRef<Path>, update: F) -> Result<T, String>
+where
+    T: Serialize + DeserializeOwned + Default,
+    F: FnOnce(&mut T),
+{
+    let path = path.as_ref();
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    let data = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    let _lock = lock_storage_file(path, true)?;
+    let mut value = if path.exists() {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&data).map_err(|e| e.to_string())?
+    } else {
+        T::default()
+    };
+    update(&mut value);
+    replace_storage_file(path, &value)?;
+    Ok(value)
+}
+
+fn workspaces_by_id(list: Vec<WorkspaceEntry>) -> HashMap<String, WorkspaceEntry> {
+    list.into_iter()
+        .map(|entry| (entry.id.clone(), entry))
+        .collect()
+}
+
+pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
+    let Some(data) = read_storage_file(path)? else {
+        return Ok(HashMap::new());
+    };
+    let list: Vec<WorkspaceEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    Ok(workspaces_by_id(list))
+}
+
+/// Applies `update` to the stored workspaces and returns what was written.
+pub(crate) fn update_workspaces(
+    path: &PathBuf,
+    update: impl FnOnce(&mut HashMap<String, WorkspaceEntry>),
+) -> Result<HashMap<String, WorkspaceEntry>, String> {
+    let list = with_locked_storage(path, |list: &mut Vec<WorkspaceEntry>| {
+        let mut workspaces = workspaces_by_id(std::mem::take(list));
+        update(&mut workspaces);
+        *list = workspaces.into_values().collect();
+    })?;
+    Ok(workspaces_by_id(list))
 }
 
 pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
-    if !path.exists() {
+    let Some(data) = read_storage_file(path)? else {
         return Ok(AppSettings::default());
-    }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    };
     serde_json::from_str(&data).map_err(|e| e.to_string())
 }
 
-pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
-    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+/// Applies `update` to the stored settings and returns what was written.
+pub(crate) fn update_settings(
+    path: &PathBuf,
+    update: impl FnOnce(&mut AppSettings),
+) -> Result<AppSettings, String> {
+    with_locked_storage(path, update)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{read_workspaces, write_workspaces};
+    use super::{read_workspaces, update_workspaces, workspaces_by_id};
     use crate::types::{WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::path::PathBuf;
+    use std::sync::Arc;
     use uuid::Uuid;
 
+    fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
+        update_workspaces(path, |stored| *stored = workspaces_by_id(entries.to_vec())).map(|_| ())
+    }
+
+    fn workspace_entry(id: &str) -> WorkspaceEntry {
+        WorkspaceEntry {
+            id: id.to_string(),
+            name: format!("Workspace {id}"),
+            path: format!("/tmp/{id}"),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            pinned: false,
+            auto_connect: true,
+            settings: WorkspaceSettings::default(),
+        }
+    }
+
     #[test]
     fn write_read_workspaces_persists_sort_and_group() {
         let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
@@ -66,12 +174,15 @@ mod tests {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: true,
+            auto_connect: true,
             settings: settings.clone(),
         };
 
         write_workspaces(&path, &[entry]).expect("write workspaces");
         let read = read_workspaces(&path).expect("read workspaces");
         let stored = read.get("w1").expect("stored workspace");
+        assert!(stored.pinned);
         assert_eq!(stored.settings.sort_order, Some(5));
         assert_eq!(stored.settings.group_id.as_deref(), Some("group-42"));
         assert!(stored.settings.sidebar_collapsed);
@@ -81,4 +192,81 @@ mod tests {
             Some("--profile personal")
         );
     }
+
+    #[test]
+    fn concurrent_workspace_writes_never_leave_a_partial_file() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let path = Arc::new(temp_dir.join("workspaces.json"));
+
+        // Writers alternate between lists of very different sizes, so a torn
+        // or interleaved write would show up as unparseable JSON.
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let path = Arc::clone(&path);
+                std::thread::spawn(move || {
+                    for round in 0..25 {
+                        let count = if round % 2 == 0 { 40 } else { 1 };
+                        let entries: Vec<WorkspaceEntry> = (0..count)
+                            .map(|index| workspace_entry(&format!("w{writer}-{index}")))
+                            .collect();
+                        write_workspaces(&path, &entries).expect("write workspaces");
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..2)
+            .map(|_| {
+                let path = Arc::clone(&path);
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        let read = read_workspaces(&path).expect("read workspaces");
+                        assert!(read.is_empty() || read.len() == 1 || read.len() == 40);
+                    }
+                })
+            })
+            .collect();
+        for handle in writers.into_iter().chain(readers) {
+            handle.join().expect("thread");
+        }
+
+        let read = read_workspaces(&path).expect("final read");
+        assert!(read.len() == 40, "last round writes 40 entries");
+        let writer_ids: std::collections::HashSet<_> = read
+            .keys()
+            .map(|id| id.split('-').next().unwrap_or_default().to_string())
+            .collect();
+        assert_eq!(writer_ids.len(), 1, "entries come from a single writer");
+        assert!(!temp_dir.join("workspaces.json.tmp").exists());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn concurrent_updates_keep_each_others_changes() {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let path = Arc::new(temp_dir.join("workspaces.json"));
+
+        // Each writer stands in for a process with its own in-memory copy;
+        // every update must land on top of the others instead of replacing them.
+        let writers: Vec<_> = (0..4)
+            .map(|writer| {
+                let path = Arc::clone(&path);
+                std::thread::spawn(move || {
+                    for index in 0..10 {
+                        let entry = workspace_entry(&format!("w{writer}-{index}"));
+                        update_workspaces(&path, |stored| {
+                            stored.insert(entry.id.clone(), entry);
+                        })
+                        .expect("update workspaces");
+                    }
+                })
+            })
+            .collect();
+        for handle in writers {
+            handle.join().expect("thread");
+        }
+
+        assert_eq!(read_workspaces(&path).expect("final read").len(), 40);
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 }
@@ -247,8 +247,7 @@ pub(super) async fn tailscale_daemon_stop(
     let mut runtime = state.tcp_daemon.lock().await;
     let mut stop_error: Option<String> = None;
     if let Some(mut child) = runtime.child.take() {
-        kill_child_process_tree(&mut child).await;
-        let _ = child.wait().await;
+        stop_owned_daemon(&mut child).await;
     } else if let Some(port) = listen_port {
         match probe_daemon(
             &configured_listen_addr,
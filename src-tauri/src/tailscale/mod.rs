@@ -18,7 +18,8 @@ use crate::daemon_binary::resolve_daemon_binary_path;
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
 use crate::state::{AppState, TcpDaemonRuntime};
 use crate::types::{
-    TailscaleDaemonCommandPreview, TailscaleStatus, TcpDaemonState, TcpDaemonStatus,
+    RemoteHostFailure, TailscaleDaemonCommandPreview, TailscaleRemoteHostCheck, TailscaleStatus,
+    TcpDaemonState, TcpDaemonStatus,
 };
 
 use self::core as tailscale_core;
@@ -154,6 +155,15 @@ fn daemon_listen_addr(remote_host: &str) -> String {
     format!("0.0.0.0:{port}")
 }
 
+fn remote_host_with_port(remote_host: &str) -> String {
+    let trimmed = remote_host.trim();
+    if parse_port_from_remote_host(trimmed).is_some() {
+        trimmed.to_string()
+    } else {
+        format!("{trimmed}:4732")
+    }
+}
+
 fn daemon_connect_addr(listen_addr: &str) -> Option<String> {
     let port = parse_port_from_remote_host(listen_addr)?;
     Some(format!("127.0.0.1:{port}"))
@@ -323,6 +333,28 @@ async fn kill_pid_gracefully(_pid: u32) -> Result<(), String> {
     Err("Stopping external daemon by pid is not supported on this platform.".to_string())
 }
 
+/// Longer than the daemon's own drain timeout, so a SIGTERM gets to finish
+/// running requests before the kill fallback.
+const OWNED_DAEMON_STOP_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Stops a daemon this app spawned: SIGTERM first so it drains and exits
+/// cleanly, killing the process tree only if it is still running afterwards.
+async fn stop_owned_daemon(child: &mut tokio::process::Child) {
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let term_result = unsafe { libc::kill(pid as i32, libc::SIGTERM) };
+        if term_result == 0
+            && timeout(OWNED_DAEMON_STOP_TIMEOUT, child.wait())
+                .await
+                .is_ok()
+        {
+            return;
+        }
+    }
+    kill_child_process_tree(child).await;
+    let _ = child.wait().await;
+}
+
 #[tauri::command]
 pub(crate) async fn tailscale_status() -> Result<TailscaleStatus, String> {
     #[cfg(any(target_os = "android", target_os = "ios"))]
@@ -406,13 +438,42 @@ pub(crate) async fn tailscale_status() -> Result<TailscaleStatus, String> {
     }
 }
 
+/// Resolves and connects to `host` (the Tailscale suggestion, then the
+/// configured remote host, when omitted) and pings the daemon there, the same
+/// way a mobile client on the tailnet would.
+#[tauri::command]
+pub(crate) async fn tailscale_verify_remote_host(
+    host: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<TailscaleRemoteHostCheck, String> {
+    let settings = state.app_settings.lock().await.clone();
+    let host = match trim_to_non_empty(host.as_deref()) {
+        Some(host) => host,
+        None => match tailscale_status()
+            .await
+            .ok()
+            .and_then(|status| status.suggested_remote_host)
+        {
+            Some(host) => host,
+            None => trim_to_non_empty(Some(&settings.remote_backend_host))
+                .ok_or_else(|| "No remote host to verify.".to_string())?,
+        },
+    };
+    Ok(rpc_client::verify_remote_host(
+        &remote_host_with_port(&host),
+        settings.remote_backend_token.as_deref(),
+    )
+    .await)
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
         daemon_listen_addr, ensure_listen_addr_available, parse_port_from_remote_host,
-        sync_tcp_daemon_listen_addr, tailscale_binary_candidates,
+        remote_host_with_port, rpc_client::verify_remote_host, sync_tcp_daemon_listen_addr,
+        tailscale_binary_candidates,
     };
-    use crate::types::{TcpDaemonState, TcpDaemonStatus};
+    use crate::types::{RemoteHostFailure, TcpDaemonState, TcpDaemonStatus};
 
     #[test]
     fn includes_path_candidate() {
@@ -498,6 +559,70 @@ mod tests {
             assert!(error.contains("unavailable"));
         });
     }
+
+    #[test]
+    fn remote_host_gets_default_daemon_port() {
+        assert_eq!(
+            remote_host_with_port("mac.example.ts.net"),
+            "mac.example.ts.net:4732"
+        );
+        assert_eq!(
+            remote_host_with_port(" 100.64.0.1:9000 "),
+            "100.64.0.1:9000"
+        );
+    }
+
+    #[test]
+    fn verify_remote_host_distinguishes_dns_refused_and_reachable() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("runtime");
+
+        runtime.block_on(async {
+            let check = verify_remote_host("codex-monitor.invalid:4732", None).await;
+            assert!(!check.reachable);
+            assert_eq!(check.failure, Some(RemoteHostFailure::Dns));
+
+            let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind");
+            let closed_addr = closed.local_addr().expect("local addr").to_string();
+            drop(closed);
+            let check = verify_remote_host(&closed_addr, None).await;
+            assert!(!check.reachable);
+            assert_eq!(check.failure, Some(RemoteHostFailure::Refused));
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+                .await
+                .expect("bind");
+            let addr = listener.local_addr().expect("local addr").to_string();
+            tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.expect("accept");
+                let (reader, mut writer) = stream.into_split();
+                let mut lines = BufReader::new(reader).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let request: serde_json::Value =
+                        serde_json::from_str(&line).expect("request json");
+                    let response = serde_json::json!({
+                        "id": request["id"],
+                        "result": { "ok": true },
+                    });
+                    let mut payload = response.to_string();
+                    payload.push('\n');
+                    writer.write_all(payload.as_bytes()).await.expect("write");
+                }
+            });
+            let check = verify_remote_host(&addr, None).await;
+            assert!(check.reachable, "{}", check.message);
+            assert!(check.auth_ok);
+            assert_eq!(check.failure, None);
+            assert!(check.connect_ms.is_some());
+            assert!(check.ping_ms.is_some());
+        });
+    }
 }
 
 #[tauri::command]
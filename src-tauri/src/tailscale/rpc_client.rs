@@ -231,6 +231,136 @@ pub(super) async fn probe_daemon(listen_addr: &str, token: Option<&str>) -> Daem
     }
 }
 
+/// Tailnet hops are slower than loopback, so resolving and connecting get
+/// more room than the local daemon probe.
+const REMOTE_VERIFY_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn elapsed_ms(started: Instant) -> u64 {
+    u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX)
+}
+
+pub(super) async fn verify_remote_host(
+    host: &str,
+    token: Option<&str>,
+) -> TailscaleRemoteHostCheck {
+    let mut check = TailscaleRemoteHostCheck {
+        host: host.to_string(),
+        reachable: false,
+        auth_ok: false,
+        resolved_addr: None,
+        failure: None,
+        connect_ms: None,
+        ping_ms: None,
+        message: String::new(),
+    };
+    let fail = |mut check: TailscaleRemoteHostCheck, failure, message: String| {
+        check.failure = Some(failure);
+        check.message = message;
+        check
+    };
+
+    let resolved = match timeout(REMOTE_VERIFY_TIMEOUT, tokio::net::lookup_host(host)).await {
+        Ok(Ok(mut addrs)) => addrs.next(),
+        Ok(Err(err)) => {
+            return fail(
+                check,
+                RemoteHostFailure::Dns,
+                format!("Could not resolve {host}: {err}"),
+            )
+        }
+        Err(_) => {
+            return fail(
+                check,
+                RemoteHostFailure::Dns,
+                format!("Timed out resolving {host}"),
+            )
+        }
+    };
+    let Some(addr) = resolved else {
+        return fail(
+            check,
+            RemoteHostFailure::Dns,
+            format!("{host} did not resolve to any address"),
+        );
+    };
+    check.resolved_addr = Some(addr.to_string());
+
+    let connect_started = Instant::now();
+    let stream = match timeout(REMOTE_VERIFY_TIMEOUT, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(err)) if err.kind() == ErrorKind::ConnectionRefused => {
+            return fail(
+                check,
+                RemoteHostFailure::Refused,
+                format!("Connection to {addr} was refused; is the daemon listening on that port?"),
+            )
+        }
+        Ok(Err(err)) => {
+            return fail(
+                check,
+                RemoteHostFailure::Unreachable,
+                format!("Could not connect to {addr}: {err}"),
+            )
+        }
+        Err(_) => {
+            return fail(
+                check,
+                RemoteHostFailure::Timeout,
+                format!("Timed out connecting to {addr}"),
+            )
+        }
+    };
+    check.connect_ms = Some(elapsed_ms(connect_started));
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let ping_started = Instant::now();
+    let ping = send_and_expect_result(&mut writer, &mut lines, 1, "ping", json!({})).await;
+    check.ping_ms = Some(elapsed_ms(ping_started));
+    match ping {
+        Ok(_) => {
+            check.reachable = true;
+            check.auth_ok = true;
+            check.message = format!("Daemon reachable at {host}.");
+        }
+        Err(message) if is_auth_error_message(&message) => {
+            check.reachable = true;
+            let auth_token = token.map(str::trim).filter(|value| !value.is_empty());
+            let Some(auth_token) = auth_token else {
+                check.message = "Daemon reachable but requires a remote backend token.".to_string();
+                return check;
+            };
+            let auth = send_and_expect_result(
+                &mut writer,
+                &mut lines,
+                2,
+                "auth",
+                json!({ "token": auth_token }),
+            )
+            .await;
+            match auth {
+                Ok(_) => {
+                    check.auth_ok = true;
+                    check.message = format!("Daemon reachable at {host}.");
+                }
+                Err(err) => {
+                    check.message =
+                        format!("Daemon reachable but token authentication failed: {err}");
+                }
+            }
+        }
+        Err(message) => {
+            check.ping_ms = None;
+            return fail(
+                check,
+                RemoteHostFailure::NotDaemon,
+                format!("{addr} accepted the connection but did not answer ping: {message}"),
+            );
+        }
+    }
+    check
+}
+
 pub(super) async fn request_daemon_shutdown(
     listen_addr: &str,
     token: Option<&str>,
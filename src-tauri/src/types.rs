@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitFileStatus {
@@ -28,6 +29,27 @@ pub(crate) struct GitFileDiff {
     pub(crate) old_image_mime: Option<String>,
     #[serde(rename = "newImageMime")]
     pub(crate) new_image_mime: Option<String>,
+    #[serde(default, rename = "isUntracked")]
+    pub(crate) is_untracked: bool,
+    /// Set when an untracked file is listed without a diff (`tooLarge` or `binary`).
+    #[serde(default, rename = "skippedReason")]
+    pub(crate) skipped_reason: Option<String>,
+    /// Set when `diff` was cut at the diff size limit; `fullDiffBytes` is the
+    /// size before truncation.
+    #[serde(default)]
+    pub(crate) truncated: bool,
+    #[serde(default, rename = "fullDiffBytes")]
+    pub(crate) full_diff_bytes: Option<usize>,
+}
+
+/// A worktree's whole contribution: everything changed since it branched
+/// from the parent's default branch, committed or not.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeBaseDiff {
+    pub(crate) base_ref: String,
+    pub(crate) merge_base: String,
+    pub(crate) files: Vec<GitFileDiff>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -53,12 +75,26 @@ pub(crate) struct GitCommitDiff {
     pub(crate) new_image_mime: Option<String>,
 }
 
+/// Change totals for one commit. Binary files count toward `files_changed`
+/// and `binary_files` but add no lines.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitCommitStats {
+    pub(crate) files_changed: usize,
+    pub(crate) insertions: usize,
+    pub(crate) deletions: usize,
+    pub(crate) binary_files: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitLogEntry {
     pub(crate) sha: String,
     pub(crate) summary: String,
     pub(crate) author: String,
     pub(crate) timestamp: i64,
+    /// Only filled in when the log is requested with `includeStats`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) stats: Option<GitCommitStats>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -77,6 +113,163 @@ pub(crate) struct GitLogResponse {
     pub(crate) upstream: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitIncomingFile {
+    pub(crate) path: String,
+    pub(crate) status: String,
+    pub(crate) additions: usize,
+    pub(crate) deletions: usize,
+    /// `None` in summary-only mode or once the diff budget is spent.
+    pub(crate) diff: Option<String>,
+}
+
+/// What a pull would bring in: upstream commits missing from HEAD and the
+/// diff from the merge base to the upstream tip.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitIncomingChanges {
+    pub(crate) upstream: String,
+    pub(crate) fetch_error: Option<String>,
+    pub(crate) behind: usize,
+    pub(crate) commits: Vec<GitLogEntry>,
+    pub(crate) files: Vec<GitIncomingFile>,
+    pub(crate) additions: usize,
+    pub(crate) deletions: usize,
+    pub(crate) diff_truncated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitPushResult {
+    pub(crate) remote: String,
+    pub(crate) branch: String,
+    pub(crate) created_remote_branch: bool,
+    #[serde(default)]
+    pub(crate) forced: bool,
+    pub(crate) upstream_set: bool,
+    pub(crate) up_to_date: bool,
+    pub(crate) rejected: bool,
+    #[serde(default)]
+    pub(crate) rejection_reason: Option<String>,
+    pub(crate) summary: String,
+}
+
+/// Output of `git format-patch --stdout` for a commit or range. `patch` is
+/// empty when the range selects no commits.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitPatchExport {
+    pub(crate) patch: String,
+    pub(crate) commit_count: usize,
+}
+
+/// A hunk `git apply` refused. `line` and `hunk` are set when git points at a
+/// specific hunk rather than the whole file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitRejectedHunk {
+    pub(crate) path: String,
+    pub(crate) reason: String,
+    #[serde(default)]
+    pub(crate) line: Option<u32>,
+    #[serde(default)]
+    pub(crate) hunk: Option<String>,
+}
+
+/// Outcome of `git apply`. With `threeWay`, hunks that fall back to a merge
+/// may leave conflict markers; those paths are listed in `conflicts`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitPatchApplyResult {
+    pub(crate) applied: bool,
+    pub(crate) check_only: bool,
+    pub(crate) rejected: Vec<GitRejectedHunk>,
+    pub(crate) conflicts: Vec<String>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitLastCommit {
+    pub(crate) sha: String,
+    pub(crate) summary: String,
+    pub(crate) message: String,
+    pub(crate) author_name: String,
+    pub(crate) author_email: String,
+    pub(crate) timestamp: i64,
+    pub(crate) files: Vec<GitFileStatus>,
+}
+
+/// In-progress rebase/merge state; `operation` is `None` when the repo is clean.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitOperationProgress {
+    pub(crate) operation: Option<String>,
+    pub(crate) interactive: bool,
+    pub(crate) current_step: Option<u32>,
+    pub(crate) total_steps: Option<u32>,
+    pub(crate) current_commit: Option<String>,
+    pub(crate) current_commit_summary: Option<String>,
+    pub(crate) head_name: Option<String>,
+    pub(crate) onto: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitReflogEntry {
+    pub(crate) sha: String,
+    pub(crate) selector: String,
+    pub(crate) action: String,
+    pub(crate) message: String,
+    pub(crate) timestamp: i64,
+    pub(crate) relative_time: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitReflogRestoreResult {
+    pub(crate) branch: String,
+    pub(crate) previous_sha: String,
+    pub(crate) target_sha: String,
+    pub(crate) applied: bool,
+    pub(crate) autostashed: bool,
+    #[serde(default)]
+    pub(crate) warning: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitLargeFile {
+    pub(crate) path: String,
+    pub(crate) size_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LargeStagedFilesResponse {
+    pub(crate) threshold_bytes: u64,
+    pub(crate) files: Vec<GitLargeFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitLockFile {
+    pub(crate) path: String,
+    pub(crate) age_seconds: u64,
+    /// Old enough and no git process running, so safe to remove.
+    pub(crate) stale: bool,
+    pub(crate) removed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitRepairLocksResult {
+    pub(crate) git_process_running: bool,
+    pub(crate) locks: Vec<GitLockFile>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubIssue {
     pub(crate) number: u64,
@@ -92,6 +285,162 @@ pub(crate) struct GitHubIssuesResponse {
     pub(crate) issues: Vec<GitHubIssue>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHubRepoSlug {
+    pub(crate) host: String,
+    pub(crate) owner: String,
+    pub(crate) repo: String,
+}
+
+impl GitHubRepoSlug {
+    pub(crate) fn name_with_owner(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    /// Value for `gh --repo`, host-qualified for GitHub Enterprise remotes.
+    pub(crate) fn gh_repo_arg(&self) -> String {
+        if self.host == "github.com" {
+            self.name_with_owner()
+        } else {
+            format!("{}/{}", self.host, self.name_with_owner())
+        }
+    }
+}
+
+/// A configured remote. `pushUrl` falls back to the fetch URL when no
+/// separate push URL is set; `tracking` marks the current branch's upstream.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitRemote {
+    pub(crate) name: String,
+    pub(crate) fetch_url: Option<String>,
+    pub(crate) push_url: Option<String>,
+    pub(crate) tracking: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitMergedBranch {
+    pub(crate) name: String,
+    pub(crate) sha: String,
+    pub(crate) last_commit: i64,
+}
+
+/// Local branches whose tips are already contained in `default_branch`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitMergedBranches {
+    pub(crate) default_branch: String,
+    pub(crate) branches: Vec<GitMergedBranch>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitBranchPruneResult {
+    pub(crate) name: String,
+    pub(crate) deleted: bool,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitWorkingTreeClean {
+    pub(crate) clean: bool,
+}
+
+/// One entry of `git worktree list`. `dirty_count` counts uncommitted files,
+/// untracked included; it is `None` with `error` set when the worktree could
+/// not be read (e.g. its directory was deleted).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeDirtySummary {
+    pub(crate) path: String,
+    /// `None` when HEAD is detached.
+    pub(crate) branch: Option<String>,
+    pub(crate) head: Option<String>,
+    /// The workspace opened on this worktree, if any.
+    pub(crate) workspace_id: Option<String>,
+    /// The worktree the request's workspace lives in.
+    pub(crate) is_current: bool,
+    pub(crate) dirty_count: Option<usize>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RepoWorktreesDiffSummary {
+    pub(crate) worktrees: Vec<WorktreeDirtySummary>,
+    pub(crate) total_dirty_count: usize,
+}
+
+/// `git status --porcelain=v2 --branch` headers.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStatusBranch {
+    /// `None` before the first commit.
+    pub(crate) oid: Option<String>,
+    /// `None` when HEAD is detached.
+    pub(crate) head: Option<String>,
+    pub(crate) upstream: Option<String>,
+    pub(crate) ahead: Option<i64>,
+    pub(crate) behind: Option<i64>,
+}
+
+/// Porcelain v2 `<sub>` field for entries that are submodules.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitSubmoduleStatus {
+    pub(crate) commit_changed: bool,
+    pub(crate) tracked_changes: bool,
+    pub(crate) untracked_changes: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStatusEntry {
+    /// `changed`, `renamed`, `copied`, `unmerged`, `untracked` or `ignored`.
+    pub(crate) kind: String,
+    pub(crate) path: String,
+    /// Source path of a rename or copy.
+    #[serde(default)]
+    pub(crate) orig_path: Option<String>,
+    /// The `X` of `XY`: index vs HEAD, `.` when unchanged.
+    pub(crate) index_status: String,
+    /// The `Y` of `XY`: worktree vs index, `.` when unchanged.
+    pub(crate) worktree_status: String,
+    /// Rename/copy similarity percentage.
+    #[serde(default)]
+    pub(crate) similarity: Option<u8>,
+    #[serde(default)]
+    pub(crate) submodule: Option<GitSubmoduleStatus>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitStatusDetailed {
+    pub(crate) branch: GitStatusBranch,
+    pub(crate) entries: Vec<GitStatusEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitRemoteInfo {
+    pub(crate) name: String,
+    pub(crate) url: String,
+    pub(crate) github: Option<GitHubRepoSlug>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHubRepoInfo {
+    pub(crate) remotes: Vec<GitRemoteInfo>,
+    pub(crate) primary_remote: Option<String>,
+    pub(crate) primary: Option<GitHubRepoSlug>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubPullRequestAuthor {
     pub(crate) login: String,
@@ -144,6 +493,41 @@ pub(crate) struct GitHubPullRequestComment {
     pub(crate) author: Option<GitHubPullRequestAuthor>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHubPullRequestCheckout {
+    pub(crate) branch: String,
+    pub(crate) existed: bool,
+    pub(crate) updated: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHubPullRequestReviewComment {
+    pub(crate) id: String,
+    #[serde(default)]
+    pub(crate) body: String,
+    pub(crate) created_at: String,
+    #[serde(default)]
+    pub(crate) url: String,
+    #[serde(default)]
+    pub(crate) author: Option<GitHubPullRequestAuthor>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GitHubPullRequestReviewThread {
+    pub(crate) id: String,
+    pub(crate) path: String,
+    #[serde(default)]
+    pub(crate) line: Option<u64>,
+    #[serde(default)]
+    pub(crate) original_line: Option<u64>,
+    pub(crate) is_resolved: bool,
+    pub(crate) is_outdated: bool,
+    pub(crate) comments: Vec<GitHubPullRequestReviewComment>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct LocalUsageDay {
@@ -187,6 +571,15 @@ pub(crate) struct LocalUsageSnapshot {
     pub(crate) top_models: Vec<LocalUsageModel>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LocalUsageRefreshResult {
+    pub(crate) snapshot: LocalUsageSnapshot,
+    pub(crate) files_scanned: usize,
+    pub(crate) sessions_roots: Vec<String>,
+    pub(crate) duration_ms: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct OrbitConnectTestResult {
@@ -306,6 +699,35 @@ pub(crate) struct TailscaleStatus {
     pub(crate) message: String,
 }
 
+/// Why `tailscale_verify_remote_host` couldn't reach a daemon.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum RemoteHostFailure {
+    Dns,
+    Refused,
+    Timeout,
+    Unreachable,
+    NotDaemon,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TailscaleRemoteHostCheck {
+    pub(crate) host: String,
+    /// A codex-monitor daemon answered `ping`, with or without auth.
+    pub(crate) reachable: bool,
+    pub(crate) auth_ok: bool,
+    #[serde(default)]
+    pub(crate) resolved_addr: Option<String>,
+    #[serde(default)]
+    pub(crate) failure: Option<RemoteHostFailure>,
+    #[serde(default)]
+    pub(crate) connect_ms: Option<u64>,
+    #[serde(default)]
+    pub(crate) ping_ms: Option<u64>,
+    pub(crate) message: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct TailscaleDaemonCommandPreview {
@@ -321,6 +743,102 @@ pub(crate) struct BranchInfo {
     pub(crate) last_commit: i64,
 }
 
+/// Aggregate turn state of a connected workspace: `idle`, `running`, or
+/// `awaiting-approval` when any thread is blocked on an approval request.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceActivity {
+    pub(crate) workspace_id: String,
+    pub(crate) state: String,
+    pub(crate) running_threads: Vec<String>,
+    pub(crate) pending_approvals: usize,
+}
+
+/// An approval request from the app-server that has not been answered yet.
+/// `requestId` is what `respond_to_server_request` expects back.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingApproval {
+    pub(crate) workspace_id: String,
+    pub(crate) request_id: serde_json::Value,
+    pub(crate) method: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) params: serde_json::Value,
+}
+
+/// Read-only view of the account `account_read` reports. `authMode` is the
+/// account type (`chatgpt`, `apikey`); `requiresOpenaiAuth` is false for
+/// providers that need no OpenAI login at all.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexLoginStatus {
+    pub(crate) authenticated: bool,
+    pub(crate) auth_mode: Option<String>,
+    pub(crate) email: Option<String>,
+    pub(crate) plan_type: Option<String>,
+    pub(crate) requires_openai_auth: Option<bool>,
+}
+
+/// One `workspace_timeline` entry. `kind` is `commit`, `branch-switch`,
+/// `git`, `session-started`, `session-exited` or `turn-completed`;
+/// `timestamp` is in milliseconds.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTimelineEntry {
+    pub(crate) kind: String,
+    pub(crate) timestamp: i64,
+    pub(crate) summary: String,
+    pub(crate) thread_id: Option<String>,
+    pub(crate) sha: Option<String>,
+}
+
+/// Most recent unrecovered session failure. `kind` is `spawn`, `protocol`
+/// (unparseable app-server output) or `exit` (unexpected exit); `timestamp`
+/// is in milliseconds.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionLastError {
+    pub(crate) kind: String,
+    pub(crate) message: String,
+    pub(crate) timestamp: i64,
+}
+
+/// Per-workspace outcome of `connect_all_workspaces`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceConnectResult {
+    pub(crate) ok: bool,
+    pub(crate) already_connected: bool,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileResponse {
+    pub(crate) content: String,
+    /// True when the file was longer than `maxWorkspaceFileBytes`.
+    pub(crate) truncated: bool,
+    pub(crate) mime_type: String,
+    pub(crate) language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileRenameResult {
+    /// Destination, relative to the workspace root.
+    pub(crate) path: String,
+    /// True when the file was tracked and renamed with `git mv`.
+    pub(crate) staged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileDeleteResult {
+    /// True when tracked content was removed with `git rm` and is staged.
+    pub(crate) staged: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceEntry {
     pub(crate) id: String,
@@ -334,6 +852,12 @@ pub(crate) struct WorkspaceEntry {
     #[serde(default)]
     pub(crate) worktree: Option<WorktreeInfo>,
     #[serde(default)]
+    pub(crate) pinned: bool,
+    /// Whether startup and `list_all_threads` may spawn this workspace's
+    /// session on their own. Explicit `connect_workspace` calls ignore it.
+    #[serde(default = "default_auto_connect", rename = "autoConnect")]
+    pub(crate) auto_connect: bool,
+    #[serde(default)]
     pub(crate) settings: WorkspaceSettings,
 }
 
@@ -351,9 +875,139 @@ pub(crate) struct WorkspaceInfo {
     #[serde(default)]
     pub(crate) worktree: Option<WorktreeInfo>,
     #[serde(default)]
+    pub(crate) pinned: bool,
+    #[serde(default = "default_auto_connect", rename = "autoConnect")]
+    pub(crate) auto_connect: bool,
+    #[serde(default, serialize_with = "serialize_public_workspace_settings")]
     pub(crate) settings: WorkspaceSettings,
 }
 
+/// Strips secrets from settings echoed back to clients; the stored
+/// `WorkspaceEntry` keeps them.
+fn serialize_public_workspace_settings<S>(
+    settings: &WorkspaceSettings,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    WorkspaceSettings {
+        github_token: None,
+        ..settings.clone()
+    }
+    .serialize(serializer)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexToolState {
+    /// Codex `[features]` key, e.g. `web_search_request`.
+    pub(crate) name: String,
+    pub(crate) enabled: bool,
+    /// False when config.toml does not mention the tool and Codex's default applies.
+    pub(crate) configured: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreePathPreview {
+    pub(crate) sanitized_name: String,
+    pub(crate) path: String,
+}
+
+/// One thread's metadata from `thread/read`, without its turns.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadInfo {
+    pub(crate) id: String,
+    pub(crate) name: Option<String>,
+    pub(crate) preview: Option<String>,
+    pub(crate) cwd: Option<String>,
+    pub(crate) created_at: Option<i64>,
+    pub(crate) updated_at: Option<i64>,
+    /// `None` when the app-server reports neither a count nor the turns.
+    pub(crate) turn_count: Option<usize>,
+    pub(crate) model: Option<String>,
+    pub(crate) model_provider: Option<String>,
+}
+
+/// Outcome of `queue_user_message`: either the `turn/start` response of a
+/// message sent right away, or where the message waits in the thread's queue.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct QueuedUserMessage {
+    pub(crate) queued: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) message_id: Option<String>,
+    /// 1-based position among the thread's queued messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) position: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) response: Option<serde_json::Value>,
+}
+
+/// Round-trip time of a no-op request through a workspace's codex session.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionPing {
+    pub(crate) latency_ms: u64,
+}
+
+/// Which operations a workspace's app-server accepts, keyed by operation name
+/// (e.g. `forkThread`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SessionCapabilities {
+    pub(crate) codex_version: Option<String>,
+    /// `None` when the probe for an operation got no reply.
+    pub(crate) operations: BTreeMap<String, Option<bool>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceFileTailStart {
+    pub(crate) tail_id: String,
+    pub(crate) path: String,
+    pub(crate) lines: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ThreadArchiveFailure {
+    pub(crate) thread_id: String,
+    pub(crate) error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BulkArchiveThreadsResult {
+    pub(crate) dry_run: bool,
+    pub(crate) count: usize,
+    /// Archived thread ids, or the ones that would be archived on a dry run.
+    pub(crate) archived: Vec<String>,
+    #[serde(default)]
+    pub(crate) failed: Vec<ThreadArchiveFailure>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ForkThreadToWorktreeResult {
+    pub(crate) workspace: WorkspaceInfo,
+    pub(crate) thread_id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorktreeFromStashResult {
+    pub(crate) workspace: WorkspaceInfo,
+    pub(crate) stash_ref: String,
+    pub(crate) applied: bool,
+    /// Paths left conflicted in the new worktree when the apply failed.
+    pub(crate) conflicts: Vec<String>,
+    pub(crate) stash_dropped: bool,
+    pub(crate) error: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "lowercase")]
 pub(crate) enum WorkspaceKind {
@@ -408,6 +1062,29 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) launch_scripts: Option<Vec<LaunchScriptEntry>>,
     #[serde(default, rename = "worktreeSetupScript")]
     pub(crate) worktree_setup_script: Option<String>,
+    /// Reasoning effort for turns that do not pass one explicitly.
+    #[serde(default, rename = "defaultEffort")]
+    pub(crate) default_effort: Option<String>,
+    /// Collaboration mode for turns that pass none and whose thread has no
+    /// sticky mode.
+    #[serde(default, rename = "defaultCollaborationMode")]
+    pub(crate) default_collaboration_mode: Option<String>,
+    /// Overrides the global `gh` login for GitHub calls in this workspace.
+    /// Write-only: omitted from every `WorkspaceInfo` response.
+    #[serde(
+        default,
+        rename = "githubToken",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) github_token: Option<String>,
+    /// Sticky collaboration mode per thread, reapplied when the session
+    /// respawns. Only `set_thread_collaboration_mode` changes it.
+    #[serde(
+        default,
+        rename = "threadCollaborationModes",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub(crate) thread_collaboration_modes: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -419,6 +1096,23 @@ pub(crate) struct LaunchScriptEntry {
     pub(crate) label: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct WorkspaceGrepMatch {
+    pub(crate) path: String,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) snippet: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub(crate) struct WorkspaceGrepResponse {
+    pub(crate) matches: Vec<WorkspaceGrepMatch>,
+    pub(crate) truncated: bool,
+    /// Set when the walk was cancelled; `matches` holds what was found so far.
+    #[serde(default)]
+    pub(crate) cancelled: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorktreeSetupStatus {
     #[serde(rename = "shouldRun")]
@@ -439,6 +1133,111 @@ pub(crate) struct OpenAppTarget {
     pub(crate) args: Vec<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsValidationIssue {
+    pub(crate) field: String,
+    pub(crate) kind: String,
+    pub(crate) message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DoctorCheck {
+    pub(crate) name: String,
+    /// One of `pass`, `warn` or `fail`.
+    pub(crate) status: String,
+    pub(crate) message: String,
+    pub(crate) remediation: Option<String>,
+}
+
+/// Result of `codex_doctor`. The flat fields predate `checks` and are kept
+/// so older clients keep working.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexDoctorReport {
+    pub(crate) ok: bool,
+    pub(crate) codex_bin: Option<String>,
+    /// Whether `codex --version` ran.
+    pub(crate) binary_found: bool,
+    pub(crate) version: Option<String>,
+    pub(crate) app_server_ok: bool,
+    pub(crate) details: Option<String>,
+    pub(crate) path: Option<String>,
+    pub(crate) node_ok: bool,
+    pub(crate) node_version: Option<String>,
+    pub(crate) node_details: Option<String>,
+    pub(crate) config_path: Option<String>,
+    /// A missing config.toml counts as valid; Codex falls back to defaults.
+    pub(crate) config_valid: bool,
+    pub(crate) config_details: Option<String>,
+    pub(crate) checks: Vec<DoctorCheck>,
+}
+
+/// Identity of the running binary, for bug reports.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BuildInfo {
+    pub(crate) version: String,
+    /// `debug` or `release`.
+    pub(crate) profile: String,
+    pub(crate) target: Option<String>,
+    pub(crate) git_hash: Option<String>,
+}
+
+/// What a daemon speaks, readable before authenticating so clients can
+/// feature-gate on `methods` instead of guessing from the version.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ServerInfo {
+    pub(crate) version: String,
+    pub(crate) protocol_version: u32,
+    pub(crate) os: String,
+    pub(crate) arch: String,
+    /// True when the daemon serves clients over Orbit's WebSocket link.
+    pub(crate) websocket: bool,
+    /// How the asking connection reaches the daemon: `tcp`, `tls` or `websocket`.
+    pub(crate) transport: String,
+    pub(crate) methods: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DaemonDoctorReport {
+    /// False when any check failed; warnings do not count.
+    pub(crate) ok: bool,
+    pub(crate) checks: Vec<DoctorCheck>,
+}
+
+/// Where codex reads config for a workspace once its `codexHome` (or its
+/// parent's, for worktrees) is taken into account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceCodexConfigPath {
+    pub(crate) codex_home: String,
+    pub(crate) config_path: String,
+}
+
+/// Offline estimate of what a draft prompt will cost as model input.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MessageCostEstimate {
+    pub(crate) model: Option<String>,
+    /// BPE encoding used for `input_tokens`.
+    pub(crate) tokenizer: String,
+    pub(crate) input_tokens: u64,
+    /// USD per million input tokens; `None` when pricing is unknown.
+    pub(crate) input_price_per_million: Option<f64>,
+    pub(crate) estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppSettingsValidation {
+    pub(crate) valid: bool,
+    pub(crate) issues: Vec<SettingsValidationIssue>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct AppSettings {
     #[serde(default, rename = "codexBin")]
@@ -463,6 +1262,13 @@ pub(crate) struct AppSettings {
     pub(crate) orbit_auto_start_runner: bool,
     #[serde(default, rename = "keepDaemonRunningAfterAppClose")]
     pub(crate) keep_daemon_running_after_app_close: bool,
+    /// When off, clients don't reconnect every workspace on launch and
+    /// `list_all_threads` won't spawn sessions; `connect_workspace` still does.
+    #[serde(
+        default = "default_auto_connect_workspaces",
+        rename = "autoConnectWorkspaces"
+    )]
+    pub(crate) auto_connect_workspaces: bool,
     #[serde(default, rename = "orbitUseAccess")]
     pub(crate) orbit_use_access: bool,
     #[serde(default, rename = "orbitAccessClientId")]
@@ -593,6 +1399,17 @@ pub(crate) struct AppSettings {
         rename = "gitDiffIgnoreWhitespaceChanges"
     )]
     pub(crate) git_diff_ignore_whitespace_changes: bool,
+    /// Per-file cap on diff text returned by `get_git_diffs`; `0` disables it.
+    #[serde(default = "default_max_diff_bytes", rename = "maxDiffBytes")]
+    pub(crate) max_diff_bytes: usize,
+    /// Cap on content returned by `read_workspace_file`; longer files come
+    /// back truncated.
+    #[serde(
+        default = "default_max_workspace_file_bytes",
+        rename = "maxWorkspaceFileBytes"
+    )]
+    pub(crate) max_workspace_file_bytes: u64,
+    /// `{diff}` is replaced with the staged diff; empty uses the built-in prompt.
     #[serde(
         default = "default_commit_message_prompt",
         rename = "commitMessagePrompt"
@@ -691,6 +1508,56 @@ pub(crate) struct AppSettings {
     pub(crate) open_app_targets: Vec<OpenAppTarget>,
     #[serde(default = "default_selected_open_app_id", rename = "selectedOpenAppId")]
     pub(crate) selected_open_app_id: String,
+    /// Turns a single thread may start before requiring a reset; `None` is unlimited.
+    #[serde(default, rename = "maxTurnsPerThread")]
+    pub(crate) max_turns_per_thread: Option<u32>,
+    /// Extra directory names skipped by `list_workspace_files` and `grep_workspace`, on top of the built-in list.
+    #[serde(default, rename = "fileListSkipDirs")]
+    pub(crate) file_list_skip_dirs: Vec<String>,
+    /// GitHub Enterprise Server hosts whose remotes count as GitHub, on top of github.com and `*.ghe.com`.
+    #[serde(default, rename = "githubEnterpriseHosts")]
+    pub(crate) github_enterprise_hosts: Vec<String>,
+    /// Whether new worktrees get the parent's AGENTS.md when the caller does not say.
+    #[serde(default = "default_copy_agents_md", rename = "defaultCopyAgentsMd")]
+    pub(crate) default_copy_agents_md: bool,
+    /// Extra repo-relative files copied from the parent into every new worktree.
+    #[serde(default, rename = "worktreeCopyFiles")]
+    pub(crate) worktree_copy_files: Vec<String>,
+    /// Models used by `send_user_message`, most recent first, keyed by workspace id.
+    /// Maintained by the backend; values sent through `update_app_settings` are ignored.
+    #[serde(default, rename = "recentModels")]
+    pub(crate) recent_models: HashMap<String, Vec<String>>,
+    /// USD per million input tokens keyed by model id, for `estimate_message_cost`.
+    /// Not shipped with defaults, since published prices change.
+    #[serde(default, rename = "modelInputPrices")]
+    pub(crate) model_input_prices: HashMap<String, f64>,
+    /// Workspaces the user opened, most recent first, capped at
+    /// `recent_workspaces_limit`. Maintained by the backend like `recentModels`.
+    #[serde(default, rename = "recentWorkspaces")]
+    pub(crate) recent_workspaces: Vec<RecentWorkspace>,
+    #[serde(
+        default = "default_recent_workspaces_limit",
+        rename = "recentWorkspacesLimit"
+    )]
+    pub(crate) recent_workspaces_limit: u32,
+}
+
+/// `opened_at` is in milliseconds.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecentWorkspace {
+    pub(crate) workspace_id: String,
+    pub(crate) opened_at: i64,
+}
+
+/// A recent workspace that still exists, with what a quick switcher shows.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecentWorkspaceInfo {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) path: String,
+    pub(crate) opened_at: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -915,6 +1782,14 @@ fn default_cycle_workspace_prev_shortcut() -> Option<String> {
     Some(value.to_string())
 }
 
+fn default_auto_connect_workspaces() -> bool {
+    true
+}
+
+fn default_auto_connect() -> bool {
+    true
+}
+
 fn default_notification_sounds_enabled() -> bool {
     true
 }
@@ -923,6 +1798,14 @@ fn default_system_notifications_enabled() -> bool {
     true
 }
 
+fn default_copy_agents_md() -> bool {
+    true
+}
+
+fn default_recent_workspaces_limit() -> u32 {
+    10
+}
+
 fn default_preload_git_diffs() -> bool {
     true
 }
@@ -931,6 +1814,14 @@ fn default_git_diff_ignore_whitespace_changes() -> bool {
     false
 }
 
+fn default_max_diff_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_max_workspace_file_bytes() -> u64 {
+    400_000
+}
+
 fn default_commit_message_prompt() -> String {
     "Generate a concise git commit message for the following changes. \
 Follow conventional commit format (e.g., feat:, fix:, refactor:, docs:, etc.). \
@@ -1150,6 +2041,7 @@ impl Default for AppSettings {
             orbit_runner_name: None,
             orbit_auto_start_runner: false,
             keep_daemon_running_after_app_close: false,
+            auto_connect_workspaces: true,
             orbit_use_access: false,
             orbit_access_client_id: None,
             orbit_access_client_secret_ref: None,
@@ -1186,6 +2078,8 @@ impl Default for AppSettings {
             system_notifications_enabled: true,
             preload_git_diffs: default_preload_git_diffs(),
             git_diff_ignore_whitespace_changes: default_git_diff_ignore_whitespace_changes(),
+            max_diff_bytes: default_max_diff_bytes(),
+            max_workspace_file_bytes: default_max_workspace_file_bytes(),
             commit_message_prompt: default_commit_message_prompt(),
             experimental_collab_enabled: false,
             collaboration_modes_enabled: true,
@@ -1211,6 +2105,15 @@ impl Default for AppSettings {
             workspace_groups: default_workspace_groups(),
             open_app_targets: default_open_app_targets(),
             selected_open_app_id: default_selected_open_app_id(),
+            max_turns_per_thread: None,
+            file_list_skip_dirs: Vec::new(),
+            github_enterprise_hosts: Vec::new(),
+            default_copy_agents_md: true,
+            worktree_copy_files: Vec::new(),
+            recent_models: HashMap::new(),
+            model_input_prices: HashMap::new(),
+            recent_workspaces: Vec::new(),
+            recent_workspaces_limit: default_recent_workspaces_limit(),
         }
     }
 }
@@ -1219,7 +2122,7 @@ impl Default for AppSettings {
 mod tests {
     use super::{
         AppSettings, BackendMode, RemoteBackendProvider, WorkspaceEntry, WorkspaceGroup,
-        WorkspaceKind, WorkspaceSettings,
+        WorkspaceInfo, WorkspaceKind, WorkspaceSettings,
     };
 
     #[test]
@@ -1246,6 +2149,7 @@ mod tests {
         assert!(settings.orbit_runner_name.is_none());
         assert!(!settings.orbit_auto_start_runner);
         assert!(!settings.keep_daemon_running_after_app_close);
+        assert!(settings.auto_connect_workspaces);
         assert!(!settings.orbit_use_access);
         assert!(settings.orbit_access_client_id.is_none());
         assert!(settings.orbit_access_client_secret_ref.is_none());
@@ -1348,6 +2252,9 @@ mod tests {
         assert!(settings.system_notifications_enabled);
         assert!(settings.preload_git_diffs);
         assert!(!settings.git_diff_ignore_whitespace_changes);
+        assert_eq!(settings.max_diff_bytes, 1024 * 1024);
+        assert!(settings.default_copy_agents_md);
+        assert!(settings.worktree_copy_files.is_empty());
         assert!(settings.commit_message_prompt.contains("{diff}"));
         assert!(settings.collaboration_modes_enabled);
         assert!(settings.steer_enabled);
@@ -1424,5 +2331,36 @@ mod tests {
         assert!(settings.sort_order.is_none());
         assert!(settings.group_id.is_none());
         assert!(settings.git_root.is_none());
+        assert!(settings.github_token.is_none());
+    }
+
+    #[test]
+    fn workspace_info_never_serializes_github_token() {
+        let entry: WorkspaceEntry = serde_json::from_str(
+            r#"{"id":"1","name":"Test","path":"/tmp","codexBin":null,"settings":{"githubToken":"ghp_secret"}}"#,
+        )
+        .expect("workspace deserialize");
+        assert_eq!(entry.settings.github_token.as_deref(), Some("ghp_secret"));
+        // Entries saved before `autoConnect` existed keep connecting.
+        assert!(entry.auto_connect);
+        let stored = serde_json::to_string(&entry).expect("serialize entry");
+        assert!(stored.contains("ghp_secret"));
+
+        let info = WorkspaceInfo {
+            id: entry.id,
+            name: entry.name,
+            path: entry.path,
+            connected: false,
+            codex_bin: None,
+            kind: entry.kind,
+            parent_id: None,
+            worktree: None,
+            pinned: false,
+            auto_connect: entry.auto_connect,
+            settings: entry.settings,
+        };
+        let response = serde_json::to_string(&info).expect("serialize info");
+        assert!(!response.contains("ghp_secret"));
+        assert!(!response.contains("githubToken"));
     }
 }
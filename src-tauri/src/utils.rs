@@ -2,6 +2,8 @@ use std::env;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
+use crate::types::BuildInfo;
+
 #[allow(dead_code)]
 pub(crate) fn normalize_git_path(path: &str) -> String {
     path.replace('\\', "/")
@@ -85,6 +87,21 @@ pub(crate) fn git_env_path() -> String {
     joined.to_string_lossy().to_string()
 }
 
+/// Build identity embedded by `build.rs`; fields it could not determine are `None`.
+pub(crate) fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        }
+        .to_string(),
+        target: option_env!("CODEX_MONITOR_TARGET").map(str::to_string),
+        git_hash: option_env!("CODEX_MONITOR_GIT_HASH").map(str::to_string),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::normalize_git_path;
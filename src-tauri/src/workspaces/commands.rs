@@ -1,11 +1,12 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use std::sync::Arc;
 
-use serde_json::json;
+use serde_json::{json, Value};
 use tauri::{AppHandle, Manager, State};
 
-use super::files::{list_workspace_files_inner, read_workspace_file_inner, WorkspaceFileResponse};
+use super::files::list_workspace_files_inner;
 use super::git::{
     git_branch_exists, git_find_remote_for_branch, git_remote_branch_exists, git_remote_exists,
     is_missing_worktree_error, run_git_command_owned, unique_branch_name,
@@ -19,11 +20,25 @@ use super::worktree::{
 
 use crate::backend::app_server::WorkspaceSession;
 use crate::codex::spawn_workspace_session;
+use crate::event_sink::TauriEventSink;
 use crate::git_utils::resolve_git_root;
 use crate::remote_backend;
+use crate::shared::file_tail_core;
+use crate::shared::git_watch_core;
+use crate::shared::search_core::{self, WorkspaceGrepOptions};
+use crate::shared::session_error_core;
+use crate::shared::settings_core;
+use crate::shared::timeline_core;
+use crate::shared::workspace_files_core;
 use crate::shared::workspaces_core;
 use crate::state::AppState;
-use crate::types::{WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus};
+use crate::types::{
+    CodexToolState, ForkThreadToWorktreeResult, RecentWorkspaceInfo, SessionLastError,
+    WorkspaceConnectResult, WorkspaceEntry, WorkspaceFileDeleteResult, WorkspaceFileRenameResult,
+    WorkspaceFileResponse, WorkspaceFileTailStart, WorkspaceGrepResponse, WorkspaceInfo,
+    WorkspaceSettings, WorkspaceTimelineEntry, WorktreeFromStashResult, WorktreePathPreview,
+    WorktreeSetupStatus,
+};
 
 fn spawn_with_app(
     app: &AppHandle,
@@ -53,15 +68,159 @@ pub(crate) async fn read_workspace_file(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
-    workspaces_core::read_workspace_file_core(
+    workspace_files_core::read_workspace_file_core(
+        &state.workspaces,
+        &state.app_settings,
+        &workspace_id,
+        &path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn rename_workspace_file(
+    workspace_id: String,
+    source: String,
+    destination: String,
+    force: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFileRenameResult, String> {
+    let force = force.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "rename_workspace_file",
+            json!({
+                "workspaceId": workspace_id,
+                "source": source,
+                "destination": destination,
+                "force": force,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_files_core::rename_workspace_file_core(
+        &state.workspaces,
+        &workspace_id,
+        &source,
+        &destination,
+        force,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn create_workspace_file(
+    workspace_id: String,
+    path: String,
+    content: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    let content = content.unwrap_or_default();
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "create_workspace_file",
+            json!({ "workspaceId": workspace_id, "path": path, "content": content }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_files_core::create_workspace_file_core(
+        &state.workspaces,
+        &workspace_id,
+        &path,
+        &content,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn delete_workspace_file(
+    workspace_id: String,
+    path: String,
+    recursive: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFileDeleteResult, String> {
+    let recursive = recursive.unwrap_or(false);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_workspace_file",
+            json!({ "workspaceId": workspace_id, "path": path, "recursive": recursive }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspace_files_core::delete_workspace_file_core(
         &state.workspaces,
         &workspace_id,
         &path,
-        |root, rel_path| read_workspace_file_inner(root, rel_path),
+        recursive,
     )
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn tail_workspace_file(
+    workspace_id: String,
+    path: String,
+    lines: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceFileTailStart, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "tail_workspace_file",
+            json!({ "workspaceId": workspace_id, "path": path, "lines": lines }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    file_tail_core::tail_workspace_file_core(
+        &state.workspaces,
+        &state.file_tails,
+        TauriEventSink::new(app),
+        None,
+        workspace_id,
+        path,
+        lines,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn untail_workspace_file(
+    tail_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "untail_workspace_file",
+            json!({ "tailId": tail_id }),
+        )
+        .await;
+    }
+
+    Ok(json!({ "stopped": state.file_tails.untail(&tail_id) }))
+}
+
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
@@ -76,6 +235,20 @@ pub(crate) async fn list_workspaces(
     Ok(workspaces_core::list_workspaces_core(&state.workspaces, &state.sessions).await)
 }
 
+#[tauri::command]
+pub(crate) async fn workspaces_etag(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workspaces_etag", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspaces_core::workspaces_etag_core(&state.workspaces, &state.sessions).await)
+}
+
 #[tauri::command]
 pub(crate) async fn is_workspace_path_dir(
     path: String,
@@ -152,16 +325,42 @@ pub(crate) async fn add_clone(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn preview_worktree_path(
+    parent_id: String,
+    branch: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorktreePathPreview, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "preview_worktree_path",
+            json!({ "parentId": parent_id, "branch": branch }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
+    workspaces_core::preview_worktree_path_core(&parent_id, &branch, &data_dir, &state.workspaces)
+        .await
+}
+
 #[tauri::command]
 pub(crate) async fn add_worktree(
     parent_id: String,
     branch: String,
     name: Option<String>,
+    base_ref: Option<String>,
     copy_agents_md: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
 ) -> Result<WorkspaceInfo, String> {
-    let copy_agents_md = copy_agents_md.unwrap_or(true);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -171,6 +370,7 @@ pub(crate) async fn add_worktree(
                 "parentId": parent_id,
                 "branch": branch,
                 "name": name,
+                "baseRef": base_ref,
                 "copyAgentsMd": copy_agents_md
             }),
         )
@@ -187,6 +387,7 @@ pub(crate) async fn add_worktree(
         parent_id,
         branch,
         name,
+        base_ref,
         copy_agents_md,
         &data_dir,
         &state.workspaces,
@@ -213,6 +414,102 @@ pub(crate) async fn add_worktree(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn fork_thread_to_worktree(
+    parent_id: String,
+    thread_id: String,
+    branch: String,
+    name: Option<String>,
+    copy_agents_md: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ForkThreadToWorktreeResult, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "fork_thread_to_worktree",
+            json!({
+                "parentId": parent_id,
+                "threadId": thread_id,
+                "branch": branch,
+                "name": name,
+                "copyAgentsMd": copy_agents_md
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::fork_thread_to_worktree_core(
+        parent_id.clone(),
+        thread_id,
+        &state.workspaces,
+        &state.sessions,
+        || {
+            add_worktree(
+                parent_id,
+                branch,
+                name,
+                None,
+                copy_agents_md,
+                state.clone(),
+                app.clone(),
+            )
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn worktree_from_stash(
+    parent_id: String,
+    stash_index: Option<u32>,
+    stash: Option<String>,
+    branch: String,
+    name: Option<String>,
+    copy_agents_md: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorktreeFromStashResult, String> {
+    let stash_index = stash_index.unwrap_or(0);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "worktree_from_stash",
+            json!({
+                "parentId": parent_id,
+                "stashIndex": stash_index,
+                "stash": stash,
+                "branch": branch,
+                "name": name,
+                "copyAgentsMd": copy_agents_md
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::worktree_from_stash_core(
+        parent_id.clone(),
+        stash.unwrap_or_else(|| stash_index.to_string()),
+        &state.workspaces,
+        |base_ref| {
+            add_worktree(
+                parent_id,
+                branch,
+                name,
+                Some(base_ref),
+                copy_agents_md,
+                state.clone(),
+                app.clone(),
+            )
+        },
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn worktree_setup_status(
     workspace_id: String,
@@ -272,7 +569,7 @@ pub(crate) async fn remove_workspace(
         return Ok(());
     }
 
-    workspaces_core::remove_workspace_core(
+    let result = workspaces_core::remove_workspace_core(
         id,
         &state.workspaces,
         &state.sessions,
@@ -290,7 +587,12 @@ pub(crate) async fn remove_workspace(
         true,
         true,
     )
-    .await
+    .await;
+    git_watch_core::prune_git_watchers_core(&state.workspaces, &state.git_watchers).await;
+    file_tail_core::prune_file_tails_core(&state.workspaces, &state.file_tails).await;
+    timeline_core::prune_session_events_core(&state.workspaces, &state.session_events).await;
+    session_error_core::prune_session_errors_core(&state.workspaces, &state.session_errors).await;
+    result
 }
 
 #[tauri::command]
@@ -304,7 +606,7 @@ pub(crate) async fn remove_worktree(
         return Ok(());
     }
 
-    workspaces_core::remove_worktree_core(
+    let result = workspaces_core::remove_worktree_core(
         id,
         &state.workspaces,
         &state.sessions,
@@ -320,7 +622,12 @@ pub(crate) async fn remove_worktree(
                 .map_err(|err| format!("Failed to remove worktree folder: {err}"))
         },
     )
-    .await
+    .await;
+    git_watch_core::prune_git_watchers_core(&state.workspaces, &state.git_watchers).await;
+    file_tail_core::prune_file_tails_core(&state.workspaces, &state.file_tails).await;
+    timeline_core::prune_session_events_core(&state.workspaces, &state.session_events).await;
+    session_error_core::prune_session_errors_core(&state.workspaces, &state.session_errors).await;
+    result
 }
 
 #[tauri::command]
@@ -378,6 +685,44 @@ pub(crate) async fn rename_worktree(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn move_worktree(
+    id: String,
+    destination: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "move_worktree",
+            json!({ "id": id, "destination": destination }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::move_worktree_core(
+        id,
+        destination,
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &state.storage_path,
+        |entry| resolve_git_root(entry),
+        |root, args| {
+            workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
+                run_git_command_owned(repo, args_owned)
+            })
+        },
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn rename_worktree_upstream(
     id: String,
@@ -476,6 +821,59 @@ pub(crate) async fn update_workspace_settings(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn get_codex_tools(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<CodexToolState>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "get_codex_tools",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::get_codex_tools_core(&state.workspaces, &workspace_id).await
+}
+
+#[tauri::command]
+pub(crate) async fn set_codex_tool_enabled(
+    workspace_id: String,
+    tool: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<CodexToolState>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_codex_tool_enabled",
+            json!({ "workspaceId": workspace_id, "tool": tool, "enabled": enabled }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::set_codex_tool_enabled_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &workspace_id,
+        &tool,
+        enabled,
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn update_workspace_codex_bin(
     id: String,
@@ -505,6 +903,174 @@ pub(crate) async fn update_workspace_codex_bin(
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn set_workspace_auto_connect(
+    id: String,
+    auto_connect: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_workspace_auto_connect",
+            json!({ "id": id, "autoConnect": auto_connect }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::set_workspace_auto_connect_core(
+        id,
+        auto_connect,
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn set_workspace_pinned(
+    id: String,
+    pinned: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_workspace_pinned",
+            json!({ "id": id, "pinned": pinned }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::set_workspace_pinned_core(
+        id,
+        pinned,
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_timeline(
+    workspace_id: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<WorkspaceTimelineEntry>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_timeline",
+            json!({ "workspaceId": workspace_id, "limit": limit }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    timeline_core::workspace_timeline_core(
+        &state.workspaces,
+        &state.session_events,
+        workspace_id,
+        limit,
+    )
+    .await
+}
+
+/// The workspace's last spawn failure, unparseable app-server output or
+/// unexpected exit; null once a session has started or answered since.
+#[tauri::command]
+pub(crate) async fn session_last_error(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Option<SessionLastError>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "session_last_error",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    session_error_core::session_last_error_core(
+        &state.workspaces,
+        &state.session_errors,
+        workspace_id,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn set_default_collaboration_mode(
+    id: String,
+    mode: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_default_collaboration_mode",
+            json!({ "id": id, "mode": mode }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::set_default_collaboration_mode_core(
+        id,
+        mode,
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn set_default_effort(
+    id: String,
+    effort: Option<String>,
+    model: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceInfo, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "set_default_effort",
+            json!({ "id": id, "effort": effort, "model": model }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workspaces_core::set_default_effort_core(
+        id,
+        effort,
+        model,
+        &state.workspaces,
+        &state.sessions,
+        &state.storage_path,
+    )
+    .await
+}
+
 #[tauri::command]
 pub(crate) async fn connect_workspace(
     id: String,
@@ -525,7 +1091,63 @@ pub(crate) async fn connect_workspace(
             spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
         },
     )
-    .await
+    .await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn record_recent_workspace(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(&*state, app, "record_recent_workspace", json!({ "id": id }))
+            .await?;
+        return Ok(());
+    }
+
+    if !state.workspaces.lock().await.contains_key(&id) {
+        return Err("workspace not found".to_string());
+    }
+    settings_core::record_recent_workspace_core(&state.app_settings, &state.settings_path, &id)
+        .await
+}
+
+#[tauri::command]
+pub(crate) async fn list_recent_workspaces(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<RecentWorkspaceInfo>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "list_recent_workspaces", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(settings_core::list_recent_workspaces_core(&state.app_settings, &state.workspaces).await)
+}
+
+#[tauri::command]
+pub(crate) async fn connect_all_workspaces(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<HashMap<String, WorkspaceConnectResult>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "connect_all_workspaces", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(workspaces_core::connect_all_workspaces_core(
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        |entry, default_bin, codex_args, codex_home| {
+            spawn_with_app(&app, entry, default_bin, codex_args, codex_home)
+        },
+    )
+    .await)
 }
 
 #[tauri::command]
@@ -545,12 +1167,83 @@ pub(crate) async fn list_workspace_files(
         return serde_json::from_value(response).map_err(|err| err.to_string());
     }
 
+    let skip_dirs = state.app_settings.lock().await.file_list_skip_dirs.clone();
     workspaces_core::list_workspace_files_core(&state.workspaces, &workspace_id, |root| {
-        list_workspace_files_inner(root, usize::MAX)
+        list_workspace_files_inner(root, usize::MAX, &skip_dirs)
     })
     .await
 }
 
+#[tauri::command]
+pub(crate) async fn grep_workspace(
+    workspace_id: String,
+    query: String,
+    is_regex: Option<bool>,
+    case_sensitive: Option<bool>,
+    globs: Option<Vec<String>>,
+    max_results: Option<usize>,
+    request_id: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<WorkspaceGrepResponse, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "grep_workspace",
+            json!({
+                "workspaceId": workspace_id,
+                "query": query,
+                "isRegex": is_regex,
+                "caseSensitive": case_sensitive,
+                "globs": globs,
+                "maxResults": max_results,
+                "requestId": request_id,
+            }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    let options = WorkspaceGrepOptions {
+        query,
+        is_regex: is_regex.unwrap_or(false),
+        case_sensitive: case_sensitive.unwrap_or(false),
+        globs: globs.unwrap_or_default(),
+        max_results,
+        skip_dirs: state.app_settings.lock().await.file_list_skip_dirs.clone(),
+        walk_key: request_id.map(|request_id| (None, request_id)),
+    };
+    search_core::grep_workspace_core(
+        &state.workspaces,
+        &state.walk_cancellations,
+        &workspace_id,
+        options,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn cancel_workspace_walk(
+    request_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "cancel_workspace_walk",
+            json!({ "requestId": request_id }),
+        )
+        .await;
+    }
+
+    let cancelled =
+        search_core::cancel_workspace_walk_core(&state.walk_cancellations, None, &request_id);
+    Ok(json!({ "cancelled": cancelled }))
+}
+
 #[tauri::command]
 pub(crate) async fn open_workspace_in(
     path: String,
@@ -561,6 +1254,14 @@ pub(crate) async fn open_workspace_in(
     workspaces_core::open_workspace_in_core(path, app, args, command).await
 }
 
+#[tauri::command]
+pub(crate) async fn reveal_workspace_path(
+    workspace_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    workspaces_core::reveal_workspace_path_core(&state.workspaces, &workspace_id).await
+}
+
 #[tauri::command]
 pub(crate) async fn get_open_app_icon(app_name: String) -> Result<Option<String>, String> {
     #[cfg(target_os = "macos")]
@@ -8,8 +8,9 @@ use super::worktree::{
     build_clone_destination_path, sanitize_clone_dir_name, sanitize_worktree_name,
 };
 use crate::backend::app_server::WorkspaceSession;
-use crate::shared::workspaces_core::rename_worktree_core;
-use crate::storage::{read_workspaces, write_workspaces};
+use crate::shared::workspaces_core::{move_worktree_core, rename_worktree_core};
+use crate::shared::worktree_core::validate_branch_name;
+use crate::storage::{read_workspaces, update_workspaces};
 use crate::types::{
     AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
 };
@@ -46,6 +47,8 @@ fn workspace_with_id_and_kind(
         kind,
         parent_id,
         worktree,
+        pinned: false,
+        auto_connect: true,
         settings: WorkspaceSettings {
             sidebar_collapsed: false,
             sort_order,
@@ -56,6 +59,10 @@ fn workspace_with_id_and_kind(
             launch_script: None,
             launch_scripts: None,
             worktree_setup_script: None,
+            default_effort: None,
+            default_collaboration_mode: None,
+            github_token: None,
+            thread_collaboration_modes: HashMap::new(),
         },
     }
 }
@@ -81,6 +88,41 @@ fn sanitize_worktree_name_allows_safe_chars() {
     assert_eq!(sanitize_worktree_name("feature--x"), "feature--x");
 }
 
+#[test]
+fn validate_branch_name_accepts_common_names() {
+    for name in [
+        "feature/new-thing",
+        "release_1.2.3",
+        "fix-123",
+        "user/topic/sub",
+    ] {
+        assert!(validate_branch_name(name).is_ok(), "{name} should be valid");
+    }
+}
+
+#[test]
+fn validate_branch_name_rejects_illegal_refs() {
+    let cases = [
+        ("-force", "cannot start with '-'"),
+        ("feature..x", "cannot contain '..'"),
+        ("bad\tname", "cannot contain control characters"),
+        ("has space", "cannot contain spaces"),
+        ("topic~1", "cannot contain '~'"),
+        ("what?", "cannot contain '?'"),
+        ("ref@{1}", "cannot contain '@{'"),
+        ("feature/", "cannot have empty path components"),
+        ("a//b", "cannot have empty path components"),
+        ("feature/.hidden", "path components cannot start with '.'"),
+        ("main.lock", "path components cannot end with '.lock'"),
+        ("trailing.", "cannot end with '.'"),
+        ("HEAD", "reserved name"),
+    ];
+    for (name, reason) in cases {
+        let error = validate_branch_name(name).expect_err(name);
+        assert!(error.contains(reason), "{name}: {error}");
+    }
+}
+
 #[test]
 fn sanitize_clone_dir_name_rewrites_specials() {
     assert_eq!(
@@ -207,6 +249,8 @@ fn update_workspace_settings_persists_sort_and_group() {
         kind: WorkspaceKind::Main,
         parent_id: None,
         worktree: None,
+        pinned: false,
+        auto_connect: true,
         settings: WorkspaceSettings::default(),
     };
     let mut workspaces = HashMap::from([(id.clone(), entry)]);
@@ -237,8 +281,7 @@ fn update_workspace_settings_persists_sort_and_group() {
     let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
     std::fs::create_dir_all(&temp_dir).expect("create temp dir");
     let path = PathBuf::from(temp_dir.join("workspaces.json"));
-    let list: Vec<_> = workspaces.values().cloned().collect();
-    write_workspaces(&path, &list).expect("write workspaces");
+    update_workspaces(&path, |stored| *stored = workspaces.clone()).expect("write workspaces");
 
     let read = read_workspaces(&path).expect("read workspaces");
     let stored = read.get(&id).expect("stored workspace");
@@ -273,6 +316,8 @@ fn rename_worktree_preserves_custom_name() {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
         let worktree = WorkspaceEntry {
@@ -285,6 +330,8 @@ fn rename_worktree_preserves_custom_name() {
             worktree: Some(WorktreeInfo {
                 branch: "feature/old".to_string(),
             }),
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
         let workspaces = Mutex::new(HashMap::from([
@@ -346,6 +393,8 @@ fn rename_worktree_updates_name_when_unmodified() {
             kind: WorkspaceKind::Main,
             parent_id: None,
             worktree: None,
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
         let worktree = WorkspaceEntry {
@@ -358,6 +407,8 @@ fn rename_worktree_updates_name_when_unmodified() {
             worktree: Some(WorktreeInfo {
                 branch: "feature/old".to_string(),
             }),
+            pinned: false,
+            auto_connect: true,
             settings: WorkspaceSettings::default(),
         };
         let workspaces = Mutex::new(HashMap::from([
@@ -394,3 +445,128 @@ fn rename_worktree_updates_name_when_unmodified() {
         assert_eq!(updated.name, "feature/new");
     });
 }
+
+fn move_worktree_fixture(temp_dir: &PathBuf) -> (WorkspaceEntry, WorkspaceEntry) {
+    let repo_path = temp_dir.join("repo");
+    std::fs::create_dir_all(&repo_path).expect("create repo path");
+    let worktree_path = temp_dir.join("worktrees").join("parent").join("feature");
+    std::fs::create_dir_all(&worktree_path).expect("create worktree path");
+    let parent = WorkspaceEntry {
+        id: "parent".to_string(),
+        name: "Parent".to_string(),
+        path: repo_path.to_string_lossy().to_string(),
+        codex_bin: None,
+        kind: WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        pinned: false,
+        auto_connect: true,
+        settings: WorkspaceSettings::default(),
+    };
+    let worktree = WorkspaceEntry {
+        id: "wt-1".to_string(),
+        name: "feature".to_string(),
+        path: worktree_path.to_string_lossy().to_string(),
+        codex_bin: None,
+        kind: WorkspaceKind::Worktree,
+        parent_id: Some(parent.id.clone()),
+        worktree: Some(WorktreeInfo {
+            branch: "feature".to_string(),
+        }),
+        pinned: false,
+        auto_connect: true,
+        settings: WorkspaceSettings::default(),
+    };
+    (parent, worktree)
+}
+
+#[test]
+fn move_worktree_updates_and_persists_path() {
+    run_async(async {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let (parent, worktree) = move_worktree_fixture(&temp_dir);
+        let repo_path = PathBuf::from(&parent.path);
+        let destination = temp_dir.join("fast-disk").join("feature");
+        let workspaces = Mutex::new(HashMap::from([
+            (parent.id.clone(), parent.clone()),
+            (worktree.id.clone(), worktree.clone()),
+        ]));
+        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let app_settings = Mutex::new(AppSettings::default());
+        let storage_path = temp_dir.join("workspaces.json");
+        let git_calls = std::sync::Mutex::new(Vec::<Vec<String>>::new());
+
+        let updated = move_worktree_core(
+            worktree.id.clone(),
+            destination.to_string_lossy().to_string(),
+            &workspaces,
+            &sessions,
+            &app_settings,
+            &storage_path,
+            |_| Ok(repo_path.clone()),
+            |_root, args| {
+                git_calls
+                    .lock()
+                    .unwrap()
+                    .push(args.iter().map(|arg| arg.to_string()).collect());
+                async move { Ok(()) }
+            },
+            |_entry, _default_bin, _codex_args, _codex_home| async move {
+                Err("spawn not expected".to_string())
+            },
+        )
+        .await
+        .expect("move worktree");
+
+        let destination_string = destination.to_string_lossy().to_string();
+        assert_eq!(updated.path, destination_string);
+        assert_eq!(
+            git_calls.lock().unwrap().clone(),
+            vec![vec![
+                "worktree".to_string(),
+                "move".to_string(),
+                worktree.path.clone(),
+                destination_string.clone(),
+            ]]
+        );
+        let stored = read_workspaces(&storage_path).expect("read workspaces");
+        assert_eq!(
+            stored.get(&worktree.id).map(|entry| entry.path.as_str()),
+            Some(destination_string.as_str())
+        );
+    });
+}
+
+#[test]
+fn move_worktree_rejects_existing_destination() {
+    run_async(async {
+        let temp_dir = std::env::temp_dir().join(format!("codex-monitor-test-{}", Uuid::new_v4()));
+        let (parent, worktree) = move_worktree_fixture(&temp_dir);
+        let repo_path = PathBuf::from(&parent.path);
+        let workspaces = Mutex::new(HashMap::from([
+            (parent.id.clone(), parent.clone()),
+            (worktree.id.clone(), worktree.clone()),
+        ]));
+        let sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>> = Mutex::new(HashMap::new());
+        let app_settings = Mutex::new(AppSettings::default());
+        let storage_path = temp_dir.join("workspaces.json");
+
+        let error = move_worktree_core(
+            worktree.id.clone(),
+            parent.path.clone(),
+            &workspaces,
+            &sessions,
+            &app_settings,
+            &storage_path,
+            |_| Ok(repo_path.clone()),
+            |_root, _args| async move { Err("git not expected".to_string()) },
+            |_entry, _default_bin, _codex_args, _codex_home| async move {
+                Err("spawn not expected".to_string())
+            },
+        )
+        .await
+        .expect_err("existing destination should fail");
+
+        assert!(error.contains("already exists"));
+    });
+}
@@ -933,6 +933,7 @@ function MainApp() {
     onSelectWorkspace: selectWorkspace,
     onWorktreeCreated: handleWorktreeCreated,
     onCompactActivate: isCompact ? () => setActiveTab("codex") : undefined,
+    defaultCopyAgentsMd: appSettings.defaultCopyAgentsMd ?? true,
     onError: (message) => {
       addDebugEntry({
         id: `${Date.now()}-client-add-worktree-error`,
@@ -1447,7 +1448,9 @@ function MainApp() {
   useWindowDrag("titlebar");
   useWorkspaceRestore({
     workspaces,
-    hasLoaded,
+    // Wait for settings so `autoConnectWorkspaces` isn't read as its default.
+    hasLoaded: hasLoaded && !appSettingsLoading,
+    autoConnect: appSettings.autoConnectWorkspaces,
     connectWorkspace,
     listThreadsForWorkspace
   });
@@ -50,6 +50,7 @@ describe("useAppServerEvents", () => {
       onThreadStarted: vi.fn(),
       onThreadNameUpdated: vi.fn(),
       onBackgroundThreadAction: vi.fn(),
+      onThreadsRefreshed: vi.fn(),
       onAgentMessageDelta: vi.fn(),
       onReasoningSummaryBoundary: vi.fn(),
       onPlanDelta: vi.fn(),
@@ -159,6 +160,17 @@ describe("useAppServerEvents", () => {
       "hide",
     );
 
+    act(() => {
+      listener?.({
+        workspace_id: "ws-1",
+        message: {
+          method: "codex/threadsRefreshed",
+          params: { workspaceId: "ws-1" },
+        },
+      });
+    });
+    expect(handlers.onThreadsRefreshed).toHaveBeenCalledWith("ws-1");
+
     act(() => {
       listener?.({
         workspace_id: "ws-1",
@@ -30,6 +30,7 @@ type AgentCompleted = {
 
 type AppServerEventHandlers = {
   onWorkspaceConnected?: (workspaceId: string) => void;
+  onThreadsRefreshed?: (workspaceId: string) => void;
   onThreadStarted?: (workspaceId: string, thread: Record<string, unknown>) => void;
   onThreadNameUpdated?: (
     workspaceId: string,
@@ -96,6 +97,7 @@ export const METHODS_ROUTED_IN_USE_APP_SERVER_EVENTS = [
   "account/updated",
   "codex/backgroundThread",
   "codex/connected",
+  "codex/threadsRefreshed",
   "error",
   "item/agentMessage/delta",
   "item/commandExecution/outputDelta",
@@ -134,6 +136,11 @@ export function useAppServerEvents(handlers: AppServerEventHandlers) {
         return;
       }
 
+      if (method === "codex/threadsRefreshed") {
+        handlers.onThreadsRefreshed?.(workspace_id);
+        return;
+      }
+
       const requestId = getAppServerRequestId(payload);
       const hasRequestId = requestId !== null;
 
@@ -178,7 +178,10 @@ export function useGitCommitController({
       commitSucceeded = true;
       setCommitMessage("");
       setCommitLoading(false);
-      await pushGit(activeWorkspace.id);
+      const pushResult = await pushGit(activeWorkspace.id);
+      if (pushResult.rejected) {
+        throw new Error(`Push rejected: ${pushResult.summary}`);
+      }
       refreshGitStatus();
       refreshGitLog?.();
     } catch (error) {
@@ -274,7 +277,10 @@ export function useGitCommitController({
     setPushLoading(true);
     setPushError(null);
     try {
-      await pushGit(activeWorkspace.id);
+      const pushResult = await pushGit(activeWorkspace.id);
+      if (pushResult.rejected) {
+        throw new Error(`Push rejected: ${pushResult.summary}`);
+      }
       setPullError(null);
       refreshGitStatus();
       refreshGitLog?.();
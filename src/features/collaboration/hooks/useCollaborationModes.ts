@@ -129,6 +129,9 @@ export function useCollaborationModes({
             developerInstructions: developerInstructions
               ? String(developerInstructions)
               : null,
+            description:
+              typeof item.description === "string" ? item.description : null,
+            isDefault: item.isDefault === true,
             value: item as Record<string, unknown>,
           };
           return option;
@@ -137,6 +140,7 @@ export function useCollaborationModes({
       setModes(data);
       lastFetchedWorkspaceId.current = workspaceId;
       const preferredModeId =
+        data.find((mode) => mode.isDefault)?.id ??
         data.find(
           (mode) =>
             mode.id.trim().toLowerCase() === "default" ||
@@ -31,6 +31,7 @@ const baseSettings: AppSettings = {
   orbitRunnerName: null,
   orbitAutoStartRunner: false,
   keepDaemonRunningAfterAppClose: false,
+  autoConnectWorkspaces: true,
   orbitUseAccess: false,
   orbitAccessClientId: null,
   orbitAccessClientSecretRef: null,
@@ -114,6 +115,11 @@ const createDoctorResult = () => ({
   nodeOk: true,
   nodeVersion: null,
   nodeDetails: null,
+  binaryFound: true,
+  configPath: null,
+  configValid: true,
+  configDetails: null,
+  checks: [],
 });
 
 const createUpdateResult = () => ({
@@ -1156,6 +1156,11 @@ export function SettingsView({
           nodeOk: false,
           nodeVersion: null,
           nodeDetails: null,
+          binaryFound: false,
+          configPath: null,
+          configValid: false,
+          configDetails: null,
+          checks: [],
         },
       });
     }
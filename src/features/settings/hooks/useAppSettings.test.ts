@@ -149,6 +149,11 @@ describe("useAppSettings", () => {
       nodeOk: true,
       nodeVersion: "20.0.0",
       nodeDetails: null,
+      binaryFound: true,
+      configPath: null,
+      configValid: true,
+      configDetails: null,
+      checks: [],
     };
     runCodexDoctorMock.mockResolvedValue(response);
     const { result } = renderHook(() => useAppSettings());
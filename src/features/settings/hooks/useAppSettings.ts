@@ -37,6 +37,7 @@ function buildDefaultSettings(): AppSettings {
     orbitRunnerName: null,
     orbitAutoStartRunner: false,
     keepDaemonRunningAfterAppClose: false,
+    autoConnectWorkspaces: true,
     orbitUseAccess: false,
     orbitAccessClientId: null,
     orbitAccessClientSecretRef: null,
@@ -97,6 +98,11 @@ function buildDefaultSettings(): AppSettings {
     workspaceGroups: [],
     openAppTargets: DEFAULT_OPEN_APP_TARGETS,
     selectedOpenAppId: DEFAULT_OPEN_APP_ID,
+    maxTurnsPerThread: null,
+    fileListSkipDirs: [],
+    githubEnterpriseHosts: [],
+    defaultCopyAgentsMd: true,
+    worktreeCopyFiles: [],
   };
 }
 
@@ -265,13 +265,35 @@ export function useThreads({
     [handleAccountUpdated],
   );
 
+  const listThreadsForWorkspaceRef = useRef<
+    | ((workspace: WorkspaceInfo, options?: { preserveState?: boolean }) => Promise<void>)
+    | null
+  >(null);
+  const handleThreadsRefreshed = useCallback(
+    (workspaceId: string) => {
+      if (!activeWorkspace || activeWorkspace.id !== workspaceId) {
+        return;
+      }
+      void listThreadsForWorkspaceRef.current?.(activeWorkspace, {
+        preserveState: true,
+      });
+    },
+    [activeWorkspace],
+  );
+
   const handlers = useMemo(
     () => ({
       ...threadHandlers,
       onAccountUpdated: handleAccountUpdated,
       onAccountLoginCompleted: handleAccountLoginCompleted,
+      onThreadsRefreshed: handleThreadsRefreshed,
     }),
-    [threadHandlers, handleAccountUpdated, handleAccountLoginCompleted],
+    [
+      threadHandlers,
+      handleAccountUpdated,
+      handleAccountLoginCompleted,
+      handleThreadsRefreshed,
+    ],
   );
 
   useAppServerEvents(handlers);
@@ -301,6 +323,7 @@ export function useThreads({
     applyCollabThreadLinksFromThread,
     updateThreadParent,
   });
+  listThreadsForWorkspaceRef.current = listThreadsForWorkspace;
 
   const startThread = useCallback(async () => {
     if (!activeWorkspaceId) {
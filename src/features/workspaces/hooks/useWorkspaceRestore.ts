@@ -4,6 +4,7 @@ import type { WorkspaceInfo } from "../../../types";
 type WorkspaceRestoreOptions = {
   workspaces: WorkspaceInfo[];
   hasLoaded: boolean;
+  autoConnect?: boolean;
   connectWorkspace: (workspace: WorkspaceInfo) => Promise<void>;
   listThreadsForWorkspace: (
     workspace: WorkspaceInfo,
@@ -14,6 +15,7 @@ type WorkspaceRestoreOptions = {
 export function useWorkspaceRestore({
   workspaces,
   hasLoaded,
+  autoConnect = true,
   connectWorkspace,
   listThreadsForWorkspace,
 }: WorkspaceRestoreOptions) {
@@ -28,6 +30,10 @@ export function useWorkspaceRestore({
         return;
       }
       restoredWorkspaces.current.add(workspace.id);
+      const shouldConnect = autoConnect && workspace.autoConnect !== false;
+      if (!shouldConnect && !workspace.connected) {
+        return;
+      }
       void (async () => {
         try {
           if (!workspace.connected) {
@@ -39,5 +45,11 @@ export function useWorkspaceRestore({
         }
       })();
     });
-  }, [connectWorkspace, hasLoaded, listThreadsForWorkspace, workspaces]);
+  }, [
+    autoConnect,
+    connectWorkspace,
+    hasLoaded,
+    listThreadsForWorkspace,
+    workspaces,
+  ]);
 }
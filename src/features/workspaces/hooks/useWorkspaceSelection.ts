@@ -1,5 +1,6 @@
 import { useCallback } from "react";
 import * as Sentry from "@sentry/react";
+import { recordRecentWorkspace } from "../../../services/tauri";
 import type { WorkspaceInfo, WorkspaceSettings } from "../../../types";
 
 type UseWorkspaceSelectionOptions = {
@@ -49,6 +50,8 @@ export function useWorkspaceSelection({
       }
       setActiveWorkspaceId(workspaceId);
       if (didSwitch) {
+        // Recents are a convenience; a failed write must not block the switch.
+        void recordRecentWorkspace(workspaceId).catch(() => {});
         Sentry.metrics.count("workspace_switched", 1, {
           attributes: {
             workspace_id: workspaceId,
@@ -293,7 +293,7 @@ export function useWorkspaces(options: UseWorkspacesOptions = {}) {
       return null;
     }
     const trimmedName = options?.displayName?.trim() || null;
-    const copyAgentsMd = options?.copyAgentsMd ?? true;
+    const copyAgentsMd = options?.copyAgentsMd ?? null;
     onDebug?.({
       id: `${Date.now()}-client-add-worktree`,
       timestamp: Date.now(),
@@ -30,6 +30,7 @@ type UseWorktreePromptOptions = {
   onWorktreeCreated?: (worktree: WorkspaceInfo, parent: WorkspaceInfo) => Promise<void> | void;
   onCompactActivate?: () => void;
   onError?: (message: string) => void;
+  defaultCopyAgentsMd?: boolean;
 };
 
 type UseWorktreePromptResult = {
@@ -71,6 +72,7 @@ export function useWorktreePrompt({
   onWorktreeCreated,
   onCompactActivate,
   onError,
+  defaultCopyAgentsMd = true,
 }: UseWorktreePromptOptions): UseWorktreePromptResult {
   const [worktreePrompt, setWorktreePrompt] = useState<WorktreePromptState>(null);
 
@@ -84,7 +86,7 @@ export function useWorktreePrompt({
       name: "",
       branch: defaultBranch,
       branchWasEdited: false,
-      copyAgentsMd: true,
+      copyAgentsMd: defaultCopyAgentsMd,
       setupScript: savedSetupScript ?? "",
       savedSetupScript,
       isSubmitting: false,
@@ -92,7 +94,7 @@ export function useWorktreePrompt({
       error: null,
       scriptError: null,
     });
-  }, []);
+  }, [defaultCopyAgentsMd]);
 
   const updateName = useCallback((value: string) => {
     setWorktreePrompt((prev) => {
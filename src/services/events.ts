@@ -14,6 +14,30 @@ export type TerminalExitEvent = {
   terminalId: string;
 };
 
+export type SessionExitedEvent = {
+  workspaceId: string;
+  code: number | null;
+  clean: boolean;
+};
+
+export type GitStatusChangedEvent = {
+  workspaceId: string;
+};
+
+export type ThreadRenamedEvent = {
+  workspaceId: string;
+  threadId: string;
+  name: string;
+};
+
+export type WorkspaceFileTailEvent = {
+  workspaceId: string;
+  tailId: string;
+  path: string;
+  lines: string[];
+  reset: boolean;
+};
+
 type SubscriptionOptions = {
   onError?: (error: unknown) => void;
 };
@@ -86,6 +110,10 @@ const dictationDownloadHub = createEventHub<DictationModelStatus>("dictation-dow
 const dictationEventHub = createEventHub<DictationEvent>("dictation-event");
 const terminalOutputHub = createEventHub<TerminalOutputEvent>("terminal-output");
 const terminalExitHub = createEventHub<TerminalExitEvent>("terminal-exit");
+const sessionExitedHub = createEventHub<SessionExitedEvent>("session-exited");
+const gitStatusChangedHub = createEventHub<GitStatusChangedEvent>("git-status-changed");
+const threadRenamedHub = createEventHub<ThreadRenamedEvent>("thread-renamed");
+const workspaceFileTailHub = createEventHub<WorkspaceFileTailEvent>("workspace-file-tail");
 const updaterCheckHub = createEventHub<void>("updater-check");
 const menuNewAgentHub = createEventHub<void>("menu-new-agent");
 const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
@@ -146,6 +174,34 @@ export function subscribeTerminalExit(
   return terminalExitHub.subscribe(onEvent, options);
 }
 
+export function subscribeSessionExited(
+  onEvent: (event: SessionExitedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return sessionExitedHub.subscribe(onEvent, options);
+}
+
+export function subscribeGitStatusChanged(
+  onEvent: (event: GitStatusChangedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return gitStatusChangedHub.subscribe(onEvent, options);
+}
+
+export function subscribeThreadRenamed(
+  onEvent: (event: ThreadRenamedEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return threadRenamedHub.subscribe(onEvent, options);
+}
+
+export function subscribeWorkspaceFileTail(
+  onEvent: (event: WorkspaceFileTailEvent) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return workspaceFileTailHub.subscribe(onEvent, options);
+}
+
 export function subscribeUpdaterCheck(
   onEvent: () => void,
   options?: SubscriptionOptions,
@@ -203,6 +203,19 @@ describe("tauri invoke wrappers", () => {
 
     expect(invokeMock).toHaveBeenCalledWith("fetch_git", {
       workspaceId: "ws-7",
+      remote: null,
+    });
+  });
+
+  it("passes the selected remote to fetch_git", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockResolvedValueOnce({});
+
+    await fetchGit("ws-7", "upstream");
+
+    expect(invokeMock).toHaveBeenCalledWith("fetch_git", {
+      workspaceId: "ws-7",
+      remote: "upstream",
     });
   });
 
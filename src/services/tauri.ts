@@ -2,12 +2,18 @@ import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import type { Options as NotificationOptions } from "@tauri-apps/plugin-notification";
 import type {
+  AllThreadsResponse,
   AppSettings,
+  BuildInfo,
   CodexUpdateResult,
   CodexDoctorResult,
+  DaemonDoctorReport,
   DictationModelStatus,
   DictationSessionState,
+  ForkThreadToWorktreeResult,
+  LocalUsageRefreshResult,
   LocalUsageSnapshot,
+  MessageCostEstimate,
   OrbitConnectTestResult,
   OrbitDeviceCodeStart,
   OrbitRunnerStatus,
@@ -15,19 +21,39 @@ import type {
   OrbitSignOutResult,
   TcpDaemonStatus,
   TailscaleDaemonCommandPreview,
+  TailscaleRemoteHostCheck,
   TailscaleStatus,
   WorkspaceInfo,
   WorkspaceSettings,
+  WorktreeBaseDiff,
+  WorktreeFromStashResult,
 } from "../types";
 import type {
   GitFileDiff,
   GitFileStatus,
   GitCommitDiff,
   GitHubIssuesResponse,
+  GitHubPullRequestCheckout,
   GitHubPullRequestComment,
   GitHubPullRequestDiff,
+  GitHubPullRequestReviewThread,
   GitHubPullRequestsResponse,
+  GitHubRepoInfo,
+  GitLastCommit,
+  GitOperationProgress,
+  GitPatchApplyResult,
+  GitPatchExport,
+  GitIncomingChanges,
   GitLogResponse,
+  GitRepairLocksResult,
+  GitBranchPruneResult,
+  GitMergedBranches,
+  GitPushResult,
+  GitStatusDetailed,
+  GitRemote,
+  GitReflogEntry,
+  GitReflogRestoreResult,
+  LargeStagedFilesResponse,
   ReviewTarget,
 } from "../types";
 
@@ -77,14 +103,39 @@ export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
   }
 }
 
+// Changes whenever `listWorkspaces` would return something different.
+export async function workspacesEtag(): Promise<string> {
+  return invoke<string>("workspaces_etag");
+}
+
 export async function getCodexConfigPath(): Promise<string> {
   return invoke<string>("get_codex_config_path");
 }
 
+export type WorkspaceCodexConfigPath = {
+  codexHome: string;
+  configPath: string;
+};
+
+// Resolves the workspace's `codexHome` (inherited by worktrees), unlike
+// `getCodexConfigPath` which only knows the global CODEX_HOME.
+export async function getWorkspaceCodexConfigPath(
+  workspaceId: string,
+): Promise<WorkspaceCodexConfigPath> {
+  return invoke<WorkspaceCodexConfigPath>("get_workspace_codex_config_path", {
+    workspaceId,
+  });
+}
+
 export type TextFileResponse = {
   exists: boolean;
   content: string;
   truncated: boolean;
+  hash?: string | null;
+};
+
+export type FileWriteResponse = {
+  hash: string;
 };
 
 export type GlobalAgentsResponse = TextFileResponse;
@@ -102,29 +153,56 @@ async function fileRead(
   return invoke<TextFileResponse>("file_read", { scope, kind, workspaceId });
 }
 
+// With `expectedHash` (the `hash` from the last read) the write fails with a
+// "conflict:" error if the file changed on disk in the meantime.
 async function fileWrite(
   scope: FileScope,
   kind: FileKind,
   content: string,
   workspaceId?: string,
-): Promise<void> {
-  return invoke("file_write", { scope, kind, workspaceId, content });
+  expectedHash?: string | null,
+): Promise<FileWriteResponse> {
+  return invoke<FileWriteResponse>("file_write", {
+    scope,
+    kind,
+    workspaceId,
+    content,
+    ...(expectedHash ? { expectedHash } : {}),
+  });
 }
 
 export async function readGlobalAgentsMd(): Promise<GlobalAgentsResponse> {
   return fileRead("global", "agents");
 }
 
-export async function writeGlobalAgentsMd(content: string): Promise<void> {
-  return fileWrite("global", "agents", content);
+export async function writeGlobalAgentsMd(
+  content: string,
+  expectedHash?: string | null,
+): Promise<FileWriteResponse> {
+  return fileWrite("global", "agents", content, undefined, expectedHash);
 }
 
 export async function readGlobalCodexConfigToml(): Promise<GlobalCodexConfigResponse> {
   return fileRead("global", "config");
 }
 
-export async function writeGlobalCodexConfigToml(content: string): Promise<void> {
-  return fileWrite("global", "config", content);
+export async function writeGlobalCodexConfigToml(
+  content: string,
+  expectedHash?: string | null,
+): Promise<FileWriteResponse> {
+  return fileWrite("global", "config", content, undefined, expectedHash);
+}
+
+export type AgentsMdFile = {
+  path: string;
+  scope: FileScope;
+  content: string;
+};
+
+export async function getEffectiveAgentsMd(
+  workspaceId: string,
+): Promise<AgentsMdFile[]> {
+  return invoke<AgentsMdFile[]>("get_effective_agents_md", { workspaceId });
 }
 
 export async function getConfigModel(workspaceId: string): Promise<string | null> {
@@ -139,6 +217,18 @@ export async function getConfigModel(workspaceId: string): Promise<string | null
   return trimmed.length > 0 ? trimmed : null;
 }
 
+export async function estimateMessageCost(
+  workspaceId: string,
+  text: string,
+  model?: string | null,
+): Promise<MessageCostEstimate> {
+  return invoke<MessageCostEstimate>("estimate_message_cost", {
+    workspaceId,
+    text,
+    model: model ?? null,
+  });
+}
+
 export async function addWorkspace(
   path: string,
   codex_bin: string | null,
@@ -162,13 +252,66 @@ export async function addClone(
   });
 }
 
+export type WorktreePathPreview = {
+  sanitizedName: string;
+  path: string;
+};
+
+export async function previewWorktreePath(
+  parentId: string,
+  branch: string,
+): Promise<WorktreePathPreview> {
+  return invoke<WorktreePathPreview>("preview_worktree_path", { parentId, branch });
+}
+
 export async function addWorktree(
   parentId: string,
   branch: string,
   name: string | null,
-  copyAgentsMd = true,
+  copyAgentsMd: boolean | null = null,
+  baseRef: string | null = null,
 ): Promise<WorkspaceInfo> {
-  return invoke<WorkspaceInfo>("add_worktree", { parentId, branch, name, copyAgentsMd });
+  return invoke<WorkspaceInfo>("add_worktree", {
+    parentId,
+    branch,
+    name,
+    baseRef,
+    copyAgentsMd,
+  });
+}
+
+export async function forkThreadToWorktree(
+  parentId: string,
+  threadId: string,
+  branch: string,
+  name: string | null,
+  copyAgentsMd: boolean | null = null,
+): Promise<ForkThreadToWorktreeResult> {
+  return invoke<ForkThreadToWorktreeResult>("fork_thread_to_worktree", {
+    parentId,
+    threadId,
+    branch,
+    name,
+    copyAgentsMd,
+  });
+}
+
+export async function worktreeFromStash(
+  parentId: string,
+  stashIndex: number,
+  branch: string,
+  name: string | null,
+  copyAgentsMd: boolean | null = null,
+  stash: string | null = null,
+): Promise<WorktreeFromStashResult> {
+  return invoke<WorktreeFromStashResult>("worktree_from_stash", {
+    parentId,
+    stashIndex,
+    stash,
+    branch,
+    name,
+    copyAgentsMd,
+  });
 }
 
 export type WorktreeSetupStatus = {
@@ -193,6 +336,31 @@ export async function updateWorkspaceSettings(
   return invoke<WorkspaceInfo>("update_workspace_settings", { id, settings });
 }
 
+export type CodexToolName = "web_search_request" | "view_image_tool" | "shell_tool";
+
+export type CodexToolState = {
+  name: CodexToolName;
+  enabled: boolean;
+  configured: boolean;
+};
+
+export async function getCodexTools(workspaceId: string): Promise<CodexToolState[]> {
+  return invoke<CodexToolState[]>("get_codex_tools", { workspaceId });
+}
+
+// Writes the flag to the workspace's Codex config and restarts its session.
+export async function setCodexToolEnabled(
+  workspaceId: string,
+  tool: CodexToolName,
+  enabled: boolean,
+): Promise<CodexToolState[]> {
+  return invoke<CodexToolState[]>("set_codex_tool_enabled", {
+    workspaceId,
+    tool,
+    enabled,
+  });
+}
+
 export async function updateWorkspaceCodexBin(
   id: string,
   codex_bin: string | null,
@@ -200,6 +368,78 @@ export async function updateWorkspaceCodexBin(
   return invoke<WorkspaceInfo>("update_workspace_codex_bin", { id, codex_bin });
 }
 
+export async function setWorkspacePinned(
+  id: string,
+  pinned: boolean,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("set_workspace_pinned", { id, pinned });
+}
+
+// Off keeps the workspace from connecting on startup; connecting it by hand
+// still works.
+export async function setWorkspaceAutoConnect(
+  id: string,
+  autoConnect: boolean,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("set_workspace_auto_connect", { id, autoConnect });
+}
+
+export type WorkspaceTimelineEntry = {
+  kind:
+    | "commit"
+    | "branch-switch"
+    | "git"
+    | "session-started"
+    | "session-exited"
+    | "turn-completed";
+  timestamp: number;
+  summary: string;
+  threadId: string | null;
+  sha: string | null;
+};
+
+export async function getWorkspaceTimeline(
+  workspaceId: string,
+  limit?: number,
+): Promise<WorkspaceTimelineEntry[]> {
+  return invoke<WorkspaceTimelineEntry[]>("workspace_timeline", {
+    workspaceId,
+    limit: limit ?? null,
+  });
+}
+
+export type SessionLastError = {
+  kind: "spawn" | "protocol" | "exit";
+  message: string;
+  timestamp: number;
+};
+
+export async function sessionLastError(
+  workspaceId: string,
+): Promise<SessionLastError | null> {
+  return invoke<SessionLastError | null>("session_last_error", { workspaceId });
+}
+
+export async function setDefaultEffort(
+  id: string,
+  effort: string | null,
+  model?: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("set_default_effort", {
+    id,
+    effort,
+    model: model ?? null,
+  });
+}
+
+// Validated against the connected session's modes; null clears the default.
+export async function setDefaultCollaborationMode(
+  id: string,
+  mode: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("set_default_collaboration_mode", { id, mode });
+}
+
 export async function removeWorkspace(id: string): Promise<void> {
   return invoke("remove_workspace", { id });
 }
@@ -215,6 +455,13 @@ export async function renameWorktree(
   return invoke<WorkspaceInfo>("rename_worktree", { id, branch });
 }
 
+export async function moveWorktree(
+  id: string,
+  destination: string,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("move_worktree", { id, destination });
+}
+
 export async function renameWorktreeUpstream(
   id: string,
   oldBranch: string,
@@ -227,6 +474,10 @@ export async function applyWorktreeChanges(workspaceId: string): Promise<void> {
   return invoke("apply_worktree_changes", { workspaceId });
 }
 
+export async function revealWorkspacePath(workspaceId: string): Promise<void> {
+  return invoke("reveal_workspace_path", { workspaceId });
+}
+
 export async function openWorkspaceIn(
   path: string,
   options: {
@@ -251,6 +502,34 @@ export async function connectWorkspace(id: string): Promise<void> {
   return invoke("connect_workspace", { id });
 }
 
+export type RecentWorkspaceInfo = {
+  id: string;
+  name: string;
+  path: string;
+  openedAt: number;
+};
+
+// Most recently opened first; survives restarts.
+export async function listRecentWorkspaces(): Promise<RecentWorkspaceInfo[]> {
+  return invoke<RecentWorkspaceInfo[]>("list_recent_workspaces");
+}
+
+export async function recordRecentWorkspace(id: string): Promise<void> {
+  return invoke("record_recent_workspace", { id });
+}
+
+export type WorkspaceConnectResult = {
+  ok: boolean;
+  alreadyConnected: boolean;
+  error: string | null;
+};
+
+export async function connectAllWorkspaces(): Promise<
+  Record<string, WorkspaceConnectResult>
+> {
+  return invoke("connect_all_workspaces");
+}
+
 export async function startThread(workspaceId: string) {
   return invoke<any>("start_thread", { workspaceId });
 }
@@ -259,10 +538,79 @@ export async function forkThread(workspaceId: string, threadId: string) {
   return invoke<any>("fork_thread", { workspaceId, threadId });
 }
 
+export type BulkArchiveThreadsResult = {
+  dryRun: boolean;
+  count: number;
+  archived: string[];
+  failed: { threadId: string; error: string }[];
+};
+
+// `inactiveBefore` is unix seconds, matching a thread's `updatedAt`.
+export async function bulkArchiveThreads(
+  workspaceId: string,
+  inactiveBefore: number,
+  dryRun = false,
+): Promise<BulkArchiveThreadsResult> {
+  return invoke<BulkArchiveThreadsResult>("bulk_archive_threads", {
+    workspaceId,
+    inactiveBefore,
+    dryRun,
+  });
+}
+
 export async function compactThread(workspaceId: string, threadId: string) {
   return invoke<any>("compact_thread", { workspaceId, threadId });
 }
 
+export type ThreadInfo = {
+  id: string;
+  name: string | null;
+  preview: string | null;
+  cwd: string | null;
+  createdAt: number | null;
+  updatedAt: number | null;
+  turnCount: number | null;
+  model: string | null;
+  modelProvider: string | null;
+};
+
+export async function getThreadInfo(
+  workspaceId: string,
+  threadId: string,
+): Promise<ThreadInfo> {
+  return invoke<ThreadInfo>("get_thread_info", { workspaceId, threadId });
+}
+
+export type SessionCapabilities = {
+  codexVersion: string | null;
+  // null when the session didn't answer the probe for an operation.
+  operations: Record<string, boolean | null>;
+};
+
+// Probed once per connected session; unanswered probes are retried.
+export async function sessionCapabilities(
+  workspaceId: string,
+): Promise<SessionCapabilities> {
+  return invoke<SessionCapabilities>("session_capabilities", { workspaceId });
+}
+
+export type SessionPing = {
+  latencyMs: number;
+};
+
+// Round-trip through the codex session only; model time is not included.
+export async function sessionPing(workspaceId: string): Promise<SessionPing> {
+  return invoke<SessionPing>("session_ping", { workspaceId });
+}
+
+// Clears every turn but keeps the thread id and name; nothing is summarized.
+export async function resetThreadContext(
+  workspaceId: string,
+  threadId: string,
+): Promise<{ threadId: string; clearedTurns: number; thread: any }> {
+  return invoke("reset_thread_context", { workspaceId, threadId });
+}
+
 export async function sendUserMessage(
   workspaceId: string,
   threadId: string,
@@ -290,6 +638,48 @@ export async function sendUserMessage(
   return invoke("send_user_message", payload);
 }
 
+export type QueuedUserMessage = {
+  queued: boolean;
+  messageId?: string;
+  position?: number;
+  response?: unknown;
+};
+
+// Sends now if the thread is idle, otherwise when its running turn finishes.
+export async function queueUserMessage(
+  workspaceId: string,
+  threadId: string,
+  text: string,
+  options?: {
+    model?: string | null;
+    effort?: string | null;
+    accessMode?: "read-only" | "current" | "full-access";
+    images?: string[];
+    collaborationMode?: Record<string, unknown> | null;
+  },
+): Promise<QueuedUserMessage> {
+  const payload: Record<string, unknown> = {
+    workspaceId,
+    threadId,
+    text,
+    model: options?.model ?? null,
+    effort: options?.effort ?? null,
+    accessMode: options?.accessMode ?? null,
+    images: options?.images ?? null,
+  };
+  if (options?.collaborationMode) {
+    payload.collaborationMode = options.collaborationMode;
+  }
+  return invoke<QueuedUserMessage>("queue_user_message", payload);
+}
+
+export async function cancelQueuedMessage(
+  workspaceId: string,
+  messageId: string,
+): Promise<{ messageId: string; cancelled: boolean }> {
+  return invoke("cancel_queued_message", { workspaceId, messageId });
+}
+
 export async function interruptTurn(
   workspaceId: string,
   threadId: string,
@@ -339,6 +729,33 @@ export async function respondToServerRequest(
   });
 }
 
+export type WorkspaceActivity = {
+  workspaceId: string;
+  state: "idle" | "running" | "awaiting-approval";
+  runningThreads: string[];
+  pendingApprovals: number;
+};
+
+export async function getWorkspaceActivity(): Promise<WorkspaceActivity[]> {
+  return invoke<WorkspaceActivity[]>("workspace_activity");
+}
+
+export type PendingApproval = {
+  workspaceId: string;
+  requestId: number | string;
+  method: string;
+  threadId: string | null;
+  params: Record<string, unknown> | null;
+};
+
+export async function listPendingApprovals(
+  workspaceId?: string | null,
+): Promise<PendingApproval[]> {
+  return invoke<PendingApproval[]>("list_pending_approvals", {
+    workspaceId: workspaceId ?? null,
+  });
+}
+
 export async function respondToUserInputRequest(
   workspaceId: string,
   requestId: number | string,
@@ -369,6 +786,46 @@ export async function getGitStatus(workspace_id: string): Promise<{
   return invoke("get_git_status", { workspaceId: workspace_id });
 }
 
+export async function getGitStatusDetailed(
+  workspaceId: string,
+): Promise<GitStatusDetailed> {
+  return invoke<GitStatusDetailed>("get_git_status_detailed", { workspaceId });
+}
+
+export async function isWorkingTreeClean(
+  workspaceId: string,
+  includeUntracked = true,
+): Promise<{ clean: boolean }> {
+  return invoke("is_working_tree_clean", { workspaceId, includeUntracked });
+}
+
+export type WorktreeDirtySummary = {
+  path: string;
+  branch: string | null;
+  head: string | null;
+  workspaceId: string | null;
+  isCurrent: boolean;
+  dirtyCount: number | null;
+  error?: string | null;
+};
+
+// Every worktree of the workspace's repo with its branch and uncommitted
+// file count.
+export async function repoWorktreesDiffSummary(
+  workspaceId: string,
+): Promise<{ worktrees: WorktreeDirtySummary[]; totalDirtyCount: number }> {
+  return invoke("repo_worktrees_diff_summary", { workspaceId });
+}
+
+// Pushes `git-status-changed` events for the workspace instead of polling;
+// pass `enabled: false` to stop.
+export async function watchGitStatus(
+  workspaceId: string,
+  enabled = true,
+): Promise<{ watching: boolean }> {
+  return invoke("watch_git_status", { workspaceId, enabled });
+}
+
 export async function listGitRoots(
   workspace_id: string,
   depth: number,
@@ -378,15 +835,54 @@ export async function listGitRoots(
 
 export async function getGitDiffs(
   workspace_id: string,
+  includeUntracked = true,
+  maxDiffBytes?: number,
 ): Promise<GitFileDiff[]> {
-  return invoke("get_git_diffs", { workspaceId: workspace_id });
+  return invoke("get_git_diffs", {
+    workspaceId: workspace_id,
+    includeUntracked,
+    maxDiffBytes: maxDiffBytes ?? null,
+  });
+}
+
+export async function getGitFileDiff(
+  workspaceId: string,
+  path: string,
+  staged?: boolean,
+): Promise<GitFileDiff | null> {
+  return invoke("get_git_file_diff", {
+    workspaceId,
+    path,
+    staged: staged ?? null,
+  });
 }
 
 export async function getGitLog(
   workspace_id: string,
   limit = 40,
+  includeStats = false,
 ): Promise<GitLogResponse> {
-  return invoke("get_git_log", { workspaceId: workspace_id, limit });
+  return invoke("get_git_log", {
+    workspaceId: workspace_id,
+    limit,
+    includeStats,
+  });
+}
+
+export async function worktreeDiffVsBase(workspaceId: string): Promise<WorktreeBaseDiff> {
+  return invoke("worktree_diff_vs_base", { workspaceId });
+}
+
+export async function previewIncomingChanges(
+  workspaceId: string,
+  options?: { fetch?: boolean; summaryOnly?: boolean; maxDiffBytes?: number | null },
+): Promise<GitIncomingChanges> {
+  return invoke("preview_incoming_changes", {
+    workspaceId,
+    fetch: options?.fetch ?? true,
+    summaryOnly: options?.summaryOnly ?? false,
+    maxDiffBytes: options?.maxDiffBytes ?? null,
+  });
 }
 
 export async function getGitCommitDiff(
@@ -396,6 +892,69 @@ export async function getGitCommitDiff(
   return invoke("get_git_commit_diff", { workspaceId: workspace_id, sha });
 }
 
+// `stash` is an index, a `stash@{n}` ref or the stash's message; messages are
+// resolved when the call runs, so they stay valid as indices shift.
+export async function getStashDiff(
+  workspaceId: string,
+  stash: string | number,
+): Promise<GitCommitDiff[]> {
+  return invoke("get_stash_diff", { workspaceId, stash: String(stash) });
+}
+
+export async function exportGitPatch(
+  workspaceId: string,
+  revision: string,
+): Promise<GitPatchExport> {
+  return invoke("export_git_patch", { workspaceId, revision });
+}
+
+export async function applyGitPatch(
+  workspaceId: string,
+  patch: string,
+  options?: { check?: boolean; threeWay?: boolean },
+): Promise<GitPatchApplyResult> {
+  return invoke("apply_git_patch", {
+    workspaceId,
+    patch,
+    check: options?.check ?? false,
+    threeWay: options?.threeWay ?? false,
+  });
+}
+
+export async function getLastCommit(workspaceId: string): Promise<GitLastCommit> {
+  return invoke("get_last_commit", { workspaceId });
+}
+
+export async function getGitOperationProgress(
+  workspaceId: string,
+): Promise<GitOperationProgress> {
+  return invoke("git_operation_progress", { workspaceId });
+}
+
+export async function gitReflog(
+  workspaceId: string,
+  options?: { refName?: string | null; limit?: number | null },
+): Promise<GitReflogEntry[]> {
+  return invoke("git_reflog", {
+    workspaceId,
+    refName: options?.refName ?? null,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function restoreFromReflog(
+  workspaceId: string,
+  sha: string,
+  options?: { branch?: string | null; confirm?: boolean },
+): Promise<GitReflogRestoreResult> {
+  return invoke("restore_from_reflog", {
+    workspaceId,
+    sha,
+    branch: options?.branch ?? null,
+    confirm: options?.confirm ?? false,
+  });
+}
+
 export async function getGitRemote(workspace_id: string): Promise<string | null> {
   return invoke("get_git_remote", { workspaceId: workspace_id });
 }
@@ -427,22 +986,63 @@ export async function commitGit(
   return invoke("commit_git", { workspaceId, message });
 }
 
-export async function pushGit(workspaceId: string): Promise<void> {
-  return invoke("push_git", { workspaceId });
+export async function checkLargeStagedFiles(
+  workspaceId: string,
+  thresholdBytes?: number | null,
+): Promise<LargeStagedFilesResponse> {
+  return invoke("check_large_staged_files", {
+    workspaceId,
+    thresholdBytes: thresholdBytes ?? null,
+  });
+}
+
+export async function gitRepairLocks(
+  workspaceId: string,
+  remove = false,
+): Promise<GitRepairLocksResult> {
+  return invoke("git_repair_locks", { workspaceId, remove });
+}
+
+export async function pushGit(
+  workspaceId: string,
+  force?: "with-lease" | "force" | null,
+  remote?: string | null,
+): Promise<GitPushResult> {
+  return invoke("push_git", {
+    workspaceId,
+    force: force ?? null,
+    remote: remote ?? null,
+  });
+}
+
+export async function pullGit(
+  workspaceId: string,
+  remote?: string | null,
+): Promise<void> {
+  return invoke("pull_git", { workspaceId, remote: remote ?? null });
 }
 
-export async function pullGit(workspaceId: string): Promise<void> {
-  return invoke("pull_git", { workspaceId });
+export async function fetchGit(
+  workspaceId: string,
+  remote?: string | null,
+): Promise<void> {
+  return invoke("fetch_git", { workspaceId, remote: remote ?? null });
 }
 
-export async function fetchGit(workspaceId: string): Promise<void> {
-  return invoke("fetch_git", { workspaceId });
+export async function listGitRemotes(workspaceId: string): Promise<GitRemote[]> {
+  return invoke("list_git_remotes", { workspaceId });
 }
 
 export async function syncGit(workspaceId: string): Promise<void> {
   return invoke("sync_git", { workspaceId });
 }
 
+export async function getGitHubRepoInfo(
+  workspaceId: string,
+): Promise<GitHubRepoInfo> {
+  return invoke("get_github_repo_info", { workspaceId });
+}
+
 export async function getGitHubIssues(
   workspace_id: string,
 ): Promise<GitHubIssuesResponse> {
@@ -475,6 +1075,28 @@ export async function getGitHubPullRequestComments(
   });
 }
 
+export async function getGitHubPullRequestReviewThreads(
+  workspace_id: string,
+  prNumber: number,
+): Promise<GitHubPullRequestReviewThread[]> {
+  return invoke("get_github_pull_request_review_threads", {
+    workspaceId: workspace_id,
+    prNumber,
+  });
+}
+
+export async function checkoutGitHubPullRequest(
+  workspaceId: string,
+  prNumber: number,
+  update = false,
+): Promise<GitHubPullRequestCheckout> {
+  return invoke("checkout_github_pull_request", {
+    workspaceId,
+    prNumber,
+    update,
+  });
+}
+
 export async function localUsageSnapshot(
   days?: number,
   workspacePath?: string | null,
@@ -506,10 +1128,26 @@ export async function localUsageSnapshot(
   }
 }
 
+export async function refreshLocalUsage(
+  days?: number,
+  workspacePath?: string | null,
+): Promise<LocalUsageRefreshResult> {
+  const payload: { days: number; workspacePath?: string } = { days: days ?? 30 };
+  if (workspacePath) {
+    payload.workspacePath = workspacePath;
+  }
+  return invoke<LocalUsageRefreshResult>("refresh_local_usage", payload);
+}
+
 export async function getModelList(workspaceId: string) {
   return invoke<any>("model_list", { workspaceId });
 }
 
+// Most recently used models for the workspace, most recent first.
+export async function getRecentModels(workspaceId: string): Promise<string[]> {
+  return invoke<string[]>("recent_models", { workspaceId });
+}
+
 export async function generateRunMetadata(workspaceId: string, prompt: string) {
   return invoke<{ title: string; worktreeName: string }>("generate_run_metadata", {
     workspaceId,
@@ -521,6 +1159,28 @@ export async function getCollaborationModes(workspaceId: string) {
   return invoke<any>("collaboration_mode_list", { workspaceId });
 }
 
+export async function setThreadCollaborationMode(
+  workspaceId: string,
+  threadId: string,
+  collaborationMode: Record<string, unknown> | null,
+) {
+  return invoke<{
+    threadId: string;
+    collaborationMode: Record<string, unknown> | null;
+  }>("set_thread_collaboration_mode", {
+    workspaceId,
+    threadId,
+    collaborationMode,
+  });
+}
+
+export async function resetThreadTurnCount(workspaceId: string, threadId: string) {
+  return invoke<{ threadId: string; previousTurnCount: number }>(
+    "reset_thread_turn_count",
+    { workspaceId, threadId },
+  );
+}
+
 export async function getAccountRateLimits(workspaceId: string) {
   return invoke<any>("account_rate_limits", { workspaceId });
 }
@@ -529,6 +1189,20 @@ export async function getAccountInfo(workspaceId: string) {
   return invoke<any>("account_read", { workspaceId });
 }
 
+export type CodexLoginStatus = {
+  authenticated: boolean;
+  authMode: string | null;
+  email: string | null;
+  planType: string | null;
+  requiresOpenaiAuth: boolean | null;
+};
+
+export async function getCodexLoginStatus(
+  workspaceId: string,
+): Promise<CodexLoginStatus> {
+  return invoke<CodexLoginStatus>("codex_login_status", { workspaceId });
+}
+
 export async function runCodexLogin(workspaceId: string) {
   return invoke<{ loginId: string; authUrl: string; raw?: unknown }>("codex_login", {
     workspaceId,
@@ -633,6 +1307,21 @@ export async function updateAppSettings(settings: AppSettings): Promise<AppSetti
   return invoke<AppSettings>("update_app_settings", { settings });
 }
 
+export type SettingsValidationIssue = {
+  field: string;
+  kind: "unknownField" | "typeMismatch" | "outOfRange" | "missingPlaceholder";
+  message: string;
+};
+
+export async function validateAppSettings(
+  settings: unknown,
+): Promise<{ valid: boolean; issues: SettingsValidationIssue[] }> {
+  return invoke<{ valid: boolean; issues: SettingsValidationIssue[] }>(
+    "validate_app_settings",
+    { settings },
+  );
+}
+
 export async function orbitConnectTest(): Promise<OrbitConnectTestResult> {
   return invoke<OrbitConnectTestResult>("orbit_connect_test");
 }
@@ -665,6 +1354,14 @@ export async function tailscaleStatus(): Promise<TailscaleStatus> {
   return invoke<TailscaleStatus>("tailscale_status");
 }
 
+export async function tailscaleVerifyRemoteHost(
+  host?: string | null,
+): Promise<TailscaleRemoteHostCheck> {
+  return invoke<TailscaleRemoteHostCheck>("tailscale_verify_remote_host", {
+    host: host ?? null,
+  });
+}
+
 export async function tailscaleDaemonCommandPreview(): Promise<TailscaleDaemonCommandPreview> {
   return invoke<TailscaleDaemonCommandPreview>("tailscale_daemon_command_preview");
 }
@@ -699,6 +1396,25 @@ export async function runCodexDoctor(
   return invoke<CodexDoctorResult>("codex_doctor", { codexBin, codexArgs });
 }
 
+export async function getDefaultCodexBin(): Promise<string | null> {
+  return invoke<string | null>("get_default_codex_bin");
+}
+
+// Rejects a binary that fails the codex doctor; pass null to use `codex` on PATH.
+export async function setDefaultCodexBin(
+  codexBin: string | null,
+): Promise<CodexDoctorResult> {
+  return invoke<CodexDoctorResult>("set_default_codex_bin", { codexBin });
+}
+
+export async function runDaemonDoctor(): Promise<DaemonDoctorReport> {
+  return invoke<DaemonDoctorReport>("daemon_doctor");
+}
+
+export async function getVersion(): Promise<BuildInfo> {
+  return invoke<BuildInfo>("version");
+}
+
 export async function runCodexUpdate(
   codexBin: string | null,
   codexArgs: string | null,
@@ -710,22 +1426,131 @@ export async function getWorkspaceFiles(workspaceId: string) {
   return invoke<string[]>("list_workspace_files", { workspaceId });
 }
 
+export type WorkspaceGrepMatch = {
+  path: string;
+  line: number;
+  column: number;
+  snippet: string;
+};
+
+export async function grepWorkspace(
+  workspaceId: string,
+  query: string,
+  options?: {
+    isRegex?: boolean;
+    caseSensitive?: boolean;
+    globs?: string[];
+    maxResults?: number;
+    requestId?: string;
+  },
+): Promise<{ matches: WorkspaceGrepMatch[]; truncated: boolean; cancelled: boolean }> {
+  return invoke<{ matches: WorkspaceGrepMatch[]; truncated: boolean; cancelled: boolean }>(
+    "grep_workspace",
+    {
+      workspaceId,
+      query,
+      isRegex: options?.isRegex ?? null,
+      caseSensitive: options?.caseSensitive ?? null,
+      globs: options?.globs ?? null,
+      maxResults: options?.maxResults ?? null,
+      requestId: options?.requestId ?? null,
+    },
+  );
+}
+
+export async function cancelWorkspaceWalk(
+  requestId: string,
+): Promise<{ cancelled: boolean }> {
+  return invoke<{ cancelled: boolean }>("cancel_workspace_walk", { requestId });
+}
+
+export type WorkspaceFileResponse = {
+  content: string;
+  truncated: boolean;
+  mimeType: string;
+  language: string | null;
+};
+
 export async function readWorkspaceFile(
   workspaceId: string,
   path: string,
-): Promise<{ content: string; truncated: boolean }> {
-  return invoke<{ content: string; truncated: boolean }>("read_workspace_file", {
+): Promise<WorkspaceFileResponse> {
+  return invoke<WorkspaceFileResponse>("read_workspace_file", {
     workspaceId,
     path,
   });
 }
 
+export async function renameWorkspaceFile(
+  workspaceId: string,
+  source: string,
+  destination: string,
+  force = false,
+): Promise<{ path: string; staged: boolean }> {
+  return invoke<{ path: string; staged: boolean }>("rename_workspace_file", {
+    workspaceId,
+    source,
+    destination,
+    force,
+  });
+}
+
+export async function createWorkspaceFile(
+  workspaceId: string,
+  path: string,
+  content = "",
+): Promise<string> {
+  return invoke<string>("create_workspace_file", { workspaceId, path, content });
+}
+
+export async function deleteWorkspaceFile(
+  workspaceId: string,
+  path: string,
+  recursive = false,
+): Promise<{ staged: boolean }> {
+  return invoke<{ staged: boolean }>("delete_workspace_file", {
+    workspaceId,
+    path,
+    recursive,
+  });
+}
+
+export type WorkspaceFileTailStart = {
+  tailId: string;
+  path: string;
+  lines: string[];
+};
+
+// Returns the last `lines` lines, then streams appended lines as
+// `workspace-file-tail` events until `untailWorkspaceFile` is called.
+export async function tailWorkspaceFile(
+  workspaceId: string,
+  path: string,
+  lines?: number | null,
+): Promise<WorkspaceFileTailStart> {
+  return invoke<WorkspaceFileTailStart>("tail_workspace_file", {
+    workspaceId,
+    path,
+    lines,
+  });
+}
+
+export async function untailWorkspaceFile(
+  tailId: string,
+): Promise<{ stopped: boolean }> {
+  return invoke<{ stopped: boolean }>("untail_workspace_file", { tailId });
+}
+
 export async function readAgentMd(workspaceId: string): Promise<AgentMdResponse> {
   return fileRead("workspace", "agents", workspaceId);
 }
 
-export async function writeAgentMd(workspaceId: string, content: string): Promise<void> {
-  return fileWrite("workspace", "agents", content, workspaceId);
+export async function writeAgentMd(
+  workspaceId: string,
+  content: string,
+  expectedHash?: string | null,
+): Promise<FileWriteResponse> {
+  return fileWrite("workspace", "agents", content, workspaceId, expectedHash);
 }
 
 export async function listGitBranches(workspaceId: string) {
@@ -740,6 +1565,31 @@ export async function createGitBranch(workspaceId: string, name: string) {
   return invoke("create_git_branch", { workspaceId, name });
 }
 
+export async function listMergedBranches(
+  workspaceId: string,
+): Promise<GitMergedBranches> {
+  return invoke("list_merged_branches", { workspaceId });
+}
+
+export async function pruneMergedBranches(
+  workspaceId: string,
+  branches: string[],
+): Promise<GitBranchPruneResult[]> {
+  return invoke("prune_merged_branches", { workspaceId, branches });
+}
+
+export async function createAndPublishBranch(
+  workspaceId: string,
+  name: string,
+  remote?: string | null,
+): Promise<GitPushResult> {
+  return invoke("create_and_publish_branch", {
+    workspaceId,
+    name,
+    remote: remote ?? null,
+  });
+}
+
 function withModelId(modelId?: string | null) {
   return modelId ? { modelId } : {};
 }
@@ -831,13 +1681,39 @@ export async function closeTerminalSession(
   return invoke("terminal_close", { workspaceId, terminalId });
 }
 
+export type ThreadStatusFilter = "active" | "archived" | "all" | "running";
+
+// `status` defaults to "active" (non-archived threads) on the backend.
 export async function listThreads(
   workspaceId: string,
   cursor?: string | null,
   limit?: number | null,
   sortKey?: "created_at" | "updated_at" | null,
+  status?: ThreadStatusFilter | null,
 ) {
-  return invoke<any>("list_threads", { workspaceId, cursor, limit, sortKey });
+  return invoke<any>("list_threads", {
+    workspaceId,
+    cursor,
+    limit,
+    sortKey,
+    status,
+  });
+}
+
+export async function refreshThreads(
+  workspaceId: string,
+  limit?: number | null,
+  sortKey?: "created_at" | "updated_at" | null,
+) {
+  return invoke<any>("refresh_threads", { workspaceId, limit, sortKey });
+}
+
+export async function listAllThreads(
+  cursor?: string | null,
+  limit?: number | null,
+  connect = false,
+): Promise<AllThreadsResponse> {
+  return invoke<AllThreadsResponse>("list_all_threads", { cursor, limit, connect });
 }
 
 export async function listMcpServerStatus(
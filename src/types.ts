@@ -8,6 +8,13 @@ export type WorkspaceSettings = {
   launchScript?: string | null;
   launchScripts?: LaunchScriptEntry[] | null;
   worktreeSetupScript?: string | null;
+  defaultEffort?: string | null;
+  defaultCollaborationMode?: string | null;
+  // Write-only: never returned by the backend. Omit to keep the stored
+  // token, send "" to clear it.
+  githubToken?: string | null;
+  // Read-only here: set through setThreadCollaborationMode.
+  threadCollaborationModes?: Record<string, unknown>;
 };
 
 export type LaunchScriptIconId =
@@ -56,9 +63,39 @@ export type WorkspaceInfo = {
   kind?: WorkspaceKind;
   parentId?: string | null;
   worktree?: WorktreeInfo | null;
+  pinned?: boolean;
+  autoConnect?: boolean;
   settings: WorkspaceSettings;
 };
 
+export type AllThreadsResponse = {
+  data: { workspaceId: string; thread: Record<string, unknown> }[];
+  nextCursor: string | null;
+  skippedWorkspaceIds: string[];
+};
+
+export type MessageCostEstimate = {
+  model: string | null;
+  tokenizer: string;
+  inputTokens: number;
+  inputPricePerMillion: number | null;
+  estimatedCostUsd: number | null;
+};
+
+export type ForkThreadToWorktreeResult = {
+  workspace: WorkspaceInfo;
+  threadId: string;
+};
+
+export type WorktreeFromStashResult = {
+  workspace: WorkspaceInfo;
+  stashRef: string;
+  applied: boolean;
+  conflicts: string[];
+  stashDropped: boolean;
+  error: string | null;
+};
+
 export type AppServerEvent = {
   workspace_id: string;
   message: Record<string, unknown>;
@@ -154,6 +191,7 @@ export type AppSettings = {
   orbitRunnerName: string | null;
   orbitAutoStartRunner: boolean;
   keepDaemonRunningAfterAppClose: boolean;
+  autoConnectWorkspaces: boolean;
   orbitUseAccess: boolean;
   orbitAccessClientId: string | null;
   orbitAccessClientSecretRef: string | null;
@@ -191,6 +229,8 @@ export type AppSettings = {
   systemNotificationsEnabled: boolean;
   preloadGitDiffs: boolean;
   gitDiffIgnoreWhitespaceChanges: boolean;
+  maxDiffBytes?: number;
+  maxWorkspaceFileBytes?: number;
   commitMessagePrompt: string;
   experimentalCollabEnabled: boolean;
   collaborationModesEnabled: boolean;
@@ -214,6 +254,15 @@ export type AppSettings = {
   workspaceGroups: WorkspaceGroup[];
   openAppTargets: OpenAppTarget[];
   selectedOpenAppId: string;
+  maxTurnsPerThread?: number | null;
+  fileListSkipDirs?: string[];
+  githubEnterpriseHosts?: string[];
+  defaultCopyAgentsMd?: boolean;
+  worktreeCopyFiles?: string[];
+  recentModels?: Record<string, string[]>;
+  modelInputPrices?: Record<string, number>;
+  recentWorkspaces?: { workspaceId: string; openedAt: number }[];
+  recentWorkspacesLimit?: number;
 };
 
 export type OrbitConnectTestResult = {
@@ -284,6 +333,17 @@ export type TailscaleStatus = {
   message: string;
 };
 
+export type TailscaleRemoteHostCheck = {
+  host: string;
+  reachable: boolean;
+  authOk: boolean;
+  resolvedAddr: string | null;
+  failure: "dns" | "refused" | "timeout" | "unreachable" | "not_daemon" | null;
+  connectMs: number | null;
+  pingMs: number | null;
+  message: string;
+};
+
 export type TailscaleDaemonCommandPreview = {
   command: string;
   daemonPath: string;
@@ -301,6 +361,30 @@ export type CodexDoctorResult = {
   nodeOk: boolean;
   nodeVersion: string | null;
   nodeDetails: string | null;
+  binaryFound: boolean;
+  configPath: string | null;
+  configValid: boolean;
+  configDetails: string | null;
+  checks: DoctorCheck[];
+};
+
+export type DoctorCheck = {
+  name: string;
+  status: "pass" | "warn" | "fail";
+  message: string;
+  remediation: string | null;
+};
+
+export type DaemonDoctorReport = {
+  ok: boolean;
+  checks: DoctorCheck[];
+};
+
+export type BuildInfo = {
+  version: string;
+  profile: "debug" | "release";
+  target: string | null;
+  gitHash: string | null;
 };
 
 export type CodexUpdateMethod = "brew_formula" | "brew_cask" | "npm" | "unknown";
@@ -375,6 +459,16 @@ export type GitFileDiff = {
   newImageData?: string | null;
   oldImageMime?: string | null;
   newImageMime?: string | null;
+  isUntracked?: boolean;
+  skippedReason?: "tooLarge" | "binary" | null;
+  truncated?: boolean;
+  fullDiffBytes?: number | null;
+};
+
+export type WorktreeBaseDiff = {
+  baseRef: string;
+  mergeBase: string;
+  files: GitFileDiff[];
 };
 
 export type GitCommitDiff = {
@@ -391,11 +485,112 @@ export type GitCommitDiff = {
   newImageMime?: string | null;
 };
 
+export type GitCommitStats = {
+  filesChanged: number;
+  insertions: number;
+  deletions: number;
+  binaryFiles: number;
+};
+
 export type GitLogEntry = {
   sha: string;
   summary: string;
   author: string;
   timestamp: number;
+  stats?: GitCommitStats;
+};
+
+export type GitPatchExport = {
+  patch: string;
+  commitCount: number;
+};
+
+export type GitRejectedHunk = {
+  path: string;
+  reason: string;
+  line: number | null;
+  hunk: string | null;
+};
+
+export type GitPatchApplyResult = {
+  applied: boolean;
+  checkOnly: boolean;
+  rejected: GitRejectedHunk[];
+  conflicts: string[];
+  error: string | null;
+};
+
+export type GitLastCommit = {
+  sha: string;
+  summary: string;
+  message: string;
+  authorName: string;
+  authorEmail: string;
+  timestamp: number;
+  files: GitFileStatus[];
+};
+
+export type GitOperationProgress = {
+  operation: "rebase" | "am" | "merge" | "cherry-pick" | "revert" | "bisect" | null;
+  interactive: boolean;
+  currentStep: number | null;
+  totalSteps: number | null;
+  currentCommit: string | null;
+  currentCommitSummary: string | null;
+  headName: string | null;
+  onto: string | null;
+};
+
+export type GitLargeFile = {
+  path: string;
+  sizeBytes: number;
+};
+
+export type LargeStagedFilesResponse = {
+  thresholdBytes: number;
+  files: GitLargeFile[];
+};
+
+export type GitLockFile = {
+  path: string;
+  ageSeconds: number;
+  stale: boolean;
+  removed: boolean;
+};
+
+export type GitRepairLocksResult = {
+  gitProcessRunning: boolean;
+  locks: GitLockFile[];
+};
+
+export type GitReflogEntry = {
+  sha: string;
+  selector: string;
+  action: string;
+  message: string;
+  timestamp: number;
+  relativeTime: string;
+};
+
+export type GitReflogRestoreResult = {
+  branch: string;
+  previousSha: string;
+  targetSha: string;
+  applied: boolean;
+  autostashed: boolean;
+  warning: string | null;
+};
+
+export type GitPushResult = {
+  remote: string;
+  branch: string;
+  createdRemoteBranch: boolean;
+  forced: boolean;
+  upstreamSet: boolean;
+  upToDate: boolean;
+  rejected: boolean;
+  rejectionReason: string | null;
+  summary: string;
 };
 
 export type GitLogResponse = {
@@ -408,6 +603,25 @@ export type GitLogResponse = {
   upstream: string | null;
 };
 
+export type GitIncomingFile = {
+  path: string;
+  status: string;
+  additions: number;
+  deletions: number;
+  diff: string | null;
+};
+
+export type GitIncomingChanges = {
+  upstream: string;
+  fetchError: string | null;
+  behind: number;
+  commits: GitLogEntry[];
+  files: GitIncomingFile[];
+  additions: number;
+  deletions: number;
+  diffTruncated: boolean;
+};
+
 export type GitHubIssue = {
   number: number;
   title: string;
@@ -415,6 +629,67 @@ export type GitHubIssue = {
   updatedAt: string;
 };
 
+export type GitRemote = {
+  name: string;
+  fetchUrl: string | null;
+  pushUrl: string | null;
+  tracking: boolean;
+};
+
+export type GitMergedBranch = {
+  name: string;
+  sha: string;
+  lastCommit: number;
+};
+
+export type GitMergedBranches = {
+  defaultBranch: string;
+  branches: GitMergedBranch[];
+};
+
+export type GitBranchPruneResult = {
+  name: string;
+  deleted: boolean;
+  error: string | null;
+};
+
+export type GitStatusEntry = {
+  kind: "changed" | "renamed" | "copied" | "unmerged" | "untracked" | "ignored";
+  path: string;
+  origPath: string | null;
+  indexStatus: string;
+  worktreeStatus: string;
+  similarity: number | null;
+  submodule: {
+    commitChanged: boolean;
+    trackedChanges: boolean;
+    untrackedChanges: boolean;
+  } | null;
+};
+
+export type GitStatusDetailed = {
+  branch: {
+    oid: string | null;
+    head: string | null;
+    upstream: string | null;
+    ahead: number | null;
+    behind: number | null;
+  };
+  entries: GitStatusEntry[];
+};
+
+export type GitHubRepoSlug = {
+  host: string;
+  owner: string;
+  repo: string;
+};
+
+export type GitHubRepoInfo = {
+  remotes: { name: string; url: string; github: GitHubRepoSlug | null }[];
+  primaryRemote: string | null;
+  primary: GitHubRepoSlug | null;
+};
+
 export type GitHubIssuesResponse = {
   total: number;
   issues: GitHubIssue[];
@@ -456,6 +731,30 @@ export type GitHubPullRequestComment = {
   author: GitHubUser | null;
 };
 
+export type GitHubPullRequestCheckout = {
+  branch: string;
+  existed: boolean;
+  updated: boolean;
+};
+
+export type GitHubPullRequestReviewComment = {
+  id: string;
+  body: string;
+  createdAt: string;
+  url: string;
+  author: GitHubUser | null;
+};
+
+export type GitHubPullRequestReviewThread = {
+  id: string;
+  path: string;
+  line: number | null;
+  originalLine: number | null;
+  isResolved: boolean;
+  isOutdated: boolean;
+  comments: GitHubPullRequestReviewComment[];
+};
+
 export type TokenUsageBreakdown = {
   totalTokens: number;
   inputTokens: number;
@@ -502,6 +801,13 @@ export type LocalUsageSnapshot = {
   topModels: LocalUsageModel[];
 };
 
+export type LocalUsageRefreshResult = {
+  snapshot: LocalUsageSnapshot;
+  filesScanned: number;
+  sessionsRoots: string[];
+  durationMs: number;
+};
+
 export type TurnPlanStepStatus = "pending" | "inProgress" | "completed";
 
 export type TurnPlanStep = {
@@ -565,6 +871,8 @@ export type CollaborationModeOption = {
   model: string;
   reasoningEffort: string | null;
   developerInstructions: string | null;
+  description?: string | null;
+  isDefault?: boolean;
   value: Record<string, unknown>;
 };
 
@@ -7,6 +7,7 @@ export const SUPPORTED_APP_SERVER_METHODS = [
   "codex/backgroundThread",
   "codex/connected",
   "codex/event/skills_update_available",
+  "codex/threadsRefreshed",
   "error",
   "item/agentMessage/delta",
   "item/commandExecution/outputDelta",